@@ -0,0 +1,286 @@
+//! aria2 process supervision
+//!
+//! Optionally runs aria2c as a managed child process with the RPC flags the
+//! crate expects, health-checks it over JSON-RPC and restarts it when it
+//! crashes. aria2 saves unfinished downloads to a session file which is
+//! reloaded on every (re)start, so downloads come back under their old GIDs.
+
+pub mod session;
+
+pub use session::{SessionEntry, parse_session, read_session};
+
+use crate::Result;
+use crate::error::DownloadError;
+use crate::backend::Aria2RpcClient;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, Instant};
+
+/// Settings for a managed aria2c process
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    /// aria2c executable
+    pub aria2c_path: PathBuf,
+    /// Port the RPC interface listens on (localhost only)
+    pub rpc_port: u16,
+    /// RPC secret token
+    pub secret: String,
+    /// Default download directory
+    pub download_dir: PathBuf,
+    /// Session file used to keep unfinished downloads across restarts
+    pub session_file: PathBuf,
+    /// How often the process is health-checked
+    pub health_check_interval: Duration,
+    /// How long to wait for the RPC interface after spawning
+    pub startup_timeout: Duration,
+    /// Restarts allowed before the supervisor gives up
+    pub max_restarts: u32,
+    /// Additional command line arguments
+    pub extra_args: Vec<String>,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            aria2c_path: PathBuf::from("aria2c"),
+            rpc_port: 6800,
+            secret: "burncloud".to_string(),
+            download_dir: PathBuf::from("./data"),
+            session_file: PathBuf::from("data/aria2.session"),
+            health_check_interval: Duration::from_secs(5),
+            startup_timeout: Duration::from_secs(10),
+            max_restarts: 5,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// Get the JSON-RPC endpoint of the managed process
+    pub fn rpc_url(&self) -> String {
+        format!("http://127.0.0.1:{}/jsonrpc", self.rpc_port)
+    }
+
+    /// Build the aria2c command line arguments
+    pub fn command_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "--enable-rpc=true".to_string(),
+            "--rpc-listen-all=false".to_string(),
+            format!("--rpc-listen-port={}", self.rpc_port),
+            format!("--rpc-secret={}", self.secret),
+            format!("--dir={}", self.download_dir.display()),
+            format!("--save-session={}", self.session_file.display()),
+            "--save-session-interval=30".to_string(),
+            "--force-save=false".to_string(),
+            "--continue=true".to_string(),
+        ];
+
+        // aria2c refuses to start when the input file is missing
+        if self.session_file.exists() {
+            args.push(format!("--input-file={}", self.session_file.display()));
+        }
+
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
+
+/// Runs, monitors and restarts an aria2c process
+pub struct Aria2Supervisor {
+    config: SupervisorConfig,
+    rpc: Arc<Aria2RpcClient>,
+    child: Arc<Mutex<Option<Child>>>,
+    restarts: Arc<AtomicU32>,
+    monitor_handle: Mutex<Option<JoinHandle<()>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl Aria2Supervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        let rpc = Arc::new(Aria2RpcClient::new(config.rpc_url(), Some(config.secret.clone())));
+
+        Self {
+            config,
+            rpc,
+            child: Arc::new(Mutex::new(None)),
+            restarts: Arc::new(AtomicU32::new(0)),
+            monitor_handle: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Get the supervisor settings
+    pub fn config(&self) -> &SupervisorConfig {
+        &self.config
+    }
+
+    /// Get the JSON-RPC endpoint of the managed process
+    pub fn rpc_url(&self) -> String {
+        self.config.rpc_url()
+    }
+
+    /// Get the number of times the process was restarted
+    pub fn restart_count(&self) -> u32 {
+        self.restarts.load(Ordering::SeqCst)
+    }
+
+    /// Check if aria2 answers RPC calls
+    pub async fn is_healthy(&self) -> bool {
+        is_healthy(&self.rpc).await
+    }
+
+    /// Make sure aria2 is running and start monitoring it
+    ///
+    /// An aria2 daemon already listening on the configured port is used as is;
+    /// otherwise a child process is spawned.
+    pub async fn start(&self) -> Result<()> {
+        if self.is_healthy().await {
+            log::info!("Using aria2 already running at {}", self.rpc_url());
+        } else {
+            let child = spawn_aria2(&self.config, &self.rpc).await?;
+            *self.child.lock().await = Some(child);
+        }
+
+        let mut handle_guard = self.monitor_handle.lock().await;
+        if handle_guard.is_none() {
+            *handle_guard = Some(self.start_monitor());
+        }
+
+        Ok(())
+    }
+
+    /// Read the downloads aria2 will restore from its session file
+    pub async fn session_entries(&self) -> Result<Vec<SessionEntry>> {
+        Ok(read_session(&self.config.session_file).await?)
+    }
+
+    /// Stop monitoring, save the session and stop the managed process
+    pub async fn shutdown(&self) -> Result<()> {
+        self.shutdown.notify_one();
+        if let Some(handle) = self.monitor_handle.lock().await.take() {
+            let _ = handle.await;
+        }
+
+        let Some(mut child) = self.child.lock().await.take() else {
+            return Ok(());
+        };
+
+        if let Err(e) = self.rpc.call("aria2.saveSession", vec![]).await {
+            log::warn!("Failed to save aria2 session: {}", e);
+        }
+        if let Err(e) = self.rpc.call("aria2.shutdown", vec![]).await {
+            log::warn!("Failed to request aria2 shutdown: {}", e);
+        }
+
+        match tokio::time::timeout(self.config.startup_timeout, child.wait()).await {
+            Ok(_) => {}
+            Err(_) => {
+                log::warn!("aria2 did not exit in time, killing it");
+                child.kill().await?;
+            }
+        }
+
+        log::info!("aria2 supervisor shutdown complete");
+        Ok(())
+    }
+
+    fn start_monitor(&self) -> JoinHandle<()> {
+        let config = self.config.clone();
+        let rpc = self.rpc.clone();
+        let child = self.child.clone();
+        let restarts = self.restarts.clone();
+        let shutdown = self.shutdown.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = interval(config.health_check_interval);
+            // The first tick fires immediately, right after startup
+            ticker.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        let mut child_guard = child.lock().await;
+
+                        let exited = match child_guard.as_mut() {
+                            Some(process) => !matches!(process.try_wait(), Ok(None)),
+                            None => false,
+                        };
+                        if !exited && is_healthy(&rpc).await {
+                            continue;
+                        }
+
+                        if restarts.load(Ordering::SeqCst) >= config.max_restarts {
+                            log::error!("aria2 is unhealthy and the restart limit ({}) was reached", config.max_restarts);
+                            continue;
+                        }
+
+                        log::warn!("aria2 is not responding, restarting it");
+                        if let Some(mut process) = child_guard.take() {
+                            let _ = process.kill().await;
+                        }
+
+                        match spawn_aria2(&config, &rpc).await {
+                            Ok(process) => {
+                                *child_guard = Some(process);
+                                let count = restarts.fetch_add(1, Ordering::SeqCst) + 1;
+                                log::info!("aria2 restarted ({} restarts so far)", count);
+                            }
+                            Err(e) => {
+                                restarts.fetch_add(1, Ordering::SeqCst);
+                                log::error!("Failed to restart aria2: {}", e);
+                            }
+                        }
+                    }
+                    _ = shutdown.notified() => {
+                        break;
+                    }
+                }
+            }
+        })
+    }
+}
+
+async fn is_healthy(rpc: &Aria2RpcClient) -> bool {
+    rpc.call("aria2.getVersion", vec![]).await.is_ok()
+}
+
+/// Spawn aria2c and wait until its RPC interface answers
+async fn spawn_aria2(config: &SupervisorConfig, rpc: &Aria2RpcClient) -> Result<Child> {
+    tokio::fs::create_dir_all(&config.download_dir).await?;
+    if let Some(parent) = config.session_file.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    log::info!("Starting {} on port {}", config.aria2c_path.display(), config.rpc_port);
+
+    let mut child = Command::new(&config.aria2c_path)
+        .args(config.command_args())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| DownloadError::DownloaderUnavailable(
+            format!("Failed to start {}: {}", config.aria2c_path.display(), e)
+        ))?;
+
+    let deadline = Instant::now() + config.startup_timeout;
+    while Instant::now() < deadline {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(DownloadError::DownloaderUnavailable(format!("aria2c exited during startup: {}", status)));
+        }
+        if is_healthy(rpc).await {
+            return Ok(child);
+        }
+        sleep(Duration::from_millis(200)).await;
+    }
+
+    let _ = child.kill().await;
+    Err(DownloadError::DownloaderUnavailable("aria2c RPC interface did not come up in time".to_string()))
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+//! aria2 session file parsing
+//!
+//! aria2 writes unfinished downloads to its session file as one line of
+//! tab-separated URIs followed by indented `key=value` option lines.
+
+use std::path::{Path, PathBuf};
+
+/// A download recorded in an aria2 session file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionEntry {
+    /// Source URIs of the download
+    pub uris: Vec<String>,
+    /// GID the download keeps when aria2 reloads the session
+    pub gid: Option<String>,
+    /// Download directory
+    pub dir: Option<PathBuf>,
+    /// Output file name relative to `dir`
+    pub out: Option<String>,
+    /// Whether the download was paused
+    pub paused: bool,
+}
+
+impl SessionEntry {
+    /// Get the full output path if both directory and file name are known
+    pub fn target_path(&self) -> Option<PathBuf> {
+        match (&self.dir, &self.out) {
+            (Some(dir), Some(out)) => Some(dir.join(out)),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the contents of an aria2 session file
+pub fn parse_session(content: &str) -> Vec<SessionEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<SessionEntry> = None;
+
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with(char::is_whitespace) {
+            let Some(entry) = current.as_mut() else {
+                continue;
+            };
+            let Some((key, value)) = line.trim().split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "gid" => entry.gid = Some(value.to_string()),
+                "dir" => entry.dir = Some(PathBuf::from(value)),
+                "out" => entry.out = Some(value.to_string()),
+                "pause" => entry.paused = value == "true",
+                _ => {}
+            }
+        } else {
+            entries.extend(current.take());
+            current = Some(SessionEntry {
+                uris: line.split('\t').map(str::to_string).collect(),
+                ..SessionEntry::default()
+            });
+        }
+    }
+
+    entries.extend(current);
+    entries
+}
+
+/// Read and parse an aria2 session file (empty if it doesn't exist yet)
+pub async fn read_session(path: &Path) -> std::io::Result<Vec<SessionEntry>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(parse_session(&content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,533 @@
+//! Aria2 download backend
+//!
+//! Wraps `Aria2DownloadManager` for the transfer lifecycle and an
+//! [`Aria2RpcClient`] for option changes it does not support. Downloads
+//! reattached after a restart are not known to `Aria2DownloadManager` and are
+//! driven directly over RPC by their GID, as are stopped downloads added
+//! again from a new source URL.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use crate::Result;
+use crate::error::DownloadError;
+use serde_json::{json, Map, Value};
+use tokio::sync::RwLock;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus, DownloadManager as DownloadManagerTrait};
+use burncloud_download_aria2::Aria2DownloadManager;
+
+use crate::traits::DownloadBackend;
+use crate::backend::Aria2RpcClient;
+use crate::models::{DownloadOptions, FileProgress, RpcTimeouts};
+
+/// Maximum number of waiting and stopped downloads inspected when resolving a GID
+const GID_LOOKUP_LIMIT: u32 = 1000;
+
+/// Download backend driving an aria2 daemon over JSON-RPC
+pub struct Aria2Backend {
+    manager: Aria2DownloadManager,
+    rpc: Aria2RpcClient,
+    /// GIDs resolved so far, by task
+    gids: RwLock<HashMap<TaskId, String>>,
+    /// Downloads taken over from a previous run
+    reattached: RwLock<HashMap<TaskId, DownloadTask>>,
+    /// Source URLs swapped in after `Aria2DownloadManager` added the download
+    sources: RwLock<HashMap<TaskId, String>>,
+}
+
+impl Aria2Backend {
+    /// Connect to the aria2 daemon at `rpc_url`
+    pub async fn new(rpc_url: String, secret: String) -> Result<Self> {
+        Self::with_timeouts(rpc_url, secret, RpcTimeouts::default()).await
+    }
+
+    /// Connect to the aria2 daemon at `rpc_url`, giving up on the connection
+    /// and on RPC calls after the given timeouts
+    pub async fn with_timeouts(rpc_url: String, secret: String, timeouts: RpcTimeouts) -> Result<Self> {
+        let rpc = Aria2RpcClient::with_timeouts(rpc_url.clone(), Some(secret.clone()), &timeouts);
+        let manager = tokio::time::timeout(timeouts.connect, Aria2DownloadManager::new(rpc_url, Some(secret)))
+            .await
+            .map_err(|_| DownloadError::BackendTimeout { operation: "connect".to_string(), timeout: timeouts.connect })?
+            .map_err(aria2_error)?;
+
+        Ok(Self {
+            manager,
+            rpc,
+            gids: RwLock::new(HashMap::new()),
+            reattached: RwLock::new(HashMap::new()),
+            sources: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Get the raw RPC client for calls not covered by the backend trait
+    pub fn rpc(&self) -> &Aria2RpcClient {
+        &self.rpc
+    }
+
+    /// Get the aria2 GID for a given task ID
+    ///
+    /// The GID is looked up once by matching the task's output path and URL
+    /// against the downloads aria2 knows, then cached.
+    pub async fn gid_for_task(&self, task_id: TaskId) -> Result<String> {
+        if let Some(gid) = self.gids.read().await.get(&task_id) {
+            return Ok(gid.clone());
+        }
+
+        let task = DownloadManagerTrait::get_task(&self.manager, task_id).await
+            .map_err(aria2_error)?;
+
+        let gid = self.rpc.tell_all(GID_LOOKUP_LIMIT).await?
+            .iter()
+            .find(|status| status_matches(status, &task.url, &task.target_path))
+            .and_then(|status| status.get("gid").and_then(Value::as_str).map(str::to_string))
+            .ok_or_else(|| DownloadError::Aria2Rpc(format!("No aria2 download found for task {}", task_id)))?;
+
+        self.gids.write().await.insert(task_id, gid.clone());
+        Ok(gid)
+    }
+
+    /// Get the GID of a reattached task
+    async fn reattached_gid(&self, task_id: TaskId) -> Option<String> {
+        if !self.reattached.read().await.contains_key(&task_id) {
+            return None;
+        }
+        self.gids.read().await.get(&task_id).cloned()
+    }
+
+    /// Get the current state of a reattached task from aria2
+    async fn reattached_task(&self, task_id: TaskId, gid: &str) -> Result<DownloadTask> {
+        let status = self.rpc.tell_status(gid).await?;
+        self.update_reattached(task_id, &status).await
+    }
+
+    /// Apply an aria2 status entry to a reattached task
+    async fn update_reattached(&self, task_id: TaskId, status: &Value) -> Result<DownloadTask> {
+        let mut reattached = self.reattached.write().await;
+        let task = reattached.get_mut(&task_id)
+            .ok_or(DownloadError::TaskNotFound(task_id))?;
+
+        let new_status = download_status(status);
+        if task.status != new_status {
+            task.update_status(new_status);
+        }
+        Ok(task.clone())
+    }
+
+    /// Find the status entry of a task among the entries of one `tell_all`
+    ///
+    /// Matches by GID when it is known, by output path and URL otherwise,
+    /// caching the GID found.
+    async fn find_status<'a>(&self, task: &DownloadTask, statuses: &'a [Value]) -> Option<&'a Value> {
+        let gid = self.gids.read().await.get(&task.id).cloned();
+        if let Some(gid) = gid {
+            return statuses.iter().find(|status| status.get("gid").and_then(Value::as_str) == Some(gid.as_str()));
+        }
+
+        let status = statuses.iter().find(|status| status_matches(status, &task.url, &task.target_path))?;
+        if let Some(gid) = status.get("gid").and_then(Value::as_str) {
+            self.gids.write().await.insert(task.id, gid.to_string());
+        }
+        Some(status)
+    }
+
+    /// Report the swapped-in source URL of a task added by `Aria2DownloadManager`
+    async fn with_source(&self, mut task: DownloadTask) -> DownloadTask {
+        if let Some(url) = self.sources.read().await.get(&task.id) {
+            task.url = url.clone();
+        }
+        task
+    }
+
+    /// Add a stopped download again from `new_url` and take it over under its task ID
+    ///
+    /// aria2 cannot change the URIs of a download it gave up on, so a new
+    /// download continuing the same file is added directly over RPC.
+    async fn readd_from(&self, task_id: TaskId, gid: &str, new_url: &str, options: &DownloadOptions) -> Result<()> {
+        let mut task = self.task(task_id).await?;
+        let mut aria2_options = options.to_aria2_options();
+        if let Some(dir) = task.target_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            aria2_options.insert("dir".to_string(), json!(dir.to_string_lossy()));
+        }
+        if let Some(name) = task.target_path.file_name() {
+            aria2_options.insert("out".to_string(), json!(name.to_string_lossy()));
+        }
+        aria2_options.insert("continue".to_string(), json!("true"));
+
+        let new_gid = self.rpc.call("aria2.addUri", vec![json!([new_url]), Value::Object(aria2_options)]).await?
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| DownloadError::Aria2Rpc("aria2.addUri returned no GID".to_string()))?;
+
+        if self.reattached.read().await.contains_key(&task_id) {
+            if let Err(e) = self.rpc.call("aria2.removeDownloadResult", vec![json!(gid)]).await {
+                log::debug!("Failed to drop the result of GID {}: {}", gid, e);
+            }
+        } else if let Err(e) = DownloadManagerTrait::cancel_download(&self.manager, task_id).await {
+            log::debug!("Failed to drop stopped task {} from the aria2 manager: {:#}", task_id, e);
+        }
+        self.sources.write().await.remove(&task_id);
+
+        task.url = new_url.to_string();
+        if !self.reattach(&task, &new_gid).await? {
+            return Err(DownloadError::Aria2Rpc(format!("aria2 lost the new download of task {}", task_id)));
+        }
+        Ok(())
+    }
+
+    /// Forget a task's GID and reattachment state
+    async fn forget(&self, task_id: TaskId) {
+        self.gids.write().await.remove(&task_id);
+        self.reattached.write().await.remove(&task_id);
+        self.sources.write().await.remove(&task_id);
+    }
+}
+
+/// Convert an error reported by the aria2 manager
+fn aria2_error(error: anyhow::Error) -> DownloadError {
+    match error.downcast::<DownloadError>() {
+        Ok(error) => error,
+        Err(error) => DownloadError::Aria2Rpc(format!("{:#}", error)),
+    }
+}
+
+/// Build an aria2 option map for a download speed limit (0 = unlimited)
+fn speed_limit_options(key: &str, bytes_per_sec: u64) -> Map<String, serde_json::Value> {
+    let mut options = Map::new();
+    options.insert(key.to_string(), json!(bytes_per_sec.to_string()));
+    options
+}
+
+/// Check if an aria2 status entry is the download of `url` into `target_path`
+fn status_matches(status: &Value, url: &str, target_path: &Path) -> bool {
+    let Some(file) = status.get("files").and_then(|files| files.get(0)) else {
+        return false;
+    };
+
+    let path = file.get("path").and_then(Value::as_str).map(Path::new);
+    if path == Some(target_path) {
+        return true;
+    }
+
+    // aria2 may report an absolute path for a relative target
+    let same_name = path.and_then(Path::file_name) == target_path.file_name();
+    let same_uri = file.get("uris")
+        .and_then(Value::as_array)
+        .is_some_and(|uris| uris.iter().any(|uri| uri.get("uri").and_then(Value::as_str) == Some(url)));
+    same_name && same_uri
+}
+
+/// Read a numeric aria2 status field (aria2 encodes numbers as strings)
+fn status_number(status: &Value, key: &str) -> u64 {
+    status.get(key)
+        .and_then(Value::as_str)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Map the `files` of an aria2 status entry to per-file progress
+fn file_progress(status: &Value) -> Vec<FileProgress> {
+    status.get("files")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .map(|(position, file)| {
+            let total = status_number(file, "length");
+            FileProgress {
+                index: status_number(file, "index").try_into().ok()
+                    .filter(|index| *index > 0)
+                    .unwrap_or(position as u32 + 1),
+                path: PathBuf::from(file.get("path").and_then(Value::as_str).unwrap_or_default()),
+                downloaded: status_number(file, "completedLength"),
+                total: (total > 0).then_some(total),
+                selected: file.get("selected").and_then(Value::as_str) != Some("false"),
+            }
+        })
+        .collect()
+}
+
+/// Map an aria2 status entry to a download status
+fn download_status(status: &Value) -> DownloadStatus {
+    match status.get("status").and_then(Value::as_str) {
+        Some("active") => DownloadStatus::Downloading,
+        Some("waiting") => DownloadStatus::Waiting,
+        Some("paused") => DownloadStatus::Paused,
+        Some("complete") => DownloadStatus::Completed,
+        Some("removed") => DownloadStatus::Failed("Removed from aria2".to_string()),
+        _ => {
+            let message = status.get("errorMessage")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown aria2 error");
+            DownloadStatus::Failed(message.to_string())
+        }
+    }
+}
+
+/// Map an aria2 status entry to download progress
+fn download_progress(status: &Value) -> DownloadProgress {
+    let downloaded_bytes = status_number(status, "completedLength");
+    let total = status_number(status, "totalLength");
+    let speed_bps = status_number(status, "downloadSpeed");
+    let total_bytes = (total > 0).then_some(total);
+
+    DownloadProgress {
+        downloaded_bytes,
+        total_bytes,
+        speed_bps,
+        eta_seconds: total_bytes
+            .filter(|_| speed_bps > 0)
+            .map(|total| total.saturating_sub(downloaded_bytes) / speed_bps),
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for Aria2Backend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        DownloadManagerTrait::add_download(&self.manager, url, target_path).await
+            .map_err(aria2_error)
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let task_id = DownloadManagerTrait::add_download(&self.manager, url, target_path).await
+            .map_err(aria2_error)?;
+
+        let aria2_options = options.to_aria2_options();
+        if !aria2_options.is_empty() {
+            let gid = self.gid_for_task(task_id).await?;
+            self.rpc.change_option(&gid, aria2_options).await?;
+        }
+
+        Ok(task_id)
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let Some((primary, mirrors)) = urls.split_first() else {
+            return Err(DownloadError::InvalidUrl("At least one source URL is required".to_string()));
+        };
+
+        let task_id = self.add_with_options(primary.clone(), target_path, options).await?;
+
+        if !mirrors.is_empty() {
+            let gid = self.gid_for_task(task_id).await?;
+            self.rpc.change_uri(&gid, 1, &[], mirrors).await?;
+        }
+
+        Ok(task_id)
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        if let Some(gid) = self.reattached_gid(task_id).await {
+            return self.rpc.pause(&gid).await;
+        }
+
+        DownloadManagerTrait::pause_download(&self.manager, task_id).await
+            .map_err(aria2_error)
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        if let Some(gid) = self.reattached_gid(task_id).await {
+            return self.rpc.unpause(&gid).await;
+        }
+
+        DownloadManagerTrait::resume_download(&self.manager, task_id).await
+            .map_err(aria2_error)
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        if let Some(gid) = self.reattached_gid(task_id).await {
+            self.rpc.remove(&gid).await?;
+            self.forget(task_id).await;
+            return Ok(());
+        }
+
+        DownloadManagerTrait::cancel_download(&self.manager, task_id).await
+            .map_err(aria2_error)?;
+        self.forget(task_id).await;
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        if let Some(gid) = self.reattached_gid(task_id).await {
+            return Ok(download_progress(&self.rpc.tell_status(&gid).await?));
+        }
+
+        DownloadManagerTrait::get_progress(&self.manager, task_id).await
+            .map_err(aria2_error)
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        if let Some(gid) = self.reattached_gid(task_id).await {
+            return self.reattached_task(task_id, &gid).await;
+        }
+
+        let task = DownloadManagerTrait::get_task(&self.manager, task_id).await
+            .map_err(aria2_error)?;
+        Ok(self.with_source(task).await)
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        let mut tasks = Vec::new();
+        for task in DownloadManagerTrait::list_tasks(&self.manager).await.map_err(aria2_error)? {
+            tasks.push(self.with_source(task).await);
+        }
+
+        let reattached_ids: Vec<TaskId> = self.reattached.read().await.keys().copied().collect();
+        for task_id in reattached_ids {
+            if let Some(gid) = self.reattached_gid(task_id).await {
+                tasks.push(self.reattached_task(task_id, &gid).await?);
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        // One tellActive, tellWaiting and tellStopped call instead of one call per task
+        let statuses = self.rpc.tell_all(GID_LOOKUP_LIMIT).await?;
+        let mut snapshot = Vec::new();
+
+        let tasks = DownloadManagerTrait::list_tasks(&self.manager).await
+            .map_err(aria2_error)?;
+        for task in tasks {
+            let progress = match self.find_status(&task, &statuses).await {
+                Some(status) => download_progress(status),
+                None => DownloadManagerTrait::get_progress(&self.manager, task.id).await
+                    .unwrap_or_else(|_| DownloadProgress::new()),
+            };
+            snapshot.push((self.with_source(task).await, progress));
+        }
+
+        let reattached_ids: Vec<TaskId> = self.reattached.read().await.keys().copied().collect();
+        for task_id in reattached_ids {
+            let Some(gid) = self.reattached_gid(task_id).await else {
+                continue;
+            };
+            let status = match statuses.iter().find(|status| status.get("gid").and_then(Value::as_str) == Some(gid.as_str())) {
+                Some(status) => status.clone(),
+                None => self.rpc.tell_status(&gid).await?,
+            };
+            let task = self.update_reattached(task_id, &status).await?;
+            snapshot.push((task, download_progress(&status)));
+        }
+
+        Ok(snapshot)
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        let mut count = DownloadManagerTrait::active_download_count(&self.manager).await
+            .map_err(aria2_error)?;
+
+        let reattached_ids: Vec<TaskId> = self.reattached.read().await.keys().copied().collect();
+        for task_id in reattached_ids {
+            if let Some(gid) = self.reattached_gid(task_id).await {
+                if self.reattached_task(task_id, &gid).await?.status.is_active() {
+                    count += 1;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.rpc.change_global_option(
+            speed_limit_options("max-overall-download-limit", bytes_per_sec)
+        ).await
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        let gid = self.gid_for_task(task_id).await?;
+        self.rpc.change_option(
+            &gid,
+            speed_limit_options("max-download-limit", bytes_per_sec)
+        ).await
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.gid_for_task(task_id).await.map(Some)
+    }
+
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool> {
+        let status = match self.rpc.tell_status(engine_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                log::debug!("aria2 no longer knows GID {}: {}", engine_id, e);
+                return Ok(false);
+            }
+        };
+
+        if status.get("status").and_then(Value::as_str) == Some("removed")
+            || !status_matches(&status, &task.url, &task.target_path)
+        {
+            return Ok(false);
+        }
+
+        let mut reattached_task = task.clone();
+        reattached_task.update_status(download_status(&status));
+
+        self.gids.write().await.insert(task.id, engine_id.to_string());
+        self.reattached.write().await.insert(task.id, reattached_task);
+        Ok(true)
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        let gid = self.gid_for_task(task_id).await?;
+        let status = self.rpc.tell_status(&gid).await?;
+
+        match status.get("status").and_then(Value::as_str) {
+            Some("active" | "waiting" | "paused") => {
+                // Drop every old URI so aria2 stops retrying them
+                let old_uris: Vec<String> = status.get("files")
+                    .and_then(|files| files.get(0))
+                    .and_then(|file| file.get("uris"))
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|uri| uri.get("uri").and_then(Value::as_str).map(str::to_string))
+                    .collect();
+                self.rpc.change_uri(&gid, 1, &old_uris, &[new_url.to_string()]).await?;
+
+                match self.reattached.write().await.get_mut(&task_id) {
+                    Some(task) => task.url = new_url.to_string(),
+                    None => {
+                        self.sources.write().await.insert(task_id, new_url.to_string());
+                    }
+                }
+            }
+            _ => self.readd_from(task_id, &gid, new_url, options).await?,
+        }
+
+        Ok(true)
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        let gid = self.gid_for_task(task_id).await?;
+        let status = self.rpc.tell_status(&gid).await?;
+        // aria2 reports 0 for downloads that did not fail
+        Ok(status.get("errorCode")
+            .and_then(Value::as_str)
+            .and_then(|code| code.parse().ok())
+            .filter(|code| *code != 0))
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        let gid = self.gid_for_task(task_id).await?;
+        let status = self.rpc.tell_status(&gid).await?;
+        Ok(file_progress(&status))
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        let gid = self.gid_for_task(task_id).await?;
+        let selection: Vec<String> = indices.iter().map(u32::to_string).collect();
+
+        let mut options = Map::new();
+        options.insert("select-file".to_string(), json!(selection.join(",")));
+        self.rpc.change_option(&gid, options).await?;
+        Ok(true)
+    }
+
+    // The checksum is passed on as aria2's `checksum` option
+    async fn verifies_checksum(&self, _task_id: TaskId) -> bool {
+        true
+    }
+}
@@ -0,0 +1,170 @@
+//! aria2 WebSocket notifications
+//!
+//! Over the WebSocket form of its RPC interface (`ws://host:port/jsonrpc`)
+//! aria2 pushes a notification whenever a download starts, pauses, stops,
+//! completes or fails. [`Aria2Notifications`] forwards them as they arrive
+//! and reconnects when the connection drops; while it is disconnected callers
+//! have to fall back to polling.
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Delay before the first reconnection attempt, doubled on each failure
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// Notifications buffered while the receiver is busy
+const NOTIFICATION_BUFFER: usize = 256;
+
+/// Kind of an aria2 notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aria2Event {
+    Start,
+    Pause,
+    Stop,
+    Complete,
+    Error,
+    /// The torrent finished downloading, seeding may continue
+    BtComplete,
+}
+
+impl Aria2Event {
+    /// Map an aria2 notification method such as `aria2.onDownloadComplete`
+    pub fn from_method(method: &str) -> Option<Self> {
+        match method {
+            "aria2.onDownloadStart" => Some(Self::Start),
+            "aria2.onDownloadPause" => Some(Self::Pause),
+            "aria2.onDownloadStop" => Some(Self::Stop),
+            "aria2.onDownloadComplete" => Some(Self::Complete),
+            "aria2.onDownloadError" => Some(Self::Error),
+            "aria2.onBtDownloadComplete" => Some(Self::BtComplete),
+            _ => None,
+        }
+    }
+}
+
+/// A notification about one aria2 download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aria2Notification {
+    pub gid: String,
+    pub event: Aria2Event,
+}
+
+impl Aria2Notification {
+    /// Parse a WebSocket message, `None` for RPC responses and unknown methods
+    pub fn parse(message: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(message).ok()?;
+        let event = Aria2Event::from_method(value.get("method")?.as_str()?)?;
+        let gid = value.get("params")?.get(0)?.get("gid")?.as_str()?;
+
+        Some(Self {
+            gid: gid.to_string(),
+            event,
+        })
+    }
+}
+
+/// Get the WebSocket URL of an aria2 JSON-RPC endpoint
+///
+/// `http` becomes `ws` and `https` becomes `wss`; other schemes have no
+/// WebSocket interface.
+pub fn websocket_url(rpc_url: &str) -> Option<String> {
+    let mut url = url::Url::parse(rpc_url).ok()?;
+    let scheme = match url.scheme() {
+        "http" | "ws" => "ws",
+        "https" | "wss" => "wss",
+        _ => return None,
+    };
+    url.set_scheme(scheme).ok()?;
+    Some(url.to_string())
+}
+
+/// Background listener for aria2 notifications
+///
+/// Stops when dropped, when [`stop`](Self::stop) is called or when the
+/// receiver is dropped.
+pub struct Aria2Notifications {
+    connected: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl Aria2Notifications {
+    /// Listen on the WebSocket endpoint at `url`
+    ///
+    /// Connection failures are retried with exponential backoff, so the
+    /// listener can be started before aria2 accepts connections.
+    pub fn listen(url: impl Into<String>) -> (Self, mpsc::Receiver<Aria2Notification>) {
+        let url = url.into();
+        let connected = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = mpsc::channel(NOTIFICATION_BUFFER);
+        let handle = tokio::spawn(run(url, connected.clone(), sender));
+
+        (Self { connected, handle }, receiver)
+    }
+
+    /// Check if notifications are currently being received
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Close the connection and stop reconnecting
+    pub fn stop(&self) {
+        self.handle.abort();
+        self.connected.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for Aria2Notifications {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn run(url: String, connected: Arc<AtomicBool>, sender: mpsc::Sender<Aria2Notification>) {
+    let mut delay = RECONNECT_DELAY;
+
+    loop {
+        match tokio_tungstenite::connect_async(url.as_str()).await {
+            Ok((mut stream, _)) => {
+                log::info!("Receiving aria2 notifications from {}", url);
+                connected.store(true, Ordering::SeqCst);
+                delay = RECONNECT_DELAY;
+
+                while let Some(message) = stream.next().await {
+                    match message {
+                        Ok(Message::Text(text)) => {
+                            let Some(notification) = Aria2Notification::parse(&text) else {
+                                continue;
+                            };
+                            if sender.send(notification).await.is_err() {
+                                connected.store(false, Ordering::SeqCst);
+                                return;
+                            }
+                        }
+                        Ok(Message::Close(_)) => break,
+                        Ok(_) => {}
+                        Err(e) => {
+                            log::warn!("aria2 notification connection failed: {}", e);
+                            break;
+                        }
+                    }
+                }
+
+                connected.store(false, Ordering::SeqCst);
+                log::warn!("Lost aria2 notifications, polling until reconnected");
+            }
+            Err(e) => log::debug!("aria2 notifications unavailable at {}: {}", url, e),
+        }
+
+        if sender.is_closed() {
+            return;
+        }
+        sleep(delay).await;
+        delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,229 @@
+//! Minimal aria2 JSON-RPC client
+//!
+//! Covers the aria2 calls that `Aria2DownloadManager` does not expose,
+//! such as changing global or per-download options.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::Result;
+use crate::error::DownloadError;
+use crate::models::RpcTimeouts;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// Thin client for the aria2 JSON-RPC interface
+pub struct Aria2RpcClient {
+    rpc_url: String,
+    secret: Option<String>,
+    http: reqwest::Client,
+    /// Longest wait for a call, `None` without a timeout
+    request_timeout: Option<Duration>,
+    next_id: AtomicU64,
+}
+
+impl Aria2RpcClient {
+    /// Create a client for the given RPC endpoint and optional secret token
+    pub fn new(rpc_url: impl Into<String>, secret: Option<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            secret,
+            http: reqwest::Client::new(),
+            request_timeout: None,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Create a client whose calls fail with `DownloadError::BackendTimeout` when they take too long
+    pub fn with_timeouts(rpc_url: impl Into<String>, secret: Option<String>, timeouts: &RpcTimeouts) -> Self {
+        let http = reqwest::Client::builder()
+            .connect_timeout(timeouts.connect)
+            .timeout(timeouts.request)
+            .build()
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to build aria2 RPC client with timeouts: {}", e);
+                reqwest::Client::new()
+            });
+
+        Self {
+            rpc_url: rpc_url.into(),
+            secret,
+            http,
+            request_timeout: Some(timeouts.request),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Get the RPC endpoint URL
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    /// Call an aria2 RPC method and return its `result` value
+    ///
+    /// The secret token is prepended to `params` automatically.
+    pub async fn call(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        let mut all_params = Vec::with_capacity(params.len() + 1);
+        if let Some(secret) = &self.secret {
+            all_params.push(Value::String(format!("token:{}", secret)));
+        }
+        all_params.extend(params);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed).to_string(),
+            "method": method,
+            "params": all_params,
+        });
+
+        let response: Value = self.http
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| self.request_error(method, "send aria2 RPC request", e))?
+            .json()
+            .await
+            .map_err(|e| self.request_error(method, "parse aria2 RPC response", e))?;
+
+        if let Some(error) = response.get("error") {
+            let message = error.get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(DownloadError::Aria2Rpc(format!("{} failed: {}", method, message)));
+        }
+
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    /// Convert a failed request, telling timeouts apart
+    fn request_error(&self, method: &str, action: &str, error: reqwest::Error) -> DownloadError {
+        match self.request_timeout {
+            Some(timeout) if error.is_timeout() => DownloadError::BackendTimeout {
+                operation: method.to_string(),
+                timeout,
+            },
+            _ => DownloadError::Aria2Rpc(format!("Failed to {} {}: {}", action, method, error)),
+        }
+    }
+
+    /// Change global options (`aria2.changeGlobalOption`)
+    pub async fn change_global_option(&self, options: Map<String, Value>) -> Result<()> {
+        self.call("aria2.changeGlobalOption", vec![Value::Object(options)]).await?;
+        Ok(())
+    }
+
+    /// Get the version of the aria2 daemon (`aria2.getVersion`)
+    pub async fn get_version(&self) -> Result<String> {
+        let version = self.call("aria2.getVersion", Vec::new()).await?;
+        version.get("version")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| DownloadError::Aria2Rpc("aria2.getVersion returned no version".to_string()))
+    }
+
+    /// Get overall transfer speeds and download counts (`aria2.getGlobalStat`)
+    pub async fn get_global_stat(&self) -> Result<Aria2GlobalStats> {
+        let stats = self.call("aria2.getGlobalStat", Vec::new()).await?;
+        Ok(Aria2GlobalStats::from_value(&stats))
+    }
+
+    /// Add and remove source URIs of a download (`aria2.changeUri`)
+    ///
+    /// `file_index` is 1-based, as in aria2.
+    pub async fn change_uri(&self, gid: &str, file_index: u32, del_uris: &[String], add_uris: &[String]) -> Result<()> {
+        self.call("aria2.changeUri", vec![json!(gid), json!(file_index), json!(del_uris), json!(add_uris)]).await?;
+        Ok(())
+    }
+
+    /// Change options of a single download (`aria2.changeOption`)
+    pub async fn change_option(&self, gid: &str, options: Map<String, Value>) -> Result<()> {
+        self.call("aria2.changeOption", vec![json!(gid), Value::Object(options)]).await?;
+        Ok(())
+    }
+
+    /// Get the status of a download (`aria2.tellStatus`)
+    pub async fn tell_status(&self, gid: &str) -> Result<Value> {
+        self.call("aria2.tellStatus", vec![json!(gid), json!(STATUS_KEYS)]).await
+    }
+
+    /// List active, waiting and stopped downloads
+    ///
+    /// Waiting and stopped downloads are limited to the first `limit` entries each.
+    pub async fn tell_all(&self, limit: u32) -> Result<Vec<Value>> {
+        let mut downloads = Vec::new();
+        for (method, params) in [
+            ("aria2.tellActive", vec![json!(STATUS_KEYS)]),
+            ("aria2.tellWaiting", vec![json!(0), json!(limit), json!(STATUS_KEYS)]),
+            ("aria2.tellStopped", vec![json!(0), json!(limit), json!(STATUS_KEYS)]),
+        ] {
+            if let Value::Array(entries) = self.call(method, params).await? {
+                downloads.extend(entries);
+            }
+        }
+        Ok(downloads)
+    }
+
+    /// Pause a download (`aria2.pause`)
+    pub async fn pause(&self, gid: &str) -> Result<()> {
+        self.call("aria2.pause", vec![json!(gid)]).await?;
+        Ok(())
+    }
+
+    /// Resume a paused download (`aria2.unpause`)
+    pub async fn unpause(&self, gid: &str) -> Result<()> {
+        self.call("aria2.unpause", vec![json!(gid)]).await?;
+        Ok(())
+    }
+
+    /// Stop a download, or drop the result of a stopped one (`aria2.remove`, `aria2.removeDownloadResult`)
+    pub async fn remove(&self, gid: &str) -> Result<()> {
+        if self.call("aria2.remove", vec![json!(gid)]).await.is_ok() {
+            return Ok(());
+        }
+
+        // Stopped downloads can't be removed, only their result can
+        self.call("aria2.removeDownloadResult", vec![json!(gid)]).await?;
+        Ok(())
+    }
+}
+
+/// Status fields requested from aria2 when inspecting downloads
+const STATUS_KEYS: [&str; 9] = [
+    "gid", "status", "totalLength", "completedLength",
+    "downloadSpeed", "errorCode", "errorMessage", "files", "dir",
+];
+
+/// Overall aria2 statistics, as reported by `aria2.getGlobalStat`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Aria2GlobalStats {
+    /// Combined download speed in bytes per second
+    pub download_speed: u64,
+    /// Combined upload speed in bytes per second
+    pub upload_speed: u64,
+    pub num_active: u64,
+    pub num_waiting: u64,
+    /// Stopped downloads kept in memory
+    pub num_stopped: u64,
+    /// All stopped downloads of the session, including those dropped from memory
+    pub num_stopped_total: u64,
+}
+
+impl Aria2GlobalStats {
+    /// Parse a `getGlobalStat` result, whose numbers aria2 sends as strings
+    pub fn from_value(stats: &Value) -> Self {
+        let number = |key: &str| {
+            stats.get(key)
+                .and_then(Value::as_str)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(0)
+        };
+
+        Self {
+            download_speed: number("downloadSpeed"),
+            upload_speed: number("uploadSpeed"),
+            num_active: number("numActive"),
+            num_waiting: number("numWaiting"),
+            num_stopped: number("numStopped"),
+            num_stopped_total: number("numStoppedTotal"),
+        }
+    }
+}
\ No newline at end of file
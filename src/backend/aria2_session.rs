@@ -0,0 +1,236 @@
+//! aria2 session files
+//!
+//! Reads and writes the format of aria2's `--save-session` and `--input-file`:
+//! each download is a line of tab-separated URIs followed by its options, one
+//! `name=value` per line indented with whitespace. Lines starting with `#` are
+//! comments.
+
+use crate::error::DownloadError;
+use crate::models::{Checksum, ChecksumAlgorithm, DownloadOptions, ImportReport};
+use crate::types::TaskId;
+use crate::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// One download of a session file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionEntry {
+    pub uris: Vec<String>,
+    /// Options in file order, some such as `header` may repeat
+    pub options: Vec<(String, String)>,
+}
+
+impl SessionEntry {
+    pub fn new(uris: Vec<String>) -> Self {
+        Self {
+            uris,
+            options: Vec::new(),
+        }
+    }
+
+    /// Build the entry of a download with the given options
+    ///
+    /// Only options with an aria2 equivalent are written, see
+    /// [`DownloadOptions::to_aria2_options`].
+    pub fn from_download(
+        uris: Vec<String>,
+        target_path: &Path,
+        gid: Option<&str>,
+        paused: bool,
+        options: &DownloadOptions,
+    ) -> Self {
+        let mut entry = Self::new(uris);
+
+        if let Some(gid) = gid {
+            entry.push_option("gid", gid);
+        }
+        if let Some(dir) = target_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            entry.push_option("dir", dir.to_string_lossy());
+        }
+        if let Some(name) = target_path.file_name() {
+            entry.push_option("out", name.to_string_lossy());
+        }
+        if paused {
+            entry.push_option("pause", "true");
+        }
+
+        for (name, value) in options.to_aria2_options() {
+            match value {
+                Value::Array(values) => {
+                    for value in values.iter().filter_map(Value::as_str) {
+                        entry.push_option(&name, value);
+                    }
+                }
+                Value::String(value) => entry.push_option(&name, value),
+                other => entry.push_option(&name, other.to_string()),
+            }
+        }
+
+        entry
+    }
+
+    /// Append an option
+    pub fn push_option(&mut self, name: &str, value: impl Into<String>) {
+        self.options.push((name.to_string(), value.into()));
+    }
+
+    /// Get the last value of an option
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.iter().rev()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the GID aria2 ran the download under
+    pub fn gid(&self) -> Option<&str> {
+        self.option("gid")
+    }
+
+    /// Check if the download was paused
+    pub fn is_paused(&self) -> bool {
+        self.option("pause") == Some("true")
+    }
+
+    /// Get the target path from `dir` and `out`
+    ///
+    /// Without `out` the file is named after the last segment of the first
+    /// URI, like aria2 does. The path is relative when `dir` is missing.
+    pub fn target_path(&self) -> Option<PathBuf> {
+        let name = match self.option("out") {
+            Some(out) => out.to_string(),
+            None => self.uris.first()
+                .and_then(|uri| url::Url::parse(uri).ok())
+                .and_then(|url| url.path_segments()?.last().map(str::to_string))
+                .filter(|name| !name.is_empty())?,
+        };
+
+        Some(match self.option("dir") {
+            Some(dir) => Path::new(dir).join(name),
+            None => PathBuf::from(name),
+        })
+    }
+
+    /// Convert the options of the entry back into download options
+    ///
+    /// Options without a [`DownloadOptions`] equivalent are ignored.
+    pub fn download_options(&self) -> DownloadOptions {
+        let mut options = DownloadOptions::default();
+
+        for (name, value) in &self.options {
+            match name.as_str() {
+                "header" => {
+                    if let Some((header, content)) = value.split_once(':') {
+                        let (header, content) = (header.trim(), content.trim());
+                        if header.eq_ignore_ascii_case("cookie") {
+                            options.cookies = Some(content.to_string());
+                        } else {
+                            options.headers.push((header.to_string(), content.to_string()));
+                        }
+                    }
+                }
+                "user-agent" => options.user_agent = Some(value.clone()),
+                "all-proxy" => options.proxy = Some(value.clone()),
+                "max-download-limit" => options.speed_limit = parse_size(value).filter(|limit| *limit > 0),
+                "checksum" => options.checksum = parse_checksum(value),
+                "split" => options.segments = value.parse().ok(),
+                "max-connection-per-server" => options.max_connections_per_server = value.parse().ok(),
+                "min-split-size" => options.min_split_size = parse_size(value),
+                "file-allocation" => options.file_allocation = value.parse().ok(),
+                "continue" => options.continue_partial = value == "true",
+                _ => {}
+            }
+        }
+
+        options
+    }
+}
+
+/// Contents of an aria2 session file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Aria2Session {
+    pub entries: Vec<SessionEntry>,
+}
+
+impl Aria2Session {
+    pub fn new(entries: Vec<SessionEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Parse a session file
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut entries: Vec<SessionEntry> = Vec::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            // Option lines are indented, everything else starts a new download
+            if line.starts_with(char::is_whitespace) {
+                let entry = entries.last_mut().ok_or_else(|| invalid_session(index, "option before any URI"))?;
+                let (name, value) = trimmed.split_once('=')
+                    .ok_or_else(|| invalid_session(index, "expected name=value"))?;
+                entry.push_option(name.trim(), value.trim());
+            } else {
+                let uris = trimmed.split('\t')
+                    .map(str::trim)
+                    .filter(|uri| !uri.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                entries.push(SessionEntry::new(uris));
+            }
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl fmt::Display for Aria2Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{}", entry.uris.join("\t"))?;
+            for (name, value) in &entry.options {
+                writeln!(f, " {}={}", name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of importing a session file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionImport {
+    pub report: ImportReport,
+    /// New task of each imported download, keyed by the GID it had in the session
+    pub gids: HashMap<String, TaskId>,
+}
+
+fn invalid_session(index: usize, reason: &str) -> DownloadError {
+    DownloadError::General(format!("Invalid aria2 session on line {}: {}", index + 1, reason))
+}
+
+/// Parse an aria2 size such as `512K` or `2M`
+fn parse_size(value: &str) -> Option<u64> {
+    let (digits, multiplier) = match value.chars().last()? {
+        'K' | 'k' => (&value[..value.len() - 1], 1024),
+        'M' | 'm' => (&value[..value.len() - 1], 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Parse an aria2 checksum of the form `type=digest`
+fn parse_checksum(value: &str) -> Option<Checksum> {
+    let (algorithm, digest) = value.split_once('=')?;
+    let algorithm = match algorithm {
+        "md5" => ChecksumAlgorithm::Md5,
+        "sha-1" => ChecksumAlgorithm::Sha1,
+        "sha-256" => ChecksumAlgorithm::Sha256,
+        "sha-512" => ChecksumAlgorithm::Sha512,
+        _ => return None,
+    };
+    Some(Checksum::new(algorithm, digest))
+}
\ No newline at end of file
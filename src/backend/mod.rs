@@ -0,0 +1,25 @@
+//! Download backend implementations
+//!
+//! Concrete engines implementing [`crate::traits::DownloadBackend`].
+
+pub mod aria2;
+pub mod aria2_notifications;
+pub mod aria2_rpc;
+pub mod aria2_session;
+pub mod part_file;
+pub mod scanning;
+pub mod timeout;
+pub mod router;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+
+pub use aria2::Aria2Backend;
+pub use aria2_notifications::{Aria2Notifications, Aria2Notification, Aria2Event};
+pub use aria2_rpc::{Aria2RpcClient, Aria2GlobalStats};
+pub use aria2_session::{Aria2Session, SessionEntry, SessionImport};
+pub use part_file::PartFileBackend;
+pub use scanning::ScanningBackend;
+pub use timeout::TimeoutBackend;
+pub use router::SchemeRouter;
+#[cfg(feature = "sftp")]
+pub use sftp::SftpBackend;
\ No newline at end of file
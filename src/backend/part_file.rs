@@ -0,0 +1,189 @@
+//! Temporary download files
+//!
+//! [`PartFileBackend`] has the engine write each download to
+//! `<target><suffix>` and moves the file to its target path once the engine
+//! reports it complete, which for downloads with a checksum is after aria2
+//! verified it. The move is a rename within the same directory, so consumers
+//! never see a half-written file at the target path.
+//!
+//! Tasks keep their real target path everywhere outside the engine.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus};
+use crate::models::{DownloadOptions, FileProgress};
+use crate::traits::DownloadBackend;
+use crate::Result;
+
+/// Suffix of the file a download is written to before it is moved into place
+pub const DEFAULT_PART_SUFFIX: &str = ".part";
+
+/// Get the temporary file of a download to `target_path`
+pub fn part_path(target_path: &Path, suffix: &str) -> PathBuf {
+    let mut name = target_path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    target_path.with_file_name(name)
+}
+
+/// Download backend writing to temporary files and renaming them on completion
+pub struct PartFileBackend {
+    inner: Arc<dyn DownloadBackend>,
+    suffix: String,
+    /// Completed tasks whose file was moved into place
+    moved: RwLock<HashSet<TaskId>>,
+}
+
+impl PartFileBackend {
+    /// Wrap `inner`, writing downloads to `<target><suffix>`
+    pub fn new(inner: Arc<dyn DownloadBackend>, suffix: impl Into<String>) -> Self {
+        Self {
+            inner,
+            suffix: suffix.into(),
+            moved: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn suffix(&self) -> &str {
+        &self.suffix
+    }
+
+    /// Get the target path of a temporary file
+    fn target_path(&self, part_path: &Path) -> PathBuf {
+        let name = part_path.file_name().unwrap_or_default().to_string_lossy();
+        match name.strip_suffix(&self.suffix) {
+            Some(target_name) => part_path.with_file_name(target_name),
+            None => part_path.to_path_buf(),
+        }
+    }
+
+    /// Report an engine task under its target path, moving its file there once complete
+    async fn finish(&self, mut task: DownloadTask) -> DownloadTask {
+        let part_path = task.target_path.clone();
+        task.target_path = self.target_path(&part_path);
+
+        if task.status != DownloadStatus::Completed || self.moved.read().await.contains(&task.id) {
+            return task;
+        }
+
+        match tokio::fs::rename(&part_path, &task.target_path).await {
+            Ok(()) => log::debug!("Moved {} into place", task.target_path.display()),
+            // Nothing to move: the engine wrote no file or a concurrent call moved it
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                log::error!("Failed to move {} to {}: {}", part_path.display(), task.target_path.display(), e);
+                task.update_status(DownloadStatus::Failed(format!(
+                    "Failed to move the completed file into place: {}", e
+                )));
+                return task;
+            }
+        }
+
+        self.moved.write().await.insert(task.id);
+        task
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for PartFileBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        self.inner.add(url, part_path(&target_path, &self.suffix)).await
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.inner.add_with_options(url, part_path(&target_path, &self.suffix), options).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.inner.add_multi_source(urls, part_path(&target_path, &self.suffix), options).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.inner.pause(task_id).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.inner.resume(task_id).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.inner.cancel(task_id).await?;
+        self.moved.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.inner.progress(task_id).await
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        let task = self.inner.task(task_id).await?;
+        Ok(self.finish(task).await)
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        let mut tasks = Vec::new();
+        for task in self.inner.list().await? {
+            tasks.push(self.finish(task).await);
+        }
+        Ok(tasks)
+    }
+
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        let mut snapshot = Vec::new();
+        for (task, progress) in self.inner.list_with_progress().await? {
+            snapshot.push((self.finish(task).await, progress));
+        }
+        Ok(snapshot)
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        self.inner.active_count().await
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.inner.set_global_speed_limit(bytes_per_sec).await
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        self.inner.set_task_speed_limit(task_id, bytes_per_sec).await
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.inner.engine_id(task_id).await
+    }
+
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool> {
+        // The engine knows the download by its temporary file
+        let mut engine_task = task.clone();
+        engine_task.target_path = part_path(&task.target_path, &self.suffix);
+        self.inner.reattach(&engine_task, engine_id).await
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        self.inner.change_source(task_id, new_url, options).await
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        self.inner.error_code(task_id).await
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        let mut files = self.inner.files(task_id).await?;
+        for file in &mut files {
+            file.path = self.target_path(&file.path);
+        }
+        Ok(files)
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        self.inner.select_files(task_id, indices).await
+    }
+
+    async fn verifies_checksum(&self, task_id: TaskId) -> bool {
+        self.inner.verifies_checksum(task_id).await
+    }
+}
\ No newline at end of file
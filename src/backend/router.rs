@@ -0,0 +1,188 @@
+//! Scheme-based backend routing
+//!
+//! [`SchemeRouter`] hands each download to the backend registered for the
+//! scheme of its URL and everything else to a default backend, so e.g.
+//! `sftp://` can be served in-process while aria2 keeps HTTP and FTP.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask};
+use crate::error::DownloadError;
+use crate::models::{DownloadOptions, FileProgress};
+use crate::traits::DownloadBackend;
+use crate::Result;
+
+/// Download backend dispatching on the URL scheme
+pub struct SchemeRouter {
+    default: Arc<dyn DownloadBackend>,
+    routes: HashMap<String, Arc<dyn DownloadBackend>>,
+    /// Backend running each task added through the router
+    owners: RwLock<HashMap<TaskId, Arc<dyn DownloadBackend>>>,
+}
+
+impl SchemeRouter {
+    /// Send every download to `default` until routes are added
+    pub fn new(default: Arc<dyn DownloadBackend>) -> Self {
+        Self {
+            default,
+            routes: HashMap::new(),
+            owners: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Send downloads of `scheme` URLs to `backend`
+    pub fn route(mut self, scheme: &str, backend: Arc<dyn DownloadBackend>) -> Self {
+        self.routes.insert(scheme.to_ascii_lowercase(), backend);
+        self
+    }
+
+    /// Get the backend serving `url`
+    pub fn backend_for_url(&self, url: &str) -> &Arc<dyn DownloadBackend> {
+        url::Url::parse(url).ok()
+            .and_then(|url| self.routes.get(url.scheme()))
+            .unwrap_or(&self.default)
+    }
+
+    /// Get the backend running a task, the default one for unknown tasks
+    async fn backend_for_task(&self, task_id: TaskId) -> Arc<dyn DownloadBackend> {
+        self.owners.read().await.get(&task_id)
+            .cloned()
+            .unwrap_or_else(|| self.default.clone())
+    }
+
+    /// Get every distinct backend, the default one first
+    fn backends(&self) -> Vec<&Arc<dyn DownloadBackend>> {
+        let mut backends = vec![&self.default];
+        for backend in self.routes.values() {
+            if !backends.iter().any(|known| Arc::ptr_eq(known, backend)) {
+                backends.push(backend);
+            }
+        }
+        backends
+    }
+
+    async fn record_owner(&self, task_id: TaskId, backend: &Arc<dyn DownloadBackend>) {
+        self.owners.write().await.insert(task_id, backend.clone());
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for SchemeRouter {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        let backend = self.backend_for_url(&url);
+        let task_id = backend.add(url, target_path).await?;
+        self.record_owner(task_id, backend).await;
+        Ok(task_id)
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let backend = self.backend_for_url(&url);
+        let task_id = backend.add_with_options(url, target_path, options).await?;
+        self.record_owner(task_id, backend).await;
+        Ok(task_id)
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        // Mirrors are handed to the backend of the primary source
+        let primary = urls.first()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        let backend = self.backend_for_url(primary);
+        let task_id = backend.add_multi_source(urls, target_path, options).await?;
+        self.record_owner(task_id, backend).await;
+        Ok(task_id)
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.backend_for_task(task_id).await.pause(task_id).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.backend_for_task(task_id).await.resume(task_id).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.backend_for_task(task_id).await.cancel(task_id).await?;
+        self.owners.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.backend_for_task(task_id).await.progress(task_id).await
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.backend_for_task(task_id).await.task(task_id).await
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        let mut tasks = Vec::new();
+        for backend in self.backends() {
+            tasks.extend(backend.list().await?);
+        }
+        Ok(tasks)
+    }
+
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        let mut snapshot = Vec::new();
+        for backend in self.backends() {
+            snapshot.extend(backend.list_with_progress().await?);
+        }
+        Ok(snapshot)
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        let mut count = 0;
+        for backend in self.backends() {
+            count += backend.active_count().await?;
+        }
+        Ok(count)
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        for backend in self.backends() {
+            backend.set_global_speed_limit(bytes_per_sec).await?;
+        }
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        self.backend_for_task(task_id).await.set_task_speed_limit(task_id, bytes_per_sec).await
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.backend_for_task(task_id).await.engine_id(task_id).await
+    }
+
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool> {
+        let backend = self.backend_for_url(&task.url);
+        let reattached = backend.reattach(task, engine_id).await?;
+        if reattached {
+            self.record_owner(task.id, backend).await;
+        }
+        Ok(reattached)
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        // The download stays with the backend running it, whatever the new scheme
+        self.backend_for_task(task_id).await.change_source(task_id, new_url, options).await
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        self.backend_for_task(task_id).await.error_code(task_id).await
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        self.backend_for_task(task_id).await.files(task_id).await
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        self.backend_for_task(task_id).await.select_files(task_id, indices).await
+    }
+
+    async fn verifies_checksum(&self, task_id: TaskId) -> bool {
+        self.backend_for_task(task_id).await.verifies_checksum(task_id).await
+    }
+}
\ No newline at end of file
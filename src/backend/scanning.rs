@@ -0,0 +1,238 @@
+//! Scanning completed downloads before they are reported complete
+//!
+//! [`ScanningBackend`] keeps reporting a download the engine finished as
+//! downloading while its [`ScanHook`]s check the file. A clean file is then
+//! reported completed; a rejected one is moved to the quarantine directory
+//! and its task reported failed with a [`SCAN_REJECTED`] reason.
+//!
+//! Wrapped by [`PartFileBackend`](super::PartFileBackend), so temporary
+//! files are scanned before they are moved to their target path.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus};
+use crate::hooks::{ScanHook, ScanVerdict, SCAN_REJECTED};
+use crate::models::{DownloadOptions, FileProgress};
+use crate::traits::DownloadBackend;
+use crate::utils::paths::move_file;
+use crate::Result;
+
+/// Where the scan of a completed download stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ScanState {
+    Running,
+    Clean,
+    Rejected(String),
+}
+
+/// Download backend scanning files once the engine completed them
+pub struct ScanningBackend {
+    inner: Arc<dyn DownloadBackend>,
+    scanners: Arc<Vec<Arc<dyn ScanHook>>>,
+    quarantine_dir: PathBuf,
+    scans: Arc<RwLock<HashMap<TaskId, ScanState>>>,
+}
+
+impl ScanningBackend {
+    /// Wrap `inner`, moving rejected files into `quarantine_dir`
+    pub fn new(inner: Arc<dyn DownloadBackend>, scanners: Vec<Arc<dyn ScanHook>>, quarantine_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            scanners: Arc::new(scanners),
+            quarantine_dir: quarantine_dir.into(),
+            scans: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn quarantine_dir(&self) -> &Path {
+        &self.quarantine_dir
+    }
+
+    /// Report an engine task with the outcome of its scan, starting the scan once complete
+    async fn finish(&self, mut task: DownloadTask) -> DownloadTask {
+        if task.status != DownloadStatus::Completed {
+            return task;
+        }
+
+        let state = self.scans.read().await.get(&task.id).cloned();
+        match state {
+            Some(ScanState::Clean) => {}
+            Some(ScanState::Rejected(reason)) => {
+                task.update_status(DownloadStatus::Failed(format!("{}: {}", SCAN_REJECTED, reason)));
+            }
+            Some(ScanState::Running) => task.update_status(DownloadStatus::Downloading),
+            None => {
+                // Only the first caller starts the scan
+                if self.scans.write().await.insert(task.id, ScanState::Running).is_none() {
+                    tokio::spawn(scan_file(
+                        self.scanners.clone(),
+                        self.scans.clone(),
+                        self.quarantine_dir.clone(),
+                        task.id,
+                        task.target_path.clone(),
+                    ));
+                }
+                task.update_status(DownloadStatus::Downloading);
+            }
+        }
+        task
+    }
+}
+
+/// Run every scanner on a file and quarantine it if one rejects it
+async fn scan_file(
+    scanners: Arc<Vec<Arc<dyn ScanHook>>>,
+    scans: Arc<RwLock<HashMap<TaskId, ScanState>>>,
+    quarantine_dir: PathBuf,
+    task_id: TaskId,
+    path: PathBuf,
+) {
+    // The file was moved on after an earlier scan, e.g. before a restart
+    if tokio::fs::metadata(&path).await.is_err() {
+        log::debug!("Skipping scan of task {}, {} no longer exists", task_id, path.display());
+        scans.write().await.insert(task_id, ScanState::Clean);
+        return;
+    }
+
+    let mut rejection = None;
+    for scanner in scanners.iter() {
+        // A scanner that cannot tell keeps the file out of use
+        match scanner.scan(task_id, &path).await {
+            Ok(ScanVerdict::Clean) => {}
+            Ok(ScanVerdict::Rejected(finding)) => {
+                rejection = Some(format!("{} found {}", scanner.name(), finding));
+                break;
+            }
+            Err(e) => {
+                rejection = Some(format!("{} failed: {}", scanner.name(), e));
+                break;
+            }
+        }
+    }
+
+    let state = match rejection {
+        None => {
+            log::info!("Scanned {} for task {}, clean", path.display(), task_id);
+            ScanState::Clean
+        }
+        Some(reason) => {
+            log::warn!("Scan rejected {} for task {}: {}", path.display(), task_id, reason);
+            if let Err(e) = quarantine(&quarantine_dir, task_id, &path).await {
+                log::error!("Failed to quarantine {}: {}", path.display(), e);
+            }
+            ScanState::Rejected(reason)
+        }
+    };
+    scans.write().await.insert(task_id, state);
+}
+
+/// Move a rejected file into the quarantine directory, prefixed with its task ID
+async fn quarantine(quarantine_dir: &Path, task_id: TaskId, path: &Path) -> io::Result<()> {
+    tokio::fs::create_dir_all(quarantine_dir).await?;
+
+    let mut name = std::ffi::OsString::from(format!("{}-", task_id));
+    name.push(path.file_name().unwrap_or_default());
+    let destination = quarantine_dir.join(name);
+
+    move_file(path, &destination).await?;
+    log::info!("Quarantined {} as {}", path.display(), destination.display());
+    Ok(())
+}
+
+#[async_trait]
+impl DownloadBackend for ScanningBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        self.inner.add(url, target_path).await
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.inner.add_with_options(url, target_path, options).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.inner.add_multi_source(urls, target_path, options).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.inner.pause(task_id).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.inner.resume(task_id).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.inner.cancel(task_id).await?;
+        self.scans.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.inner.progress(task_id).await
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        let task = self.inner.task(task_id).await?;
+        Ok(self.finish(task).await)
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        let mut tasks = Vec::new();
+        for task in self.inner.list().await? {
+            tasks.push(self.finish(task).await);
+        }
+        Ok(tasks)
+    }
+
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        let mut snapshot = Vec::new();
+        for (task, progress) in self.inner.list_with_progress().await? {
+            snapshot.push((self.finish(task).await, progress));
+        }
+        Ok(snapshot)
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        self.inner.active_count().await
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.inner.set_global_speed_limit(bytes_per_sec).await
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        self.inner.set_task_speed_limit(task_id, bytes_per_sec).await
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.inner.engine_id(task_id).await
+    }
+
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool> {
+        self.inner.reattach(task, engine_id).await
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        self.inner.change_source(task_id, new_url, options).await
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        self.inner.error_code(task_id).await
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        self.inner.files(task_id).await
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        self.inner.select_files(task_id, indices).await
+    }
+
+    async fn verifies_checksum(&self, task_id: TaskId) -> bool {
+        self.inner.verifies_checksum(task_id).await
+    }
+}
\ No newline at end of file
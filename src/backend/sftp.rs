@@ -0,0 +1,459 @@
+//! SFTP download backend
+//!
+//! Fetches `sftp://` URLs in-process with libssh2, for aria2 builds without
+//! SFTP support. Each transfer runs on a blocking thread. Pausing stops it and
+//! resuming continues from the bytes already on disk; a new download only
+//! continues an existing file when its options ask to. Available with the
+//! `sftp` feature.
+//!
+//! The server's host key must be listed in the known_hosts file, by default
+//! `~/.ssh/known_hosts`; unknown or changed keys fail the download before any
+//! credentials are sent. Logins use `Basic` credentials from the download
+//! options: the password when one is given, the SSH agent otherwise. Of the other options only the speed
+//! limit and file allocation apply. A preallocated file can't be told apart
+//! from a complete one, so new downloads with an allocation mode start over
+//! instead of continuing an existing file.
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus};
+use crate::error::DownloadError;
+use crate::models::{Credentials, DownloadOptions, FileAllocation};
+use crate::traits::DownloadBackend;
+use crate::Result;
+
+const DEFAULT_SFTP_PORT: u16 = 22;
+/// How long connecting, the SSH handshake and logging in may take
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Bytes requested from the server per read
+const CHUNK_SIZE: usize = 64 * 1024;
+/// How often the transfer speed is measured
+const SPEED_INTERVAL: Duration = Duration::from_secs(1);
+
+const RUNNING: u8 = 0;
+const PAUSING: u8 = 1;
+const CANCELLING: u8 = 2;
+
+/// Where a run of a transfer picks up
+enum StartAt {
+    /// Truncate the file and download all of it
+    Beginning,
+    /// After the bytes of a file already on disk
+    ExistingFile,
+    /// After the bytes an earlier run of the transfer wrote
+    Offset(u64),
+}
+
+/// How a transfer thread stopped without an error
+enum Outcome {
+    Finished,
+    Paused,
+    Cancelled,
+}
+
+/// Speed limits shared by all transfers
+#[derive(Default)]
+struct Limits {
+    /// Combined limit in bytes per second, 0 = unlimited
+    global: AtomicU64,
+    /// Transfers currently moving data, sharing the global limit
+    running: AtomicUsize,
+}
+
+/// State of one SFTP download
+struct Transfer {
+    task: Mutex<DownloadTask>,
+    credentials: Option<Credentials>,
+    allocation: Option<FileAllocation>,
+    downloaded: AtomicU64,
+    /// Size of the remote file, 0 while unknown
+    total: AtomicU64,
+    speed: AtomicU64,
+    speed_limit: AtomicU64,
+    control: AtomicU8,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Transfer {
+    fn status(&self) -> DownloadStatus {
+        self.task.lock().unwrap().status.clone()
+    }
+
+    fn set_status(&self, status: DownloadStatus) {
+        let mut task = self.task.lock().unwrap();
+        if task.status != status {
+            task.update_status(status);
+        }
+    }
+
+    /// Get the effective speed limit, 0 = unlimited
+    fn limit(&self, limits: &Limits) -> u64 {
+        let task_limit = self.speed_limit.load(Ordering::SeqCst);
+        let global = limits.global.load(Ordering::SeqCst);
+        let share = global / limits.running.load(Ordering::SeqCst).max(1) as u64;
+
+        match (task_limit, share) {
+            (0, share) => share,
+            (limit, 0) => limit,
+            (limit, share) => limit.min(share),
+        }
+    }
+}
+
+/// How transfers connect to the server
+#[derive(Debug, Clone)]
+struct Connection {
+    /// OpenSSH known_hosts file the server's host key is checked against
+    known_hosts: PathBuf,
+    timeout: Duration,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        Self {
+            known_hosts: home.join(".ssh").join("known_hosts"),
+            timeout: DEFAULT_CONNECT_TIMEOUT,
+        }
+    }
+}
+
+/// Download backend fetching `sftp://` URLs over SSH
+#[derive(Default)]
+pub struct SftpBackend {
+    transfers: RwLock<HashMap<TaskId, Arc<Transfer>>>,
+    limits: Arc<Limits>,
+    connection: Connection,
+}
+
+impl SftpBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check host keys against this known_hosts file instead of `~/.ssh/known_hosts`
+    pub fn known_hosts(mut self, path: impl Into<PathBuf>) -> Self {
+        self.connection.known_hosts = path.into();
+        self
+    }
+
+    /// Set how long connecting, the SSH handshake and logging in may take
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connection.timeout = timeout;
+        self
+    }
+
+    async fn transfer(&self, task_id: TaskId) -> Result<Arc<Transfer>> {
+        self.transfers.read().await.get(&task_id)
+            .cloned()
+            .ok_or(DownloadError::TaskNotFound(task_id))
+    }
+
+    /// Run a transfer on a blocking thread
+    ///
+    /// Waits for a previous run of the transfer to stop first, so two
+    /// threads never write the same file.
+    async fn start(&self, transfer: Arc<Transfer>, start_at: StartAt) {
+        let previous = transfer.worker.lock().unwrap().take();
+        if let Some(previous) = previous {
+            let _ = previous.await;
+        }
+
+        transfer.control.store(RUNNING, Ordering::SeqCst);
+        transfer.set_status(DownloadStatus::Downloading);
+
+        let limits = self.limits.clone();
+        let connection = self.connection.clone();
+        let worker = transfer.clone();
+        let handle = tokio::task::spawn_blocking(move || {
+            limits.running.fetch_add(1, Ordering::SeqCst);
+            let result = download(&worker, &limits, &connection, start_at);
+            limits.running.fetch_sub(1, Ordering::SeqCst);
+            worker.speed.store(0, Ordering::SeqCst);
+
+            match result {
+                Ok(Outcome::Finished) => worker.set_status(DownloadStatus::Completed),
+                Ok(Outcome::Paused) | Ok(Outcome::Cancelled) => {}
+                Err(e) => {
+                    log::warn!("SFTP download failed: {}", e);
+                    worker.set_status(DownloadStatus::Failed(e.to_string()));
+                }
+            }
+        });
+        *transfer.worker.lock().unwrap() = Some(handle);
+    }
+}
+
+/// Open an SSH session to a server whose host key is known
+///
+/// Fails closed: a missing known_hosts file, an unknown host and a changed
+/// host key all refuse the connection.
+fn connect(host: &str, port: u16, connection: &Connection) -> io::Result<Session> {
+    let address = (host, port).to_socket_addrs()?.next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("SFTP host {} has no address", host)))?;
+    let stream = TcpStream::connect_timeout(&address, connection.timeout)?;
+
+    let mut session = Session::new()?;
+    session.set_timeout(connection.timeout.as_millis().min(u32::MAX as u128) as u32);
+    session.set_tcp_stream(stream);
+    session.handshake()?;
+    verify_host_key(&session, host, port, &connection.known_hosts)?;
+    Ok(session)
+}
+
+/// Check the host key the server presented against the known_hosts file
+fn verify_host_key(session: &Session, host: &str, port: u16, known_hosts: &Path) -> io::Result<()> {
+    let (key, _) = session.host_key()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::PermissionDenied, "SFTP server sent no host key"))?;
+
+    let mut hosts = session.known_hosts()?;
+    hosts.read_file(known_hosts, KnownHostFileKind::OpenSSH).map_err(|e| io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!("Cannot check the host key of {}, failed to read {}: {}", host, known_hosts.display(), e),
+    ))?;
+
+    match hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Host key of {} is not in {}", host, known_hosts.display()),
+        )),
+        CheckResult::Mismatch => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Host key of {} does not match the one in {}", host, known_hosts.display()),
+        )),
+        CheckResult::Failure => Err(io::Error::other(format!("Failed to check the host key of {}", host))),
+    }
+}
+
+/// Log in with the given credentials, through the SSH agent without a password
+fn authenticate(session: &Session, credentials: Option<&Credentials>) -> io::Result<()> {
+    match credentials {
+        Some(Credentials::Basic { username, password }) if !password.is_empty() => {
+            session.userauth_password(username, password)?;
+        }
+        Some(Credentials::Basic { username, .. }) => session.userauth_agent(username)?,
+        _ => return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SFTP downloads need a user name")),
+    }
+
+    if !session.authenticated() {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "SFTP authentication failed"));
+    }
+    Ok(())
+}
+
+/// Copy the remote file into the target path until done, paused or cancelled
+fn download(transfer: &Transfer, limits: &Limits, connection: &Connection, start_at: StartAt) -> io::Result<Outcome> {
+    let (url, target_path) = {
+        let task = transfer.task.lock().unwrap();
+        (task.url.clone(), task.target_path.clone())
+    };
+    let url = url::Url::parse(&url)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid SFTP URL: {}", e)))?;
+    let host = url.host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "SFTP URL has no host"))?;
+    let remote_path = percent_encoding::percent_decode_str(url.path()).decode_utf8_lossy().into_owned();
+
+    let session = connect(host, url.port().unwrap_or(DEFAULT_SFTP_PORT), connection)?;
+    authenticate(&session, transfer.credentials.as_ref())?;
+    // Slow reads are throttled or paused by the transfer loop, not timed out
+    session.set_timeout(0);
+
+    let sftp = session.sftp()?;
+    let mut remote = sftp.open(Path::new(&remote_path))?;
+    let total = remote.stat()?.size.unwrap_or(0);
+    transfer.total.store(total, Ordering::SeqCst);
+
+    let offset = match start_at {
+        StartAt::Beginning => 0,
+        // Unless the file on disk can't be a prefix of the remote one
+        StartAt::ExistingFile => match std::fs::metadata(&target_path) {
+            Ok(metadata) if total == 0 || metadata.len() <= total => metadata.len(),
+            _ => 0,
+        },
+        StartAt::Offset(offset) => offset,
+    };
+    let mut file = OpenOptions::new().create(true).write(true).truncate(offset == 0).open(&target_path)?;
+    if let Some(allocation) = transfer.allocation.filter(|_| offset == 0 && total > 0) {
+        allocation.allocate(&file, total)?;
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    remote.seek(SeekFrom::Start(offset))?;
+    transfer.downloaded.store(offset, Ordering::SeqCst);
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut window_start = Instant::now();
+    let mut window_bytes = 0u64;
+
+    loop {
+        match transfer.control.load(Ordering::SeqCst) {
+            PAUSING => return Ok(Outcome::Paused),
+            CANCELLING => return Ok(Outcome::Cancelled),
+            _ => {}
+        }
+
+        let read = remote.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        transfer.downloaded.fetch_add(read as u64, Ordering::SeqCst);
+        window_bytes += read as u64;
+
+        // Sleep off whatever the window got ahead of the limit
+        let limit = transfer.limit(limits);
+        if limit > 0 {
+            let expected = Duration::from_secs_f64(window_bytes as f64 / limit as f64);
+            if let Some(ahead) = expected.checked_sub(window_start.elapsed()) {
+                std::thread::sleep(ahead);
+            }
+        }
+
+        let elapsed = window_start.elapsed();
+        if elapsed >= SPEED_INTERVAL {
+            transfer.speed.store((window_bytes as f64 / elapsed.as_secs_f64()) as u64, Ordering::SeqCst);
+            window_start = Instant::now();
+            window_bytes = 0;
+        }
+    }
+
+    file.flush()?;
+    Ok(Outcome::Finished)
+}
+
+#[async_trait]
+impl DownloadBackend for SftpBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        self.add_with_options(url, target_path, &DownloadOptions::default()).await
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let task = DownloadTask::new(url, target_path);
+        let task_id = task.id;
+        let transfer = Arc::new(Transfer {
+            task: Mutex::new(task),
+            credentials: options.credentials.clone(),
+            allocation: options.file_allocation,
+            downloaded: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            speed: AtomicU64::new(0),
+            speed_limit: AtomicU64::new(options.speed_limit.unwrap_or(0)),
+            control: AtomicU8::new(RUNNING),
+            worker: Mutex::new(None),
+        });
+
+        self.transfers.write().await.insert(task_id, transfer.clone());
+        let preallocated = !matches!(options.file_allocation, None | Some(FileAllocation::None));
+        let start_at = if options.continue_partial && !preallocated {
+            StartAt::ExistingFile
+        } else {
+            StartAt::Beginning
+        };
+        self.start(transfer, start_at).await;
+        Ok(task_id)
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        // A single SSH connection serves the whole file, mirrors are not used
+        let url = urls.into_iter().next()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        self.add_with_options(url, target_path, options).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        let transfer = self.transfer(task_id).await?;
+        let status = transfer.status();
+        if !status.can_pause() {
+            return Err(DownloadError::InvalidTaskState { task_id, operation: "pause", status });
+        }
+
+        transfer.control.store(PAUSING, Ordering::SeqCst);
+        transfer.set_status(DownloadStatus::Paused);
+        Ok(())
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        let transfer = self.transfer(task_id).await?;
+        let status = transfer.status();
+        if !status.can_resume() {
+            return Err(DownloadError::InvalidTaskState { task_id, operation: "resume", status });
+        }
+
+        let offset = transfer.downloaded.load(Ordering::SeqCst);
+        self.start(transfer, StartAt::Offset(offset)).await;
+        Ok(())
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        let transfer = self.transfers.write().await.remove(&task_id)
+            .ok_or(DownloadError::TaskNotFound(task_id))?;
+        transfer.control.store(CANCELLING, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        let transfer = self.transfer(task_id).await?;
+        let downloaded_bytes = transfer.downloaded.load(Ordering::SeqCst);
+        let total = transfer.total.load(Ordering::SeqCst);
+        let speed_bps = transfer.speed.load(Ordering::SeqCst);
+        let total_bytes = (total > 0).then_some(total);
+
+        Ok(DownloadProgress {
+            downloaded_bytes,
+            total_bytes,
+            speed_bps,
+            eta_seconds: total_bytes
+                .filter(|_| speed_bps > 0)
+                .map(|total| total.saturating_sub(downloaded_bytes) / speed_bps),
+        })
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        let transfer = self.transfer(task_id).await?;
+        let task = transfer.task.lock().unwrap().clone();
+        Ok(task)
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        Ok(self.transfers.read().await.values()
+            .map(|transfer| transfer.task.lock().unwrap().clone())
+            .collect())
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        Ok(self.transfers.read().await.values()
+            .filter(|transfer| transfer.status().is_active())
+            .count())
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.limits.global.store(bytes_per_sec, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        self.transfer(task_id).await?.speed_limit.store(bytes_per_sec, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        // Transfers have no identity beyond the task, which also keys the GID mapping
+        self.transfer(task_id).await?;
+        Ok(Some(format!("sftp-{}", task_id)))
+    }
+
+    async fn reattach(&self, _task: &DownloadTask, _engine_id: &str) -> Result<bool> {
+        // Transfers end with the process, restored tasks are added again
+        Ok(false)
+    }
+}
\ No newline at end of file
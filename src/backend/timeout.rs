@@ -0,0 +1,173 @@
+//! Timeouts and circuit breaking for backend calls
+//!
+//! [`TimeoutBackend`] fails every call the wrapped backend does not finish
+//! within the request timeout with `DownloadError::BackendTimeout`. Once
+//! enough calls in a row timed out, the circuit opens: calls fail with
+//! `DownloadError::DownloaderUnavailable` without reaching the backend until
+//! the cooldown passed. The first call after that tries the backend again and
+//! either closes the circuit or opens it for another cooldown.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+use async_trait::async_trait;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask};
+use crate::error::DownloadError;
+use crate::models::{DownloadOptions, FileProgress, RpcTimeouts};
+use crate::traits::DownloadBackend;
+use crate::Result;
+
+#[derive(Debug, Default)]
+struct Breaker {
+    /// Calls timed out in a row
+    timeouts: u32,
+    /// When an open circuit lets calls through again
+    open_until: Option<Instant>,
+}
+
+/// Download backend failing calls that take too long
+pub struct TimeoutBackend {
+    inner: Arc<dyn DownloadBackend>,
+    timeouts: RpcTimeouts,
+    breaker: Mutex<Breaker>,
+}
+
+impl TimeoutBackend {
+    pub fn new(inner: Arc<dyn DownloadBackend>, timeouts: RpcTimeouts) -> Self {
+        Self {
+            inner,
+            timeouts,
+            breaker: Mutex::new(Breaker::default()),
+        }
+    }
+
+    pub fn timeouts(&self) -> RpcTimeouts {
+        self.timeouts
+    }
+
+    /// Check if calls currently fail without reaching the backend
+    pub fn is_open(&self) -> bool {
+        let breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        breaker.open_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    async fn call<T>(&self, operation: &str, call: impl Future<Output = Result<T>>) -> Result<T> {
+        if self.is_open() {
+            return Err(DownloadError::DownloaderUnavailable(format!(
+                "{} skipped, the backend stopped responding", operation
+            )));
+        }
+
+        let result = tokio::time::timeout(self.timeouts.request, call).await;
+
+        let mut breaker = self.breaker.lock().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Ok(result) => {
+                breaker.timeouts = 0;
+                breaker.open_until = None;
+                result
+            }
+            Err(_) => {
+                breaker.timeouts += 1;
+                let threshold = self.timeouts.breaker_threshold;
+                if threshold > 0 && breaker.timeouts >= threshold {
+                    log::warn!(
+                        "Backend timed out {} times in a row, failing calls for {:?}",
+                        breaker.timeouts, self.timeouts.breaker_cooldown
+                    );
+                    breaker.open_until = Some(Instant::now() + self.timeouts.breaker_cooldown);
+                }
+                Err(DownloadError::BackendTimeout {
+                    operation: operation.to_string(),
+                    timeout: self.timeouts.request,
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for TimeoutBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        self.call("add", self.inner.add(url, target_path)).await
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.call("add", self.inner.add_with_options(url, target_path, options)).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        self.call("add", self.inner.add_multi_source(urls, target_path, options)).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.call("pause", self.inner.pause(task_id)).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.call("resume", self.inner.resume(task_id)).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.call("cancel", self.inner.cancel(task_id)).await
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.call("progress", self.inner.progress(task_id)).await
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.call("task", self.inner.task(task_id)).await
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        self.call("list", self.inner.list()).await
+    }
+
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        self.call("list", self.inner.list_with_progress()).await
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        self.call("active_count", self.inner.active_count()).await
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.call("set_global_speed_limit", self.inner.set_global_speed_limit(bytes_per_sec)).await
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        self.call("set_task_speed_limit", self.inner.set_task_speed_limit(task_id, bytes_per_sec)).await
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.call("engine_id", self.inner.engine_id(task_id)).await
+    }
+
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool> {
+        self.call("reattach", self.inner.reattach(task, engine_id)).await
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        self.call("change_source", self.inner.change_source(task_id, new_url, options)).await
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        self.call("error_code", self.inner.error_code(task_id)).await
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        self.call("files", self.inner.files(task_id)).await
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        self.call("select_files", self.inner.select_files(task_id, indices)).await
+    }
+
+    // Answered without asking the engine, so there is nothing to time out
+    async fn verifies_checksum(&self, task_id: TaskId) -> bool {
+        self.inner.verifies_checksum(task_id).await
+    }
+}
\ No newline at end of file
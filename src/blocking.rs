@@ -0,0 +1,144 @@
+//! Blocking API
+//!
+//! Mirrors the simple functions of the crate root for callers without an
+//! async runtime, similar to `reqwest::blocking`. Calls run on a runtime owned
+//! by this module, which also drives the global manager's background work,
+//! such as persistence, between calls.
+//!
+//! These functions must not be called from within an async runtime, where
+//! they panic; use the async functions there instead.
+//!
+//! ```rust,no_run
+//! use burncloud_download::blocking;
+//!
+//! fn main() -> anyhow::Result<()> {
+//!     let task_id = blocking::download("https://example.com/file.zip")?;
+//!     let task = blocking::wait(task_id, None)?;
+//!     println!("Saved to {}", task.target_path.display());
+//!
+//!     blocking::shutdown()?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::types::{DownloadProgress, DownloadTask, TaskId};
+use crate::models::DuplicatePolicy;
+use crate::probe::ProbeResult;
+use crate::Result;
+use std::future::Future;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Get or start the runtime shared by all blocking calls
+fn runtime() -> Result<&'static Runtime> {
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("burncloud-download")
+        .build()?;
+    // Another thread may have won the race, its runtime is used instead
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+fn block_on<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+    runtime()?.block_on(future)
+}
+
+/// Ask the server about a URL before downloading it, see [`crate::probe()`]
+pub fn probe<S: AsRef<str>>(url: S) -> Result<ProbeResult> {
+    block_on(crate::probe(url))
+}
+
+/// Download a file to the default ./data/ directory, see [`crate::download`]
+pub fn download<S: AsRef<str>>(url: S) -> Result<TaskId> {
+    block_on(crate::download(url))
+}
+
+/// Download a file to a specific path, see [`crate::download_to`]
+pub fn download_to<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P) -> Result<TaskId> {
+    block_on(crate::download_to(url, target_path))
+}
+
+/// Download a file handling an existing download of it with `policy`, see [`crate::download_with_policy`]
+pub fn download_with_policy<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P, policy: DuplicatePolicy) -> Result<TaskId> {
+    block_on(crate::download_with_policy(url, target_path, policy))
+}
+
+/// Block until a download task completes, see [`crate::wait_for_download`]
+pub fn wait(task_id: TaskId, timeout: Option<Duration>) -> Result<DownloadTask> {
+    block_on(crate::wait_for_download(task_id, timeout))
+}
+
+/// Download a file to the default ./data/ directory and block until it completes
+pub fn download_and_wait<S: AsRef<str>>(url: S) -> Result<DownloadTask> {
+    block_on(crate::download_and_wait(url))
+}
+
+/// Get the progress of a download task
+pub fn get_download_progress(task_id: TaskId) -> Result<DownloadProgress> {
+    block_on(crate::get_download_progress(task_id))
+}
+
+/// Get detailed information about a download task
+pub fn get_download_task(task_id: TaskId) -> Result<DownloadTask> {
+    block_on(crate::get_download_task(task_id))
+}
+
+/// Pause a download task
+pub fn pause_download(task_id: TaskId) -> Result<()> {
+    block_on(crate::pause_download(task_id))
+}
+
+/// Resume a paused download task
+pub fn resume_download(task_id: TaskId) -> Result<()> {
+    block_on(crate::resume_download(task_id))
+}
+
+/// Cancel and remove a download task
+pub fn cancel_download(task_id: TaskId) -> Result<()> {
+    block_on(crate::cancel_download(task_id))
+}
+
+/// Cancel a download task but keep it with a cancelled status
+pub fn cancel_and_keep(task_id: TaskId, delete_files: bool) -> Result<bool> {
+    block_on(crate::cancel_and_keep(task_id, delete_files))
+}
+
+/// List all download tasks
+pub fn list_downloads() -> Result<Vec<DownloadTask>> {
+    block_on(crate::list_downloads())
+}
+
+/// Get the number of active downloads
+pub fn active_download_count() -> Result<usize> {
+    block_on(crate::active_download_count())
+}
+
+/// Pause every download and return the IDs of the paused tasks
+pub fn pause_all() -> Result<Vec<TaskId>> {
+    block_on(crate::pause_all())
+}
+
+/// Resume every paused download and return the IDs of the resumed tasks
+pub fn resume_all() -> Result<Vec<TaskId>> {
+    block_on(crate::resume_all())
+}
+
+/// Cancel every download and return the IDs of the removed tasks
+pub fn cancel_all() -> Result<Vec<TaskId>> {
+    block_on(crate::cancel_all())
+}
+
+/// Flush all tasks to the database, see [`crate::shutdown_global_manager`]
+///
+/// Call this before the program exits.
+pub fn shutdown() -> Result<()> {
+    block_on(crate::shutdown_global_manager())
+}
\ No newline at end of file
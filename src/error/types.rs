@@ -1,8 +1,13 @@
 use thiserror::Error;
-use crate::types::TaskId;
+use crate::types::{TaskId, DownloadStatus};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Download manager error types
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum DownloadError {
     #[error("Task with ID {0} not found")]
     TaskNotFound(TaskId),
@@ -10,15 +15,24 @@ pub enum DownloadError {
     #[error("Invalid task status transition")]
     InvalidStatusTransition,
 
+    #[error("Cannot {operation} task {task_id} in status {status}")]
+    InvalidTaskState { task_id: TaskId, operation: &'static str, status: DownloadStatus },
+
     #[error("Maximum concurrent downloads exceeded")]
     ConcurrencyLimitExceeded,
 
+    #[error("Download queue is full: {0}")]
+    QueueFull(String),
+
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
     #[error("Invalid target path: {0}")]
     InvalidPath(String),
 
+    #[error("Invalid download option: {0}")]
+    InvalidOption(String),
+
     #[error("Downloader not available: {0}")]
     DownloaderUnavailable(String),
 
@@ -28,9 +42,15 @@ pub enum DownloadError {
     #[error("Database error: {0}")]
     DatabaseError(String),
 
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
     #[error("General error: {0}")]
     General(String),
 
+    #[error("Configuration error: {0}")]
+    Config(String),
+
     // New errors for duplicate detection
     #[error("Duplicate detection failed: {0}")]
     DuplicateDetectionError(String),
@@ -40,4 +60,82 @@ pub enum DownloadError {
 
     #[error("Policy violation: {reason}, found duplicate task {task_id}")]
     PolicyViolation { task_id: TaskId, reason: String },
+
+    // Storage errors
+    #[error("Insufficient disk space for {}: {required} bytes required, {available} bytes available", .path.display())]
+    InsufficientDiskSpace { path: PathBuf, required: u64, available: u64 },
+
+    #[error("Storage quota for {} exceeded: {required} bytes required, {remaining} bytes remaining", .directory.display())]
+    QuotaExceeded { directory: PathBuf, required: u64, remaining: u64 },
+
+    #[error("File of {size} bytes exceeds the maximum size of {limit} bytes")]
+    FileTooLarge { size: u64, limit: u64 },
+
+    #[error("Content not allowed: {0}")]
+    ContentNotAllowed(String),
+
+    #[error("Target file {} already exists", .0.display())]
+    FileExists(PathBuf),
+
+    #[error("Target path {} is already used by another download", .path.display())]
+    TargetPathConflict { path: PathBuf, task_id: Option<TaskId> },
+
+    #[error("The file of task {0} was already taken")]
+    FileTaken(TaskId),
+
+    // Backend and network errors
+    #[error("aria2 RPC error: {0}")]
+    Aria2Rpc(String),
+
+    #[error("Backend {operation} timed out after {timeout:?}")]
+    BackendTimeout { operation: String, timeout: Duration },
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    // Integrity errors
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Checksum mismatch in piece {piece}: expected {expected}, got {actual}")]
+    PieceChecksumMismatch { piece: usize, expected: String, actual: String },
+
+    // Post-processing errors
+    #[error("Post-processing hook {hook} failed for task {task_id}: {reason}")]
+    PostProcessingFailed { task_id: TaskId, hook: String, reason: String },
+
+    #[error("Archive extraction failed: {0}")]
+    ExtractionFailed(String),
+
+    // Completion errors
+    #[error("Download {task_id} failed: {reason}")]
+    DownloadFailed { task_id: TaskId, reason: String },
+
+    #[error("Timed out after {timeout:?} waiting for task {task_id}")]
+    WaitTimeout { task_id: TaskId, timeout: Duration },
+
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+}
+
+impl From<anyhow::Error> for DownloadError {
+    fn from(error: anyhow::Error) -> Self {
+        // Keep typed errors that were wrapped on the way up
+        match error.downcast::<DownloadError>() {
+            Ok(error) => error,
+            Err(error) => DownloadError::General(format!("{:#}", error)),
+        }
+    }
+}
+
+impl From<sqlx::Error> for DownloadError {
+    fn from(error: sqlx::Error) -> Self {
+        DownloadError::DatabaseError(error.to_string())
+    }
+}
+
+impl From<reqwest::Error> for DownloadError {
+    fn from(error: reqwest::Error) -> Self {
+        DownloadError::Network(error.to_string())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,206 @@
+//! Task groups
+//!
+//! A group ties the tasks of a multi-file artifact, such as the shards of a
+//! model or the files of a repository, into one download whose progress and
+//! status are combined from its members and which can be paused, resumed or
+//! cancelled as a whole. Groups and their members are persisted in SQLite so
+//! they survive restarts. A task belongs to at most one group.
+
+pub mod store;
+
+pub use store::{GroupId, GroupStore, TaskGroup};
+
+use crate::error::DownloadError;
+use crate::traits::DownloadManager;
+use crate::types::{DownloadProgress, DownloadStatus, TaskId};
+use crate::Result;
+use std::sync::Arc;
+
+/// Creates task groups and applies operations to all tasks of a group
+pub struct TaskGroups {
+    manager: Arc<dyn DownloadManager>,
+    store: GroupStore,
+}
+
+impl TaskGroups {
+    /// Create groups of tasks managed by `manager`
+    pub fn new(manager: Arc<dyn DownloadManager>, store: GroupStore) -> Self {
+        Self { manager, store }
+    }
+
+    /// Get the manager running the grouped tasks
+    pub fn manager(&self) -> Arc<dyn DownloadManager> {
+        self.manager.clone()
+    }
+
+    /// Create an empty group
+    pub async fn create_group(&self, name: &str) -> Result<GroupId> {
+        let id = self.store.insert(name).await?;
+        log::info!("Created task group {} ({})", id, name);
+        Ok(id)
+    }
+
+    /// Get a group with its members
+    pub async fn group(&self, id: GroupId) -> Result<TaskGroup> {
+        self.store.get(id).await?
+            .ok_or_else(|| group_not_found(id))
+    }
+
+    /// Find the oldest group with the given name
+    pub async fn find_group(&self, name: &str) -> Result<Option<TaskGroup>> {
+        self.store.find_by_name(name).await
+    }
+
+    /// List all groups in creation order
+    pub async fn list_groups(&self) -> Result<Vec<TaskGroup>> {
+        self.store.list().await
+    }
+
+    /// Get the group a task belongs to
+    pub async fn group_of(&self, task_id: TaskId) -> Result<Option<GroupId>> {
+        self.store.group_of(&task_id).await
+    }
+
+    /// Add an existing task to a group
+    ///
+    /// Adding a task to its own group again does nothing; a task of another
+    /// group must be removed from it first.
+    pub async fn add_task(&self, id: GroupId, task_id: TaskId) -> Result<()> {
+        self.group(id).await?;
+        self.manager.get_task(task_id).await?;
+
+        match self.store.group_of(&task_id).await? {
+            Some(current) if current != id => Err(DownloadError::General(format!(
+                "Task {} already belongs to {}", task_id, current
+            ))),
+            _ => self.store.add_member(id, &task_id).await,
+        }
+    }
+
+    /// Remove a task from its group, keeping the task
+    pub async fn remove_task(&self, task_id: TaskId) -> Result<bool> {
+        self.store.remove_member(&task_id).await
+    }
+
+    /// Remove a group, keeping its tasks
+    pub async fn delete_group(&self, id: GroupId) -> Result<()> {
+        if !self.store.delete(id).await? {
+            return Err(group_not_found(id));
+        }
+        Ok(())
+    }
+
+    /// Get the combined progress of the tasks of a group, see [`aggregate_progress`]
+    pub async fn progress(&self, id: GroupId) -> Result<DownloadProgress> {
+        let mut progress = Vec::new();
+        for task_id in self.live_tasks(id).await? {
+            progress.push(self.manager.get_progress(task_id).await?);
+        }
+        Ok(aggregate_progress(&progress))
+    }
+
+    /// Get the combined status of the tasks of a group, see [`aggregate_status`]
+    pub async fn status(&self, id: GroupId) -> Result<DownloadStatus> {
+        let mut statuses = Vec::new();
+        for task_id in self.live_tasks(id).await? {
+            statuses.push(self.manager.get_task(task_id).await?.status);
+        }
+        Ok(aggregate_status(&statuses))
+    }
+
+    /// Pause every task of a group that can be paused and return their IDs
+    pub async fn pause_group(&self, id: GroupId) -> Result<Vec<TaskId>> {
+        let mut paused = Vec::new();
+        for task_id in self.live_tasks(id).await? {
+            if self.manager.get_task(task_id).await?.status.can_pause() {
+                self.manager.pause_download(task_id).await?;
+                paused.push(task_id);
+            }
+        }
+        Ok(paused)
+    }
+
+    /// Resume every paused task of a group and return their IDs
+    pub async fn resume_group(&self, id: GroupId) -> Result<Vec<TaskId>> {
+        let mut resumed = Vec::new();
+        for task_id in self.live_tasks(id).await? {
+            if self.manager.get_task(task_id).await?.status.can_resume() {
+                self.manager.resume_download(task_id).await?;
+                resumed.push(task_id);
+            }
+        }
+        Ok(resumed)
+    }
+
+    /// Cancel every task of a group, remove the group and return the cancelled IDs
+    pub async fn cancel_group(&self, id: GroupId) -> Result<Vec<TaskId>> {
+        let mut cancelled = Vec::new();
+        for task_id in self.live_tasks(id).await? {
+            self.manager.cancel_download(task_id).await?;
+            cancelled.push(task_id);
+        }
+
+        self.store.delete(id).await?;
+        log::info!("Cancelled task group {} with {} tasks", id, cancelled.len());
+        Ok(cancelled)
+    }
+
+    /// Get the members of a group that still exist, dropping those removed from the manager
+    async fn live_tasks(&self, id: GroupId) -> Result<Vec<TaskId>> {
+        let mut live = Vec::new();
+        for task_id in self.group(id).await?.task_ids {
+            match self.manager.get_task(task_id).await {
+                Ok(_) => live.push(task_id),
+                Err(DownloadError::TaskNotFound(_)) => {
+                    log::debug!("Dropping removed task {} from {}", task_id, id);
+                    self.store.remove_member(&task_id).await?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(live)
+    }
+}
+
+fn group_not_found(id: GroupId) -> DownloadError {
+    DownloadError::General(format!("Task group {} not found", id))
+}
+
+/// Combine the progress of several tasks
+///
+/// The total is only known when every task knows its own.
+pub fn aggregate_progress(progress: &[DownloadProgress]) -> DownloadProgress {
+    let downloaded_bytes = progress.iter().map(|p| p.downloaded_bytes).sum();
+    let total_bytes: Option<u64> = progress.iter().map(|p| p.total_bytes).sum();
+    let speed_bps = progress.iter().map(|p| p.speed_bps).sum();
+
+    DownloadProgress {
+        downloaded_bytes,
+        total_bytes,
+        speed_bps,
+        eta_seconds: total_bytes
+            .filter(|_| speed_bps > 0)
+            .map(|total| total.saturating_sub(downloaded_bytes) / speed_bps),
+    }
+}
+
+/// Combine the statuses of several tasks
+///
+/// Any failure fails the whole, which is complete once every task is. Until
+/// then it is downloading while any task is, waiting while any task waits and
+/// paused otherwise. No tasks count as completed.
+pub fn aggregate_status(statuses: &[DownloadStatus]) -> DownloadStatus {
+    if let Some(failed) = statuses.iter().find(|status| matches!(status, DownloadStatus::Failed(_))) {
+        return failed.clone();
+    }
+    if statuses.iter().all(|status| *status == DownloadStatus::Completed) {
+        return DownloadStatus::Completed;
+    }
+    if statuses.contains(&DownloadStatus::Downloading) {
+        return DownloadStatus::Downloading;
+    }
+    if statuses.contains(&DownloadStatus::Waiting) {
+        return DownloadStatus::Waiting;
+    }
+    DownloadStatus::Paused
+}
\ No newline at end of file
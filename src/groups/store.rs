@@ -0,0 +1,173 @@
+//! Persistent storage for task groups
+
+use crate::types::TaskId;
+use crate::error::DownloadError;
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, encode_task_id, decode_value, db_error, unix_now};
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+use std::path::Path;
+
+/// Identifier of a task group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GroupId(pub i64);
+
+impl std::fmt::Display for GroupId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "group-{}", self.0)
+    }
+}
+
+/// A named set of tasks handled as one download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskGroup {
+    pub id: GroupId,
+    pub name: String,
+    /// Member tasks in the order they were added
+    pub task_ids: Vec<TaskId>,
+}
+
+/// SQLite-backed store of task groups and their members
+#[derive(Clone)]
+pub struct GroupStore {
+    pool: SqlitePool,
+}
+
+impl GroupStore {
+    /// Open (or create) a store in the given SQLite file
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Ok(Self { pool: open_pool(path).await? })
+    }
+
+    /// Create a store that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Ok(Self { pool: in_memory_pool().await? })
+    }
+
+    /// Store a new, empty group
+    pub async fn insert(&self, name: &str) -> Result<GroupId, DownloadError> {
+        let result = sqlx::query("INSERT INTO task_groups (name, created_at) VALUES (?, ?)")
+            .bind(name)
+            .bind(unix_now())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(GroupId(result.last_insert_rowid()))
+    }
+
+    /// Load a group with its members
+    pub async fn get(&self, id: GroupId) -> Result<Option<TaskGroup>, DownloadError> {
+        let row = sqlx::query("SELECT id, name FROM task_groups WHERE id = ?")
+            .bind(id.0)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        match row {
+            Some(row) => Ok(Some(self.with_members(&row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Find the oldest group with the given name
+    pub async fn find_by_name(&self, name: &str) -> Result<Option<TaskGroup>, DownloadError> {
+        let row = sqlx::query("SELECT id, name FROM task_groups WHERE name = ? ORDER BY id LIMIT 1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        match row {
+            Some(row) => Ok(Some(self.with_members(&row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List all groups in creation order
+    pub async fn list(&self) -> Result<Vec<TaskGroup>, DownloadError> {
+        let rows = sqlx::query("SELECT id, name FROM task_groups ORDER BY id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut groups = Vec::with_capacity(rows.len());
+        for row in &rows {
+            groups.push(self.with_members(row).await?);
+        }
+        Ok(groups)
+    }
+
+    /// Get the group a task belongs to
+    pub async fn group_of(&self, task_id: &TaskId) -> Result<Option<GroupId>, DownloadError> {
+        let row = sqlx::query("SELECT group_id FROM task_group_members WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(row.map(|row| GroupId(row.get("group_id"))))
+    }
+
+    /// Add a task to a group, doing nothing if it is already a member
+    pub async fn add_member(&self, id: GroupId, task_id: &TaskId) -> Result<(), DownloadError> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO task_group_members (group_id, task_id, position)
+             SELECT ?, ?, COALESCE(MAX(position) + 1, 0) FROM task_group_members WHERE group_id = ?"
+        )
+        .bind(id.0)
+        .bind(encode_task_id(task_id)?)
+        .bind(id.0)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Remove a task from whichever group it belongs to, returning whether it was a member
+    pub async fn remove_member(&self, task_id: &TaskId) -> Result<bool, DownloadError> {
+        let result = sqlx::query("DELETE FROM task_group_members WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove a group and its memberships, returning whether it existed
+    pub async fn delete(&self, id: GroupId) -> Result<bool, DownloadError> {
+        sqlx::query("DELETE FROM task_group_members WHERE group_id = ?")
+            .bind(id.0)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let result = sqlx::query("DELETE FROM task_groups WHERE id = ?")
+            .bind(id.0)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn with_members(&self, row: &SqliteRow) -> Result<TaskGroup, DownloadError> {
+        let id = GroupId(row.get("id"));
+        let rows = sqlx::query("SELECT task_id FROM task_group_members WHERE group_id = ? ORDER BY position")
+            .bind(id.0)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let task_ids = rows.into_iter()
+            .map(|row| decode_value(row.get::<String, _>("task_id")))
+            .collect::<Result<_, _>>()?;
+
+        Ok(TaskGroup {
+            id,
+            name: row.get("name"),
+            task_ids,
+        })
+    }
+}
\ No newline at end of file
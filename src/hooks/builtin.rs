@@ -0,0 +1,125 @@
+//! Built-in post-download hooks
+
+use super::{HookContext, PostDownloadHook};
+use crate::Result;
+use crate::utils::paths::move_file;
+use async_trait::async_trait;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+/// Move the file into a directory, keeping its file name
+#[derive(Debug, Clone)]
+pub struct MoveToDirectory {
+    directory: PathBuf,
+}
+
+impl MoveToDirectory {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self { directory: directory.into() }
+    }
+
+    /// Get the directory files are moved into
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for MoveToDirectory {
+    fn name(&self) -> &str {
+        "move"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        let file_name = context.path.file_name()
+            .ok_or_else(|| crate::DownloadError::InvalidPath(context.path.display().to_string()))?;
+        let destination = self.directory.join(file_name);
+
+        tokio::fs::create_dir_all(&self.directory).await?;
+
+        move_file(&context.path, &destination).await?;
+
+        log::info!("Moved {} to {}", context.path.display(), destination.display());
+        context.path = destination;
+        Ok(())
+    }
+}
+
+/// Set the permissions of the file
+///
+/// `mode` is a Unix permission mode such as `0o644`. Other platforms only
+/// honour the write bits, making the file read-only when none are set.
+#[derive(Debug, Clone, Copy)]
+pub struct SetPermissions {
+    mode: u32,
+}
+
+impl SetPermissions {
+    pub fn new(mode: u32) -> Self {
+        Self { mode }
+    }
+
+    /// Get the permission mode applied to files
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for SetPermissions {
+    fn name(&self) -> &str {
+        "chmod"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        #[cfg(unix)]
+        let permissions = {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::Permissions::from_mode(self.mode)
+        };
+        #[cfg(not(unix))]
+        let permissions = {
+            let mut permissions = tokio::fs::metadata(&context.path).await?.permissions();
+            permissions.set_readonly(self.mode & 0o222 == 0);
+            permissions
+        };
+
+        tokio::fs::set_permissions(&context.path, permissions).await?;
+        Ok(())
+    }
+}
+
+/// Run a closure as a hook
+///
+/// The closure receives the current context and returns it, with `path`
+/// changed if it moved the file.
+pub struct FnHook<F> {
+    name: String,
+    action: F,
+}
+
+impl<F, Fut> FnHook<F>
+where
+    F: Fn(HookContext) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<HookContext>> + Send,
+{
+    pub fn new<S: Into<String>>(name: S, action: F) -> Self {
+        Self { name: name.into(), action }
+    }
+}
+
+#[async_trait]
+impl<F, Fut> PostDownloadHook for FnHook<F>
+where
+    F: Fn(HookContext) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<HookContext>> + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        *context = (self.action)(context.clone()).await?;
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+//! Checksum of a completed download
+//!
+//! Hashes the whole file for engines that don't check the `checksum` option
+//! of a download themselves.
+
+use super::{HookContext, PostDownloadHook};
+use crate::error::DownloadError;
+use crate::models::Checksum;
+use crate::services::piece_verifier::hash_file;
+use crate::Result;
+use async_trait::async_trait;
+
+/// Verify a completed download against its expected checksum
+pub struct VerifyChecksum {
+    checksum: Checksum,
+}
+
+impl VerifyChecksum {
+    pub fn new(checksum: Checksum) -> Self {
+        Self { checksum }
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for VerifyChecksum {
+    fn name(&self) -> &str {
+        "verify-checksum"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        let path = context.path.clone();
+        let algorithm = self.checksum.algorithm;
+        let actual = tokio::task::spawn_blocking(move || hash_file(&path, algorithm))
+            .await
+            .map_err(|e| DownloadError::General(format!("Checksum hashing failed: {}", e)))??;
+
+        if !actual.eq_ignore_ascii_case(&self.checksum.value) {
+            return Err(DownloadError::ChecksumMismatch {
+                expected: self.checksum.value.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,255 @@
+//! Copies of a completed download
+//!
+//! Writes the downloaded file into further directories, e.g. a local cache
+//! and a shared network mount, so an artifact needed in several places is
+//! fetched only once. Each copy is written under a temporary name and renamed
+//! once complete, so a destination never holds a partial file under the
+//! final name.
+
+use super::{HookContext, PostDownloadHook};
+use crate::traits::DownloadEventHandler;
+use crate::error::DownloadError;
+use crate::types::TaskId;
+use crate::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::RwLock;
+
+/// Bytes read and written at a time
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+/// Bytes copied between two progress reports
+const REPORT_INTERVAL: u64 = 16 * 1024 * 1024;
+
+/// Status of one copy of a download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CopyStatus {
+    /// Waiting for the copies before it
+    Pending,
+    /// Being written
+    Copying,
+    /// Written in full under its final name
+    Completed,
+    /// Could not be written, the destination keeps no partial file
+    Failed(String),
+}
+
+/// Progress and status of one copy of a download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyState {
+    /// Path the copy is written to
+    pub destination: PathBuf,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub status: CopyStatus,
+}
+
+/// Copies made of each task's download
+#[derive(Default)]
+pub struct CopyTracker {
+    copies: RwLock<HashMap<TaskId, Vec<CopyState>>>,
+}
+
+impl CopyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the copies of a task, in the order they are made
+    pub async fn copies(&self, task_id: TaskId) -> Vec<CopyState> {
+        self.copies.read().await.get(&task_id).cloned().unwrap_or_default()
+    }
+
+    /// Forget the copies of a task; the files stay where they are
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.copies.write().await.remove(&task_id);
+    }
+
+    /// Start tracking the copies of a task, keeping those already completed
+    async fn start(&self, task_id: TaskId, destinations: &[PathBuf], total_bytes: u64) {
+        let mut copies = self.copies.write().await;
+        let previous = copies.remove(&task_id).unwrap_or_default();
+        let states = destinations.iter()
+            .map(|destination| {
+                previous.iter()
+                    .find(|state| state.destination == *destination
+                        && state.status == CopyStatus::Completed
+                        && state.total_bytes == total_bytes)
+                    .cloned()
+                    .unwrap_or_else(|| CopyState {
+                        destination: destination.clone(),
+                        copied_bytes: 0,
+                        total_bytes,
+                        status: CopyStatus::Pending,
+                    })
+            })
+            .collect();
+        copies.insert(task_id, states);
+    }
+
+    /// Get the status of one copy of a task
+    async fn status(&self, task_id: TaskId, destination: &Path) -> Option<CopyStatus> {
+        self.copies.read().await.get(&task_id)?
+            .iter()
+            .find(|state| state.destination == destination)
+            .map(|state| state.status.clone())
+    }
+
+    async fn update(&self, task_id: TaskId, destination: &Path, copied_bytes: u64, status: CopyStatus) {
+        let mut copies = self.copies.write().await;
+        let state = copies.get_mut(&task_id)
+            .and_then(|states| states.iter_mut().find(|state| state.destination == destination));
+        if let Some(state) = state {
+            state.copied_bytes = copied_bytes;
+            state.status = status;
+        }
+    }
+}
+
+/// Hook copying a downloaded file into further directories, keeping its file name
+///
+/// Every directory is tried even when an earlier copy failed; the hook fails
+/// if any copy did. When post-processing is retried, copies that completed
+/// before are not written again.
+#[derive(Clone)]
+pub struct CopyToDirectories {
+    directories: Vec<PathBuf>,
+    tracker: Arc<CopyTracker>,
+    events: Option<Arc<dyn DownloadEventHandler>>,
+}
+
+impl CopyToDirectories {
+    pub fn new<I, P>(directories: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        Self {
+            directories: directories.into_iter().map(Into::into).collect(),
+            tracker: Arc::new(CopyTracker::new()),
+            events: None,
+        }
+    }
+
+    /// Record the progress and status of copies in `tracker`
+    pub fn with_tracker(mut self, tracker: Arc<CopyTracker>) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
+    /// Report copy progress to `handler`
+    pub fn with_events(mut self, handler: Arc<dyn DownloadEventHandler>) -> Self {
+        self.events = Some(handler);
+        self
+    }
+
+    /// Get the directories files are copied into
+    pub fn directories(&self) -> &[PathBuf] {
+        &self.directories
+    }
+
+    /// Get the tracker recording the copies
+    pub fn tracker(&self) -> Arc<CopyTracker> {
+        self.tracker.clone()
+    }
+
+    /// Copy `source` to `destination` through a temporary file
+    async fn copy(&self, task_id: TaskId, source: &Path, destination: &Path, total_bytes: u64) -> Result<()> {
+        if let Some(parent) = destination.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let partial = partial_copy_path(destination);
+        let result = async {
+            let mut reader = tokio::fs::File::open(source).await?;
+            let mut writer = tokio::fs::File::create(&partial).await?;
+            let mut buffer = vec![0; COPY_BUFFER_SIZE];
+            let mut copied = 0;
+            let mut reported = 0;
+            loop {
+                let read = reader.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                writer.write_all(&buffer[..read]).await?;
+                copied += read as u64;
+                if copied - reported >= REPORT_INTERVAL && copied < total_bytes {
+                    self.report(task_id, destination, copied, total_bytes).await;
+                    reported = copied;
+                }
+            }
+            writer.sync_all().await?;
+            drop(writer);
+
+            tokio::fs::rename(&partial, destination).await?;
+            self.report(task_id, destination, copied, total_bytes).await;
+            Ok(())
+        }.await;
+
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&partial).await;
+        }
+        result
+    }
+
+    async fn report(&self, task_id: TaskId, destination: &Path, copied_bytes: u64, total_bytes: u64) {
+        self.tracker.update(task_id, destination, copied_bytes, CopyStatus::Copying).await;
+        if let Some(handler) = &self.events {
+            handler.on_copy_progress(task_id, destination.to_path_buf(), copied_bytes, total_bytes).await;
+        }
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for CopyToDirectories {
+    fn name(&self) -> &str {
+        "copy"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        let file_name = context.path.file_name()
+            .ok_or_else(|| DownloadError::InvalidPath(context.path.display().to_string()))?;
+        let total_bytes = tokio::fs::metadata(&context.path).await?.len();
+        let destinations: Vec<PathBuf> = self.directories.iter()
+            .map(|directory| directory.join(file_name))
+            .collect();
+        self.tracker.start(context.task_id, &destinations, total_bytes).await;
+
+        let mut failures = Vec::new();
+        for destination in &destinations {
+            let copied = self.tracker.status(context.task_id, destination).await == Some(CopyStatus::Completed)
+                && tokio::fs::metadata(destination).await.is_ok_and(|metadata| metadata.len() == total_bytes);
+            if copied {
+                continue;
+            }
+
+            self.tracker.update(context.task_id, destination, 0, CopyStatus::Copying).await;
+            match self.copy(context.task_id, &context.path, destination, total_bytes).await {
+                Ok(()) => {
+                    log::info!("Copied {} to {}", context.path.display(), destination.display());
+                    self.tracker.update(context.task_id, destination, total_bytes, CopyStatus::Completed).await;
+                }
+                Err(e) => {
+                    log::warn!("Failed to copy {} to {}: {}", context.path.display(), destination.display(), e);
+                    self.tracker.update(context.task_id, destination, 0, CopyStatus::Failed(e.to_string())).await;
+                    failures.push(format!("{}: {}", destination.display(), e));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(DownloadError::General(format!("Failed to copy to {}", failures.join(", "))))
+        }
+    }
+}
+
+/// Get the temporary path a copy is written to before it is complete
+fn partial_copy_path(destination: &Path) -> PathBuf {
+    let mut name = destination.file_name().unwrap_or_default().to_os_string();
+    name.push(".copying");
+    destination.with_file_name(name)
+}
\ No newline at end of file
@@ -0,0 +1,254 @@
+//! Archive extraction
+//!
+//! Unpacks zip, tar.gz and 7z archives after their download completed. The
+//! archive format is detected from the file name; entries that would land
+//! outside the destination directory are rejected.
+
+use super::{HookContext, PostDownloadHook};
+use crate::traits::DownloadEventHandler;
+use crate::error::DownloadError;
+use crate::utils::paths::enclosed_path;
+use crate::Result;
+use async_trait::async_trait;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Archive formats that can be extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+    SevenZ,
+}
+
+impl ArchiveFormat {
+    /// Detect the format of an archive from its file name
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".7z") {
+            Some(ArchiveFormat::SevenZ)
+        } else {
+            None
+        }
+    }
+
+    /// Get the extensions of this format, longest first
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ArchiveFormat::Zip => &[".zip"],
+            ArchiveFormat::TarGz => &[".tar.gz", ".tgz"],
+            ArchiveFormat::SevenZ => &[".7z"],
+        }
+    }
+}
+
+/// Get the directory an archive is extracted into by default
+///
+/// This is a directory next to the archive named after it without its
+/// extension, e.g. `data/model` for `data/model.tar.gz`.
+pub fn default_extract_dir(archive: &Path, format: ArchiveFormat) -> PathBuf {
+    let name = archive.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let stem = format.extensions().iter()
+        .find_map(|extension| {
+            let split = name.len().checked_sub(extension.len())?;
+            let (stem, suffix) = name.split_at(split);
+            suffix.eq_ignore_ascii_case(extension).then_some(stem)
+        })
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(&name);
+
+    archive.with_file_name(stem)
+}
+
+/// Extract an archive into `destination` and return the number of bytes written
+///
+/// `progress` is called after every entry with the bytes extracted so far and
+/// the total, when the format records it up front. This blocks and should be
+/// run on a blocking thread.
+pub fn extract_archive(
+    archive: &Path,
+    format: ArchiveFormat,
+    destination: &Path,
+    mut progress: impl FnMut(u64, Option<u64>),
+) -> Result<u64> {
+    std::fs::create_dir_all(destination)?;
+
+    match format {
+        ArchiveFormat::Zip => extract_zip(archive, destination, &mut progress),
+        ArchiveFormat::TarGz => extract_tar_gz(archive, destination, &mut progress),
+        ArchiveFormat::SevenZ => extract_7z(archive, destination, &mut progress),
+    }
+}
+
+fn extract_zip(archive: &Path, destination: &Path, progress: &mut impl FnMut(u64, Option<u64>)) -> Result<u64> {
+    let mut zip = zip::ZipArchive::new(File::open(archive)?)
+        .map_err(|e| DownloadError::ExtractionFailed(e.to_string()))?;
+
+    let mut total = 0;
+    for index in 0..zip.len() {
+        let entry = zip.by_index(index)
+            .map_err(|e| DownloadError::ExtractionFailed(e.to_string()))?;
+        total += entry.size();
+    }
+
+    let mut extracted = 0;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)
+            .map_err(|e| DownloadError::ExtractionFailed(e.to_string()))?;
+        let relative = entry.enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| escaping_entry(entry.name()))?;
+        let output = destination.join(relative);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&output)?;
+            continue;
+        }
+
+        if let Some(parent) = output.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        extracted += std::io::copy(&mut entry, &mut File::create(&output)?)?;
+        progress(extracted, Some(total));
+    }
+
+    Ok(extracted)
+}
+
+fn extract_tar_gz(archive: &Path, destination: &Path, progress: &mut impl FnMut(u64, Option<u64>)) -> Result<u64> {
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(File::open(archive)?));
+
+    let mut extracted = 0;
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let size = entry.size();
+        if !entry.unpack_in(destination)? {
+            return Err(escaping_entry(&entry.path()?.to_string_lossy()));
+        }
+
+        extracted += size;
+        progress(extracted, None);
+    }
+
+    Ok(extracted)
+}
+
+fn extract_7z(archive: &Path, destination: &Path, progress: &mut impl FnMut(u64, Option<u64>)) -> Result<u64> {
+    let mut reader = sevenz_rust::SevenZReader::open(archive, sevenz_rust::Password::empty())
+        .map_err(|e| DownloadError::ExtractionFailed(e.to_string()))?;
+
+    // The reader only takes its own error type, so keep ours and stop at the first one
+    let mut failure = None;
+    let mut extracted = 0;
+    reader.for_each_entries(|entry, data| {
+        let result = (|| -> Result<()> {
+            let relative = enclosed_path(entry.name()).ok_or_else(|| escaping_entry(entry.name()))?;
+            let output = destination.join(relative);
+
+            if entry.is_directory() {
+                std::fs::create_dir_all(&output)?;
+                return Ok(());
+            }
+
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            extracted += std::io::copy(data, &mut File::create(&output)?)?;
+            progress(extracted, None);
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                failure = Some(e);
+                Ok(false)
+            }
+        }
+    }).map_err(|e| DownloadError::ExtractionFailed(e.to_string()))?;
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(extracted),
+    }
+}
+
+fn escaping_entry(name: &str) -> DownloadError {
+    DownloadError::ExtractionFailed(format!("Entry {} would be extracted outside the destination", name))
+}
+
+/// Hook extracting a downloaded archive
+///
+/// Files that are not a supported archive are left alone. The archive itself
+/// is kept, so hooks after this one still see its path.
+#[derive(Clone, Default)]
+pub struct ExtractArchive {
+    destination: Option<PathBuf>,
+    events: Option<Arc<dyn DownloadEventHandler>>,
+}
+
+impl ExtractArchive {
+    /// Extract into the default directory next to the archive
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extract into `destination` instead of the default directory
+    pub fn destination<P: Into<PathBuf>>(mut self, destination: P) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    /// Report extraction progress to `handler`
+    pub fn with_events(mut self, handler: Arc<dyn DownloadEventHandler>) -> Self {
+        self.events = Some(handler);
+        self
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for ExtractArchive {
+    fn name(&self) -> &str {
+        "extract"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        let Some(format) = ArchiveFormat::detect(&context.path) else {
+            log::info!("{} is not a supported archive, skipping extraction", context.path.display());
+            return Ok(());
+        };
+
+        let destination = self.destination.clone()
+            .unwrap_or_else(|| default_extract_dir(&context.path, format));
+        log::info!("Extracting {} into {}", context.path.display(), destination.display());
+
+        // Extraction blocks, so it reports progress back over a channel
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let archive = context.path.clone();
+        let target = destination.clone();
+        let extraction = tokio::task::spawn_blocking(move || {
+            extract_archive(&archive, format, &target, |extracted, total| {
+                let _ = sender.send((extracted, total));
+            })
+        });
+
+        while let Some((extracted, total)) = receiver.recv().await {
+            if let Some(handler) = &self.events {
+                handler.on_extraction_progress(context.task_id, extracted, total).await;
+            }
+        }
+
+        let extracted = extraction.await
+            .map_err(|e| DownloadError::ExtractionFailed(e.to_string()))??;
+        log::info!("Extracted {} bytes from {}", extracted, context.path.display());
+        Ok(())
+    }
+}
\ No newline at end of file
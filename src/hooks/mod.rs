@@ -0,0 +1,54 @@
+//! Post-download hooks
+//!
+//! Hooks are async actions run after a download completes, such as moving the
+//! file to its final directory or changing its permissions. Global hooks run
+//! first, then the hooks registered for the task, each one after the other.
+//! A failing hook stops the pipeline and leaves the task in
+//! [`TaskStatus::PostProcessingFailed`](crate::models::TaskStatus::PostProcessingFailed)
+//! until post-processing is retried, without downloading the file again.
+
+pub mod builtin;
+pub mod checksum;
+pub mod copy;
+pub mod extract;
+pub mod pieces;
+pub mod pipeline;
+pub mod scan;
+
+pub use builtin::{MoveToDirectory, SetPermissions, FnHook};
+pub use checksum::VerifyChecksum;
+pub use copy::{CopyToDirectories, CopyTracker, CopyState, CopyStatus};
+pub use extract::{ArchiveFormat, ExtractArchive};
+pub use pieces::VerifyPieces;
+pub use pipeline::{HookPipeline, PostProcessingState};
+pub use scan::{ScanHook, ScanVerdict, CommandScanner, SCAN_REJECTED};
+
+use crate::types::TaskId;
+use crate::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// The downloaded file a hook works on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookContext {
+    pub task_id: TaskId,
+    pub url: String,
+    /// Current location of the file, updated by hooks that move it
+    pub path: PathBuf,
+}
+
+impl HookContext {
+    pub fn new(task_id: TaskId, url: String, path: PathBuf) -> Self {
+        Self { task_id, url, path }
+    }
+}
+
+/// An action run on a completed download
+#[async_trait]
+pub trait PostDownloadHook: Send + Sync {
+    /// Name reported when the hook fails
+    fn name(&self) -> &str;
+
+    /// Run the action, setting `context.path` if the file was moved
+    async fn run(&self, context: &mut HookContext) -> Result<()>;
+}
\ No newline at end of file
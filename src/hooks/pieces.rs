@@ -0,0 +1,32 @@
+//! Piece checksums of a completed download
+//!
+//! Checks the pieces of the file that were not checked while it downloaded,
+//! which are all of them for downloads written in several segments.
+
+use super::{HookContext, PostDownloadHook};
+use crate::services::PieceVerifier;
+use crate::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Verify the remaining pieces of a download with piece checksums
+pub struct VerifyPieces {
+    verifier: Arc<PieceVerifier>,
+}
+
+impl VerifyPieces {
+    pub fn new(verifier: Arc<PieceVerifier>) -> Self {
+        Self { verifier }
+    }
+}
+
+#[async_trait]
+impl PostDownloadHook for VerifyPieces {
+    fn name(&self) -> &str {
+        "verify-pieces"
+    }
+
+    async fn run(&self, context: &mut HookContext) -> Result<()> {
+        self.verifier.finish(context.task_id, &context.path).await
+    }
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! Ordered execution of post-download hooks
+//!
+//! The pipeline snapshots the hooks of a task when its download completes,
+//! so hooks registered later only apply to later downloads. A run that fails
+//! remembers the failing hook and picks up there when it is retried.
+
+use super::{HookContext, PostDownloadHook};
+use crate::types::{TaskId, DownloadStatus};
+use crate::error::DownloadError;
+use crate::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Post-processing state of a completed download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostProcessingState {
+    /// Hooks are waiting to run
+    Pending,
+    /// Hooks are running
+    Running,
+    /// Every hook succeeded, leaving the file at `path`
+    Succeeded { path: PathBuf },
+    /// `hook` failed, the hooks after it have not run
+    Failed { hook: String, error: String },
+}
+
+/// Hooks of one completed download and how far they got
+struct HookRun {
+    hooks: Vec<Arc<dyn PostDownloadHook>>,
+    next: usize,
+    context: HookContext,
+    state: PostProcessingState,
+}
+
+/// Global and per-task hooks and the post-processing runs of completed downloads
+#[derive(Default)]
+pub struct HookPipeline {
+    global: RwLock<Vec<Arc<dyn PostDownloadHook>>>,
+    task_hooks: RwLock<HashMap<TaskId, Vec<Arc<dyn PostDownloadHook>>>>,
+    runs: Mutex<HashMap<TaskId, HookRun>>,
+}
+
+impl HookPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a hook run after every download
+    pub async fn add_global_hook(&self, hook: Arc<dyn PostDownloadHook>) {
+        self.global.write().await.push(hook);
+    }
+
+    /// Add a hook run after the download of one task, after the global hooks
+    pub async fn add_task_hook(&self, task_id: TaskId, hook: Arc<dyn PostDownloadHook>) {
+        self.task_hooks.write().await.entry(task_id).or_default().push(hook);
+    }
+
+    /// Get the hooks that would run for a task, in order
+    pub async fn hooks_for(&self, task_id: TaskId) -> Vec<Arc<dyn PostDownloadHook>> {
+        let mut hooks = self.global.read().await.clone();
+        if let Some(task_hooks) = self.task_hooks.read().await.get(&task_id) {
+            hooks.extend(task_hooks.iter().cloned());
+        }
+        hooks
+    }
+
+    /// Start post-processing a completed download
+    ///
+    /// Returns `false` if the task was already prepared, so callers that see
+    /// the completion repeatedly run its hooks only once.
+    pub async fn prepare(&self, context: HookContext) -> bool {
+        let task_id = context.task_id;
+        let hooks = self.hooks_for(task_id).await;
+
+        let mut runs = self.runs.lock().await;
+        if runs.contains_key(&task_id) {
+            return false;
+        }
+
+        runs.insert(task_id, HookRun {
+            hooks,
+            next: 0,
+            context,
+            state: PostProcessingState::Pending,
+        });
+        true
+    }
+
+    /// Run the remaining hooks of a prepared task and return the final file path
+    ///
+    /// A failing hook fails with [`DownloadError::PostProcessingFailed`].
+    pub async fn execute(&self, task_id: TaskId) -> Result<PathBuf> {
+        {
+            let mut runs = self.runs.lock().await;
+            let run = runs.get_mut(&task_id).ok_or(DownloadError::TaskNotFound(task_id))?;
+            if run.state != PostProcessingState::Pending {
+                return Err(DownloadError::InvalidTaskState {
+                    task_id,
+                    operation: "run post-processing of",
+                    status: DownloadStatus::Completed,
+                });
+            }
+            run.state = PostProcessingState::Running;
+        }
+
+        loop {
+            // Run each hook without holding the lock so other tasks are not blocked
+            let (hook, mut context) = {
+                let mut runs = self.runs.lock().await;
+                let run = runs.get_mut(&task_id).ok_or(DownloadError::TaskNotFound(task_id))?;
+                match run.hooks.get(run.next) {
+                    Some(hook) => (hook.clone(), run.context.clone()),
+                    None => {
+                        let path = run.context.path.clone();
+                        run.state = PostProcessingState::Succeeded { path: path.clone() };
+                        return Ok(path);
+                    }
+                }
+            };
+
+            let result = hook.run(&mut context).await;
+
+            let mut runs = self.runs.lock().await;
+            let run = runs.get_mut(&task_id).ok_or(DownloadError::TaskNotFound(task_id))?;
+            match result {
+                Ok(()) => {
+                    run.context = context;
+                    run.next += 1;
+                }
+                Err(e) => {
+                    let hook = hook.name().to_string();
+                    let reason = e.to_string();
+                    log::warn!("Post-processing hook {} failed for task {}: {}", hook, task_id, reason);
+
+                    run.state = PostProcessingState::Failed { hook: hook.clone(), error: reason.clone() };
+                    return Err(DownloadError::PostProcessingFailed { task_id, hook, reason });
+                }
+            }
+        }
+    }
+
+    /// Run the hooks of a task again, starting at the one that failed
+    pub async fn retry(&self, task_id: TaskId) -> Result<PathBuf> {
+        {
+            let mut runs = self.runs.lock().await;
+            let run = runs.get_mut(&task_id).ok_or(DownloadError::TaskNotFound(task_id))?;
+            if !matches!(run.state, PostProcessingState::Failed { .. }) {
+                return Err(DownloadError::InvalidTaskState {
+                    task_id,
+                    operation: "retry post-processing of",
+                    status: DownloadStatus::Completed,
+                });
+            }
+            run.state = PostProcessingState::Pending;
+        }
+
+        self.execute(task_id).await
+    }
+
+    /// Get the post-processing state of a task, `None` before its download completed
+    pub async fn state(&self, task_id: TaskId) -> Option<PostProcessingState> {
+        self.runs.lock().await.get(&task_id).map(|run| run.state.clone())
+    }
+
+    /// Forget the hooks and post-processing state of a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.task_hooks.write().await.remove(&task_id);
+        self.runs.lock().await.remove(&task_id);
+    }
+}
\ No newline at end of file
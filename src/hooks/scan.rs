@@ -0,0 +1,91 @@
+//! Virus scanning of completed downloads
+//!
+//! Scanners run after the engine finished a download and before the task is
+//! reported completed. A file any scanner rejects, or that a scanner fails
+//! to check, is moved to a quarantine directory and its task fails with a
+//! reason starting with [`SCAN_REJECTED`].
+
+use crate::error::DownloadError;
+use crate::types::TaskId;
+use crate::Result;
+use async_trait::async_trait;
+use std::path::Path;
+
+/// Prefix of the failure reason of downloads a scanner rejected
+pub const SCAN_REJECTED: &str = "ScanRejected";
+
+/// What a scanner found in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// The file must not be used, with what was found
+    Rejected(String),
+}
+
+/// A check run on every completed download before it is handed out
+#[async_trait]
+pub trait ScanHook: Send + Sync {
+    /// Name reported when the scanner rejects a file
+    fn name(&self) -> &str;
+
+    /// Check the file a task downloaded to `path`
+    async fn scan(&self, task_id: TaskId, path: &Path) -> Result<ScanVerdict>;
+}
+
+/// Scanner running a command line tool on each file
+///
+/// The file path is passed as the last argument. Exit code 0 means clean,
+/// 1 means the tool found something, which it is expected to print; any
+/// other exit code is a failed scan.
+#[derive(Debug, Clone)]
+pub struct CommandScanner {
+    name: String,
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandScanner {
+    pub fn new(program: impl Into<String>) -> Self {
+        let program = program.into();
+        Self {
+            name: program.clone(),
+            program,
+            args: Vec::new(),
+        }
+    }
+
+    /// Scan with ClamAV's `clamscan`
+    pub fn clamav() -> Self {
+        Self::new("clamscan").arg("--no-summary").arg("--infected")
+    }
+
+    /// Add an argument passed before the file path
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+}
+
+#[async_trait]
+impl ScanHook for CommandScanner {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn scan(&self, _task_id: TaskId, path: &Path) -> Result<ScanVerdict> {
+        let output = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .arg(path)
+            .output()
+            .await?;
+
+        match output.status.code() {
+            Some(0) => Ok(ScanVerdict::Clean),
+            Some(1) => Ok(ScanVerdict::Rejected(String::from_utf8_lossy(&output.stdout).trim().to_string())),
+            _ => Err(DownloadError::General(format!(
+                "{} exited with {}: {}",
+                self.program, output.status, String::from_utf8_lossy(&output.stderr).trim()
+            ))),
+        }
+    }
+}
\ No newline at end of file
@@ -9,6 +9,14 @@
 //! - Progress monitoring and task lifecycle management
 //! - Default storage to `./data/` directory with customizable paths
 //! - Automatic database persistence and recovery
+//! - Scheduled and recurring downloads
+//! - Post-download hooks such as moving or chmod-ing finished files
+//! - Optional extraction of zip, tar.gz and 7z archives
+//! - Task groups for multi-file downloads such as model shards
+//! - Whole-repository downloads from the Hugging Face Hub
+//! - Optional HTTP control server with server-sent events (`server` feature)
+//! - Optional in-process SFTP downloads (`sftp` feature)
+//! - Blocking API for callers without an async runtime ([`blocking`])
 //!
 //! ## Simple Usage (Recommended)
 //!
@@ -33,6 +41,9 @@
 //!     let progress = get_download_progress(task_id).await?;
 //!     println!("Downloaded: {} bytes", progress.downloaded_bytes);
 //!
+//!     // Flush all tasks to the database before exiting
+//!     burncloud_download::shutdown_global_manager().await?;
+//!
 //!     Ok(())
 //! }
 //! ```
@@ -73,50 +84,312 @@ pub mod error;
 pub mod utils;
 pub mod models;     // New module for duplicate detection models
 pub mod services;   // New module for duplicate detection services
+pub mod backend;
+pub mod scheduler;
+pub mod storage;
+pub mod aria2_supervisor;
+pub mod hooks;
+pub mod probe;
+pub mod sources;
+pub mod groups;
+pub mod migrations;
+pub mod blocking;
+#[cfg(feature = "server")]
+pub mod server;
 
 // Re-export core types from burncloud-download-types
 pub use burncloud_download_types::{DownloadTask, DownloadProgress, DownloadStatus, TaskId};
 
 // Re-export traits and implementations
-pub use traits::{DownloadManager, DownloadEventHandler};
-pub use queue::TaskQueueManager;
-pub use manager::{BasicDownloadManager, PersistentAria2Manager};
+pub use traits::{DownloadManager, DownloadEventHandler, DownloadEventListener, DuplicateDecisionHandler, DownloadBackend, CredentialProvider, QueueScheduler, QueuedTask};
+pub use queue::{TaskQueueManager, FifoScheduler, PriorityScheduler, RoundRobinScheduler};
+pub use manager::{BasicDownloadManager, PersistentAria2Manager, PersistentDownloadManager, PersistentAria2ManagerBuilder, ManagerConfig, StaleTaskCollector};
 
 // Re-export duplicate detection types
 pub use models::{
-    FileIdentifier, TaskStatus, DuplicatePolicy, DuplicateResult,
-    DuplicateReason, DuplicateAction
+    FileIdentifier, TaskStatus, DuplicatePolicy, DuplicateDecision,
+    DuplicateCandidate, DuplicatePreview, DuplicateReason, Priority, RetryPolicy, Backoff, RetryOn,
+    DownloadOptions, Checksum, ChecksumAlgorithm, PieceChecksums, SegmentDefaults, DownloadEvent, OverwritePolicy, UrlPolicy, Credentials,
+    RecoveryReport, RestoredTask, FailedRecovery, TaskExport, ExportedTask, ImportPolicy, ImportReport,
+    SmoothedProgress, ProgressKind, FileProgress, AdmissionLimits, AdmissionMode, ProgressSample, MirrorStats, FileAllocation, GcPolicy, StaleTaskAction, GcReport, DeleteMode, TrashPolicy, TrashedTask, HostLimits, HealthReport, ListOrder, DomainUsage, HandlerError, HandlerFailure, HandlerId,
+    ConditionalDownload, ContentPolicy, ProgressDelivery, RpcTimeouts, TorrentSettings, PortRange, SpeedSample, TaskSpeed, DownloadProfile, OwnerQuotas,
+    FailureInfo, FailureKind
 };
-pub use services::{DuplicateDetector, TaskRepository, BackgroundHashCalculator, TaskValidation};
+pub use services::{DuplicateDetector, InMemoryDuplicateDetector, SqliteDuplicateDetector, IndexedTask, DuplicateResolver, TaskRepository, InMemoryTaskRepository, SqliteTaskRepository, EncryptedTaskRepository, FieldCipher, BackgroundHashCalculator, TaskValidation, BandwidthLimiter, EventBus, PartialDownload, SpeedSmoother, ProgressHistory, StallTracker, DomainUsageTracker, DownloadStream};
+pub use backend::{Aria2Backend, Aria2Session, SessionImport, SchemeRouter, Aria2GlobalStats, TimeoutBackend};
+#[cfg(feature = "sftp")]
+pub use backend::SftpBackend;
+#[cfg(feature = "postgres")]
+pub use services::PostgresTaskRepository;
+pub use scheduler::{DownloadScheduler, ScheduleSpec, ScheduleId, ThrottleRule, ThrottleSchedule, Throttler};
+pub use storage::StorageChecker;
+pub use aria2_supervisor::{Aria2Supervisor, SupervisorConfig};
+pub use hooks::{PostDownloadHook, HookContext, HookPipeline, PostProcessingState, ScanHook, ScanVerdict, CommandScanner, CopyState, CopyStatus};
+pub use probe::{DownloadProbe, RemoteMetadata, RemoteValidators, ProbeResult};
+pub use sources::{HfClient, HfRepo, HfRepoDownload, MirrorManager};
+pub use groups::{TaskGroups, GroupId, TaskGroup};
 
 pub use error::DownloadError;
+pub use tokio_util::sync::CancellationToken;
 
 /// Result type alias for download operations
-pub type Result<T> = std::result::Result<T, anyhow::Error>;
+pub type Result<T> = std::result::Result<T, DownloadError>;
 
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, broadcast, watch};
 
 // Global manager instance for convenience functions
-static GLOBAL_MANAGER: OnceLock<Mutex<Option<std::sync::Arc<PersistentAria2Manager>>>> = OnceLock::new();
+static GLOBAL_MANAGER: OnceLock<Mutex<Option<std::sync::Arc<dyn DownloadManager>>>> = OnceLock::new();
 
 /// Get or initialize the global download manager
-async fn get_global_manager() -> Result<std::sync::Arc<PersistentAria2Manager>> {
+async fn get_global_manager() -> Result<std::sync::Arc<dyn DownloadManager>> {
     let manager_lock = GLOBAL_MANAGER.get_or_init(|| Mutex::new(None));
     let mut manager_guard = manager_lock.lock().await;
 
     if manager_guard.is_none() {
-        let new_manager = PersistentAria2Manager::new().await?;
-        *manager_guard = Some(std::sync::Arc::new(new_manager));
+        // The convenience API is configured through BURNCLOUD_* environment variables
+        let config = ManagerConfig::from_env()?;
+        let new_manager: std::sync::Arc<dyn DownloadManager> = std::sync::Arc::new(
+            PersistentAria2ManagerBuilder::from_config(config).build().await?
+        );
+        *manager_guard = Some(new_manager);
     }
 
     Ok(manager_guard.as_ref().unwrap().clone())
 }
 
+/// Get the global manager for a feature only [`PersistentAria2Manager`] provides
+fn require_persistent<'a>(manager: &'a dyn DownloadManager, feature: &str) -> Result<&'a PersistentAria2Manager> {
+    manager.as_persistent().ok_or_else(|| DownloadError::Config(format!(
+        "{} requires a PersistentAria2Manager as the global download manager", feature
+    )))
+}
+
+/// Get the directory [`download`] saves files to
+///
+/// Injected managers other than [`PersistentAria2Manager`] have no download
+/// directory, the one from the environment is used for them.
+fn global_download_dir(manager: &dyn DownloadManager) -> Result<PathBuf> {
+    match manager.as_persistent() {
+        Some(manager) => Ok(manager.download_dir().to_path_buf()),
+        None => Ok(ManagerConfig::from_env()?.download_dir),
+    }
+}
+
+/// Use the given manager for [`download`] and the other convenience functions
+///
+/// Must be called before the first convenience call, which would otherwise
+/// start a manager configured from the environment. Returns
+/// [`DownloadError::Config`] when a global manager is already running; call
+/// [`shutdown_global_manager`] first to replace it.
+///
+/// Any [`DownloadManager`] can be injected, e.g. a [`TaskQueueManager`] or a
+/// test double. Functions relying on features of [`PersistentAria2Manager`],
+/// like [`probe()`] or [`subscribe_events`], return [`DownloadError::Config`]
+/// for other managers.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use burncloud_download::{init_global_manager, PersistentAria2Manager};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let manager = PersistentAria2Manager::builder()
+///         .download_dir("/srv/downloads")
+///         .build()
+///         .await?;
+///     init_global_manager(Arc::new(manager)).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn init_global_manager(manager: std::sync::Arc<dyn DownloadManager>) -> Result<()> {
+    let manager_lock = GLOBAL_MANAGER.get_or_init(|| Mutex::new(None));
+    let mut manager_guard = manager_lock.lock().await;
+
+    if manager_guard.is_some() {
+        return Err(DownloadError::Config("The global download manager is already initialized".to_string()));
+    }
+
+    *manager_guard = Some(manager);
+    Ok(())
+}
+
+/// Build the global download manager from the given builder
+///
+/// Like [`init_global_manager`], but nothing is built when a global manager
+/// is already running.
+pub async fn with_global_manager(builder: PersistentAria2ManagerBuilder) -> Result<()> {
+    let manager_lock = GLOBAL_MANAGER.get_or_init(|| Mutex::new(None));
+    let mut manager_guard = manager_lock.lock().await;
+
+    if manager_guard.is_some() {
+        return Err(DownloadError::Config("The global download manager is already initialized".to_string()));
+    }
+
+    let manager: std::sync::Arc<dyn DownloadManager> = std::sync::Arc::new(builder.build().await?);
+    *manager_guard = Some(manager);
+    Ok(())
+}
+
+/// Tear down all global state so the next test starts from scratch
+///
+/// Shuts down the global manager, scheduler and task groups like
+/// [`shutdown_global_manager`], ignoring flush errors, and restores the
+/// default duplicate policy.
+#[cfg(feature = "test-util")]
+pub async fn reset_global_manager_for_tests() {
+    if let Err(e) = shutdown_global_manager().await {
+        log::warn!("Failed to flush global download manager: {}", e);
+    }
+    set_default_duplicate_policy(DuplicatePolicy::ReuseExisting);
+}
+
+// Duplicate policy of the convenience functions, kept across global manager restarts
+static DEFAULT_DUPLICATE_POLICY: std::sync::RwLock<DuplicatePolicy> = std::sync::RwLock::new(DuplicatePolicy::ReuseExisting);
+
+/// Set how [`download`], [`download_to`] and the blocking API handle duplicates
+///
+/// Defaults to [`DuplicatePolicy::ReuseExisting`], which returns the existing
+/// task for a URL and target path that were downloaded before.
+pub fn set_default_duplicate_policy(policy: DuplicatePolicy) {
+    *DEFAULT_DUPLICATE_POLICY.write().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+/// Get the duplicate policy of the convenience functions
+pub fn default_duplicate_policy() -> DuplicatePolicy {
+    DEFAULT_DUPLICATE_POLICY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Global scheduler instance feeding the global manager
+static GLOBAL_SCHEDULER: OnceLock<Mutex<Option<std::sync::Arc<DownloadScheduler>>>> = OnceLock::new();
+
+/// Get or initialize the global download scheduler
+async fn get_global_scheduler() -> Result<std::sync::Arc<DownloadScheduler>> {
+    let scheduler_lock = GLOBAL_SCHEDULER.get_or_init(|| Mutex::new(None));
+    let mut scheduler_guard = scheduler_lock.lock().await;
+
+    if scheduler_guard.is_none() {
+        let manager = get_global_manager().await?;
+        let mut store = scheduler::ScheduleStore::open(Path::new(services::task_metadata_store::DEFAULT_METADATA_DB_PATH)).await?;
+        if let Some(cipher) = manager.as_persistent().and_then(|m| m.field_cipher()) {
+            store = store.with_cipher(cipher.clone());
+        }
+        let new_scheduler = std::sync::Arc::new(DownloadScheduler::new(manager, store));
+        new_scheduler.start().await;
+        *scheduler_guard = Some(new_scheduler);
+    }
+
+    Ok(scheduler_guard.as_ref().unwrap().clone())
+}
+
+// Global task groups of the global manager
+static GLOBAL_GROUPS: OnceLock<Mutex<Option<std::sync::Arc<TaskGroups>>>> = OnceLock::new();
+
+/// Get or initialize the global task groups
+async fn get_global_groups() -> Result<std::sync::Arc<TaskGroups>> {
+    let groups_lock = GLOBAL_GROUPS.get_or_init(|| Mutex::new(None));
+    let mut groups_guard = groups_lock.lock().await;
+
+    if groups_guard.is_none() {
+        let manager = get_global_manager().await?;
+        let store = groups::GroupStore::open(Path::new(services::task_metadata_store::DEFAULT_METADATA_DB_PATH)).await?;
+        *groups_guard = Some(std::sync::Arc::new(TaskGroups::new(manager, store)));
+    }
+
+    Ok(groups_guard.as_ref().unwrap().clone())
+}
+
+/// Shut down the global download manager
+///
+/// Stops the scheduler and persistence poller and flushes all tasks and their
+/// progress to the database. Call this before the program exits; the next
+/// convenience call starts a fresh manager.
+pub async fn shutdown_global_manager() -> Result<()> {
+    if let Some(scheduler_lock) = GLOBAL_SCHEDULER.get() {
+        let scheduler = scheduler_lock.lock().await.take();
+        if let Some(scheduler) = scheduler {
+            scheduler.shutdown().await;
+        }
+    }
+
+    // Groups hold the manager, the next call opens them on the fresh one
+    if let Some(groups_lock) = GLOBAL_GROUPS.get() {
+        groups_lock.lock().await.take();
+    }
+
+    let manager = match GLOBAL_MANAGER.get() {
+        Some(manager_lock) => manager_lock.lock().await.take(),
+        None => None,
+    };
+
+    // Other managers have nothing to flush
+    if let Some(manager) = manager.as_deref().and_then(|manager| manager.as_persistent()) {
+        manager.shutdown().await?;
+    }
+
+    Ok(())
+}
+
+/// Flush the global download manager when the process receives ctrl-c
+///
+/// This is opt-in: once installed, ctrl-c shuts the global manager down with
+/// [`shutdown_global_manager`] and then exits the process with status 130.
+/// Installing the hook more than once has no effect. Must be called from
+/// within a Tokio runtime.
+pub fn install_shutdown_hook() {
+    static HOOK_INSTALLED: OnceLock<()> = OnceLock::new();
+    if HOOK_INSTALLED.set(()).is_err() {
+        return;
+    }
+
+    tokio::spawn(async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for ctrl-c: {}", e);
+            return;
+        }
+
+        log::info!("Received ctrl-c, flushing downloads before exit");
+        if let Err(e) = shutdown_global_manager().await {
+            log::error!("Failed to shut down download manager: {}", e);
+        }
+
+        std::process::exit(130);
+    });
+}
+
+/// Ask the server about a URL before downloading it
+///
+/// Nothing is downloaded or added to the queue. Useful to show the size and
+/// file name in a confirmation dialog first.
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::{probe, download};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let info = probe("https://example.com/file.zip").await?;
+///     println!("{}: {:?} bytes, resumable: {}", info.filename, info.size, info.resumable);
+///     download(&info.final_url).await?;
+///     Ok(())
+/// }
+/// ```
+pub async fn probe<S: AsRef<str>>(url: S) -> Result<ProbeResult> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "probe")?.probe_url(url.as_ref()).await
+}
+
 /// Simple download function that downloads a file to the default ./data/ directory
 ///
-/// The filename is automatically extracted from the URL.
+/// The filename is taken from the server's `Content-Disposition` header or the
+/// URL after redirects, falling back to the requested URL when the server
+/// can't be probed. Query strings, percent escapes and directories in the
+/// name are stripped, names longer than `BURNCLOUD_MAX_FILENAME_LENGTH` bytes
+/// (255 by default) are shortened, and a name another download already uses
+/// gets a `name (n).ext` suffix. The directory can be changed with the
+/// `BURNCLOUD_DOWNLOAD_DIR` environment variable.
 ///
 /// # Arguments
 /// * `url` - The URL to download from
@@ -136,22 +409,22 @@ async fn get_global_manager() -> Result<std::sync::Arc<PersistentAria2Manager>>
 /// }
 /// ```
 pub async fn download<S: AsRef<str>>(url: S) -> Result<TaskId> {
-    let url_str = url.as_ref();
-
-    // Extract filename from URL
-    let filename = url_str
-        .split('/')
-        .next_back()
-        .and_then(|name| if name.is_empty() { None } else { Some(name) })
-        .unwrap_or("download");
-
-    let target_path = PathBuf::from("./data").join(filename);
+    let manager = get_global_manager().await?;
+    if let Some(manager) = manager.as_persistent() {
+        return manager.download_named(url.as_ref(), default_duplicate_policy()).await;
+    }
 
-    download_to(url_str, target_path).await
+    // Without probing, the file is named after the URL
+    let filename = probe::filename_from_url(url.as_ref()).unwrap_or_else(|| probe::DEFAULT_FILENAME.to_string());
+    let target_path = global_download_dir(manager.as_ref())?.join(filename);
+    let (task_id, _) = manager.add_download_with_policy(url.as_ref(), &target_path, default_duplicate_policy()).await?;
+    Ok(task_id)
 }
 
 /// Download a file to a specific path
 ///
+/// Missing parent directories of `target_path` are created.
+///
 /// # Arguments
 /// * `url` - The URL to download from
 /// * `target_path` - Where to save the downloaded file
@@ -174,11 +447,207 @@ pub async fn download<S: AsRef<str>>(url: S) -> Result<TaskId> {
 /// }
 /// ```
 pub async fn download_to<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P) -> Result<TaskId> {
+    download_with_policy(url, target_path, default_duplicate_policy()).await
+}
+
+/// Download a file to a specific path, handling an existing download of it with `policy`
+///
+/// # Arguments
+/// * `url` - The URL to download from
+/// * `target_path` - Where to save the downloaded file
+/// * `policy` - What to do when the URL and path were downloaded before
+///
+/// # Returns
+/// * `TaskId` - The new task, or the existing one the policy reused
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::{download_with_policy, DuplicatePolicy};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     // Fetch a fresh copy even if the file was downloaded before
+///     let task_id = download_with_policy(
+///         "https://example.com/nightly.tar.gz",
+///         "./downloads/nightly.tar.gz",
+///         DuplicatePolicy::AllowDuplicate
+///     ).await?;
+///     println!("Download started: {}", task_id);
+///     Ok(())
+/// }
+/// ```
+pub async fn download_with_policy<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P, policy: DuplicatePolicy) -> Result<TaskId> {
     let manager = get_global_manager().await?;
-    manager.add_download(
-        url.as_ref().to_string(),
-        target_path.as_ref().to_path_buf()
-    ).await
+    let (task_id, _) = manager.add_download_with_policy(url.as_ref(), target_path.as_ref(), policy).await?;
+    Ok(task_id)
+}
+
+/// Tell what [`download_with_policy`] would do, without adding anything
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::{evaluate_duplicate, DuplicatePolicy, DuplicatePreview};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let preview = evaluate_duplicate(
+///         "https://example.com/model.bin",
+///         "./downloads/model.bin",
+///         DuplicatePolicy::ReuseIfComplete
+///     ).await?;
+///     if let DuplicatePreview::Reuse { task_id, .. } = preview {
+///         println!("Already downloaded by task {}", task_id);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn evaluate_duplicate<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P, policy: DuplicatePolicy) -> Result<DuplicatePreview> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "evaluate_duplicate")?
+        .evaluate_duplicate(url.as_ref(), target_path.as_ref(), policy).await
+}
+
+/// Download a file into the directory of a named download profile
+///
+/// The profile's concurrency, speed limit and overwrite policy apply to the
+/// download. Profiles are set in the configuration or with
+/// [`PersistentAria2Manager::set_profile`].
+///
+/// # Arguments
+/// * `profile` - Name of the profile, e.g. `models`
+/// * `url` - The URL to download from
+///
+/// # Returns
+/// * `TaskId` - The unique identifier for this download task
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::download_with_profile;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let task_id = download_with_profile("models", "https://example.com/model.gguf").await?;
+///     println!("Download started: {}", task_id);
+///     Ok(())
+/// }
+/// ```
+pub async fn download_with_profile<S: AsRef<str>>(profile: &str, url: S) -> Result<TaskId> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "download_with_profile")?
+        .download_with_profile(profile, url.as_ref()).await
+}
+
+/// Download every file of a Hugging Face Hub model repository
+///
+/// Files are saved below `<download dir>/<repo id>/`, keeping their paths in
+/// the repository, and their tasks are put into one task group. The access token for private or gated repositories is
+/// read from `HF_TOKEN` (or `HUGGING_FACE_HUB_TOKEN`) and the Hub endpoint
+/// from `HF_ENDPOINT`. Calling this again for the same repository resumes the
+/// download instead of starting over.
+///
+/// # Arguments
+/// * `repo_id` - The repository id, e.g. `org/model`
+///
+/// # Returns
+/// * `HfRepoDownload` - The repository download with the task of each file
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::download_hf_repo;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let repo = download_hf_repo("org/model").await?;
+///     println!("Downloading {} files", repo.files.len());
+///
+///     if let Some(group_id) = repo.group_id {
+///         let progress = burncloud_download::get_group_progress(group_id).await?;
+///         println!("Downloaded: {} bytes", progress.downloaded_bytes);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn download_hf_repo<S: AsRef<str>>(repo_id: S) -> Result<HfRepoDownload> {
+    let manager = get_global_manager().await?;
+    let groups = get_global_groups().await?;
+    let repo = HfRepo::model(repo_id.as_ref());
+    repo.validate()?;
+
+    let directory = global_download_dir(manager.as_ref())?.join(&repo.repo_id);
+    HfClient::from_env().download_repo_grouped(&groups, &repo, &directory).await
+}
+
+/// Create an empty task group
+///
+/// # Arguments
+/// * `name` - A name describing the group, e.g. the artifact it downloads
+///
+/// # Returns
+/// * `GroupId` - The identifier of the new group
+pub async fn create_group<S: AsRef<str>>(name: S) -> Result<GroupId> {
+    let groups = get_global_groups().await?;
+    groups.create_group(name.as_ref()).await
+}
+
+/// Add a download task to a task group
+///
+/// # Arguments
+/// * `group_id` - The group to add the task to
+/// * `task_id` - The task to add, which must not belong to another group
+pub async fn add_to_group(group_id: GroupId, task_id: TaskId) -> Result<()> {
+    let groups = get_global_groups().await?;
+    groups.add_task(group_id, task_id).await
+}
+
+/// Get the combined progress of all tasks in a group
+///
+/// # Arguments
+/// * `group_id` - The identifier of the task group
+///
+/// # Returns
+/// * `DownloadProgress` - Progress summed over the tasks of the group
+pub async fn get_group_progress(group_id: GroupId) -> Result<DownloadProgress> {
+    let groups = get_global_groups().await?;
+    groups.progress(group_id).await
+}
+
+/// Get the combined status of all tasks in a group
+///
+/// # Arguments
+/// * `group_id` - The identifier of the task group
+///
+/// # Returns
+/// * `DownloadStatus` - Failed if any task failed, completed once all are
+pub async fn get_group_status(group_id: GroupId) -> Result<DownloadStatus> {
+    let groups = get_global_groups().await?;
+    groups.status(group_id).await
+}
+
+/// Pause all tasks of a group
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were paused
+pub async fn pause_group(group_id: GroupId) -> Result<Vec<TaskId>> {
+    let groups = get_global_groups().await?;
+    groups.pause_group(group_id).await
+}
+
+/// Resume all paused tasks of a group
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were resumed
+pub async fn resume_group(group_id: GroupId) -> Result<Vec<TaskId>> {
+    let groups = get_global_groups().await?;
+    groups.resume_group(group_id).await
+}
+
+/// Cancel all tasks of a group and remove the group
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were cancelled
+pub async fn cancel_group(group_id: GroupId) -> Result<Vec<TaskId>> {
+    let groups = get_global_groups().await?;
+    groups.cancel_group(group_id).await
 }
 
 /// Get the progress of a download task
@@ -205,6 +674,48 @@ pub async fn get_download_task(task_id: TaskId) -> Result<DownloadTask> {
     manager.get_task(task_id).await
 }
 
+/// Wait until a download task completes
+///
+/// Resolves as soon as the manager sees the task finish, without polling.
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+/// * `timeout` - How long to wait at most, `None` to wait indefinitely
+///
+/// # Returns
+/// * `DownloadTask` - The completed task; failed, cancelled and timed out
+///   waits return an error
+pub async fn wait_for_download(task_id: TaskId, timeout: Option<std::time::Duration>) -> Result<DownloadTask> {
+    let manager = get_global_manager().await?;
+    manager.wait_for_completion(task_id, timeout).await
+}
+
+/// Download a file to the default ./data/ directory and wait until it completes
+///
+/// See [`download`] for how the file is named.
+///
+/// # Arguments
+/// * `url` - The URL to download from
+///
+/// # Returns
+/// * `DownloadTask` - The completed task, whose `target_path` is the file
+///
+/// # Example
+/// ```no_run
+/// use burncloud_download::download_and_wait;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let task = download_and_wait("https://example.com/file.zip").await?;
+///     println!("Saved to {}", task.target_path.display());
+///     Ok(())
+/// }
+/// ```
+pub async fn download_and_wait<S: AsRef<str>>(url: S) -> Result<DownloadTask> {
+    let task_id = download(url).await?;
+    wait_for_download(task_id, None).await
+}
+
 /// Pause a download task
 ///
 /// # Arguments
@@ -232,6 +743,58 @@ pub async fn cancel_download(task_id: TaskId) -> Result<()> {
     manager.cancel_download(task_id).await
 }
 
+/// Cancel a download task but keep it with a cancelled status
+///
+/// Unlike [`cancel_download`], the task stays listed so it can be told apart
+/// from one that never existed. Returns `false` when the task was already
+/// cancelled or completed.
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+/// * `delete_files` - Whether to remove the partial file as well
+pub async fn cancel_and_keep(task_id: TaskId, delete_files: bool) -> Result<bool> {
+    let manager = get_global_manager().await?;
+    manager.cancel_and_keep(task_id, delete_files).await
+}
+
+/// Continue a download task from a new URL, e.g. a refreshed presigned URL
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+/// * `new_url` - The URL to fetch the rest of the file from
+pub async fn update_task_url<S: AsRef<str>>(task_id: TaskId, new_url: S) -> Result<()> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "update_task_url")?
+        .update_task_url(task_id, new_url.as_ref()).await
+}
+
+/// Claim the file of a completed download, optionally moving it to `destination`
+///
+/// Only one caller gets the file; afterwards the manager no longer manages it.
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+/// * `destination` - Where to move the file, `None` to leave it in place
+pub async fn take_file<P: AsRef<Path>>(task_id: TaskId, destination: Option<P>) -> Result<PathBuf> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "take_file")?
+        .take_file_to(task_id, destination.map(|path| path.as_ref().to_path_buf())).await
+}
+
+/// Get the diagnostics of the last `limit` failures of a download task, oldest first
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+/// * `limit` - How many of the most recent failures to return
+pub async fn failure_history(task_id: TaskId, limit: usize) -> Result<Vec<FailureInfo>> {
+    let manager = get_global_manager().await?;
+    match manager.as_persistent() {
+        Some(manager) => manager.failure_history(task_id, limit).await,
+        // Other managers only report the most recent failure
+        None => Ok(manager.failure_info(task_id).await?.into_iter().take(limit).collect()),
+    }
+}
+
 /// List all download tasks
 ///
 /// # Returns
@@ -248,4 +811,97 @@ pub async fn list_downloads() -> Result<Vec<DownloadTask>> {
 pub async fn active_download_count() -> Result<usize> {
     let manager = get_global_manager().await?;
     manager.active_download_count().await
+}
+
+/// Pause all downloads
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were paused
+pub async fn pause_all() -> Result<Vec<TaskId>> {
+    let manager = get_global_manager().await?;
+    manager.pause_all().await
+}
+
+/// Resume all paused downloads
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were resumed
+pub async fn resume_all() -> Result<Vec<TaskId>> {
+    let manager = get_global_manager().await?;
+    manager.resume_all().await
+}
+
+/// Cancel and remove all downloads
+///
+/// # Returns
+/// * `Vec<TaskId>` - The tasks that were cancelled
+pub async fn cancel_all() -> Result<Vec<TaskId>> {
+    let manager = get_global_manager().await?;
+    manager.cancel_all().await
+}
+
+/// Cap the combined download speed of all downloads
+///
+/// # Arguments
+/// * `bytes_per_sec` - Maximum download speed in bytes per second, 0 for unlimited
+pub async fn set_global_download_limit(bytes_per_sec: u64) -> Result<()> {
+    let manager = get_global_manager().await?;
+    manager.set_global_download_limit(bytes_per_sec).await
+}
+
+/// Limit the combined size of all files below a directory
+///
+/// New downloads into the directory fail with `DownloadError::QuotaExceeded`
+/// once their size would exceed the quota.
+///
+/// # Arguments
+/// * `directory` - The directory to limit, e.g. `./data`
+/// * `max_bytes` - Maximum combined size of its files in bytes
+pub async fn set_storage_quota<P: AsRef<Path>>(directory: P, max_bytes: u64) -> Result<()> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "set_storage_quota")?
+        .storage().set_quota(directory.as_ref(), max_bytes).await;
+    Ok(())
+}
+
+/// Subscribe to events of all downloads started through the convenience API
+///
+/// # Returns
+/// * `broadcast::Receiver<DownloadEvent>` - Receiver for every download event
+pub async fn subscribe_events() -> Result<broadcast::Receiver<DownloadEvent>> {
+    let manager = get_global_manager().await?;
+    Ok(require_persistent(manager.as_ref(), "subscribe_events")?.subscribe_events())
+}
+
+/// Subscribe to progress updates of a download task
+///
+/// # Arguments
+/// * `task_id` - The unique identifier of the download task
+///
+/// # Returns
+/// * `watch::Receiver<DownloadProgress>` - Receiver holding the latest progress
+pub async fn subscribe_progress(task_id: TaskId) -> Result<watch::Receiver<DownloadProgress>> {
+    let manager = get_global_manager().await?;
+    require_persistent(manager.as_ref(), "subscribe_progress")?
+        .subscribe_progress(task_id).await
+}
+
+/// Schedule a download for a later time or on a recurring schedule
+///
+/// Scheduled downloads are persisted and picked up again after a restart.
+///
+/// # Arguments
+/// * `url` - The URL to download from
+/// * `target_path` - Where to save the downloaded file
+/// * `spec` - When the download should run
+///
+/// # Returns
+/// * `ScheduleId` - The identifier of the scheduled download
+pub async fn schedule_download<S: AsRef<str>, P: AsRef<Path>>(url: S, target_path: P, spec: ScheduleSpec) -> Result<ScheduleId> {
+    let scheduler = get_global_scheduler().await?;
+    scheduler.schedule_download(
+        url.as_ref().to_string(),
+        target_path.as_ref().to_path_buf(),
+        spec
+    ).await
 }
\ No newline at end of file
@@ -2,14 +2,15 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tokio::time::Instant;
+use tokio::time::{Duration, Instant};
 use async_trait::async_trait;
-use anyhow::Result;
+use crate::Result;
 
-use crate::traits::DownloadManager;
+use crate::traits::{DownloadManager, DuplicateDecisionHandler};
 use crate::types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus};
-use crate::models::{DuplicatePolicy, DuplicateResult, FileIdentifier, DuplicateReason, TaskStatus};
+use crate::models::{DuplicatePolicy, DuplicateDecision, DuplicateCandidate, DuplicatePreview, FileIdentifier, DuplicateReason, TaskStatus, DownloadOptions, TargetAction, UrlPolicy, ListOrder};
 use crate::error::DownloadError;
+use crate::services::{BandwidthLimiter, DuplicateResolver, CompletionWaiters, TaskOutcome};
 
 /// Basic download manager implementation for demonstration and testing
 ///
@@ -22,6 +23,14 @@ pub struct BasicDownloadManager {
     progress: Arc<RwLock<HashMap<TaskId, DownloadProgress>>>,
     /// Mock download simulation data
     mock_data: Arc<RwLock<HashMap<TaskId, MockDownloadData>>>,
+    /// Speed limits applied to the simulated transfers
+    bandwidth: Arc<BandwidthLimiter>,
+    /// Decides how duplicate requests are handled
+    duplicates: Arc<DuplicateResolver>,
+    /// Rules download URLs have to satisfy
+    url_policy: Arc<RwLock<UrlPolicy>>,
+    /// Callers waiting for tasks to finish
+    completions: Arc<CompletionWaiters>,
 }
 
 /// Mock data for simulating download progress
@@ -38,9 +47,87 @@ impl BasicDownloadManager {
             tasks: Arc::new(RwLock::new(HashMap::new())),
             progress: Arc::new(RwLock::new(HashMap::new())),
             mock_data: Arc::new(RwLock::new(HashMap::new())),
+            bandwidth: Arc::new(BandwidthLimiter::new()),
+            duplicates: Arc::new(DuplicateResolver::new()),
+            url_policy: Arc::new(RwLock::new(UrlPolicy::default())),
+            completions: Arc::new(CompletionWaiters::new()),
         }
     }
 
+    /// Create another handle to the same state for background tasks
+    fn share(&self) -> Self {
+        Self {
+            tasks: self.tasks.clone(),
+            progress: self.progress.clone(),
+            mock_data: self.mock_data.clone(),
+            bandwidth: self.bandwidth.clone(),
+            duplicates: self.duplicates.clone(),
+            url_policy: self.url_policy.clone(),
+            completions: self.completions.clone(),
+        }
+    }
+
+    /// Set the handler asked to decide on duplicates under [`DuplicatePolicy::PromptUser`]
+    pub async fn set_duplicate_handler(&self, handler: Arc<dyn DuplicateDecisionHandler>) {
+        self.duplicates.set_handler(handler).await;
+    }
+
+    /// Set the rules download URLs have to satisfy
+    pub async fn set_url_policy(&self, policy: UrlPolicy) {
+        *self.url_policy.write().await = policy;
+    }
+
+    /// Tell what adding `url` -> `target_path` under `policy` would do
+    ///
+    /// Nothing is added and the decision handler is not asked; under
+    /// [`DuplicatePolicy::PromptUser`] the result says it would be.
+    pub async fn evaluate_duplicate(&self, url: &str, target_path: &Path, policy: DuplicatePolicy) -> Result<DuplicatePreview> {
+        self.validate_url(url).await?;
+
+        let candidates = self.duplicate_candidates(url, target_path).await?;
+        Ok(self.duplicates.preview(&policy, &candidates).await)
+    }
+
+    /// Get the existing task matching a download request with its status
+    async fn duplicate_candidates(&self, url: &str, target_path: &Path) -> Result<Vec<DuplicateCandidate>> {
+        let mut candidates = Vec::new();
+        if let Some(existing_task_id) = self.find_duplicate_task(url, target_path).await? {
+            let task = self.get_task(existing_task_id).await?;
+            candidates.push(DuplicateCandidate::new(
+                existing_task_id,
+                TaskStatus::from_download_status(task.status),
+                DuplicateReason::UrlAndPath,
+            ));
+        }
+        Ok(candidates)
+    }
+
+    /// Check a download URL against the URL policy
+    async fn validate_url(&self, url: &str) -> Result<()> {
+        self.url_policy.read().await.validate(url)
+    }
+
+    /// Record a download whose existing target file is kept, as a completed task
+    async fn add_skipped_task(&self, url: String, target_path: PathBuf) -> TaskId {
+        let size = tokio::fs::metadata(&target_path).await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        let mut task = DownloadTask::new(url, target_path);
+        task.update_status(DownloadStatus::Completed);
+        let task_id = task.id;
+
+        self.tasks.write().await.insert(task_id, task);
+        self.progress.write().await.insert(task_id, DownloadProgress {
+            downloaded_bytes: size,
+            total_bytes: Some(size),
+            speed_bps: 0,
+            eta_seconds: None,
+        });
+
+        task_id
+    }
+
     /// Update progress for a task (internal method)
     async fn update_task_progress(&self, task_id: TaskId) -> Result<()> {
         let mock_data = {
@@ -76,13 +163,20 @@ impl BasicDownloadManager {
 
             // If download is complete, update task status
             if downloaded_bytes >= mock_data.total_size {
-                let mut tasks = self.tasks.write().await;
-                if let Some(task) = tasks.get_mut(&task_id) {
-                    task.update_status(DownloadStatus::Completed);
-                }
+                let completed = {
+                    let mut tasks = self.tasks.write().await;
+                    tasks.get_mut(&task_id).map(|task| {
+                        task.update_status(DownloadStatus::Completed);
+                        task.clone()
+                    })
+                };
 
                 // Remove mock data as download is complete
                 self.mock_data.write().await.remove(&task_id);
+
+                if let Some(task) = completed {
+                    self.completions.resolve(task_id, TaskOutcome::Completed(task)).await;
+                }
             }
         }
 
@@ -90,22 +184,46 @@ impl BasicDownloadManager {
     }
 
     /// Start mock download simulation for a task
+    ///
+    /// Speed limits are applied when the simulation (re)starts.
     async fn start_mock_download(&self, task_id: TaskId) {
-        // Create mock download data (simulate a 10MB file downloading at 1MB/s)
+        // Simulate a 10MB file downloading at 1MB/s unless a limit is stricter
+        let total_size: u64 = 10 * 1024 * 1024; // 10MB
+        let active_count = self.mock_data.read().await.len() + 1;
+        let limit = self.bandwidth.effective_limit(task_id, active_count).await;
+        let download_speed = match limit {
+            0 => 1024 * 1024, // 1MB/s
+            limit => limit.min(1024 * 1024),
+        };
+
         let mock_data = MockDownloadData {
             start_time: Instant::now(),
-            total_size: 10 * 1024 * 1024, // 10MB
-            download_speed: 1024 * 1024,  // 1MB/s
+            total_size,
+            download_speed,
         };
 
+        let start_time = mock_data.start_time;
         self.mock_data.write().await.insert(task_id, mock_data);
 
+        // Finish the simulated transfer on time even when nobody reads its progress
+        let manager = self.share();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(total_size.div_ceil(download_speed))).await;
+
+            // A pause or resume since then restarted the simulation
+            let current = manager.mock_data.read().await.get(&task_id)
+                .is_some_and(|data| data.start_time == start_time);
+            if current {
+                let _ = manager.update_task_progress(task_id).await;
+            }
+        });
+
         // Initialize progress
         let initial_progress = DownloadProgress {
             downloaded_bytes: 0,
-            total_bytes: Some(10 * 1024 * 1024),
-            speed_bps: 1024 * 1024,
-            eta_seconds: Some(10),
+            total_bytes: Some(total_size),
+            speed_bps: download_speed,
+            eta_seconds: Some(total_size / download_speed),
         };
 
         self.progress.write().await.insert(task_id, initial_progress);
@@ -121,6 +239,23 @@ impl Default for BasicDownloadManager {
 #[async_trait]
 impl DownloadManager for BasicDownloadManager {
     async fn add_download(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        self.add_download_with_options(url, target_path, DownloadOptions::default()).await
+    }
+
+    async fn add_download_with_options(
+        &self,
+        url: String,
+        target_path: PathBuf,
+        options: DownloadOptions,
+    ) -> Result<TaskId> {
+        self.validate_url(&url).await?;
+
+        // The mock transfer writes nothing, so overwriting needs no preparation
+        let target_path = match options.overwrite.apply(&target_path)? {
+            TargetAction::Download { path, .. } => path,
+            TargetAction::Skip(path) => return Ok(self.add_skipped_task(url, path).await),
+        };
+
         let mut task = DownloadTask::new(url, target_path);
         task.update_status(DownloadStatus::Downloading);
         let task_id = task.id;
@@ -128,19 +263,39 @@ impl DownloadManager for BasicDownloadManager {
         // Store the task
         self.tasks.write().await.insert(task_id, task);
 
+        // Only the speed limit affects the simulated transfer
+        if let Some(limit) = options.speed_limit {
+            self.bandwidth.set_task_limit(task_id, limit).await;
+        }
+
         // Start mock download simulation
         self.start_mock_download(task_id).await;
 
         Ok(task_id)
     }
 
+    async fn add_download_multi_source(&self, urls: Vec<String>, target_path: PathBuf) -> Result<TaskId> {
+        for url in &urls {
+            self.validate_url(url).await?;
+        }
+
+        // The mock transfer only ever uses the primary source
+        let url = urls.into_iter().next()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        self.add_download(url, target_path).await
+    }
+
     async fn pause_download(&self, task_id: TaskId) -> Result<()> {
         let mut tasks = self.tasks.write().await;
         let task = tasks.get_mut(&task_id)
             .ok_or(DownloadError::TaskNotFound(task_id))?;
 
         if !task.status.can_pause() {
-            return Err(anyhow::anyhow!("Task cannot be paused in current status: {}", task.status));
+            return Err(DownloadError::InvalidTaskState {
+                task_id,
+                operation: "pause",
+                status: task.status.clone(),
+            });
         }
 
         task.update_status(DownloadStatus::Paused);
@@ -157,7 +312,11 @@ impl DownloadManager for BasicDownloadManager {
             .ok_or(DownloadError::TaskNotFound(task_id))?;
 
         if !task.status.can_resume() {
-            return Err(anyhow::anyhow!("Task cannot be resumed in current status: {}", task.status));
+            return Err(DownloadError::InvalidTaskState {
+                task_id,
+                operation: "resume",
+                status: task.status.clone(),
+            });
         }
 
         task.update_status(DownloadStatus::Downloading);
@@ -173,10 +332,63 @@ impl DownloadManager for BasicDownloadManager {
         self.tasks.write().await.remove(&task_id);
         self.progress.write().await.remove(&task_id);
         self.mock_data.write().await.remove(&task_id);
+        self.bandwidth.remove_task(task_id).await;
+        self.completions.resolve(task_id, TaskOutcome::Removed).await;
 
         Ok(())
     }
 
+    async fn pause_all(&self) -> Result<Vec<TaskId>> {
+        let mut tasks = self.tasks.write().await;
+        let mut mock_data = self.mock_data.write().await;
+
+        let mut paused = Vec::new();
+        for task in tasks.values_mut() {
+            if task.status.can_pause() {
+                task.update_status(DownloadStatus::Paused);
+                mock_data.remove(&task.id);
+                paused.push(task.id);
+            }
+        }
+
+        Ok(paused)
+    }
+
+    async fn resume_all(&self) -> Result<Vec<TaskId>> {
+        let resumed: Vec<TaskId> = {
+            let mut tasks = self.tasks.write().await;
+            tasks.values_mut()
+                .filter(|task| task.status == DownloadStatus::Paused)
+                .map(|task| {
+                    task.update_status(DownloadStatus::Downloading);
+                    task.id
+                })
+                .collect()
+        }; // Release write lock before restarting simulations
+
+        for task_id in &resumed {
+            self.start_mock_download(*task_id).await;
+        }
+
+        Ok(resumed)
+    }
+
+    async fn cancel_all(&self) -> Result<Vec<TaskId>> {
+        let cancelled: Vec<TaskId> = self.tasks.write().await
+            .drain()
+            .map(|(task_id, _)| task_id)
+            .collect();
+
+        self.progress.write().await.clear();
+        self.mock_data.write().await.clear();
+        for task_id in &cancelled {
+            self.bandwidth.remove_task(*task_id).await;
+            self.completions.resolve(*task_id, TaskOutcome::Removed).await;
+        }
+
+        Ok(cancelled)
+    }
+
     async fn get_progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
         // Update progress before returning
         self.update_task_progress(task_id).await?;
@@ -184,7 +396,7 @@ impl DownloadManager for BasicDownloadManager {
         let progress_map = self.progress.read().await;
         progress_map.get(&task_id)
             .cloned()
-            .ok_or_else(|| DownloadError::TaskNotFound(task_id).into())
+            .ok_or_else(|| DownloadError::TaskNotFound(task_id))
     }
 
     async fn get_task(&self, task_id: TaskId) -> Result<DownloadTask> {
@@ -194,12 +406,13 @@ impl DownloadManager for BasicDownloadManager {
         let tasks = self.tasks.read().await;
         tasks.get(&task_id)
             .cloned()
-            .ok_or_else(|| DownloadError::TaskNotFound(task_id).into())
+            .ok_or_else(|| DownloadError::TaskNotFound(task_id))
     }
 
     async fn list_tasks(&self) -> Result<Vec<DownloadTask>> {
-        let tasks = self.tasks.read().await;
-        Ok(tasks.values().cloned().collect())
+        let mut tasks: Vec<DownloadTask> = self.tasks.read().await.values().cloned().collect();
+        ListOrder::CreatedAsc.sort(&mut tasks);
+        Ok(tasks)
     }
 
     async fn active_download_count(&self) -> Result<usize> {
@@ -210,6 +423,12 @@ impl DownloadManager for BasicDownloadManager {
         Ok(count)
     }
 
+    async fn wait_for_completion(&self, task_id: TaskId, timeout: Option<Duration>) -> Result<DownloadTask> {
+        let receiver = self.completions.subscribe(task_id).await;
+        let task = self.get_task(task_id).await?;
+        receiver.wait(TaskOutcome::of(&task), timeout).await
+    }
+
     // Duplicate detection methods
 
     async fn find_duplicate_task(
@@ -236,29 +455,23 @@ impl DownloadManager for BasicDownloadManager {
         url: &str,
         target_path: &Path,
         policy: DuplicatePolicy,
-    ) -> Result<DuplicateResult> {
+    ) -> Result<(TaskId, DuplicateDecision)> {
+        self.validate_url(url).await?;
+
         // Check for duplicates first
-        if let Some(existing_task_id) = self.find_duplicate_task(url, target_path).await? {
-            let task = self.get_task(existing_task_id).await?;
-            let task_status = TaskStatus::from_download_status(task.status);
-
-            if policy.allows_reuse(&task_status) {
-                return Ok(DuplicateResult::ExistingTask {
-                    task_id: existing_task_id,
-                    status: task_status,
-                    reason: DuplicateReason::UrlAndPath,
-                });
-            } else if policy.should_fail_on_duplicate() {
-                return Err(DownloadError::PolicyViolation {
-                    task_id: existing_task_id,
-                    reason: "Duplicate found but policy forbids reuse".to_string(),
-                }.into());
+        let candidates = self.duplicate_candidates(url, target_path).await?;
+        let decision = self.duplicates.resolve(url, target_path, &policy, &candidates).await;
+        match decision {
+            DuplicateDecision::CreateNew => {
+                let task_id = self.add_download(url.to_string(), target_path.to_path_buf()).await?;
+                Ok((task_id, decision))
             }
+            DuplicateDecision::Reuse { task_id, .. } => Ok((task_id, decision)),
+            DuplicateDecision::Reject { task_id, .. } => Err(DownloadError::PolicyViolation {
+                task_id,
+                reason: "Duplicate found but policy forbids reuse".to_string(),
+            }),
         }
-
-        // No duplicate found or policy allows new task, create new download
-        let task_id = self.add_download(url.to_string(), target_path.to_path_buf()).await?;
-        Ok(DuplicateResult::NewTask(task_id))
     }
 
     async fn verify_task_validity(&self, task_id: &TaskId) -> Result<bool> {
@@ -273,18 +486,30 @@ impl DownloadManager for BasicDownloadManager {
         url: &str,
         target_path: &Path,
     ) -> Result<Vec<TaskId>> {
-        let mut candidates = Vec::new();
         let tasks = self.tasks.read().await;
 
-        // Look for exact matches first
-        for task in tasks.values() {
-            if task.url == url && task.target_path == target_path {
-                candidates.push(task.id);
-            }
-        }
-
         // For BasicDownloadManager, we don't do complex duplicate detection
         // Just return exact matches
-        Ok(candidates)
+        let mut candidates: Vec<DownloadTask> = tasks.values()
+            .filter(|task| task.url == url && task.target_path == target_path)
+            .cloned()
+            .collect();
+
+        ListOrder::CreatedDesc.sort(&mut candidates);
+        Ok(candidates.into_iter().map(|task| task.id).collect())
+    }
+
+    async fn set_global_download_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.bandwidth.set_global_limit(bytes_per_sec).await;
+        Ok(())
+    }
+
+    async fn set_task_download_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        if !self.tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
+        }
+
+        self.bandwidth.set_task_limit(task_id, bytes_per_sec).await;
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,529 @@
+//! Builder for [`PersistentAria2Manager`]
+
+use crate::Result;
+use crate::error::DownloadError;
+use crate::backend::{Aria2Backend, Aria2RpcClient, TimeoutBackend};
+use crate::backend::aria2_notifications::websocket_url;
+#[cfg(feature = "sftp")]
+use crate::backend::{SchemeRouter, SftpBackend};
+use crate::aria2_supervisor::{Aria2Supervisor, SupervisorConfig};
+use crate::traits::DownloadBackend;
+use crate::hooks::ScanHook;
+use crate::services::{DuplicateDetector, TaskRepository, FieldCipher};
+use crate::manager::config::ManagerConfig;
+use crate::manager::persistent_aria2::PersistentAria2Manager;
+use crate::models::{RetryPolicy, UrlPolicy, ContentPolicy, SegmentDefaults, FileAllocation, GcPolicy, TrashPolicy, RpcTimeouts, DownloadProfile, AdmissionLimits, TorrentSettings};
+use crate::services::speed_smoother::DEFAULT_SMOOTHING_WINDOW;
+use crate::services::progress_history::DEFAULT_HISTORY_CAPACITY;
+use crate::services::task_cache::DEFAULT_CACHE_TTL;
+use crate::services::isolated_handler::DEFAULT_HANDLER_TIMEOUT;
+use serde_json::{json, Map};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Step-by-step configuration of a [`PersistentAria2Manager`]
+///
+/// Unset values fall back to the same defaults as [`PersistentAria2Manager::new`].
+pub struct PersistentAria2ManagerBuilder {
+    pub(crate) rpc_url: String,
+    pub(crate) secret: String,
+    pub(crate) rpc_timeouts: RpcTimeouts,
+    pub(crate) db_path: Option<PathBuf>,
+    pub(crate) poll_interval: Duration,
+    pub(crate) progress_save_interval: Duration,
+    pub(crate) speed_sample_interval: Duration,
+    pub(crate) smoothing_window: Duration,
+    pub(crate) history_capacity: usize,
+    pub(crate) persist_history: bool,
+    pub(crate) cache_ttl: Duration,
+    pub(crate) handler_timeout: Duration,
+    pub(crate) remove_faulty_handlers: bool,
+    pub(crate) max_concurrent_downloads: Option<u32>,
+    pub(crate) download_dir: PathBuf,
+    pub(crate) max_filename_length: usize,
+    pub(crate) profiles: BTreeMap<String, DownloadProfile>,
+    pub(crate) retry_policy: RetryPolicy,
+    pub(crate) hash_concurrency: usize,
+    pub(crate) url_policy: UrlPolicy,
+    pub(crate) content_policy: ContentPolicy,
+    pub(crate) segment_defaults: SegmentDefaults,
+    pub(crate) file_allocation: Option<FileAllocation>,
+    pub(crate) max_file_size: Option<u64>,
+    pub(crate) no_space_headroom: u64,
+    pub(crate) admission: AdmissionLimits,
+    pub(crate) torrent: TorrentSettings,
+    pub(crate) temp_files: bool,
+    pub(crate) temp_file_suffix: String,
+    pub(crate) gc_policy: GcPolicy,
+    pub(crate) trash_policy: TrashPolicy,
+    pub(crate) scanners: Vec<Arc<dyn ScanHook>>,
+    /// Index of tasks for duplicate checks, the metadata database when unset
+    pub(crate) duplicate_detector: Option<Arc<dyn DuplicateDetector>>,
+    /// Where tasks and progress are saved, the task database at `db_path` when unset
+    pub(crate) task_repository: Option<Arc<dyn TaskRepository>>,
+    /// Keep tasks and metadata in memory only, writing no database files
+    pub(crate) ephemeral: bool,
+    /// Encrypts URLs and metadata values at rest, stored in plaintext when unset
+    pub(crate) encryption: Option<FieldCipher>,
+    /// Where rejected files go, `quarantine` in the download directory by default
+    pub(crate) quarantine_dir: Option<PathBuf>,
+    pub(crate) supervisor: Option<Arc<Aria2Supervisor>>,
+    /// WebSocket endpoint of the aria2 notifications, set when building an aria2 backend
+    pub(crate) notification_url: Option<String>,
+    /// Client for aria2 calls outside the backend trait, set when building an aria2 backend
+    pub(crate) aria2_rpc: Option<Aria2RpcClient>,
+    /// known_hosts file of the SFTP backend, `~/.ssh/known_hosts` when unset
+    pub(crate) sftp_known_hosts: Option<PathBuf>,
+    notifications: bool,
+    backend: Option<Arc<dyn DownloadBackend>>,
+    supervisor_config: Option<SupervisorConfig>,
+}
+
+impl Default for PersistentAria2ManagerBuilder {
+    fn default() -> Self {
+        Self::from_config(ManagerConfig::default())
+    }
+}
+
+impl PersistentAria2ManagerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a loaded configuration
+    pub fn from_config(config: ManagerConfig) -> Self {
+        Self {
+            rpc_url: config.rpc_url,
+            secret: config.secret,
+            rpc_timeouts: config.rpc_timeouts,
+            db_path: config.db_path,
+            poll_interval: Duration::from_secs(config.poll_interval_secs),
+            progress_save_interval: Duration::from_secs(config.progress_save_interval_secs),
+            speed_sample_interval: Duration::from_secs(config.speed_sample_interval_secs),
+            smoothing_window: DEFAULT_SMOOTHING_WINDOW,
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            persist_history: false,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            handler_timeout: DEFAULT_HANDLER_TIMEOUT,
+            remove_faulty_handlers: false,
+            max_concurrent_downloads: config.max_concurrent_downloads,
+            download_dir: config.download_dir,
+            max_filename_length: config.max_filename_length,
+            profiles: config.profiles,
+            retry_policy: config.retry_policy,
+            hash_concurrency: config.hash_concurrency,
+            url_policy: UrlPolicy::default(),
+            content_policy: ContentPolicy::default(),
+            segment_defaults: config.segment_defaults,
+            file_allocation: config.file_allocation,
+            max_file_size: config.max_file_size,
+            no_space_headroom: config.no_space_headroom,
+            admission: config.admission,
+            torrent: config.torrent,
+            temp_files: config.download_to_temp_file,
+            temp_file_suffix: config.temp_file_suffix,
+            gc_policy: GcPolicy::default(),
+            trash_policy: TrashPolicy::default(),
+            scanners: Vec::new(),
+            duplicate_detector: None,
+            task_repository: None,
+            ephemeral: false,
+            encryption: None,
+            quarantine_dir: None,
+            supervisor: None,
+            notification_url: None,
+            aria2_rpc: None,
+            sftp_known_hosts: config.sftp_known_hosts,
+            notifications: true,
+            backend: None,
+            supervisor_config: None,
+        }
+    }
+
+    /// Set the aria2 JSON-RPC endpoint
+    pub fn rpc_url(mut self, rpc_url: impl Into<String>) -> Self {
+        self.rpc_url = rpc_url.into();
+        self
+    }
+
+    /// Set the aria2 RPC secret token
+    pub fn secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = secret.into();
+        self
+    }
+
+    /// Set how long to wait for the connection to aria2
+    pub fn rpc_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeouts.connect = timeout;
+        self
+    }
+
+    /// Set how long an aria2 call may take before it fails with `DownloadError::BackendTimeout`
+    pub fn rpc_request_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeouts.request = timeout;
+        self
+    }
+
+    /// Fail aria2 calls straight away for `cooldown` after `threshold` timeouts in a row
+    ///
+    /// A threshold of 0 disables the circuit breaker.
+    pub fn rpc_circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.rpc_timeouts = self.rpc_timeouts.circuit_breaker(threshold, cooldown);
+        self
+    }
+
+    /// Set the task database location
+    pub fn db_path(mut self, db_path: impl Into<PathBuf>) -> Self {
+        self.db_path = Some(db_path.into());
+        self
+    }
+
+    /// Set how often task status is polled from the backend
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set how often download progress is written to the database
+    pub fn progress_save_interval(mut self, progress_save_interval: Duration) -> Self {
+        self.progress_save_interval = progress_save_interval;
+        self
+    }
+
+    /// Set how often handlers receive the speeds of all active tasks
+    ///
+    /// Samples are taken on status polls, so intervals shorter than the
+    /// poll interval sample on every poll.
+    pub fn speed_sample_interval(mut self, speed_sample_interval: Duration) -> Self {
+        self.speed_sample_interval = speed_sample_interval;
+        self
+    }
+
+    /// Set the window over which download speed is averaged for smoothed progress
+    ///
+    /// A zero window reports the instant speed.
+    pub fn speed_smoothing_window(mut self, window: Duration) -> Self {
+        self.smoothing_window = window;
+        self
+    }
+
+    /// Set how many progress samples are kept per task for transfer graphs
+    ///
+    /// Zero disables the history.
+    pub fn progress_history(mut self, capacity: usize) -> Self {
+        self.history_capacity = capacity;
+        self
+    }
+
+    /// Keep the progress history in the database so it survives restarts
+    pub fn persist_progress_history(mut self, persist: bool) -> Self {
+        self.persist_history = persist;
+        self
+    }
+
+    /// Set how long task state and progress read from the backend are served from memory
+    ///
+    /// One second by default, zero reads every `get_task` and `get_progress`
+    /// from the backend.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Set how long an event handler call may take before it is aborted
+    ///
+    /// Ten seconds by default, zero lets handlers take as long as they like.
+    pub fn handler_timeout(mut self, timeout: Duration) -> Self {
+        self.handler_timeout = timeout;
+        self
+    }
+
+    /// Remove event handlers the first time they panic or time out
+    pub fn remove_faulty_handlers(mut self, remove: bool) -> Self {
+        self.remove_faulty_handlers = remove;
+        self
+    }
+
+    /// Set the maximum number of downloads aria2 runs at once
+    ///
+    /// Only applied to the aria2 backend created by the builder.
+    pub fn max_concurrent_downloads(mut self, max: u32) -> Self {
+        self.max_concurrent_downloads = Some(max);
+        self
+    }
+
+    /// Set the directory used by the convenience API when no target path is given
+    pub fn download_dir(mut self, download_dir: impl Into<PathBuf>) -> Self {
+        self.download_dir = download_dir.into();
+        self
+    }
+
+    /// Shorten names the convenience API takes from servers to at most `bytes`
+    pub fn max_filename_length(mut self, bytes: usize) -> Self {
+        self.max_filename_length = bytes;
+        self
+    }
+
+    /// Add a named download profile, replacing a saved profile of that name
+    ///
+    /// See [`PersistentAria2Manager::download_with_profile`].
+    pub fn profile(mut self, name: impl Into<String>, profile: DownloadProfile) -> Self {
+        self.profiles.insert(name.into(), profile);
+        self
+    }
+
+    /// Set the retry policy for tasks without their own policy
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set how many completed files are hashed at once for duplicate detection
+    pub fn hash_concurrency(mut self, limit: usize) -> Self {
+        self.hash_concurrency = limit;
+        self
+    }
+
+    /// Save tasks and progress in a custom repository instead of the task database
+    ///
+    /// `db_path` then only locates the crate-owned metadata database. The
+    /// repository is initialized when the manager is built.
+    pub fn task_repository(mut self, repository: Arc<dyn TaskRepository>) -> Self {
+        self.task_repository = Some(repository);
+        self
+    }
+
+    /// Keep tasks and metadata in memory only, without any database file
+    ///
+    /// Useful in CI and for callers who want the manager's handling of
+    /// retries, duplicates and events but nothing to survive a restart.
+    /// `db_path` is ignored; a repository set with
+    /// [`task_repository`](Self::task_repository) is still used.
+    pub fn ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = ephemeral;
+        self
+    }
+
+    /// Encrypt stored URLs and metadata values with `cipher`
+    ///
+    /// Covers the task URLs in the repository, the values of the metadata
+    /// database and the URLs of the default duplicate index. Values written
+    /// before encryption was enabled stay readable; after rotating keys, see
+    /// [`PersistentAria2Manager::reencrypt_stored_fields`].
+    pub fn encryption(mut self, cipher: FieldCipher) -> Self {
+        self.encryption = Some(cipher);
+        self
+    }
+
+    /// Look up and record tasks for duplicate checks with a custom detector
+    ///
+    /// By default tasks are indexed in the metadata database. The detector
+    /// is told about every new task, status change and removed task; the
+    /// tasks it returns are only reused while they still exist.
+    pub fn duplicate_detector(mut self, detector: Arc<dyn DuplicateDetector>) -> Self {
+        self.duplicate_detector = Some(detector);
+        self
+    }
+
+    /// Set the rules download URLs have to satisfy
+    pub fn url_policy(mut self, url_policy: UrlPolicy) -> Self {
+        self.url_policy = url_policy;
+        self
+    }
+
+    /// Scan every completed download before it is reported completed
+    ///
+    /// Scanners run in the order they were added; the first rejection moves
+    /// the file to the quarantine directory and fails the task.
+    pub fn scan_hook(mut self, scanner: Arc<dyn ScanHook>) -> Self {
+        self.scanners.push(scanner);
+        self
+    }
+
+    /// Set where files rejected by a scanner are moved
+    pub fn quarantine_dir(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.quarantine_dir = Some(directory.into());
+        self
+    }
+
+    /// Set the media types and file extensions downloads may have
+    pub fn content_policy(mut self, content_policy: ContentPolicy) -> Self {
+        self.content_policy = content_policy;
+        self
+    }
+
+    /// Receive status changes through aria2's WebSocket notifications
+    ///
+    /// Enabled by default for the aria2 backend created by the builder. The
+    /// poller still handles status changes whenever the WebSocket is
+    /// unavailable.
+    pub fn aria2_notifications(mut self, enabled: bool) -> Self {
+        self.notifications = enabled;
+        self
+    }
+
+    /// Use a custom download backend instead of connecting to aria2
+    pub fn backend(mut self, backend: Arc<dyn DownloadBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Run aria2c as a supervised child process instead of expecting a daemon
+    ///
+    /// The RPC URL and secret are taken from `config`. The process is
+    /// health-checked, restarted when it crashes and stopped on shutdown.
+    pub fn supervise_aria2(mut self, config: SupervisorConfig) -> Self {
+        self.rpc_url = config.rpc_url();
+        self.secret = config.secret.clone();
+        self.supervisor_config = Some(config);
+        self
+    }
+
+    /// Split downloads whose options leave segment settings unset like this
+    ///
+    /// Checked when the manager is built, see [`SegmentDefaults::validate`].
+    pub fn segment_defaults(mut self, defaults: SegmentDefaults) -> Self {
+        self.segment_defaults = defaults;
+        self
+    }
+
+    /// Allocate the files of downloads whose options leave it unset like this
+    pub fn file_allocation(mut self, allocation: FileAllocation) -> Self {
+        self.file_allocation = Some(allocation);
+        self
+    }
+
+    /// Abort downloads whose options leave it unset when their file is larger than `bytes`
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Resume downloads paused by a full disk once it has `bytes` free again
+    pub fn no_space_headroom(mut self, bytes: u64) -> Self {
+        self.no_space_headroom = bytes;
+        self
+    }
+
+    /// Limit how many unfinished tasks there are and how fast downloads are added
+    ///
+    /// Additions over a limit fail with `DownloadError::QueueFull` or wait,
+    /// depending on the limits' mode.
+    pub fn admission_limits(mut self, limits: AdmissionLimits) -> Self {
+        self.admission = limits;
+        self
+    }
+
+    /// Write downloads to a temporary file and rename it once complete
+    ///
+    /// Enabled by default, so half-written files never appear at the target
+    /// path. Stale temporary files are removed when the manager starts.
+    pub fn download_to_temp_file(mut self, enabled: bool) -> Self {
+        self.temp_files = enabled;
+        self
+    }
+
+    /// Check SFTP host keys against this known_hosts file instead of `~/.ssh/known_hosts`
+    ///
+    /// Only used with the `sftp` feature. Servers whose key is missing from
+    /// the file or differs from it are refused.
+    pub fn sftp_known_hosts(mut self, path: impl Into<PathBuf>) -> Self {
+        self.sftp_known_hosts = Some(path.into());
+        self
+    }
+
+    /// Set the suffix of temporary download files, `.part` by default
+    pub fn temp_file_suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.temp_file_suffix = suffix.into();
+        self
+    }
+
+    /// Set how [`run_gc`](PersistentAria2Manager::run_gc) treats stale and orphaned tasks
+    pub fn gc_policy(mut self, policy: GcPolicy) -> Self {
+        self.gc_policy = policy;
+        self
+    }
+
+    /// Set where [`delete_task`](PersistentAria2Manager::delete_task) moves trashed files and how long they are kept
+    pub fn trash_policy(mut self, policy: TrashPolicy) -> Self {
+        self.trash_policy = policy;
+        self
+    }
+
+    /// Set the BitTorrent and DHT settings passed to aria2
+    ///
+    /// Listen ports and DHT are command line options of a daemon started
+    /// with [`supervise_aria2`](Self::supervise_aria2); the other settings
+    /// are changed on any aria2 daemon the manager connects to.
+    pub fn torrent_settings(mut self, settings: TorrentSettings) -> Self {
+        self.torrent = settings;
+        self
+    }
+
+    /// Connect to the backend, restore persisted tasks and start the manager
+    pub async fn build(mut self) -> Result<PersistentAria2Manager> {
+        self.segment_defaults.validate()?;
+        self.torrent.validate()?;
+        if self.temp_files && self.temp_file_suffix.is_empty() {
+            return Err(DownloadError::Config("The temporary file suffix must not be empty".to_string()));
+        }
+
+        let backend = match self.backend.take() {
+            Some(backend) => backend,
+            None => {
+                if let Some(mut config) = self.supervisor_config.take() {
+                    config.extra_args.extend(self.torrent.startup_args());
+                    let supervisor = Arc::new(Aria2Supervisor::new(config));
+                    supervisor.start().await?;
+                    self.supervisor = Some(supervisor);
+                }
+
+                let aria2 = Aria2Backend::with_timeouts(self.rpc_url.clone(), self.secret.clone(), self.rpc_timeouts).await?;
+
+                if let Some(max) = self.max_concurrent_downloads {
+                    let mut options = Map::new();
+                    options.insert("max-concurrent-downloads".to_string(), json!(max.to_string()));
+                    aria2.rpc().change_global_option(options).await?;
+                }
+
+                if self.supervisor.is_none() && self.torrent.has_startup_options() {
+                    log::warn!("Listen ports and DHT settings only apply to an aria2 daemon started by the manager");
+                }
+                let torrent_options = self.torrent.to_aria2_options();
+                if !torrent_options.is_empty() {
+                    aria2.rpc().change_global_option(torrent_options).await?;
+                }
+
+                if self.notifications {
+                    self.notification_url = websocket_url(&self.rpc_url);
+                }
+                self.aria2_rpc = Some(Aria2RpcClient::with_timeouts(self.rpc_url.clone(), Some(self.secret.clone()), &self.rpc_timeouts));
+
+                // A wedged daemon fails calls instead of hanging them
+                let aria2: Arc<dyn DownloadBackend> = Arc::new(TimeoutBackend::new(Arc::new(aria2), self.rpc_timeouts));
+
+                // aria2 only speaks SFTP when built with libssh2
+                #[cfg(feature = "sftp")]
+                let backend: Arc<dyn DownloadBackend> = {
+                    let sftp = match &self.sftp_known_hosts {
+                        Some(path) => SftpBackend::new().known_hosts(path),
+                        None => SftpBackend::new(),
+                    };
+                    Arc::new(SchemeRouter::new(aria2).route("sftp", Arc::new(sftp)))
+                };
+                #[cfg(not(feature = "sftp"))]
+                if self.sftp_known_hosts.is_some() {
+                    log::warn!("The SFTP known_hosts file only applies with the sftp feature");
+                }
+                #[cfg(not(feature = "sftp"))]
+                let backend = aria2;
+
+                backend
+            }
+        };
+
+        PersistentAria2Manager::open(backend, self).await
+    }
+}
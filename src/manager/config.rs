@@ -0,0 +1,289 @@
+//! Configuration for [`PersistentAria2Manager`](crate::PersistentAria2Manager)
+//!
+//! Settings can be built in code, read from `BURNCLOUD_*` environment
+//! variables, or loaded from a TOML file with the `toml-config` feature.
+
+use crate::Result;
+use crate::error::DownloadError;
+use crate::models::{RetryPolicy, SegmentDefaults, FileAllocation, RpcTimeouts, DownloadProfile, AdmissionLimits, TorrentSettings};
+use crate::services::hash_calculator::DEFAULT_HASH_CONCURRENCY;
+use crate::services::no_space_watch::DEFAULT_NO_SPACE_HEADROOM;
+use crate::backend::part_file::DEFAULT_PART_SUFFIX;
+use crate::probe::DEFAULT_MAX_FILENAME_LENGTH;
+use crate::manager::persistent_aria2::{
+    ARIA2_RPC_URL, ARIA2_RPC_SECRET, STATUS_POLL_INTERVAL_SECS,
+    PROGRESS_SAVE_INTERVAL_SECS, SPEED_SAMPLE_INTERVAL_SECS, DEFAULT_DOWNLOAD_DIR,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Environment variable overriding [`ManagerConfig::rpc_url`]
+pub const ENV_RPC_URL: &str = "BURNCLOUD_ARIA2_RPC_URL";
+/// Environment variable overriding [`ManagerConfig::secret`]
+pub const ENV_SECRET: &str = "BURNCLOUD_ARIA2_SECRET";
+/// Environment variable overriding [`ManagerConfig::db_path`]
+pub const ENV_DB_PATH: &str = "BURNCLOUD_DOWNLOAD_DB_PATH";
+/// Environment variable overriding [`ManagerConfig::poll_interval_secs`]
+pub const ENV_POLL_INTERVAL_SECS: &str = "BURNCLOUD_POLL_INTERVAL_SECS";
+/// Environment variable overriding [`ManagerConfig::progress_save_interval_secs`]
+pub const ENV_PROGRESS_SAVE_INTERVAL_SECS: &str = "BURNCLOUD_PROGRESS_SAVE_INTERVAL_SECS";
+/// Environment variable overriding [`ManagerConfig::speed_sample_interval_secs`]
+pub const ENV_SPEED_SAMPLE_INTERVAL_SECS: &str = "BURNCLOUD_SPEED_SAMPLE_INTERVAL_SECS";
+/// Environment variable overriding [`ManagerConfig::max_concurrent_downloads`]
+pub const ENV_MAX_CONCURRENT_DOWNLOADS: &str = "BURNCLOUD_MAX_CONCURRENT_DOWNLOADS";
+/// Environment variable overriding [`ManagerConfig::download_dir`]
+pub const ENV_DOWNLOAD_DIR: &str = "BURNCLOUD_DOWNLOAD_DIR";
+/// Environment variable overriding the retry policy's `max_attempts`
+pub const ENV_MAX_RETRIES: &str = "BURNCLOUD_MAX_RETRIES";
+/// Environment variable overriding [`ManagerConfig::hash_concurrency`]
+pub const ENV_HASH_CONCURRENCY: &str = "BURNCLOUD_HASH_CONCURRENCY";
+/// Environment variable overriding the default number of segments
+pub const ENV_SEGMENTS: &str = "BURNCLOUD_SEGMENTS";
+/// Environment variable overriding the default connections per server
+pub const ENV_MAX_CONNECTIONS_PER_SERVER: &str = "BURNCLOUD_MAX_CONNECTIONS_PER_SERVER";
+/// Environment variable overriding the default minimum segment size in bytes
+pub const ENV_MIN_SPLIT_SIZE: &str = "BURNCLOUD_MIN_SPLIT_SIZE";
+/// Environment variable overriding [`ManagerConfig::file_allocation`], e.g. `falloc`
+pub const ENV_FILE_ALLOCATION: &str = "BURNCLOUD_FILE_ALLOCATION";
+/// Environment variable overriding [`ManagerConfig::max_file_size`] in bytes
+pub const ENV_MAX_FILE_SIZE: &str = "BURNCLOUD_MAX_FILE_SIZE";
+/// Environment variable overriding [`ManagerConfig::no_space_headroom`] in bytes
+pub const ENV_NO_SPACE_HEADROOM: &str = "BURNCLOUD_NO_SPACE_HEADROOM";
+/// Environment variable overriding [`ManagerConfig::max_filename_length`] in bytes
+pub const ENV_MAX_FILENAME_LENGTH: &str = "BURNCLOUD_MAX_FILENAME_LENGTH";
+/// Environment variable overriding the admission limit on unfinished tasks
+pub const ENV_MAX_QUEUED_TASKS: &str = "BURNCLOUD_MAX_QUEUED_TASKS";
+/// Environment variable overriding the admission limit on additions per second
+pub const ENV_MAX_ADDITIONS_PER_SECOND: &str = "BURNCLOUD_MAX_ADDITIONS_PER_SECOND";
+/// Environment variable overriding the admission mode, `reject` or `wait`
+pub const ENV_ADMISSION_MODE: &str = "BURNCLOUD_ADMISSION_MODE";
+/// Environment variable overriding the BitTorrent listen ports, e.g. `6881-6999`
+pub const ENV_BT_LISTEN_PORTS: &str = "BURNCLOUD_BT_LISTEN_PORTS";
+/// Environment variable turning the DHT on or off, `true` or `false`
+pub const ENV_BT_DHT: &str = "BURNCLOUD_BT_DHT";
+/// Environment variable overriding the DHT listen ports
+pub const ENV_BT_DHT_LISTEN_PORTS: &str = "BURNCLOUD_BT_DHT_LISTEN_PORTS";
+/// Environment variable requiring encrypted peer connections, `true` or `false`
+pub const ENV_BT_REQUIRE_ENCRYPTION: &str = "BURNCLOUD_BT_REQUIRE_ENCRYPTION";
+/// Environment variable overriding the most peers per torrent
+pub const ENV_BT_MAX_PEERS: &str = "BURNCLOUD_BT_MAX_PEERS";
+/// Environment variable overriding the share ratio seeding stops at
+pub const ENV_BT_SEED_RATIO: &str = "BURNCLOUD_BT_SEED_RATIO";
+/// Environment variable overriding how long completed torrents are seeded, in seconds
+pub const ENV_BT_SEED_TIME_SECS: &str = "BURNCLOUD_BT_SEED_TIME_SECS";
+/// Environment variable overriding the aria2 connect timeout in seconds
+pub const ENV_RPC_CONNECT_TIMEOUT_SECS: &str = "BURNCLOUD_RPC_CONNECT_TIMEOUT_SECS";
+/// Environment variable overriding the aria2 request timeout in seconds
+pub const ENV_RPC_REQUEST_TIMEOUT_SECS: &str = "BURNCLOUD_RPC_REQUEST_TIMEOUT_SECS";
+/// Environment variable overriding the known_hosts file SFTP host keys are checked against
+pub const ENV_SFTP_KNOWN_HOSTS: &str = "BURNCLOUD_SFTP_KNOWN_HOSTS";
+
+/// Settings for a persistent download manager
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ManagerConfig {
+    /// aria2 JSON-RPC endpoint
+    pub rpc_url: String,
+    /// aria2 RPC secret token
+    pub secret: String,
+    /// Timeouts and circuit breaking of aria2 calls
+    pub rpc_timeouts: RpcTimeouts,
+    /// Task database location, `None` for the default database
+    pub db_path: Option<PathBuf>,
+    /// How often task status is polled from the backend
+    pub poll_interval_secs: u64,
+    /// How often download progress is written to the database
+    pub progress_save_interval_secs: u64,
+    /// How often event handlers receive the speeds of all active tasks
+    pub speed_sample_interval_secs: u64,
+    /// Maximum number of downloads aria2 runs at once, `None` keeps the daemon setting
+    pub max_concurrent_downloads: Option<u32>,
+    /// Directory used by the convenience API when no target path is given
+    pub download_dir: PathBuf,
+    /// Longest name in bytes the convenience API saves files under, longer server names are shortened
+    pub max_filename_length: usize,
+    /// Retry policy for tasks without their own policy
+    pub retry_policy: RetryPolicy,
+    /// How many completed files are hashed at once for duplicate detection
+    pub hash_concurrency: usize,
+    /// Segment settings for downloads whose options leave them unset
+    pub segment_defaults: SegmentDefaults,
+    /// File allocation for downloads whose options leave it unset, `None` keeps the engine default
+    pub file_allocation: Option<FileAllocation>,
+    /// Download to `<target><temp_file_suffix>` and rename the file once complete
+    pub download_to_temp_file: bool,
+    /// Largest file in bytes downloads whose options leave it unset may fetch, `None` for no limit
+    pub max_file_size: Option<u64>,
+    /// Free bytes a full disk needs again before the downloads paused by it resume
+    pub no_space_headroom: u64,
+    /// Limits on adding new downloads
+    pub admission: AdmissionLimits,
+    /// BitTorrent and DHT settings passed to aria2
+    pub torrent: TorrentSettings,
+    /// Suffix of the temporary download files
+    pub temp_file_suffix: String,
+    /// Named download roots with their defaults, e.g. `[profiles.models]` in TOML
+    pub profiles: BTreeMap<String, DownloadProfile>,
+    /// known_hosts file SFTP host keys are checked against, `None` for `~/.ssh/known_hosts`
+    pub sftp_known_hosts: Option<PathBuf>,
+}
+
+impl Default for ManagerConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: ARIA2_RPC_URL.to_string(),
+            secret: ARIA2_RPC_SECRET.to_string(),
+            rpc_timeouts: RpcTimeouts::default(),
+            db_path: None,
+            poll_interval_secs: STATUS_POLL_INTERVAL_SECS,
+            progress_save_interval_secs: PROGRESS_SAVE_INTERVAL_SECS,
+            speed_sample_interval_secs: SPEED_SAMPLE_INTERVAL_SECS,
+            max_concurrent_downloads: None,
+            download_dir: PathBuf::from(DEFAULT_DOWNLOAD_DIR),
+            max_filename_length: DEFAULT_MAX_FILENAME_LENGTH,
+            retry_policy: RetryPolicy::default(),
+            hash_concurrency: DEFAULT_HASH_CONCURRENCY,
+            segment_defaults: SegmentDefaults::default(),
+            file_allocation: None,
+            download_to_temp_file: true,
+            max_file_size: None,
+            no_space_headroom: DEFAULT_NO_SPACE_HEADROOM,
+            admission: AdmissionLimits::default(),
+            torrent: TorrentSettings::default(),
+            temp_file_suffix: DEFAULT_PART_SUFFIX.to_string(),
+            profiles: BTreeMap::new(),
+            sftp_known_hosts: None,
+        }
+    }
+}
+
+impl ManagerConfig {
+    /// Default settings overridden by any `BURNCLOUD_*` environment variables
+    pub fn from_env() -> Result<Self> {
+        Self::default().apply_env()
+    }
+
+    /// Override settings with any `BURNCLOUD_*` environment variables that are set
+    pub fn apply_env(mut self) -> Result<Self> {
+        if let Some(rpc_url) = env_var(ENV_RPC_URL) {
+            self.rpc_url = rpc_url;
+        }
+        if let Some(secret) = env_var(ENV_SECRET) {
+            self.secret = secret;
+        }
+        if let Some(secs) = parse_env_var(ENV_RPC_CONNECT_TIMEOUT_SECS)? {
+            self.rpc_timeouts.connect = Duration::from_secs(secs);
+        }
+        if let Some(secs) = parse_env_var(ENV_RPC_REQUEST_TIMEOUT_SECS)? {
+            self.rpc_timeouts.request = Duration::from_secs(secs);
+        }
+        if let Some(db_path) = env_var(ENV_DB_PATH) {
+            self.db_path = Some(PathBuf::from(db_path));
+        }
+        if let Some(known_hosts) = env_var(ENV_SFTP_KNOWN_HOSTS) {
+            self.sftp_known_hosts = Some(PathBuf::from(known_hosts));
+        }
+        if let Some(secs) = parse_env_var(ENV_POLL_INTERVAL_SECS)? {
+            self.poll_interval_secs = secs;
+        }
+        if let Some(secs) = parse_env_var(ENV_PROGRESS_SAVE_INTERVAL_SECS)? {
+            self.progress_save_interval_secs = secs;
+        }
+        if let Some(secs) = parse_env_var(ENV_SPEED_SAMPLE_INTERVAL_SECS)? {
+            self.speed_sample_interval_secs = secs;
+        }
+        if let Some(max) = parse_env_var(ENV_MAX_CONCURRENT_DOWNLOADS)? {
+            self.max_concurrent_downloads = Some(max);
+        }
+        if let Some(download_dir) = env_var(ENV_DOWNLOAD_DIR) {
+            self.download_dir = PathBuf::from(download_dir);
+        }
+        if let Some(length) = parse_env_var(ENV_MAX_FILENAME_LENGTH)? {
+            self.max_filename_length = length;
+        }
+        if let Some(max_attempts) = parse_env_var(ENV_MAX_RETRIES)? {
+            self.retry_policy.max_attempts = max_attempts;
+        }
+        if let Some(limit) = parse_env_var(ENV_HASH_CONCURRENCY)? {
+            self.hash_concurrency = limit;
+        }
+        if let Some(segments) = parse_env_var(ENV_SEGMENTS)? {
+            self.segment_defaults.segments = Some(segments);
+        }
+        if let Some(connections) = parse_env_var(ENV_MAX_CONNECTIONS_PER_SERVER)? {
+            self.segment_defaults.max_connections_per_server = Some(connections);
+        }
+        if let Some(size) = parse_env_var(ENV_MIN_SPLIT_SIZE)? {
+            self.segment_defaults.min_split_size = Some(size);
+        }
+        if let Some(allocation) = parse_env_var(ENV_FILE_ALLOCATION)? {
+            self.file_allocation = Some(allocation);
+        }
+        if let Some(bytes) = parse_env_var(ENV_MAX_FILE_SIZE)? {
+            self.max_file_size = Some(bytes);
+        }
+        if let Some(bytes) = parse_env_var(ENV_NO_SPACE_HEADROOM)? {
+            self.no_space_headroom = bytes;
+        }
+        if let Some(max) = parse_env_var(ENV_MAX_QUEUED_TASKS)? {
+            self.admission.max_queued_tasks = Some(max);
+        }
+        if let Some(max) = parse_env_var(ENV_MAX_ADDITIONS_PER_SECOND)? {
+            self.admission.max_additions_per_second = Some(max);
+        }
+        if let Some(mode) = parse_env_var(ENV_ADMISSION_MODE)? {
+            self.admission.mode = mode;
+        }
+        if let Some(ports) = parse_env_var(ENV_BT_LISTEN_PORTS)? {
+            self.torrent.listen_ports = Some(ports);
+        }
+        if let Some(enabled) = parse_env_var(ENV_BT_DHT)? {
+            self.torrent.dht = Some(enabled);
+        }
+        if let Some(ports) = parse_env_var(ENV_BT_DHT_LISTEN_PORTS)? {
+            self.torrent.dht_listen_ports = Some(ports);
+        }
+        if let Some(required) = parse_env_var(ENV_BT_REQUIRE_ENCRYPTION)? {
+            self.torrent.require_encryption = Some(required);
+        }
+        if let Some(max) = parse_env_var(ENV_BT_MAX_PEERS)? {
+            self.torrent.max_peers = Some(max);
+        }
+        if let Some(ratio) = parse_env_var(ENV_BT_SEED_RATIO)? {
+            self.torrent.seed_ratio = Some(ratio);
+        }
+        if let Some(secs) = parse_env_var(ENV_BT_SEED_TIME_SECS)? {
+            self.torrent.seed_time = Some(Duration::from_secs(secs));
+        }
+
+        Ok(self)
+    }
+
+    /// Parse settings from TOML, using defaults for missing keys
+    #[cfg(feature = "toml-config")]
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| DownloadError::Config(e.to_string()))
+    }
+
+    /// Load settings from a TOML file, using defaults for missing keys
+    #[cfg(feature = "toml-config")]
+    pub async fn from_toml_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path.as_ref()).await?;
+        Self::from_toml_str(&content)
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn parse_env_var<T: FromStr>(name: &str) -> Result<Option<T>> {
+    env_var(name)
+        .map(|value| value.parse().map_err(|_| {
+            DownloadError::Config(format!("{} has an invalid value: {}", name, value))
+        }))
+        .transpose()
+}
@@ -0,0 +1,95 @@
+//! Periodic garbage collection of stale tasks
+//!
+//! A background task calls [`PersistentAria2Manager::run_gc`] at a fixed
+//! interval, so downloads aria2 silently dropped are failed or requeued
+//! without anyone having to notice them.
+
+use crate::manager::PersistentAria2Manager;
+use crate::models::GcReport;
+use crate::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, MissedTickBehavior};
+
+/// How often garbage collection runs by default
+const DEFAULT_GC_INTERVAL_SECS: u64 = 15 * 60;
+
+/// Runs garbage collection on a manager at a fixed interval
+pub struct StaleTaskCollector {
+    manager: Arc<PersistentAria2Manager>,
+    interval: Duration,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl StaleTaskCollector {
+    /// Create a collector for `manager`, using the manager's [`GcPolicy`](crate::models::GcPolicy)
+    pub fn new(manager: Arc<PersistentAria2Manager>) -> Self {
+        Self {
+            manager,
+            interval: Duration::from_secs(DEFAULT_GC_INTERVAL_SECS),
+            handle: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Set how often the background task runs garbage collection
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Run garbage collection once
+    pub async fn run(&self) -> Result<GcReport> {
+        self.manager.run_gc().await
+    }
+
+    /// Start the background task
+    ///
+    /// The first run happens one interval after starting, giving restored
+    /// downloads time to report progress.
+    pub async fn start(&self) {
+        let mut handle_guard = self.handle.lock().await;
+        if handle_guard.is_some() {
+            return;
+        }
+
+        let manager = self.manager.clone();
+        let shutdown = self.shutdown.clone();
+        let period = self.interval.max(Duration::from_millis(1));
+
+        *handle_guard = Some(tokio::spawn(async move {
+            let mut ticker = interval(period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            ticker.tick().await;
+
+            log::info!("Starting stale task collector");
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        if let Err(e) = manager.run_gc().await {
+                            log::error!("Failed to collect stale tasks: {}", e);
+                        }
+                    }
+                    _ = shutdown.notified() => {
+                        log::info!("Stale task collector shutting down");
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Stop the background task
+    pub async fn shutdown(&self) {
+        self.shutdown.notify_one();
+
+        if let Some(handle) = self.handle.lock().await.take() {
+            let _ = handle.await;
+        }
+    }
+}
\ No newline at end of file
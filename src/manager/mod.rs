@@ -1,5 +1,11 @@
 pub mod basic;
 pub mod persistent_aria2;
+pub mod builder;
+pub mod config;
+pub mod gc;
 
 pub use basic::BasicDownloadManager;
-pub use persistent_aria2::PersistentAria2Manager;
\ No newline at end of file
+pub use persistent_aria2::{PersistentAria2Manager, PersistentDownloadManager};
+pub use builder::PersistentAria2ManagerBuilder;
+pub use config::ManagerConfig;
+pub use gc::StaleTaskCollector;
\ No newline at end of file
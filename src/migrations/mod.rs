@@ -0,0 +1,267 @@
+//! Schema migrations of the crate-owned SQLite database
+//!
+//! Every table the crate keeps next to the task database is created by one
+//! of the numbered [`MIGRATIONS`]. Applied versions are recorded in the
+//! `schema_version` table, so each migration runs once per database. Pools
+//! opened through the crate apply pending migrations before any store uses
+//! them, and [`PersistentAria2Manager`](crate::PersistentAria2Manager)
+//! migrates its database on startup, so no separate tool has to be run
+//! after an upgrade.
+//!
+//! Migrations are append-only: a released migration is never changed, new
+//! schema goes into a new version. The first versions only use `IF NOT
+//! EXISTS` statements, so databases created before migrations existed are
+//! adopted as they are.
+
+use crate::error::DownloadError;
+use crate::services::task_metadata_store::{connect_pool, db_error, unix_now};
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// One versioned change of the schema
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// Statements run in order, in one transaction
+    pub statements: &'static [&'static str],
+}
+
+/// All migrations, in version order
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "task metadata, GID mapping, target paths and download validators",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS task_metadata (
+                task_id TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (task_id, key)
+            )",
+            "CREATE TABLE IF NOT EXISTS task_gid_mapping (
+                task_id TEXT PRIMARY KEY,
+                gid TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS task_target_paths (
+                target_path TEXT PRIMARY KEY,
+                task_id TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS download_validators (
+                url TEXT NOT NULL,
+                target_path TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                validators TEXT NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (url, target_path)
+            )",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "task state journal",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS task_journal (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                state TEXT NOT NULL,
+                recorded_at INTEGER NOT NULL
+            )",
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "progress history",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS progress_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                recorded_at INTEGER NOT NULL,
+                downloaded_bytes INTEGER NOT NULL,
+                speed_bps INTEGER NOT NULL
+            )",
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "scheduled downloads",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS scheduled_downloads (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                url TEXT NOT NULL,
+                target_path TEXT NOT NULL,
+                spec TEXT NOT NULL,
+                next_run INTEGER NOT NULL,
+                last_task_id TEXT,
+                created_at INTEGER NOT NULL
+            )",
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "task groups",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS task_groups (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            "CREATE TABLE IF NOT EXISTS task_group_members (
+                group_id INTEGER NOT NULL,
+                task_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (group_id, task_id)
+            )",
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "URL hash index for duplicate checks",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS task_url_hashes (
+                task_id TEXT PRIMARY KEY,
+                url_hash TEXT NOT NULL,
+                target_path TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            // Not unique, tasks added with `DuplicatePolicy::AllowDuplicate` share a key
+            "CREATE INDEX IF NOT EXISTS idx_task_url_hashes_lookup ON task_url_hashes (url_hash, target_path)",
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "bytes downloaded per source host",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS domain_usage (
+                host TEXT PRIMARY KEY,
+                bytes_downloaded INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "URL and status of tasks in the duplicate index",
+        statements: &[
+            "ALTER TABLE task_url_hashes ADD COLUMN url TEXT",
+            "ALTER TABLE task_url_hashes ADD COLUMN status TEXT",
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "named download profiles",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS download_profiles (
+                name TEXT PRIMARY KEY,
+                profile TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "owners of tasks",
+        statements: &[
+            "CREATE TABLE IF NOT EXISTS task_owners (
+                task_id TEXT PRIMARY KEY,
+                owner TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            "CREATE INDEX IF NOT EXISTS idx_task_owners_owner ON task_owners(owner)",
+        ],
+    },
+];
+
+/// Get the version a fully migrated database has
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+/// Bring the SQLite file at `path` up to date, creating it if needed
+///
+/// Returns the versions applied now, empty when the schema was current.
+pub async fn migrate(path: &Path) -> Result<Vec<u32>, DownloadError> {
+    let pool = connect_pool(path).await?;
+    let applied = apply(&pool).await?;
+    pool.close().await;
+    Ok(applied)
+}
+
+/// Apply the migrations a database has not seen yet
+///
+/// Returns the versions applied now, in order. Each migration runs in its
+/// own transaction; when another connection applied it first its record
+/// is kept.
+pub async fn apply(pool: &SqlitePool) -> Result<Vec<u32>, DownloadError> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )"
+    )
+    .execute(pool)
+    .await
+    .map_err(db_error)?;
+
+    let done = applied_versions(pool).await?;
+    if let Some(newest) = done.iter().max().filter(|newest| **newest > latest_version()) {
+        log::warn!("Database schema version {} is newer than this version of the crate ({})", newest, latest_version());
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| !done.contains(&migration.version)) {
+        let mut tx = pool.begin().await.map_err(db_error)?;
+        for statement in migration.statements {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| DownloadError::DatabaseError(format!(
+                    "Migration {} ({}) failed: {}", migration.version, migration.description, e
+                )))?;
+        }
+        sqlx::query(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)
+             ON CONFLICT(version) DO NOTHING"
+        )
+        .bind(migration.version as i64)
+        .bind(migration.description)
+        .bind(unix_now())
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+        tx.commit().await.map_err(db_error)?;
+
+        log::debug!("Applied database migration {}: {}", migration.version, migration.description);
+        applied.push(migration.version);
+    }
+
+    Ok(applied)
+}
+
+/// Get the highest migration version applied to a database, 0 for none
+pub async fn current_version(pool: &SqlitePool) -> Result<u32, DownloadError> {
+    Ok(applied_versions(pool).await?.into_iter().max().unwrap_or(0))
+}
+
+async fn applied_versions(pool: &SqlitePool) -> Result<HashSet<u32>, DownloadError> {
+    let exists = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'")
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?
+        .is_some();
+    if !exists {
+        return Ok(HashSet::new());
+    }
+
+    let rows = sqlx::query("SELECT version FROM schema_version")
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+    Ok(rows.into_iter().map(|row| row.get::<i64, _>("version") as u32).collect())
+}
\ No newline at end of file
@@ -0,0 +1,83 @@
+//! Admission control of new downloads
+//!
+//! A caller adding thousands of tasks a second can swamp aria2 and the
+//! task database. Limits on the unfinished tasks and on the additions per
+//! second either reject further additions or hold them back until there
+//! is room again.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens to an addition over the limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AdmissionMode {
+    /// Fail the addition with `DownloadError::QueueFull`
+    #[default]
+    Reject,
+    /// Make the addition wait until it fits within the limits
+    Wait,
+}
+
+impl fmt::Display for AdmissionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AdmissionMode::Reject => "reject",
+            AdmissionMode::Wait => "wait",
+        })
+    }
+}
+
+impl FromStr for AdmissionMode {
+    type Err = String;
+
+    /// Parse `reject` or `wait`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "reject" => Ok(AdmissionMode::Reject),
+            "wait" => Ok(AdmissionMode::Wait),
+            _ => Err(format!("Unknown admission mode: {}", value)),
+        }
+    }
+}
+
+/// Limits on adding new downloads, none by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdmissionLimits {
+    /// Most unfinished tasks at once, `None` for no limit
+    pub max_queued_tasks: Option<usize>,
+    /// Most downloads added within any second, `None` for no limit
+    pub max_additions_per_second: Option<u32>,
+    /// Whether additions over a limit fail or wait
+    pub mode: AdmissionMode,
+}
+
+impl AdmissionLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `max` unfinished tasks
+    pub fn max_queued_tasks(mut self, max: usize) -> Self {
+        self.max_queued_tasks = Some(max);
+        self
+    }
+
+    /// Allow at most `max` additions within any second
+    pub fn max_additions_per_second(mut self, max: u32) -> Self {
+        self.max_additions_per_second = Some(max);
+        self
+    }
+
+    /// Set whether additions over a limit fail or wait
+    pub fn mode(mut self, mode: AdmissionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Check if any limit is set
+    pub fn is_limited(&self) -> bool {
+        self.max_queued_tasks.is_some() || self.max_additions_per_second.is_some()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+//! Outcome of a conditional download
+
+use crate::types::TaskId;
+use serde::{Deserialize, Serialize};
+
+/// What a download that only fetches changed files did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConditionalDownload {
+    /// The remote file changed or was not downloaded before, this task fetches it
+    Started(TaskId),
+    /// The file at the target path is the version the server has, no task was created
+    UpToDate,
+}
+
+impl ConditionalDownload {
+    /// Get the task fetching the file, if one was started
+    pub fn task_id(&self) -> Option<TaskId> {
+        match self {
+            Self::Started(task_id) => Some(*task_id),
+            Self::UpToDate => None,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,139 @@
+//! Content type and extension policy
+//!
+//! Limits what kind of files a manager downloads, for managers fed URLs by
+//! end users. Media types are checked against the `Content-Type` the server
+//! reports before the download starts, extensions against the name of the
+//! file being written.
+
+use crate::error::DownloadError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Allowed and blocked media types and file extensions
+///
+/// Blocked entries always win. An empty allow list accepts everything that
+/// is not blocked, a non-empty one only what it names. Media types match
+/// without parameters, `image/*` covers every image type; extensions are
+/// given without the leading dot and match the last one of the file name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContentPolicy {
+    /// Accepted media types, lowercase
+    pub allowed_mime_types: Vec<String>,
+    /// Rejected media types, lowercase
+    pub blocked_mime_types: Vec<String>,
+    /// Accepted file extensions, lowercase
+    pub allowed_extensions: Vec<String>,
+    /// Rejected file extensions, lowercase
+    pub blocked_extensions: Vec<String>,
+}
+
+impl ContentPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept only the given media types
+    pub fn allow_mime_types<I, S>(mut self, mime_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_mime_types = lowercase(mime_types);
+        self
+    }
+
+    /// Reject the given media types
+    pub fn block_mime_types<I, S>(mut self, mime_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.blocked_mime_types = lowercase(mime_types);
+        self
+    }
+
+    /// Accept only files with the given extensions
+    pub fn allow_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_extensions = lowercase(extensions.into_iter().map(trim_dot));
+        self
+    }
+
+    /// Reject files with the given extensions
+    pub fn block_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.blocked_extensions = lowercase(extensions.into_iter().map(trim_dot));
+        self
+    }
+
+    /// Check if the policy restricts nothing
+    pub fn is_empty(&self) -> bool {
+        self.allowed_mime_types.is_empty() && self.blocked_mime_types.is_empty()
+            && self.allowed_extensions.is_empty() && self.blocked_extensions.is_empty()
+    }
+
+    /// Check if the policy restricts media types
+    pub fn checks_mime_types(&self) -> bool {
+        !self.allowed_mime_types.is_empty() || !self.blocked_mime_types.is_empty()
+    }
+
+    /// Check a `Content-Type` header value
+    ///
+    /// Fails with [`DownloadError::ContentNotAllowed`] naming the media type.
+    pub fn check_mime_type(&self, content_type: &str) -> Result<(), DownloadError> {
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        let matches = |pattern: &String| match pattern.strip_suffix("/*") {
+            Some(top_level) => mime_type.split('/').next() == Some(top_level),
+            None => *pattern == mime_type,
+        };
+
+        if self.blocked_mime_types.iter().any(matches) {
+            return Err(DownloadError::ContentNotAllowed(format!("media type '{}' is blocked", mime_type)));
+        }
+        if !self.allowed_mime_types.is_empty() && !self.allowed_mime_types.iter().any(matches) {
+            return Err(DownloadError::ContentNotAllowed(format!("media type '{}' is not allowed", mime_type)));
+        }
+        Ok(())
+    }
+
+    /// Check the name of the file a download writes
+    ///
+    /// Files without an extension pass only when no extensions are required.
+    pub fn check_filename(&self, path: &Path) -> Result<(), DownloadError> {
+        let extension = path.extension()
+            .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+        match extension {
+            Some(extension) if self.blocked_extensions.contains(&extension) => Err(DownloadError::ContentNotAllowed(
+                format!("file '{}' has blocked extension '{}'", name, extension)
+            )),
+            Some(extension) if !self.allowed_extensions.is_empty() && !self.allowed_extensions.contains(&extension) => {
+                Err(DownloadError::ContentNotAllowed(format!("file '{}' has extension '{}', which is not allowed", name, extension)))
+            }
+            None if !self.allowed_extensions.is_empty() => Err(DownloadError::ContentNotAllowed(
+                format!("file '{}' has no extension", name)
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn trim_dot(extension: impl AsRef<str>) -> String {
+    extension.as_ref().trim_start_matches('.').to_string()
+}
+
+fn lowercase<I, S>(values: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    values.into_iter().map(|value| value.as_ref().to_ascii_lowercase()).collect()
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+//! Download credentials
+//!
+//! Authentication for protected downloads. Credentials are only held in
+//! memory: they are skipped when options are serialized, so they never reach
+//! the task database, and `Debug` output hides the secrets.
+
+use std::fmt;
+
+/// Authentication sent with the requests of a download
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// HTTP Basic (and FTP/SFTP) user name and password
+    Basic { username: String, password: String },
+    /// `Authorization: Bearer` token
+    Bearer(String),
+    /// Signed or session cookies, as a `Cookie` header value
+    Cookies(String),
+}
+
+impl Credentials {
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Credentials::Basic {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Credentials::Bearer(token.into())
+    }
+
+    pub fn cookies(cookies: impl Into<String>) -> Self {
+        Credentials::Cookies(cookies.into())
+    }
+
+    /// Take the user name and password embedded in a URL
+    ///
+    /// Returns the URL without them and matching `Basic` credentials, or
+    /// `None` when the URL has no user name. A missing password is left empty,
+    /// e.g. for SFTP logins through an SSH agent.
+    pub fn from_url(url: &str) -> Option<(String, Credentials)> {
+        let mut parsed = url::Url::parse(url).ok()?;
+        if parsed.username().is_empty() {
+            return None;
+        }
+
+        let username = decode(parsed.username());
+        let password = parsed.password().map(decode).unwrap_or_default();
+        parsed.set_username("").ok()?;
+        parsed.set_password(None).ok()?;
+
+        Some((parsed.to_string(), Credentials::basic(username, password)))
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::Basic { username, .. } => f.debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"<redacted>")
+                .finish(),
+            Credentials::Bearer(_) => f.debug_tuple("Bearer").field(&"<redacted>").finish(),
+            Credentials::Cookies(_) => f.debug_tuple("Cookies").field(&"<redacted>").finish(),
+        }
+    }
+}
+
+/// Decode a percent-encoded URL component
+fn decode(component: &str) -> String {
+    percent_encoding::percent_decode_str(component).decode_utf8_lossy().into_owned()
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+//! Per-host transfer accounting
+//!
+//! How many bytes were downloaded from one source host, summed over all
+//! tasks and kept across restarts.
+
+use crate::types::serialize::system_time_millis;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Bytes downloaded from one source host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DomainUsage {
+    /// Host, with the port when the URLs name one
+    pub host: String,
+    pub bytes_downloaded: u64,
+    /// When bytes from the host were last counted
+    #[serde(rename = "last_updated_ms", with = "system_time_millis")]
+    pub last_updated: SystemTime,
+}
\ No newline at end of file
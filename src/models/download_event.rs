@@ -0,0 +1,104 @@
+//! Download events
+//!
+//! Value representation of the notifications delivered to
+//! `DownloadEventHandler`, suitable for sending over channels.
+//!
+//! Events serialize with an `event` tag in snake case, e.g.
+//! `{"event": "completed", "task_id": "..."}`. Statuses, progress and times
+//! use the formats of [`crate::types::serialize`], durations are milliseconds.
+
+use crate::models::{FailureInfo, SpeedSample};
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use crate::types::serialize::{download_progress, download_status, duration_millis, system_time_millis};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A single download notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum DownloadEvent {
+    /// Task status changed
+    StatusChanged {
+        task_id: TaskId,
+        #[serde(with = "download_status")]
+        old_status: DownloadStatus,
+        #[serde(with = "download_status")]
+        new_status: DownloadStatus,
+    },
+    /// Task progress updated
+    ProgressUpdated {
+        task_id: TaskId,
+        #[serde(with = "download_progress")]
+        progress: DownloadProgress,
+    },
+    /// Task completed successfully
+    Completed { task_id: TaskId },
+    /// Task failed
+    Failed { task_id: TaskId, error: String },
+    /// Failed task will be retried after `delay`
+    RetryScheduled {
+        task_id: TaskId,
+        attempt: u32,
+        #[serde(rename = "delay_ms", with = "duration_millis")]
+        delay: Duration,
+    },
+    /// Task was restored after a restart and resumes at `resumed_from` bytes
+    Restored { task_id: TaskId, resumed_from: u64 },
+    /// The remote file changed while the task was stopped, so it restarted from zero as `restarted_as`
+    SourceChanged { task_id: TaskId, restarted_as: TaskId },
+    /// Archive extraction advanced to `extracted_bytes` of `total_bytes`, when known
+    ExtractionProgress {
+        task_id: TaskId,
+        extracted_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    /// Copying the completed file to `destination` advanced to `copied_bytes` of `total_bytes`
+    CopyProgress {
+        task_id: TaskId,
+        destination: PathBuf,
+        copied_bytes: u64,
+        total_bytes: u64,
+    },
+    /// Post-download hooks finished, leaving the file at `path`
+    PostProcessed { task_id: TaskId, path: PathBuf },
+    /// Post-download hook `hook` failed
+    PostProcessingFailed {
+        task_id: TaskId,
+        hook: String,
+        error: String,
+    },
+    /// Task was still waiting to start at its deadline `expires_at` and was failed
+    Expired {
+        task_id: TaskId,
+        #[serde(with = "system_time_millis")]
+        expires_at: SystemTime,
+    },
+    /// Diagnostics of a task's failure were recorded
+    FailureRecorded { task_id: TaskId, failure: FailureInfo },
+    /// Speeds of all active tasks, sent at a fixed cadence
+    SpeedSample { sample: SpeedSample },
+}
+
+impl DownloadEvent {
+    /// Get the task this event refers to, `None` for events about all tasks
+    pub fn task_id(&self) -> Option<TaskId> {
+        let task_id = match self {
+            DownloadEvent::StatusChanged { task_id, .. } => *task_id,
+            DownloadEvent::ProgressUpdated { task_id, .. } => *task_id,
+            DownloadEvent::Completed { task_id } => *task_id,
+            DownloadEvent::Failed { task_id, .. } => *task_id,
+            DownloadEvent::RetryScheduled { task_id, .. } => *task_id,
+            DownloadEvent::Restored { task_id, .. } => *task_id,
+            DownloadEvent::SourceChanged { task_id, .. } => *task_id,
+            DownloadEvent::ExtractionProgress { task_id, .. } => *task_id,
+            DownloadEvent::CopyProgress { task_id, .. } => *task_id,
+            DownloadEvent::PostProcessed { task_id, .. } => *task_id,
+            DownloadEvent::PostProcessingFailed { task_id, .. } => *task_id,
+            DownloadEvent::Expired { task_id, .. } => *task_id,
+            DownloadEvent::FailureRecorded { task_id, .. } => *task_id,
+            DownloadEvent::SpeedSample { .. } => return None,
+        };
+        Some(task_id)
+    }
+}
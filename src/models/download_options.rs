@@ -0,0 +1,479 @@
+//! Per-task download options
+//!
+//! Collects the settings that can be attached to a single download and
+//! maps the transfer-related ones onto aria2 RPC options.
+
+use crate::error::DownloadError;
+use crate::models::{Priority, RetryPolicy, OverwritePolicy, Credentials, FileAllocation};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tokio_util::sync::CancellationToken;
+
+/// Most segments a download may be split into
+pub const MAX_SEGMENTS: u16 = 64;
+/// Most connections aria2 opens to a single server
+pub const MAX_CONNECTIONS_PER_SERVER: u16 = 16;
+/// Segment sizes aria2 accepts, 1 MiB to 1 GiB
+pub const MIN_SPLIT_SIZE_RANGE: RangeInclusive<u64> = 1024 * 1024..=1024 * 1024 * 1024;
+
+/// Hash algorithm used to verify a completed download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// Get the algorithm name as understood by aria2
+    pub fn aria2_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha1 => "sha-1",
+            ChecksumAlgorithm::Sha256 => "sha-256",
+            ChecksumAlgorithm::Sha512 => "sha-512",
+        }
+    }
+}
+
+/// Expected checksum of the downloaded file
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    /// Lowercase hex digest
+    pub value: String,
+}
+
+impl Checksum {
+    pub fn new(algorithm: ChecksumAlgorithm, value: impl Into<String>) -> Self {
+        Self {
+            algorithm,
+            value: value.into().to_lowercase(),
+        }
+    }
+}
+
+/// Expected digests of the fixed-size pieces of the downloaded file
+///
+/// Pieces are checked while the file downloads, so a corrupted transfer
+/// fails after the first bad piece instead of once the whole file arrived.
+/// Without digests the pieces are only hashed, for callers that record them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PieceChecksums {
+    pub algorithm: ChecksumAlgorithm,
+    /// Size of every piece but the last, which may be shorter
+    pub piece_size: u64,
+    /// Lowercase hex digest of each piece in file order
+    pub digests: Vec<String>,
+}
+
+impl PieceChecksums {
+    pub fn new<I, S>(algorithm: ChecksumAlgorithm, piece_size: u64, digests: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            algorithm,
+            piece_size,
+            digests: digests.into_iter().map(|digest| digest.into().to_lowercase()).collect(),
+        }
+    }
+
+    /// Hash the pieces without expecting particular digests
+    pub fn compute(algorithm: ChecksumAlgorithm, piece_size: u64) -> Self {
+        Self::new(algorithm, piece_size, Vec::<String>::new())
+    }
+}
+
+/// Options for a single download task
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadOptions {
+    /// Extra HTTP request headers as (name, value) pairs
+    pub headers: Vec<(String, String)>,
+    /// Value of the `Cookie` request header
+    pub cookies: Option<String>,
+    /// Custom `User-Agent`
+    pub user_agent: Option<String>,
+    /// Proxy URL, e.g. `http://proxy:8080`
+    pub proxy: Option<String>,
+    /// Download speed limit in bytes per second
+    pub speed_limit: Option<u64>,
+    /// Queue priority
+    pub priority: Priority,
+    /// User or tenant the download belongs to, `None` for unowned downloads
+    pub owner: Option<String>,
+    /// Fail the task as expired if it is still waiting to start at this time
+    pub expires_at: Option<SystemTime>,
+    /// Expected checksum of the completed file
+    pub checksum: Option<Checksum>,
+    /// Expected digests of the file's pieces, checked during the download
+    pub piece_checksums: Option<PieceChecksums>,
+    /// Retry policy overriding the manager default
+    pub retry_policy: Option<RetryPolicy>,
+    /// Number of parallel segments (connections) to use
+    pub segments: Option<u16>,
+    /// Connections to open per server, by default as many as segments up to 16
+    pub max_connections_per_server: Option<u16>,
+    /// Don't split off segments smaller than this many bytes
+    pub min_split_size: Option<u64>,
+    /// How the target file is allocated, the manager default when unset
+    pub file_allocation: Option<FileAllocation>,
+    /// Continue an existing partial file instead of starting over
+    pub continue_partial: bool,
+    /// Pick a free name like `file (1).zip` when the target path is taken
+    pub auto_rename: bool,
+    /// What to do when the target file already exists on disk
+    pub overwrite: OverwritePolicy,
+    /// Unpack zip, tar.gz and 7z archives once the download completed
+    pub auto_extract: bool,
+    /// Directory archives are unpacked into, next to the archive by default
+    pub extract_dir: Option<PathBuf>,
+    /// Further directories the completed file is copied into
+    pub copy_to: Vec<PathBuf>,
+    /// Abort the download if the file is larger than this many bytes, the manager default when unset
+    pub max_file_size: Option<u64>,
+    /// Authentication for the download, kept in memory only and never serialized
+    #[serde(skip)]
+    pub credentials: Option<Credentials>,
+    /// Aborts adding the download, and pausing, resuming or cancelling it while
+    /// in flight, when cancelled; kept in memory only and never serialized
+    #[serde(skip)]
+    pub cancel_token: Option<CancellationToken>,
+}
+
+/// Options are equal when they download the same way, whatever their cancellation token
+impl PartialEq for DownloadOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.headers == other.headers
+            && self.cookies == other.cookies
+            && self.user_agent == other.user_agent
+            && self.proxy == other.proxy
+            && self.speed_limit == other.speed_limit
+            && self.priority == other.priority
+            && self.owner == other.owner
+            && self.expires_at == other.expires_at
+            && self.checksum == other.checksum
+            && self.piece_checksums == other.piece_checksums
+            && self.retry_policy == other.retry_policy
+            && self.segments == other.segments
+            && self.max_connections_per_server == other.max_connections_per_server
+            && self.min_split_size == other.min_split_size
+            && self.file_allocation == other.file_allocation
+            && self.continue_partial == other.continue_partial
+            && self.auto_rename == other.auto_rename
+            && self.overwrite == other.overwrite
+            && self.auto_extract == other.auto_extract
+            && self.extract_dir == other.extract_dir
+            && self.copy_to == other.copy_to
+            && self.max_file_size == other.max_file_size
+            && self.credentials == other.credentials
+    }
+}
+
+impl DownloadOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an HTTP request header
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the `Cookie` request header
+    pub fn cookies(mut self, cookies: impl Into<String>) -> Self {
+        self.cookies = Some(cookies.into());
+        self
+    }
+
+    /// Set the `User-Agent`
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Route the download through a proxy
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Limit the download speed in bytes per second
+    pub fn speed_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.speed_limit = Some(bytes_per_sec);
+        self
+    }
+
+    /// Set the queue priority
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Set the user or tenant the download belongs to
+    pub fn owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Give up on the download if it has not started by `expires_at`, e.g. when a presigned URL stops being valid
+    pub fn expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Verify the completed file against a checksum
+    ///
+    /// aria2 checks it while downloading; with other backends the file is
+    /// hashed once it completes and post-processing fails with
+    /// `DownloadError::ChecksumMismatch` if it differs.
+    pub fn checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Verify the file piece by piece while it downloads
+    ///
+    /// Single-segment downloads, which write the file front to back, are
+    /// aborted with `DownloadError::PieceChecksumMismatch` as soon as a bad
+    /// piece arrived; the pieces of other downloads are checked once they
+    /// complete, failing post-processing on a mismatch.
+    pub fn piece_checksums(mut self, checksums: PieceChecksums) -> Self {
+        self.piece_checksums = Some(checksums);
+        self
+    }
+
+    /// Override the retry policy
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Set the number of parallel segments
+    pub fn segments(mut self, segments: u16) -> Self {
+        self.segments = Some(segments);
+        self
+    }
+
+    /// Set how many connections to open per server
+    pub fn max_connections_per_server(mut self, connections: u16) -> Self {
+        self.max_connections_per_server = Some(connections);
+        self
+    }
+
+    /// Set the smallest segment size in bytes
+    pub fn min_split_size(mut self, bytes: u64) -> Self {
+        self.min_split_size = Some(bytes);
+        self
+    }
+
+    /// Set how the target file is allocated before downloading
+    pub fn file_allocation(mut self, allocation: FileAllocation) -> Self {
+        self.file_allocation = Some(allocation);
+        self
+    }
+
+    /// Continue an existing partial file instead of starting over
+    pub fn continue_partial(mut self, continue_partial: bool) -> Self {
+        self.continue_partial = continue_partial;
+        self
+    }
+
+    /// Pick a free name like `file (1).zip` when the target path is taken
+    ///
+    /// Without it a taken path fails with `DownloadError::TargetPathConflict`.
+    pub fn auto_rename(mut self, auto_rename: bool) -> Self {
+        self.auto_rename = auto_rename;
+        self
+    }
+
+    /// Set what to do when the target file already exists on disk
+    pub fn overwrite(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Unpack zip, tar.gz and 7z archives once the download completed
+    ///
+    /// Archives go into a directory next to them named after the archive,
+    /// see [`extract_to`](Self::extract_to) to pick another one.
+    pub fn auto_extract(mut self, auto_extract: bool) -> Self {
+        self.auto_extract = auto_extract;
+        self
+    }
+
+    /// Unpack archives into `directory` once the download completed
+    pub fn extract_to(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.auto_extract = true;
+        self.extract_dir = Some(directory.into());
+        self
+    }
+
+    /// Copy the completed file into `directory` as well, keeping its file name
+    ///
+    /// Can be given several times, e.g. for a local cache and a shared mount.
+    /// The file is downloaded once and copied after the other post-download
+    /// steps; a failed copy fails post-processing, which can be retried
+    /// without downloading again.
+    pub fn copy_to(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.copy_to.push(directory.into());
+        self
+    }
+
+    /// Abort the download if the file turns out larger than `bytes`
+    ///
+    /// Fails with `DownloadError::FileTooLarge` before the download starts
+    /// when the server reports the size up front.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Authenticate the download
+    ///
+    /// Credentials are not persisted; tasks restored after a restart get them
+    /// from the manager's `CredentialProvider`.
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Abort operations on the download when `token` is cancelled
+    ///
+    /// Adding the download fails with `DownloadError::Cancelled` if the token
+    /// is cancelled before it was added; pausing, resuming and cancelling it
+    /// later fail the same way if the token is cancelled while they wait.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Fill in the segment settings left unset from manager-wide defaults
+    pub fn apply_segment_defaults(&mut self, defaults: &SegmentDefaults) {
+        self.segments = self.segments.or(defaults.segments);
+        self.max_connections_per_server = self.max_connections_per_server.or(defaults.max_connections_per_server);
+        self.min_split_size = self.min_split_size.or(defaults.min_split_size);
+    }
+
+    /// Check that the options are within the ranges aria2 accepts
+    pub fn validate(&self) -> Result<()> {
+        if self.piece_checksums.as_ref().is_some_and(|checksums| checksums.piece_size == 0) {
+            return Err(DownloadError::InvalidOption("piece size must be greater than 0".to_string()));
+        }
+        validate_segments(self.segments, self.max_connections_per_server, self.min_split_size)
+    }
+
+    /// Convert the transfer-related options into aria2 RPC options
+    ///
+    /// Priority, retry policy, extraction, piece checksums and the size limit
+    /// are handled by the manager and have no aria2 equivalent.
+    pub fn to_aria2_options(&self) -> Map<String, Value> {
+        let mut options = Map::new();
+
+        let mut headers: Vec<String> = self.headers.iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect();
+
+        let mut cookies: Vec<&str> = self.cookies.iter().map(String::as_str).collect();
+        match &self.credentials {
+            Some(Credentials::Basic { username, password }) => {
+                // aria2 reads FTP and SFTP logins from the ftp-* options
+                for (user_key, password_key) in [("http-user", "http-passwd"), ("ftp-user", "ftp-passwd")] {
+                    options.insert(user_key.to_string(), json!(username));
+                    options.insert(password_key.to_string(), json!(password));
+                }
+            }
+            Some(Credentials::Bearer(token)) => headers.push(format!("Authorization: Bearer {}", token)),
+            Some(Credentials::Cookies(signed)) => cookies.push(signed),
+            None => {}
+        }
+        if !cookies.is_empty() {
+            headers.push(format!("Cookie: {}", cookies.join("; ")));
+        }
+        if !headers.is_empty() {
+            options.insert("header".to_string(), json!(headers));
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            options.insert("user-agent".to_string(), json!(user_agent));
+        }
+        if let Some(proxy) = &self.proxy {
+            options.insert("all-proxy".to_string(), json!(proxy));
+        }
+        if let Some(limit) = self.speed_limit {
+            options.insert("max-download-limit".to_string(), json!(limit.to_string()));
+        }
+        if let Some(checksum) = &self.checksum {
+            options.insert(
+                "checksum".to_string(),
+                json!(format!("{}={}", checksum.algorithm.aria2_name(), checksum.value)),
+            );
+        }
+        if let Some(segments) = self.segments {
+            options.insert("split".to_string(), json!(segments.to_string()));
+        }
+        let connections = self.max_connections_per_server
+            .or(self.segments.map(|segments| segments.min(MAX_CONNECTIONS_PER_SERVER)));
+        if let Some(connections) = connections {
+            options.insert("max-connection-per-server".to_string(), json!(connections.to_string()));
+        }
+        if let Some(size) = self.min_split_size {
+            options.insert("min-split-size".to_string(), json!(size.to_string()));
+        }
+        if let Some(allocation) = self.file_allocation {
+            options.insert("file-allocation".to_string(), json!(allocation.aria2_name()));
+        }
+        if self.continue_partial {
+            options.insert("continue".to_string(), json!("true"));
+        }
+
+        options
+    }
+}
+
+/// Manager-wide segment settings for downloads whose options leave them unset
+///
+/// Without them aria2 falls back to its own defaults of 5 segments, a single
+/// connection per server and 20 MiB segments, so large files barely split.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentDefaults {
+    pub segments: Option<u16>,
+    pub max_connections_per_server: Option<u16>,
+    pub min_split_size: Option<u64>,
+}
+
+impl SegmentDefaults {
+    /// Check that the defaults are within the ranges aria2 accepts
+    pub fn validate(&self) -> Result<()> {
+        validate_segments(self.segments, self.max_connections_per_server, self.min_split_size)
+    }
+}
+
+fn validate_segments(segments: Option<u16>, connections: Option<u16>, min_split_size: Option<u64>) -> Result<()> {
+    if let Some(segments) = segments.filter(|segments| !(1..=MAX_SEGMENTS).contains(segments)) {
+        return Err(DownloadError::InvalidOption(format!(
+            "segments must be between 1 and {}, got {}", MAX_SEGMENTS, segments
+        )));
+    }
+    if let Some(connections) = connections.filter(|connections| !(1..=MAX_CONNECTIONS_PER_SERVER).contains(connections)) {
+        return Err(DownloadError::InvalidOption(format!(
+            "max_connections_per_server must be between 1 and {}, got {}", MAX_CONNECTIONS_PER_SERVER, connections
+        )));
+    }
+    if let Some(size) = min_split_size.filter(|size| !MIN_SPLIT_SIZE_RANGE.contains(size)) {
+        return Err(DownloadError::InvalidOption(format!(
+            "min_split_size must be between {} and {} bytes, got {}",
+            MIN_SPLIT_SIZE_RANGE.start(), MIN_SPLIT_SIZE_RANGE.end(), size
+        )));
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,63 @@
+//! Named download profiles
+//!
+//! A profile gives a kind of download its own root directory and defaults,
+//! e.g. `models` saved to `/mnt/models` with eight connections, or
+//! `datasets` saved to `/data/sets` at a limited speed. Profiles come from
+//! the [`ManagerConfig`](crate::ManagerConfig) or are added at runtime and
+//! are kept in the metadata database.
+
+use crate::models::{DownloadOptions, OverwritePolicy};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Root directory and download defaults of a named profile
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadProfile {
+    /// Directory downloads of the profile are saved in
+    pub directory: PathBuf,
+    /// Parallel connections of each download, `None` for the segment defaults
+    pub concurrency: Option<u16>,
+    /// Speed limit of each download in bytes per second, `None` for no limit
+    pub speed_limit: Option<u64>,
+    /// What happens when a download's target file already exists
+    pub overwrite: OverwritePolicy,
+}
+
+impl DownloadProfile {
+    /// Profile saving downloads in `directory` with default settings
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the parallel connections of each download
+    pub fn concurrency(mut self, connections: u16) -> Self {
+        self.concurrency = Some(connections);
+        self
+    }
+
+    /// Limit the speed of each download in bytes per second
+    pub fn speed_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.speed_limit = Some(bytes_per_sec);
+        self
+    }
+
+    /// Set what happens when a target file already exists
+    pub fn overwrite(mut self, overwrite: OverwritePolicy) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Options of a download started with this profile
+    pub fn download_options(&self) -> DownloadOptions {
+        DownloadOptions {
+            segments: self.concurrency,
+            speed_limit: self.speed_limit,
+            overwrite: self.overwrite,
+            ..DownloadOptions::default()
+        }
+    }
+}
\ No newline at end of file
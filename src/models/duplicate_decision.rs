@@ -0,0 +1,124 @@
+//! Decision types for duplicate detection
+//!
+//! A [`DuplicateDecision`] is the single outcome of checking a download
+//! request against existing tasks under a [`DuplicatePolicy`](crate::models::DuplicatePolicy).
+//! A [`DuplicatePreview`] tells what a request would do before it is made.
+
+use crate::types::TaskId;
+use crate::models::{TaskStatus, DuplicateReason};
+use serde::{Deserialize, Serialize};
+
+/// Existing task that matches a download request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateCandidate {
+    pub task_id: TaskId,
+    pub status: TaskStatus,
+    pub reason: DuplicateReason,
+}
+
+impl DuplicateCandidate {
+    /// Create a new candidate
+    pub fn new(task_id: TaskId, status: TaskStatus, reason: DuplicateReason) -> Self {
+        Self { task_id, status, reason }
+    }
+}
+
+/// How a download request that may duplicate an existing task is handled
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuplicateDecision {
+    /// Create a new task
+    CreateNew,
+    /// Reuse an existing task
+    Reuse {
+        task_id: TaskId,
+        status: TaskStatus,
+        reason: DuplicateReason,
+    },
+    /// Refuse the request because it duplicates an existing task
+    Reject {
+        task_id: TaskId,
+        reason: DuplicateReason,
+    },
+}
+
+impl DuplicateDecision {
+    /// Reuse the given candidate
+    pub fn reuse(candidate: &DuplicateCandidate) -> Self {
+        Self::Reuse {
+            task_id: candidate.task_id,
+            status: candidate.status.clone(),
+            reason: candidate.reason.clone(),
+        }
+    }
+
+    /// Reject the request in favour of the given candidate
+    pub fn reject(candidate: &DuplicateCandidate) -> Self {
+        Self::Reject {
+            task_id: candidate.task_id,
+            reason: candidate.reason.clone(),
+        }
+    }
+
+    /// Get the ID of the existing task this decision refers to, if any
+    pub fn task_id(&self) -> Option<TaskId> {
+        match self {
+            DuplicateDecision::CreateNew => None,
+            DuplicateDecision::Reuse { task_id, .. } => Some(*task_id),
+            DuplicateDecision::Reject { task_id, .. } => Some(*task_id),
+        }
+    }
+
+    /// Check if this decision creates a new task
+    pub fn is_create_new(&self) -> bool {
+        matches!(self, DuplicateDecision::CreateNew)
+    }
+
+    /// Check if this decision reuses an existing task
+    pub fn is_reuse(&self) -> bool {
+        matches!(self, DuplicateDecision::Reuse { .. })
+    }
+
+    /// Check if this decision rejects the request
+    pub fn is_reject(&self) -> bool {
+        matches!(self, DuplicateDecision::Reject { .. })
+    }
+}
+
+/// What a download request would do, worked out without adding anything
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuplicatePreview {
+    /// A new task would be created
+    CreateNew,
+    /// The existing task would be reused, and resumed if it is paused or failed
+    Reuse {
+        task_id: TaskId,
+        status: TaskStatus,
+        reason: DuplicateReason,
+    },
+    /// The request would be refused because it duplicates an existing task
+    Reject {
+        task_id: TaskId,
+        reason: DuplicateReason,
+    },
+    /// The decision handler would be asked to choose among `candidates`
+    RequiresDecision {
+        candidates: Vec<DuplicateCandidate>,
+    },
+}
+
+impl DuplicatePreview {
+    /// Check if the request would need a decision from the handler
+    pub fn requires_decision(&self) -> bool {
+        matches!(self, DuplicatePreview::RequiresDecision { .. })
+    }
+}
+
+impl From<DuplicateDecision> for DuplicatePreview {
+    fn from(decision: DuplicateDecision) -> Self {
+        match decision {
+            DuplicateDecision::CreateNew => DuplicatePreview::CreateNew,
+            DuplicateDecision::Reuse { task_id, status, reason } => DuplicatePreview::Reuse { task_id, status, reason },
+            DuplicateDecision::Reject { task_id, reason } => DuplicatePreview::Reject { task_id, reason },
+        }
+    }
+}
\ No newline at end of file
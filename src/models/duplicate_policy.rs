@@ -19,6 +19,9 @@ pub enum DuplicatePolicy {
     ReuseIfIncomplete,
     /// Fail with error if duplicate is found
     FailIfDuplicate,
+    /// Reuse existing task, but a completed one only while its file is on
+    /// disk and matches its recorded content hash; otherwise download again
+    ReuseVerified,
 }
 
 impl Default for DuplicatePolicy {
@@ -31,7 +34,7 @@ impl DuplicatePolicy {
     /// Check if this policy allows reusing the given task status
     pub fn allows_reuse(&self, status: &crate::models::TaskStatus) -> bool {
         match self {
-            DuplicatePolicy::ReuseExisting => true,
+            DuplicatePolicy::ReuseExisting | DuplicatePolicy::ReuseVerified => true,
             DuplicatePolicy::AllowDuplicate => false,
             DuplicatePolicy::PromptUser => false, // Requires user decision
             DuplicatePolicy::ReuseIfComplete => {
@@ -42,6 +45,7 @@ impl DuplicatePolicy {
                     crate::models::TaskStatus::Waiting |
                     crate::models::TaskStatus::Downloading |
                     crate::models::TaskStatus::Paused |
+                    crate::models::TaskStatus::PausedNoSpace |
                     crate::models::TaskStatus::Failed(_)
                 )
             }
@@ -54,6 +58,11 @@ impl DuplicatePolicy {
         matches!(self, DuplicatePolicy::FailIfDuplicate)
     }
 
+    /// Check if completed files have to be verified before they are reused
+    pub fn verifies_files(&self) -> bool {
+        matches!(self, DuplicatePolicy::ReuseVerified)
+    }
+
     /// Check if this policy requires user interaction
     pub fn requires_user_decision(&self) -> bool {
         matches!(self, DuplicatePolicy::PromptUser)
@@ -0,0 +1,144 @@
+//! Structured diagnostics of a failed download
+//!
+//! A failed task only carries the engine's error message in its status. The
+//! manager also records what kind of failure it was, the HTTP status and
+//! aria2 error code when they are known, and how many retries came before.
+
+use super::task_status::{CANCELLED_REASON, EXPIRED_REASON};
+use crate::hooks::SCAN_REJECTED;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// What made a download fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FailureKind {
+    /// Connection refused or reset, or the host name did not resolve
+    Network,
+    /// The server stopped responding or was too slow
+    Timeout,
+    /// The server does not have the file
+    NotFound,
+    /// The server refused the credentials
+    Unauthorized,
+    /// Any other unsuccessful or malformed HTTP response
+    Http,
+    /// The file did not match its checksum or piece checksums
+    Checksum,
+    /// The disk ran out of space
+    DiskFull,
+    /// The file is larger than its size limit
+    TooLarge,
+    /// The content scanner rejected the file
+    Rejected,
+    /// The task was still waiting to start at its deadline
+    Expired,
+    /// The download was removed from the engine
+    Removed,
+    /// The user cancelled the download
+    Cancelled,
+    Unknown,
+}
+
+impl FailureKind {
+    /// Classify a failure by its aria2 error code, HTTP status and message, in that order
+    pub fn classify(message: &str, aria2_error_code: Option<u32>, http_status: Option<u16>) -> Self {
+        let by_code = aria2_error_code.and_then(|code| match code {
+            2 | 5 => Some(FailureKind::Timeout),
+            3 | 4 => Some(FailureKind::NotFound),
+            6 | 19 => Some(FailureKind::Network),
+            9 => Some(FailureKind::DiskFull),
+            22 | 23 => Some(FailureKind::Http),
+            24 => Some(FailureKind::Unauthorized),
+            32 => Some(FailureKind::Checksum),
+            _ => None,
+        });
+        let by_status = http_status.map(|status| match status {
+            401 | 403 => FailureKind::Unauthorized,
+            404 | 410 => FailureKind::NotFound,
+            408 | 504 => FailureKind::Timeout,
+            _ => FailureKind::Http,
+        });
+        by_code.or(by_status).unwrap_or_else(|| Self::from_message(message))
+    }
+
+    fn from_message(message: &str) -> Self {
+        if message == EXPIRED_REASON {
+            return FailureKind::Expired;
+        }
+        if message == CANCELLED_REASON {
+            return FailureKind::Cancelled;
+        }
+        if message.starts_with(SCAN_REJECTED) {
+            return FailureKind::Rejected;
+        }
+
+        let message = message.to_lowercase();
+        let contains_any = |patterns: &[&str]| patterns.iter().any(|pattern| message.contains(pattern));
+        if contains_any(&["removed from"]) {
+            FailureKind::Removed
+        } else if contains_any(&["checksum"]) {
+            FailureKind::Checksum
+        } else if contains_any(&["exceeds the maximum size"]) {
+            FailureKind::TooLarge
+        } else if contains_any(&["disk full", "no space", "not enough disk space"]) {
+            FailureKind::DiskFull
+        } else if contains_any(&["timeout", "timed out"]) {
+            FailureKind::Timeout
+        } else if contains_any(&["connection", "network", "resolve", "unreachable"]) {
+            FailureKind::Network
+        } else {
+            FailureKind::Unknown
+        }
+    }
+}
+
+/// Diagnostics of one failure of a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureInfo {
+    pub kind: FailureKind,
+    /// Error message the task failed with
+    pub message: String,
+    /// Status of the unsuccessful HTTP response, when the message names one
+    pub http_status: Option<u16>,
+    /// aria2 error code of the download, for tasks run by aria2
+    pub aria2_error_code: Option<u32>,
+    /// Retries made before this failure
+    pub retry_count: u32,
+    /// When the failed attempt ended
+    pub last_attempt_at: SystemTime,
+}
+
+impl FailureInfo {
+    /// Diagnose a failure that just happened, before any retry
+    pub fn new(message: impl Into<String>, aria2_error_code: Option<u32>) -> Self {
+        let message = message.into();
+        let http_status = http_status(&message);
+        Self {
+            kind: FailureKind::classify(&message, aria2_error_code, http_status),
+            message,
+            http_status,
+            aria2_error_code,
+            retry_count: 0,
+            last_attempt_at: SystemTime::now(),
+        }
+    }
+
+    /// Set the number of retries made before this failure
+    pub fn with_retry_count(mut self, retry_count: u32) -> Self {
+        self.retry_count = retry_count;
+        self
+    }
+}
+
+/// Find the HTTP status in an error message
+///
+/// aria2 reports unsuccessful responses as `... status=404`, other engines
+/// as `HTTP 404`.
+fn http_status(message: &str) -> Option<u16> {
+    let lower = message.to_lowercase();
+    ["status=", "http "].iter().find_map(|prefix| {
+        let start = lower.find(prefix)? + prefix.len();
+        let digits = lower.get(start..start + 3)?;
+        digits.parse().ok().filter(|status| (100..600).contains(status))
+    })
+}
\ No newline at end of file
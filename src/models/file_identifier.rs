@@ -6,9 +6,10 @@
 use std::path::{Path, PathBuf};
 use crate::utils::url_normalization::{process_url_for_storage};
 use blake3;
+use serde::{Deserialize, Serialize};
 
 /// Composite key for identifying duplicate downloads
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FileIdentifier {
     pub url_hash: String,
     pub target_path: PathBuf,
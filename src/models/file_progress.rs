@@ -0,0 +1,48 @@
+//! Per-file progress of a download
+//!
+//! A torrent, metalink or repository download writes several files, which a
+//! single [`DownloadProgress`] sums up. Each file is reported on its own,
+//! together with whether it is selected for download.
+
+use crate::types::DownloadProgress;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Progress of one file of a download
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileProgress {
+    /// 1-based index of the file within its download, as used by aria2's file selection
+    pub index: u32,
+    pub path: PathBuf,
+    /// Bytes of the file written so far
+    pub downloaded: u64,
+    /// Size of the file, `None` while it is unknown
+    pub total: Option<u64>,
+    /// Whether the file is downloaded or skipped
+    pub selected: bool,
+}
+
+impl FileProgress {
+    /// Report a single-file download as its only file
+    pub fn whole(path: impl Into<PathBuf>, progress: &DownloadProgress) -> Self {
+        Self {
+            index: 1,
+            path: path.into(),
+            downloaded: progress.downloaded_bytes,
+            total: progress.total_bytes.filter(|total| *total > 0),
+            selected: true,
+        }
+    }
+
+    /// Percentage of the file written, between 0 and 100, `None` while the size is unknown
+    pub fn percentage(&self) -> Option<f64> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded.min(total) as f64 / total as f64) * 100.0)
+    }
+
+    /// Check if every byte of the file was written
+    pub fn is_complete(&self) -> bool {
+        self.total.is_some_and(|total| self.downloaded >= total)
+    }
+}
\ No newline at end of file
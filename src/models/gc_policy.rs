@@ -0,0 +1,61 @@
+//! Stale task garbage collection policy
+//!
+//! Describes when a waiting or downloading task counts as stuck, what happens
+//! to it, and whether database rows of vanished downloads are removed.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// What garbage collection does with a stuck task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StaleTaskAction {
+    /// Stop the download and mark the task as failed
+    Fail,
+    /// Stop the download and add it to the backend again, continuing from its partial file
+    Requeue,
+}
+
+/// Settings of [`PersistentAria2Manager::run_gc`](crate::PersistentAria2Manager::run_gc)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GcPolicy {
+    /// How long a waiting or downloading task may go without progress before it is stale
+    pub stall_timeout: Duration,
+    /// What happens to stale tasks
+    pub action: StaleTaskAction,
+    /// Delete tasks the backend no longer knows whose files are gone
+    pub remove_orphans: bool,
+}
+
+impl Default for GcPolicy {
+    fn default() -> Self {
+        Self {
+            stall_timeout: Duration::from_secs(6 * 60 * 60),
+            action: StaleTaskAction::Requeue,
+            remove_orphans: true,
+        }
+    }
+}
+
+impl GcPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long a task may go without progress
+    pub fn stall_timeout(mut self, stall_timeout: Duration) -> Self {
+        self.stall_timeout = stall_timeout;
+        self
+    }
+
+    /// Set what happens to stale tasks
+    pub fn action(mut self, action: StaleTaskAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Set whether orphaned tasks are deleted
+    pub fn remove_orphans(mut self, remove_orphans: bool) -> Self {
+        self.remove_orphans = remove_orphans;
+        self
+    }
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+//! Garbage collection report
+//!
+//! Lists the tasks a garbage collection run failed, requeued, deleted or
+//! purged from the trash.
+
+use crate::models::RestoredTask;
+use crate::types::TaskId;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a garbage collection run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Stale tasks marked as failed
+    pub failed: Vec<TaskId>,
+    /// Stale tasks added to the backend again
+    pub requeued: Vec<RestoredTask>,
+    /// Orphaned tasks deleted from the database
+    pub removed: Vec<TaskId>,
+    /// Tasks purged because they were in the trash for longer than its TTL
+    pub purged: Vec<TaskId>,
+}
+
+impl GcReport {
+    /// Check if the run changed nothing
+    pub fn is_empty(&self) -> bool {
+        self.failed.is_empty() && self.requeued.is_empty() && self.removed.is_empty() && self.purged.is_empty()
+    }
+
+    /// Number of tasks the run changed
+    pub fn total(&self) -> usize {
+        self.failed.len() + self.requeued.len() + self.removed.len() + self.purged.len()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+//! Event handler failures
+//!
+//! Reported when a registered [`DownloadEventHandler`] panics or takes
+//! longer than the manager allows, so applications learn about broken
+//! handlers instead of a stalled queue.
+
+use crate::models::HandlerId;
+use crate::traits::DownloadEventHandler;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How a handler call went wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerFailure {
+    /// The handler panicked, with the panic message when it was a string
+    Panicked(String),
+    /// The handler did not return within the timeout and was aborted
+    TimedOut(Duration),
+}
+
+impl fmt::Display for HandlerFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandlerFailure::Panicked(message) => write!(f, "panicked: {}", message),
+            HandlerFailure::TimedOut(timeout) => write!(f, "timed out after {:?}", timeout),
+        }
+    }
+}
+
+/// A failed call of an event handler
+#[derive(Clone)]
+pub struct HandlerError {
+    /// The handler as it was registered, compare with `Arc::ptr_eq`
+    pub handler: Arc<dyn DownloadEventHandler>,
+    /// ID the handler was registered under, `None` outside a manager
+    pub handler_id: Option<HandlerId>,
+    /// Name of the callback, e.g. `on_progress_updated`
+    pub event: &'static str,
+    pub failure: HandlerFailure,
+    /// Whether the handler was removed because of the failure
+    pub removed: bool,
+}
+
+impl fmt::Debug for HandlerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerError")
+            .field("handler_id", &self.handler_id)
+            .field("event", &self.event)
+            .field("failure", &self.failure)
+            .field("removed", &self.removed)
+            .finish_non_exhaustive()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+//! Event handler identifiers
+//!
+//! Handlers are registered behind wrappers, so managers identify them by
+//! the ID handed out at registration rather than by the handler itself.
+
+use serde::{Deserialize, Serialize};
+
+/// Identifier of an event handler registered with a manager
+///
+/// Returned when a handler is added and used to remove it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HandlerId(pub u64);
+
+impl std::fmt::Display for HandlerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "handler-{}", self.0)
+    }
+}
\ No newline at end of file
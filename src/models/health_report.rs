@@ -0,0 +1,35 @@
+//! Health report
+//!
+//! Snapshot of the download subsystem's dependencies, for orchestration
+//! layers probing it before relying on it.
+
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// State of the backend, database, status poller and download queue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether the download backend answered
+    pub backend_reachable: bool,
+    /// Version of the aria2 daemon, `None` when unreachable or not running on aria2
+    pub aria2_version: Option<String>,
+    /// Whether the task database answered a query
+    pub database_reachable: bool,
+    /// When the status poller last ran, `None` before its first run
+    pub last_poll: Option<SystemTime>,
+    /// Whether the status poller is running and ran recently
+    pub poller_alive: bool,
+    /// Downloads currently transferring
+    pub active_downloads: usize,
+    /// Downloads waiting to start
+    pub waiting_downloads: usize,
+    /// Failed checks, empty when everything is healthy
+    pub problems: Vec<String>,
+}
+
+impl HealthReport {
+    /// Check if every dependency is available
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
\ No newline at end of file
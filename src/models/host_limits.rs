@@ -0,0 +1,58 @@
+//! Per-host concurrency limits
+//!
+//! Servers often rate limit clients that open several downloads at once.
+//! A limit configured for a domain covers the domain and all of its
+//! subdomains together, hosts without an entry share the default limit
+//! individually.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How many downloads may run at once per host or domain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostLimits {
+    /// Limit of each host without an entry in `limits`, `None` for no limit
+    pub default_limit: Option<usize>,
+    /// Limits by lowercase host or domain, `example.com` also covering `cdn.example.com`
+    pub limits: HashMap<String, usize>,
+}
+
+impl HostLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit of hosts without their own entry
+    pub fn default_limit(mut self, limit: usize) -> Self {
+        self.default_limit = Some(limit);
+        self
+    }
+
+    /// Set the limit of a host or domain
+    pub fn limit(mut self, host: impl Into<String>, limit: usize) -> Self {
+        self.limits.insert(host.into().to_ascii_lowercase(), limit);
+        self
+    }
+
+    /// Get the host or domain a download of `url` counts towards, and its limit
+    ///
+    /// The most specific configured entry wins, other hosts count on their
+    /// own against the default limit. `None` when the URL is not limited.
+    pub fn slot_for(&self, url: &str) -> Option<(String, usize)> {
+        let url = url::Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        let mut domain = host.as_str();
+        loop {
+            if let Some(limit) = self.limits.get(domain) {
+                return Some((domain.to_string(), *limit));
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => break,
+            }
+        }
+
+        self.default_limit.map(|limit| (host, limit))
+    }
+}
\ No newline at end of file
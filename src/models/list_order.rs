@@ -0,0 +1,40 @@
+//! Task list ordering
+//!
+//! Task IDs carry no time component, so listings are ordered by the
+//! creation and update times stored with each task instead.
+
+use crate::types::DownloadTask;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Order of tasks returned by listing and duplicate lookups
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ListOrder {
+    /// Oldest task first
+    CreatedAsc,
+    /// Newest task first
+    #[default]
+    CreatedDesc,
+    /// Least recently changed task first
+    UpdatedAsc,
+    /// Most recently changed task first
+    UpdatedDesc,
+}
+
+impl ListOrder {
+    /// Compare two tasks, ties broken by task ID so the order is stable across calls
+    pub fn compare(&self, a: &DownloadTask, b: &DownloadTask) -> Ordering {
+        let by_time = match self {
+            ListOrder::CreatedAsc => a.created_at.cmp(&b.created_at),
+            ListOrder::CreatedDesc => b.created_at.cmp(&a.created_at),
+            ListOrder::UpdatedAsc => a.updated_at.cmp(&b.updated_at),
+            ListOrder::UpdatedDesc => b.updated_at.cmp(&a.updated_at),
+        };
+        by_time.then_with(|| a.id.to_string().cmp(&b.id.to_string()))
+    }
+
+    /// Sort tasks in this order
+    pub fn sort(&self, tasks: &mut [DownloadTask]) {
+        tasks.sort_by(|a, b| self.compare(a, b));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+//! Mirror health statistics
+//!
+//! What downloads so far have shown about one mirror host: how often it
+//! failed and how long it took to deliver the first bytes.
+
+use crate::types::serialize::{option_duration_millis, option_system_time_millis};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+/// Health of one mirror host across all tasks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MirrorStats {
+    /// Host, with the port when the URLs name one
+    pub host: String,
+    pub successes: u64,
+    pub failures: u64,
+    /// Moving average of the time to the first downloaded bytes
+    #[serde(rename = "average_latency_ms", with = "option_duration_millis")]
+    pub average_latency: Option<Duration>,
+    #[serde(rename = "last_failure_ms", with = "option_system_time_millis")]
+    pub last_failure: Option<SystemTime>,
+}
+
+impl MirrorStats {
+    /// Statistics of a host nothing is known about yet
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            successes: 0,
+            failures: 0,
+            average_latency: None,
+            last_failure: None,
+        }
+    }
+
+    /// Share of finished downloads that failed, 0 before any finished
+    pub fn failure_rate(&self) -> f64 {
+        let attempts = self.successes + self.failures;
+        if attempts == 0 {
+            return 0.0;
+        }
+        self.failures as f64 / attempts as f64
+    }
+
+    /// Rank of the host among mirrors, higher is better
+    ///
+    /// The success rate counts one success and one failure in advance, so a
+    /// single outcome doesn't decide the rank, and is divided by one plus the
+    /// average latency in seconds. A host without history scores 0.5.
+    pub fn score(&self) -> f64 {
+        let success_rate = (self.successes + 1) as f64 / (self.successes + self.failures + 2) as f64;
+        let latency = self.average_latency.map_or(0.0, |latency| latency.as_secs_f64());
+        success_rate / (1.0 + latency)
+    }
+}
\ No newline at end of file
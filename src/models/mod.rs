@@ -6,11 +6,79 @@
 pub mod file_identifier;
 pub mod task_status;
 pub mod duplicate_policy;
-pub mod duplicate_result;
+pub mod duplicate_decision;
 pub mod duplicate_reason;
+pub mod priority;
+pub mod retry_policy;
+pub mod download_options;
+pub mod download_event;
+pub mod overwrite_policy;
+pub mod url_policy;
+pub mod credentials;
+pub mod recovery_report;
+pub mod task_export;
+pub mod smoothed_progress;
+pub mod progress_sample;
+pub mod mirror_stats;
+pub mod domain_usage;
+pub mod handler_error;
+pub mod handler_id;
+pub mod file_allocation;
+pub mod gc_policy;
+pub mod gc_report;
+pub mod host_limits;
+pub mod health_report;
+pub mod list_order;
+pub mod conditional_download;
+pub mod content_policy;
+pub mod progress_delivery;
+pub mod rpc_timeouts;
+pub mod download_profile;
+pub mod owner_quotas;
+pub mod failure_info;
+pub mod progress_kind;
+pub mod admission_limits;
+pub mod trash_policy;
+pub mod file_progress;
+pub mod torrent_settings;
+pub mod speed_sample;
 
 pub use file_identifier::FileIdentifier;
-pub use task_status::TaskStatus;
+pub use task_status::{TaskStatus, EXPIRED_REASON, CANCELLED_REASON};
 pub use duplicate_policy::DuplicatePolicy;
-pub use duplicate_result::{DuplicateResult, DuplicateAction};
-pub use duplicate_reason::DuplicateReason;
\ No newline at end of file
+pub use duplicate_decision::{DuplicateDecision, DuplicateCandidate, DuplicatePreview};
+pub use duplicate_reason::DuplicateReason;
+pub use priority::Priority;
+pub use retry_policy::{RetryPolicy, Backoff, RetryOn};
+pub use download_options::{DownloadOptions, Checksum, ChecksumAlgorithm, PieceChecksums, SegmentDefaults};
+pub use download_event::DownloadEvent;
+pub use overwrite_policy::{OverwritePolicy, TargetAction};
+pub use url_policy::UrlPolicy;
+pub use credentials::Credentials;
+pub use recovery_report::{RecoveryReport, RestoredTask, FailedRecovery};
+pub use task_export::{TaskExport, ExportedTask, ImportPolicy, ImportReport};
+pub use smoothed_progress::SmoothedProgress;
+pub use progress_kind::{ProgressKind, completion_percentage};
+pub use admission_limits::{AdmissionLimits, AdmissionMode};
+pub use progress_sample::ProgressSample;
+pub use mirror_stats::MirrorStats;
+pub use domain_usage::DomainUsage;
+pub use handler_error::{HandlerError, HandlerFailure};
+pub use handler_id::HandlerId;
+pub use file_allocation::FileAllocation;
+pub use gc_policy::{GcPolicy, StaleTaskAction};
+pub use gc_report::GcReport;
+pub use trash_policy::{DeleteMode, TrashPolicy, TrashedTask};
+pub use file_progress::FileProgress;
+pub use torrent_settings::{PortRange, TorrentSettings};
+pub use speed_sample::{SpeedSample, TaskSpeed};
+pub use host_limits::HostLimits;
+pub use health_report::HealthReport;
+pub use list_order::ListOrder;
+pub use conditional_download::ConditionalDownload;
+pub use content_policy::ContentPolicy;
+pub use progress_delivery::ProgressDelivery;
+pub use rpc_timeouts::RpcTimeouts;
+pub use download_profile::DownloadProfile;
+pub use owner_quotas::OwnerQuotas;
+pub use failure_info::{FailureInfo, FailureKind};
\ No newline at end of file
@@ -0,0 +1,58 @@
+//! Overwrite policies
+//!
+//! Defines what happens when a download's target file already exists on disk.
+
+use crate::error::DownloadError;
+use crate::utils::paths::first_free_renamed_path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Policy for target files that already exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverwritePolicy {
+    /// Replace the existing file
+    Overwrite,
+    /// Keep the existing file and record the task as completed without downloading
+    Skip,
+    /// Download to the first free `name (n).ext` next to the existing file (default)
+    RenameWithSuffix,
+    /// Fail with `DownloadError::FileExists`
+    Fail,
+}
+
+impl Default for OverwritePolicy {
+    fn default() -> Self {
+        Self::RenameWithSuffix
+    }
+}
+
+/// What a manager does with a download after applying an [`OverwritePolicy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetAction {
+    /// Download to this path; replace the file there if `overwrite` is set
+    Download { path: PathBuf, overwrite: bool },
+    /// Keep the existing file at this path and download nothing
+    Skip(PathBuf),
+}
+
+impl OverwritePolicy {
+    /// Decide where a download to `target_path` goes
+    pub fn apply(&self, target_path: &Path) -> Result<TargetAction, DownloadError> {
+        if !target_path.exists() {
+            return Ok(TargetAction::Download { path: target_path.to_path_buf(), overwrite: false });
+        }
+
+        match self {
+            OverwritePolicy::Overwrite => {
+                Ok(TargetAction::Download { path: target_path.to_path_buf(), overwrite: true })
+            }
+            OverwritePolicy::Skip => Ok(TargetAction::Skip(target_path.to_path_buf())),
+            OverwritePolicy::RenameWithSuffix => {
+                first_free_renamed_path(target_path, |candidate| candidate.exists())
+                    .map(|path| TargetAction::Download { path, overwrite: false })
+                    .ok_or_else(|| DownloadError::FileExists(target_path.to_path_buf()))
+            }
+            OverwritePolicy::Fail => Err(DownloadError::FileExists(target_path.to_path_buf())),
+        }
+    }
+}
\ No newline at end of file
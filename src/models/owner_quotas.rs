@@ -0,0 +1,41 @@
+//! Per-owner concurrency quotas
+//!
+//! In a multi-user deployment one user queuing hundreds of downloads would
+//! otherwise take every download slot. A quota caps how many downloads of
+//! one owner run at once; the rest wait in the queue while other owners'
+//! downloads start. Downloads without an owner are not limited.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How many downloads of each owner may run at once
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerQuotas {
+    /// Quota of each owner without an entry in `quotas`, `None` for no limit
+    pub default_quota: Option<usize>,
+    /// Quotas by owner
+    pub quotas: HashMap<String, usize>,
+}
+
+impl OwnerQuotas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the quota of owners without their own entry
+    pub fn default_quota(mut self, quota: usize) -> Self {
+        self.default_quota = Some(quota);
+        self
+    }
+
+    /// Set the quota of one owner
+    pub fn quota(mut self, owner: impl Into<String>, quota: usize) -> Self {
+        self.quotas.insert(owner.into(), quota);
+        self
+    }
+
+    /// Get how many downloads of `owner` may run at once, `None` when unlimited
+    pub fn quota_for(&self, owner: &str) -> Option<usize> {
+        self.quotas.get(owner).copied().or(self.default_quota)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+//! Download priority levels
+//!
+//! Controls the order in which waiting tasks are started by the queue manager.
+
+use serde::{Deserialize, Serialize};
+
+/// Priority of a download task in the waiting queue
+///
+/// Variants are declared from lowest to highest so that the derived `Ord`
+/// ranks `Urgent` above `Low`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Priority {
+    /// Background downloads, started only when nothing else is waiting
+    Low,
+    /// Regular downloads (default)
+    Normal,
+    /// Downloads that should jump ahead of regular ones
+    High,
+    /// Downloads that must start before anything else
+    Urgent,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        };
+        write!(f, "{}", name)
+    }
+}
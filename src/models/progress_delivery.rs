@@ -0,0 +1,41 @@
+//! Progress delivery limits of an event handler
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How often an event handler receives progress of the same task
+///
+/// An update is delivered once both limits are reached since the last one
+/// delivered; the updates in between are coalesced into the latest. The
+/// default delivers every update.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgressDelivery {
+    /// Shortest time between two updates
+    pub min_interval: Duration,
+    /// Fewest bytes downloaded between two updates
+    pub min_delta_bytes: u64,
+}
+
+impl ProgressDelivery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver at most one update per `interval`
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Deliver an update only after `bytes` more were downloaded
+    pub fn min_delta_bytes(mut self, bytes: u64) -> Self {
+        self.min_delta_bytes = bytes;
+        self
+    }
+
+    /// Check if every update is delivered
+    pub fn is_unlimited(&self) -> bool {
+        self.min_interval.is_zero() && self.min_delta_bytes == 0
+    }
+}
\ No newline at end of file
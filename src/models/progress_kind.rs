@@ -0,0 +1,50 @@
+//! Progress kinds
+//!
+//! Tells UIs whether a download can be shown with a progress bar. Servers
+//! that send no `Content-Length` leave the size unknown, so neither a
+//! percentage nor an ETA exists and a spinner with the bytes received so
+//! far fits better.
+
+use crate::types::DownloadProgress;
+use serde::{Deserialize, Serialize};
+
+/// How the progress of a download can be presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProgressKind {
+    /// The size is known, a percentage and ETA can be shown
+    Determinate,
+    /// The size is unknown, only the bytes received and the speed are meaningful
+    Indeterminate,
+    /// Every byte arrived, the file is being checked, moved or post-processed
+    Finalizing,
+}
+
+impl ProgressKind {
+    /// Kind of the progress reported by a backend
+    ///
+    /// A total of zero counts as unknown, engines report it before the
+    /// server answered.
+    pub fn of(progress: &DownloadProgress) -> Self {
+        match progress.total_bytes {
+            Some(total) if total > 0 && progress.downloaded_bytes >= total => ProgressKind::Finalizing,
+            Some(total) if total > 0 => ProgressKind::Determinate,
+            _ => ProgressKind::Indeterminate,
+        }
+    }
+
+    /// Whether a UI should show a spinner rather than a progress bar
+    pub fn is_indeterminate(&self) -> bool {
+        matches!(self, ProgressKind::Indeterminate)
+    }
+}
+
+/// Percentage of a download completed, between 0 and 100
+///
+/// Returns `None` whenever the size is unknown (including a reported total of
+/// zero), so UIs can rely on it to switch to an indeterminate display instead
+/// of showing 0% or dividing by zero.
+pub fn completion_percentage(progress: &DownloadProgress) -> Option<f64> {
+    progress.total_bytes
+        .filter(|total| *total > 0)
+        .map(|total| (progress.downloaded_bytes.min(total) as f64 / total as f64) * 100.0)
+}
\ No newline at end of file
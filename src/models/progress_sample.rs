@@ -0,0 +1,30 @@
+//! Progress samples
+//!
+//! Snapshots of a download taken over time, from which UIs draw transfer
+//! speed graphs.
+
+use crate::types::DownloadProgress;
+use crate::types::serialize::system_time_millis;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Progress of a task at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressSample {
+    #[serde(rename = "recorded_at_ms", with = "system_time_millis")]
+    pub recorded_at: SystemTime,
+    pub downloaded_bytes: u64,
+    /// Speed reported by the backend when the sample was taken
+    pub speed_bps: u64,
+}
+
+impl ProgressSample {
+    /// Take a sample of the given progress
+    pub fn new(recorded_at: SystemTime, progress: &DownloadProgress) -> Self {
+        Self {
+            recorded_at,
+            downloaded_bytes: progress.downloaded_bytes,
+            speed_bps: progress.speed_bps,
+        }
+    }
+}
\ No newline at end of file
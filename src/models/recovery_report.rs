@@ -0,0 +1,51 @@
+//! Recovery report
+//!
+//! Describes what happened to each persisted task when the manager restored
+//! its downloads after a restart, so applications can tell the user which
+//! downloads continued and which were lost.
+
+use crate::types::TaskId;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// A task that was restored into the backend
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoredTask {
+    /// ID the task runs under now
+    pub task_id: TaskId,
+    /// ID the task was persisted under, differs from `task_id` when it was re-added
+    pub original_task_id: TaskId,
+    /// Byte offset the download continues from
+    pub resumed_from: u64,
+}
+
+/// A task that could not be restored and was marked as failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedRecovery {
+    pub task_id: TaskId,
+    pub url: String,
+    pub target_path: PathBuf,
+    /// Why the task could not be restored
+    pub error: String,
+}
+
+/// Outcome of restoring persisted tasks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    pub restored: Vec<RestoredTask>,
+    pub failed: Vec<FailedRecovery>,
+    /// Tasks left alone because they had already finished
+    pub skipped_completed: Vec<TaskId>,
+}
+
+impl RecoveryReport {
+    /// Check if every unfinished task was restored
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Number of tasks the report covers
+    pub fn total(&self) -> usize {
+        self.restored.len() + self.failed.len() + self.skipped_completed.len()
+    }
+}
\ No newline at end of file
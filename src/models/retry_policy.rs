@@ -0,0 +1,127 @@
+//! Retry policy for failed downloads
+//!
+//! Describes how many times a failed download is retried, how long to wait
+//! between attempts and which failures are worth retrying.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Delay strategy between retry attempts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Backoff {
+    /// Wait the same amount of time before every attempt
+    Fixed(Duration),
+    /// Multiply the delay by `multiplier` after every attempt, capped at `max_delay`
+    Exponential {
+        initial_delay: Duration,
+        multiplier: f64,
+        max_delay: Duration,
+    },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::Exponential {
+            initial_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// Get the delay before the given attempt (1-based)
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        match self {
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential { initial_delay, multiplier, max_delay } => {
+                let exponent = attempt.saturating_sub(1) as i32;
+                let secs = initial_delay.as_secs_f64() * multiplier.powi(exponent);
+                if !secs.is_finite() || secs >= max_delay.as_secs_f64() {
+                    *max_delay
+                } else {
+                    Duration::from_secs_f64(secs)
+                }
+            }
+        }
+    }
+}
+
+/// Which failures should trigger a retry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetryOn {
+    /// Retry every failure
+    AnyError,
+    /// Retry failures that look temporary (timeouts, connection errors, 5xx, 429)
+    Transient,
+    /// Retry failures whose message contains one of the given substrings (case-insensitive)
+    Matching(Vec<String>),
+}
+
+/// Error message fragments that indicate a temporary failure
+const TRANSIENT_ERROR_PATTERNS: &[&str] = &[
+    "timeout",
+    "timed out",
+    "connection",
+    "temporarily",
+    "network",
+    "reset",
+    "429",
+    "500",
+    "502",
+    "503",
+    "504",
+];
+
+impl RetryOn {
+    /// Check if the given error message should be retried
+    pub fn matches(&self, error: &str) -> bool {
+        let error = error.to_lowercase();
+        match self {
+            RetryOn::AnyError => true,
+            RetryOn::Transient => TRANSIENT_ERROR_PATTERNS.iter().any(|p| error.contains(p)),
+            RetryOn::Matching(patterns) => patterns.iter().any(|p| error.contains(&p.to_lowercase())),
+        }
+    }
+}
+
+/// Retry policy applied when a download fails
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Maximum number of retries after the initial attempt (0 disables retrying)
+    pub max_attempts: u32,
+    /// Delay strategy between attempts
+    pub backoff: Backoff,
+    /// Failures eligible for retrying
+    pub retry_on: RetryOn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Backoff::default(),
+            retry_on: RetryOn::AnyError,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Check if a task that already used `attempts` retries should retry after `error`
+    pub fn should_retry(&self, attempts: u32, error: &str) -> bool {
+        attempts < self.max_attempts && self.retry_on.matches(error)
+    }
+
+    /// Get the delay before the given retry attempt (1-based)
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        self.backoff.delay_for(attempt)
+    }
+}
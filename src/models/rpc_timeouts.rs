@@ -0,0 +1,59 @@
+//! Timeouts of calls to the aria2 daemon
+//!
+//! A wedged daemon would otherwise hang every call waiting on it. Calls
+//! running longer than the request timeout fail, and after enough timeouts
+//! in a row the circuit breaker fails further calls straight away for a
+//! while instead of piling them up behind the daemon.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long calls to aria2 may take and when to stop calling it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcTimeouts {
+    /// Longest wait for the connection to the RPC endpoint
+    pub connect: Duration,
+    /// Longest wait for a call to complete, connecting included
+    pub request: Duration,
+    /// Timeouts in a row that open the circuit breaker, 0 never opens it
+    pub breaker_threshold: u32,
+    /// How long an open circuit breaker fails calls before aria2 is tried again
+    pub breaker_cooldown: Duration,
+}
+
+impl Default for RpcTimeouts {
+    fn default() -> Self {
+        Self {
+            connect: Duration::from_secs(5),
+            request: Duration::from_secs(30),
+            breaker_threshold: 5,
+            breaker_cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RpcTimeouts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the longest wait for the connection
+    pub fn connect(mut self, timeout: Duration) -> Self {
+        self.connect = timeout;
+        self
+    }
+
+    /// Set the longest wait for a call
+    pub fn request(mut self, timeout: Duration) -> Self {
+        self.request = timeout;
+        self
+    }
+
+    /// Fail calls for `cooldown` after `threshold` timeouts in a row
+    pub fn circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker_threshold = threshold;
+        self.breaker_cooldown = cooldown;
+        self
+    }
+}
\ No newline at end of file
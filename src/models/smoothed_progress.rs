@@ -0,0 +1,62 @@
+//! Smoothed download progress
+//!
+//! Pairs the progress reported by the backend, whose speed and ETA jump
+//! around from one poll to the next, with an averaged speed and the ETA
+//! derived from it, and tells how the progress can be shown.
+
+use crate::models::progress_kind::{self, ProgressKind};
+use crate::types::DownloadProgress;
+use crate::types::serialize::download_progress;
+use serde::{Deserialize, Serialize};
+
+/// Progress with both the instant and the averaged speed and ETA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmoothedProgress {
+    /// Progress as reported by the backend
+    #[serde(with = "download_progress")]
+    pub progress: DownloadProgress,
+    /// Moving average of the speed in bytes per second
+    pub average_speed_bps: u64,
+    /// Remaining time at the average speed, `None` when the size is unknown or nothing moves
+    pub average_eta_seconds: Option<u64>,
+    /// Whether a progress bar or a spinner fits the download
+    pub kind: ProgressKind,
+}
+
+impl SmoothedProgress {
+    /// Combine reported progress with an averaged speed
+    pub fn new(progress: DownloadProgress, average_speed_bps: u64) -> Self {
+        let average_eta_seconds = progress.total_bytes
+            .filter(|_| average_speed_bps > 0)
+            .map(|total| total.saturating_sub(progress.downloaded_bytes) / average_speed_bps);
+
+        Self {
+            kind: ProgressKind::of(&progress),
+            progress,
+            average_speed_bps,
+            average_eta_seconds,
+        }
+    }
+
+    /// Percentage completed, `None` while the size is unknown
+    ///
+    /// See [`completion_percentage`](crate::models::completion_percentage).
+    pub fn completion_percentage(&self) -> Option<f64> {
+        progress_kind::completion_percentage(&self.progress)
+    }
+
+    /// Bytes received so far, shown next to a spinner when the size is unknown
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.progress.downloaded_bytes
+    }
+
+    /// Speed reported by the backend for the last sample
+    pub fn instant_speed_bps(&self) -> u64 {
+        self.progress.speed_bps
+    }
+
+    /// ETA reported by the backend for the last sample
+    pub fn instant_eta_seconds(&self) -> Option<u64> {
+        self.progress.eta_seconds
+    }
+}
\ No newline at end of file
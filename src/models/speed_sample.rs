@@ -0,0 +1,56 @@
+//! Speed samples
+//!
+//! Transfer speeds of all active downloads taken together at a fixed
+//! cadence, so dashboards can draw per-task and overall speed without
+//! diffing byte counters themselves.
+
+use crate::types::TaskId;
+use crate::types::serialize::system_time_millis;
+use serde::{Deserialize, Serialize};
+use std::time::SystemTime;
+
+/// Speed of one task when a sample was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskSpeed {
+    pub task_id: TaskId,
+    /// Speed reported by the backend in bytes per second
+    pub speed_bps: u64,
+    /// Moving average of the speed in bytes per second
+    pub average_speed_bps: u64,
+}
+
+/// Speeds of all active tasks at one point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpeedSample {
+    #[serde(rename = "sampled_at_ms", with = "system_time_millis")]
+    pub sampled_at: SystemTime,
+    pub tasks: Vec<TaskSpeed>,
+    /// Sum of the reported speeds of all tasks
+    pub total_speed_bps: u64,
+    /// Sum of the average speeds of all tasks
+    pub total_average_speed_bps: u64,
+}
+
+impl SpeedSample {
+    /// Combine the speeds of the given tasks
+    pub fn new(sampled_at: SystemTime, tasks: Vec<TaskSpeed>) -> Self {
+        let total_speed_bps = tasks.iter().fold(0u64, |total, task| total.saturating_add(task.speed_bps));
+        let total_average_speed_bps = tasks.iter().fold(0u64, |total, task| total.saturating_add(task.average_speed_bps));
+        Self {
+            sampled_at,
+            tasks,
+            total_speed_bps,
+            total_average_speed_bps,
+        }
+    }
+
+    /// Get the speed of one task, `None` if it was not sampled
+    pub fn task(&self, task_id: TaskId) -> Option<&TaskSpeed> {
+        self.tasks.iter().find(|task| task.task_id == task_id)
+    }
+
+    /// Check if nothing was transferring when the sample was taken
+    pub fn is_idle(&self) -> bool {
+        self.total_speed_bps == 0
+    }
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+//! Task import and export
+//!
+//! A stable JSON representation of the task list, used to back up the queue
+//! or move downloads to another machine independently of the SQLite files.
+//! Credentials are never exported.
+
+use crate::error::DownloadError;
+use crate::models::{DownloadOptions, TaskStatus};
+use crate::types::TaskId;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Version of the export schema written by this crate
+pub const TASK_EXPORT_VERSION: u32 = 1;
+
+/// An exported task list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskExport {
+    /// Schema version, see [`TASK_EXPORT_VERSION`]
+    pub version: u32,
+    /// Unix time the export was taken
+    pub exported_at: u64,
+    pub tasks: Vec<ExportedTask>,
+}
+
+/// A single exported task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTask {
+    pub url: String,
+    /// All source URLs of a multi-source download, empty otherwise
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    pub target_path: PathBuf,
+    pub status: TaskStatus,
+    /// Options the task was added with, including its priority and checksum
+    #[serde(default)]
+    pub options: DownloadOptions,
+}
+
+impl TaskExport {
+    /// Create an export of the given tasks taken now
+    pub fn new(tasks: Vec<ExportedTask>) -> Self {
+        let exported_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            version: TASK_EXPORT_VERSION,
+            exported_at,
+            tasks,
+        }
+    }
+
+    /// Write the export as pretty-printed JSON
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self)
+            .map_err(|e| DownloadError::General(format!("Failed to write task export: {}", e)))
+    }
+
+    /// Read an export, rejecting schema versions newer than this crate understands
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let export: Self = serde_json::from_reader(reader)
+            .map_err(|e| DownloadError::General(format!("Invalid task export: {}", e)))?;
+
+        if export.version > TASK_EXPORT_VERSION {
+            return Err(DownloadError::General(format!(
+                "Unsupported task export version {}, expected at most {}",
+                export.version, TASK_EXPORT_VERSION
+            )));
+        }
+        Ok(export)
+    }
+}
+
+/// What to do with an imported task whose URL and target path are already in use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportPolicy {
+    /// Keep the existing task and skip the imported one
+    #[default]
+    SkipExisting,
+    /// Cancel the existing task and import the new one in its place
+    Replace,
+    /// Import the task next to the existing one under a free file name
+    KeepBoth,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Tasks created by the import
+    pub imported: Vec<TaskId>,
+    /// Target paths of tasks skipped because they already exist
+    pub skipped: Vec<PathBuf>,
+    /// Target paths of tasks that could not be imported, with the reason
+    pub failed: Vec<(PathBuf, String)>,
+}
\ No newline at end of file
@@ -7,6 +7,12 @@ use crate::types::TaskId;
 use crate::utils::url_normalization::is_valid_url_hash;
 use serde::{Deserialize, Serialize};
 
+/// Failure reason of tasks that expired before they started
+pub const EXPIRED_REASON: &str = "Expired before the download started";
+
+/// Failure reason of tasks the user cancelled but kept
+pub const CANCELLED_REASON: &str = "Cancelled by the user";
+
 /// Extended task status that includes duplicate detection states
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskStatus {
@@ -16,12 +22,20 @@ pub enum TaskStatus {
     Downloading,
     /// Task has been paused
     Paused,
+    /// Task was paused because its disk ran full and resumes once space is free
+    PausedNoSpace,
     /// Task completed successfully
     Completed,
     /// Task failed with error message
     Failed(String),
     /// Task is a duplicate of another task
     Duplicate(TaskId),
+    /// Download completed but a post-download hook failed with this error
+    PostProcessingFailed(String),
+    /// Task was still waiting to start when its deadline passed
+    Expired,
+    /// Task was cancelled by the user and kept in the history
+    Cancelled,
 }
 
 impl TaskStatus {
@@ -30,7 +44,10 @@ impl TaskStatus {
         matches!(self,
             TaskStatus::Waiting |
             TaskStatus::Paused |
-            TaskStatus::Failed(_)
+            TaskStatus::PausedNoSpace |
+            TaskStatus::Failed(_) |
+            TaskStatus::Expired |
+            TaskStatus::Cancelled
         )
     }
 
@@ -39,7 +56,7 @@ impl TaskStatus {
         match self {
             TaskStatus::Waiting => crate::types::DownloadStatus::Waiting,
             TaskStatus::Downloading => crate::types::DownloadStatus::Downloading,
-            TaskStatus::Paused => crate::types::DownloadStatus::Paused,
+            TaskStatus::Paused | TaskStatus::PausedNoSpace => crate::types::DownloadStatus::Paused,
             TaskStatus::Completed => crate::types::DownloadStatus::Completed,
             TaskStatus::Failed(msg) => crate::types::DownloadStatus::Failed(msg.clone()),
             TaskStatus::Duplicate(_) => {
@@ -47,6 +64,10 @@ impl TaskStatus {
                 // since the original task provides the actual download
                 crate::types::DownloadStatus::Completed
             }
+            // The file is not where the hooks should have left it, so it is not usable yet
+            TaskStatus::PostProcessingFailed(msg) => crate::types::DownloadStatus::Failed(msg.clone()),
+            TaskStatus::Expired => crate::types::DownloadStatus::Failed(EXPIRED_REASON.to_string()),
+            TaskStatus::Cancelled => crate::types::DownloadStatus::Failed(CANCELLED_REASON.to_string()),
         }
     }
 
@@ -57,6 +78,8 @@ impl TaskStatus {
             crate::types::DownloadStatus::Downloading => TaskStatus::Downloading,
             crate::types::DownloadStatus::Paused => TaskStatus::Paused,
             crate::types::DownloadStatus::Completed => TaskStatus::Completed,
+            crate::types::DownloadStatus::Failed(msg) if msg == EXPIRED_REASON => TaskStatus::Expired,
+            crate::types::DownloadStatus::Failed(msg) if msg == CANCELLED_REASON => TaskStatus::Cancelled,
             crate::types::DownloadStatus::Failed(msg) => TaskStatus::Failed(msg),
         }
     }
@@ -97,7 +120,9 @@ impl TaskValidator {
             // Valid transitions to Duplicate
             (TaskStatus::Waiting, TaskStatus::Duplicate(_)) => Ok(()),
             (TaskStatus::Paused, TaskStatus::Duplicate(_)) => Ok(()),
+            (TaskStatus::PausedNoSpace, TaskStatus::Duplicate(_)) => Ok(()),
             (TaskStatus::Failed(_), TaskStatus::Duplicate(_)) => Ok(()),
+            (TaskStatus::Cancelled, TaskStatus::Duplicate(_)) => Ok(()),
 
             // Invalid transitions to Duplicate
             (TaskStatus::Downloading, TaskStatus::Duplicate(_)) => {
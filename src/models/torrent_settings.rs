@@ -0,0 +1,199 @@
+//! BitTorrent and DHT settings of the aria2 daemon
+//!
+//! Distributing models over BitTorrent needs the peer ports, DHT,
+//! encryption, peer count and seeding tuned. The settings are validated
+//! and handed to aria2: ports and DHT on the command line of a managed
+//! daemon, the rest as global options, so no separate aria2 config file is
+//! needed.
+
+use crate::Result;
+use crate::error::DownloadError;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A port or an inclusive range of ports, written `6881` or `6881-6999`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    /// A single port
+    pub fn single(port: u16) -> Self {
+        Self { start: port, end: port }
+    }
+
+    /// Ports `start` to `end`, both included
+    pub fn new(start: u16, end: u16) -> Self {
+        Self { start, end }
+    }
+
+    /// Check that the range is not empty and leaves out port 0
+    pub fn validate(&self) -> Result<()> {
+        if self.start == 0 || self.start > self.end {
+            return Err(DownloadError::InvalidOption(format!("invalid port range {}", self)));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = DownloadError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || DownloadError::InvalidOption(format!("invalid port range: {}", s));
+        let range = match s.trim().split_once('-') {
+            Some((start, end)) => PortRange::new(
+                start.trim().parse().map_err(|_| invalid())?,
+                end.trim().parse().map_err(|_| invalid())?,
+            ),
+            None => PortRange::single(s.trim().parse().map_err(|_| invalid())?),
+        };
+        range.validate()?;
+        Ok(range)
+    }
+}
+
+/// BitTorrent settings, unset fields keep aria2's own defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TorrentSettings {
+    /// Ports peers connect to (`listen-port`)
+    pub listen_ports: Option<PortRange>,
+    /// Whether peers are found through the DHT (`enable-dht`, turning it off also sets `enable-dht6`)
+    pub dht: Option<bool>,
+    /// UDP ports of the DHT (`dht-listen-port`)
+    pub dht_listen_ports: Option<PortRange>,
+    /// Only talk to peers over encrypted connections (`bt-require-crypto`, `bt-min-crypto-level=arc4`)
+    pub require_encryption: Option<bool>,
+    /// Most peers per torrent, 0 for no limit (`bt-max-peers`)
+    pub max_peers: Option<u32>,
+    /// Share ratio seeding stops at, 0 to seed for `seed_time` only (`seed-ratio`)
+    pub seed_ratio: Option<f64>,
+    /// How long a completed torrent is seeded, zero for not at all (`seed-time`)
+    pub seed_time: Option<Duration>,
+}
+
+impl TorrentSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the ports peers connect to
+    pub fn listen_ports(mut self, ports: PortRange) -> Self {
+        self.listen_ports = Some(ports);
+        self
+    }
+
+    /// Turn the DHT on or off
+    pub fn dht(mut self, enabled: bool) -> Self {
+        self.dht = Some(enabled);
+        self
+    }
+
+    /// Set the UDP ports of the DHT
+    pub fn dht_listen_ports(mut self, ports: PortRange) -> Self {
+        self.dht_listen_ports = Some(ports);
+        self
+    }
+
+    /// Require encrypted peer connections
+    pub fn require_encryption(mut self, required: bool) -> Self {
+        self.require_encryption = Some(required);
+        self
+    }
+
+    /// Limit the peers per torrent, 0 for no limit
+    pub fn max_peers(mut self, max_peers: u32) -> Self {
+        self.max_peers = Some(max_peers);
+        self
+    }
+
+    /// Stop seeding at `ratio`
+    pub fn seed_ratio(mut self, ratio: f64) -> Self {
+        self.seed_ratio = Some(ratio);
+        self
+    }
+
+    /// Stop seeding after `time`
+    pub fn seed_time(mut self, time: Duration) -> Self {
+        self.seed_time = Some(time);
+        self
+    }
+
+    /// Check if every setting keeps aria2's default
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the ports and the seed ratio
+    pub fn validate(&self) -> Result<()> {
+        for ports in [self.listen_ports, self.dht_listen_ports].into_iter().flatten() {
+            ports.validate()?;
+        }
+        if let Some(ratio) = self.seed_ratio.filter(|ratio| !ratio.is_finite() || *ratio < 0.0) {
+            return Err(DownloadError::InvalidOption(format!(
+                "seed ratio must be a finite number of at least 0, got {}", ratio
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check if any setting only takes effect when aria2 starts
+    pub fn has_startup_options(&self) -> bool {
+        self.listen_ports.is_some() || self.dht.is_some() || self.dht_listen_ports.is_some()
+    }
+
+    /// Command line arguments of the settings aria2 only reads at startup
+    pub fn startup_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(ports) = self.listen_ports {
+            args.push(format!("--listen-port={}", ports));
+        }
+        match self.dht {
+            Some(true) => args.push("--enable-dht=true".to_string()),
+            // aria2 runs an IPv6 DHT next to the IPv4 one
+            Some(false) => args.extend(["--enable-dht=false".to_string(), "--enable-dht6=false".to_string()]),
+            None => {}
+        }
+        if let Some(ports) = self.dht_listen_ports {
+            args.push(format!("--dht-listen-port={}", ports));
+        }
+        args
+    }
+
+    /// Global aria2 options of the settings that can change while aria2 runs
+    pub fn to_aria2_options(&self) -> Map<String, Value> {
+        let mut options = Map::new();
+        if let Some(required) = self.require_encryption {
+            options.insert("bt-require-crypto".to_string(), json!(required.to_string()));
+            let level = if required { "arc4" } else { "plain" };
+            options.insert("bt-min-crypto-level".to_string(), json!(level));
+        }
+        if let Some(max_peers) = self.max_peers {
+            options.insert("bt-max-peers".to_string(), json!(max_peers.to_string()));
+        }
+        if let Some(ratio) = self.seed_ratio {
+            options.insert("seed-ratio".to_string(), json!(ratio.to_string()));
+        }
+        if let Some(time) = self.seed_time {
+            // aria2 takes the seed time in minutes, fractions included
+            options.insert("seed-time".to_string(), json!((time.as_secs_f64() / 60.0).to_string()));
+        }
+        options
+    }
+}
\ No newline at end of file
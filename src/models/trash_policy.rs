@@ -0,0 +1,72 @@
+//! Trash for deleted tasks
+//!
+//! Deleting a task into the trash keeps its database row and, when a trash
+//! directory is set, moves its file there, so an accidental delete can be
+//! undone until the trash is purged.
+
+use crate::types::TaskId;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How [`PersistentAria2Manager::delete_task`](crate::PersistentAria2Manager::delete_task) deletes a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeleteMode {
+    /// Stop the download and keep the task in the trash until it is restored or purged
+    Trash,
+    /// Remove the task and its partial or trashed files for good
+    Purge,
+}
+
+/// Where trashed files go and how long trashed tasks are kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashPolicy {
+    /// Directory trashed files are moved to, `None` leaves them in place
+    pub dir: Option<PathBuf>,
+    /// How long a task stays in the trash before garbage collection purges it
+    pub ttl: Duration,
+}
+
+impl Default for TrashPolicy {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            ttl: Duration::from_secs(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+impl TrashPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the files of trashed tasks to `dir`
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// Set how long tasks stay in the trash
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Check if a task deleted at `deleted_at` is due to be purged at `now`
+    pub fn is_expired(&self, deleted_at: SystemTime, now: SystemTime) -> bool {
+        now.duration_since(deleted_at).is_ok_and(|age| age >= self.ttl)
+    }
+}
+
+/// A task in the trash
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashedTask {
+    pub task_id: TaskId,
+    /// When the task was deleted
+    pub deleted_at: SystemTime,
+    /// Where the task's file was before it was deleted
+    pub original_path: PathBuf,
+    /// Where the file was moved to, `None` when it stayed in place
+    pub trash_path: Option<PathBuf>,
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+//! URL validation policy
+//!
+//! Decides which URLs the managers accept before a download is created, so
+//! empty or malformed URLs fail right away instead of inside the backend.
+
+use crate::error::DownloadError;
+use serde::{Deserialize, Serialize};
+use url::{Host, Url};
+
+/// Schemes accepted unless configured otherwise
+pub const DEFAULT_ALLOWED_SCHEMES: &[&str] = &["http", "https", "ftp", "sftp", "magnet"];
+
+/// Rules a download URL has to satisfy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UrlPolicy {
+    /// Accepted schemes, lowercase
+    pub allowed_schemes: Vec<String>,
+    /// Accept single-label hosts like `localhost` or `mirror`
+    pub allow_plain_hosts: bool,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: DEFAULT_ALLOWED_SCHEMES.iter().map(|scheme| scheme.to_string()).collect(),
+            allow_plain_hosts: false,
+        }
+    }
+}
+
+impl UrlPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept only the given schemes
+    pub fn allowed_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_schemes = schemes.into_iter()
+            .map(|scheme| scheme.as_ref().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Accept single-label hosts like `localhost`, e.g. for local test servers
+    pub fn allow_plain_hosts(mut self, allow: bool) -> Self {
+        self.allow_plain_hosts = allow;
+        self
+    }
+
+    /// Check if `scheme` is accepted
+    pub fn allows_scheme(&self, scheme: &str) -> bool {
+        self.allowed_schemes.iter().any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+    }
+
+    /// Check a download URL
+    ///
+    /// Fails with [`DownloadError::InvalidUrl`] naming the URL and what is wrong with it.
+    pub fn validate(&self, url: &str) -> Result<(), DownloadError> {
+        let invalid = |reason: String| Err(DownloadError::InvalidUrl(reason));
+
+        if url.trim().is_empty() {
+            return invalid("URL is empty".to_string());
+        }
+        if url.trim() != url {
+            return invalid(format!("'{}' has leading or trailing whitespace", url));
+        }
+
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(e) => return invalid(format!("'{}' is not a valid URL: {}", url, e)),
+        };
+
+        let scheme = parsed.scheme();
+        if !self.allows_scheme(scheme) {
+            return invalid(format!(
+                "'{}' uses scheme '{}', allowed schemes are {}",
+                url, scheme, self.allowed_schemes.join(", ")
+            ));
+        }
+
+        // Magnet links identify content instead of a server
+        if scheme == "magnet" {
+            let has_topic = parsed.query_pairs().any(|(key, _)| key.starts_with("xt"));
+            if !has_topic {
+                return invalid(format!("'{}' has no exact topic (xt) parameter", url));
+            }
+            return Ok(());
+        }
+
+        match parsed.host() {
+            None => invalid(format!("'{}' has no host", url)),
+            Some(Host::Domain(domain)) if !domain.contains('.') && !self.allow_plain_hosts => invalid(format!(
+                "'{}' uses plain host '{}', enable allow_plain_hosts to accept it",
+                url, domain
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,181 @@
+//! File name resolution
+//!
+//! Picks the name a download is saved under from the `Content-Disposition`
+//! header or the URL, and strips anything that could escape the download
+//! directory.
+
+use url::Url;
+
+/// Name used when neither the server nor the URL provide one
+pub const DEFAULT_FILENAME: &str = "download";
+
+/// Longest file name in bytes most filesystems accept
+pub const DEFAULT_MAX_FILENAME_LENGTH: usize = 255;
+
+/// Resolve the file name of a download
+///
+/// Preference order is the `Content-Disposition` header, the URL the request
+/// was redirected to and the requested URL.
+pub fn resolve_filename(content_disposition: Option<&str>, final_url: &str, original_url: &str) -> String {
+    content_disposition
+        .and_then(filename_from_content_disposition)
+        .or_else(|| filename_from_url(final_url))
+        .or_else(|| filename_from_url(original_url))
+        .unwrap_or_else(|| DEFAULT_FILENAME.to_string())
+}
+
+/// Get the file name from a `Content-Disposition` header value
+///
+/// An RFC 5987 `filename*` parameter wins over a plain `filename`.
+pub fn filename_from_content_disposition(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+
+    for parameter in split_parameters(value).into_iter().skip(1) {
+        let Some((name, value)) = parameter.split_once('=') else {
+            continue;
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "filename*" => extended = decode_extended_value(value.trim()),
+            "filename" => plain = Some(unquote(value.trim())),
+            _ => {}
+        }
+    }
+
+    extended.or(plain).and_then(|name| sanitize_filename(&name))
+}
+
+/// Get the file name from the last path segment of a URL, ignoring the query
+pub fn filename_from_url(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let segment = url.path_segments()?.next_back()?;
+    sanitize_filename(&percent_decode(segment))
+}
+
+/// Reduce a suggested name to a plain file name
+///
+/// Directories are dropped and names that are empty or refer to a directory
+/// are rejected.
+pub fn sanitize_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    let name = name.trim();
+
+    if name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Shorten a file name to at most `max_len` bytes, keeping its extension
+///
+/// The extension is dropped as well when it alone would not leave room for
+/// the rest of the name. Names are cut on character boundaries.
+pub fn truncate_filename(name: &str, max_len: usize) -> String {
+    let max_len = max_len.max(1);
+    if name.len() <= max_len {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(index) if index > 0 && name.len() - index < max_len => name.split_at(index),
+        _ => (name, ""),
+    };
+    let mut end = max_len - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let stem = stem[..end].trim_end_matches([' ', '.']);
+    if stem.is_empty() {
+        return truncate_filename(DEFAULT_FILENAME, max_len);
+    }
+    format!("{}{}", stem, extension)
+}
+
+/// Split a header value on `;`, keeping quoted strings together
+fn split_parameters(value: &str) -> Vec<&str> {
+    let mut parameters = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                parameters.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parameters.push(&value[start..]);
+
+    parameters
+}
+
+/// Remove the quotes and escapes of a quoted string
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) else {
+        return value.to_string();
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            c => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// Decode an RFC 5987 value such as `UTF-8''na%C3%AFve.zip`
+fn decode_extended_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let bytes = percent_decode_bytes(parts.next()?);
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+/// Decode `%XX` escapes, replacing invalid UTF-8
+fn percent_decode(value: &str) -> String {
+    String::from_utf8_lossy(&percent_decode_bytes(value)).into_owned()
+}
+
+fn percent_decode_bytes(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex_value(bytes[index + 1]), hex_value(bytes[index + 2])) {
+                decoded.push(high << 4 | low);
+                index += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[index]);
+        index += 1;
+    }
+
+    decoded
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+//! Pre-flight download probing
+//!
+//! Asks the server about a URL before downloading it: the name to save it
+//! under, its size and whether transfers can be resumed. Redirects are
+//! followed so the answer describes the file that will actually be fetched.
+
+pub mod filename;
+pub mod validators;
+
+pub use filename::{resolve_filename, filename_from_url, filename_from_content_disposition, truncate_filename, DEFAULT_FILENAME, DEFAULT_MAX_FILENAME_LENGTH};
+pub use validators::RemoteValidators;
+
+use crate::Result;
+use reqwest::header::{HeaderMap, HeaderName, ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// How long a probe may take by default
+pub const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// What the server reported about a download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteMetadata {
+    /// URL after following redirects
+    pub final_url: String,
+    /// Name to save the file under
+    pub filename: String,
+    /// Size in bytes, if the server reported it
+    pub size: Option<u64>,
+    /// Whether the server accepts range requests, so transfers can be resumed
+    pub resumable: bool,
+    /// Media type of the file
+    pub content_type: Option<String>,
+    /// Entity tag of the file's current version
+    pub etag: Option<String>,
+    /// When the file last changed, as the `Last-Modified` header reported it
+    pub last_modified: Option<String>,
+}
+
+impl RemoteMetadata {
+    /// Build the metadata from a probe response
+    ///
+    /// `status` tells a ranged `206 Partial Content` answer, whose size comes
+    /// from `Content-Range`, apart from a full one.
+    pub fn from_response(original_url: &str, final_url: &str, status: StatusCode, headers: &HeaderMap) -> Self {
+        let header = |name: HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+
+        let (size, resumable) = if status == StatusCode::PARTIAL_CONTENT {
+            // Content-Range: bytes 0-0/12345
+            let size = header(CONTENT_RANGE)
+                .and_then(|range| range.rsplit_once('/'))
+                .and_then(|(_, total)| total.trim().parse().ok());
+            (size, true)
+        } else {
+            let size = header(CONTENT_LENGTH).and_then(|length| length.trim().parse().ok());
+            let resumable = header(ACCEPT_RANGES)
+                .map(|ranges| ranges.split(',').any(|unit| unit.trim().eq_ignore_ascii_case("bytes")))
+                .unwrap_or(false);
+            (size, resumable)
+        };
+
+        Self {
+            final_url: final_url.to_string(),
+            filename: resolve_filename(header(CONTENT_DISPOSITION), final_url, original_url),
+            size,
+            resumable,
+            content_type: header(CONTENT_TYPE).map(str::to_string),
+            etag: header(ETAG).map(str::to_string),
+            last_modified: header(LAST_MODIFIED).map(str::to_string),
+        }
+    }
+}
+
+/// What a pre-flight probe found out, to show before a download is added
+pub type ProbeResult = RemoteMetadata;
+
+/// Probes URLs with a HEAD request
+#[derive(Debug, Clone)]
+pub struct DownloadProbe {
+    http: reqwest::Client,
+}
+
+impl Default for DownloadProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DownloadProbe {
+    pub fn new() -> Self {
+        Self::with_timeout(Duration::from_secs(DEFAULT_PROBE_TIMEOUT_SECS))
+    }
+
+    /// Create a probe giving up on servers that take longer than `timeout`
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self { http }
+    }
+
+    /// Ask the server about `url`
+    ///
+    /// Servers that refuse HEAD requests are asked for the first byte
+    /// instead, which also shows whether they support ranges.
+    pub async fn probe(&self, url: &str) -> Result<RemoteMetadata> {
+        let response = self.http.head(url).send().await?;
+        let response = if response.status().is_success() {
+            response
+        } else {
+            log::debug!("HEAD {} returned {}, retrying with a ranged GET", url, response.status());
+            self.http.get(url)
+                .header(RANGE, "bytes=0-0")
+                .send().await?
+                .error_for_status()?
+        };
+
+        Ok(RemoteMetadata::from_response(url, response.url().as_str(), response.status(), response.headers()))
+    }
+
+    /// Ask the server about `url` unless its file still matches `validators`
+    ///
+    /// Sends the stored ETag and modification time as `If-None-Match` and
+    /// `If-Modified-Since`. Returns `None` when the server answers
+    /// `304 Not Modified` or reports the same ETag, servers honouring
+    /// neither report the file as changed.
+    pub async fn probe_if_changed(&self, url: &str, validators: &RemoteValidators) -> Result<Option<RemoteMetadata>> {
+        let conditional = |mut request: reqwest::RequestBuilder| {
+            if let Some(etag) = &validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+            request
+        };
+
+        let mut response = conditional(self.http.head(url)).send().await?;
+        if !response.status().is_success() && response.status() != StatusCode::NOT_MODIFIED {
+            log::debug!("HEAD {} returned {}, retrying with a ranged GET", url, response.status());
+            response = conditional(self.http.get(url).header(RANGE, "bytes=0-0"))
+                .send().await?;
+            if response.status() != StatusCode::NOT_MODIFIED {
+                response = response.error_for_status()?;
+            }
+        }
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let metadata = RemoteMetadata::from_response(url, response.url().as_str(), response.status(), response.headers());
+        if metadata.etag.is_some() && metadata.etag == validators.etag {
+            return Ok(None);
+        }
+        Ok(Some(metadata))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+//! Remote file validators
+//!
+//! The ETag, modification time and size a server reports identify one
+//! version of a file. Comparing them tells whether bytes downloaded earlier
+//! still belong to the file the server has now.
+
+use crate::probe::RemoteMetadata;
+use serde::{Deserialize, Serialize};
+
+/// What identifies the version of a remote file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteValidators {
+    pub etag: Option<String>,
+    /// `Last-Modified` header value, compared verbatim
+    pub last_modified: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl RemoteValidators {
+    /// Take the validators from a probe result
+    pub fn from_metadata(metadata: &RemoteMetadata) -> Self {
+        Self {
+            etag: metadata.etag.clone(),
+            last_modified: metadata.last_modified.clone(),
+            size: metadata.size,
+        }
+    }
+
+    /// Check if the server reported nothing to compare
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none() && self.size.is_none()
+    }
+
+    /// Check if `current` describes another version of the file than `self`
+    ///
+    /// The strongest validator both sides know decides: the ETag, then the
+    /// modification time, then the size. Without a common one the file
+    /// counts as unchanged.
+    pub fn changed(&self, current: &RemoteValidators) -> bool {
+        if let (Some(stored), Some(current)) = (&self.etag, &current.etag) {
+            return stored != current;
+        }
+        if let (Some(stored), Some(current)) = (&self.last_modified, &current.last_modified) {
+            return stored != current;
+        }
+        matches!((self.size, current.size), (Some(stored), Some(current)) if stored != current)
+    }
+}
\ No newline at end of file
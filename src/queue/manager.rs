@@ -1,12 +1,16 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use std::path::PathBuf;
-use tokio::sync::{RwLock, Mutex};
-use anyhow::{Result, bail};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{RwLock, Mutex, broadcast, watch};
+use crate::Result;
 use async_trait::async_trait;
 use crate::types::{TaskId, DownloadTask, DownloadStatus, DownloadProgress};
-use crate::traits::{DownloadEventHandler, DownloadManager};
+use crate::traits::{DownloadEventHandler, DownloadEventListener, DownloadManager, DuplicateDecisionHandler, QueueScheduler, QueuedTask};
 use crate::error::DownloadError;
+use crate::models::{Priority, RetryPolicy, DownloadOptions, DownloadEvent, TargetAction, UrlPolicy, HostLimits, OwnerQuotas, ListOrder, ProgressDelivery, HandlerId, TaskStatus, EXPIRED_REASON, DuplicatePolicy, DuplicatePreview, DuplicateCandidate, DuplicateReason};
+use crate::services::{BandwidthLimiter, RetryTracker, EventBus, DuplicateResolver, DuplicateDetector, CompletionWaiters, TaskOutcome, ThrottledHandler, HandlerRegistry, WeakHandler, ListenerHandler};
+use crate::queue::scheduler::{TaskScheduler, PriorityScheduler};
 
 /// Maximum number of concurrent downloads
 const MAX_CONCURRENT_DOWNLOADS: usize = 3;
@@ -15,14 +19,44 @@ const MAX_CONCURRENT_DOWNLOADS: usize = 3;
 pub struct TaskQueueManager {
     /// Active download tasks (currently downloading)
     active_tasks: Arc<RwLock<HashMap<TaskId, DownloadTask>>>,
-    /// Queued tasks waiting to start
+    /// Queued tasks waiting to start, in the order they were queued
     queued_tasks: Arc<Mutex<VecDeque<DownloadTask>>>,
+    /// Task priorities
+    priorities: Arc<RwLock<HashMap<TaskId, Priority>>>,
+    /// Group or tenant of each grouped task
+    groups: Arc<RwLock<HashMap<TaskId, String>>>,
+    /// User or tenant of each owned task
+    owners: Arc<RwLock<HashMap<TaskId, String>>>,
+    /// Deadlines of the tasks that have not started yet
+    deadlines: Arc<RwLock<HashMap<TaskId, SystemTime>>>,
+    /// Picks the queued task that starts next
+    scheduler: Arc<dyn QueueScheduler>,
     /// All tasks by ID
     all_tasks: Arc<RwLock<HashMap<TaskId, DownloadTask>>>,
     /// Task progress tracking
     progress: Arc<RwLock<HashMap<TaskId, DownloadProgress>>>,
     /// Event handlers
     event_handlers: Arc<RwLock<Vec<Arc<dyn DownloadEventHandler>>>>,
+    /// Registrations of the event handlers, owning their list
+    handlers: Arc<HandlerRegistry>,
+    /// Configured speed limits
+    bandwidth: Arc<BandwidthLimiter>,
+    /// Retry policies and attempt counts
+    retry: Arc<RetryTracker>,
+    /// Channel subscribers, also registered as an event handler
+    events: Arc<EventBus>,
+    /// Decides how duplicate requests are handled
+    duplicates: Arc<DuplicateResolver>,
+    /// Index consulted for duplicates instead of the task registry, when set
+    detector: Arc<RwLock<Option<Arc<dyn DuplicateDetector>>>>,
+    /// Rules download URLs have to satisfy
+    url_policy: Arc<RwLock<UrlPolicy>>,
+    /// How many downloads may run at once per host
+    host_limits: Arc<RwLock<HostLimits>>,
+    /// How many downloads may run at once per owner
+    owner_quotas: Arc<RwLock<OwnerQuotas>>,
+    /// Callers waiting for tasks to finish
+    completions: Arc<CompletionWaiters>,
 }
 
 impl Default for TaskQueueManager {
@@ -32,27 +66,127 @@ impl Default for TaskQueueManager {
 }
 
 impl TaskQueueManager {
+    /// Create a queue starting waiting tasks by priority
     pub fn new() -> Self {
+        Self::with_scheduler(Arc::new(PriorityScheduler))
+    }
+
+    /// Create a queue whose waiting tasks are started by `scheduler`
+    pub fn with_scheduler(scheduler: Arc<dyn QueueScheduler>) -> Self {
+        let events = Arc::new(EventBus::default());
+        let bus_handler: Arc<dyn DownloadEventHandler> = events.clone();
+        let handlers = Arc::new(HandlerRegistry::new(vec![bus_handler]));
+
         Self {
             active_tasks: Arc::new(RwLock::new(HashMap::new())),
             queued_tasks: Arc::new(Mutex::new(VecDeque::new())),
+            priorities: Arc::new(RwLock::new(HashMap::new())),
+            groups: Arc::new(RwLock::new(HashMap::new())),
+            owners: Arc::new(RwLock::new(HashMap::new())),
+            deadlines: Arc::new(RwLock::new(HashMap::new())),
+            scheduler,
             all_tasks: Arc::new(RwLock::new(HashMap::new())),
             progress: Arc::new(RwLock::new(HashMap::new())),
-            event_handlers: Arc::new(RwLock::new(Vec::new())),
+            event_handlers: handlers.list().clone(),
+            handlers,
+            bandwidth: Arc::new(BandwidthLimiter::new()),
+            retry: Arc::new(RetryTracker::new(RetryPolicy::default())),
+            events,
+            duplicates: Arc::new(DuplicateResolver::new()),
+            detector: Arc::new(RwLock::new(None)),
+            url_policy: Arc::new(RwLock::new(UrlPolicy::default())),
+            host_limits: Arc::new(RwLock::new(HostLimits::default())),
+            owner_quotas: Arc::new(RwLock::new(OwnerQuotas::default())),
+            completions: Arc::new(CompletionWaiters::new()),
+        }
+    }
+
+    /// Create another handle to the same queue state for background tasks
+    fn share(&self) -> Self {
+        Self {
+            active_tasks: self.active_tasks.clone(),
+            queued_tasks: self.queued_tasks.clone(),
+            priorities: self.priorities.clone(),
+            groups: self.groups.clone(),
+            owners: self.owners.clone(),
+            deadlines: self.deadlines.clone(),
+            scheduler: self.scheduler.clone(),
+            all_tasks: self.all_tasks.clone(),
+            progress: self.progress.clone(),
+            event_handlers: self.event_handlers.clone(),
+            handlers: self.handlers.clone(),
+            bandwidth: self.bandwidth.clone(),
+            retry: self.retry.clone(),
+            events: self.events.clone(),
+            duplicates: self.duplicates.clone(),
+            detector: self.detector.clone(),
+            url_policy: self.url_policy.clone(),
+            host_limits: self.host_limits.clone(),
+            owner_quotas: self.owner_quotas.clone(),
+            completions: self.completions.clone(),
         }
     }
 
     /// Add a new download task to the queue
     pub async fn add_task(&self, url: String, target_path: std::path::PathBuf) -> Result<TaskId> {
+        self.add_download_with_priority(url, target_path, Priority::default()).await
+    }
+
+    /// Add a new download task with the given priority
+    ///
+    /// If no download slot is free, or its host is at its limit, the task is
+    /// queued until the scheduler picks it.
+    pub async fn add_download_with_priority(
+        &self,
+        url: String,
+        target_path: std::path::PathBuf,
+        priority: Priority,
+    ) -> Result<TaskId> {
+        self.add_owned_task(url, target_path, priority, None, None).await
+    }
+
+    /// Add a task, starting it if the global, host and owner limits allow
+    ///
+    /// A task still waiting at `expires_at` is failed as expired.
+    async fn add_owned_task(
+        &self,
+        url: String,
+        target_path: PathBuf,
+        priority: Priority,
+        owner: Option<String>,
+        expires_at: Option<SystemTime>,
+    ) -> Result<TaskId> {
+        self.validate_url(&url).await?;
+
         let mut task = DownloadTask::new(url, target_path);
         let task_id = task.id;
 
+        self.priorities.write().await.insert(task_id, priority);
+        if let Some(owner) = owner {
+            self.owners.write().await.insert(task_id, owner);
+        }
+        if let Some(expires_at) = expires_at {
+            self.deadlines.write().await.insert(task_id, expires_at);
+        }
+        if let Some(detector) = self.detector.read().await.clone() {
+            if let Err(e) = detector.record(task_id, &task.url, &task.target_path).await {
+                log::warn!("Failed to index URL of task {}: {}", task_id, e);
+            }
+        }
+
         // Check if we can start immediately or need to queue
-        let active_count = self.active_tasks.read().await.len();
-        let should_start = active_count < MAX_CONCURRENT_DOWNLOADS;
+        let should_start = {
+            let owners = self.owners.read().await;
+            let deadlines = self.deadlines.read().await;
+            let active_tasks = self.active_tasks.read().await;
+            let host_limits = self.host_limits.read().await;
+            let owner_quotas = self.owner_quotas.read().await;
+            can_start(&task, &active_tasks, &host_limits, &owners, &owner_quotas, &deadlines)
+        };
 
         if should_start {
             // Start immediately
+            self.deadlines.write().await.remove(&task_id);
             task.update_status(DownloadStatus::Downloading);
             self.active_tasks.write().await.insert(task_id, task.clone());
 
@@ -63,7 +197,7 @@ impl TaskQueueManager {
             self.notify_status_changed(task_id, DownloadStatus::Waiting, DownloadStatus::Downloading).await;
         } else {
             // Add to queue (keep waiting status)
-            self.queued_tasks.lock().await.push_back(task.clone());
+            self.enqueue(task.clone()).await;
 
             // Store in all_tasks registry
             self.all_tasks.write().await.insert(task_id, task);
@@ -72,11 +206,45 @@ impl TaskQueueManager {
         Ok(task_id)
     }
 
+    /// Set the rules download URLs have to satisfy
+    pub async fn set_url_policy(&self, policy: UrlPolicy) {
+        *self.url_policy.write().await = policy;
+    }
+
+    /// Set how many downloads may run at once per host
+    ///
+    /// Queued tasks are started right away if the new limits allow it.
+    pub async fn set_host_limits(&self, limits: HostLimits) -> Result<()> {
+        *self.host_limits.write().await = limits;
+        self.try_start_next_queued_task().await
+    }
+
+    /// Get the per-host download limits
+    pub async fn host_limits(&self) -> HostLimits {
+        self.host_limits.read().await.clone()
+    }
+
+    /// Check a download URL against the URL policy
+    async fn validate_url(&self, url: &str) -> Result<()> {
+        self.url_policy.read().await.validate(url)
+    }
+
+    /// Record a download whose existing target file is kept, as a completed task
+    async fn add_skipped_task(&self, url: String, target_path: PathBuf) -> TaskId {
+        let mut task = DownloadTask::new(url, target_path);
+        task.update_status(DownloadStatus::Completed);
+        let task_id = task.id;
+
+        self.all_tasks.write().await.insert(task_id, task);
+
+        task_id
+    }
+
     /// Update progress for a task
     pub async fn update_progress(&self, task_id: TaskId, progress: DownloadProgress) -> Result<()> {
         // Verify task exists
         if !self.all_tasks.read().await.contains_key(&task_id) {
-            return Err(DownloadError::TaskNotFound(task_id).into());
+            return Err(DownloadError::TaskNotFound(task_id));
         }
 
         // Update progress
@@ -92,7 +260,7 @@ impl TaskQueueManager {
     pub async fn get_progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
         // First verify task exists
         if !self.all_tasks.read().await.contains_key(&task_id) {
-            return Err(DownloadError::TaskNotFound(task_id).into());
+            return Err(DownloadError::TaskNotFound(task_id));
         }
 
         let progress_map = self.progress.read().await;
@@ -109,7 +277,11 @@ impl TaskQueueManager {
                 .ok_or(DownloadError::TaskNotFound(task_id))?;
 
             if !task.status.can_pause() {
-                bail!("Task cannot be paused in current status: {}", task.status);
+                return Err(DownloadError::InvalidTaskState {
+                    task_id,
+                    operation: "pause",
+                    status: task.status.clone(),
+                });
             }
 
             let old_status = task.status.clone();
@@ -136,14 +308,25 @@ impl TaskQueueManager {
                 .ok_or(DownloadError::TaskNotFound(task_id))?;
 
             if !task.status.can_resume() {
-                bail!("Task cannot be resumed in current status: {}", task.status);
+                return Err(DownloadError::InvalidTaskState {
+                    task_id,
+                    operation: "resume",
+                    status: task.status.clone(),
+                });
             }
 
             let old_status = task.status.clone();
 
             // Check if we can start immediately or need to queue
-            let active_count = self.active_tasks.read().await.len();
-            if active_count < MAX_CONCURRENT_DOWNLOADS {
+            let should_start = {
+                let owners = self.owners.read().await;
+                let deadlines = self.deadlines.read().await;
+                let active_tasks = self.active_tasks.read().await;
+                let host_limits = self.host_limits.read().await;
+                let owner_quotas = self.owner_quotas.read().await;
+                can_start(task, &active_tasks, &host_limits, &owners, &owner_quotas, &deadlines)
+            };
+            if should_start {
                 task.update_status(DownloadStatus::Downloading);
                 (old_status, DownloadStatus::Downloading, Some(task.clone()))
             } else {
@@ -154,11 +337,12 @@ impl TaskQueueManager {
 
         // Update appropriate collections after lock released
         if new_status == DownloadStatus::Downloading {
+            self.deadlines.write().await.remove(&task_id);
             if let Some(task) = task_clone {
                 self.active_tasks.write().await.insert(task_id, task);
             }
         } else if let Some(task) = task_clone {
-            self.queued_tasks.lock().await.push_back(task);
+            self.enqueue(task).await;
         }
 
         // Notify after locks released
@@ -172,6 +356,19 @@ impl TaskQueueManager {
         // Remove from all collections
         self.all_tasks.write().await.remove(&task_id);
         self.active_tasks.write().await.remove(&task_id);
+        self.priorities.write().await.remove(&task_id);
+        self.groups.write().await.remove(&task_id);
+        self.owners.write().await.remove(&task_id);
+        self.deadlines.write().await.remove(&task_id);
+        self.bandwidth.remove_task(task_id).await;
+        self.retry.remove_task(task_id).await;
+        self.events.remove_task(task_id).await;
+        self.completions.resolve(task_id, TaskOutcome::Removed).await;
+        if let Some(detector) = self.detector.read().await.clone() {
+            if let Err(e) = detector.forget(task_id).await {
+                log::warn!("Failed to remove task {} from the duplicate index: {}", task_id, e);
+            }
+        }
 
         // Remove from queue if present
         {
@@ -185,18 +382,236 @@ impl TaskQueueManager {
         Ok(())
     }
 
+    /// Pause every waiting or downloading task
+    ///
+    /// The registry, active set and queue are updated under one set of locks,
+    /// so no queued task is promoted into a slot freed by the pause.
+    pub async fn pause_all_tasks(&self) -> Result<Vec<TaskId>> {
+        self.pause_tasks(None).await
+    }
+
+    /// Pause every waiting or downloading task of `owner`
+    ///
+    /// Slots freed by the pause go to queued tasks of other owners.
+    pub async fn pause_all_for_owner(&self, owner: &str) -> Result<Vec<TaskId>> {
+        let owned = self.tasks_of_owner(owner).await;
+        self.pause_tasks(Some(&owned)).await
+    }
+
+    /// Pause the waiting or downloading tasks among `only`, or all of them
+    async fn pause_tasks(&self, only: Option<&HashSet<TaskId>>) -> Result<Vec<TaskId>> {
+        let changes = {
+            let mut all_tasks = self.all_tasks.write().await;
+            let mut active_tasks = self.active_tasks.write().await;
+            let mut queue = self.queued_tasks.lock().await;
+
+            let mut changes = Vec::new();
+            for task in all_tasks.values_mut() {
+                if only.is_some_and(|only| !only.contains(&task.id)) {
+                    continue;
+                }
+                if matches!(task.status, DownloadStatus::Downloading | DownloadStatus::Waiting) {
+                    changes.push((task.id, task.status.clone()));
+                    task.update_status(DownloadStatus::Paused);
+                    active_tasks.remove(&task.id);
+                }
+            }
+            queue.retain(|task| !changes.iter().any(|(task_id, _)| *task_id == task.id));
+            changes
+        }; // Release locks
+
+        if only.is_some() {
+            self.try_start_next_queued_task().await?;
+        }
+
+        // Notify after locks released
+        let mut paused = Vec::with_capacity(changes.len());
+        for (task_id, old_status) in changes {
+            self.notify_status_changed(task_id, old_status, DownloadStatus::Paused).await;
+            paused.push(task_id);
+        }
+
+        Ok(paused)
+    }
+
+    /// Resume every paused task
+    ///
+    /// Higher priority tasks take the free download slots first; the rest
+    /// are queued, as are tasks whose host is at its limit.
+    pub async fn resume_all_tasks(&self) -> Result<Vec<TaskId>> {
+        let (changes, waiting) = {
+            let priorities = self.priorities.read().await;
+            let owners = self.owners.read().await;
+            let deadlines = self.deadlines.read().await;
+            let mut all_tasks = self.all_tasks.write().await;
+            let mut active_tasks = self.active_tasks.write().await;
+            let host_limits = self.host_limits.read().await;
+            let owner_quotas = self.owner_quotas.read().await;
+
+            let mut resumable: Vec<&mut DownloadTask> = all_tasks.values_mut()
+                .filter(|task| task.status == DownloadStatus::Paused)
+                .collect();
+            resumable.sort_by_key(|task| {
+                std::cmp::Reverse(priorities.get(&task.id).copied().unwrap_or_default())
+            });
+
+            let mut changes = Vec::new();
+            let mut waiting = Vec::new();
+            for task in resumable {
+                let old_status = task.status.clone();
+                if can_start(task, &active_tasks, &host_limits, &owners, &owner_quotas, &deadlines) {
+                    task.update_status(DownloadStatus::Downloading);
+                    active_tasks.insert(task.id, task.clone());
+                } else {
+                    task.update_status(DownloadStatus::Waiting);
+                    waiting.push(task.clone());
+                }
+                changes.push((task.id, old_status, task.status.clone()));
+            }
+            (changes, waiting)
+        }; // Release locks
+
+        {
+            let mut deadlines = self.deadlines.write().await;
+            for (task_id, _, new_status) in &changes {
+                if *new_status == DownloadStatus::Downloading {
+                    deadlines.remove(task_id);
+                }
+            }
+        }
+        for task in waiting {
+            self.enqueue(task).await;
+        }
+
+        // Notify after locks released
+        let mut resumed = Vec::with_capacity(changes.len());
+        for (task_id, old_status, new_status) in changes {
+            self.notify_status_changed(task_id, old_status, new_status).await;
+            resumed.push(task_id);
+        }
+
+        Ok(resumed)
+    }
+
+    /// Cancel and remove every task
+    pub async fn cancel_all_tasks(&self) -> Result<Vec<TaskId>> {
+        let cancelled: Vec<TaskId> = {
+            let mut all_tasks = self.all_tasks.write().await;
+            let mut active_tasks = self.active_tasks.write().await;
+            let mut queue = self.queued_tasks.lock().await;
+
+            active_tasks.clear();
+            queue.clear();
+            all_tasks.drain().map(|(task_id, _)| task_id).collect()
+        }; // Release locks
+
+        self.priorities.write().await.clear();
+        self.groups.write().await.clear();
+        self.owners.write().await.clear();
+        self.deadlines.write().await.clear();
+        {
+            let mut progress = self.progress.write().await;
+            for task_id in &cancelled {
+                progress.remove(task_id);
+            }
+        }
+        for task_id in &cancelled {
+            self.bandwidth.remove_task(*task_id).await;
+            self.retry.remove_task(*task_id).await;
+            self.events.remove_task(*task_id).await;
+            self.completions.resolve(*task_id, TaskOutcome::Removed).await;
+        }
+
+        Ok(cancelled)
+    }
+
+    /// Cancel and remove every task of `owner`
+    ///
+    /// Slots freed by the removal go to queued tasks of other owners.
+    pub async fn cancel_all_for_owner(&self, owner: &str) -> Result<Vec<TaskId>> {
+        let owned = self.tasks_of_owner(owner).await;
+        {
+            let mut all_tasks = self.all_tasks.write().await;
+            let mut active_tasks = self.active_tasks.write().await;
+            let mut queue = self.queued_tasks.lock().await;
+
+            active_tasks.retain(|task_id, _| !owned.contains(task_id));
+            queue.retain(|task| !owned.contains(&task.id));
+            all_tasks.retain(|task_id, _| !owned.contains(task_id));
+        } // Release locks
+
+        let detector = self.detector.read().await.clone();
+        for task_id in &owned {
+            self.priorities.write().await.remove(task_id);
+            self.groups.write().await.remove(task_id);
+            self.owners.write().await.remove(task_id);
+            self.deadlines.write().await.remove(task_id);
+            self.progress.write().await.remove(task_id);
+            self.bandwidth.remove_task(*task_id).await;
+            self.retry.remove_task(*task_id).await;
+            self.events.remove_task(*task_id).await;
+            self.completions.resolve(*task_id, TaskOutcome::Removed).await;
+            if let Some(detector) = &detector {
+                if let Err(e) = detector.forget(*task_id).await {
+                    log::warn!("Failed to remove task {} from the duplicate index: {}", task_id, e);
+                }
+            }
+        }
+
+        self.try_start_next_queued_task().await?;
+        Ok(owned.into_iter().collect())
+    }
+
+    /// Get the owner of a task
+    pub async fn task_owner(&self, task_id: TaskId) -> Option<String> {
+        self.owners.read().await.get(&task_id).cloned()
+    }
+
+    /// List the tasks of `owner`, oldest first
+    pub async fn list_tasks_for_owner(&self, owner: &str) -> Result<Vec<DownloadTask>> {
+        let owned = self.tasks_of_owner(owner).await;
+        let mut tasks: Vec<DownloadTask> = self.all_tasks.read().await.values()
+            .filter(|task| owned.contains(&task.id))
+            .cloned()
+            .collect();
+        ListOrder::CreatedAsc.sort(&mut tasks);
+        Ok(tasks)
+    }
+
+    /// Set how many downloads of each owner may run at once
+    ///
+    /// Queued tasks are started right away if the new quotas allow it.
+    pub async fn set_owner_quotas(&self, quotas: OwnerQuotas) -> Result<()> {
+        *self.owner_quotas.write().await = quotas;
+        self.try_start_next_queued_task().await
+    }
+
+    /// Get the per-owner download quotas
+    pub async fn owner_quotas(&self) -> OwnerQuotas {
+        self.owner_quotas.read().await.clone()
+    }
+
+    /// Get the IDs of the tasks of `owner`
+    async fn tasks_of_owner(&self, owner: &str) -> HashSet<TaskId> {
+        self.owners.read().await.iter()
+            .filter(|(_, task_owner)| task_owner.as_str() == owner)
+            .map(|(task_id, _)| *task_id)
+            .collect()
+    }
+
     /// Get task information
     pub async fn get_task(&self, task_id: TaskId) -> Result<DownloadTask> {
         let all_tasks = self.all_tasks.read().await;
         all_tasks.get(&task_id)
             .cloned()
-            .ok_or_else(|| DownloadError::TaskNotFound(task_id).into())
+            .ok_or_else(|| DownloadError::TaskNotFound(task_id))
     }
 
-    /// List all tasks
+    /// List all tasks, oldest first
     pub async fn list_tasks(&self) -> Result<Vec<DownloadTask>> {
-        let all_tasks = self.all_tasks.read().await;
-        Ok(all_tasks.values().cloned().collect())
+        let mut tasks: Vec<DownloadTask> = self.all_tasks.read().await.values().cloned().collect();
+        ListOrder::CreatedAsc.sort(&mut tasks);
+        Ok(tasks)
     }
 
     /// Get number of active downloads
@@ -204,6 +619,20 @@ impl TaskQueueManager {
         self.active_tasks.read().await.len()
     }
 
+    /// Wait until a task completes and return it
+    ///
+    /// A failed task keeps being waited for while a retry is scheduled.
+    pub async fn wait_for_completion(&self, task_id: TaskId, timeout: Option<Duration>) -> Result<DownloadTask> {
+        let receiver = self.completions.subscribe(task_id).await;
+        let task = self.get_task(task_id).await?;
+
+        let current = match TaskOutcome::of(&task) {
+            Some(TaskOutcome::Failed(_)) if self.retry.is_pending(task_id).await => None,
+            outcome => outcome,
+        };
+        receiver.wait(current, timeout).await
+    }
+
     /// Mark task as completed and try to start next queued task
     pub async fn complete_task(&self, task_id: TaskId) -> Result<()> {
         let old_status = {
@@ -227,6 +656,10 @@ impl TaskQueueManager {
         if let Some(old_status) = old_status {
             self.notify_status_changed(task_id, old_status, DownloadStatus::Completed).await;
             self.notify_download_completed(task_id).await;
+
+            if let Ok(task) = self.get_task(task_id).await {
+                self.completions.resolve(task_id, TaskOutcome::Completed(task)).await;
+            }
         }
 
         Ok(())
@@ -254,32 +687,324 @@ impl TaskQueueManager {
         // Notify after all locks are released
         if let Some(old_status) = old_status {
             self.notify_status_changed(task_id, old_status, DownloadStatus::Failed(error.clone())).await;
-            self.notify_download_failed(task_id, error).await;
+            self.notify_download_failed(task_id, error.clone()).await;
+
+            // Reschedule according to the retry policy
+            if let Some((attempt, delay)) = self.retry.record_failure(task_id, &error).await {
+                self.notify_retry_scheduled(task_id, attempt, delay).await;
+
+                let manager = self.share();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    if let Err(e) = manager.retry_task(task_id).await {
+                        log::warn!("Failed to retry task {}: {}", task_id, e);
+                    }
+                });
+            } else if let Ok(task) = self.get_task(task_id).await {
+                self.completions.resolve(task_id, TaskOutcome::Failed(task)).await;
+            }
         }
 
         Ok(())
     }
 
-    /// Add event handler
-    pub async fn add_event_handler(&self, handler: Arc<dyn DownloadEventHandler>) {
-        self.event_handlers.write().await.push(handler);
+    /// Put a failed task back into the waiting queue
+    async fn retry_task(&self, task_id: TaskId) -> Result<()> {
+        self.retry.clear_pending(task_id).await;
+
+        let (old_status, task) = {
+            let mut all_tasks = self.all_tasks.write().await;
+            let task = all_tasks.get_mut(&task_id)
+                .ok_or(DownloadError::TaskNotFound(task_id))?;
+
+            // The task was resumed or changed manually in the meantime
+            if !matches!(task.status, DownloadStatus::Failed(_)) {
+                return Ok(());
+            }
+
+            let old_status = task.status.clone();
+            task.update_status(DownloadStatus::Waiting);
+            (old_status, task.clone())
+        }; // Release write lock
+
+        self.enqueue(task).await;
+        self.notify_status_changed(task_id, old_status, DownloadStatus::Waiting).await;
+
+        self.try_start_next_queued_task().await
     }
 
-    /// Try to start the next queued task if slot available
-    async fn try_start_next_queued_task(&self) -> Result<()> {
-        let active_count = self.active_tasks.read().await.len();
-        if active_count >= MAX_CONCURRENT_DOWNLOADS {
-            return Ok(());
+    /// Set the retry policy for tasks without their own policy
+    pub async fn set_retry_policy(&self, policy: RetryPolicy) {
+        self.retry.set_default_policy(policy).await;
+    }
+
+    /// Set the retry policy for a single task
+    pub async fn set_task_retry_policy(&self, task_id: TaskId, policy: RetryPolicy) -> Result<()> {
+        if !self.all_tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
         }
 
-        let next_task = {
-            let mut queue = self.queued_tasks.lock().await;
-            queue.pop_front()
+        self.retry.set_task_policy(task_id, policy).await;
+        Ok(())
+    }
+
+    /// Get the number of retries already used by a task
+    pub async fn retry_attempts(&self, task_id: TaskId) -> u32 {
+        self.retry.attempts(task_id).await
+    }
+
+    /// Get the priority of a task
+    pub async fn get_priority(&self, task_id: TaskId) -> Result<Priority> {
+        if !self.all_tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
+        }
+
+        Ok(self.priorities.read().await
+            .get(&task_id)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    /// Change the priority of a task
+    ///
+    /// The scheduler sees the new priority of a waiting task the next time a
+    /// slot frees up.
+    pub async fn set_priority(&self, task_id: TaskId, priority: Priority) -> Result<()> {
+        if !self.all_tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
+        }
+
+        self.priorities.write().await.insert(task_id, priority);
+        Ok(())
+    }
+
+    /// Assign a task to a group or tenant, used by schedulers sharing slots between groups
+    pub async fn set_task_group(&self, task_id: TaskId, group: impl Into<String>) -> Result<()> {
+        if !self.all_tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
+        }
+
+        self.groups.write().await.insert(task_id, group.into());
+        Ok(())
+    }
+
+    /// Get the group a task was assigned to
+    pub async fn task_group(&self, task_id: TaskId) -> Option<String> {
+        self.groups.read().await.get(&task_id).cloned()
+    }
+
+    /// Get the speed limits configured for queued and active tasks
+    pub fn bandwidth_limiter(&self) -> Arc<BandwidthLimiter> {
+        self.bandwidth.clone()
+    }
+
+    /// Subscribe to all download events
+    pub fn subscribe_events(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.events.subscribe_events()
+    }
+
+    /// Subscribe to progress updates of a single task
+    pub async fn subscribe_progress(&self, task_id: TaskId) -> Result<watch::Receiver<DownloadProgress>> {
+        let current = TaskQueueManager::get_progress(self, task_id).await?;
+        Ok(self.events.subscribe_progress(task_id, current).await)
+    }
+
+    /// Add event handler, kept until it is removed with the returned ID
+    pub async fn add_event_handler(&self, handler: Arc<dyn DownloadEventHandler>) -> HandlerId {
+        self.handlers.add(handler).await
+    }
+
+    /// Add an event handler receiving progress at most as often as `delivery` allows
+    pub async fn add_event_handler_with_delivery(&self, handler: Arc<dyn DownloadEventHandler>, delivery: ProgressDelivery) -> HandlerId {
+        self.add_event_handler(Arc::new(ThrottledHandler::new(handler, delivery))).await
+    }
+
+    /// Add a listener receiving every notification as a [`DownloadEvent`], e.g. a channel sender
+    pub async fn add_event_listener(&self, listener: Arc<dyn DownloadEventListener>) -> HandlerId {
+        self.add_event_handler(Arc::new(ListenerHandler::new(listener))).await
+    }
+
+    /// Add an event handler without keeping it alive
+    ///
+    /// The handler is removed with the first event after the application
+    /// dropped its last reference to it.
+    pub async fn add_weak_event_handler(&self, handler: &Arc<dyn DownloadEventHandler>) -> HandlerId {
+        let id = self.handlers.next_id();
+        self.handlers.insert(id, Arc::new(WeakHandler::new(handler, id, &self.handlers))).await;
+        id
+    }
+
+    /// Remove an event handler, `false` when it was already removed
+    pub async fn remove_event_handler(&self, id: HandlerId) -> bool {
+        self.handlers.remove(id).await
+    }
+
+    /// Set the handler asked to decide on duplicates under [`DuplicatePolicy::PromptUser`](crate::models::DuplicatePolicy::PromptUser)
+    pub async fn set_duplicate_handler(&self, handler: Arc<dyn DuplicateDecisionHandler>) {
+        self.duplicates.set_handler(handler).await;
+    }
+
+    /// Look up and record tasks for duplicate checks with `detector`
+    ///
+    /// Tasks already in the queue are recorded right away. Without a
+    /// detector, duplicates are found by comparing URLs and paths of the
+    /// tasks in the queue.
+    pub async fn set_duplicate_detector(&self, detector: Arc<dyn DuplicateDetector>) -> Result<()> {
+        let tasks: Vec<DownloadTask> = self.all_tasks.read().await.values().cloned().collect();
+        detector.record_all(&tasks).await?;
+        *self.detector.write().await = Some(detector);
+        Ok(())
+    }
+
+    /// Get the tasks the duplicate detector matches that are still in the queue
+    ///
+    /// `None` when no detector is set.
+    async fn detected_duplicates(&self, url: &str, target_path: &std::path::Path) -> Result<Option<Vec<TaskId>>> {
+        let Some(detector) = self.detector.read().await.clone() else {
+            return Ok(None);
         };
 
-        if let Some(mut task) = next_task {
+        let candidates = detector.get_candidates(url, target_path).await?;
+        let all_tasks = self.all_tasks.read().await;
+        Ok(Some(candidates.into_iter()
+            .filter(|task_id| all_tasks.contains_key(task_id))
+            .collect()))
+    }
+
+    /// Tell what adding `url` -> `target_path` under `policy` would do
+    ///
+    /// Nothing is added and the decision handler is not asked; under
+    /// [`DuplicatePolicy::PromptUser`] the result says it would be.
+    pub async fn evaluate_duplicate(&self, url: &str, target_path: &std::path::Path, policy: DuplicatePolicy) -> Result<DuplicatePreview> {
+        self.validate_url(url).await?;
+
+        let candidates = self.duplicate_candidates(url, target_path).await?;
+        Ok(self.duplicates.preview(&policy, &candidates).await)
+    }
+
+    /// Get the existing task matching a download request with its status
+    async fn duplicate_candidates(&self, url: &str, target_path: &std::path::Path) -> Result<Vec<DuplicateCandidate>> {
+        let mut candidates = Vec::new();
+        if let Some(existing_task_id) = self.find_duplicate_task(url, target_path).await? {
+            let task = self.get_task(existing_task_id).await?;
+            candidates.push(DuplicateCandidate::new(
+                existing_task_id,
+                TaskStatus::from_download_status(task.status),
+                DuplicateReason::UrlAndPath,
+            ));
+        }
+        Ok(candidates)
+    }
+
+    /// Append a task to the waiting queue
+    ///
+    /// A task with a deadline is failed as expired if it is still waiting then.
+    async fn enqueue(&self, task: DownloadTask) {
+        let task_id = task.id;
+        self.queued_tasks.lock().await.push_back(task);
+
+        let Some(expires_at) = self.deadlines.read().await.get(&task_id).copied() else {
+            return;
+        };
+        let manager = self.share();
+        tokio::spawn(async move {
+            let remaining = expires_at.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO);
+            tokio::time::sleep(remaining).await;
+            manager.expire_task(task_id).await;
+        });
+    }
+
+    /// Fail a task that is still waiting after its deadline
+    ///
+    /// Tasks that started in the meantime are left alone; paused ones are
+    /// checked again once they are queued.
+    async fn expire_task(&self, task_id: TaskId) {
+        let Some(expires_at) = self.deadlines.read().await.get(&task_id).copied() else {
+            return;
+        };
+
+        let (old_status, task) = {
+            let mut all_tasks = self.all_tasks.write().await;
+            let mut queue = self.queued_tasks.lock().await;
+            let Some(task) = all_tasks.get_mut(&task_id) else {
+                return;
+            };
+            if task.status != DownloadStatus::Waiting {
+                return;
+            }
+
+            queue.retain(|queued| queued.id != task_id);
+            let old_status = task.status.clone();
+            task.update_status(DownloadStatus::Failed(EXPIRED_REASON.to_string()));
+            (old_status, task.clone())
+        }; // Release locks
+
+        self.deadlines.write().await.remove(&task_id);
+        log::info!("Task {} expired before it started", task_id);
+
+        // Notify after locks released
+        self.notify_status_changed(task_id, old_status, task.status.clone()).await;
+        self.notify_task_expired(task_id, expires_at).await;
+        self.completions.resolve(task_id, TaskOutcome::Failed(task)).await;
+    }
+
+    /// Get the time a task is failed at if it has not started by then
+    pub async fn task_expires_at(&self, task_id: TaskId) -> Option<SystemTime> {
+        self.deadlines.read().await.get(&task_id).copied()
+    }
+
+    /// Start queued tasks while download slots are free
+    ///
+    /// The scheduler picks among the tasks allowed to start; tasks whose host
+    /// is at its limit keep their place in the queue.
+    async fn try_start_next_queued_task(&self) -> Result<()> {
+        loop {
+            let next_task = {
+                let priorities = self.priorities.read().await;
+                let groups = self.groups.read().await;
+                let owners = self.owners.read().await;
+                let deadlines = self.deadlines.read().await;
+                let active_tasks = self.active_tasks.read().await;
+                let host_limits = self.host_limits.read().await;
+                let owner_quotas = self.owner_quotas.read().await;
+                let mut queue = self.queued_tasks.lock().await;
+
+                let startable: Vec<usize> = queue.iter()
+                    .enumerate()
+                    .filter(|(_, task)| can_start(task, &active_tasks, &host_limits, &owners, &owner_quotas, &deadlines))
+                    .map(|(index, _)| index)
+                    .collect();
+                let candidates: Vec<QueuedTask<'_>> = startable.iter()
+                    .map(|&index| {
+                        let task = &queue[index];
+                        QueuedTask {
+                            task,
+                            priority: priorities.get(&task.id).copied().unwrap_or_default(),
+                            group: groups.get(&task.id).map(String::as_str),
+                        }
+                    })
+                    .collect();
+
+                let active: Vec<QueuedTask<'_>> = active_tasks.values()
+                    .map(|task| QueuedTask {
+                        task,
+                        priority: priorities.get(&task.id).copied().unwrap_or_default(),
+                        group: groups.get(&task.id).map(String::as_str),
+                    })
+                    .collect();
+
+                let picked = self.scheduler.next(&candidates, &active)
+                    .and_then(|index| startable.get(index).copied());
+                picked.and_then(|index| queue.remove(index))
+            };
+
+            let Some(mut task) = next_task else {
+                return Ok(());
+            };
+
             let task_id = task.id;
             task.update_status(DownloadStatus::Downloading);
+            self.deadlines.write().await.remove(&task_id);
 
             // Update in all_tasks registry
             {
@@ -292,12 +1017,16 @@ impl TaskQueueManager {
 
             self.notify_status_changed(task_id, DownloadStatus::Waiting, DownloadStatus::Downloading).await;
         }
-
-        Ok(())
     }
 
     /// Notify event handlers of status change
     async fn notify_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        if let Some(detector) = self.detector.read().await.clone() {
+            if let Err(e) = detector.update_status(task_id, TaskStatus::from_download_status(new_status.clone())).await {
+                log::warn!("Failed to update task {} in the duplicate index: {}", task_id, e);
+            }
+        }
+
         let handlers = {
             let handlers_lock = self.event_handlers.read().await;
             handlers_lock.clone()
@@ -332,6 +1061,30 @@ impl TaskQueueManager {
         }
     }
 
+    /// Notify event handlers of a scheduled retry
+    async fn notify_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        let handlers = {
+            let handlers_lock = self.event_handlers.read().await;
+            handlers_lock.clone()
+        }; // Release read lock before calling handlers
+
+        for handler in handlers.iter() {
+            handler.on_retry_scheduled(task_id, attempt, delay).await;
+        }
+    }
+
+    /// Notify event handlers of an expired task
+    async fn notify_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        let handlers = {
+            let handlers_lock = self.event_handlers.read().await;
+            handlers_lock.clone()
+        }; // Release read lock before calling handlers
+
+        for handler in handlers.iter() {
+            handler.on_task_expired(task_id, expires_at).await;
+        }
+    }
+
     /// Notify event handlers of progress update
     async fn notify_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
         let handlers = {
@@ -345,10 +1098,64 @@ impl TaskQueueManager {
     }
 }
 
+/// Check if a task may start under the global, per-host and per-owner download limits
+///
+/// Tasks past their deadline never start, they wait to be failed as expired.
+fn can_start(
+    task: &DownloadTask,
+    active_tasks: &HashMap<TaskId, DownloadTask>,
+    host_limits: &HostLimits,
+    owners: &HashMap<TaskId, String>,
+    owner_quotas: &OwnerQuotas,
+    deadlines: &HashMap<TaskId, SystemTime>,
+) -> bool {
+    !TaskScheduler::is_expired(task, deadlines, SystemTime::now())
+        && TaskScheduler::should_schedule_task(task, active_tasks.len(), MAX_CONCURRENT_DOWNLOADS)
+        && TaskScheduler::within_host_limit(task, active_tasks.values(), host_limits)
+        && TaskScheduler::within_owner_quota(task, active_tasks.values(), owners, owner_quotas)
+}
+
 #[async_trait]
 impl DownloadManager for TaskQueueManager {
     async fn add_download(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
-        self.add_task(url, target_path).await
+        self.add_download_with_options(url, target_path, DownloadOptions::default()).await
+    }
+
+    async fn add_download_with_options(
+        &self,
+        url: String,
+        target_path: PathBuf,
+        options: DownloadOptions,
+    ) -> Result<TaskId> {
+        self.validate_url(&url).await?;
+
+        // The queue only schedules tasks, so overwriting needs no preparation
+        let target_path = match options.overwrite.apply(&target_path)? {
+            TargetAction::Download { path, .. } => path,
+            TargetAction::Skip(path) => return Ok(self.add_skipped_task(url, path).await),
+        };
+
+        let task_id = self.add_owned_task(url, target_path, options.priority, options.owner, options.expires_at).await?;
+
+        if let Some(limit) = options.speed_limit {
+            self.bandwidth.set_task_limit(task_id, limit).await;
+        }
+        if let Some(policy) = options.retry_policy {
+            self.retry.set_task_policy(task_id, policy).await;
+        }
+
+        Ok(task_id)
+    }
+
+    async fn add_download_multi_source(&self, urls: Vec<String>, target_path: PathBuf) -> Result<TaskId> {
+        for url in &urls {
+            self.validate_url(url).await?;
+        }
+
+        // The queue only schedules tasks, so it tracks the primary source
+        let url = urls.into_iter().next()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        self.add_download(url, target_path).await
     }
 
     async fn pause_download(&self, task_id: TaskId) -> Result<()> {
@@ -363,6 +1170,18 @@ impl DownloadManager for TaskQueueManager {
         self.cancel_task(task_id).await
     }
 
+    async fn pause_all(&self) -> Result<Vec<TaskId>> {
+        self.pause_all_tasks().await
+    }
+
+    async fn resume_all(&self) -> Result<Vec<TaskId>> {
+        self.resume_all_tasks().await
+    }
+
+    async fn cancel_all(&self) -> Result<Vec<TaskId>> {
+        self.cancel_all_tasks().await
+    }
+
     async fn get_progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
         TaskQueueManager::get_progress(self, task_id).await
     }
@@ -379,6 +1198,10 @@ impl DownloadManager for TaskQueueManager {
         Ok(TaskQueueManager::active_download_count(self).await)
     }
 
+    async fn wait_for_completion(&self, task_id: TaskId, timeout: Option<Duration>) -> Result<DownloadTask> {
+        TaskQueueManager::wait_for_completion(self, task_id, timeout).await
+    }
+
     // Duplicate detection methods
 
     async fn find_duplicate_task(
@@ -386,6 +1209,10 @@ impl DownloadManager for TaskQueueManager {
         url: &str,
         target_path: &std::path::Path,
     ) -> Result<Option<TaskId>> {
+        if let Some(candidates) = self.detected_duplicates(url, target_path).await? {
+            return Ok(candidates.into_iter().next());
+        }
+
         // Check all tasks for URL and path matches
         let all_tasks = self.all_tasks.read().await;
         for task in all_tasks.values() {
@@ -401,31 +1228,25 @@ impl DownloadManager for TaskQueueManager {
         url: &str,
         target_path: &std::path::Path,
         policy: crate::models::DuplicatePolicy,
-    ) -> Result<crate::models::DuplicateResult> {
-        use crate::models::{DuplicateResult, DuplicateReason, TaskStatus};
+    ) -> Result<(TaskId, crate::models::DuplicateDecision)> {
+        use crate::models::DuplicateDecision;
 
-        // Check for duplicates first
-        if let Some(existing_task_id) = self.find_duplicate_task(url, target_path).await? {
-            let task = self.get_task(existing_task_id).await?;
-            let task_status = TaskStatus::from_download_status(task.status);
+        self.validate_url(url).await?;
 
-            if policy.allows_reuse(&task_status) {
-                return Ok(DuplicateResult::ExistingTask {
-                    task_id: existing_task_id,
-                    status: task_status,
-                    reason: DuplicateReason::UrlAndPath,
-                });
-            } else if policy.should_fail_on_duplicate() {
-                return Err(crate::error::DownloadError::PolicyViolation {
-                    task_id: existing_task_id,
-                    reason: "Duplicate found but policy forbids reuse".to_string(),
-                }.into());
+        // Check for duplicates first
+        let candidates = self.duplicate_candidates(url, target_path).await?;
+        let decision = self.duplicates.resolve(url, target_path, &policy, &candidates).await;
+        match decision {
+            DuplicateDecision::CreateNew => {
+                let task_id = self.add_download(url.to_string(), target_path.to_path_buf()).await?;
+                Ok((task_id, decision))
             }
+            DuplicateDecision::Reuse { task_id, .. } => Ok((task_id, decision)),
+            DuplicateDecision::Reject { task_id, .. } => Err(DownloadError::PolicyViolation {
+                task_id,
+                reason: "Duplicate found but policy forbids reuse".to_string(),
+            }),
         }
-
-        // No duplicate found or policy allows new task, create new download
-        let task_id = self.add_download(url.to_string(), target_path.to_path_buf()).await?;
-        Ok(DuplicateResult::NewTask(task_id))
     }
 
     async fn verify_task_validity(&self, task_id: &TaskId) -> Result<bool> {
@@ -439,16 +1260,33 @@ impl DownloadManager for TaskQueueManager {
         url: &str,
         target_path: &std::path::Path,
     ) -> Result<Vec<TaskId>> {
-        let mut candidates = Vec::new();
+        if let Some(candidates) = self.detected_duplicates(url, target_path).await? {
+            return Ok(candidates);
+        }
+
         let all_tasks = self.all_tasks.read().await;
 
         // Look for exact matches
-        for task in all_tasks.values() {
-            if task.url == url && task.target_path == target_path {
-                candidates.push(task.id);
-            }
+        let mut candidates: Vec<DownloadTask> = all_tasks.values()
+            .filter(|task| task.url == url && task.target_path == target_path)
+            .cloned()
+            .collect();
+
+        ListOrder::CreatedDesc.sort(&mut candidates);
+        Ok(candidates.into_iter().map(|task| task.id).collect())
+    }
+
+    async fn set_global_download_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        self.bandwidth.set_global_limit(bytes_per_sec).await;
+        Ok(())
+    }
+
+    async fn set_task_download_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()> {
+        if !self.all_tasks.read().await.contains_key(&task_id) {
+            return Err(DownloadError::TaskNotFound(task_id));
         }
 
-        Ok(candidates)
+        self.bandwidth.set_task_limit(task_id, bytes_per_sec).await;
+        Ok(())
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 pub mod manager;
 pub mod scheduler;
 
-pub use manager::TaskQueueManager;
\ No newline at end of file
+pub use manager::TaskQueueManager;
+pub use scheduler::{FifoScheduler, PriorityScheduler, RoundRobinScheduler};
\ No newline at end of file
@@ -1,4 +1,9 @@
-use crate::types::DownloadTask;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use crate::types::{DownloadTask, TaskId};
+use crate::models::{HostLimits, OwnerQuotas, Priority};
+use crate::traits::{QueueScheduler, QueuedTask};
 
 /// Task scheduling logic for download queue management
 pub struct TaskScheduler;
@@ -9,9 +14,125 @@ impl TaskScheduler {
         active_count < max_concurrent
     }
 
+    /// Determine if starting a task keeps its host within its concurrency limit
+    pub fn within_host_limit<'a>(
+        task: &DownloadTask,
+        active_tasks: impl IntoIterator<Item = &'a DownloadTask>,
+        limits: &HostLimits,
+    ) -> bool {
+        let Some((slot, limit)) = limits.slot_for(&task.url) else {
+            return true;
+        };
+
+        let running = active_tasks.into_iter()
+            .filter(|active| limits.slot_for(&active.url).is_some_and(|(active_slot, _)| active_slot == slot))
+            .count();
+        running < limit
+    }
+
+    /// Determine if starting a task keeps its owner within their quota
+    pub fn within_owner_quota<'a>(
+        task: &DownloadTask,
+        active_tasks: impl IntoIterator<Item = &'a DownloadTask>,
+        owners: &HashMap<TaskId, String>,
+        quotas: &OwnerQuotas,
+    ) -> bool {
+        let Some(owner) = owners.get(&task.id) else {
+            return true;
+        };
+        let Some(quota) = quotas.quota_for(owner) else {
+            return true;
+        };
+
+        let running = active_tasks.into_iter()
+            .filter(|active| owners.get(&active.id) == Some(owner))
+            .count();
+        running < quota
+    }
+
+    /// Determine if a task missed its deadline to start at `now`
+    pub fn is_expired(task: &DownloadTask, deadlines: &HashMap<TaskId, SystemTime>, now: SystemTime) -> bool {
+        deadlines.get(&task.id).is_some_and(|expires_at| *expires_at <= now)
+    }
+
     /// Get priority score for a task (lower score = higher priority)
     /// Currently uses FIFO ordering, but can be extended for priority-based scheduling
     pub fn get_task_priority(_task: &DownloadTask) -> u32 {
         0 // FIFO scheduling - all tasks have same priority
     }
+}
+
+/// Starts waiting tasks in the order they were queued, ignoring priorities
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FifoScheduler;
+
+impl QueueScheduler for FifoScheduler {
+    fn next(&self, candidates: &[QueuedTask<'_>], _active: &[QueuedTask<'_>]) -> Option<usize> {
+        if candidates.is_empty() { None } else { Some(0) }
+    }
+}
+
+/// Starts the waiting task with the highest priority, FIFO within a level
+///
+/// This is the scheduler `TaskQueueManager` uses by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PriorityScheduler;
+
+impl QueueScheduler for PriorityScheduler {
+    fn next(&self, candidates: &[QueuedTask<'_>], _active: &[QueuedTask<'_>]) -> Option<usize> {
+        highest_priority(candidates.iter().enumerate())
+    }
+}
+
+/// Takes turns between groups so one large group cannot starve the others
+///
+/// The group with the fewest running downloads goes next, between equals
+/// the one served least recently; ungrouped tasks share one turn. Within a
+/// group the task with the highest priority starts first.
+#[derive(Debug, Default)]
+pub struct RoundRobinScheduler {
+    /// When each group was last served, by a counter increasing with every pick
+    served: Mutex<HashMap<Option<String>, u64>>,
+}
+
+impl RoundRobinScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl QueueScheduler for RoundRobinScheduler {
+    fn next(&self, candidates: &[QueuedTask<'_>], active: &[QueuedTask<'_>]) -> Option<usize> {
+        let mut served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+        let last_served = |group: Option<&str>| {
+            served.get(&group.map(str::to_string)).copied().unwrap_or(0)
+        };
+
+        let running = |group: Option<&str>| {
+            active.iter().filter(|task| task.group == group).count()
+        };
+
+        // Ties go to the group queued earliest
+        let group = candidates.iter()
+            .map(|candidate| candidate.group)
+            .min_by_key(|group| (running(*group), last_served(*group)))?;
+
+        let index = highest_priority(candidates.iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.group == group))?;
+
+        let tick = served.values().max().copied().unwrap_or(0) + 1;
+        served.insert(group.map(str::to_string), tick);
+        Some(index)
+    }
+}
+
+/// Get the index of the earliest of the highest priority tasks
+fn highest_priority<'a>(candidates: impl Iterator<Item = (usize, &'a QueuedTask<'a>)>) -> Option<usize> {
+    candidates
+        .fold(None, |best: Option<(usize, Priority)>, (index, candidate)| match best {
+            Some((_, priority)) if priority >= candidate.priority => best,
+            _ => Some((index, candidate.priority)),
+        })
+        .map(|(index, _)| index)
 }
\ No newline at end of file
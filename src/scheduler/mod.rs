@@ -0,0 +1,183 @@
+//! Scheduled downloads
+//!
+//! Downloads can be scheduled for a start time or a recurring schedule. Entries
+//! are persisted in SQLite so they survive restarts, and a background task
+//! promotes due entries into the normal download queue.
+
+pub mod spec;
+pub mod store;
+pub mod throttle;
+
+pub use spec::ScheduleSpec;
+pub use store::{ScheduleId, ScheduleStore, ScheduledDownload};
+pub use throttle::{ThrottleRule, ThrottleSchedule, Throttler};
+
+use crate::traits::DownloadManager;
+use crate::types::TaskId;
+use crate::models::DuplicatePolicy;
+use crate::Result;
+use crate::error::DownloadError;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+/// How often the scheduler checks for due entries by default
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 1;
+/// Delay before a one-off entry whose download could not be added is tried again
+const FAILED_RUN_RETRY_SECS: u64 = 60;
+
+/// Promotes scheduled downloads into a download manager when they become due
+pub struct DownloadScheduler {
+    manager: Arc<dyn DownloadManager>,
+    store: ScheduleStore,
+    poll_interval: Duration,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl DownloadScheduler {
+    /// Create a scheduler that adds due downloads to `manager`
+    pub fn new(manager: Arc<dyn DownloadManager>, store: ScheduleStore) -> Self {
+        Self {
+            manager,
+            store,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_INTERVAL_SECS),
+            handle: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Set how often the background task checks for due entries
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Schedule a download
+    ///
+    /// The download is added to the manager once `spec` says it is due.
+    /// Recurring schedules add a fresh task on every run.
+    pub async fn schedule_download(
+        &self,
+        url: String,
+        target_path: PathBuf,
+        spec: ScheduleSpec,
+    ) -> Result<ScheduleId> {
+        spec.validate()?;
+
+        let next_run = spec.first_run(SystemTime::now());
+        let id = self.store.insert(&url, &target_path, &spec, next_run).await?;
+
+        log::info!("Scheduled download {} for {} ({:?})", id, url, spec);
+        Ok(id)
+    }
+
+    /// Remove a scheduled download
+    pub async fn cancel_schedule(&self, id: ScheduleId) -> Result<()> {
+        if !self.store.delete(id).await? {
+            return Err(DownloadError::General(format!("Scheduled download {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// List all scheduled downloads ordered by next run time
+    pub async fn list_schedules(&self) -> Result<Vec<ScheduledDownload>> {
+        self.store.list().await
+    }
+
+    /// Add every due download to the manager
+    ///
+    /// Returns the IDs of the tasks that were added.
+    pub async fn run_due(&self) -> Result<Vec<TaskId>> {
+        run_due(self.manager.as_ref(), &self.store).await
+    }
+
+    /// Start the background task that promotes due downloads
+    pub async fn start(&self) {
+        let mut handle_guard = self.handle.lock().await;
+        if handle_guard.is_some() {
+            return;
+        }
+
+        let manager = self.manager.clone();
+        let store = self.store.clone();
+        let shutdown = self.shutdown.clone();
+        let poll_interval = self.poll_interval;
+
+        *handle_guard = Some(tokio::spawn(async move {
+            let mut ticker = interval(poll_interval);
+
+            log::info!("Starting download scheduler");
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        if let Err(e) = run_due(manager.as_ref(), &store).await {
+                            log::error!("Failed to run scheduled downloads: {}", e);
+                        }
+                    }
+                    _ = shutdown.notified() => {
+                        log::info!("Download scheduler shutting down");
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Stop the background task
+    pub async fn shutdown(&self) {
+        self.shutdown.notify_one();
+
+        if let Some(handle) = self.handle.lock().await.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// Add every due entry in `store` to `manager` and move entries to their next run
+async fn run_due(manager: &dyn DownloadManager, store: &ScheduleStore) -> Result<Vec<TaskId>> {
+    let now = SystemTime::now();
+    let mut started = Vec::new();
+
+    for entry in store.due(now).await? {
+        match start_entry(manager, &entry).await {
+            Ok(task_id) => {
+                log::info!("Started scheduled download {} as task {}", entry.id, task_id);
+                started.push(task_id);
+
+                match entry.spec.next_occurrence(now) {
+                    Some(next_run) => store.reschedule(entry.id, next_run, Some(&task_id)).await?,
+                    None => {
+                        store.delete(entry.id).await?;
+                    }
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to start scheduled download {}: {}", entry.id, e);
+
+                // Recurring entries skip to the next occurrence, one-off entries try again later
+                let next_run = entry.spec.next_occurrence(now)
+                    .unwrap_or(now + Duration::from_secs(FAILED_RUN_RETRY_SECS));
+                store.reschedule(entry.id, next_run, None).await?;
+            }
+        }
+    }
+
+    Ok(started)
+}
+
+async fn start_entry(manager: &dyn DownloadManager, entry: &ScheduledDownload) -> Result<TaskId> {
+    if !entry.spec.is_recurring() {
+        return manager.add_download(entry.url.clone(), entry.target_path.clone()).await;
+    }
+
+    // Every run of a recurring schedule fetches the file again
+    let (task_id, _) = manager
+        .add_download_with_policy(&entry.url, &entry.target_path, DuplicatePolicy::AllowDuplicate)
+        .await?;
+    Ok(task_id)
+}
\ No newline at end of file
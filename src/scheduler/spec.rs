@@ -0,0 +1,110 @@
+//! Schedule specifications
+//!
+//! Describes when a scheduled download should run: once, at a fixed
+//! interval, or at a fixed time of day/week (UTC).
+
+use crate::error::DownloadError;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+
+/// When a scheduled download runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleSpec {
+    /// Run once at the given time
+    At(SystemTime),
+    /// Run at `start`, then every `interval`
+    Every { start: SystemTime, interval: Duration },
+    /// Run every day at the given UTC time
+    DailyAt { hour: u8, minute: u8 },
+    /// Run every week on `weekday` (0 = Monday) at the given UTC time
+    WeeklyAt { weekday: u8, hour: u8, minute: u8 },
+}
+
+impl ScheduleSpec {
+    /// Check that the specification describes a valid schedule
+    pub fn validate(&self) -> Result<(), DownloadError> {
+        let invalid = |reason: &str| Err(DownloadError::General(format!("Invalid schedule: {}", reason)));
+
+        match self {
+            ScheduleSpec::At(_) => Ok(()),
+            ScheduleSpec::Every { interval, .. } if interval.as_secs() == 0 => {
+                invalid("interval must be at least one second")
+            }
+            ScheduleSpec::Every { .. } => Ok(()),
+            ScheduleSpec::DailyAt { hour, minute } | ScheduleSpec::WeeklyAt { hour, minute, .. }
+                if *hour > 23 || *minute > 59 => invalid("time of day out of range"),
+            ScheduleSpec::WeeklyAt { weekday, .. } if *weekday > 6 => invalid("weekday out of range"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check if the schedule runs more than once
+    pub fn is_recurring(&self) -> bool {
+        !matches!(self, ScheduleSpec::At(_))
+    }
+
+    /// Get the first run time for a schedule created at `now`
+    ///
+    /// One-off and interval schedules keep a start time in the past, so
+    /// they run as soon as the scheduler sees them.
+    pub fn first_run(&self, now: SystemTime) -> SystemTime {
+        match self {
+            ScheduleSpec::At(at) => *at,
+            ScheduleSpec::Every { start, .. } => *start,
+            _ => self.next_occurrence(now).unwrap_or(now),
+        }
+    }
+
+    /// Get the earliest run time strictly after `after`, if the schedule recurs
+    pub fn next_occurrence(&self, after: SystemTime) -> Option<SystemTime> {
+        let after_secs = to_unix_secs(after);
+
+        let next_secs = match self {
+            ScheduleSpec::At(_) => return None,
+            ScheduleSpec::Every { start, interval } => {
+                let start_secs = to_unix_secs(*start);
+                let interval_secs = interval.as_secs().max(1);
+                if after_secs < start_secs {
+                    start_secs
+                } else {
+                    let elapsed_intervals = (after_secs - start_secs) / interval_secs + 1;
+                    start_secs + elapsed_intervals * interval_secs
+                }
+            }
+            ScheduleSpec::DailyAt { hour, minute } => {
+                let day_start = after_secs - after_secs % SECS_PER_DAY;
+                let candidate = day_start + time_of_day(*hour, *minute);
+                if candidate > after_secs { candidate } else { candidate + SECS_PER_DAY }
+            }
+            ScheduleSpec::WeeklyAt { weekday, hour, minute } => {
+                let day_start = after_secs - after_secs % SECS_PER_DAY;
+                // 1970-01-01 was a Thursday (weekday 3 when Monday is 0)
+                let current_weekday = (after_secs / SECS_PER_DAY + 3) % 7;
+                let days_ahead = (*weekday as u64 + 7 - current_weekday) % 7;
+                let candidate = day_start + days_ahead * SECS_PER_DAY + time_of_day(*hour, *minute);
+                if candidate > after_secs { candidate } else { candidate + SECS_PER_WEEK }
+            }
+        };
+
+        Some(from_unix_secs(next_secs))
+    }
+}
+
+fn time_of_day(hour: u8, minute: u8) -> u64 {
+    hour as u64 * SECS_PER_HOUR + minute as u64 * SECS_PER_MINUTE
+}
+
+/// Convert a time to whole seconds since the UNIX epoch (0 for earlier times)
+pub(crate) fn to_unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Convert whole seconds since the UNIX epoch to a time
+pub(crate) fn from_unix_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
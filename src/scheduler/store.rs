@@ -0,0 +1,159 @@
+//! Persistent storage for scheduled downloads
+
+use crate::types::TaskId;
+use crate::error::DownloadError;
+use crate::scheduler::spec::{ScheduleSpec, to_unix_secs, from_unix_secs};
+use crate::services::field_cipher::{self, FieldCipher};
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, encode_task_id, decode_value, db_error, unix_now};
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Identifier of a scheduled download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScheduleId(pub i64);
+
+impl std::fmt::Display for ScheduleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "schedule-{}", self.0)
+    }
+}
+
+/// A download waiting for its scheduled time
+#[derive(Debug, Clone)]
+pub struct ScheduledDownload {
+    pub id: ScheduleId,
+    pub url: String,
+    pub target_path: PathBuf,
+    pub spec: ScheduleSpec,
+    /// When the download is started next
+    pub next_run: SystemTime,
+    /// Task created by the most recent run
+    pub last_task_id: Option<TaskId>,
+}
+
+/// SQLite-backed store of scheduled downloads
+#[derive(Clone)]
+pub struct ScheduleStore {
+    pool: SqlitePool,
+    cipher: Option<FieldCipher>,
+}
+
+impl ScheduleStore {
+    /// Open (or create) a store in the given SQLite file
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Ok(Self { pool: open_pool(path).await?, cipher: None })
+    }
+
+    /// Create a store that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Ok(Self { pool: in_memory_pool().await?, cipher: None })
+    }
+
+    /// Encrypt stored URLs with `cipher`
+    ///
+    /// URLs written without encryption can still be read.
+    pub fn with_cipher(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Store a new scheduled download
+    pub async fn insert(
+        &self,
+        url: &str,
+        target_path: &Path,
+        spec: &ScheduleSpec,
+        next_run: SystemTime,
+    ) -> Result<ScheduleId, DownloadError> {
+        let spec = serde_json::to_string(spec)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+
+        let result = sqlx::query(
+            "INSERT INTO scheduled_downloads (url, target_path, spec, next_run, created_at)
+             VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(field_cipher::seal(self.cipher.as_ref(), url)?)
+        .bind(target_path.to_string_lossy().into_owned())
+        .bind(spec)
+        .bind(to_unix_secs(next_run) as i64)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(ScheduleId(result.last_insert_rowid()))
+    }
+
+    /// List all scheduled downloads ordered by next run time
+    pub async fn list(&self) -> Result<Vec<ScheduledDownload>, DownloadError> {
+        let rows = sqlx::query("SELECT * FROM scheduled_downloads ORDER BY next_run, id")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(|row| row_to_scheduled(row, self.cipher.as_ref())).collect()
+    }
+
+    /// List scheduled downloads whose run time is at or before `now`
+    pub async fn due(&self, now: SystemTime) -> Result<Vec<ScheduledDownload>, DownloadError> {
+        let rows = sqlx::query("SELECT * FROM scheduled_downloads WHERE next_run <= ? ORDER BY next_run, id")
+            .bind(to_unix_secs(now) as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.iter().map(|row| row_to_scheduled(row, self.cipher.as_ref())).collect()
+    }
+
+    /// Move a scheduled download to its next run time
+    pub async fn reschedule(
+        &self,
+        id: ScheduleId,
+        next_run: SystemTime,
+        last_task_id: Option<&TaskId>,
+    ) -> Result<(), DownloadError> {
+        let last_task_id = last_task_id.map(encode_task_id).transpose()?;
+
+        sqlx::query(
+            "UPDATE scheduled_downloads
+             SET next_run = ?, last_task_id = COALESCE(?, last_task_id)
+             WHERE id = ?"
+        )
+        .bind(to_unix_secs(next_run) as i64)
+        .bind(last_task_id)
+        .bind(id.0)
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Remove a scheduled download, returning whether it existed
+    pub async fn delete(&self, id: ScheduleId) -> Result<bool, DownloadError> {
+        let result = sqlx::query("DELETE FROM scheduled_downloads WHERE id = ?")
+            .bind(id.0)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_scheduled(row: &SqliteRow, cipher: Option<&FieldCipher>) -> Result<ScheduledDownload, DownloadError> {
+    let last_task_id = row.get::<Option<String>, _>("last_task_id")
+        .map(decode_value)
+        .transpose()?;
+
+    Ok(ScheduledDownload {
+        id: ScheduleId(row.get("id")),
+        url: field_cipher::open(cipher, row.get("url"))?,
+        target_path: PathBuf::from(row.get::<String, _>("target_path")),
+        spec: decode_value(row.get::<String, _>("spec"))?,
+        next_run: from_unix_secs(row.get::<i64, _>("next_run").max(0) as u64),
+        last_task_id,
+    })
+}
@@ -0,0 +1,228 @@
+//! Time-of-day bandwidth rules
+//!
+//! A throttle schedule maps UTC time ranges to global speed limits, such as
+//! unlimited at night and 1 MB/s during business hours. A background task
+//! applies the limit in effect through the download manager, which updates
+//! its bandwidth limiter and the engine's options.
+
+use crate::error::DownloadError;
+use crate::scheduler::spec::to_unix_secs;
+use crate::services::bandwidth_limiter::UNLIMITED;
+use crate::traits::DownloadManager;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Mutex, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::interval;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+/// How often the throttler checks for a new limit by default
+const DEFAULT_THROTTLE_POLL_SECS: u64 = 30;
+
+/// A global speed limit applying every day between two UTC times
+///
+/// A range ending before it starts wraps past midnight, one ending where it
+/// starts covers the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleRule {
+    pub start_hour: u8,
+    pub start_minute: u8,
+    pub end_hour: u8,
+    pub end_minute: u8,
+    /// Limit in bytes per second (0 = unlimited)
+    pub limit: u64,
+}
+
+impl ThrottleRule {
+    /// Check if the rule applies at `minute` minutes past UTC midnight
+    fn contains(&self, minute: u16) -> bool {
+        let start = minute_of_day(self.start_hour, self.start_minute);
+        let end = minute_of_day(self.end_hour, self.end_minute);
+
+        match start.cmp(&end) {
+            std::cmp::Ordering::Less => (start..end).contains(&minute),
+            std::cmp::Ordering::Greater => minute >= start || minute < end,
+            std::cmp::Ordering::Equal => true,
+        }
+    }
+}
+
+/// Global speed limits by time of day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThrottleSchedule {
+    /// Rules in order of precedence, the first matching one applies
+    pub rules: Vec<ThrottleRule>,
+    /// Limit outside of every rule in bytes per second (0 = unlimited)
+    pub default_limit: u64,
+}
+
+impl ThrottleSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit outside of every rule
+    pub fn default_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.default_limit = bytes_per_sec;
+        self
+    }
+
+    /// Limit downloads to `bytes_per_sec` from `start` until `end`, as UTC (hour, minute)
+    pub fn rule(mut self, start: (u8, u8), end: (u8, u8), bytes_per_sec: u64) -> Self {
+        self.rules.push(ThrottleRule {
+            start_hour: start.0,
+            start_minute: start.1,
+            end_hour: end.0,
+            end_minute: end.1,
+            limit: bytes_per_sec,
+        });
+        self
+    }
+
+    /// Check that every rule has valid times of day
+    pub fn validate(&self) -> Result<()> {
+        let out_of_range = |hour: u8, minute: u8| hour > 23 || minute > 59;
+
+        if self.rules.iter().any(|rule| {
+            out_of_range(rule.start_hour, rule.start_minute) || out_of_range(rule.end_hour, rule.end_minute)
+        }) {
+            return Err(DownloadError::General("Invalid throttle schedule: time of day out of range".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Get the limit in effect at `time`
+    pub fn limit_at(&self, time: SystemTime) -> u64 {
+        let minute = ((to_unix_secs(time) / 60) % MINUTES_PER_DAY as u64) as u16;
+
+        self.rules.iter()
+            .find(|rule| rule.contains(minute))
+            .map(|rule| rule.limit)
+            .unwrap_or(self.default_limit)
+    }
+}
+
+fn minute_of_day(hour: u8, minute: u8) -> u16 {
+    hour as u16 * 60 + minute as u16
+}
+
+/// Applies a throttle schedule to a download manager's global speed limit
+pub struct Throttler {
+    manager: Arc<dyn DownloadManager>,
+    schedule: Arc<RwLock<ThrottleSchedule>>,
+    /// Limit last set on the manager, `None` before the first update
+    applied: Arc<Mutex<Option<u64>>>,
+    poll_interval: Duration,
+    handle: Mutex<Option<JoinHandle<()>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl Throttler {
+    /// Create a throttler setting the global limit of `manager` from `schedule`
+    pub fn new(manager: Arc<dyn DownloadManager>, schedule: ThrottleSchedule) -> Result<Self> {
+        schedule.validate()?;
+
+        Ok(Self {
+            manager,
+            schedule: Arc::new(RwLock::new(schedule)),
+            applied: Arc::new(Mutex::new(None)),
+            poll_interval: Duration::from_secs(DEFAULT_THROTTLE_POLL_SECS),
+            handle: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Set how often the background task checks for a new limit
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Get the schedule being applied
+    pub async fn schedule(&self) -> ThrottleSchedule {
+        self.schedule.read().await.clone()
+    }
+
+    /// Replace the schedule and apply its current limit
+    pub async fn set_schedule(&self, schedule: ThrottleSchedule) -> Result<u64> {
+        schedule.validate()?;
+        *self.schedule.write().await = schedule;
+        *self.applied.lock().await = None;
+        self.apply().await
+    }
+
+    /// Set the limit in effect now on the manager and return it
+    ///
+    /// The manager is only updated when the limit changed since the last
+    /// call, so a limit set by hand lasts until the next rule begins.
+    pub async fn apply(&self) -> Result<u64> {
+        apply_schedule(self.manager.as_ref(), &self.schedule, &self.applied).await
+    }
+
+    /// Start the background task applying the schedule
+    pub async fn start(&self) {
+        let mut handle_guard = self.handle.lock().await;
+        if handle_guard.is_some() {
+            return;
+        }
+
+        let manager = self.manager.clone();
+        let schedule = self.schedule.clone();
+        let applied = self.applied.clone();
+        let shutdown = self.shutdown.clone();
+        let poll_interval = self.poll_interval.max(Duration::from_millis(1));
+
+        *handle_guard = Some(tokio::spawn(async move {
+            let mut ticker = interval(poll_interval);
+
+            log::info!("Starting bandwidth throttler");
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        if let Err(e) = apply_schedule(manager.as_ref(), &schedule, &applied).await {
+                            log::error!("Failed to apply throttle schedule: {}", e);
+                        }
+                    }
+                    _ = shutdown.notified() => {
+                        log::info!("Bandwidth throttler shutting down");
+                        break;
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Stop the background task, keeping the limit last applied
+    pub async fn shutdown(&self) {
+        self.shutdown.notify_one();
+
+        if let Some(handle) = self.handle.lock().await.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+/// Set the limit `schedule` has now on `manager` unless it is already applied
+async fn apply_schedule(
+    manager: &dyn DownloadManager,
+    schedule: &RwLock<ThrottleSchedule>,
+    applied: &Mutex<Option<u64>>,
+) -> Result<u64> {
+    let limit = schedule.read().await.limit_at(SystemTime::now());
+
+    let mut applied = applied.lock().await;
+    if *applied != Some(limit) {
+        manager.set_global_download_limit(limit).await?;
+        *applied = Some(limit);
+
+        if limit == UNLIMITED {
+            log::info!("Throttle schedule lifted the global download limit");
+        } else {
+            log::info!("Throttle schedule set the global download limit to {} B/s", limit);
+        }
+    }
+    Ok(limit)
+}
\ No newline at end of file
@@ -0,0 +1,255 @@
+//! HTTP control server
+//!
+//! Exposes a [`DownloadManager`] over HTTP and JSON so components in other
+//! processes or languages can drive downloads. Available with the `server`
+//! feature.
+//!
+//! | Method and path                | Action                                   |
+//! |--------------------------------|------------------------------------------|
+//! | `GET /tasks`                   | List tasks, a page with `offset`, `limit` and `order` (e.g. `CreatedDesc`) |
+//! | `GET /snapshot`                | List tasks with their progress           |
+//! | `POST /tasks`                  | Add a download (`url`, `target_path`, optional `options`) |
+//! | `GET /tasks/{id}`              | Get a task, with its `failure` diagnostics when it failed |
+//! | `DELETE /tasks/{id}`           | Cancel a task                            |
+//! | `POST /tasks/{id}/cancel`      | Cancel a task but keep it as `cancelled`, with `delete_files` to remove its partial file |
+//! | `POST /tasks/{id}/pause`       | Pause a task                             |
+//! | `POST /tasks/{id}/resume`      | Resume a task                            |
+//! | `GET /tasks/{id}/progress`     | Get the progress of a task               |
+//! | `PUT /tasks/{id}/speed-limit`  | Limit the speed of a task (`bytes_per_sec`) |
+//! | `POST /pause-all`, `/resume-all` | Pause or resume every task             |
+//! | `PUT /speed-limit`             | Limit the global speed (`bytes_per_sec`) |
+//! | `GET /events`                  | Server-sent events of every task         |
+//!
+//! Errors are returned as `{"error": "..."}` with a matching status code.
+//!
+//! ```rust,no_run
+//! use burncloud_download::{EventBus, PersistentAria2Manager};
+//! use burncloud_download::server::ControlServer;
+//! use std::sync::Arc;
+//!
+//! #[tokio::main]
+//! async fn main() -> anyhow::Result<()> {
+//!     let manager = Arc::new(PersistentAria2Manager::new().await?);
+//!     let events = Arc::new(EventBus::default());
+//!     manager.add_event_handler(events.clone()).await;
+//!
+//!     ControlServer::new(manager)
+//!         .with_events(events)
+//!         .with_token("secret")
+//!         .serve("127.0.0.1:6900".parse()?)
+//!         .await?;
+//!     Ok(())
+//! }
+//! ```
+
+pub mod wire;
+
+use crate::models::ListOrder;
+use crate::services::EventBus;
+use crate::traits::DownloadManager;
+use crate::types::DownloadStatus;
+use crate::Result;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::Response;
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use wire::{AddDownloadRequest, ApiError, CancelQuery, ListTasksQuery, SpeedLimitRequest, parse_task_id};
+
+/// HTTP server driving a download manager
+#[derive(Clone)]
+pub struct ControlServer {
+    manager: Arc<dyn DownloadManager>,
+    events: Option<Arc<EventBus>>,
+    token: Option<String>,
+}
+
+impl ControlServer {
+    /// Serve the given manager without events or authentication
+    pub fn new(manager: Arc<dyn DownloadManager>) -> Self {
+        Self {
+            manager,
+            events: None,
+            token: None,
+        }
+    }
+
+    /// Stream the events of `events` on `GET /events`
+    ///
+    /// The bus must be registered as an event handler of the manager.
+    pub fn with_events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Require `Authorization: Bearer <token>` on every request
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Build the router, e.g. to nest it into an existing axum application
+    pub fn router(self) -> Router {
+        let state = Arc::new(self);
+
+        Router::new()
+            .route("/tasks", get(list_tasks).post(add_download))
+            .route("/snapshot", get(snapshot))
+            .route("/tasks/:id", get(get_task).delete(cancel_download))
+            .route("/tasks/:id/pause", post(pause_download))
+            .route("/tasks/:id/resume", post(resume_download))
+            .route("/tasks/:id/cancel", post(cancel_and_keep))
+            .route("/tasks/:id/progress", get(get_progress))
+            .route("/tasks/:id/speed-limit", put(set_task_speed_limit))
+            .route("/pause-all", post(pause_all))
+            .route("/resume-all", post(resume_all))
+            .route("/speed-limit", put(set_global_speed_limit))
+            .route("/events", get(events))
+            .layer(middleware::from_fn_with_state(state.clone(), authorize))
+            .with_state(state)
+    }
+
+    /// Listen on `addr` until the process ends
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        log::info!("Control server listening on {}", addr);
+
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+type ServerState = State<Arc<ControlServer>>;
+type ApiResult = std::result::Result<Json<Value>, ApiError>;
+
+async fn authorize(State(server): ServerState, request: Request, next: Next) -> std::result::Result<Response, ApiError> {
+    if let Some(token) = &server.token {
+        let authorized = request.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|provided| provided == token);
+        if !authorized {
+            return Err(ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string()));
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+async fn list_tasks(State(server): ServerState, Query(query): Query<ListTasksQuery>) -> ApiResult {
+    let tasks = match query {
+        ListTasksQuery { offset: None, limit: None, order: None } => server.manager.list_tasks().await?,
+        ListTasksQuery { offset, limit, order } => server.manager.list_tasks_page(
+            offset.unwrap_or(0),
+            limit.unwrap_or(usize::MAX),
+            order.unwrap_or(ListOrder::CreatedAsc),
+        ).await?,
+    };
+    Ok(Json(Value::Array(tasks.iter().map(wire::task_json).collect())))
+}
+
+async fn snapshot(State(server): ServerState) -> ApiResult {
+    let snapshot = server.manager.list_tasks_with_progress().await?;
+    Ok(Json(Value::Array(snapshot.iter()
+        .map(|(task, progress)| {
+            let mut task_json = wire::task_json(task);
+            task_json["progress"] = wire::progress_json(progress);
+            task_json
+        })
+        .collect())))
+}
+
+async fn add_download(State(server): ServerState, Json(request): Json<AddDownloadRequest>) -> ApiResult {
+    let task_id = match request.options {
+        Some(options) => server.manager.add_download_with_options(request.url, request.target_path, options).await?,
+        None => server.manager.add_download(request.url, request.target_path).await?,
+    };
+    Ok(Json(json!({ "task_id": wire::task_id_json(task_id) })))
+}
+
+async fn get_task(State(server): ServerState, Path(id): Path<String>) -> ApiResult {
+    let task_id = parse_task_id(&id)?;
+    let task = server.manager.get_task(task_id).await?;
+    let mut body = wire::task_json(&task);
+    // Failed tasks come with the diagnostics of their failure
+    if matches!(task.status, DownloadStatus::Failed(_)) {
+        if let Some(failure) = server.manager.failure_info(task_id).await? {
+            body["failure"] = wire::failure_json(&failure);
+        }
+    }
+    Ok(Json(body))
+}
+
+async fn cancel_download(State(server): ServerState, Path(id): Path<String>) -> ApiResult {
+    server.manager.cancel_download(parse_task_id(&id)?).await?;
+    Ok(Json(json!({})))
+}
+
+async fn cancel_and_keep(State(server): ServerState, Path(id): Path<String>, Query(query): Query<CancelQuery>) -> ApiResult {
+    let cancelled = server.manager.cancel_and_keep(parse_task_id(&id)?, query.delete_files).await?;
+    Ok(Json(json!({ "cancelled": cancelled })))
+}
+
+async fn pause_download(State(server): ServerState, Path(id): Path<String>) -> ApiResult {
+    server.manager.pause_download(parse_task_id(&id)?).await?;
+    Ok(Json(json!({})))
+}
+
+async fn resume_download(State(server): ServerState, Path(id): Path<String>) -> ApiResult {
+    server.manager.resume_download(parse_task_id(&id)?).await?;
+    Ok(Json(json!({})))
+}
+
+async fn get_progress(State(server): ServerState, Path(id): Path<String>) -> ApiResult {
+    let progress = server.manager.get_progress(parse_task_id(&id)?).await?;
+    Ok(Json(wire::progress_json(&progress)))
+}
+
+async fn set_task_speed_limit(
+    State(server): ServerState,
+    Path(id): Path<String>,
+    Json(request): Json<SpeedLimitRequest>,
+) -> ApiResult {
+    server.manager.set_task_download_limit(parse_task_id(&id)?, request.bytes_per_sec).await?;
+    Ok(Json(json!({})))
+}
+
+async fn pause_all(State(server): ServerState) -> ApiResult {
+    let paused = server.manager.pause_all().await?;
+    Ok(Json(json!({ "task_ids": paused.into_iter().map(wire::task_id_json).collect::<Vec<_>>() })))
+}
+
+async fn resume_all(State(server): ServerState) -> ApiResult {
+    let resumed = server.manager.resume_all().await?;
+    Ok(Json(json!({ "task_ids": resumed.into_iter().map(wire::task_id_json).collect::<Vec<_>>() })))
+}
+
+async fn set_global_speed_limit(State(server): ServerState, Json(request): Json<SpeedLimitRequest>) -> ApiResult {
+    server.manager.set_global_download_limit(request.bytes_per_sec).await?;
+    Ok(Json(json!({})))
+}
+
+async fn events(
+    State(server): ServerState,
+) -> std::result::Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>, ApiError> {
+    let events = server.events.as_ref()
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "Events are not enabled on this server".to_string()))?;
+
+    // Subscribers that fall behind skip the missed events
+    let stream = BroadcastStream::new(events.subscribe_events())
+        .filter_map(|event| event.ok())
+        .map(|event| {
+            let (name, data) = wire::event_json(&event);
+            Ok(Event::default().event(name).data(data.to_string()))
+        });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
\ No newline at end of file
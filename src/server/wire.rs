@@ -0,0 +1,220 @@
+//! JSON representation of tasks, progress and events
+//!
+//! Kept separate from the crate types so the wire format stays stable for
+//! clients in other languages.
+
+use crate::error::DownloadError;
+use crate::models::{DownloadEvent, DownloadOptions, FailureInfo, FailureKind, ListOrder, SpeedSample, ProgressKind, completion_percentage};
+use crate::types::{DownloadProgress, DownloadStatus, DownloadTask, TaskId};
+use crate::types::serialize::status_fields;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/// Body of a request adding a download
+#[derive(Debug, Deserialize)]
+pub struct AddDownloadRequest {
+    pub url: String,
+    pub target_path: PathBuf,
+    #[serde(default)]
+    pub options: Option<DownloadOptions>,
+}
+
+/// Query of a request listing a page of tasks
+#[derive(Debug, Default, Deserialize)]
+pub struct ListTasksQuery {
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+    pub order: Option<ListOrder>,
+}
+
+/// Query of a request cancelling a download and keeping it
+#[derive(Debug, Default, Deserialize)]
+pub struct CancelQuery {
+    #[serde(default)]
+    pub delete_files: bool,
+}
+
+/// Body of a request limiting the download speed
+#[derive(Debug, Deserialize)]
+pub struct SpeedLimitRequest {
+    /// Bytes per second, 0 removes the limit
+    pub bytes_per_sec: u64,
+}
+
+/// Error returned to clients as `{"error": "..."}` with a matching status code
+#[derive(Debug)]
+pub struct ApiError(pub StatusCode, pub String);
+
+impl From<DownloadError> for ApiError {
+    fn from(error: DownloadError) -> Self {
+        let status = match &error {
+            DownloadError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+            DownloadError::InvalidUrl(_)
+            | DownloadError::InvalidPath(_)
+            | DownloadError::InvalidOption(_)
+            | DownloadError::InvalidTaskState { .. }
+            | DownloadError::InvalidStatusTransition => StatusCode::BAD_REQUEST,
+            DownloadError::PolicyViolation { .. }
+            | DownloadError::FileExists(_)
+            | DownloadError::TargetPathConflict { .. }
+            | DownloadError::FileTaken(_) => StatusCode::CONFLICT,
+            DownloadError::ConcurrencyLimitExceeded
+            | DownloadError::QueueFull(_) => StatusCode::TOO_MANY_REQUESTS,
+            DownloadError::FileTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            DownloadError::ContentNotAllowed(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            DownloadError::BackendTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            DownloadError::DownloaderUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Parse a task ID as it appears in URLs
+pub fn parse_task_id(raw: &str) -> Result<TaskId, ApiError> {
+    serde_json::from_value(Value::String(raw.to_string()))
+        .map_err(|_| ApiError(StatusCode::BAD_REQUEST, format!("Invalid task ID: {}", raw)))
+}
+
+pub fn task_id_json(task_id: TaskId) -> Value {
+    serde_json::to_value(task_id).unwrap_or(Value::Null)
+}
+
+pub fn status_json(status: &DownloadStatus) -> Value {
+    let (name, error) = status_fields(status);
+    json!({ "status": name, "error": error })
+}
+
+pub fn task_json(task: &DownloadTask) -> Value {
+    let (status, error) = status_fields(&task.status);
+    json!({
+        "id": task_id_json(task.id),
+        "url": task.url,
+        "target_path": task.target_path,
+        "status": status,
+        "error": error,
+    })
+}
+
+pub fn progress_json(progress: &DownloadProgress) -> Value {
+    json!({
+        "downloaded_bytes": progress.downloaded_bytes,
+        "total_bytes": progress.total_bytes,
+        "speed_bps": progress.speed_bps,
+        "eta_seconds": progress.eta_seconds,
+        "kind": progress_kind_name(ProgressKind::of(progress)),
+        "percentage": completion_percentage(progress),
+    })
+}
+
+fn progress_kind_name(kind: ProgressKind) -> &'static str {
+    match kind {
+        ProgressKind::Determinate => "determinate",
+        ProgressKind::Indeterminate => "indeterminate",
+        ProgressKind::Finalizing => "finalizing",
+    }
+}
+
+fn failure_kind_name(kind: FailureKind) -> &'static str {
+    match kind {
+        FailureKind::Network => "network",
+        FailureKind::Timeout => "timeout",
+        FailureKind::NotFound => "not_found",
+        FailureKind::Unauthorized => "unauthorized",
+        FailureKind::Http => "http",
+        FailureKind::Checksum => "checksum",
+        FailureKind::DiskFull => "disk_full",
+        FailureKind::TooLarge => "too_large",
+        FailureKind::Rejected => "rejected",
+        FailureKind::Expired => "expired",
+        FailureKind::Removed => "removed",
+        FailureKind::Cancelled => "cancelled",
+        FailureKind::Unknown => "unknown",
+    }
+}
+
+pub fn failure_json(failure: &FailureInfo) -> Value {
+    json!({
+        "kind": failure_kind_name(failure.kind),
+        "message": failure.message,
+        "http_status": failure.http_status,
+        "aria2_error_code": failure.aria2_error_code,
+        "retry_count": failure.retry_count,
+        "last_attempt_at": failure.last_attempt_at.duration_since(UNIX_EPOCH).map(|age| age.as_secs()).unwrap_or(0),
+    })
+}
+
+pub fn speed_sample_json(sample: &SpeedSample) -> Value {
+    serde_json::to_value(sample).unwrap_or(Value::Null)
+}
+
+/// Get the event name and payload sent to subscribers
+pub fn event_json(event: &DownloadEvent) -> (&'static str, Value) {
+    let task_id = event.task_id().map(task_id_json);
+    match event {
+        DownloadEvent::StatusChanged { old_status, new_status, .. } => ("status_changed", json!({
+            "task_id": task_id,
+            "old": status_json(old_status),
+            "new": status_json(new_status),
+        })),
+        DownloadEvent::ProgressUpdated { progress, .. } => ("progress", json!({
+            "task_id": task_id,
+            "progress": progress_json(progress),
+        })),
+        DownloadEvent::Completed { .. } => ("completed", json!({ "task_id": task_id })),
+        DownloadEvent::Failed { error, .. } => ("failed", json!({ "task_id": task_id, "error": error })),
+        DownloadEvent::RetryScheduled { attempt, delay, .. } => ("retry_scheduled", json!({
+            "task_id": task_id,
+            "attempt": attempt,
+            "delay_ms": delay.as_millis() as u64,
+        })),
+        DownloadEvent::Restored { resumed_from, .. } => ("restored", json!({
+            "task_id": task_id,
+            "resumed_from": resumed_from,
+        })),
+        DownloadEvent::SourceChanged { restarted_as, .. } => ("source_changed", json!({
+            "task_id": task_id,
+            "restarted_as": task_id_json(*restarted_as),
+        })),
+        DownloadEvent::ExtractionProgress { extracted_bytes, total_bytes, .. } => ("extraction_progress", json!({
+            "task_id": task_id,
+            "extracted_bytes": extracted_bytes,
+            "total_bytes": total_bytes,
+        })),
+        DownloadEvent::CopyProgress { destination, copied_bytes, total_bytes, .. } => ("copy_progress", json!({
+            "task_id": task_id,
+            "destination": destination,
+            "copied_bytes": copied_bytes,
+            "total_bytes": total_bytes,
+        })),
+        DownloadEvent::PostProcessed { path, .. } => ("post_processed", json!({
+            "task_id": task_id,
+            "path": path,
+        })),
+        DownloadEvent::PostProcessingFailed { hook, error, .. } => ("post_processing_failed", json!({
+            "task_id": task_id,
+            "hook": hook,
+            "error": error,
+        })),
+        DownloadEvent::Expired { expires_at, .. } => ("expired", json!({
+            "task_id": task_id,
+            "expires_at": expires_at.duration_since(UNIX_EPOCH).map(|age| age.as_secs()).unwrap_or(0),
+        })),
+        DownloadEvent::FailureRecorded { failure, .. } => ("failure_recorded", json!({
+            "task_id": task_id,
+            "failure": failure_json(failure),
+        })),
+        DownloadEvent::SpeedSample { sample } => ("speed", speed_sample_json(sample)),
+    }
+}
\ No newline at end of file
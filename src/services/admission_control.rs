@@ -0,0 +1,119 @@
+//! Admission control of new downloads
+//!
+//! Keeps the additions of the last second and the additions still being
+//! created, and tells the manager whether another one fits within the
+//! [`AdmissionLimits`].
+
+use crate::models::AdmissionLimits;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long an addition held back by the task limit waits before checking again
+pub const ADMISSION_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Why an addition was not admitted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdmissionRefusal {
+    /// Which limit was reached
+    pub reason: String,
+    /// When the addition may fit again
+    pub retry_after: Duration,
+}
+
+/// Admission of new downloads under the configured limits
+#[derive(Debug, Default)]
+pub struct AdmissionControl {
+    limits: Mutex<AdmissionLimits>,
+    state: Mutex<AdmissionState>,
+}
+
+#[derive(Debug, Default)]
+struct AdmissionState {
+    /// When the additions of the last second were admitted, oldest first
+    recent: VecDeque<Instant>,
+    /// Additions admitted whose task is not created yet
+    pending: usize,
+}
+
+/// Admission of one addition, counted as an unfinished task until dropped
+#[derive(Debug)]
+pub struct AdmissionPermit<'a> {
+    control: &'a AdmissionControl,
+}
+
+impl Drop for AdmissionPermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.control.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending = state.pending.saturating_sub(1);
+    }
+}
+
+impl AdmissionControl {
+    pub fn new(limits: AdmissionLimits) -> Self {
+        Self {
+            limits: Mutex::new(limits),
+            state: Mutex::new(AdmissionState::default()),
+        }
+    }
+
+    /// Get the current limits
+    pub fn limits(&self) -> AdmissionLimits {
+        *self.limits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Replace the limits, additions waiting for room are checked against the new ones
+    pub fn set_limits(&self, limits: AdmissionLimits) {
+        *self.limits.lock().unwrap_or_else(|e| e.into_inner()) = limits;
+    }
+
+    /// Admit an addition if it fits, with `queued` unfinished tasks besides the pending additions
+    pub fn try_admit(&self, queued: usize) -> Result<AdmissionPermit<'_>, AdmissionRefusal> {
+        self.try_admit_at(queued, Instant::now())
+    }
+
+    /// [`try_admit`](Self::try_admit) at a given time
+    pub fn try_admit_at(&self, queued: usize, now: Instant) -> Result<AdmissionPermit<'_>, AdmissionRefusal> {
+        let limits = self.limits();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(max) = limits.max_queued_tasks {
+            let unfinished = queued + state.pending;
+            if unfinished >= max {
+                return Err(AdmissionRefusal {
+                    reason: format!("{} unfinished tasks, at most {} allowed", unfinished, max),
+                    retry_after: ADMISSION_RETRY_INTERVAL,
+                });
+            }
+        }
+
+        match limits.max_additions_per_second {
+            Some(max) => {
+                while state.recent.front().is_some_and(|admitted| now.saturating_duration_since(*admitted) >= RATE_WINDOW) {
+                    state.recent.pop_front();
+                }
+                if state.recent.len() >= max as usize {
+                    let retry_after = state.recent.front()
+                        .map(|oldest| RATE_WINDOW.saturating_sub(now.saturating_duration_since(*oldest)))
+                        .unwrap_or(ADMISSION_RETRY_INTERVAL);
+                    return Err(AdmissionRefusal {
+                        reason: format!("at most {} additions per second allowed", max),
+                        retry_after,
+                    });
+                }
+                state.recent.push_back(now);
+            }
+            None => state.recent.clear(),
+        }
+
+        state.pending += 1;
+        Ok(AdmissionPermit { control: self })
+    }
+
+    /// Number of additions admitted whose task is not created yet
+    pub fn pending(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).pending
+    }
+}
\ No newline at end of file
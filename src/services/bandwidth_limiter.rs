@@ -0,0 +1,82 @@
+//! Bandwidth limiting service
+//!
+//! Tracks global and per-task download speed limits. Backends that enforce
+//! limits themselves (aria2) only need the configured values; native backends
+//! use [`BandwidthLimiter::effective_limit`] to throttle each transfer.
+
+use crate::types::TaskId;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Limit value meaning "no limit", following the aria2 convention
+pub const UNLIMITED: u64 = 0;
+
+/// Global and per-task download speed limits in bytes per second
+#[derive(Debug, Default)]
+pub struct BandwidthLimiter {
+    global_limit: RwLock<u64>,
+    task_limits: RwLock<HashMap<TaskId, u64>>,
+}
+
+impl BandwidthLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the combined limit for all downloads (0 = unlimited)
+    pub async fn set_global_limit(&self, bytes_per_sec: u64) {
+        *self.global_limit.write().await = bytes_per_sec;
+    }
+
+    /// Get the combined limit for all downloads (0 = unlimited)
+    pub async fn global_limit(&self) -> u64 {
+        *self.global_limit.read().await
+    }
+
+    /// Set the limit for a single task (0 = unlimited)
+    pub async fn set_task_limit(&self, task_id: TaskId, bytes_per_sec: u64) {
+        let mut limits = self.task_limits.write().await;
+        if bytes_per_sec == UNLIMITED {
+            limits.remove(&task_id);
+        } else {
+            limits.insert(task_id, bytes_per_sec);
+        }
+    }
+
+    /// Get the limit for a single task (0 = unlimited)
+    pub async fn task_limit(&self, task_id: TaskId) -> u64 {
+        self.task_limits.read().await
+            .get(&task_id)
+            .copied()
+            .unwrap_or(UNLIMITED)
+    }
+
+    /// Forget the limit of a task that no longer exists
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.task_limits.write().await.remove(&task_id);
+    }
+
+    /// Compute the rate a task may use when `active_count` downloads share the global limit
+    ///
+    /// The global limit is split evenly across active downloads and combined
+    /// with the task's own limit, whichever is stricter. Returns 0 if neither
+    /// limit applies.
+    pub async fn effective_limit(&self, task_id: TaskId, active_count: usize) -> u64 {
+        let global = self.global_limit().await;
+        let global_share = if global == UNLIMITED {
+            UNLIMITED
+        } else {
+            (global / active_count.max(1) as u64).max(1)
+        };
+
+        stricter_limit(global_share, self.task_limit(task_id).await)
+    }
+}
+
+/// Pick the stricter of two limits, treating 0 as unlimited
+fn stricter_limit(a: u64, b: u64) -> u64 {
+    match (a, b) {
+        (UNLIMITED, other) | (other, UNLIMITED) => other,
+        (a, b) => a.min(b),
+    }
+}
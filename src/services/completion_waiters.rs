@@ -0,0 +1,127 @@
+//! Completion waiters
+//!
+//! Lets callers await a task reaching its final state. Each awaited task gets
+//! a `watch` channel that the manager resolves when it sees the task complete,
+//! fail for good or get removed, so waiting costs no polling.
+
+use crate::error::DownloadError;
+use crate::types::{TaskId, DownloadTask, DownloadStatus};
+use crate::Result;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{RwLock, watch};
+
+/// How a task ended
+#[derive(Debug, Clone)]
+pub enum TaskOutcome {
+    Completed(DownloadTask),
+    /// The task failed and no retry is left
+    Failed(DownloadTask),
+    /// The task was cancelled
+    Removed,
+}
+
+impl TaskOutcome {
+    /// Get the outcome of a task in a final status
+    pub fn of(task: &DownloadTask) -> Option<Self> {
+        match task.status {
+            DownloadStatus::Completed => Some(Self::Completed(task.clone())),
+            DownloadStatus::Failed(_) => Some(Self::Failed(task.clone())),
+            _ => None,
+        }
+    }
+
+    /// Turn the outcome into the result of a wait, the task when it completed
+    pub fn into_result(self, task_id: TaskId) -> Result<DownloadTask> {
+        match self {
+            Self::Completed(task) => Ok(task),
+            Self::Failed(task) => {
+                let reason = match task.status {
+                    DownloadStatus::Failed(error) => error,
+                    other => other.to_string(),
+                };
+                Err(DownloadError::DownloadFailed { task_id, reason })
+            }
+            Self::Removed => Err(DownloadError::TaskNotFound(task_id)),
+        }
+    }
+}
+
+/// Channels of the tasks someone is waiting for
+#[derive(Debug, Default)]
+pub struct CompletionWaiters {
+    channels: RwLock<HashMap<TaskId, watch::Sender<Option<TaskOutcome>>>>,
+}
+
+impl CompletionWaiters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start waiting for a task
+    ///
+    /// Subscribe before reading the current status of the task, so an outcome
+    /// reported in between is not missed.
+    pub async fn subscribe(&self, task_id: TaskId) -> CompletionReceiver {
+        let receiver = self.channels.write().await
+            .entry(task_id)
+            .or_insert_with(|| watch::channel(None).0)
+            .subscribe();
+
+        CompletionReceiver { task_id, receiver }
+    }
+
+    /// Report how a task ended to everyone waiting for it
+    ///
+    /// Does nothing when nobody waits for the task.
+    pub async fn resolve(&self, task_id: TaskId, outcome: TaskOutcome) {
+        if let Some(sender) = self.channels.write().await.remove(&task_id) {
+            sender.send_replace(Some(outcome));
+        }
+    }
+
+    /// Check if anyone waits for a task
+    pub async fn is_waiting(&self, task_id: TaskId) -> bool {
+        self.channels.read().await.contains_key(&task_id)
+    }
+}
+
+/// A subscription to the outcome of one task
+pub struct CompletionReceiver {
+    task_id: TaskId,
+    receiver: watch::Receiver<Option<TaskOutcome>>,
+}
+
+impl CompletionReceiver {
+    /// Wait until the task ends and return it once completed
+    ///
+    /// `current` is the outcome already known from the status read after
+    /// subscribing, if any. Fails with [`DownloadError::DownloadFailed`] when
+    /// the task failed, [`DownloadError::TaskNotFound`] when it was removed
+    /// and [`DownloadError::WaitTimeout`] once `timeout` elapsed.
+    pub async fn wait(mut self, current: Option<TaskOutcome>, timeout: Option<Duration>) -> Result<DownloadTask> {
+        let task_id = self.task_id;
+        if let Some(outcome) = current {
+            return outcome.into_result(task_id);
+        }
+
+        let outcome = async move {
+            loop {
+                if let Some(outcome) = self.receiver.borrow_and_update().clone() {
+                    return outcome;
+                }
+                // The channel only closes after its outcome was sent
+                if self.receiver.changed().await.is_err() {
+                    return TaskOutcome::Removed;
+                }
+            }
+        };
+
+        let outcome = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, outcome).await
+                .map_err(|_| DownloadError::WaitTimeout { task_id, timeout })?,
+            None => outcome.await,
+        };
+        outcome.into_result(task_id)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+//! Start deadlines of queued downloads
+//!
+//! A download can be given the time by which it has to start, e.g. when its
+//! presigned URL stops being valid. The persistence poller asks for the
+//! deadlines that passed and fails the tasks still waiting as expired.
+
+use crate::types::TaskId;
+use std::collections::HashMap;
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+
+/// Deadlines of the downloads that have not started yet
+#[derive(Debug, Default)]
+pub struct DeadlineTracker {
+    deadlines: RwLock<HashMap<TaskId, SystemTime>>,
+}
+
+impl DeadlineTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch a task until it starts, if it has a deadline
+    pub async fn track(&self, task_id: TaskId, expires_at: Option<SystemTime>) {
+        if let Some(expires_at) = expires_at {
+            self.deadlines.write().await.insert(task_id, expires_at);
+        }
+    }
+
+    /// Get the deadline of a task that has not started yet
+    pub async fn deadline(&self, task_id: TaskId) -> Option<SystemTime> {
+        self.deadlines.read().await.get(&task_id).copied()
+    }
+
+    /// Check if any task is watched, so polls without deadlines cost nothing
+    pub async fn is_empty(&self) -> bool {
+        self.deadlines.read().await.is_empty()
+    }
+
+    /// Get the tasks whose deadline passed at `now`, with their deadlines
+    ///
+    /// The tasks stay watched until they are removed, so a paused task is
+    /// reported again on later polls.
+    pub async fn due(&self, now: SystemTime) -> Vec<(TaskId, SystemTime)> {
+        self.deadlines.read().await.iter()
+            .filter(|(_, expires_at)| **expires_at <= now)
+            .map(|(task_id, expires_at)| (*task_id, *expires_at))
+            .collect()
+    }
+
+    /// Forget a task, once it started or was removed
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.deadlines.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,168 @@
+//! Domain usage accounting
+//!
+//! Sums the bytes each task downloads by the host of its source URL and
+//! keeps the totals in the `domain_usage` table of the crate-owned SQLite
+//! database. Tasks with several mirrors count towards the host they were
+//! started from.
+
+use crate::types::TaskId;
+use crate::error::DownloadError;
+use crate::models::DomainUsage;
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, db_error};
+use crate::sources::mirrors::host_of;
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// Host of a task and the bytes of it already counted
+struct TrackedTask {
+    host: String,
+    counted_bytes: u64,
+}
+
+/// Cumulative bytes downloaded per source host
+pub struct DomainUsageTracker {
+    tasks: RwLock<HashMap<TaskId, TrackedTask>>,
+    totals: RwLock<HashMap<String, DomainUsage>>,
+    /// Bytes counted since the last flush, by host
+    pending: RwLock<HashMap<String, u64>>,
+    pool: SqlitePool,
+}
+
+impl DomainUsageTracker {
+    /// Keep the totals in the given SQLite file, loading those of earlier runs
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Self::with_pool(open_pool(path).await?).await
+    }
+
+    /// Keep the totals in a SQLite database that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Self::with_pool(in_memory_pool().await?).await
+    }
+
+    pub(crate) async fn with_pool(pool: SqlitePool) -> Result<Self, DownloadError> {
+        let rows = sqlx::query("SELECT host, bytes_downloaded, updated_at FROM domain_usage")
+            .fetch_all(&pool)
+            .await
+            .map_err(db_error)?;
+
+        let totals = rows.into_iter()
+            .map(|row| {
+                let usage = DomainUsage {
+                    host: row.get("host"),
+                    bytes_downloaded: row.get::<i64, _>("bytes_downloaded") as u64,
+                    last_updated: UNIX_EPOCH + Duration::from_millis(row.get::<i64, _>("updated_at") as u64),
+                };
+                (usage.host.clone(), usage)
+            })
+            .collect();
+
+        Ok(Self {
+            tasks: RwLock::new(HashMap::new()),
+            totals: RwLock::new(totals),
+            pending: RwLock::new(HashMap::new()),
+            pool,
+        })
+    }
+
+    /// Count the bytes of a task towards the host of `url`
+    ///
+    /// `resumed_from` bytes were already downloaded, and counted, before the
+    /// task was added; they are not counted again.
+    pub async fn track(&self, task_id: TaskId, url: &str, resumed_from: u64) {
+        let Some(host) = host_of(url) else {
+            return;
+        };
+        self.tasks.write().await.insert(task_id, TrackedTask { host, counted_bytes: resumed_from });
+    }
+
+    /// Count the bytes a task downloaded since it was last observed
+    ///
+    /// When the downloaded bytes went down the download started over, and
+    /// everything it reports now was downloaded again.
+    pub async fn observe(&self, task_id: TaskId, downloaded_bytes: u64) {
+        let (host, delta) = {
+            let mut tasks = self.tasks.write().await;
+            let Some(task) = tasks.get_mut(&task_id) else {
+                return;
+            };
+            let delta = downloaded_bytes.checked_sub(task.counted_bytes).unwrap_or(downloaded_bytes);
+            task.counted_bytes = downloaded_bytes;
+            (task.host.clone(), delta)
+        };
+        if delta == 0 {
+            return;
+        }
+
+        let now = SystemTime::now();
+        let mut totals = self.totals.write().await;
+        let usage = totals.entry(host.clone()).or_insert_with(|| DomainUsage {
+            host: host.clone(),
+            bytes_downloaded: 0,
+            last_updated: now,
+        });
+        usage.bytes_downloaded = usage.bytes_downloaded.saturating_add(delta);
+        usage.last_updated = now;
+        *self.pending.write().await.entry(host).or_insert(0) += delta;
+    }
+
+    /// Write the bytes counted since the last flush to the database
+    ///
+    /// Bytes that could not be written are kept for the next flush.
+    pub async fn flush(&self) -> Result<(), DownloadError> {
+        let pending = std::mem::take(&mut *self.pending.write().await);
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.write(&pending).await;
+        if result.is_err() {
+            let mut kept = self.pending.write().await;
+            for (host, bytes) in pending {
+                *kept.entry(host).or_insert(0) += bytes;
+            }
+        }
+        result
+    }
+
+    async fn write(&self, pending: &HashMap<String, u64>) -> Result<(), DownloadError> {
+        let now = unix_millis(SystemTime::now());
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for (host, bytes) in pending {
+            sqlx::query(
+                "INSERT INTO domain_usage (host, bytes_downloaded, updated_at) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(host) DO UPDATE SET
+                    bytes_downloaded = bytes_downloaded + excluded.bytes_downloaded,
+                    updated_at = excluded.updated_at"
+            )
+            .bind(host)
+            .bind(*bytes as i64)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)
+    }
+
+    /// Get the bytes downloaded from every host seen so far, most first
+    pub async fn usage(&self) -> Vec<DomainUsage> {
+        let mut usage: Vec<DomainUsage> = self.totals.read().await.values().cloned().collect();
+        usage.sort_by(|a, b| b.bytes_downloaded.cmp(&a.bytes_downloaded).then_with(|| a.host.cmp(&b.host)));
+        usage
+    }
+
+    /// Stop counting the bytes of a task; what it downloaded so far stays counted
+    pub async fn forget(&self, task_id: TaskId) {
+        self.tasks.write().await.remove(&task_id);
+    }
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
\ No newline at end of file
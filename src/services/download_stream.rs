@@ -0,0 +1,170 @@
+//! Reading a download while it is written
+//!
+//! A [`DownloadStream`] tails the file of a running download: it yields the
+//! bytes the backend reports as downloaded and waits at the end of them until
+//! more arrive. It ends once the download completed and its whole file was
+//! read, and fails when the download fails or is removed.
+//!
+//! Only the downloaded prefix of the file is read, so a file preallocated to
+//! its full size is not read past the data written so far. Segmented
+//! downloads write several parts of the file at once, so the prefix is only
+//! contiguous for downloads using a single segment.
+
+use crate::services::TaskRepository;
+use crate::traits::DownloadBackend;
+use crate::types::{TaskId, DownloadStatus};
+use crate::error::DownloadError;
+use std::io::{self, SeekFrom};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, ReadBuf};
+use tokio::sync::mpsc;
+
+/// Largest chunk read from the file at a time
+const CHUNK_SIZE: usize = 256 * 1024;
+/// Chunks read ahead of the consumer
+const CHUNKS_AHEAD: usize = 4;
+/// How often the download is checked for new bytes at the end of the data
+const TAIL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Bytes of a download, readable while it is still running
+pub struct DownloadStream {
+    receiver: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl DownloadStream {
+    /// Start tailing a download
+    ///
+    /// `partial` is the file the download is written to, `target` the file it
+    /// ends up in once complete.
+    pub(crate) fn spawn(
+        task_id: TaskId,
+        backend: Arc<dyn DownloadBackend>,
+        repository: Arc<dyn TaskRepository>,
+        partial: PathBuf,
+        target: PathBuf,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(CHUNKS_AHEAD);
+        tokio::spawn(async move {
+            let tail = Tail { task_id, backend, repository, partial, target, sender };
+            if let Err(e) = tail.run().await {
+                let _ = tail.sender.send(Err(e)).await;
+            }
+        });
+
+        Self { receiver, chunk: Vec::new(), position: 0 }
+    }
+}
+
+impl AsyncRead for DownloadStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        loop {
+            if self.position < self.chunk.len() {
+                let available = &self.chunk[self.position..];
+                let len = available.len().min(buf.remaining());
+                buf.put_slice(&available[..len]);
+                self.position += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            match self.receiver.poll_recv(cx) {
+                Poll::Ready(Some(Ok(chunk))) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Err(e)),
+                // The download completed and everything was read
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Background reader feeding a [`DownloadStream`]
+struct Tail {
+    task_id: TaskId,
+    backend: Arc<dyn DownloadBackend>,
+    repository: Arc<dyn TaskRepository>,
+    partial: PathBuf,
+    target: PathBuf,
+    sender: mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl Tail {
+    async fn run(&self) -> io::Result<()> {
+        let mut file = None;
+        let mut offset = 0u64;
+        let mut buffer = vec![0; CHUNK_SIZE];
+
+        loop {
+            let (status, downloaded) = self.state().await?;
+            let completed = match status {
+                DownloadStatus::Completed => true,
+                DownloadStatus::Failed(reason) => {
+                    return Err(io::Error::new(io::ErrorKind::Other, format!("Download {} failed: {}", self.task_id, reason)));
+                }
+                _ => false,
+            };
+
+            // An open file follows the rename from the partial to the target path
+            if file.is_none() {
+                let path = if completed { &self.target } else { &self.partial };
+                match tokio::fs::File::open(path).await {
+                    Ok(mut opened) => {
+                        opened.seek(SeekFrom::Start(offset)).await?;
+                        file = Some(opened);
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::NotFound && !completed => {}
+                    Err(e) => return Err(e),
+                }
+            }
+
+            if let Some(file) = file.as_mut() {
+                let limit = if completed { u64::MAX } else { downloaded };
+                while offset < limit {
+                    let len = (limit - offset).min(CHUNK_SIZE as u64) as usize;
+                    let read = file.read(&mut buffer[..len]).await?;
+                    if read == 0 {
+                        break;
+                    }
+                    offset += read as u64;
+                    if self.sender.send(Ok(buffer[..read].to_vec())).await.is_err() {
+                        // The stream was dropped
+                        return Ok(());
+                    }
+                }
+                if completed {
+                    return Ok(());
+                }
+            }
+
+            if self.sender.is_closed() {
+                return Ok(());
+            }
+            tokio::time::sleep(TAIL_INTERVAL).await;
+        }
+    }
+
+    /// Get the status of the download and the bytes downloaded so far
+    async fn state(&self) -> io::Result<(DownloadStatus, u64)> {
+        match self.backend.task(self.task_id).await {
+            Ok(task) => {
+                let downloaded = self.backend.progress(self.task_id).await
+                    .map(|progress| progress.downloaded_bytes)
+                    .unwrap_or(0);
+                Ok((task.status, downloaded))
+            }
+            Err(DownloadError::TaskNotFound(_)) => match self.repository.get_task(&self.task_id).await {
+                Ok(task) => Ok((task.status, 0)),
+                Err(_) => Err(io::Error::new(io::ErrorKind::NotFound, format!("Task {} was removed", self.task_id))),
+            },
+            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+        }
+    }
+}
\ No newline at end of file
@@ -1,149 +1,400 @@
 //! Duplicate detection service
 //!
-//! Core service for detecting duplicate downloads and applying policies.
+//! A [`DuplicateDetector`] remembers which tasks download which URL to which
+//! path, so requests for a download that already exists can be matched to
+//! its task. URLs are compared by the hash of their normalized form and
+//! paths in their absolute, cleaned form.
+//!
+//! [`SqliteDuplicateDetector`] keeps the index in the `task_url_hashes`
+//! table of the crate-owned database and is what
+//! [`PersistentAria2Manager`](crate::PersistentAria2Manager) uses unless
+//! another detector is given to its builder. [`InMemoryDuplicateDetector`]
+//! forgets everything when dropped. Custom detectors, for example one that
+//! also asks a remote dedup service, only have to implement the four
+//! required methods.
 
-use crate::types::TaskId;
-use crate::models::{DuplicatePolicy, DuplicateResult};
-use crate::utils::url_normalization::{process_url_for_storage};
+use crate::types::{DownloadTask, TaskId};
+use crate::models::{DuplicatePolicy, DuplicateDecision, DuplicateCandidate, DuplicateReason, FileIdentifier, TaskStatus};
+use crate::services::DuplicateResolver;
+use crate::services::field_cipher::{self, FieldCipher};
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, encode_task_id, decode_value, db_error, unix_now};
+use crate::utils::url_normalization::process_url_for_storage;
+use crate::utils::paths::path_key;
 use crate::error::DownloadError;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use async_trait::async_trait;
-use anyhow::Result;
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use tokio::sync::RwLock;
+
+/// Task as a duplicate detector knows it
+#[derive(Debug, Clone)]
+pub struct IndexedTask {
+    pub id: TaskId,
+    /// URL as it was recorded, empty for tasks indexed before URLs were kept
+    pub url: String,
+    pub url_hash: String,
+    /// Normalized target path
+    pub target_path: PathBuf,
+    pub status: TaskStatus,
+}
+
+/// Former name of [`IndexedTask`]
+pub type MockDownloadTask = IndexedTask;
 
 /// Service for detecting duplicate downloads
 #[async_trait]
 pub trait DuplicateDetector: Send + Sync {
-    /// Find duplicate download task for the given URL and target path
-    async fn find_duplicate(
+    /// Remember a new task, replacing what was recorded for the same ID
+    async fn record(
         &self,
+        task_id: TaskId,
         url: &str,
         target_path: &Path,
-    ) -> Result<DuplicateResult>;
+    ) -> Result<(), DownloadError>;
+
+    /// Update the status of a recorded task, unknown tasks are ignored
+    async fn update_status(
+        &self,
+        task_id: TaskId,
+        status: TaskStatus,
+    ) -> Result<(), DownloadError>;
 
-    /// Find all tasks with the same URL hash
+    /// Forget a task, unknown tasks are ignored
+    async fn forget(&self, task_id: TaskId) -> Result<(), DownloadError>;
+
+    /// Find all tasks with the same URL hash, oldest first
     async fn find_by_url_hash(
         &self,
         url_hash: &str,
-    ) -> Result<Vec<MockDownloadTask>>;
+    ) -> Result<Vec<IndexedTask>, DownloadError>;
+
+    /// Record existing tasks with their current status
+    ///
+    /// Used to fill the index when a manager starts; tasks already recorded
+    /// only have their URL and status updated.
+    async fn record_all(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        for task in tasks {
+            self.record(task.id, &task.url, &task.target_path).await?;
+            self.update_status(task.id, TaskStatus::from_download_status(task.status.clone())).await?;
+        }
+        Ok(())
+    }
+
+    /// Find the tasks downloading the same URL to the same path, oldest first
+    async fn find_matching(
+        &self,
+        url: &str,
+        target_path: &Path,
+    ) -> Result<Vec<IndexedTask>, DownloadError> {
+        let (_normalized_url, url_hash) = process_url_for_storage(url)
+            .map_err(|e| DownloadError::InvalidUrl(e.to_string()))?;
+        let target_path = path_key(target_path);
+
+        Ok(self.find_by_url_hash(&url_hash).await?
+            .into_iter()
+            .filter(|task| task.target_path == target_path)
+            .collect())
+    }
+
+    /// Find duplicate download task for the given URL and target path
+    async fn find_duplicate(
+        &self,
+        url: &str,
+        target_path: &Path,
+    ) -> Result<Option<DuplicateCandidate>, DownloadError> {
+        Ok(self.find_matching(url, target_path).await?
+            .into_iter()
+            .next()
+            .map(|task| DuplicateCandidate::new(task.id, task.status, DuplicateReason::ExactMatch)))
+    }
 
     /// Legacy method for compatibility
     async fn find_by_url_and_path(
         &self,
         url: &str,
         target_path: &Path,
-    ) -> Result<Option<TaskId>, DownloadError>;
+    ) -> Result<Option<TaskId>, DownloadError> {
+        Ok(self.get_candidates(url, target_path).await?.into_iter().next())
+    }
 
-    /// Apply duplicate policy and return appropriate result
+    /// Apply duplicate policy and decide how the request is handled
     async fn apply_policy(
         &self,
         url: &str,
         target_path: &Path,
         policy: DuplicatePolicy,
-    ) -> Result<DuplicateResult, DownloadError>;
+    ) -> Result<DuplicateDecision, DownloadError> {
+        let candidate = self.find_duplicate(url, target_path).await?;
+        let candidates: Vec<DuplicateCandidate> = candidate.into_iter().collect();
+
+        Ok(DuplicateResolver::new().resolve(url, target_path, &policy, &candidates).await)
+    }
 
-    /// Get all potential duplicate candidates
+    /// Get all potential duplicate candidates, oldest first
     async fn get_candidates(
         &self,
         url: &str,
         target_path: &Path,
-    ) -> Result<Vec<TaskId>, DownloadError>;
+    ) -> Result<Vec<TaskId>, DownloadError> {
+        let tasks = self.find_matching(url, target_path).await?;
+        Ok(tasks.into_iter().map(|task| task.id).collect())
+    }
 }
 
-/// Default implementation of DuplicateDetector
-pub struct DefaultDuplicateDetector {
-    // Repository dependencies will be added when implemented
+/// Key a task is indexed under
+fn identify(url: &str, target_path: &Path) -> FileIdentifier {
+    FileIdentifier::new(url, &path_key(target_path), None)
 }
 
-impl Default for DefaultDuplicateDetector {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Detector keeping its index in memory
+#[derive(Default)]
+pub struct InMemoryDuplicateDetector {
+    /// Recorded tasks and the order they were recorded in
+    tasks: RwLock<(HashMap<TaskId, (u64, IndexedTask)>, u64)>,
 }
 
-impl DefaultDuplicateDetector {
+/// Detector used when no store is configured
+pub type DefaultDuplicateDetector = InMemoryDuplicateDetector;
+
+impl InMemoryDuplicateDetector {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
     }
 }
 
 #[async_trait]
-impl DuplicateDetector for DefaultDuplicateDetector {
-    async fn find_duplicate(
+impl DuplicateDetector for InMemoryDuplicateDetector {
+    async fn record(
         &self,
+        task_id: TaskId,
         url: &str,
         target_path: &Path,
-    ) -> Result<DuplicateResult> {
-        // Implementation for TDD - this will be fully implemented
-        let (_normalized_url, url_hash) = process_url_for_storage(url)?;
+    ) -> Result<(), DownloadError> {
+        let identifier = identify(url, target_path);
+        let task = IndexedTask {
+            id: task_id,
+            url: url.to_string(),
+            url_hash: identifier.url_hash,
+            target_path: identifier.target_path,
+            status: TaskStatus::Waiting,
+        };
 
-        // TODO: Query database for existing task with same url_hash and target_path
-        // For now, return NotFound to make tests compile
-        Ok(DuplicateResult::NotFound {
-            url_hash,
-            target_path: target_path.to_path_buf(),
-        })
+        let mut guard = self.tasks.write().await;
+        let (tasks, counter) = &mut *guard;
+        *counter += 1;
+        tasks.insert(task_id, (*counter, task));
+        Ok(())
     }
 
-    async fn find_by_url_hash(
+    async fn update_status(
         &self,
-        _url_hash: &str,
-    ) -> Result<Vec<MockDownloadTask>> {
-        // Implementation for TDD - this will query database by URL hash
-        Ok(Vec::new())
+        task_id: TaskId,
+        status: TaskStatus,
+    ) -> Result<(), DownloadError> {
+        if let Some((_, task)) = self.tasks.write().await.0.get_mut(&task_id) {
+            task.status = status;
+        }
+        Ok(())
     }
 
-    async fn find_by_url_and_path(
-        &self,
-        _url: &str,
-        _target_path: &Path,
-    ) -> Result<Option<TaskId>, DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 3
-        Ok(None)
+    async fn forget(&self, task_id: TaskId) -> Result<(), DownloadError> {
+        self.tasks.write().await.0.remove(&task_id);
+        Ok(())
     }
 
-    async fn apply_policy(
+    async fn find_by_url_hash(
         &self,
-        _url: &str,
-        _target_path: &Path,
-        policy: DuplicatePolicy,
-    ) -> Result<DuplicateResult, DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 3
-        match policy {
-            DuplicatePolicy::AllowDuplicate => {
-                let (_normalized_url, url_hash) = process_url_for_storage(_url)
-                    .map_err(|e| DownloadError::InvalidUrl(e.to_string()))?;
-                Ok(DuplicateResult::NotFound {
-                    url_hash,
-                    target_path: _target_path.to_path_buf(),
-                })
-            }
-            _ => {
-                let (_normalized_url, url_hash) = process_url_for_storage(_url)
-                    .map_err(|e| DownloadError::InvalidUrl(e.to_string()))?;
-                Ok(DuplicateResult::NotFound {
-                    url_hash,
-                    target_path: _target_path.to_path_buf(),
-                })
+        url_hash: &str,
+    ) -> Result<Vec<IndexedTask>, DownloadError> {
+        let guard = self.tasks.read().await;
+        let mut matches: Vec<_> = guard.0.values()
+            .filter(|(_, task)| task.url_hash == url_hash)
+            .collect();
+        matches.sort_by_key(|(order, _)| *order);
+        Ok(matches.into_iter().map(|(_, task)| task.clone()).collect())
+    }
+}
+
+/// Detector keeping its index in the `task_url_hashes` table of a SQLite database
+#[derive(Clone)]
+pub struct SqliteDuplicateDetector {
+    pool: SqlitePool,
+    cipher: Option<FieldCipher>,
+}
+
+impl SqliteDuplicateDetector {
+    /// Keep the index in the given SQLite file
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Ok(Self::with_pool(open_pool(path).await?))
+    }
+
+    /// Keep the index in a SQLite database that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Ok(Self::with_pool(in_memory_pool().await?))
+    }
+
+    /// Share a pool opened by another store of the crate-owned database
+    pub(crate) fn with_pool(pool: SqlitePool) -> Self {
+        Self { pool, cipher: None }
+    }
+
+    /// Encrypt the recorded URLs with `cipher`, hashes and paths stay readable
+    pub fn with_cipher(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Rewrite URLs that are not encrypted with the current key
+    ///
+    /// Returns the number of URLs rewritten.
+    pub async fn reencrypt(&self) -> Result<usize, DownloadError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+        let rows = sqlx::query("SELECT task_id, url FROM task_url_hashes WHERE url IS NOT NULL")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let mut rewritten = 0;
+        for row in rows {
+            let url = row.get::<String, _>("url");
+            if cipher.is_current(&url) {
+                continue;
             }
+            sqlx::query("UPDATE task_url_hashes SET url = ? WHERE task_id = ?")
+                .bind(cipher.encrypt(&cipher.decrypt(&url)?)?)
+                .bind(row.get::<String, _>("task_id"))
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            rewritten += 1;
         }
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(rewritten)
     }
+}
 
-    async fn get_candidates(
+#[async_trait]
+impl DuplicateDetector for SqliteDuplicateDetector {
+    async fn record(
         &self,
-        _url: &str,
-        _target_path: &Path,
-    ) -> Result<Vec<TaskId>, DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 3
-        Ok(vec![])
+        task_id: TaskId,
+        url: &str,
+        target_path: &Path,
+    ) -> Result<(), DownloadError> {
+        let identifier = identify(url, target_path);
+        sqlx::query(
+            "INSERT INTO task_url_hashes (task_id, url_hash, target_path, url, status, updated_at)
+             VALUES (?, ?, ?, ?, NULL, ?)
+             ON CONFLICT(task_id) DO UPDATE SET url_hash = excluded.url_hash,
+                target_path = excluded.target_path, url = excluded.url,
+                status = NULL, updated_at = excluded.updated_at"
+        )
+        .bind(encode_task_id(&task_id)?)
+        .bind(&identifier.url_hash)
+        .bind(identifier.target_path.to_string_lossy())
+        .bind(field_cipher::seal(self.cipher.as_ref(), url)?)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
     }
-}
 
-// Mock types for testing
-#[derive(Debug, Clone)]
-pub struct MockDownloadTask {
-    pub id: TaskId,
-    pub url: String,
-    pub url_hash: String,
-    pub target_path: std::path::PathBuf,
-    pub status: crate::types::DownloadStatus,
+    async fn update_status(
+        &self,
+        task_id: TaskId,
+        status: TaskStatus,
+    ) -> Result<(), DownloadError> {
+        let status = serde_json::to_string(&status)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+
+        sqlx::query("UPDATE task_url_hashes SET status = ? WHERE task_id = ?")
+            .bind(status)
+            .bind(encode_task_id(&task_id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn forget(&self, task_id: TaskId) -> Result<(), DownloadError> {
+        sqlx::query("DELETE FROM task_url_hashes WHERE task_id = ?")
+            .bind(encode_task_id(&task_id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    async fn find_by_url_hash(
+        &self,
+        url_hash: &str,
+    ) -> Result<Vec<IndexedTask>, DownloadError> {
+        let rows = sqlx::query(
+            "SELECT task_id, url, url_hash, target_path, status FROM task_url_hashes
+             WHERE url_hash = ? ORDER BY updated_at, rowid"
+        )
+        .bind(url_hash)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DownloadError::DuplicateDetectionError(e.to_string()))?;
+
+        let tasks = rows.into_iter()
+            .map(|row| {
+                // Tasks recorded before their status was known count as waiting
+                let status = match row.get::<Option<String>, _>("status") {
+                    Some(status) => decode_value(status)?,
+                    None => TaskStatus::Waiting,
+                };
+                Ok(IndexedTask {
+                    id: decode_value(row.get::<String, _>("task_id"))?,
+                    url: match row.get::<Option<String>, _>("url") {
+                        Some(url) => field_cipher::open(self.cipher.as_ref(), url)?,
+                        None => String::new(),
+                    },
+                    url_hash: row.get("url_hash"),
+                    target_path: PathBuf::from(row.get::<String, _>("target_path")),
+                    status,
+                })
+            })
+            .collect::<Result<Vec<_>, DownloadError>>()?;
+        Ok(tasks)
+    }
+
+    /// Record all tasks in one transaction
+    async fn record_all(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        let now = unix_now();
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for task in tasks {
+            let identifier = identify(&task.url, &task.target_path);
+            let status = serde_json::to_string(&TaskStatus::from_download_status(task.status.clone()))
+                .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+
+            // Existing entries keep their place in the order of candidates
+            sqlx::query(
+                "INSERT INTO task_url_hashes (task_id, url_hash, target_path, url, status, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(task_id) DO UPDATE SET url = excluded.url, status = excluded.status"
+            )
+            .bind(encode_task_id(&task.id)?)
+            .bind(&identifier.url_hash)
+            .bind(identifier.target_path.to_string_lossy())
+            .bind(field_cipher::seal(self.cipher.as_ref(), &task.url)?)
+            .bind(status)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)
+    }
 }
\ No newline at end of file
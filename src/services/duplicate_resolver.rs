@@ -0,0 +1,88 @@
+//! Duplicate resolution service
+//!
+//! Turns the tasks matching a download request and a [`DuplicatePolicy`] into
+//! a single [`DuplicateDecision`]. Under [`DuplicatePolicy::PromptUser`] the
+//! choice is handed to the registered [`DuplicateDecisionHandler`], so
+//! applications can ask the user.
+
+use crate::models::{DuplicateCandidate, DuplicateDecision, DuplicatePolicy, DuplicatePreview};
+use crate::traits::DuplicateDecisionHandler;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Decides how duplicate download requests are handled
+#[derive(Default)]
+pub struct DuplicateResolver {
+    handler: RwLock<Option<Arc<dyn DuplicateDecisionHandler>>>,
+}
+
+impl DuplicateResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the handler asked for a decision under [`DuplicatePolicy::PromptUser`]
+    pub async fn set_handler(&self, handler: Arc<dyn DuplicateDecisionHandler>) {
+        *self.handler.write().await = Some(handler);
+    }
+
+    /// Remove the decision handler; prompting then falls back to creating a new task
+    pub async fn clear_handler(&self) {
+        *self.handler.write().await = None;
+    }
+
+    /// Decide how to handle a request for `url` -> `target_path`
+    ///
+    /// `candidates` are the existing tasks matching the request, best match first.
+    pub async fn resolve(
+        &self,
+        url: &str,
+        target_path: &Path,
+        policy: &DuplicatePolicy,
+        candidates: &[DuplicateCandidate],
+    ) -> DuplicateDecision {
+        if let Some(decision) = decide_by_policy(policy, candidates) {
+            return decision;
+        }
+
+        let handler = self.handler.read().await.clone();
+        match handler {
+            Some(handler) => handler.decide(url, target_path, candidates).await,
+            None => {
+                log::warn!("Duplicate of {} requires a decision but no handler is set, creating new task", url);
+                DuplicateDecision::CreateNew
+            }
+        }
+    }
+
+    /// Tell what [`resolve`](Self::resolve) would decide, without asking the handler
+    pub async fn preview(&self, policy: &DuplicatePolicy, candidates: &[DuplicateCandidate]) -> DuplicatePreview {
+        if let Some(decision) = decide_by_policy(policy, candidates) {
+            return decision.into();
+        }
+
+        if self.handler.read().await.is_some() {
+            DuplicatePreview::RequiresDecision { candidates: candidates.to_vec() }
+        } else {
+            DuplicatePreview::CreateNew
+        }
+    }
+}
+
+/// Decide by the policy alone, `None` when the handler has to be asked
+fn decide_by_policy(policy: &DuplicatePolicy, candidates: &[DuplicateCandidate]) -> Option<DuplicateDecision> {
+    let Some(first) = candidates.first() else {
+        return Some(DuplicateDecision::CreateNew);
+    };
+
+    match policy {
+        DuplicatePolicy::AllowDuplicate => Some(DuplicateDecision::CreateNew),
+        DuplicatePolicy::FailIfDuplicate => Some(DuplicateDecision::reject(first)),
+        DuplicatePolicy::PromptUser => None,
+        _ => Some(candidates.iter()
+            .find(|candidate| policy.allows_reuse(&candidate.status))
+            .map(DuplicateDecision::reuse)
+            .unwrap_or(DuplicateDecision::CreateNew)),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+//! Channel-based event distribution
+//!
+//! Fans download notifications out to tokio channels so callers can await
+//! changes instead of polling. The bus is itself a `DownloadEventHandler`
+//! and is registered with the managers like any other handler.
+
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use crate::models::{DownloadEvent, FailureInfo, SpeedSample};
+use crate::traits::{DownloadEventHandler, DownloadEventListener};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, watch, RwLock};
+
+/// Default number of events buffered for slow broadcast subscribers
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
+
+/// Broadcasts all events and keeps the latest progress of each task in a watch channel
+#[derive(Debug)]
+pub struct EventBus {
+    events: broadcast::Sender<DownloadEvent>,
+    progress: RwLock<HashMap<TaskId, watch::Sender<DownloadProgress>>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Create a bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (events, _) = broadcast::channel(capacity);
+        Self {
+            events,
+            progress: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Subscribe to every event of every task
+    ///
+    /// Subscribers that fall more than the channel capacity behind receive
+    /// `RecvError::Lagged` and skip the missed events.
+    pub fn subscribe_events(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.events.subscribe()
+    }
+
+    /// Subscribe to the progress of one task, starting from `current`
+    pub async fn subscribe_progress(&self, task_id: TaskId, current: DownloadProgress) -> watch::Receiver<DownloadProgress> {
+        let mut progress = self.progress.write().await;
+        match progress.get(&task_id) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = watch::channel(current);
+                progress.insert(task_id, sender);
+                receiver
+            }
+        }
+    }
+
+    /// Check if anyone listens for progress of a task
+    pub async fn wants_progress(&self, task_id: TaskId) -> bool {
+        if self.has_subscribers() {
+            return true;
+        }
+
+        self.progress.read().await
+            .get(&task_id)
+            .map(|sender| sender.receiver_count() > 0)
+            .unwrap_or(false)
+    }
+
+    /// Check if anyone listens for events of all tasks
+    pub fn has_subscribers(&self) -> bool {
+        self.events.receiver_count() > 0
+    }
+
+    /// Publish an event to all subscribers
+    pub async fn publish(&self, event: DownloadEvent) {
+        if let DownloadEvent::ProgressUpdated { task_id, progress } = &event {
+            if let Some(sender) = self.progress.read().await.get(task_id) {
+                sender.send_replace(progress.clone());
+            }
+        }
+
+        // Sending only fails when there are no subscribers
+        let _ = self.events.send(event);
+    }
+
+    /// Drop the progress channel of a task that no longer exists
+    ///
+    /// Existing receivers see the channel close.
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.progress.write().await.remove(&task_id);
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for EventBus {
+    async fn on_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        self.publish(DownloadEvent::StatusChanged { task_id, old_status, new_status }).await;
+    }
+
+    async fn on_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
+        self.publish(DownloadEvent::ProgressUpdated { task_id, progress }).await;
+    }
+
+    async fn on_download_completed(&self, task_id: TaskId) {
+        self.publish(DownloadEvent::Completed { task_id }).await;
+    }
+
+    async fn on_download_failed(&self, task_id: TaskId, error: String) {
+        self.publish(DownloadEvent::Failed { task_id, error }).await;
+    }
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        self.publish(DownloadEvent::RetryScheduled { task_id, attempt, delay }).await;
+    }
+
+    async fn on_download_restored(&self, task_id: TaskId, resumed_from: u64) {
+        self.publish(DownloadEvent::Restored { task_id, resumed_from }).await;
+    }
+
+    async fn on_source_changed(&self, task_id: TaskId, restarted_as: TaskId) {
+        self.publish(DownloadEvent::SourceChanged { task_id, restarted_as }).await;
+    }
+
+    async fn on_extraction_progress(&self, task_id: TaskId, extracted_bytes: u64, total_bytes: Option<u64>) {
+        self.publish(DownloadEvent::ExtractionProgress { task_id, extracted_bytes, total_bytes }).await;
+    }
+
+    async fn on_copy_progress(&self, task_id: TaskId, destination: PathBuf, copied_bytes: u64, total_bytes: u64) {
+        self.publish(DownloadEvent::CopyProgress { task_id, destination, copied_bytes, total_bytes }).await;
+    }
+
+    async fn on_post_processed(&self, task_id: TaskId, path: PathBuf) {
+        self.publish(DownloadEvent::PostProcessed { task_id, path }).await;
+    }
+
+    async fn on_post_processing_failed(&self, task_id: TaskId, hook: String, error: String) {
+        self.publish(DownloadEvent::PostProcessingFailed { task_id, hook, error }).await;
+    }
+
+    async fn on_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        self.publish(DownloadEvent::Expired { task_id, expires_at }).await;
+    }
+
+    async fn on_failure_recorded(&self, task_id: TaskId, failure: FailureInfo) {
+        self.publish(DownloadEvent::FailureRecorded { task_id, failure }).await;
+    }
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        self.publish(DownloadEvent::SpeedSample { sample }).await;
+    }
+}
+
+#[async_trait]
+impl DownloadEventListener for EventBus {
+    async fn on_event(&self, event: DownloadEvent) {
+        self.publish(event).await;
+    }
+}
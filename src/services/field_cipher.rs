@@ -0,0 +1,164 @@
+//! At-rest encryption of sensitive database fields
+//!
+//! Download URLs often carry signed tokens or credentials, and the stored
+//! download options can hold authorization headers. A [`FieldCipher`]
+//! encrypts such values with AES-256-GCM before they are written and
+//! decrypts them when read, so the rest of the crate only sees plaintext.
+//!
+//! Encrypted values look like `enc:v1:<key id>:<base64 nonce + ciphertext>`.
+//! Values without that prefix were written before encryption was enabled and
+//! are read as they are. For key rotation the cipher keeps the keys it used
+//! before: values under a previous key still decrypt, and stores rewrite them
+//! under the current key when asked to re-encrypt.
+
+use crate::error::DownloadError;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt;
+
+/// Prefix of values written by a [`FieldCipher`]
+const PREFIX: &str = "enc:v1:";
+
+/// Length of the AES-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// Length of a key in bytes
+pub const KEY_LEN: usize = 32;
+
+#[derive(Clone)]
+struct FieldKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl FieldKey {
+    fn new(id: String, key: &[u8; KEY_LEN]) -> Result<Self, DownloadError> {
+        if id.is_empty() || id.contains(':') {
+            return Err(DownloadError::Config(format!("Invalid encryption key ID {:?}", id)));
+        }
+        Ok(Self { id, cipher: Aes256Gcm::new(key.into()) })
+    }
+}
+
+/// Encrypts and decrypts stored field values with a current and previous keys
+#[derive(Clone)]
+pub struct FieldCipher {
+    current: FieldKey,
+    previous: Vec<FieldKey>,
+}
+
+impl fmt::Debug for FieldCipher {
+    // Only the key IDs, never the keys
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldCipher")
+            .field("current", &self.current.id)
+            .field("previous", &self.previous.iter().map(|key| &key.id).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl FieldCipher {
+    /// Encrypt with `key`, identified in stored values by `key_id`
+    ///
+    /// Key IDs must be non-empty and must not contain `:`.
+    pub fn new(key_id: impl Into<String>, key: [u8; KEY_LEN]) -> Result<Self, DownloadError> {
+        Ok(Self {
+            current: FieldKey::new(key_id.into(), &key)?,
+            previous: Vec::new(),
+        })
+    }
+
+    /// Encrypt with a base64-encoded 32-byte key, as kept in configuration
+    pub fn from_base64(key_id: impl Into<String>, key: &str) -> Result<Self, DownloadError> {
+        Self::new(key_id, decode_key(key)?)
+    }
+
+    /// Keep decrypting values written with a key that was rotated out
+    pub fn with_previous_key(mut self, key_id: impl Into<String>, key: [u8; KEY_LEN]) -> Result<Self, DownloadError> {
+        self.previous.push(FieldKey::new(key_id.into(), &key)?);
+        Ok(self)
+    }
+
+    /// Keep decrypting values written with a base64-encoded previous key
+    pub fn with_previous_base64(self, key_id: impl Into<String>, key: &str) -> Result<Self, DownloadError> {
+        self.with_previous_key(key_id, decode_key(key)?)
+    }
+
+    /// ID of the key new values are encrypted with
+    pub fn key_id(&self) -> &str {
+        &self.current.id
+    }
+
+    /// Encrypt a value with the current key
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, DownloadError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self.current.cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| DownloadError::EncryptionError("Failed to encrypt field".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}:{}", PREFIX, self.current.id, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a stored value, values that were never encrypted are returned as they are
+    pub fn decrypt(&self, stored: &str) -> Result<String, DownloadError> {
+        let Some((key_id, sealed)) = split(stored) else {
+            return Ok(stored.to_string());
+        };
+        let key = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| key.id == key_id)
+            .ok_or_else(|| DownloadError::EncryptionError(format!("No key with ID {:?} to decrypt field", key_id)))?;
+
+        let sealed = STANDARD.decode(sealed)
+            .map_err(|e| DownloadError::EncryptionError(format!("Malformed encrypted field: {}", e)))?;
+        if sealed.len() < NONCE_LEN {
+            return Err(DownloadError::EncryptionError("Malformed encrypted field: too short".to_string()));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = key.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| DownloadError::EncryptionError(format!("Failed to decrypt field with key {:?}", key_id)))?;
+
+        String::from_utf8(plaintext)
+            .map_err(|e| DownloadError::EncryptionError(format!("Decrypted field is not UTF-8: {}", e)))
+    }
+
+    /// Whether a stored value is encrypted with the current key
+    ///
+    /// Values for which this is false are rewritten when re-encrypting.
+    pub fn is_current(&self, stored: &str) -> bool {
+        matches!(split(stored), Some((key_id, _)) if key_id == self.current.id)
+    }
+}
+
+/// Split an encrypted value into key ID and sealed data
+fn split(stored: &str) -> Option<(&str, &str)> {
+    stored.strip_prefix(PREFIX)?.split_once(':')
+}
+
+fn decode_key(key: &str) -> Result<[u8; KEY_LEN], DownloadError> {
+    STANDARD.decode(key.trim())
+        .ok()
+        .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+        .ok_or_else(|| DownloadError::Config(format!("Encryption keys must be {} bytes of base64", KEY_LEN)))
+}
+
+/// Encrypt a value if a cipher is configured
+pub(crate) fn seal(cipher: Option<&FieldCipher>, value: &str) -> Result<String, DownloadError> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(value),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Decrypt a value if a cipher is configured
+pub(crate) fn open(cipher: Option<&FieldCipher>, value: String) -> Result<String, DownloadError> {
+    match cipher {
+        Some(cipher) => cipher.decrypt(&value),
+        None => Ok(value),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,96 @@
+//! Event handler registration
+//!
+//! Managers notify the handlers in a plain list, iterating over a copy of
+//! it. A [`HandlerRegistry`] owns that list and hands out a [`HandlerId`]
+//! for every handler it adds, so applications can remove handlers again
+//! instead of keeping them alive for the lifetime of the manager.
+
+use crate::models::HandlerId;
+use crate::traits::DownloadEventHandler;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::RwLock;
+
+/// Handlers notified by a manager
+pub type HandlerList = RwLock<Vec<Arc<dyn DownloadEventHandler>>>;
+
+/// Handler list with removable entries
+pub struct HandlerRegistry {
+    list: Arc<HandlerList>,
+    next_id: AtomicU64,
+    /// Entry each ID stands for in the list, weak so removed entries are freed
+    entries: Mutex<HashMap<HandlerId, Weak<dyn DownloadEventHandler>>>,
+}
+
+impl HandlerRegistry {
+    /// Start with `handlers`, which cannot be removed
+    pub fn new(handlers: Vec<Arc<dyn DownloadEventHandler>>) -> Self {
+        Self {
+            list: Arc::new(RwLock::new(handlers)),
+            next_id: AtomicU64::new(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the list handlers are notified from
+    pub fn list(&self) -> &Arc<HandlerList> {
+        &self.list
+    }
+
+    /// Reserve the ID of a handler about to be added
+    ///
+    /// Lets wrappers know the ID of the handler they are built for.
+    pub fn next_id(&self) -> HandlerId {
+        HandlerId(self.next_id.fetch_add(1, Ordering::SeqCst))
+    }
+
+    /// Add a handler under an ID from [`next_id`](Self::next_id)
+    pub async fn insert(&self, id: HandlerId, handler: Arc<dyn DownloadEventHandler>) {
+        {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            // Entries a handler took out of the list itself are gone by now
+            entries.retain(|_, entry| entry.strong_count() > 0);
+            entries.insert(id, Arc::downgrade(&handler));
+        }
+        self.list.write().await.push(handler);
+    }
+
+    /// Add a handler
+    pub async fn add(&self, handler: Arc<dyn DownloadEventHandler>) -> HandlerId {
+        let id = self.next_id();
+        self.insert(id, handler).await;
+        id
+    }
+
+    /// Remove a handler, `false` when it was already removed
+    pub async fn remove(&self, id: HandlerId) -> bool {
+        let entry = self.entries.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+        match entry {
+            Some(entry) => unlist(&self.list, Weak::as_ptr(&entry) as *const ()).await,
+            None => false,
+        }
+    }
+
+    /// Get the number of handlers that can be removed
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.values().filter(|entry| entry.strong_count() > 0).count()
+    }
+
+    /// Check if no removable handler is registered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Take the handler at `handler` out of `list`, `false` when it was not listed
+///
+/// Callers notify handlers from a copy of the list, so handlers may remove
+/// themselves while they are being called.
+pub(crate) async fn unlist(list: &HandlerList, handler: *const ()) -> bool {
+    let mut list = list.write().await;
+    let before = list.len();
+    list.retain(|listed| Arc::as_ptr(listed) as *const () != handler);
+    list.len() < before
+}
\ No newline at end of file
@@ -1,11 +1,24 @@
 //! Background hash calculator service
 //!
-//! Calculates file hashes in the background after download completion.
+//! Calculates Blake3 hashes of completed downloads in the background. The
+//! number of files hashed at once is capped so large downloads finishing
+//! together do not saturate the disk. Hashes are kept in memory and, when a
+//! [`TaskMetadataStore`] is attached, persisted under [`FILE_HASH_KEY`].
 
 use crate::types::TaskId;
 use crate::error::DownloadError;
-use std::path::Path;
+use crate::services::TaskMetadataStore;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use async_trait::async_trait;
+use tokio::sync::{RwLock, Semaphore};
+
+/// Key under which the content hash of a completed download is stored
+pub const FILE_HASH_KEY: &str = "file_hash";
+
+/// Number of files hashed at once unless configured otherwise
+pub const DEFAULT_HASH_CONCURRENCY: usize = 2;
 
 /// Service for calculating file hashes in the background
 #[async_trait]
@@ -19,7 +32,11 @@ pub trait HashCalculator: Send + Sync {
 
 /// Background hash calculator implementation
 pub struct BackgroundHashCalculator {
-    // Background task queue will be added when implemented
+    concurrency: usize,
+    permits: Arc<Semaphore>,
+    hashes: Arc<RwLock<HashMap<TaskId, String>>>,
+    pending: Arc<RwLock<HashSet<TaskId>>>,
+    store: Option<Arc<TaskMetadataStore>>,
 }
 
 impl Default for BackgroundHashCalculator {
@@ -30,38 +47,154 @@ impl Default for BackgroundHashCalculator {
 
 impl BackgroundHashCalculator {
     pub fn new() -> Self {
-        Self {}
+        Self::with_concurrency(DEFAULT_HASH_CONCURRENCY)
+    }
+
+    /// Create a calculator that hashes at most `limit` files at once
+    pub fn with_concurrency(limit: usize) -> Self {
+        let concurrency = limit.max(1);
+        Self {
+            concurrency,
+            permits: Arc::new(Semaphore::new(concurrency)),
+            hashes: Arc::new(RwLock::new(HashMap::new())),
+            pending: Arc::new(RwLock::new(HashSet::new())),
+            store: None,
+        }
+    }
+
+    /// Persist calculated hashes in `store`
+    pub fn with_store(mut self, store: Arc<TaskMetadataStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Get the maximum number of files hashed at once
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Check if a task's file is queued or being hashed
+    pub async fn is_pending(&self, task_id: TaskId) -> bool {
+        self.pending.read().await.contains(&task_id)
+    }
+
+    /// Get the content hash of a task's file, if it has been calculated
+    pub async fn hash_for(&self, task_id: TaskId) -> Option<String> {
+        if let Some(hash) = self.hashes.read().await.get(&task_id) {
+            return Some(hash.clone());
+        }
+
+        let store = self.store.as_ref()?;
+        match store.get::<String>(&task_id, FILE_HASH_KEY).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                log::warn!("Failed to load file hash for task {}: {}", task_id, e);
+                None
+            }
+        }
+    }
+
+    /// Find the tasks whose files have the given content hash
+    pub async fn find_by_hash(&self, hash: &str) -> Vec<TaskId> {
+        let mut task_ids: Vec<TaskId> = self.hashes.read().await.iter()
+            .filter(|(_, task_hash)| task_hash.as_str() == hash)
+            .map(|(task_id, _)| *task_id)
+            .collect();
+
+        if let Some(store) = &self.store {
+            match store.entries::<String>(FILE_HASH_KEY).await {
+                Ok(entries) => {
+                    for (task_id, task_hash) in entries {
+                        if task_hash == hash && !task_ids.contains(&task_id) {
+                            task_ids.push(task_id);
+                        }
+                    }
+                }
+                Err(e) => log::warn!("Failed to load file hashes: {}", e),
+            }
+        }
+
+        task_ids
+    }
+
+    /// Forget the hash of a removed task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.hashes.write().await.remove(&task_id);
     }
 }
 
 #[async_trait]
 impl HashCalculator for BackgroundHashCalculator {
-    async fn queue_calculation(&self, _task_id: TaskId, _file_path: &Path) -> Result<(), DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 6
+    async fn queue_calculation(&self, task_id: TaskId, file_path: &Path) -> Result<(), DownloadError> {
+        if self.hashes.read().await.contains_key(&task_id) || !self.pending.write().await.insert(task_id) {
+            return Ok(());
+        }
+
+        let permits = self.permits.clone();
+        let hashes = self.hashes.clone();
+        let pending = self.pending.clone();
+        let store = self.store.clone();
+        let file_path = file_path.to_path_buf();
+
+        tokio::spawn(async move {
+            let result = match permits.acquire_owned().await {
+                Ok(_permit) => hash_file_blocking(file_path.clone()).await,
+                Err(_) => Err(DownloadError::General("Hash calculator was closed".to_string())),
+            };
+
+            match result {
+                Ok(hash) => {
+                    log::debug!("Hashed {:?} for task {}: {}", file_path, task_id, hash);
+                    if let Some(store) = &store {
+                        if let Err(e) = store.put(&task_id, FILE_HASH_KEY, &hash).await {
+                            log::error!("Failed to persist file hash for task {}: {}", task_id, e);
+                        }
+                    }
+                    hashes.write().await.insert(task_id, hash);
+                }
+                Err(e) => log::warn!("Failed to hash {:?} for task {}: {}", file_path, task_id, e),
+            }
+
+            pending.write().await.remove(&task_id);
+        });
+
         Ok(())
     }
 
     async fn calculate_hash(&self, file_path: &Path) -> Result<String, DownloadError> {
-        // Placeholder implementation that calculates hash immediately
-        use std::io::Read;
+        let _permit = self.permits.acquire().await
+            .map_err(|_| DownloadError::General("Hash calculator was closed".to_string()))?;
+        hash_file_blocking(file_path.to_path_buf()).await
+    }
+}
 
-        let mut file = std::fs::File::open(file_path)
-            .map_err(DownloadError::IoError)?;
+/// Hash a file on the blocking thread pool
+async fn hash_file_blocking(file_path: PathBuf) -> Result<String, DownloadError> {
+    tokio::task::spawn_blocking(move || hash_file(&file_path))
+        .await
+        .map_err(|e| DownloadError::General(format!("Hash task failed: {}", e)))?
+}
 
-        let mut hasher = blake3::Hasher::new();
-        let mut buffer = [0; 8192];
+/// Calculate the Blake3 hash of a file
+pub fn hash_file(file_path: &Path) -> Result<String, DownloadError> {
+    use std::io::Read;
 
-        loop {
-            let bytes_read = file.read(&mut buffer)
-                .map_err(DownloadError::IoError)?;
+    let mut file = std::fs::File::open(file_path)
+        .map_err(DownloadError::IoError)?;
 
-            if bytes_read == 0 {
-                break;
-            }
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = [0; 8192];
 
-            hasher.update(&buffer[..bytes_read]);
+    loop {
+        let bytes_read = file.read(&mut buffer)
+            .map_err(DownloadError::IoError)?;
+
+        if bytes_read == 0 {
+            break;
         }
 
-        Ok(hasher.finalize().to_hex().to_string())
+        hasher.update(&buffer[..bytes_read]);
     }
+
+    Ok(hasher.finalize().to_hex().to_string())
 }
\ No newline at end of file
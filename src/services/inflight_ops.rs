@@ -0,0 +1,85 @@
+//! Cancellation of backend operations in flight
+//!
+//! Adding, pausing, resuming or cancelling a download waits on aria2 and, for
+//! new downloads, on probing the server, either of which can hang. Each of
+//! these operations is raced against the token from the download's options
+//! and against a manager-wide token that aborts everything at once.
+
+use crate::error::DownloadError;
+use crate::types::TaskId;
+use crate::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+/// Cancellation tokens of downloads and of the manager
+#[derive(Debug, Default)]
+pub struct InflightOps {
+    /// Replaced by a fresh token each time everything is aborted
+    abort: Mutex<CancellationToken>,
+    tokens: RwLock<HashMap<TaskId, CancellationToken>>,
+}
+
+impl InflightOps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the token a download was added with
+    pub async fn track(&self, task_id: TaskId, token: Option<CancellationToken>) {
+        if let Some(token) = token {
+            self.tokens.write().await.insert(task_id, token);
+        }
+    }
+
+    /// Get the token operations on a download are raced against
+    ///
+    /// A token cancelled before the operation starts aborted earlier
+    /// operations only and is forgotten, so the task can still be managed.
+    pub async fn token_for(&self, task_id: TaskId) -> Option<CancellationToken> {
+        let token = self.tokens.read().await.get(&task_id).cloned()?;
+        if token.is_cancelled() {
+            self.tokens.write().await.remove(&task_id);
+            return None;
+        }
+        Some(token)
+    }
+
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.tokens.write().await.remove(&task_id);
+    }
+
+    /// Abort all operations in flight, later operations are not affected
+    pub fn abort_all(&self) {
+        let mut abort = self.abort.lock().unwrap_or_else(|e| e.into_inner());
+        abort.cancel();
+        *abort = CancellationToken::new();
+    }
+
+    /// Run an operation until it finishes or is cancelled
+    ///
+    /// Fails with `DownloadError::Cancelled` when `token` or the manager-wide
+    /// token is cancelled first, dropping the operation.
+    pub async fn run<T>(
+        &self,
+        operation: &str,
+        token: Option<&CancellationToken>,
+        op: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let abort = self.abort.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let cancelled = async {
+            match token {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            result = op => result,
+            _ = abort.cancelled() => Err(DownloadError::Cancelled(format!("{} aborted", operation))),
+            _ = cancelled => Err(DownloadError::Cancelled(operation.to_string())),
+        }
+    }
+}
\ No newline at end of file
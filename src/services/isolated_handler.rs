@@ -0,0 +1,228 @@
+//! Fault isolation for event handlers
+//!
+//! Handlers are application code running inside the manager's loops. An
+//! [`IsolatedHandler`] runs each call of the handler it wraps in its own
+//! tokio task, where a panic unwinds no further than the task, and aborts
+//! calls that outlive the timeout. Failures are broadcast as
+//! [`HandlerError`]s; a handler can be removed after its first one.
+
+use crate::models::{FailureInfo, HandlerError, HandlerFailure, HandlerId, SpeedSample};
+use crate::services::handler_registry::{HandlerList, unlist};
+use crate::traits::DownloadEventHandler;
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use async_trait::async_trait;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+use tokio::sync::broadcast;
+
+/// Default time a handler call may take before it is aborted
+pub const DEFAULT_HANDLER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Event handler wrapper containing panics and hangs of the handler
+pub struct IsolatedHandler {
+    inner: Arc<dyn DownloadEventHandler>,
+    /// The handler reported in errors, `inner` unless it wraps the registered one
+    registered: Arc<dyn DownloadEventHandler>,
+    id: Option<HandlerId>,
+    timeout: Duration,
+    remove_on_error: bool,
+    removed: AtomicBool,
+    errors: broadcast::Sender<HandlerError>,
+    list: Option<Weak<HandlerList>>,
+}
+
+impl IsolatedHandler {
+    /// Wrap `inner`, aborting calls after `timeout` (zero for no limit)
+    ///
+    /// With `remove_on_error` the handler receives no further events after
+    /// its first failure.
+    pub fn new(
+        inner: Arc<dyn DownloadEventHandler>,
+        timeout: Duration,
+        remove_on_error: bool,
+        errors: broadcast::Sender<HandlerError>,
+    ) -> Self {
+        Self {
+            registered: inner.clone(),
+            inner,
+            id: None,
+            timeout,
+            remove_on_error,
+            removed: AtomicBool::new(false),
+            errors,
+            list: None,
+        }
+    }
+
+    /// Report failures as failures of `handler`, which `inner` wraps
+    pub fn registered_as(mut self, handler: Arc<dyn DownloadEventHandler>) -> Self {
+        self.registered = handler;
+        self
+    }
+
+    /// Report failures under the ID the handler was registered with
+    pub fn with_id(mut self, id: HandlerId) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Take the handler out of `list` when it is removed after a failure
+    pub fn listed_in(mut self, list: &Arc<HandlerList>) -> Self {
+        self.list = Some(Arc::downgrade(list));
+        self
+    }
+
+    /// Check if the handler was removed after a failure
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::SeqCst)
+    }
+
+    /// Run one call of the handler, reporting a panic or timeout
+    async fn call<F, Fut>(&self, event: &'static str, call: F)
+    where
+        F: FnOnce(Arc<dyn DownloadEventHandler>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.is_removed() {
+            return;
+        }
+
+        let mut handle = tokio::spawn(call(self.inner.clone()));
+        let joined = if self.timeout.is_zero() {
+            (&mut handle).await
+        } else {
+            match tokio::time::timeout(self.timeout, &mut handle).await {
+                Ok(joined) => joined,
+                Err(_) => {
+                    handle.abort();
+                    self.fail(event, HandlerFailure::TimedOut(self.timeout)).await;
+                    return;
+                }
+            }
+        };
+
+        if let Err(e) = joined {
+            if e.is_panic() {
+                self.fail(event, HandlerFailure::Panicked(panic_message(e.into_panic()))).await;
+            }
+        }
+    }
+
+    async fn fail(&self, event: &'static str, failure: HandlerFailure) {
+        let removed = self.remove_on_error && !self.removed.swap(true, Ordering::SeqCst);
+        log::warn!("Event handler {} {}{}", event, failure, if removed { ", handler removed" } else { "" });
+
+        if let Some(list) = removed.then(|| self.list.as_ref().and_then(Weak::upgrade)).flatten() {
+            unlist(&list, self as *const Self as *const ()).await;
+        }
+
+        // Nobody listening is fine, the failure was logged
+        let _ = self.errors.send(HandlerError {
+            handler: self.registered.clone(),
+            handler_id: self.id,
+            event,
+            failure,
+            removed,
+        });
+    }
+}
+
+/// Get the message of a panic payload
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_else(|| "unknown panic".to_string()),
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for IsolatedHandler {
+    async fn on_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        self.call("on_status_changed", |handler| async move {
+            handler.on_status_changed(task_id, old_status, new_status).await
+        }).await;
+    }
+
+    async fn on_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
+        self.call("on_progress_updated", |handler| async move {
+            handler.on_progress_updated(task_id, progress).await
+        }).await;
+    }
+
+    async fn on_download_completed(&self, task_id: TaskId) {
+        self.call("on_download_completed", |handler| async move {
+            handler.on_download_completed(task_id).await
+        }).await;
+    }
+
+    async fn on_download_failed(&self, task_id: TaskId, error: String) {
+        self.call("on_download_failed", |handler| async move {
+            handler.on_download_failed(task_id, error).await
+        }).await;
+    }
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        self.call("on_retry_scheduled", |handler| async move {
+            handler.on_retry_scheduled(task_id, attempt, delay).await
+        }).await;
+    }
+
+    async fn on_download_restored(&self, task_id: TaskId, resumed_from: u64) {
+        self.call("on_download_restored", |handler| async move {
+            handler.on_download_restored(task_id, resumed_from).await
+        }).await;
+    }
+
+    async fn on_source_changed(&self, task_id: TaskId, restarted_as: TaskId) {
+        self.call("on_source_changed", |handler| async move {
+            handler.on_source_changed(task_id, restarted_as).await
+        }).await;
+    }
+
+    async fn on_extraction_progress(&self, task_id: TaskId, extracted_bytes: u64, total_bytes: Option<u64>) {
+        self.call("on_extraction_progress", |handler| async move {
+            handler.on_extraction_progress(task_id, extracted_bytes, total_bytes).await
+        }).await;
+    }
+
+    async fn on_copy_progress(&self, task_id: TaskId, destination: PathBuf, copied_bytes: u64, total_bytes: u64) {
+        self.call("on_copy_progress", |handler| async move {
+            handler.on_copy_progress(task_id, destination, copied_bytes, total_bytes).await
+        }).await;
+    }
+
+    async fn on_post_processed(&self, task_id: TaskId, path: PathBuf) {
+        self.call("on_post_processed", |handler| async move {
+            handler.on_post_processed(task_id, path).await
+        }).await;
+    }
+
+    async fn on_post_processing_failed(&self, task_id: TaskId, hook: String, error: String) {
+        self.call("on_post_processing_failed", |handler| async move {
+            handler.on_post_processing_failed(task_id, hook, error).await
+        }).await;
+    }
+
+    async fn on_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        self.call("on_task_expired", |handler| async move {
+            handler.on_task_expired(task_id, expires_at).await
+        }).await;
+    }
+
+    async fn on_failure_recorded(&self, task_id: TaskId, failure: FailureInfo) {
+        self.call("on_failure_recorded", |handler| async move {
+            handler.on_failure_recorded(task_id, failure).await
+        }).await;
+    }
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        self.call("on_speed_sample", |handler| async move {
+            handler.on_speed_sample(sample).await
+        }).await;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,109 @@
+//! Single-callback event listeners
+//!
+//! A [`ListenerHandler`] turns every [`DownloadEventHandler`] call into a
+//! [`DownloadEvent`] and hands it to one [`DownloadEventListener`], so
+//! observers that forward events elsewhere implement a single method.
+//! Channel senders are listeners themselves and can be registered as is.
+
+use crate::models::{DownloadEvent, FailureInfo, SpeedSample};
+use crate::traits::{DownloadEventHandler, DownloadEventListener};
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{broadcast, mpsc};
+
+/// Event handler wrapper delivering every notification to a listener
+pub struct ListenerHandler {
+    inner: Arc<dyn DownloadEventListener>,
+}
+
+impl ListenerHandler {
+    pub fn new(inner: Arc<dyn DownloadEventListener>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for ListenerHandler {
+    async fn on_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        self.inner.on_event(DownloadEvent::StatusChanged { task_id, old_status, new_status }).await;
+    }
+
+    async fn on_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
+        self.inner.on_event(DownloadEvent::ProgressUpdated { task_id, progress }).await;
+    }
+
+    async fn on_download_completed(&self, task_id: TaskId) {
+        self.inner.on_event(DownloadEvent::Completed { task_id }).await;
+    }
+
+    async fn on_download_failed(&self, task_id: TaskId, error: String) {
+        self.inner.on_event(DownloadEvent::Failed { task_id, error }).await;
+    }
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        self.inner.on_event(DownloadEvent::RetryScheduled { task_id, attempt, delay }).await;
+    }
+
+    async fn on_download_restored(&self, task_id: TaskId, resumed_from: u64) {
+        self.inner.on_event(DownloadEvent::Restored { task_id, resumed_from }).await;
+    }
+
+    async fn on_source_changed(&self, task_id: TaskId, restarted_as: TaskId) {
+        self.inner.on_event(DownloadEvent::SourceChanged { task_id, restarted_as }).await;
+    }
+
+    async fn on_extraction_progress(&self, task_id: TaskId, extracted_bytes: u64, total_bytes: Option<u64>) {
+        self.inner.on_event(DownloadEvent::ExtractionProgress { task_id, extracted_bytes, total_bytes }).await;
+    }
+
+    async fn on_copy_progress(&self, task_id: TaskId, destination: PathBuf, copied_bytes: u64, total_bytes: u64) {
+        self.inner.on_event(DownloadEvent::CopyProgress { task_id, destination, copied_bytes, total_bytes }).await;
+    }
+
+    async fn on_post_processed(&self, task_id: TaskId, path: PathBuf) {
+        self.inner.on_event(DownloadEvent::PostProcessed { task_id, path }).await;
+    }
+
+    async fn on_post_processing_failed(&self, task_id: TaskId, hook: String, error: String) {
+        self.inner.on_event(DownloadEvent::PostProcessingFailed { task_id, hook, error }).await;
+    }
+
+    async fn on_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        self.inner.on_event(DownloadEvent::Expired { task_id, expires_at }).await;
+    }
+
+    async fn on_failure_recorded(&self, task_id: TaskId, failure: FailureInfo) {
+        self.inner.on_event(DownloadEvent::FailureRecorded { task_id, failure }).await;
+    }
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        self.inner.on_event(DownloadEvent::SpeedSample { sample }).await;
+    }
+}
+
+/// Events are dropped while nobody is subscribed
+#[async_trait]
+impl DownloadEventListener for broadcast::Sender<DownloadEvent> {
+    async fn on_event(&self, event: DownloadEvent) {
+        let _ = self.send(event);
+    }
+}
+
+/// Waits for room in the channel, events are dropped once the receiver is gone
+#[async_trait]
+impl DownloadEventListener for mpsc::Sender<DownloadEvent> {
+    async fn on_event(&self, event: DownloadEvent) {
+        let _ = self.send(event).await;
+    }
+}
+
+/// Events are dropped once the receiver is gone
+#[async_trait]
+impl DownloadEventListener for mpsc::UnboundedSender<DownloadEvent> {
+    async fn on_event(&self, event: DownloadEvent) {
+        let _ = self.send(event);
+    }
+}
\ No newline at end of file
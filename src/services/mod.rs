@@ -1,14 +1,76 @@
-//! Services for duplicate detection
+//! Services for duplicate detection and transfer control
 //!
-//! This module contains the core services that implement duplicate detection
-//! logic and coordinate with the download manager.
+//! This module contains the core services that implement duplicate detection,
+//! bandwidth limiting, retry and status tracking, metadata persistence, state
+//! journaling, field encryption, speed smoothing, progress history, completion waiting, stall
+//! tracking, event distribution, cancellation, caching and per-host usage accounting, handler isolation and registration, reading running downloads, piece verification, disk-full pausing, admission control, and coordinate with the download manager.
 
 pub mod duplicate_detector;
+pub mod duplicate_resolver;
 pub mod task_repository;
+pub mod field_cipher;
+#[cfg(feature = "postgres")]
+pub mod postgres_repository;
 pub mod hash_calculator;
 pub mod task_validation;
+pub mod bandwidth_limiter;
+pub mod retry_tracker;
+pub mod task_metadata_store;
+pub mod event_bus;
+pub mod partial_download;
+pub mod target_path_registry;
+pub mod status_tracker;
+pub mod task_journal;
+pub mod speed_smoother;
+pub mod progress_history;
+pub mod completion_waiters;
+pub mod stall_tracker;
+pub mod size_guard;
+pub mod deadline_tracker;
+pub mod throttled_handler;
+pub mod inflight_ops;
+pub mod task_cache;
+pub mod domain_usage;
+pub mod isolated_handler;
+pub mod handler_registry;
+pub mod weak_handler;
+pub mod listener_handler;
+pub mod download_stream;
+pub mod piece_verifier;
+pub mod no_space_watch;
+pub mod admission_control;
 
-pub use duplicate_detector::DuplicateDetector;
-pub use task_repository::TaskRepository;
+pub use duplicate_detector::{DuplicateDetector, InMemoryDuplicateDetector, SqliteDuplicateDetector, IndexedTask};
+pub use duplicate_resolver::DuplicateResolver;
+pub use task_repository::{TaskRepository, InMemoryTaskRepository, SqliteTaskRepository, EncryptedTaskRepository};
+pub use field_cipher::FieldCipher;
+#[cfg(feature = "postgres")]
+pub use postgres_repository::PostgresTaskRepository;
 pub use hash_calculator::BackgroundHashCalculator;
-pub use task_validation::TaskValidation;
\ No newline at end of file
+pub use task_validation::TaskValidation;
+pub use bandwidth_limiter::BandwidthLimiter;
+pub use retry_tracker::RetryTracker;
+pub use task_metadata_store::TaskMetadataStore;
+pub use event_bus::EventBus;
+pub use partial_download::PartialDownload;
+pub use target_path_registry::TargetPathRegistry;
+pub use status_tracker::StatusTracker;
+pub use task_journal::{TaskJournal, JournaledState, JournalEntry};
+pub use speed_smoother::SpeedSmoother;
+pub use progress_history::ProgressHistory;
+pub use completion_waiters::{CompletionWaiters, CompletionReceiver, TaskOutcome};
+pub use stall_tracker::StallTracker;
+pub use size_guard::SizeGuard;
+pub use piece_verifier::PieceVerifier;
+pub use no_space_watch::{NoSpaceWatch, DEFAULT_NO_SPACE_HEADROOM};
+pub use admission_control::{AdmissionControl, AdmissionPermit, AdmissionRefusal};
+pub use deadline_tracker::DeadlineTracker;
+pub use throttled_handler::ThrottledHandler;
+pub use inflight_ops::InflightOps;
+pub use task_cache::TaskCache;
+pub use domain_usage::DomainUsageTracker;
+pub use isolated_handler::IsolatedHandler;
+pub use handler_registry::HandlerRegistry;
+pub use weak_handler::WeakHandler;
+pub use listener_handler::ListenerHandler;
+pub use download_stream::DownloadStream;
\ No newline at end of file
@@ -0,0 +1,81 @@
+//! Downloads paused by a full disk
+//!
+//! A download that fails because its disk ran out of space is paused
+//! instead, and resumed by the persistence poller once the disk has the
+//! configured headroom free again, e.g. after other files were cleaned up.
+
+use crate::storage::available_space;
+use crate::types::TaskId;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+
+/// Free space downloads wait for by default, 1 GiB
+pub const DEFAULT_NO_SPACE_HEADROOM: u64 = 1024 * 1024 * 1024;
+
+/// Downloads waiting for free space, with the file each one writes to
+#[derive(Debug)]
+pub struct NoSpaceWatch {
+    /// Free bytes a disk needs before its downloads are resumed
+    headroom: AtomicU64,
+    tasks: RwLock<HashMap<TaskId, PathBuf>>,
+}
+
+impl Default for NoSpaceWatch {
+    fn default() -> Self {
+        Self::new(DEFAULT_NO_SPACE_HEADROOM)
+    }
+}
+
+impl NoSpaceWatch {
+    pub fn new(headroom: u64) -> Self {
+        Self {
+            headroom: AtomicU64::new(headroom),
+            tasks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the free space downloads wait for
+    pub fn headroom(&self) -> u64 {
+        self.headroom.load(Ordering::Relaxed)
+    }
+
+    /// Change the free space downloads wait for
+    pub fn set_headroom(&self, bytes: u64) {
+        self.headroom.store(bytes, Ordering::Relaxed);
+    }
+
+    /// Wait for free space on the disk holding `path` before resuming a task
+    pub async fn pause(&self, task_id: TaskId, path: PathBuf) {
+        self.tasks.write().await.insert(task_id, path);
+    }
+
+    /// Check if a task waits for free space
+    pub async fn is_paused(&self, task_id: TaskId) -> bool {
+        self.tasks.read().await.contains_key(&task_id)
+    }
+
+    /// Check if any task waits, so polls without them cost nothing
+    pub async fn is_empty(&self) -> bool {
+        self.tasks.read().await.is_empty()
+    }
+
+    /// Get the tasks whose disk has the headroom free again
+    ///
+    /// The tasks keep waiting until they are removed, so a task that fails
+    /// to resume is tried again later. Tasks whose free space can't be read
+    /// keep waiting as well.
+    pub async fn ready(&self) -> Vec<TaskId> {
+        let headroom = self.headroom();
+        self.tasks.read().await.iter()
+            .filter(|(_, path)| available_space(path).is_ok_and(|available| available >= headroom))
+            .map(|(task_id, _)| *task_id)
+            .collect()
+    }
+
+    /// Stop waiting for a task, once it was resumed otherwise or removed
+    pub async fn remove_task(&self, task_id: TaskId) -> bool {
+        self.tasks.write().await.remove(&task_id).is_some()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,166 @@
+//! Partial download detection
+//!
+//! Inspects what an interrupted download left on disk so a restored task can
+//! continue from its partial file instead of starting over. aria2 keeps its
+//! progress in a `<file>.aria2` control file next to the download.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension aria2 appends to the output file name for its control file
+pub const CONTROL_FILE_EXTENSION: &str = "aria2";
+
+/// Progress recorded in an aria2 control file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControlFile {
+    pub piece_length: u32,
+    pub total_length: u64,
+    /// Bytes in pieces marked complete
+    pub completed_length: u64,
+}
+
+/// State of the control file belonging to a partial download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlState {
+    Missing,
+    Unreadable,
+    Valid(ControlFile),
+}
+
+/// A partial file left by an interrupted download
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialDownload {
+    pub path: PathBuf,
+    pub bytes_on_disk: u64,
+    pub control: ControlState,
+}
+
+impl PartialDownload {
+    /// Look for a partial file at `target_path`
+    ///
+    /// Returns `None` when there is nothing to continue from.
+    pub async fn inspect(target_path: &Path) -> Option<Self> {
+        let bytes_on_disk = tokio::fs::metadata(target_path).await
+            .ok()
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())?;
+
+        let control = match tokio::fs::read(control_file_path(target_path)).await {
+            Ok(bytes) => parse_control_file(&bytes)
+                .map(ControlState::Valid)
+                .unwrap_or(ControlState::Unreadable),
+            Err(_) => ControlState::Missing,
+        };
+
+        if bytes_on_disk == 0 && control == ControlState::Missing {
+            return None;
+        }
+
+        Some(Self {
+            path: target_path.to_path_buf(),
+            bytes_on_disk,
+            control,
+        })
+    }
+
+    /// Check that the partial file can be continued and get the byte offset it resumes from
+    pub fn verify(&self) -> Result<u64, String> {
+        match &self.control {
+            // Without a control file aria2 continues from the end of the file
+            ControlState::Missing => Ok(self.bytes_on_disk),
+            ControlState::Unreadable => Err("control file is unreadable".to_string()),
+            ControlState::Valid(control) if self.bytes_on_disk > control.total_length => {
+                Err(format!(
+                    "partial file has {} bytes but the download is only {} bytes",
+                    self.bytes_on_disk, control.total_length
+                ))
+            }
+            ControlState::Valid(control) => Ok(control.completed_length),
+        }
+    }
+
+    /// Delete the partial file and its control file
+    pub async fn discard(&self) -> io::Result<()> {
+        for path in [self.path.clone(), control_file_path(&self.path)] {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Get the path of the aria2 control file for a download
+pub fn control_file_path(target_path: &Path) -> PathBuf {
+    let mut path = target_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(CONTROL_FILE_EXTENSION);
+    PathBuf::from(path)
+}
+
+/// Parse an aria2 control file (format version 1, big-endian)
+pub fn parse_control_file(bytes: &[u8]) -> Option<ControlFile> {
+    let mut reader = ByteReader { bytes, offset: 0 };
+
+    if reader.u16()? != 1 {
+        return None;
+    }
+    let _extension = reader.take(4)?;
+    let info_hash_length = reader.u32()? as usize;
+    let _info_hash = reader.take(info_hash_length)?;
+    let piece_length = reader.u32()?;
+    let total_length = reader.u64()?;
+    let _upload_length = reader.u64()?;
+    let bitfield_length = reader.u32()? as usize;
+    let bitfield = reader.take(bitfield_length)?;
+
+    if piece_length == 0 {
+        return None;
+    }
+
+    let piece_count = total_length.div_ceil(piece_length as u64);
+    if (bitfield_length as u64) < piece_count.div_ceil(8) {
+        return None;
+    }
+
+    let completed_length = (0..piece_count)
+        .filter(|piece| bitfield[(piece / 8) as usize] & (0x80 >> (piece % 8)) != 0)
+        .map(|piece| {
+            let start = piece * piece_length as u64;
+            (total_length - start).min(piece_length as u64)
+        })
+        .sum();
+
+    Some(ControlFile {
+        piece_length,
+        total_length,
+        completed_length,
+    })
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.bytes.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(slice)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.take(2)?.try_into().ok()?))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.take(8)?.try_into().ok()?))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,267 @@
+//! Piecewise checksum verification
+//!
+//! Downloads with piece checksums have their file hashed in fixed-size
+//! pieces. Single-segment downloads write the file front to back, so their
+//! pieces are checked as soon as the engine reports them downloaded and a
+//! corrupted transfer fails after the first bad piece. Other downloads fill
+//! the file in any order and are checked, along with the last pieces of the
+//! first kind, once they complete.
+
+use crate::error::DownloadError;
+use crate::models::{ChecksumAlgorithm, PieceChecksums};
+use crate::types::TaskId;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+/// Bytes read at a time
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Pieces of one download
+#[derive(Debug)]
+struct PieceState {
+    /// File the download writes to
+    path: PathBuf,
+    checksums: PieceChecksums,
+    /// Whether pieces are checked while the download runs
+    progressive: bool,
+    /// Digests of the pieces checked so far, in file order
+    computed: Vec<String>,
+}
+
+/// Piece checksums of the downloads that have them
+#[derive(Debug, Default)]
+pub struct PieceVerifier {
+    tasks: RwLock<HashMap<TaskId, PieceState>>,
+}
+
+impl PieceVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify the pieces of a task written to `path`
+    ///
+    /// With `progressive` the pieces are checked on every
+    /// [`observe`](Self::observe), the file must then be written in order.
+    pub async fn track(&self, task_id: TaskId, path: PathBuf, checksums: PieceChecksums, progressive: bool) {
+        self.tasks.write().await.insert(task_id, PieceState {
+            path,
+            checksums,
+            progressive,
+            computed: Vec::new(),
+        });
+    }
+
+    /// Check if a task's pieces are checked while it downloads
+    pub async fn is_progressive(&self, task_id: TaskId) -> bool {
+        self.tasks.read().await.get(&task_id).is_some_and(|state| state.progressive)
+    }
+
+    /// Get the digests of the pieces of a task checked so far
+    pub async fn digests(&self, task_id: TaskId) -> Option<Vec<String>> {
+        self.tasks.read().await.get(&task_id).map(|state| state.computed.clone())
+    }
+
+    /// Check the pieces of a progressively checked task that downloaded in full
+    ///
+    /// A download that started over has its pieces checked again.
+    pub async fn observe(&self, task_id: TaskId, downloaded_bytes: u64) -> Result<(), DownloadError> {
+        let Some((path, start, piece_size)) = self.next_pieces(task_id, true).await else {
+            return Ok(());
+        };
+        if downloaded_bytes < start as u64 * piece_size {
+            if let Some(state) = self.tasks.write().await.get_mut(&task_id) {
+                state.computed.clear();
+            }
+            return Ok(());
+        }
+        let pieces = (downloaded_bytes / piece_size) as usize - start;
+        if pieces == 0 {
+            return Ok(());
+        }
+        self.check(task_id, path, start, Some(pieces)).await
+    }
+
+    /// Check the pieces of a completed task not checked yet, reading `path` to its end
+    ///
+    /// Fails if the file has more or fewer pieces than there are digests.
+    pub async fn finish(&self, task_id: TaskId, path: &Path) -> Result<(), DownloadError> {
+        let Some((_, start, _)) = self.next_pieces(task_id, false).await else {
+            return Ok(());
+        };
+        self.check(task_id, path.to_path_buf(), start, None).await?;
+
+        let tasks = self.tasks.read().await;
+        let Some(state) = tasks.get(&task_id) else {
+            return Ok(());
+        };
+        let expected = state.checksums.digests.len();
+        if expected > 0 && state.computed.len() != expected {
+            return Err(DownloadError::VerificationError(format!(
+                "file has {} pieces, expected {}", state.computed.len(), expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// Forget a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.tasks.write().await.remove(&task_id);
+    }
+
+    /// Get the file, first unchecked piece and piece size of a task
+    async fn next_pieces(&self, task_id: TaskId, progressive_only: bool) -> Option<(PathBuf, usize, u64)> {
+        let tasks = self.tasks.read().await;
+        let state = tasks.get(&task_id).filter(|state| state.progressive || !progressive_only)?;
+        Some((state.path.clone(), state.computed.len(), state.checksums.piece_size.max(1)))
+    }
+
+    /// Hash `count` pieces starting at piece `start`, or all up to the end of
+    /// the file, and compare them with the expected digests
+    async fn check(&self, task_id: TaskId, path: PathBuf, start: usize, count: Option<usize>) -> Result<(), DownloadError> {
+        let Some((algorithm, piece_size)) = self.tasks.read().await
+            .get(&task_id)
+            .map(|state| (state.checksums.algorithm, state.checksums.piece_size.max(1)))
+        else {
+            return Ok(());
+        };
+
+        let digests = tokio::task::spawn_blocking(move || hash_pieces(&path, algorithm, piece_size, start, count))
+            .await
+            .map_err(|e| DownloadError::General(format!("Piece hashing failed: {}", e)))??;
+
+        let mut tasks = self.tasks.write().await;
+        let Some(state) = tasks.get_mut(&task_id) else {
+            return Ok(());
+        };
+        // Another check got there first
+        if state.computed.len() != start {
+            return Ok(());
+        }
+        for (index, actual) in digests.into_iter().enumerate().map(|(i, digest)| (start + i, digest)) {
+            if !state.checksums.digests.is_empty() {
+                match state.checksums.digests.get(index) {
+                    Some(expected) if *expected != actual => {
+                        return Err(DownloadError::PieceChecksumMismatch {
+                            piece: index,
+                            expected: expected.clone(),
+                            actual,
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        return Err(DownloadError::VerificationError(format!(
+                            "file has more than the {} expected pieces", state.checksums.digests.len()
+                        )));
+                    }
+                }
+            }
+            state.computed.push(actual);
+        }
+        Ok(())
+    }
+}
+
+/// Hash function of a piece
+enum PieceHasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl PieceHasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => PieceHasher::Md5(Md5::new()),
+            ChecksumAlgorithm::Sha1 => PieceHasher::Sha1(Sha1::new()),
+            ChecksumAlgorithm::Sha256 => PieceHasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Sha512 => PieceHasher::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            PieceHasher::Md5(hasher) => hasher.update(data),
+            PieceHasher::Sha1(hasher) => hasher.update(data),
+            PieceHasher::Sha256(hasher) => hasher.update(data),
+            PieceHasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        let digest = match self {
+            PieceHasher::Md5(hasher) => hasher.finalize().to_vec(),
+            PieceHasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            PieceHasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            PieceHasher::Sha512(hasher) => hasher.finalize().to_vec(),
+        };
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+/// Hash the pieces of a file as lowercase hex digests
+///
+/// Reads up to `count` whole pieces from piece `start`, or every piece up to
+/// the end of the file with the last one possibly shorter.
+fn hash_pieces(
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    piece_size: u64,
+    start: usize,
+    count: Option<usize>,
+) -> io::Result<Vec<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        // Nothing written yet
+        Err(e) if e.kind() == io::ErrorKind::NotFound && count.is_some() => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(start as u64 * piece_size))?;
+
+    let mut digests = Vec::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    while count.map_or(true, |count| digests.len() < count) {
+        let mut hasher = PieceHasher::new(algorithm);
+        let mut remaining = piece_size;
+        while remaining > 0 {
+            let wanted = remaining.min(buffer.len() as u64) as usize;
+            let read = file.read(&mut buffer[..wanted])?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            remaining -= read as u64;
+        }
+        // Pieces still being written are checked on a later call
+        if remaining == piece_size || (remaining > 0 && count.is_some()) {
+            break;
+        }
+        digests.push(hasher.finish());
+        if remaining > 0 {
+            break;
+        }
+    }
+    Ok(digests)
+}
+
+/// Hash a whole file as a lowercase hex digest
+pub(crate) fn hash_file(path: &Path, algorithm: ChecksumAlgorithm) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = PieceHasher::new(algorithm);
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+//! Progress history
+//!
+//! Keeps the most recent progress samples of each task in a ring buffer so
+//! UIs can draw transfer graphs. The history can also be written to the
+//! `progress_history` table of the crate-owned SQLite database, in which case
+//! it is loaded again after a restart.
+
+use crate::types::{TaskId, DownloadProgress};
+use crate::error::DownloadError;
+use crate::models::ProgressSample;
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, encode_task_id, decode_value, db_error};
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// Default number of samples kept per task, an hour at the default progress save interval
+pub const DEFAULT_HISTORY_CAPACITY: usize = 720;
+
+/// Recent progress samples of each task
+pub struct ProgressHistory {
+    capacity: usize,
+    samples: RwLock<HashMap<TaskId, VecDeque<ProgressSample>>>,
+    pool: Option<SqlitePool>,
+}
+
+impl ProgressHistory {
+    /// Keep up to `capacity` samples per task in memory only
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: RwLock::new(HashMap::new()),
+            pool: None,
+        }
+    }
+
+    /// Keep up to `capacity` samples per task and persist them in the given SQLite file
+    ///
+    /// Samples saved by an earlier run are loaded.
+    pub async fn open(path: &Path, capacity: usize) -> Result<Self, DownloadError> {
+        Self::with_pool(open_pool(path).await?, capacity).await
+    }
+
+    /// Keep samples in a SQLite database that lives only in memory
+    pub async fn in_memory(capacity: usize) -> Result<Self, DownloadError> {
+        Self::with_pool(in_memory_pool().await?, capacity).await
+    }
+
+    pub(crate) async fn with_pool(pool: SqlitePool, capacity: usize) -> Result<Self, DownloadError> {
+        let rows = sqlx::query("SELECT task_id, recorded_at, downloaded_bytes, speed_bps FROM progress_history ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut samples: HashMap<TaskId, VecDeque<ProgressSample>> = HashMap::new();
+        for row in rows {
+            let task_id: TaskId = decode_value(row.get::<String, _>("task_id"))?;
+            let sample = ProgressSample {
+                recorded_at: UNIX_EPOCH + Duration::from_millis(row.get::<i64, _>("recorded_at") as u64),
+                downloaded_bytes: row.get::<i64, _>("downloaded_bytes") as u64,
+                speed_bps: row.get::<i64, _>("speed_bps") as u64,
+            };
+            push_sample(samples.entry(task_id).or_default(), sample, capacity);
+        }
+
+        Ok(Self {
+            capacity,
+            samples: RwLock::new(samples),
+            pool: Some(pool),
+        })
+    }
+
+    /// Get the number of samples kept per task
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Record the current progress of a task
+    pub async fn record(&self, task_id: TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.record_sample(task_id, ProgressSample::new(SystemTime::now(), progress)).await
+    }
+
+    /// Record a sample, dropping the oldest one of the task when the buffer is full
+    pub async fn record_sample(&self, task_id: TaskId, sample: ProgressSample) -> Result<(), DownloadError> {
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        push_sample(self.samples.write().await.entry(task_id).or_default(), sample, self.capacity);
+
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+
+        let task_id = encode_task_id(&task_id)?;
+        sqlx::query("INSERT INTO progress_history (task_id, recorded_at, downloaded_bytes, speed_bps) VALUES (?, ?, ?, ?)")
+            .bind(&task_id)
+            .bind(unix_millis(sample.recorded_at))
+            .bind(sample.downloaded_bytes as i64)
+            .bind(sample.speed_bps as i64)
+            .execute(pool)
+            .await
+            .map_err(db_error)?;
+
+        // Trim the table to the ring buffer
+        sqlx::query(
+            "DELETE FROM progress_history WHERE task_id = ?1 AND id NOT IN (
+                SELECT id FROM progress_history WHERE task_id = ?1 ORDER BY id DESC LIMIT ?2
+            )"
+        )
+        .bind(&task_id)
+        .bind(self.capacity as i64)
+        .execute(pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Get the samples of a task taken at or after `since`, oldest first
+    ///
+    /// `None` returns every sample kept.
+    pub async fn samples(&self, task_id: TaskId, since: Option<SystemTime>) -> Vec<ProgressSample> {
+        self.samples.read().await.get(&task_id)
+            .map(|samples| samples.iter()
+                .filter(|sample| !since.is_some_and(|since| sample.recorded_at < since))
+                .copied()
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Forget the history of a task
+    pub async fn remove_task(&self, task_id: TaskId) -> Result<(), DownloadError> {
+        self.samples.write().await.remove(&task_id);
+
+        if let Some(pool) = &self.pool {
+            sqlx::query("DELETE FROM progress_history WHERE task_id = ?")
+                .bind(encode_task_id(&task_id)?)
+                .execute(pool)
+                .await
+                .map_err(db_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn push_sample(samples: &mut VecDeque<ProgressSample>, sample: ProgressSample, capacity: usize) {
+    if capacity == 0 {
+        return;
+    }
+    while samples.len() >= capacity {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
\ No newline at end of file
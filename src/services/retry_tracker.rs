@@ -0,0 +1,99 @@
+//! Retry tracking service
+//!
+//! Keeps per-task retry policies and attempt counts and decides when a
+//! failed task should be rescheduled.
+
+use crate::types::TaskId;
+use crate::models::RetryPolicy;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Tracks retry state for failed downloads
+#[derive(Debug, Default)]
+pub struct RetryTracker {
+    /// Policy for tasks without their own policy
+    default_policy: RwLock<RetryPolicy>,
+    /// Per-task policy overrides
+    task_policies: RwLock<HashMap<TaskId, RetryPolicy>>,
+    /// Retries already used per task
+    attempts: RwLock<HashMap<TaskId, u32>>,
+    /// Tasks with a retry scheduled but not yet started
+    pending: RwLock<HashSet<TaskId>>,
+}
+
+impl RetryTracker {
+    pub fn new(default_policy: RetryPolicy) -> Self {
+        Self {
+            default_policy: RwLock::new(default_policy),
+            ..Self::default()
+        }
+    }
+
+    /// Set the policy used for tasks without their own policy
+    pub async fn set_default_policy(&self, policy: RetryPolicy) {
+        *self.default_policy.write().await = policy;
+    }
+
+    /// Set the policy for a single task
+    pub async fn set_task_policy(&self, task_id: TaskId, policy: RetryPolicy) {
+        self.task_policies.write().await.insert(task_id, policy);
+    }
+
+    /// Get the policy that applies to a task
+    pub async fn policy_for(&self, task_id: TaskId) -> RetryPolicy {
+        match self.task_policies.read().await.get(&task_id) {
+            Some(policy) => policy.clone(),
+            None => self.default_policy.read().await.clone(),
+        }
+    }
+
+    /// Get the number of retries already used by a task
+    pub async fn attempts(&self, task_id: TaskId) -> u32 {
+        self.attempts.read().await.get(&task_id).copied().unwrap_or(0)
+    }
+
+    /// Restore the number of retries used by a task (e.g. from the database)
+    pub async fn set_attempts(&self, task_id: TaskId, attempts: u32) {
+        self.attempts.write().await.insert(task_id, attempts);
+    }
+
+    /// Record a failure and decide whether to retry
+    ///
+    /// Returns the attempt number and the delay before it, or `None` if the
+    /// policy does not allow another attempt or a retry is already pending.
+    pub async fn record_failure(&self, task_id: TaskId, error: &str) -> Option<(u32, Duration)> {
+        if self.pending.read().await.contains(&task_id) {
+            return None;
+        }
+
+        let policy = self.policy_for(task_id).await;
+        let used = self.attempts(task_id).await;
+        if !policy.should_retry(used, error) {
+            return None;
+        }
+
+        let attempt = used + 1;
+        self.attempts.write().await.insert(task_id, attempt);
+        self.pending.write().await.insert(task_id);
+
+        Some((attempt, policy.delay_for(attempt)))
+    }
+
+    /// Check if a retry of the task is scheduled but not started yet
+    pub async fn is_pending(&self, task_id: TaskId) -> bool {
+        self.pending.read().await.contains(&task_id)
+    }
+
+    /// Mark a scheduled retry as started
+    pub async fn clear_pending(&self, task_id: TaskId) {
+        self.pending.write().await.remove(&task_id);
+    }
+
+    /// Forget all retry state of a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.task_policies.write().await.remove(&task_id);
+        self.attempts.write().await.remove(&task_id);
+        self.pending.write().await.remove(&task_id);
+    }
+}
@@ -0,0 +1,69 @@
+//! Maximum file size enforcement
+//!
+//! Downloads are checked against their size limit before they start, from
+//! the size a probe reports, and again once the engine learns the total
+//! size, for servers that only tell it in the response to the transfer.
+
+use crate::error::DownloadError;
+use crate::types::TaskId;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Size limits of the downloads whose total size is still unknown
+#[derive(Debug, Default)]
+pub struct SizeGuard {
+    /// Limit of downloads without their own, `None` for no limit
+    default_limit: Option<u64>,
+    limits: RwLock<HashMap<TaskId, u64>>,
+}
+
+impl SizeGuard {
+    pub fn new(default_limit: Option<u64>) -> Self {
+        Self {
+            default_limit,
+            limits: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the limit of a download with `limit` in its options
+    pub fn limit_for(&self, limit: Option<u64>) -> Option<u64> {
+        limit.or(self.default_limit)
+    }
+
+    /// Check a size against a limit
+    pub fn check(size: u64, limit: Option<u64>) -> Result<(), DownloadError> {
+        match limit {
+            Some(limit) if size > limit => Err(DownloadError::FileTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Watch a task until its total size is known, if it has a limit
+    pub async fn track(&self, task_id: TaskId, limit: Option<u64>) {
+        if let Some(limit) = self.limit_for(limit) {
+            self.limits.write().await.insert(task_id, limit);
+        }
+    }
+
+    /// Check if a task still waits for its total size to be checked
+    pub async fn is_tracked(&self, task_id: TaskId) -> bool {
+        self.limits.read().await.contains_key(&task_id)
+    }
+
+    /// Check the total size the engine reported for a task
+    ///
+    /// The task stops being watched once its size is known, whether it
+    /// fits or not.
+    pub async fn observe(&self, task_id: TaskId, total_bytes: Option<u64>) -> Result<(), DownloadError> {
+        let Some(size) = total_bytes else {
+            return Ok(());
+        };
+        let limit = self.limits.write().await.remove(&task_id);
+        Self::check(size, limit)
+    }
+
+    /// Forget a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.limits.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
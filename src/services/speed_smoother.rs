@@ -0,0 +1,89 @@
+//! Speed and ETA smoothing
+//!
+//! Backends report the speed of roughly the last second, which swings with
+//! the network. The smoother keeps an exponential moving average of each
+//! task's speed, weighted by the time between samples so irregular polling
+//! does not skew it, and derives a stable ETA from the average.
+
+use crate::models::SmoothedProgress;
+use crate::types::{DownloadProgress, TaskId};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Default time over which speed samples are averaged
+pub const DEFAULT_SMOOTHING_WINDOW: Duration = Duration::from_secs(5);
+
+/// Average speed of a task and when it was last updated
+#[derive(Debug, Clone, Copy)]
+struct Average {
+    speed_bps: f64,
+    sampled_at: Instant,
+}
+
+/// Moving average of the speed of each task
+#[derive(Debug)]
+pub struct SpeedSmoother {
+    window: Duration,
+    averages: RwLock<HashMap<TaskId, Average>>,
+}
+
+impl Default for SpeedSmoother {
+    fn default() -> Self {
+        Self::new(DEFAULT_SMOOTHING_WINDOW)
+    }
+}
+
+impl SpeedSmoother {
+    /// Average speed samples over `window`
+    ///
+    /// A sample taken one window after the previous one weighs about 63% of
+    /// the new average. A zero window disables smoothing.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            averages: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Get the averaging window
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Add a progress sample of a task and get its smoothed progress
+    pub async fn smooth(&self, task_id: TaskId, progress: DownloadProgress) -> SmoothedProgress {
+        self.smooth_at(task_id, progress, Instant::now()).await
+    }
+
+    /// Add a progress sample taken at `sampled_at`, see [`smooth`](Self::smooth)
+    pub async fn smooth_at(&self, task_id: TaskId, progress: DownloadProgress, sampled_at: Instant) -> SmoothedProgress {
+        let sample = progress.speed_bps as f64;
+        let mut averages = self.averages.write().await;
+
+        // The first sample of a task starts the average
+        let speed_bps = match averages.get(&task_id) {
+            Some(previous) if !self.window.is_zero() => {
+                let elapsed = sampled_at.saturating_duration_since(previous.sampled_at).as_secs_f64();
+                let weight = 1.0 - (-elapsed / self.window.as_secs_f64()).exp();
+                previous.speed_bps + weight * (sample - previous.speed_bps)
+            }
+            _ => sample,
+        };
+        averages.insert(task_id, Average { speed_bps, sampled_at });
+        drop(averages);
+
+        SmoothedProgress::new(progress, speed_bps.round() as u64)
+    }
+
+    /// Get the current average speed of a task
+    pub async fn average_speed(&self, task_id: TaskId) -> Option<u64> {
+        self.averages.read().await.get(&task_id)
+            .map(|average| average.speed_bps.round() as u64)
+    }
+
+    /// Forget a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.averages.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
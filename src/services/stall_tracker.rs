@@ -0,0 +1,62 @@
+//! Download stall tracking
+//!
+//! Remembers when each task last downloaded more bytes so garbage collection
+//! can tell a slow download from one the engine silently dropped.
+
+use crate::types::TaskId;
+use std::collections::HashMap;
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+
+/// When each task last made progress
+#[derive(Debug)]
+pub struct StallTracker {
+    /// Downloaded bytes last seen and when they last grew
+    progress: RwLock<HashMap<TaskId, (u64, SystemTime)>>,
+    created: SystemTime,
+}
+
+impl Default for StallTracker {
+    fn default() -> Self {
+        Self {
+            progress: RwLock::new(HashMap::new()),
+            created: SystemTime::now(),
+        }
+    }
+}
+
+impl StallTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the downloaded bytes of a task
+    ///
+    /// A task seen for the first time counts as progressing now.
+    pub async fn observe(&self, task_id: TaskId, downloaded_bytes: u64) {
+        let now = SystemTime::now();
+        let mut progress = self.progress.write().await;
+        match progress.get_mut(&task_id) {
+            Some((bytes, _)) if *bytes == downloaded_bytes => {}
+            Some(entry) => *entry = (downloaded_bytes, now),
+            None => {
+                progress.insert(task_id, (downloaded_bytes, now));
+            }
+        }
+    }
+
+    /// Get when a task last made progress
+    ///
+    /// Tasks never observed count from when the tracker was created, so a
+    /// restart does not make every download look stuck.
+    pub async fn last_progress(&self, task_id: TaskId) -> SystemTime {
+        self.progress.read().await.get(&task_id)
+            .map(|(_, since)| *since)
+            .unwrap_or(self.created)
+    }
+
+    /// Forget a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.progress.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+//! Task status tracking
+//!
+//! Remembers the last status persisted for each task so the persistence
+//! poller only writes to the database and notifies handlers when a status
+//! actually changed, instead of saving every task on every poll.
+
+use crate::types::{TaskId, DownloadStatus, DownloadTask};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Last persisted status of each task
+#[derive(Debug, Default)]
+pub struct StatusTracker {
+    statuses: RwLock<HashMap<TaskId, DownloadStatus>>,
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the last status recorded for a task
+    pub async fn last_status(&self, task_id: TaskId) -> Option<DownloadStatus> {
+        self.statuses.read().await.get(&task_id).cloned()
+    }
+
+    /// Check if `status` differs from the last status recorded for a task
+    ///
+    /// Tasks without a recorded status count as changed.
+    pub async fn has_changed(&self, task_id: TaskId, status: &DownloadStatus) -> bool {
+        self.statuses.read().await.get(&task_id) != Some(status)
+    }
+
+    /// Keep the tasks whose status changed, see [`has_changed`](Self::has_changed)
+    pub async fn changed(&self, tasks: Vec<DownloadTask>) -> Vec<DownloadTask> {
+        let statuses = self.statuses.read().await;
+        tasks.into_iter()
+            .filter(|task| statuses.get(&task.id) != Some(&task.status))
+            .collect()
+    }
+
+    /// Record the status of a task once it was persisted, returning the previous one
+    pub async fn record(&self, task_id: TaskId, status: DownloadStatus) -> Option<DownloadStatus> {
+        self.statuses.write().await.insert(task_id, status)
+    }
+
+    /// Forget a task
+    pub async fn remove_task(&self, task_id: TaskId) {
+        self.statuses.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+//! Target path registry
+//!
+//! Tracks which task writes to which file so two downloads never share a
+//! target path. A path is reserved before the download is handed to the
+//! backend and assigned to the task once its ID is known.
+
+use crate::types::TaskId;
+use crate::error::DownloadError;
+use crate::utils::paths::{first_free_renamed_path, path_key};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// Registry of target paths held by unfinished downloads
+#[derive(Debug, Default)]
+pub struct TargetPathRegistry {
+    /// Held paths and their task, `None` while the task is being created
+    paths: Mutex<HashMap<PathBuf, Option<TaskId>>>,
+}
+
+impl TargetPathRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve `target_path` for a new download and return the path to use
+    ///
+    /// A taken path fails with [`DownloadError::TargetPathConflict`], or with
+    /// `auto_rename` the first free `name (n).ext` that does not exist on disk
+    /// is reserved instead.
+    pub async fn reserve(&self, target_path: &Path, auto_rename: bool) -> Result<PathBuf, DownloadError> {
+        let mut paths = self.paths.lock().await;
+
+        let key = path_key(target_path);
+        if !paths.contains_key(&key) {
+            paths.insert(key, None);
+            return Ok(target_path.to_path_buf());
+        }
+        if !auto_rename {
+            return Err(DownloadError::TargetPathConflict {
+                path: target_path.to_path_buf(),
+                task_id: paths.get(&key).copied().flatten(),
+            });
+        }
+
+        let free = first_free_renamed_path(target_path, |candidate| {
+            paths.contains_key(&path_key(candidate)) || candidate.exists()
+        });
+        if let Some(candidate) = free {
+            log::info!("Target path {:?} is taken, using {:?}", target_path, candidate);
+            paths.insert(path_key(&candidate), None);
+            return Ok(candidate);
+        }
+
+        Err(DownloadError::TargetPathConflict {
+            path: target_path.to_path_buf(),
+            task_id: paths.get(&key).copied().flatten(),
+        })
+    }
+
+    /// Hand a reserved path to the task created for it
+    pub async fn assign(&self, target_path: &Path, task_id: TaskId) {
+        self.paths.lock().await.insert(path_key(target_path), Some(task_id));
+    }
+
+    /// Drop a reservation whose task could not be created
+    pub async fn release_path(&self, target_path: &Path) {
+        self.paths.lock().await.remove(&path_key(target_path));
+    }
+
+    /// Release the path held by a task
+    pub async fn release(&self, task_id: TaskId) {
+        self.paths.lock().await.retain(|_, owner| *owner != Some(task_id));
+    }
+
+    /// Get the task holding `target_path`
+    pub async fn owner(&self, target_path: &Path) -> Option<TaskId> {
+        self.paths.lock().await.get(&path_key(target_path)).copied().flatten()
+    }
+}
\ No newline at end of file
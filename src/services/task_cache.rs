@@ -0,0 +1,86 @@
+//! Short-lived cache of task state and progress
+//!
+//! Callers reading tasks many times a second, like a UI redrawing at 10 Hz,
+//! would otherwise turn every read into an aria2 RPC. The poller and the
+//! aria2 notifications keep the cache fed; entries older than the TTL are
+//! fetched from the backend again.
+
+use crate::types::{TaskId, DownloadTask, DownloadProgress};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// How long cached task state is served by default
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+struct Entry {
+    task: Option<(Instant, DownloadTask)>,
+    progress: Option<(Instant, DownloadProgress)>,
+}
+
+/// Task state and progress last read from the backend
+#[derive(Debug)]
+pub struct TaskCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<TaskId, Entry>>,
+}
+
+impl Default for TaskCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_TTL)
+    }
+}
+
+impl TaskCache {
+    /// Create a cache serving entries for `ttl`, a zero TTL disables caching
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn is_fresh(&self, at: Instant) -> bool {
+        at.elapsed() < self.ttl
+    }
+
+    /// Get a task if it was cached within the TTL
+    pub async fn task(&self, task_id: TaskId) -> Option<DownloadTask> {
+        self.entries.read().await.get(&task_id)?
+            .task.as_ref()
+            .filter(|(at, _)| self.is_fresh(*at))
+            .map(|(_, task)| task.clone())
+    }
+
+    /// Get the progress of a task if it was cached within the TTL
+    pub async fn progress(&self, task_id: TaskId) -> Option<DownloadProgress> {
+        self.entries.read().await.get(&task_id)?
+            .progress.as_ref()
+            .filter(|(at, _)| self.is_fresh(*at))
+            .map(|(_, progress)| progress.clone())
+    }
+
+    pub async fn put_task(&self, task: &DownloadTask) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.write().await.entry(task.id).or_default().task = Some((Instant::now(), task.clone()));
+    }
+
+    pub async fn put_progress(&self, task_id: TaskId, progress: &DownloadProgress) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        self.entries.write().await.entry(task_id).or_default().progress = Some((Instant::now(), progress.clone()));
+    }
+
+    /// Drop what is cached of a task, e.g. after it was changed
+    pub async fn invalidate(&self, task_id: TaskId) {
+        self.entries.write().await.remove(&task_id);
+    }
+}
\ No newline at end of file
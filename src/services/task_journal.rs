@@ -0,0 +1,173 @@
+//! Write-ahead journal of task state changes
+//!
+//! A state change touches aria2 and the task database one after the other, so
+//! a crash in between leaves them disagreeing. Each change is first appended to
+//! the `task_journal` staging table of the crate-owned SQLite database and
+//! committed (removed) once the task database reflects it. Entries still in the
+//! journal at startup are replayed to reconcile the task database before tasks
+//! are restored.
+
+use crate::types::{TaskId, DownloadStatus};
+use crate::error::DownloadError;
+use crate::services::task_metadata_store::{open_pool, in_memory_pool, encode_task_id, decode_value, db_error, unix_now};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+use sqlx::{QueryBuilder, Row, Sqlite};
+use std::path::Path;
+
+/// State a journaled change moves a task to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JournaledState {
+    Waiting,
+    Downloading,
+    Paused,
+    Completed,
+    Failed(String),
+    /// The task is cancelled and removed
+    Removed,
+}
+
+impl JournaledState {
+    /// Get the status a task is moved to, `None` for removed tasks
+    pub fn status(&self) -> Option<DownloadStatus> {
+        match self {
+            JournaledState::Waiting => Some(DownloadStatus::Waiting),
+            JournaledState::Downloading => Some(DownloadStatus::Downloading),
+            JournaledState::Paused => Some(DownloadStatus::Paused),
+            JournaledState::Completed => Some(DownloadStatus::Completed),
+            JournaledState::Failed(error) => Some(DownloadStatus::Failed(error.clone())),
+            JournaledState::Removed => None,
+        }
+    }
+}
+
+impl From<&DownloadStatus> for JournaledState {
+    fn from(status: &DownloadStatus) -> Self {
+        match status {
+            DownloadStatus::Waiting => JournaledState::Waiting,
+            DownloadStatus::Downloading => JournaledState::Downloading,
+            DownloadStatus::Paused => JournaledState::Paused,
+            DownloadStatus::Completed => JournaledState::Completed,
+            DownloadStatus::Failed(error) => JournaledState::Failed(error.clone()),
+        }
+    }
+}
+
+/// A state change not yet committed to the task database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// Position in the journal, entries replay in ascending order
+    pub seq: i64,
+    pub task_id: TaskId,
+    pub state: JournaledState,
+}
+
+/// SQLite-backed append-only journal of task state changes
+#[derive(Clone)]
+pub struct TaskJournal {
+    pool: SqlitePool,
+}
+
+impl TaskJournal {
+    /// Open (or create) a journal in the given SQLite file
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Ok(Self { pool: open_pool(path).await? })
+    }
+
+    /// Create a journal that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Ok(Self { pool: in_memory_pool().await? })
+    }
+
+    /// Use a migrated pool shared with other stores
+    pub(crate) fn with_pool(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Append a state change, returning its sequence number
+    pub async fn record(&self, task_id: &TaskId, state: &JournaledState) -> Result<i64, DownloadError> {
+        let result = sqlx::query("INSERT INTO task_journal (task_id, state, recorded_at) VALUES (?, ?, ?)")
+            .bind(encode_task_id(task_id)?)
+            .bind(encode_state(state)?)
+            .bind(unix_now())
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Append several state changes in one statement, returning their sequence numbers in order
+    pub async fn record_all(&self, changes: &[(TaskId, JournaledState)]) -> Result<Vec<i64>, DownloadError> {
+        if changes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = changes.iter()
+            .map(|(task_id, state)| Ok((encode_task_id(task_id)?, encode_state(state)?)))
+            .collect::<Result<Vec<_>, DownloadError>>()?;
+        let now = unix_now();
+
+        let mut query = QueryBuilder::<Sqlite>::new("INSERT INTO task_journal (task_id, state, recorded_at) ");
+        query.push_values(rows, |mut row, (task_id, state)| {
+            row.push_bind(task_id).push_bind(state).push_bind(now);
+        });
+        query.push(" RETURNING seq");
+
+        let rows = query.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        // Rows are inserted in order but may be returned in any order
+        let mut seqs: Vec<i64> = rows.into_iter().map(|row| row.get("seq")).collect();
+        seqs.sort_unstable();
+        Ok(seqs)
+    }
+
+    /// Remove an applied change from the journal
+    pub async fn commit(&self, seq: i64) -> Result<(), DownloadError> {
+        self.commit_all(&[seq]).await
+    }
+
+    /// Remove several applied changes from the journal in one statement
+    pub async fn commit_all(&self, seqs: &[i64]) -> Result<(), DownloadError> {
+        if seqs.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM task_journal WHERE seq IN (");
+        let mut separated = query.separated(", ");
+        for seq in seqs {
+            separated.push_bind(*seq);
+        }
+        separated.push_unseparated(")");
+
+        query.build()
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// List the changes not committed yet, oldest first
+    pub async fn pending(&self) -> Result<Vec<JournalEntry>, DownloadError> {
+        let rows = sqlx::query("SELECT seq, task_id, state FROM task_journal ORDER BY seq")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| Ok(JournalEntry {
+                seq: row.get("seq"),
+                task_id: decode_value(row.get::<String, _>("task_id"))?,
+                state: decode_value(row.get::<String, _>("state"))?,
+            }))
+            .collect()
+    }
+}
+
+fn encode_state(state: &JournaledState) -> Result<String, DownloadError> {
+    serde_json::to_string(state).map_err(|e| DownloadError::DatabaseError(e.to_string()))
+}
\ No newline at end of file
@@ -0,0 +1,683 @@
+//! Task metadata store
+//!
+//! Persists crate-level task state that the download database schema has no
+//! columns for (retry attempts, download options, mirror URLs and similar). Values are stored as JSON under
+//! a `(task_id, key)` pair in a SQLite table owned by this crate, next to the
+//! `task_gid_mapping` table linking tasks to their aria2 GIDs, the
+//! `task_target_paths` table whose unique key keeps two tasks off one file and
+//! the `download_validators` table remembering which version of a URL was
+//! last downloaded to a path and the `task_url_hashes` table indexing tasks
+//! by normalized URL hash and target path for duplicate checks, and the
+//! `download_profiles` table holding named download profiles and the indexed
+//! `task_owners` table recording which user or tenant a task belongs to. All of them
+//! are created by the [`migrations`](crate::migrations) when a pool is opened.
+//! With a [`FieldCipher`] the stored values and scheduled URLs are encrypted
+//! at rest, and download validators are keyed by a hash of their URL instead
+//! of the URL itself.
+
+use crate::types::TaskId;
+use crate::error::DownloadError;
+use crate::probe::RemoteValidators;
+use crate::models::DownloadProfile;
+use crate::migrations;
+use crate::services::field_cipher::{self, FieldCipher};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix of download validator keys that hash their URL
+const URL_DIGEST_PREFIX: &str = "sha256:";
+
+/// Default location of the crate-owned SQLite database
+pub const DEFAULT_METADATA_DB_PATH: &str = "data/burncloud_download_metadata.db";
+
+/// Key under which retry attempt counts are stored
+pub const RETRY_ATTEMPTS_KEY: &str = "retry_attempts";
+
+/// Key under which per-task download options are stored
+pub const DOWNLOAD_OPTIONS_KEY: &str = "download_options";
+
+/// Key under which all source URLs of a multi-source download are stored
+pub const SOURCE_URLS_KEY: &str = "source_urls";
+
+/// Key under which the validators of the remote file a download started from are stored
+pub const REMOTE_VALIDATORS_KEY: &str = "remote_validators";
+
+/// Key under which the name of the profile a download was started with is stored
+pub const PROFILE_KEY: &str = "profile";
+
+/// Key under which the path a completed file was handed over at is stored
+pub const CONSUMED_KEY: &str = "consumed";
+
+/// Key under which the diagnostics of a task's most recent failures are stored
+pub const FAILURES_KEY: &str = "failures";
+
+/// Key under which the file of a download paused by a full disk is stored
+pub const NO_SPACE_KEY: &str = "paused_no_space";
+
+/// Key under which tasks deleted into the trash are flagged
+pub const TRASH_KEY: &str = "trash";
+
+/// SQLite-backed key/value store for per-task metadata
+#[derive(Clone)]
+pub struct TaskMetadataStore {
+    pool: SqlitePool,
+    cipher: Option<FieldCipher>,
+}
+
+impl TaskMetadataStore {
+    /// Open (or create) a store in the given SQLite file
+    pub async fn open(path: &Path) -> Result<Self, DownloadError> {
+        Ok(Self::with_pool(open_pool(path).await?))
+    }
+
+    /// Create a store that lives only in memory
+    pub async fn in_memory() -> Result<Self, DownloadError> {
+        Ok(Self::with_pool(in_memory_pool().await?))
+    }
+
+    /// Use a migrated pool shared with other stores
+    pub(crate) fn with_pool(pool: SqlitePool) -> Self {
+        Self { pool, cipher: None }
+    }
+
+    /// Encrypt stored values with `cipher`
+    ///
+    /// Values written without encryption can still be read.
+    pub fn with_cipher(mut self, cipher: FieldCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Get the pool, for stores sharing the database
+    pub(crate) fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Store a value for a task, replacing any previous value under the same key
+    pub async fn put<T: Serialize>(&self, task_id: &TaskId, key: &str, value: &T) -> Result<(), DownloadError> {
+        let value = serde_json::to_string(value)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+        let value = field_cipher::seal(self.cipher.as_ref(), &value)?;
+
+        sqlx::query(
+            "INSERT INTO task_metadata (task_id, key, value, updated_at) VALUES (?, ?, ?, ?)
+             ON CONFLICT(task_id, key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at"
+        )
+        .bind(encode_task_id(task_id)?)
+        .bind(key)
+        .bind(value)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Store a value for a task unless one is already stored under the same key
+    ///
+    /// Returns whether the value was stored. Of several processes storing
+    /// the same key at once, exactly one succeeds.
+    pub async fn put_new<T: Serialize>(&self, task_id: &TaskId, key: &str, value: &T) -> Result<bool, DownloadError> {
+        let value = serde_json::to_string(value)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+        let value = field_cipher::seal(self.cipher.as_ref(), &value)?;
+
+        let result = sqlx::query(
+            "INSERT INTO task_metadata (task_id, key, value, updated_at) VALUES (?, ?, ?, ?)
+             ON CONFLICT(task_id, key) DO NOTHING"
+        )
+        .bind(encode_task_id(task_id)?)
+        .bind(key)
+        .bind(value)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Load a value stored for a task
+    pub async fn get<T: DeserializeOwned>(&self, task_id: &TaskId, key: &str) -> Result<Option<T>, DownloadError> {
+        let row = sqlx::query("SELECT value FROM task_metadata WHERE task_id = ? AND key = ?")
+            .bind(encode_task_id(task_id)?)
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| self.decode_stored(row.get::<String, _>("value")))
+            .transpose()
+    }
+
+    /// Load every value stored under `key`, across all tasks
+    pub async fn entries<T: DeserializeOwned>(&self, key: &str) -> Result<Vec<(TaskId, T)>, DownloadError> {
+        let rows = sqlx::query("SELECT task_id, value FROM task_metadata WHERE key = ?")
+            .bind(key)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let task_id = decode_value(row.get::<String, _>("task_id"))?;
+                let value = self.decode_stored(row.get::<String, _>("value"))?;
+                Ok((task_id, value))
+            })
+            .collect()
+    }
+
+    /// Rewrite values that are not encrypted with the current key
+    ///
+    /// Run after rotating keys or enabling encryption, so that previous keys
+    /// can be dropped. Returns the number of values rewritten.
+    pub async fn reencrypt(&self) -> Result<usize, DownloadError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+        let rows = sqlx::query("SELECT task_id, key, value FROM task_metadata")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        let mut rewritten = 0;
+        for row in rows {
+            let value = row.get::<String, _>("value");
+            if cipher.is_current(&value) {
+                continue;
+            }
+            sqlx::query("UPDATE task_metadata SET value = ? WHERE task_id = ? AND key = ?")
+                .bind(cipher.encrypt(&cipher.decrypt(&value)?)?)
+                .bind(row.get::<String, _>("task_id"))
+                .bind(row.get::<String, _>("key"))
+                .execute(&mut *tx)
+                .await
+                .map_err(db_error)?;
+            rewritten += 1;
+        }
+        rewritten += self.hash_validator_urls(&mut tx).await?;
+        rewritten += self.reencrypt_scheduled_urls(&mut tx).await?;
+        tx.commit().await.map_err(db_error)?;
+
+        Ok(rewritten)
+    }
+
+    /// Replace plaintext URLs keying download validators with their hash
+    async fn hash_validator_urls(&self, tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>) -> Result<usize, DownloadError> {
+        let rows = sqlx::query("SELECT url, target_path FROM download_validators")
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(db_error)?;
+
+        let mut rewritten = 0;
+        for row in rows {
+            let url = row.get::<String, _>("url");
+            if url.starts_with(URL_DIGEST_PREFIX) {
+                continue;
+            }
+            // An entry already under the hashed key is newer than the plaintext one
+            let target_path = row.get::<String, _>("target_path");
+            sqlx::query("UPDATE OR IGNORE download_validators SET url = ? WHERE url = ? AND target_path = ?")
+                .bind(url_digest(&url))
+                .bind(&url)
+                .bind(&target_path)
+                .execute(&mut **tx)
+                .await
+                .map_err(db_error)?;
+            sqlx::query("DELETE FROM download_validators WHERE url = ? AND target_path = ?")
+                .bind(&url)
+                .bind(&target_path)
+                .execute(&mut **tx)
+                .await
+                .map_err(db_error)?;
+            rewritten += 1;
+        }
+        Ok(rewritten)
+    }
+
+    /// Rewrite scheduled download URLs that are not encrypted with the current key
+    async fn reencrypt_scheduled_urls(&self, tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>) -> Result<usize, DownloadError> {
+        let Some(cipher) = &self.cipher else {
+            return Ok(0);
+        };
+        let rows = sqlx::query("SELECT id, url FROM scheduled_downloads")
+            .fetch_all(&mut **tx)
+            .await
+            .map_err(db_error)?;
+
+        let mut rewritten = 0;
+        for row in rows {
+            let url = row.get::<String, _>("url");
+            if cipher.is_current(&url) {
+                continue;
+            }
+            sqlx::query("UPDATE scheduled_downloads SET url = ? WHERE id = ?")
+                .bind(cipher.encrypt(&cipher.decrypt(&url)?)?)
+                .bind(row.get::<i64, _>("id"))
+                .execute(&mut **tx)
+                .await
+                .map_err(db_error)?;
+            rewritten += 1;
+        }
+        Ok(rewritten)
+    }
+
+    /// Get the key download validators of `url` are stored under
+    ///
+    /// With encryption the URL is hashed, as its tokens must not be stored
+    /// in plaintext while it still has to be looked up.
+    fn validator_key(&self, url: &str) -> String {
+        match self.cipher {
+            Some(_) => url_digest(url),
+            None => url.to_string(),
+        }
+    }
+
+    fn decode_stored<T: DeserializeOwned>(&self, raw: String) -> Result<T, DownloadError> {
+        decode_value(field_cipher::open(self.cipher.as_ref(), raw)?)
+    }
+
+    /// Remove a single value of a task
+    pub async fn remove(&self, task_id: &TaskId, key: &str) -> Result<(), DownloadError> {
+        sqlx::query("DELETE FROM task_metadata WHERE task_id = ? AND key = ?")
+            .bind(encode_task_id(task_id)?)
+            .bind(key)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Remove all values and the GID mapping of a task
+    pub async fn remove_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        let encoded = encode_task_id(task_id)?;
+
+        for statement in [
+            "DELETE FROM task_metadata WHERE task_id = ?",
+            "DELETE FROM task_gid_mapping WHERE task_id = ?",
+            "DELETE FROM task_target_paths WHERE task_id = ?",
+            "DELETE FROM task_url_hashes WHERE task_id = ?",
+            "DELETE FROM task_owners WHERE task_id = ?",
+        ] {
+            sqlx::query(statement)
+                .bind(&encoded)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move all values and the GID mapping of a task to a new task ID
+    pub async fn rekey_task(&self, old_task_id: &TaskId, new_task_id: &TaskId) -> Result<(), DownloadError> {
+        let old_encoded = encode_task_id(old_task_id)?;
+        let new_encoded = encode_task_id(new_task_id)?;
+
+        for statement in [
+            "UPDATE task_metadata SET task_id = ? WHERE task_id = ?",
+            "UPDATE task_gid_mapping SET task_id = ? WHERE task_id = ?",
+            "UPDATE task_target_paths SET task_id = ? WHERE task_id = ?",
+            "UPDATE download_validators SET task_id = ? WHERE task_id = ?",
+            "UPDATE task_url_hashes SET task_id = ? WHERE task_id = ?",
+            "UPDATE task_owners SET task_id = ? WHERE task_id = ?",
+        ] {
+            sqlx::query(statement)
+                .bind(&new_encoded)
+                .bind(&old_encoded)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Store the aria2 GID of a task
+    pub async fn put_gid(&self, task_id: &TaskId, gid: &str) -> Result<(), DownloadError> {
+        sqlx::query(
+            "INSERT INTO task_gid_mapping (task_id, gid, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(task_id) DO UPDATE SET gid = excluded.gid, updated_at = excluded.updated_at"
+        )
+        .bind(encode_task_id(task_id)?)
+        .bind(gid)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Get the aria2 GID stored for a task
+    pub async fn get_gid(&self, task_id: &TaskId) -> Result<Option<String>, DownloadError> {
+        let row = sqlx::query("SELECT gid FROM task_gid_mapping WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(row.map(|row| row.get("gid")))
+    }
+
+    /// Record that a task writes to `target_path`
+    ///
+    /// Fails with [`DownloadError::TargetPathConflict`] if another task already
+    /// holds the path.
+    pub async fn claim_path(&self, task_id: &TaskId, target_path: &Path) -> Result<(), DownloadError> {
+        let result = sqlx::query(
+            "INSERT INTO task_target_paths (target_path, task_id, updated_at) VALUES (?, ?, ?)"
+        )
+        .bind(target_path.to_string_lossy())
+        .bind(encode_task_id(task_id)?)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                match self.path_owner(target_path).await? {
+                    Some(owner) if owner == *task_id => Ok(()),
+                    owner => Err(DownloadError::TargetPathConflict {
+                        path: PathBuf::from(target_path),
+                        task_id: owner,
+                    }),
+                }
+            }
+            Err(e) => Err(db_error(e)),
+        }
+    }
+
+    /// Get the task holding `target_path`
+    pub async fn path_owner(&self, target_path: &Path) -> Result<Option<TaskId>, DownloadError> {
+        let row = sqlx::query("SELECT task_id FROM task_target_paths WHERE target_path = ?")
+            .bind(target_path.to_string_lossy())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        row.map(|row| decode_value(row.get("task_id"))).transpose()
+    }
+
+    /// Record the version of `url` a task downloads to `target_path`
+    pub async fn put_download_validators(
+        &self,
+        url: &str,
+        target_path: &Path,
+        task_id: &TaskId,
+        validators: &RemoteValidators,
+    ) -> Result<(), DownloadError> {
+        let validators = serde_json::to_string(validators)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO download_validators (url, target_path, task_id, validators, updated_at) VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(url, target_path) DO UPDATE SET task_id = excluded.task_id,
+                validators = excluded.validators, updated_at = excluded.updated_at"
+        )
+        .bind(self.validator_key(url))
+        .bind(target_path.to_string_lossy())
+        .bind(encode_task_id(task_id)?)
+        .bind(validators)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Get the task that last downloaded `url` to `target_path` and the version it fetched
+    pub async fn get_download_validators(
+        &self,
+        url: &str,
+        target_path: &Path,
+    ) -> Result<Option<(TaskId, RemoteValidators)>, DownloadError> {
+        // Entries written before encryption was enabled are keyed by the plain URL
+        let row = sqlx::query(
+            "SELECT task_id, validators FROM download_validators WHERE url IN (?, ?) AND target_path = ?
+             ORDER BY updated_at DESC LIMIT 1"
+        )
+        .bind(self.validator_key(url))
+        .bind(url)
+        .bind(target_path.to_string_lossy())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        row.map(|row| {
+            let task_id = decode_value(row.get::<String, _>("task_id"))?;
+            let validators = decode_value(row.get::<String, _>("validators"))?;
+            Ok((task_id, validators))
+        }).transpose()
+    }
+
+    /// Record the user or tenant a task belongs to
+    pub async fn put_owner(&self, task_id: &TaskId, owner: &str) -> Result<(), DownloadError> {
+        sqlx::query(
+            "INSERT INTO task_owners (task_id, owner, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(task_id) DO UPDATE SET owner = excluded.owner, updated_at = excluded.updated_at"
+        )
+        .bind(encode_task_id(task_id)?)
+        .bind(owner)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Get the owner of a task
+    pub async fn owner_of(&self, task_id: &TaskId) -> Result<Option<String>, DownloadError> {
+        let row = sqlx::query("SELECT owner FROM task_owners WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(row.map(|row| row.get("owner")))
+    }
+
+    /// Get the tasks of an owner
+    pub async fn tasks_of_owner(&self, owner: &str) -> Result<Vec<TaskId>, DownloadError> {
+        let rows = sqlx::query("SELECT task_id FROM task_owners WHERE owner = ?")
+            .bind(owner)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| decode_value(row.get("task_id")))
+            .collect()
+    }
+
+    /// Save a named download profile, replacing the profile of that name
+    pub async fn put_profile(&self, name: &str, profile: &DownloadProfile) -> Result<(), DownloadError> {
+        let profile = serde_json::to_string(profile)
+            .map_err(|e| DownloadError::DatabaseError(e.to_string()))?;
+
+        sqlx::query(
+            "INSERT INTO download_profiles (name, profile, updated_at) VALUES (?, ?, ?)
+             ON CONFLICT(name) DO UPDATE SET profile = excluded.profile, updated_at = excluded.updated_at"
+        )
+        .bind(name)
+        .bind(profile)
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Load all saved download profiles by name
+    pub async fn profiles(&self) -> Result<BTreeMap<String, DownloadProfile>, DownloadError> {
+        let rows = sqlx::query("SELECT name, profile FROM download_profiles")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| Ok((row.get("name"), decode_value(row.get("profile"))?)))
+            .collect()
+    }
+
+    /// Delete a saved download profile, returning whether it existed
+    pub async fn remove_profile(&self, name: &str) -> Result<bool, DownloadError> {
+        let result = sqlx::query("DELETE FROM download_profiles WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Index a task by the hash of its normalized URL and its target path
+    pub async fn put_url_hash(&self, task_id: &TaskId, url_hash: &str, target_path: &Path) -> Result<(), DownloadError> {
+        sqlx::query(
+            "INSERT INTO task_url_hashes (task_id, url_hash, target_path, updated_at) VALUES (?, ?, ?, ?)
+             ON CONFLICT(task_id) DO UPDATE SET url_hash = excluded.url_hash,
+                target_path = excluded.target_path, updated_at = excluded.updated_at"
+        )
+        .bind(encode_task_id(task_id)?)
+        .bind(url_hash)
+        .bind(target_path.to_string_lossy())
+        .bind(unix_now())
+        .execute(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Index tasks not indexed yet, in one transaction
+    ///
+    /// Tasks that already have an entry keep it.
+    pub async fn backfill_url_hashes(&self, entries: &[(TaskId, String, PathBuf)]) -> Result<(), DownloadError> {
+        let now = unix_now();
+        let mut tx = self.pool.begin().await.map_err(db_error)?;
+        for (task_id, url_hash, target_path) in entries {
+            sqlx::query(
+                "INSERT INTO task_url_hashes (task_id, url_hash, target_path, updated_at) VALUES (?, ?, ?, ?)
+                 ON CONFLICT(task_id) DO NOTHING"
+            )
+            .bind(encode_task_id(task_id)?)
+            .bind(url_hash)
+            .bind(target_path.to_string_lossy())
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_error)?;
+        }
+        tx.commit().await.map_err(db_error)
+    }
+
+    /// Find the tasks downloading the URL with `url_hash` to `target_path`, oldest first
+    pub async fn find_by_url_hash_and_path(&self, url_hash: &str, target_path: &Path) -> Result<Vec<TaskId>, DownloadError> {
+        let rows = sqlx::query(
+            "SELECT task_id FROM task_url_hashes WHERE url_hash = ? AND target_path = ? ORDER BY updated_at, rowid"
+        )
+        .bind(url_hash)
+        .bind(target_path.to_string_lossy())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        rows.into_iter()
+            .map(|row| decode_value(row.get::<String, _>("task_id")))
+            .collect()
+    }
+
+    /// Remove a task from the URL hash index
+    pub async fn remove_url_hash(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        sqlx::query("DELETE FROM task_url_hashes WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+
+    /// Release the target path held by a task
+    pub async fn release_path(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        sqlx::query("DELETE FROM task_target_paths WHERE task_id = ?")
+            .bind(encode_task_id(task_id)?)
+            .execute(&self.pool)
+            .await
+            .map_err(db_error)?;
+
+        Ok(())
+    }
+}
+
+/// Open a connection pool to a SQLite file, creating it if needed and applying pending migrations
+pub(crate) async fn open_pool(path: &Path) -> Result<SqlitePool, DownloadError> {
+    let pool = connect_pool(path).await?;
+    migrations::apply(&pool).await?;
+    Ok(pool)
+}
+
+/// Open a connection pool to a SQLite file without touching its schema
+pub(crate) async fn connect_pool(path: &Path) -> Result<SqlitePool, DownloadError> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    SqlitePoolOptions::new()
+        .connect_with(options)
+        .await
+        .map_err(db_error)
+}
+
+/// Open a connection pool to a private, fully migrated in-memory SQLite database
+pub(crate) async fn in_memory_pool() -> Result<SqlitePool, DownloadError> {
+    // A single connection keeps every query on the same in-memory database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .map_err(db_error)?;
+    migrations::apply(&pool).await?;
+    Ok(pool)
+}
+
+pub(crate) fn encode_task_id(task_id: &TaskId) -> Result<String, DownloadError> {
+    serde_json::to_string(task_id).map_err(|e| DownloadError::DatabaseError(e.to_string()))
+}
+
+pub(crate) fn decode_value<T: DeserializeOwned>(raw: String) -> Result<T, DownloadError> {
+    serde_json::from_str(&raw).map_err(|e| DownloadError::DatabaseError(e.to_string()))
+}
+
+/// Hash a URL so it can be looked up without being stored
+fn url_digest(url: &str) -> String {
+    let digest = Sha256::digest(url.as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}{}", URL_DIGEST_PREFIX, hex)
+}
+
+pub(crate) fn db_error(error: sqlx::Error) -> DownloadError {
+    DownloadError::DatabaseError(error.to_string())
+}
+
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
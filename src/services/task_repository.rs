@@ -1,82 +1,433 @@
-//! Task repository for duplicate detection database operations
+//! Task repository abstraction over the database layer
 //!
-//! Provides database access layer for duplicate detection queries.
+//! [`TaskRepository`] is where managers keep tasks and their progress
+//! between runs. [`SqliteTaskRepository`] stores them through
+//! `burncloud_database_download` and is what
+//! [`PersistentAria2Manager`](crate::PersistentAria2Manager) uses unless
+//! another repository is given to its builder; [`InMemoryTaskRepository`]
+//! keeps them only as long as it lives, which suits tests. Other stores,
+//! such as Postgres, only have to implement the task and progress methods.
+//! [`EncryptedTaskRepository`] wraps any of them to keep URLs encrypted at rest.
 
-use crate::types::TaskId;
+use crate::types::{DownloadProgress, DownloadTask, TaskId};
 use crate::error::DownloadError;
-use std::path::Path;
+use crate::models::{FileIdentifier, ListOrder};
+use crate::services::field_cipher::FieldCipher;
+use crate::utils::paths::path_key;
+use burncloud_database_download::{Database, DownloadRepository};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use async_trait::async_trait;
+use tokio::sync::RwLock;
 
 /// Repository for task-related database operations
 #[async_trait]
 pub trait TaskRepository: Send + Sync {
+    /// Prepare the store before first use, e.g. create its schema
+    async fn initialize(&self) -> Result<(), DownloadError> {
+        Ok(())
+    }
+
+    /// Save a task, replacing the saved task with the same ID
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError>;
+
+    /// Save several tasks at once
+    ///
+    /// Saves one task after another by default, so a failure can leave the
+    /// tasks before it saved; stores with transactions should override it to
+    /// save all or none.
+    async fn save_tasks(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        for task in tasks {
+            self.save_task(task).await?;
+        }
+        Ok(())
+    }
+
+    /// Get a saved task
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError>;
+
+    /// Get all saved tasks, in no particular order
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError>;
+
+    /// Get all saved tasks in the given order
+    ///
+    /// Sorts every task in memory by default; stores that can query should
+    /// override it.
+    async fn list_tasks_ordered(&self, order: ListOrder) -> Result<Vec<DownloadTask>, DownloadError> {
+        let mut tasks = self.list_tasks().await?;
+        order.sort(&mut tasks);
+        Ok(tasks)
+    }
+
+    /// Get up to `limit` saved tasks starting at `offset` in the given order, leaving out `excluded`
+    ///
+    /// Ties are broken by task ID like in [`ListOrder::compare`]. Loads every
+    /// task by default; stores that can query should override it.
+    async fn list_tasks_page(
+        &self,
+        offset: usize,
+        limit: usize,
+        order: ListOrder,
+        excluded: &HashSet<TaskId>,
+    ) -> Result<Vec<DownloadTask>, DownloadError> {
+        Ok(self.list_tasks_ordered(order).await?
+            .into_iter()
+            .filter(|task| !excluded.contains(&task.id))
+            .skip(offset)
+            .take(limit)
+            .collect())
+    }
+
+    /// Delete a saved task, its progress is deleted separately
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError>;
+
+    /// Save the progress of a task, replacing the saved progress
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError>;
+
+    /// Get the saved progress of a task
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError>;
+
+    /// Delete the saved progress of a task
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError>;
+
+    /// Find tasks whose normalized URL has the given hash
+    ///
+    /// Scans all tasks by default; stores with an index should override it.
+    async fn find_by_url_hash(&self, url_hash: &str) -> Result<Vec<TaskId>, DownloadError> {
+        Ok(self.list_tasks().await?
+            .into_iter()
+            .filter(|task| FileIdentifier::new(&task.url, &task.target_path, None).url_hash == url_hash)
+            .map(|task| task.id)
+            .collect())
+    }
+
     /// Find tasks by URL hash and target path
     async fn find_by_url_hash_and_path(
         &self,
         url_hash: &str,
         target_path: &Path,
-    ) -> Result<Vec<TaskId>, DownloadError>;
+    ) -> Result<Vec<TaskId>, DownloadError> {
+        let target_path = path_key(target_path);
+        Ok(self.list_tasks().await?
+            .into_iter()
+            .filter(|task| {
+                FileIdentifier::new(&task.url, &task.target_path, None).url_hash == url_hash
+                    && path_key(&task.target_path) == target_path
+            })
+            .map(|task| task.id)
+            .collect())
+    }
 
     /// Find tasks by file content hash
+    ///
+    /// Stores without file hashes find nothing.
     async fn find_by_file_hash(
         &self,
-        file_hash: &str,
-    ) -> Result<Vec<TaskId>, DownloadError>;
+        _file_hash: &str,
+    ) -> Result<Vec<TaskId>, DownloadError> {
+        Ok(Vec::new())
+    }
 
     /// Update task with duplicate detection fields
+    ///
+    /// Ignored by stores without file hashes.
     async fn update_duplicate_fields(
         &self,
-        task_id: &TaskId,
-        url_hash: &str,
-        file_hash: Option<&str>,
-        file_size: Option<u64>,
-    ) -> Result<(), DownloadError>;
+        _task_id: &TaskId,
+        _url_hash: &str,
+        _file_hash: Option<&str>,
+        _file_size: Option<u64>,
+    ) -> Result<(), DownloadError> {
+        Ok(())
+    }
 }
 
-/// Default implementation of TaskRepository
-pub struct DefaultTaskRepository {
-    // Database connection will be added when implemented
+/// Repository keeping tasks and progress in memory
+#[derive(Default)]
+pub struct InMemoryTaskRepository {
+    tasks: RwLock<HashMap<TaskId, DownloadTask>>,
+    progress: RwLock<HashMap<TaskId, DownloadProgress>>,
 }
 
-impl Default for DefaultTaskRepository {
-    fn default() -> Self {
-        Self::new()
+/// Repository used when no database is configured
+pub type DefaultTaskRepository = InMemoryTaskRepository;
+
+impl InMemoryTaskRepository {
+    pub fn new() -> Self {
+        Self::default()
     }
 }
 
-impl DefaultTaskRepository {
-    pub fn new() -> Self {
-        Self {}
+#[async_trait]
+impl TaskRepository for InMemoryTaskRepository {
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError> {
+        self.tasks.write().await.insert(task.id, task.clone());
+        Ok(())
+    }
+
+    async fn save_tasks(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        let mut saved = self.tasks.write().await;
+        for task in tasks {
+            saved.insert(task.id, task.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError> {
+        self.tasks.read().await.get(task_id)
+            .cloned()
+            .ok_or(DownloadError::TaskNotFound(*task_id))
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError> {
+        Ok(self.tasks.read().await.values().cloned().collect())
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.tasks.write().await.remove(task_id);
+        Ok(())
+    }
+
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.progress.write().await.insert(*task_id, progress.clone());
+        Ok(())
+    }
+
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError> {
+        self.progress.read().await.get(task_id)
+            .cloned()
+            .ok_or(DownloadError::TaskNotFound(*task_id))
+    }
+
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.progress.write().await.remove(task_id);
+        Ok(())
     }
 }
 
+/// Repository storing tasks and progress in SQLite through `burncloud_database_download`
+///
+/// `DownloadRepository` only lists all tasks, so ordered and paged listings
+/// are sorted in memory.
+pub struct SqliteTaskRepository {
+    repository: DownloadRepository,
+}
+
+impl SqliteTaskRepository {
+    /// Use an already opened database repository
+    pub fn new(repository: DownloadRepository) -> Self {
+        Self { repository }
+    }
+
+    /// Open the database at `path`, or the default database when `None`
+    ///
+    /// The schema is created by [`initialize`](TaskRepository::initialize).
+    pub async fn open(path: Option<PathBuf>) -> Result<Self, DownloadError> {
+        let db = match path {
+            Some(path) => {
+                let mut db = Database::new(path);
+                db.initialize().await
+                    .map_err(|e| DownloadError::DatabaseError(format!("Failed to initialize database: {}", e)))?;
+                db
+            }
+            None => Database::new_default_initialized().await
+                .map_err(|e| DownloadError::DatabaseError(format!("Failed to initialize database: {}", e)))?,
+        };
+
+        Ok(Self::new(DownloadRepository::new(db)))
+    }
+}
+
+/// Wrap an error of the database layer
+fn database_error(error: impl std::fmt::Display) -> DownloadError {
+    DownloadError::DatabaseError(error.to_string())
+}
+
 #[async_trait]
-impl TaskRepository for DefaultTaskRepository {
-    async fn find_by_url_hash_and_path(
-        &self,
-        _url_hash: &str,
-        _target_path: &Path,
-    ) -> Result<Vec<TaskId>, DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 2
-        Ok(vec![])
+impl TaskRepository for SqliteTaskRepository {
+    async fn initialize(&self) -> Result<(), DownloadError> {
+        self.repository.initialize().await.map_err(database_error)?;
+        Ok(())
     }
 
-    async fn find_by_file_hash(
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError> {
+        self.repository.save_task(task).await.map_err(database_error)?;
+        Ok(())
+    }
+
+    /// Save all tasks or none
+    ///
+    /// `DownloadRepository` saves one task per statement, so when a save
+    /// fails the tasks saved before it are put back as they were.
+    async fn save_tasks(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        let mut previous = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            previous.push(self.repository.get_task(&task.id).await.ok());
+        }
+
+        for (saved, task) in tasks.iter().enumerate() {
+            if let Err(e) = self.repository.save_task(task).await {
+                for (task, previous) in tasks[..saved].iter().zip(&previous).rev() {
+                    let rollback = match previous {
+                        Some(previous) => self.repository.save_task(previous).await.map(|_| ()).map_err(database_error),
+                        None => self.repository.delete_task(&task.id).await.map(|_| ()).map_err(database_error),
+                    };
+                    if let Err(rollback_error) = rollback {
+                        log::error!("Failed to roll back task {}: {}", task.id, rollback_error);
+                    }
+                }
+                return Err(database_error(e));
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError> {
+        self.repository.get_task(task_id).await.map_err(database_error)
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.repository.list_tasks().await.map_err(database_error)
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.repository.delete_task(task_id).await.map_err(database_error)?;
+        Ok(())
+    }
+
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.repository.save_progress(task_id, progress).await.map_err(database_error)?;
+        Ok(())
+    }
+
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError> {
+        self.repository.get_progress(task_id).await.map_err(database_error)
+    }
+
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.repository.delete_progress(task_id).await.map_err(database_error)?;
+        Ok(())
+    }
+}
+
+/// Repository encrypting task URLs before they reach another repository
+///
+/// The wrapped repository only ever sees encrypted URLs, so its indexes on
+/// them are of no use: URL hash lookups scan the decrypted tasks instead.
+pub struct EncryptedTaskRepository {
+    inner: Arc<dyn TaskRepository>,
+    cipher: FieldCipher,
+}
+
+impl EncryptedTaskRepository {
+    pub fn new(inner: Arc<dyn TaskRepository>, cipher: FieldCipher) -> Self {
+        Self { inner, cipher }
+    }
+
+    /// Rewrite tasks whose URL is not encrypted with the current key
+    ///
+    /// Returns the number of tasks rewritten.
+    pub async fn reencrypt(&self) -> Result<usize, DownloadError> {
+        let mut rewritten = 0;
+        for mut task in self.inner.list_tasks().await? {
+            if self.cipher.is_current(&task.url) {
+                continue;
+            }
+            task.url = self.cipher.encrypt(&self.cipher.decrypt(&task.url)?)?;
+            self.inner.save_task(&task).await?;
+            rewritten += 1;
+        }
+        Ok(rewritten)
+    }
+
+    fn decrypt_task(&self, mut task: DownloadTask) -> Result<DownloadTask, DownloadError> {
+        task.url = self.cipher.decrypt(&task.url)?;
+        Ok(task)
+    }
+}
+
+#[async_trait]
+impl TaskRepository for EncryptedTaskRepository {
+    async fn initialize(&self) -> Result<(), DownloadError> {
+        self.inner.initialize().await
+    }
+
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError> {
+        let mut task = task.clone();
+        task.url = self.cipher.encrypt(&task.url)?;
+        self.inner.save_task(&task).await
+    }
+
+    async fn save_tasks(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        let tasks = tasks.iter()
+            .map(|task| {
+                let mut task = task.clone();
+                task.url = self.cipher.encrypt(&task.url)?;
+                Ok(task)
+            })
+            .collect::<Result<Vec<_>, DownloadError>>()?;
+        self.inner.save_tasks(&tasks).await
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError> {
+        self.decrypt_task(self.inner.get_task(task_id).await?)
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.inner.list_tasks().await?
+            .into_iter()
+            .map(|task| self.decrypt_task(task))
+            .collect()
+    }
+
+    async fn list_tasks_ordered(&self, order: ListOrder) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.inner.list_tasks_ordered(order).await?
+            .into_iter()
+            .map(|task| self.decrypt_task(task))
+            .collect()
+    }
+
+    async fn list_tasks_page(
         &self,
-        _file_hash: &str,
-    ) -> Result<Vec<TaskId>, DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 2
-        Ok(vec![])
+        offset: usize,
+        limit: usize,
+        order: ListOrder,
+        excluded: &HashSet<TaskId>,
+    ) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.inner.list_tasks_page(offset, limit, order, excluded).await?
+            .into_iter()
+            .map(|task| self.decrypt_task(task))
+            .collect()
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_task(task_id).await
+    }
+
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.inner.save_progress(task_id, progress).await
+    }
+
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError> {
+        self.inner.get_progress(task_id).await
+    }
+
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_progress(task_id).await
+    }
+
+    async fn find_by_file_hash(&self, file_hash: &str) -> Result<Vec<TaskId>, DownloadError> {
+        self.inner.find_by_file_hash(file_hash).await
     }
 
     async fn update_duplicate_fields(
         &self,
-        _task_id: &TaskId,
-        _url_hash: &str,
-        _file_hash: Option<&str>,
-        _file_size: Option<u64>,
+        task_id: &TaskId,
+        url_hash: &str,
+        file_hash: Option<&str>,
+        file_size: Option<u64>,
     ) -> Result<(), DownloadError> {
-        // Placeholder implementation - will be implemented in Phase 2
-        Ok(())
+        self.inner.update_duplicate_fields(task_id, url_hash, file_hash, file_size).await
     }
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+//! Progress coalescing for event handlers
+//!
+//! aria2 reports progress far more often than a UI can redraw. A
+//! [`ThrottledHandler`] passes every event on to the handler it wraps except
+//! progress, which it holds back until the handler's [`ProgressDelivery`]
+//! limits are reached. The latest update held back is delivered before the
+//! task's next status change, so handlers always see where a task stopped.
+
+use crate::models::{FailureInfo, ProgressDelivery, SpeedSample};
+use crate::traits::DownloadEventHandler;
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Mutex;
+
+/// What a handler last received of a task
+#[derive(Debug)]
+struct Delivered {
+    at: Instant,
+    downloaded_bytes: u64,
+    /// Latest update held back since
+    pending: Option<DownloadProgress>,
+}
+
+/// Event handler wrapper limiting how often progress is delivered
+pub struct ThrottledHandler {
+    inner: Arc<dyn DownloadEventHandler>,
+    delivery: ProgressDelivery,
+    tasks: Mutex<HashMap<TaskId, Delivered>>,
+}
+
+impl ThrottledHandler {
+    pub fn new(inner: Arc<dyn DownloadEventHandler>, delivery: ProgressDelivery) -> Self {
+        Self {
+            inner,
+            delivery,
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn delivery(&self) -> ProgressDelivery {
+        self.delivery
+    }
+
+    /// Decide whether an update goes out now, holding it back otherwise
+    ///
+    /// The first update of a task and the one reaching its total size always go out.
+    async fn due(&self, task_id: TaskId, progress: &DownloadProgress) -> bool {
+        let now = Instant::now();
+        let mut tasks = self.tasks.lock().await;
+        let due = match tasks.get_mut(&task_id) {
+            None => true,
+            Some(last) => {
+                let finished = progress.total_bytes.is_some_and(|total| progress.downloaded_bytes >= total);
+                let due = finished || (
+                    now.duration_since(last.at) >= self.delivery.min_interval
+                        && progress.downloaded_bytes.saturating_sub(last.downloaded_bytes) >= self.delivery.min_delta_bytes
+                );
+                if !due {
+                    last.pending = Some(progress.clone());
+                }
+                due
+            }
+        };
+
+        if due {
+            tasks.insert(task_id, Delivered { at: now, downloaded_bytes: progress.downloaded_bytes, pending: None });
+        }
+        due
+    }
+
+    /// Deliver the update held back for a task, if any
+    async fn flush(&self, task_id: TaskId) {
+        let pending = self.tasks.lock().await
+            .get_mut(&task_id)
+            .and_then(|last| last.pending.take());
+        if let Some(progress) = pending {
+            self.inner.on_progress_updated(task_id, progress).await;
+        }
+    }
+
+    /// Deliver what was held back for a finished task and forget it
+    async fn finish(&self, task_id: TaskId) {
+        self.flush(task_id).await;
+        self.tasks.lock().await.remove(&task_id);
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for ThrottledHandler {
+    async fn on_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        self.flush(task_id).await;
+        self.inner.on_status_changed(task_id, old_status, new_status).await;
+    }
+
+    async fn on_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
+        if self.delivery.is_unlimited() || self.due(task_id, &progress).await {
+            self.inner.on_progress_updated(task_id, progress).await;
+        }
+    }
+
+    async fn on_download_completed(&self, task_id: TaskId) {
+        self.finish(task_id).await;
+        self.inner.on_download_completed(task_id).await;
+    }
+
+    async fn on_download_failed(&self, task_id: TaskId, error: String) {
+        self.finish(task_id).await;
+        self.inner.on_download_failed(task_id, error).await;
+    }
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        self.inner.on_retry_scheduled(task_id, attempt, delay).await;
+    }
+
+    async fn on_download_restored(&self, task_id: TaskId, resumed_from: u64) {
+        self.inner.on_download_restored(task_id, resumed_from).await;
+    }
+
+    async fn on_source_changed(&self, task_id: TaskId, restarted_as: TaskId) {
+        self.finish(task_id).await;
+        self.inner.on_source_changed(task_id, restarted_as).await;
+    }
+
+    async fn on_extraction_progress(&self, task_id: TaskId, extracted_bytes: u64, total_bytes: Option<u64>) {
+        self.inner.on_extraction_progress(task_id, extracted_bytes, total_bytes).await;
+    }
+
+    async fn on_copy_progress(&self, task_id: TaskId, destination: PathBuf, copied_bytes: u64, total_bytes: u64) {
+        self.inner.on_copy_progress(task_id, destination, copied_bytes, total_bytes).await;
+    }
+
+    async fn on_post_processed(&self, task_id: TaskId, path: PathBuf) {
+        self.inner.on_post_processed(task_id, path).await;
+    }
+
+    async fn on_post_processing_failed(&self, task_id: TaskId, hook: String, error: String) {
+        self.inner.on_post_processing_failed(task_id, hook, error).await;
+    }
+
+    async fn on_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        self.finish(task_id).await;
+        self.inner.on_task_expired(task_id, expires_at).await;
+    }
+
+    async fn on_failure_recorded(&self, task_id: TaskId, failure: FailureInfo) {
+        self.inner.on_failure_recorded(task_id, failure).await;
+    }
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        self.inner.on_speed_sample(sample).await;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+//! Weakly registered event handlers
+//!
+//! A [`WeakHandler`] holds the handler it forwards to without keeping it
+//! alive. Once the application drops its last reference, the next event
+//! removes the registration instead, so short-lived observers such as UI
+//! views need no explicit deregistration.
+
+use crate::models::{FailureInfo, HandlerId, SpeedSample};
+use crate::services::handler_registry::HandlerRegistry;
+use crate::traits::DownloadEventHandler;
+use crate::types::{TaskId, DownloadStatus, DownloadProgress};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+
+/// Event handler wrapper forwarding to a handler as long as it lives
+pub struct WeakHandler {
+    inner: Weak<dyn DownloadEventHandler>,
+    id: HandlerId,
+    registry: Weak<HandlerRegistry>,
+}
+
+impl WeakHandler {
+    /// Forward to `inner` while it lives, then remove registration `id` from `registry`
+    pub fn new(inner: &Arc<dyn DownloadEventHandler>, id: HandlerId, registry: &Arc<HandlerRegistry>) -> Self {
+        Self {
+            inner: Arc::downgrade(inner),
+            id,
+            registry: Arc::downgrade(registry),
+        }
+    }
+
+    /// Check if the wrapped handler was dropped
+    pub fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
+
+    /// Get the handler, removing the registration once it was dropped
+    async fn handler(&self) -> Option<Arc<dyn DownloadEventHandler>> {
+        let handler = self.inner.upgrade();
+        if handler.is_none() {
+            if let Some(registry) = self.registry.upgrade() {
+                if registry.remove(self.id).await {
+                    log::debug!("Removed event handler {}, it was dropped", self.id);
+                }
+            }
+        }
+        handler
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for WeakHandler {
+    async fn on_status_changed(&self, task_id: TaskId, old_status: DownloadStatus, new_status: DownloadStatus) {
+        if let Some(handler) = self.handler().await {
+            handler.on_status_changed(task_id, old_status, new_status).await;
+        }
+    }
+
+    async fn on_progress_updated(&self, task_id: TaskId, progress: DownloadProgress) {
+        if let Some(handler) = self.handler().await {
+            handler.on_progress_updated(task_id, progress).await;
+        }
+    }
+
+    async fn on_download_completed(&self, task_id: TaskId) {
+        if let Some(handler) = self.handler().await {
+            handler.on_download_completed(task_id).await;
+        }
+    }
+
+    async fn on_download_failed(&self, task_id: TaskId, error: String) {
+        if let Some(handler) = self.handler().await {
+            handler.on_download_failed(task_id, error).await;
+        }
+    }
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, delay: Duration) {
+        if let Some(handler) = self.handler().await {
+            handler.on_retry_scheduled(task_id, attempt, delay).await;
+        }
+    }
+
+    async fn on_download_restored(&self, task_id: TaskId, resumed_from: u64) {
+        if let Some(handler) = self.handler().await {
+            handler.on_download_restored(task_id, resumed_from).await;
+        }
+    }
+
+    async fn on_source_changed(&self, task_id: TaskId, restarted_as: TaskId) {
+        if let Some(handler) = self.handler().await {
+            handler.on_source_changed(task_id, restarted_as).await;
+        }
+    }
+
+    async fn on_extraction_progress(&self, task_id: TaskId, extracted_bytes: u64, total_bytes: Option<u64>) {
+        if let Some(handler) = self.handler().await {
+            handler.on_extraction_progress(task_id, extracted_bytes, total_bytes).await;
+        }
+    }
+
+    async fn on_copy_progress(&self, task_id: TaskId, destination: PathBuf, copied_bytes: u64, total_bytes: u64) {
+        if let Some(handler) = self.handler().await {
+            handler.on_copy_progress(task_id, destination, copied_bytes, total_bytes).await;
+        }
+    }
+
+    async fn on_post_processed(&self, task_id: TaskId, path: PathBuf) {
+        if let Some(handler) = self.handler().await {
+            handler.on_post_processed(task_id, path).await;
+        }
+    }
+
+    async fn on_post_processing_failed(&self, task_id: TaskId, hook: String, error: String) {
+        if let Some(handler) = self.handler().await {
+            handler.on_post_processing_failed(task_id, hook, error).await;
+        }
+    }
+
+    async fn on_task_expired(&self, task_id: TaskId, expires_at: SystemTime) {
+        if let Some(handler) = self.handler().await {
+            handler.on_task_expired(task_id, expires_at).await;
+        }
+    }
+
+    async fn on_failure_recorded(&self, task_id: TaskId, failure: FailureInfo) {
+        if let Some(handler) = self.handler().await {
+            handler.on_failure_recorded(task_id, failure).await;
+        }
+    }
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        if let Some(handler) = self.handler().await {
+            handler.on_speed_sample(sample).await;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,456 @@
+//! Hugging Face Hub repositories
+//!
+//! Lists the files of a model, dataset or space repository through the Hub
+//! API and downloads all of them as one grouped download. The revision is
+//! pinned to the commit the listing came from, so a download that is resumed
+//! later still fetches the same files.
+
+use crate::error::DownloadError;
+use crate::groups::{aggregate_progress, aggregate_status, GroupId, TaskGroups};
+use crate::models::{Checksum, ChecksumAlgorithm, Credentials, DownloadOptions, OverwritePolicy};
+use crate::services::partial_download::control_file_path;
+use crate::traits::DownloadManager;
+use crate::types::{DownloadProgress, DownloadStatus, TaskId};
+use crate::utils::paths::enclosed_path;
+use crate::Result;
+use reqwest::header::AUTHORIZATION;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Hub the client talks to by default
+pub const DEFAULT_HF_ENDPOINT: &str = "https://huggingface.co";
+/// Revision downloaded when none is given
+pub const DEFAULT_REVISION: &str = "main";
+/// Environment variable overriding the Hub endpoint, e.g. for a mirror
+pub const ENV_HF_ENDPOINT: &str = "HF_ENDPOINT";
+/// Environment variables holding a Hub access token, in order of precedence
+pub const ENV_HF_TOKEN: [&str; 2] = ["HF_TOKEN", "HUGGING_FACE_HUB_TOKEN"];
+
+/// Kind of repository on the Hub
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HfRepoType {
+    #[default]
+    Model,
+    Dataset,
+    Space,
+}
+
+impl HfRepoType {
+    /// Path segment of the repository kind in API URLs
+    fn api_segment(&self) -> &'static str {
+        match self {
+            HfRepoType::Model => "models",
+            HfRepoType::Dataset => "datasets",
+            HfRepoType::Space => "spaces",
+        }
+    }
+
+    /// Path segment before the repository id in file URLs, models have none
+    fn url_segment(&self) -> Option<&'static str> {
+        match self {
+            HfRepoType::Model => None,
+            HfRepoType::Dataset => Some("datasets"),
+            HfRepoType::Space => Some("spaces"),
+        }
+    }
+}
+
+/// A repository on the Hub at a revision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfRepo {
+    /// Repository id such as `org/model`
+    pub repo_id: String,
+    /// Branch, tag or commit hash
+    pub revision: String,
+    pub repo_type: HfRepoType,
+}
+
+impl HfRepo {
+    pub fn new(repo_id: impl Into<String>, repo_type: HfRepoType) -> Self {
+        Self {
+            repo_id: repo_id.into(),
+            revision: DEFAULT_REVISION.to_string(),
+            repo_type,
+        }
+    }
+
+    /// A model repository at the default revision
+    pub fn model(repo_id: impl Into<String>) -> Self {
+        Self::new(repo_id, HfRepoType::Model)
+    }
+
+    /// A dataset repository at the default revision
+    pub fn dataset(repo_id: impl Into<String>) -> Self {
+        Self::new(repo_id, HfRepoType::Dataset)
+    }
+
+    /// Use a branch, tag or commit hash instead of the default revision
+    pub fn revision(mut self, revision: impl Into<String>) -> Self {
+        self.revision = revision.into();
+        self
+    }
+
+    /// Check that the repository id is `name` or `namespace/name`
+    ///
+    /// Fails with [`DownloadError::InvalidUrl`] otherwise.
+    pub fn validate(&self) -> Result<()> {
+        let valid_part = |part: &str| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+
+        let parts: Vec<&str> = self.repo_id.split('/').collect();
+        if parts.len() > 2 || !parts.iter().all(|part| valid_part(part)) {
+            return Err(DownloadError::InvalidUrl(format!("'{}' is not a valid Hugging Face repository id", self.repo_id)));
+        }
+        if self.revision.is_empty() {
+            return Err(DownloadError::InvalidUrl(format!("No revision given for {}", self.repo_id)));
+        }
+        Ok(())
+    }
+
+    /// Name of the task group holding the download of this repository
+    pub fn group_name(&self) -> String {
+        format!("huggingface:{}/{}@{}", self.repo_type.api_segment(), self.repo_id, self.revision)
+    }
+}
+
+/// A file listed in a repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfFile {
+    /// Path inside the repository, e.g. `onnx/model.onnx`
+    pub path: String,
+    /// Size in bytes, if the Hub reported it
+    pub size: Option<u64>,
+    /// SHA-256 of files stored in Git LFS
+    pub sha256: Option<String>,
+}
+
+/// Files of a repository at a revision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfRepoInfo {
+    /// Commit hash the revision resolved to
+    pub sha: Option<String>,
+    pub files: Vec<HfFile>,
+}
+
+#[derive(Deserialize)]
+struct ApiRepoInfo {
+    sha: Option<String>,
+    #[serde(default)]
+    siblings: Vec<ApiSibling>,
+}
+
+#[derive(Deserialize)]
+struct ApiSibling {
+    rfilename: String,
+    size: Option<u64>,
+    lfs: Option<ApiLfs>,
+}
+
+#[derive(Deserialize)]
+struct ApiLfs {
+    sha256: String,
+    size: Option<u64>,
+}
+
+impl HfRepoInfo {
+    /// Parse the answer of the Hub's repository info endpoint
+    pub fn from_json(json: &str) -> Result<Self> {
+        let info: ApiRepoInfo = serde_json::from_str(json)
+            .map_err(|e| DownloadError::Network(format!("Unexpected Hugging Face API response: {}", e)))?;
+
+        let files = info.siblings.into_iter()
+            .map(|sibling| {
+                let lfs_size = sibling.lfs.as_ref().and_then(|lfs| lfs.size);
+                HfFile {
+                    path: sibling.rfilename,
+                    size: sibling.size.or(lfs_size),
+                    sha256: sibling.lfs.map(|lfs| lfs.sha256.to_lowercase()),
+                }
+            })
+            .collect();
+
+        Ok(Self { sha: info.sha, files })
+    }
+
+    /// Total size of the files, if the Hub reported every size
+    pub fn total_size(&self) -> Option<u64> {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+/// A file of a repository download and the task fetching it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfRepoFile {
+    pub path: String,
+    pub size: Option<u64>,
+    pub task_id: TaskId,
+    pub target_path: PathBuf,
+}
+
+/// A repository download: one parent made of a task per file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HfRepoDownload {
+    /// The repository, with its revision pinned to a commit when the Hub reported one
+    pub repo: HfRepo,
+    /// Directory the repository is downloaded into
+    pub directory: PathBuf,
+    pub files: Vec<HfRepoFile>,
+    /// Task group holding the file tasks, if the download was grouped
+    pub group_id: Option<GroupId>,
+}
+
+impl HfRepoDownload {
+    /// Get the IDs of the file tasks
+    pub fn task_ids(&self) -> Vec<TaskId> {
+        self.files.iter().map(|file| file.task_id).collect()
+    }
+
+    /// Get the combined progress of all files
+    pub async fn progress(&self, manager: &dyn DownloadManager) -> Result<DownloadProgress> {
+        let mut progress = Vec::with_capacity(self.files.len());
+        for file in &self.files {
+            let mut file_progress = manager.get_progress(file.task_id).await?;
+            // Files that have not started yet report no size
+            file_progress.total_bytes = file_progress.total_bytes.or(file.size);
+            progress.push(file_progress);
+        }
+        Ok(aggregate_progress(&progress))
+    }
+
+    /// Get the combined status of all files, see [`aggregate_status`]
+    pub async fn status(&self, manager: &dyn DownloadManager) -> Result<DownloadStatus> {
+        let mut statuses = Vec::with_capacity(self.files.len());
+        for file in &self.files {
+            statuses.push(manager.get_task(file.task_id).await?.status);
+        }
+        Ok(aggregate_status(&statuses))
+    }
+}
+
+/// Client for the Hugging Face Hub
+#[derive(Clone)]
+pub struct HfClient {
+    endpoint: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl fmt::Debug for HfClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HfClient")
+            .field("endpoint", &self.endpoint)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Default for HfClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HfClient {
+    /// Create an anonymous client for the public Hub
+    pub fn new() -> Self {
+        Self {
+            endpoint: DEFAULT_HF_ENDPOINT.to_string(),
+            token: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a client configured by `HF_ENDPOINT` and `HF_TOKEN` (or `HUGGING_FACE_HUB_TOKEN`)
+    pub fn from_env() -> Self {
+        let env_var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        let mut client = Self::new();
+        if let Some(endpoint) = env_var(ENV_HF_ENDPOINT) {
+            client = client.with_endpoint(endpoint);
+        }
+        if let Some(token) = ENV_HF_TOKEN.iter().find_map(|name| env_var(name)) {
+            client = client.with_token(token);
+        }
+        client
+    }
+
+    /// Talk to another Hub, such as a mirror
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Authenticate with an access token, needed for private and gated repositories
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Get the Hub endpoint
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Get the URL of the repository info endpoint
+    pub fn api_url(&self, repo: &HfRepo) -> Result<String> {
+        let mut segments = vec!["api", repo.repo_type.api_segment()];
+        segments.extend(repo.repo_id.split('/'));
+        segments.extend(["revision", repo.revision.as_str()]);
+
+        let mut url = self.url(&segments)?;
+        url.query_pairs_mut().append_pair("blobs", "true");
+        Ok(url.into())
+    }
+
+    /// Get the download URL of a file in a repository
+    pub fn file_url(&self, repo: &HfRepo, path: &str) -> Result<String> {
+        let mut segments: Vec<&str> = repo.repo_type.url_segment().into_iter().collect();
+        segments.extend(repo.repo_id.split('/'));
+        segments.extend(["resolve", repo.revision.as_str()]);
+        segments.extend(path.split('/'));
+
+        Ok(self.url(&segments)?.into())
+    }
+
+    /// Join percent-encoded path segments onto the endpoint
+    fn url(&self, segments: &[&str]) -> Result<Url> {
+        let mut url = Url::parse(&self.endpoint)
+            .map_err(|e| DownloadError::InvalidUrl(format!("'{}' is not a valid Hub endpoint: {}", self.endpoint, e)))?;
+        url.path_segments_mut()
+            .map_err(|_| DownloadError::InvalidUrl(format!("'{}' is not a valid Hub endpoint", self.endpoint)))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// List the files of a repository
+    pub async fn repo_info(&self, repo: &HfRepo) -> Result<HfRepoInfo> {
+        repo.validate()?;
+
+        let mut request = self.http.get(self.api_url(repo)?);
+        if let Some(token) = &self.token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        match response.status() {
+            status if status.is_success() => HfRepoInfo::from_json(&response.text().await?),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(DownloadError::Network(format!(
+                "Access to {} denied, it may be private or gated and need a token", repo.repo_id
+            ))),
+            StatusCode::NOT_FOUND => Err(DownloadError::InvalidUrl(format!(
+                "Repository {} or revision {} not found", repo.repo_id, repo.revision
+            ))),
+            status => Err(DownloadError::Network(format!(
+                "Hugging Face API returned {} for {}", status, repo.repo_id
+            ))),
+        }
+    }
+
+    /// Download every file of a repository into `directory`
+    ///
+    /// Files keep their path inside the repository below `directory`.
+    pub async fn download_repo(&self, manager: &dyn DownloadManager, repo: &HfRepo, directory: &Path) -> Result<HfRepoDownload> {
+        let info = self.repo_info(repo).await?;
+        log::info!("Downloading {} files of {} at {}", info.files.len(), repo.repo_id, repo.revision);
+        self.download_files(manager, repo, &info, directory).await
+    }
+
+    /// Download every file of a repository into `directory` as one task group
+    ///
+    /// The group is named after the repository and revision, see
+    /// [`HfRepo::group_name`]; downloading the same repository again resumes
+    /// into the existing group instead of creating another one.
+    pub async fn download_repo_grouped(&self, groups: &TaskGroups, repo: &HfRepo, directory: &Path) -> Result<HfRepoDownload> {
+        let name = repo.group_name();
+        let info = self.repo_info(repo).await?;
+
+        let group_id = match groups.find_group(&name).await? {
+            Some(group) => group.id,
+            None => groups.create_group(&name).await?,
+        };
+
+        let manager = groups.manager();
+        let mut download = self.download_files(manager.as_ref(), repo, &info, directory).await?;
+        for task_id in download.task_ids() {
+            groups.add_task(group_id, task_id).await?;
+        }
+
+        download.group_id = Some(group_id);
+        Ok(download)
+    }
+
+    /// Add a task for each listed file of a repository
+    ///
+    /// Calling this again resumes the download: files whose task is still known
+    /// reuse it, complete files left on disk are kept and anything else is
+    /// fetched again. A failure leaves the tasks added before it in place, so
+    /// the next call picks them up.
+    pub async fn download_files(
+        &self,
+        manager: &dyn DownloadManager,
+        repo: &HfRepo,
+        info: &HfRepoInfo,
+        directory: &Path,
+    ) -> Result<HfRepoDownload> {
+        repo.validate()?;
+
+        // Pin the revision so a resumed download fetches the same files
+        let mut repo = repo.clone();
+        if let Some(sha) = &info.sha {
+            repo.revision = sha.clone();
+        }
+
+        let mut files = Vec::with_capacity(info.files.len());
+        for file in &info.files {
+            let relative = enclosed_path(&file.path)
+                .filter(|relative| !relative.as_os_str().is_empty())
+                .ok_or_else(|| DownloadError::InvalidPath(format!("{} in {}", file.path, repo.repo_id)))?;
+            let target_path = directory.join(relative);
+
+            let url = self.file_url(&repo, &file.path)?;
+            let options = self.file_options(file, &target_path).await;
+            let task_id = manager.add_download_with_options(url, target_path.clone(), options).await?;
+
+            files.push(HfRepoFile {
+                path: file.path.clone(),
+                size: file.size,
+                task_id,
+                target_path,
+            });
+        }
+
+        Ok(HfRepoDownload {
+            repo,
+            directory: directory.to_path_buf(),
+            files,
+            group_id: None,
+        })
+    }
+
+    /// Options for downloading one file
+    async fn file_options(&self, file: &HfFile, target_path: &Path) -> DownloadOptions {
+        let mut options = DownloadOptions::new().continue_partial(true);
+        if let Some(token) = &self.token {
+            options = options.credentials(Credentials::bearer(token.clone()));
+        }
+        if let Some(sha256) = &file.sha256 {
+            options = options.checksum(Checksum::new(ChecksumAlgorithm::Sha256, sha256.clone()));
+        }
+
+        // A file of the listed size without an aria2 control file was completed earlier
+        let size_on_disk = tokio::fs::metadata(target_path).await.ok().map(|metadata| metadata.len());
+        let complete = file.size.is_some()
+            && size_on_disk == file.size
+            && !tokio::fs::try_exists(control_file_path(target_path)).await.unwrap_or(false);
+
+        options.overwrite(if complete { OverwritePolicy::Skip } else { OverwritePolicy::Overwrite })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,155 @@
+//! Mirror health tracking
+//!
+//! [`MirrorManager`] learns from every download which hosts fail often or
+//! are slow to respond, and orders the sources of multi-source downloads so
+//! the healthiest mirror comes first. aria2 tries the URIs of a download in
+//! the given order, so bad mirrors are only used once the good ones fail.
+//!
+//! Each task is credited to the host of the source it starts with: its time
+//! to the first bytes is the latency sample, and its completion or failure
+//! the outcome.
+
+use crate::models::MirrorStats;
+use crate::types::TaskId;
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime};
+use tokio::sync::RwLock;
+
+/// Weight of a new latency sample in the moving average
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// A task whose outcome counts towards a host
+struct TrackedTask {
+    host: String,
+    /// When the task was added, until its first bytes arrive
+    started: Option<Instant>,
+}
+
+/// Health statistics of mirror hosts, shared by all tasks
+#[derive(Default)]
+pub struct MirrorManager {
+    hosts: RwLock<HashMap<String, MirrorStats>>,
+    tasks: RwLock<HashMap<TaskId, TrackedTask>>,
+}
+
+impl MirrorManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Order `urls` from the healthiest host to the worst
+    ///
+    /// The order of URLs whose hosts score the same is kept, so without any
+    /// history the caller's order is unchanged.
+    pub async fn rank(&self, mut urls: Vec<String>) -> Vec<String> {
+        let hosts = self.hosts.read().await;
+        let score = |url: &String| {
+            host_of(url)
+                .and_then(|host| hosts.get(&host))
+                .map_or_else(|| MirrorStats::new("").score(), MirrorStats::score)
+        };
+
+        urls.sort_by(|a, b| score(b).total_cmp(&score(a)));
+        urls
+    }
+
+    /// Start counting the outcome of a task towards the host of `url`
+    pub async fn track(&self, task_id: TaskId, url: &str) {
+        let Some(host) = host_of(url) else {
+            return;
+        };
+
+        self.tasks.write().await.insert(task_id, TrackedTask {
+            host,
+            started: Some(Instant::now()),
+        });
+    }
+
+    /// Check if a task still waits for its first bytes
+    pub async fn awaits_first_bytes(&self, task_id: TaskId) -> bool {
+        self.tasks.read().await.get(&task_id).is_some_and(|task| task.started.is_some())
+    }
+
+    /// Record the latency of a task once its first bytes arrived
+    pub async fn observe(&self, task_id: TaskId, downloaded_bytes: u64) {
+        if downloaded_bytes == 0 {
+            return;
+        }
+
+        let (host, latency) = {
+            let mut tasks = self.tasks.write().await;
+            let Some(task) = tasks.get_mut(&task_id) else {
+                return;
+            };
+            let Some(started) = task.started.take() else {
+                return;
+            };
+            (task.host.clone(), started.elapsed())
+        };
+
+        let mut hosts = self.hosts.write().await;
+        let stats = hosts.entry(host.clone()).or_insert_with(|| MirrorStats::new(host));
+        stats.average_latency = Some(match stats.average_latency {
+            Some(average) => average.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING),
+            None => latency,
+        });
+    }
+
+    /// Count a completed task as a success of its host and stop tracking it
+    pub async fn task_completed(&self, task_id: TaskId) {
+        let Some(task) = self.tasks.write().await.remove(&task_id) else {
+            return;
+        };
+
+        let mut hosts = self.hosts.write().await;
+        let host = task.host;
+        hosts.entry(host.clone()).or_insert_with(|| MirrorStats::new(host)).successes += 1;
+    }
+
+    /// Count a failed task as a failure of its host
+    ///
+    /// The task stays tracked, a retry that completes still counts as a success.
+    pub async fn task_failed(&self, task_id: TaskId) {
+        let host = {
+            let mut tasks = self.tasks.write().await;
+            let Some(task) = tasks.get_mut(&task_id) else {
+                return;
+            };
+            task.started = None;
+            task.host.clone()
+        };
+
+        let mut hosts = self.hosts.write().await;
+        let stats = hosts.entry(host.clone()).or_insert_with(|| MirrorStats::new(host));
+        stats.failures += 1;
+        stats.last_failure = Some(SystemTime::now());
+    }
+
+    /// Stop tracking a task without counting an outcome
+    pub async fn forget(&self, task_id: TaskId) {
+        self.tasks.write().await.remove(&task_id);
+    }
+
+    /// Get the statistics of every host seen so far, best first
+    pub async fn stats(&self) -> Vec<MirrorStats> {
+        let mut stats: Vec<MirrorStats> = self.hosts.read().await.values().cloned().collect();
+        stats.sort_by(|a, b| b.score().total_cmp(&a.score()).then_with(|| a.host.cmp(&b.host)));
+        stats
+    }
+
+    /// Get the statistics of the host serving `url`
+    pub async fn host_stats(&self, url: &str) -> Option<MirrorStats> {
+        let host = host_of(url)?;
+        self.hosts.read().await.get(&host).cloned()
+    }
+}
+
+/// Get the host of a URL, with the port when it names one
+pub(crate) fn host_of(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    let host = url.host_str()?;
+    Some(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+//! Download sources
+//!
+//! Sources turn a reference to remote content, such as a repository on a
+//! model hub, into the downloads of its files.
+//! [`MirrorManager`] decides which of several sources of a file to try first.
+
+pub mod huggingface;
+pub mod mirrors;
+
+pub use huggingface::{HfClient, HfRepo, HfRepoType, HfRepoDownload};
+pub use mirrors::MirrorManager;
\ No newline at end of file
@@ -0,0 +1,129 @@
+//! Disk space and quota checks run before a download starts
+
+use crate::error::DownloadError;
+use crate::probe::DEFAULT_PROBE_TIMEOUT_SECS;
+use crate::storage::disk::{available_space, directory_size};
+use reqwest::header::CONTENT_LENGTH;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Verifies that downloads fit on disk and within directory quotas
+#[derive(Debug)]
+pub struct StorageChecker {
+    http: reqwest::Client,
+    quotas: RwLock<HashMap<PathBuf, u64>>,
+}
+
+impl Default for StorageChecker {
+    fn default() -> Self {
+        Self::with_timeout(Duration::from_secs(DEFAULT_PROBE_TIMEOUT_SECS))
+    }
+}
+
+impl StorageChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a checker whose size probes give up on servers that take longer than `timeout`
+    ///
+    /// Probes run before every download is added, so an unresponsive server
+    /// must not hold the addition up for longer.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self { http, quotas: RwLock::new(HashMap::new()) }
+    }
+
+    /// Limit the combined size of all files below `directory`
+    pub async fn set_quota(&self, directory: impl Into<PathBuf>, max_bytes: u64) {
+        self.quotas.write().await.insert(normalize(&directory.into()), max_bytes);
+    }
+
+    /// Remove the quota of `directory`
+    pub async fn remove_quota(&self, directory: &Path) {
+        self.quotas.write().await.remove(&normalize(directory));
+    }
+
+    /// Get the quota that applies to `target_path`, if any
+    ///
+    /// When several quota directories contain the path the innermost one wins.
+    pub async fn quota_for(&self, target_path: &Path) -> Option<(PathBuf, u64)> {
+        let target_path = normalize(target_path);
+        let quotas = self.quotas.read().await;
+        quotas.iter()
+            .filter(|(directory, _)| target_path.starts_with(directory))
+            .max_by_key(|(directory, _)| directory.components().count())
+            .map(|(directory, max_bytes)| (directory.clone(), *max_bytes))
+    }
+
+    /// Ask the server for the size of `url` with a HEAD request
+    ///
+    /// Returns `None` when the server doesn't report a length or doesn't
+    /// answer in time.
+    pub async fn probe_size(&self, url: &str) -> Option<u64> {
+        let response = match self.http.head(url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                log::debug!("HEAD {} returned {}", url, response.status());
+                return None;
+            }
+            Err(e) => {
+                log::debug!("HEAD {} failed: {}", url, e);
+                return None;
+            }
+        };
+
+        response.headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+    }
+
+    /// Check that `url` fits at `target_path`, probing its size first
+    ///
+    /// Downloads of unknown size are allowed.
+    pub async fn check(&self, url: &str, target_path: &Path) -> Result<(), DownloadError> {
+        match self.probe_size(url).await {
+            Some(size) => self.check_size(target_path, size).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Check that `size` bytes fit at `target_path`
+    pub async fn check_size(&self, target_path: &Path, size: u64) -> Result<(), DownloadError> {
+        let available = available_space(target_path)?;
+        if size > available {
+            return Err(DownloadError::InsufficientDiskSpace {
+                path: target_path.to_path_buf(),
+                required: size,
+                available,
+            });
+        }
+
+        if let Some((directory, max_bytes)) = self.quota_for(target_path).await {
+            let remaining = max_bytes.saturating_sub(directory_size(&directory).await?);
+            if size > remaining {
+                return Err(DownloadError::QuotaExceeded {
+                    directory,
+                    required: size,
+                    remaining,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Drop `.` components so `./data` and `data` name the same directory
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
\ No newline at end of file
@@ -0,0 +1,40 @@
+//! Disk usage helpers
+
+use std::io;
+use std::path::Path;
+
+/// Get the space available to this process on the filesystem holding `path`
+///
+/// `path` does not need to exist yet; the nearest existing ancestor is used.
+pub fn available_space(path: &Path) -> io::Result<u64> {
+    let existing = path.ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+        .unwrap_or_else(|| Path::new("."));
+
+    fs2::available_space(existing)
+}
+
+/// Get the combined size of all files below `dir` (0 if it doesn't exist)
+pub async fn directory_size(dir: &Path) -> io::Result<u64> {
+    let mut total = 0;
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let mut entries = match tokio::fs::read_dir(&current).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+
+    Ok(total)
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+//! Storage management
+//!
+//! Checks that a download fits on disk before it starts and enforces optional
+//! per-directory quotas so download directories can't grow unbounded.
+
+pub mod disk;
+pub mod checker;
+
+pub use disk::{available_space, directory_size};
+pub use checker::StorageChecker;
\ No newline at end of file
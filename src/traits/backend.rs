@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+use async_trait::async_trait;
+use crate::Result;
+use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask};
+use crate::models::{DownloadOptions, FileProgress};
+
+/// Low-level download engine used by the persistence layer
+///
+/// A backend only moves bytes: it knows nothing about the database,
+/// duplicate detection or recovery. `PersistentAria2Manager` drives any
+/// backend through this trait, so aria2, an in-process HTTP client or a
+/// test double can be plugged in without touching the persistence code.
+#[async_trait]
+pub trait DownloadBackend: Send + Sync {
+    /// Start downloading `url` into `target_path` and return the backend task ID
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId>;
+
+    /// Start a download with per-task options applied by the engine
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId>;
+
+    /// Start a download that may fetch from any of `urls`
+    ///
+    /// The first URL is the primary source; the rest are mirrors the engine may
+    /// fail over to or split segments across.
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId>;
+
+    /// Pause an active download
+    async fn pause(&self, task_id: TaskId) -> Result<()>;
+
+    /// Resume a paused download
+    async fn resume(&self, task_id: TaskId) -> Result<()>;
+
+    /// Stop a download and forget about it
+    async fn cancel(&self, task_id: TaskId) -> Result<()>;
+
+    /// Get current progress for a download
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress>;
+
+    /// Get current task information for a download
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask>;
+
+    /// List all downloads known to the backend
+    async fn list(&self) -> Result<Vec<DownloadTask>>;
+
+    /// List all downloads with their current progress
+    ///
+    /// The default asks for the progress of each download separately;
+    /// engines that can fetch both in one go override it. Downloads whose
+    /// progress is unavailable are listed with empty progress.
+    async fn list_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        let mut snapshot = Vec::new();
+        for task in self.list().await? {
+            let progress = self.progress(task.id).await.unwrap_or_else(|_| DownloadProgress::new());
+            snapshot.push((task, progress));
+        }
+        Ok(snapshot)
+    }
+
+    /// Get number of downloads currently transferring data
+    async fn active_count(&self) -> Result<usize>;
+
+    /// Cap the combined download speed of all transfers (0 = unlimited)
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()>;
+
+    /// Cap the download speed of a single transfer (0 = unlimited)
+    async fn set_task_speed_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()>;
+
+    /// Get the engine's own identifier for a download (the aria2 GID), if it has one
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>>;
+
+    /// Take over a download the engine still holds from a previous run
+    ///
+    /// `task` keeps its ID. Returns `false` when the engine no longer knows
+    /// `engine_id`, in which case the download has to be added again.
+    async fn reattach(&self, task: &DownloadTask, engine_id: &str) -> Result<bool>;
+
+    /// Fetch the rest of a download from `new_url`, keeping its task ID and the bytes already written
+    ///
+    /// `options` are the task's options, with `continue_partial` set, for
+    /// engines that have to add the download again. Returns `false` when the
+    /// engine cannot change the source of a download, which is the default.
+    async fn change_source(&self, _task_id: TaskId, _new_url: &str, _options: &DownloadOptions) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Get the engine's error code for a failed download
+    ///
+    /// `None` when the download did not fail or the engine has no error
+    /// codes, which is the default.
+    async fn error_code(&self, _task_id: TaskId) -> Result<Option<u32>> {
+        Ok(None)
+    }
+
+    /// Get the progress of each file of a download
+    ///
+    /// Engines without multi-file downloads report the download as its only
+    /// file, which is the default.
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        let task = self.task(task_id).await?;
+        let progress = self.progress(task_id).await?;
+        Ok(vec![FileProgress::whole(task.target_path, &progress)])
+    }
+
+    /// Download only the files with the given 1-based indices
+    ///
+    /// Returns `false` when the engine cannot select files, which is the default.
+    async fn select_files(&self, _task_id: TaskId, _indices: &[u32]) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Check if the engine verifies the `checksum` option of a download itself
+    ///
+    /// Downloads of engines that don't, which is the default, have their file
+    /// hashed once they complete.
+    async fn verifies_checksum(&self, _task_id: TaskId) -> bool {
+        false
+    }
+}
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+use crate::Result;
+use crate::models::Credentials;
+
+/// Application callback supplying credentials when a download is handed to the backend
+///
+/// The provider is asked whenever a download without its own credentials is
+/// added and again when it is restored after a restart, so expiring tokens or
+/// signed cookies can be refreshed instead of persisted.
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// Get the credentials for `url`, `None` if it needs none
+    async fn credentials(&self, url: &str) -> Result<Option<Credentials>>;
+}
\ No newline at end of file
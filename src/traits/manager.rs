@@ -1,8 +1,9 @@
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use async_trait::async_trait;
-use anyhow::Result;
+use crate::Result;
 use burncloud_download_types::{TaskId, DownloadProgress, DownloadTask, DownloadStatus};
-use crate::models::{DuplicatePolicy, DuplicateResult};
+use crate::models::{DuplicatePolicy, DuplicateDecision, DuplicateCandidate, DownloadOptions, ListOrder, FailureInfo, SpeedSample, DownloadEvent};
 
 /// Core download manager trait for implementing download backends
 #[async_trait]
@@ -10,6 +11,19 @@ pub trait DownloadManager: Send + Sync {
     /// Add a new download task and return task ID
     async fn add_download(&self, url: String, target_path: PathBuf) -> Result<TaskId>;
 
+    /// Add a new download task with per-task options and return task ID
+    async fn add_download_with_options(
+        &self,
+        url: String,
+        target_path: PathBuf,
+        options: DownloadOptions,
+    ) -> Result<TaskId>;
+
+    /// Add a download that can be fetched from several mirror URLs and return task ID
+    ///
+    /// The first URL is the primary source recorded on the task.
+    async fn add_download_multi_source(&self, urls: Vec<String>, target_path: PathBuf) -> Result<TaskId>;
+
     /// Pause an active download task
     async fn pause_download(&self, task_id: TaskId) -> Result<()>;
 
@@ -19,18 +33,76 @@ pub trait DownloadManager: Send + Sync {
     /// Cancel and remove a download task
     async fn cancel_download(&self, task_id: TaskId) -> Result<()>;
 
+    /// Cancel a download task but keep it with a cancelled status
+    ///
+    /// With `delete_files` the partial download is removed as well. Returns
+    /// `false` when the task was already cancelled or completed. Managers
+    /// that don't keep cancelled tasks remove them like `cancel_download()`.
+    async fn cancel_and_keep(&self, task_id: TaskId, _delete_files: bool) -> Result<bool> {
+        self.cancel_download(task_id).await?;
+        Ok(true)
+    }
+
     /// Get current progress for a download task
     async fn get_progress(&self, task_id: TaskId) -> Result<DownloadProgress>;
 
     /// Get download task information
     async fn get_task(&self, task_id: TaskId) -> Result<DownloadTask>;
 
-    /// List all download tasks
+    /// List all download tasks, oldest first
     async fn list_tasks(&self) -> Result<Vec<DownloadTask>>;
 
+    /// List all download tasks in the given order
+    async fn list_tasks_ordered(&self, order: ListOrder) -> Result<Vec<DownloadTask>> {
+        let mut tasks = self.list_tasks().await?;
+        order.sort(&mut tasks);
+        Ok(tasks)
+    }
+
+    /// List up to `limit` tasks starting at `offset` in the given order
+    ///
+    /// Ties are broken by task ID, so pages neither overlap nor skip tasks
+    /// while the task list doesn't change.
+    async fn list_tasks_page(&self, offset: usize, limit: usize, order: ListOrder) -> Result<Vec<DownloadTask>> {
+        let tasks = self.list_tasks_ordered(order).await?;
+        Ok(tasks.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// List all download tasks together with their current progress
+    ///
+    /// Saves dashboards a `get_progress` call per task. Tasks whose progress
+    /// is unavailable are listed with empty progress.
+    async fn list_tasks_with_progress(&self) -> Result<Vec<(DownloadTask, DownloadProgress)>> {
+        let mut snapshot = Vec::new();
+        for task in self.list_tasks().await? {
+            let progress = self.get_progress(task.id).await.unwrap_or_else(|_| DownloadProgress::new());
+            snapshot.push((task, progress));
+        }
+        Ok(snapshot)
+    }
+
     /// Get number of active downloads
     async fn active_download_count(&self) -> Result<usize>;
 
+    /// Wait until a task completes and return it
+    ///
+    /// Fails with [`DownloadError::DownloadFailed`](crate::DownloadError::DownloadFailed)
+    /// once the task failed with no retry left, [`DownloadError::TaskNotFound`](crate::DownloadError::TaskNotFound)
+    /// when it is cancelled and [`DownloadError::WaitTimeout`](crate::DownloadError::WaitTimeout)
+    /// when `timeout` elapses first.
+    async fn wait_for_completion(&self, task_id: TaskId, timeout: Option<Duration>) -> Result<DownloadTask>;
+
+    // Bulk operations
+
+    /// Pause every task that can be paused and return the IDs of the paused tasks
+    async fn pause_all(&self) -> Result<Vec<TaskId>>;
+
+    /// Resume every paused task and return the IDs of the resumed tasks
+    async fn resume_all(&self) -> Result<Vec<TaskId>>;
+
+    /// Cancel and remove every task and return the IDs of the removed tasks
+    async fn cancel_all(&self) -> Result<Vec<TaskId>>;
+
     // New methods for duplicate detection
 
     /// Find existing task for the same download request
@@ -41,22 +113,63 @@ pub trait DownloadManager: Send + Sync {
     ) -> Result<Option<TaskId>>;
 
     /// Add download with explicit duplicate handling policy
+    ///
+    /// Returns the task serving the request and the decision that selected it.
+    /// A rejected duplicate is returned as [`DownloadError::PolicyViolation`](crate::DownloadError::PolicyViolation).
     async fn add_download_with_policy(
         &self,
         url: &str,
         target_path: &Path,
         policy: DuplicatePolicy,
-    ) -> Result<DuplicateResult>;
+    ) -> Result<(TaskId, DuplicateDecision)>;
 
     /// Verify if existing task is still valid for reuse
     async fn verify_task_validity(&self, task_id: &TaskId) -> Result<bool>;
 
-    /// Get all potential duplicate candidates
+    /// Get all potential duplicate candidates, newest first
     async fn get_duplicate_candidates(
         &self,
         url: &str,
         target_path: &Path,
     ) -> Result<Vec<TaskId>>;
+
+    /// Get the tasks that are potential duplicates, with their creation and update times, in the given order
+    async fn get_duplicate_tasks(
+        &self,
+        url: &str,
+        target_path: &Path,
+        order: ListOrder,
+    ) -> Result<Vec<DownloadTask>> {
+        let mut tasks = Vec::new();
+        for task_id in self.get_duplicate_candidates(url, target_path).await? {
+            tasks.push(self.get_task(task_id).await?);
+        }
+        order.sort(&mut tasks);
+        Ok(tasks)
+    }
+
+    // Bandwidth limiting
+
+    /// Cap the combined download speed of all tasks in bytes per second (0 = unlimited)
+    async fn set_global_download_limit(&self, bytes_per_sec: u64) -> Result<()>;
+
+    /// Cap the download speed of a single task in bytes per second (0 = unlimited)
+    async fn set_task_download_limit(&self, task_id: TaskId, bytes_per_sec: u64) -> Result<()>;
+
+    /// Get the diagnostics of the most recent failure of a task
+    ///
+    /// `None` for tasks that never failed or managers that don't record failures.
+    async fn failure_info(&self, _task_id: TaskId) -> Result<Option<FailureInfo>> {
+        Ok(None)
+    }
+
+    /// Get this manager as a [`PersistentAria2Manager`](crate::PersistentAria2Manager)
+    ///
+    /// Lets the convenience API reach features beyond this trait on a manager
+    /// injected as `Arc<dyn DownloadManager>`. `None` for other managers.
+    fn as_persistent(&self) -> Option<&crate::PersistentAria2Manager> {
+        None
+    }
 }
 
 /// Download event notification trait for implementing observers
@@ -73,4 +186,56 @@ pub trait DownloadEventHandler: Send + Sync {
 
     /// Called when download task fails
     async fn on_download_failed(&self, task_id: TaskId, error: String);
+
+    /// Called when a failed task is scheduled for another attempt after `delay`
+    async fn on_retry_scheduled(&self, _task_id: TaskId, _attempt: u32, _delay: Duration) {}
+
+    /// Called when a task is restored after a restart, resuming at `resumed_from` bytes
+    async fn on_download_restored(&self, _task_id: TaskId, _resumed_from: u64) {}
+
+    /// Called when a task restarted from zero as `restarted_as` because its remote file changed
+    async fn on_source_changed(&self, _task_id: TaskId, _restarted_as: TaskId) {}
+
+    /// Called while the archive of a task is extracted, with the total size when it is known
+    async fn on_extraction_progress(&self, _task_id: TaskId, _extracted_bytes: u64, _total_bytes: Option<u64>) {}
+
+    /// Called while the completed file of a task is copied to `destination`
+    async fn on_copy_progress(&self, _task_id: TaskId, _destination: PathBuf, _copied_bytes: u64, _total_bytes: u64) {}
+
+    /// Called when the post-download hooks of a task finished, leaving the file at `path`
+    async fn on_post_processed(&self, _task_id: TaskId, _path: PathBuf) {}
+
+    /// Called when post-download hook `hook` of a task failed
+    async fn on_post_processing_failed(&self, _task_id: TaskId, _hook: String, _error: String) {}
+
+    /// Called when a task was failed because it had not started by its deadline `expires_at`
+    async fn on_task_expired(&self, _task_id: TaskId, _expires_at: SystemTime) {}
+
+    /// Called when the diagnostics of a task's failure were recorded, right after it failed
+    async fn on_failure_recorded(&self, _task_id: TaskId, _failure: FailureInfo) {}
+
+    /// Called at a fixed cadence with the speeds of all active tasks and their totals
+    async fn on_speed_sample(&self, _sample: SpeedSample) {}
+}
+
+/// Single-callback observer receiving every notification as a [`DownloadEvent`]
+///
+/// Simpler to forward over channels than [`DownloadEventHandler`]. Managers
+/// deliver to listeners through a [`ListenerHandler`](crate::services::ListenerHandler).
+#[async_trait]
+pub trait DownloadEventListener: Send + Sync {
+    /// Called for every download notification
+    async fn on_event(&self, event: DownloadEvent);
+}
+
+/// Application callback for duplicates under [`DuplicatePolicy::PromptUser`]
+#[async_trait]
+pub trait DuplicateDecisionHandler: Send + Sync {
+    /// Decide how to handle a request for `url` -> `target_path` that matches `candidates`
+    async fn decide(
+        &self,
+        url: &str,
+        target_path: &Path,
+        candidates: &[DuplicateCandidate],
+    ) -> DuplicateDecision;
 }
\ No newline at end of file
@@ -1,3 +1,9 @@
 pub mod manager;
+pub mod backend;
+pub mod credentials;
+pub mod queue_scheduler;
 
-pub use manager::{DownloadManager, DownloadEventHandler};
\ No newline at end of file
+pub use manager::{DownloadManager, DownloadEventHandler, DownloadEventListener, DuplicateDecisionHandler};
+pub use backend::DownloadBackend;
+pub use credentials::CredentialProvider;
+pub use queue_scheduler::{QueueScheduler, QueuedTask};
\ No newline at end of file
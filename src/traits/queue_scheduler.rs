@@ -0,0 +1,25 @@
+use crate::models::Priority;
+use burncloud_download_types::DownloadTask;
+
+/// A waiting or running task as seen by a [`QueueScheduler`]
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedTask<'a> {
+    pub task: &'a DownloadTask,
+    pub priority: Priority,
+    /// Group or tenant the task belongs to, `None` for ungrouped tasks
+    pub group: Option<&'a str>,
+}
+
+/// Strategy deciding which waiting task gets the next free download slot
+///
+/// `TaskQueueManager` keeps waiting tasks in the order they were queued and
+/// asks its scheduler whenever a slot frees up, so fairness between users or
+/// groups can be changed without touching the queue itself.
+pub trait QueueScheduler: Send + Sync {
+    /// Pick the task to start next and return its index in `candidates`
+    ///
+    /// `candidates` are in queue order and only contain tasks the global and
+    /// per-host limits allow to start; returning `None` leaves them waiting.
+    /// `active` are the tasks currently downloading.
+    fn next(&self, candidates: &[QueuedTask<'_>], active: &[QueuedTask<'_>]) -> Option<usize>;
+}
\ No newline at end of file
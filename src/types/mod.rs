@@ -1,6 +1,7 @@
 pub mod task;
 pub mod progress;
 pub mod status;
+pub mod serialize;
 
 // Re-export types from burncloud-download-types for backwards compatibility
 pub use burncloud_download_types::{DownloadTask, TaskId, DownloadProgress, DownloadStatus};
\ No newline at end of file
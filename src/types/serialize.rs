@@ -0,0 +1,248 @@
+//! Stable serde formats of the core download types
+//!
+//! `DownloadTask`, `DownloadProgress` and `DownloadStatus` come from
+//! `burncloud-download-types`, so their representation is fixed here
+//! instead. Each module is meant for `#[serde(with = "...")]`:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Snapshot {
+//!     #[serde(with = "burncloud_download::types::serialize::download_task")]
+//!     task: DownloadTask,
+//! }
+//! ```
+//!
+//! The formats match the control server:
+//!
+//! - status: `{"status": "failed", "error": "404"}`, where `status` is one of
+//!   [`STATUS_NAMES`] and `error` is only present for failed tasks. Tasks
+//!   failed with [`CANCELLED_REASON`] are `"cancelled"` without an error.
+//! - progress: `downloaded_bytes`, `total_bytes`, `speed_bps` and `eta_seconds`,
+//!   the sizes `null` while unknown.
+//! - task: `id`, `url`, `target_path`, the status fields, and `created_at` /
+//!   `updated_at` in milliseconds since the Unix epoch.
+
+use crate::models::CANCELLED_REASON;
+use crate::types::{DownloadTask, DownloadProgress, DownloadStatus, TaskId};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Every value of the `status` field
+pub const STATUS_NAMES: [&str; 6] = ["waiting", "downloading", "paused", "completed", "failed", "cancelled"];
+
+/// Get the `status` name and `error` of a status
+pub fn status_fields(status: &DownloadStatus) -> (&'static str, Option<&str>) {
+    match status {
+        DownloadStatus::Waiting => ("waiting", None),
+        DownloadStatus::Downloading => ("downloading", None),
+        DownloadStatus::Paused => ("paused", None),
+        DownloadStatus::Completed => ("completed", None),
+        DownloadStatus::Failed(error) if error == CANCELLED_REASON => ("cancelled", None),
+        DownloadStatus::Failed(error) => ("failed", Some(error)),
+    }
+}
+
+/// Build a status from its `status` name and `error`, `None` for unknown names
+pub fn status_from_fields(name: &str, error: Option<String>) -> Option<DownloadStatus> {
+    let status = match name {
+        "waiting" => DownloadStatus::Waiting,
+        "downloading" => DownloadStatus::Downloading,
+        "paused" => DownloadStatus::Paused,
+        "completed" => DownloadStatus::Completed,
+        "failed" => DownloadStatus::Failed(error.unwrap_or_default()),
+        "cancelled" => DownloadStatus::Failed(CANCELLED_REASON.to_string()),
+        _ => return None,
+    };
+    Some(status)
+}
+
+#[derive(Serialize, Deserialize)]
+struct StatusRepr {
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl StatusRepr {
+    fn new(status: &DownloadStatus) -> Self {
+        let (name, error) = status_fields(status);
+        Self {
+            status: name.to_string(),
+            error: error.map(str::to_string),
+        }
+    }
+
+    fn into_status<E: serde::de::Error>(self) -> Result<DownloadStatus, E> {
+        status_from_fields(&self.status, self.error)
+            .ok_or_else(|| E::unknown_variant(&self.status, &STATUS_NAMES))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProgressRepr {
+    downloaded_bytes: u64,
+    total_bytes: Option<u64>,
+    speed_bps: u64,
+    eta_seconds: Option<u64>,
+}
+
+impl ProgressRepr {
+    fn new(progress: &DownloadProgress) -> Self {
+        Self {
+            downloaded_bytes: progress.downloaded_bytes,
+            total_bytes: progress.total_bytes,
+            speed_bps: progress.speed_bps,
+            eta_seconds: progress.eta_seconds,
+        }
+    }
+
+    fn into_progress(self) -> DownloadProgress {
+        let mut progress = DownloadProgress::new();
+        progress.downloaded_bytes = self.downloaded_bytes;
+        progress.total_bytes = self.total_bytes;
+        progress.speed_bps = self.speed_bps;
+        progress.eta_seconds = self.eta_seconds;
+        progress
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskRepr {
+    id: TaskId,
+    url: String,
+    target_path: PathBuf,
+    #[serde(flatten)]
+    status: StatusRepr,
+    #[serde(with = "system_time_millis")]
+    created_at: SystemTime,
+    #[serde(with = "system_time_millis")]
+    updated_at: SystemTime,
+}
+
+/// `DownloadStatus` as `{"status": ..., "error": ...}`
+pub mod download_status {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(status: &DownloadStatus, serializer: S) -> Result<S::Ok, S::Error> {
+        StatusRepr::new(status).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DownloadStatus, D::Error> {
+        StatusRepr::deserialize(deserializer)?.into_status()
+    }
+}
+
+/// `DownloadProgress` with its four public fields
+pub mod download_progress {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(progress: &DownloadProgress, serializer: S) -> Result<S::Ok, S::Error> {
+        ProgressRepr::new(progress).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DownloadProgress, D::Error> {
+        Ok(ProgressRepr::deserialize(deserializer)?.into_progress())
+    }
+}
+
+/// `DownloadTask` with its status fields inlined
+pub mod download_task {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(task: &DownloadTask, serializer: S) -> Result<S::Ok, S::Error> {
+        TaskRepr {
+            id: task.id,
+            url: task.url.clone(),
+            target_path: task.target_path.clone(),
+            status: StatusRepr::new(&task.status),
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+        }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DownloadTask, D::Error> {
+        let repr = TaskRepr::deserialize(deserializer)?;
+        let mut task = DownloadTask::new(repr.url, repr.target_path);
+        task.id = repr.id;
+        task.status = repr.status.into_status()?;
+        task.created_at = repr.created_at;
+        task.updated_at = repr.updated_at;
+        Ok(task)
+    }
+}
+
+/// `Vec<DownloadTask>` in the format of [`download_task`]
+pub mod download_tasks {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Task(#[serde(with = "download_task")] DownloadTask);
+
+    pub fn serialize<S: Serializer>(tasks: &[DownloadTask], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(tasks.iter().map(|task| Task(task.clone())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<DownloadTask>, D::Error> {
+        Ok(Vec::<Task>::deserialize(deserializer)?.into_iter().map(|task| task.0).collect())
+    }
+}
+
+/// `SystemTime` in milliseconds since the Unix epoch, earlier times as zero
+pub mod system_time_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let millis = time.duration_since(UNIX_EPOCH).map(|age| age.as_millis() as u64).unwrap_or(0);
+        serializer.serialize_u64(millis)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        Ok(UNIX_EPOCH + Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// `Duration` in whole milliseconds
+pub mod duration_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// `Option<SystemTime>` in milliseconds since the Unix epoch
+pub mod option_system_time_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => system_time_millis::serialize(time, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<SystemTime>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(|millis| UNIX_EPOCH + Duration::from_millis(millis)))
+    }
+}
+
+/// `Option<Duration>` in whole milliseconds
+pub mod option_duration_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => duration_millis::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
\ No newline at end of file
@@ -2,3 +2,4 @@
 // ID utilities moved to burncloud-download-types
 
 pub mod url_normalization;
+pub mod paths;
\ No newline at end of file
@@ -0,0 +1,140 @@
+//! Target path helpers
+//!
+//! Path normalization and the keys target paths are compared under, the `file (n).ext`
+//! naming used when a download must not replace an existing file, and moving
+//! files between file systems.
+
+use std::path::{Component, Path, PathBuf};
+
+/// Highest suffix tried when picking a free name
+pub const MAX_RENAME_ATTEMPTS: u32 = 9999;
+
+/// Insert ` (n)` before the extension: `file.zip` -> `file (1).zip`
+pub fn renamed_path(target_path: &Path, n: u32) -> PathBuf {
+    let stem = target_path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match target_path.extension() {
+        Some(extension) => format!("{} ({}).{}", stem, n, extension.to_string_lossy()),
+        None => format!("{} ({})", stem, n),
+    };
+    target_path.with_file_name(file_name)
+}
+
+/// Find the first `file (n).ext` variant of `target_path` for which `is_taken` is false
+pub fn first_free_renamed_path(target_path: &Path, is_taken: impl Fn(&Path) -> bool) -> Option<PathBuf> {
+    (1..=MAX_RENAME_ATTEMPTS)
+        .map(|n| renamed_path(target_path, n))
+        .find(|candidate| !is_taken(candidate))
+}
+
+/// Absolute, lexically cleaned form of a path for comparing target paths
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Key target paths are compared and indexed under
+///
+/// The [`normalize_path`] form of `path`, on Windows additionally reduced to
+/// its [`windows_path_key`] so `C:\Data\file`, `c:/data/file` and
+/// `\\?\C:\Data\file` are the same target.
+pub fn path_key(path: &Path) -> PathBuf {
+    let normalized = normalize_path(path);
+    if cfg!(windows) {
+        PathBuf::from(windows_path_key(&normalized.to_string_lossy()))
+    } else {
+        normalized
+    }
+}
+
+/// Comparison form of a Windows path
+///
+/// Both separators become `\`, empty, `.` and `..` segments are resolved
+/// without climbing above the drive or share, the `\\?\` prefix is dropped
+/// and the path is lowercased, as Windows file systems ignore case.
+pub fn windows_path_key(path: &str) -> String {
+    let path = path.replace('/', "\\");
+    let path = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        path
+    };
+
+    let (prefix, rest) = if let Some(rest) = path.strip_prefix(r"\\") {
+        (r"\\", rest)
+    } else if let Some(rest) = path.strip_prefix('\\') {
+        ("\\", rest)
+    } else {
+        ("", path.as_str())
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in rest.split('\\') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                // The share of a UNC path and a drive are never left
+                let root = if prefix == r"\\" {
+                    2
+                } else if segments.first().is_some_and(|first| first.ends_with(':')) {
+                    1
+                } else {
+                    0
+                };
+                if segments.len() > root {
+                    segments.pop();
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut key = format!("{}{}", prefix, segments.join("\\"));
+    if segments.len() == 1 && segments[0].ends_with(':') {
+        key.push('\\');
+    }
+    key.to_lowercase()
+}
+
+/// Move a file to `to`, copying it when `to` is on another file system
+pub async fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    // Renaming fails across file systems, fall back to copying
+    if tokio::fs::rename(from, to).await.is_err() {
+        tokio::fs::copy(from, to).await?;
+        tokio::fs::remove_file(from).await?;
+    }
+    Ok(())
+}
+
+/// Turn a relative path from an archive or remote listing into a local one
+///
+/// Returns `None` if the path is absolute or climbs out of its directory with `..`.
+pub fn enclosed_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(path)
+}
\ No newline at end of file
@@ -0,0 +1,203 @@
+//! Tests for injecting the manager behind the global convenience API
+//!
+//! The global manager is process-wide state, so these tests live in their own
+//! binary and take a lock while they use it.
+
+#![cfg(feature = "test-util")]
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use async_trait::async_trait;
+use tokio::sync::{Mutex, RwLock};
+
+use burncloud_download::{DownloadError, Result, PersistentAria2Manager, PersistentAria2ManagerBuilder, TaskQueueManager};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{DownloadOptions, DuplicatePolicy};
+use burncloud_download::types::{TaskId, DownloadTask, DownloadProgress, DownloadStatus};
+
+/// In-memory backend that records tasks without transferring anything
+#[derive(Default)]
+struct MemoryBackend {
+    tasks: RwLock<HashMap<TaskId, DownloadTask>>,
+}
+
+#[async_trait]
+impl DownloadBackend for MemoryBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        let mut task = DownloadTask::new(url, target_path);
+        task.update_status(DownloadStatus::Downloading);
+        let task_id = task.id;
+        self.tasks.write().await.insert(task_id, task);
+        Ok(task_id)
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        self.add(url, target_path).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        let url = urls.into_iter().next()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        self.add(url, target_path).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.set_status(task_id, DownloadStatus::Paused).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.set_status(task_id, DownloadStatus::Downloading).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.tasks.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.task(task_id).await?;
+        Ok(DownloadProgress::new())
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.tasks.read().await.get(&task_id).cloned()
+            .ok_or(DownloadError::TaskNotFound(task_id))
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        Ok(self.tasks.read().await.values().cloned().collect())
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        Ok(self.tasks.read().await.values().filter(|t| t.status.is_active()).count())
+    }
+
+    async fn set_global_speed_limit(&self, _bytes_per_sec: u64) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, _bytes_per_sec: u64) -> Result<()> {
+        self.task(task_id).await.map(|_| ())
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.task(task_id).await.map(|_| Some(task_id.to_string()))
+    }
+
+    async fn reattach(&self, _task: &DownloadTask, _engine_id: &str) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+impl MemoryBackend {
+    async fn set_status(&self, task_id: TaskId, status: DownloadStatus) -> Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id)
+            .ok_or(DownloadError::TaskNotFound(task_id))?;
+        task.update_status(status);
+        Ok(())
+    }
+}
+
+// Tests share the global manager, so only one of them may use it at a time
+static GLOBAL: Mutex<()> = Mutex::const_new(());
+
+// Nothing listens on the discard port, so size probes fail right away
+const URL: &str = "http://127.0.0.1:9/file.zip";
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_global_{}_{}", name, std::process::id()))
+}
+
+fn builder(dir: &Path) -> PersistentAria2ManagerBuilder {
+    PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::default()))
+        .download_dir(dir)
+        .ephemeral(true)
+}
+
+#[tokio::test]
+async fn test_convenience_api_uses_injected_manager() {
+    let _guard = GLOBAL.lock().await;
+    burncloud_download::reset_global_manager_for_tests().await;
+    let dir = test_dir("injected");
+
+    let manager = Arc::new(builder(&dir).build().await.unwrap());
+    burncloud_download::init_global_manager(manager.clone()).await.unwrap();
+
+    let task_id = burncloud_download::download_to(URL, dir.join("file.zip")).await.unwrap();
+    assert_eq!(manager.get_task(task_id).await.unwrap().url, URL);
+    assert_eq!(burncloud_download::get_download_task(task_id).await.unwrap().url, URL);
+
+    burncloud_download::reset_global_manager_for_tests().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_init_fails_when_manager_is_running() {
+    let _guard = GLOBAL.lock().await;
+    burncloud_download::reset_global_manager_for_tests().await;
+    let dir = test_dir("running");
+
+    burncloud_download::with_global_manager(builder(&dir)).await.unwrap();
+
+    let second = Arc::new(builder(&dir).build().await.unwrap());
+    let result = burncloud_download::init_global_manager(second.clone()).await;
+    assert!(matches!(result, Err(DownloadError::Config(_))));
+    let result = burncloud_download::with_global_manager(builder(&dir)).await;
+    assert!(matches!(result, Err(DownloadError::Config(_))));
+
+    // The rejected manager was never installed
+    let task_id = burncloud_download::download_to(URL, dir.join("file.zip")).await.unwrap();
+    assert!(second.get_task(task_id).await.is_err());
+
+    second.shutdown().await.unwrap();
+    burncloud_download::reset_global_manager_for_tests().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_reset_allows_new_manager() {
+    let _guard = GLOBAL.lock().await;
+    burncloud_download::reset_global_manager_for_tests().await;
+    let dir = test_dir("reset");
+
+    let first = Arc::new(builder(&dir).build().await.unwrap());
+    burncloud_download::init_global_manager(first.clone()).await.unwrap();
+    burncloud_download::set_default_duplicate_policy(DuplicatePolicy::AllowDuplicate);
+    burncloud_download::reset_global_manager_for_tests().await;
+
+    assert!(matches!(burncloud_download::default_duplicate_policy(), DuplicatePolicy::ReuseExisting));
+
+    let second = Arc::new(builder(&dir).build().await.unwrap());
+    burncloud_download::init_global_manager(second.clone()).await.unwrap();
+
+    let task_id = burncloud_download::download_to(URL, dir.join("file.zip")).await.unwrap();
+    assert!(second.get_task(task_id).await.is_ok());
+    assert!(first.get_task(task_id).await.is_err());
+
+    burncloud_download::reset_global_manager_for_tests().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_convenience_api_accepts_any_manager() {
+    let _guard = GLOBAL.lock().await;
+    burncloud_download::reset_global_manager_for_tests().await;
+    let dir = test_dir("queue");
+
+    let manager = Arc::new(TaskQueueManager::new());
+    burncloud_download::init_global_manager(manager.clone()).await.unwrap();
+
+    let task_id = burncloud_download::download_to(URL, dir.join("file.zip")).await.unwrap();
+    assert_eq!(manager.get_task(task_id).await.unwrap().url, URL);
+    assert_eq!(burncloud_download::list_downloads().await.unwrap().len(), 1);
+
+    // Features of the persistent manager are reported as unavailable
+    let result = burncloud_download::subscribe_events().await;
+    assert!(matches!(result, Err(DownloadError::Config(_))));
+
+    burncloud_download::reset_global_manager_for_tests().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
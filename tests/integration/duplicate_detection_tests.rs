@@ -5,7 +5,7 @@
 
 use burncloud_download::manager::persistent_aria2::PersistentAria2Manager;
 use burncloud_download::services::duplicate_detector::DuplicateDetector;
-use burncloud_download::models::{DuplicateResult, DuplicateReason};
+use burncloud_download::models::DuplicateReason;
 use burncloud_download::types::{TaskId, DownloadStatus};
 use std::path::Path;
 use tempfile::TempDir;
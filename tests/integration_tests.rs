@@ -440,11 +440,9 @@ mod error_handling_tests {
             "".to_string(),
             PathBuf::from("/tmp/empty-url.zip")
         ).await;
-        // Should succeed (URL validation is not in scope of this manager)
-        assert!(result.is_ok());
-        if let Ok(task_id) = result {
-            manager.cancel_download(task_id).await.expect("Failed to cancel");
-        }
+        // Empty URLs are rejected before a task is created
+        assert!(matches!(result, Err(burncloud_download::DownloadError::InvalidUrl(_))));
+        assert!(manager.list_tasks().await.unwrap().is_empty());
 
         // Test with empty path
         let result = manager.add_download(
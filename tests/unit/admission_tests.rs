@@ -0,0 +1,118 @@
+//! Unit tests for admission control of new downloads
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::models::{AdmissionLimits, AdmissionMode, RetryPolicy};
+use burncloud_download::services::AdmissionControl;
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_admission_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+fn url(n: usize) -> String {
+    format!("http://127.0.0.1:9/model-{}.bin", n)
+}
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf, limits: AdmissionLimits) -> PersistentAria2Manager {
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .admission_limits(limits)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    manager
+}
+
+#[test]
+fn test_task_limit_counts_pending_additions() {
+    let control = AdmissionControl::new(AdmissionLimits::new().max_queued_tasks(2));
+
+    let first = control.try_admit(0).unwrap();
+    let second = control.try_admit(0).unwrap();
+    assert_eq!(control.pending(), 2);
+    assert!(control.try_admit(0).is_err());
+
+    drop(first);
+    drop(second);
+    assert_eq!(control.pending(), 0);
+    assert!(control.try_admit(1).is_ok());
+    assert!(control.try_admit(2).is_err());
+}
+
+#[test]
+fn test_rate_limit_uses_a_sliding_second() {
+    let control = AdmissionControl::new(AdmissionLimits::new().max_additions_per_second(2));
+    let start = Instant::now();
+
+    assert!(control.try_admit_at(0, start).is_ok());
+    assert!(control.try_admit_at(0, start + Duration::from_millis(400)).is_ok());
+    let refusal = control.try_admit_at(0, start + Duration::from_millis(600)).unwrap_err();
+    assert_eq!(refusal.retry_after, Duration::from_millis(400));
+
+    // The first addition left the window
+    assert!(control.try_admit_at(0, start + Duration::from_millis(1000)).is_ok());
+    assert!(control.try_admit_at(0, start + Duration::from_millis(1100)).is_err());
+}
+
+#[test]
+fn test_no_limits_admit_everything() {
+    let control = AdmissionControl::new(AdmissionLimits::default());
+    assert!(!control.limits().is_limited());
+    for queued in 0..1000 {
+        assert!(control.try_admit(queued).is_ok());
+    }
+}
+
+#[tokio::test]
+async fn test_full_queue_rejects_additions() {
+    let dir = test_dir("reject");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, AdmissionLimits::new().max_queued_tasks(1)).await;
+
+    manager.add_download(url(1), dir.join("model-1.bin")).await.unwrap();
+    match manager.add_download(url(2), dir.join("model-2.bin")).await {
+        Err(DownloadError::QueueFull(_)) => {}
+        other => panic!("Expected a full queue, got {:?}", other),
+    }
+
+    // Lifting the limit at runtime admits the addition
+    manager.set_admission_limits(AdmissionLimits::default());
+    manager.add_download(url(2), dir.join("model-2.bin")).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_mode_holds_additions_back() {
+    let dir = test_dir("wait");
+    let backend = Arc::new(MemoryBackend::default());
+    let limits = AdmissionLimits::new().max_queued_tasks(1).mode(AdmissionMode::Wait);
+    let manager = Arc::new(manager(backend.clone(), &dir, limits).await);
+
+    let first = manager.add_download(url(1), dir.join("model-1.bin")).await.unwrap();
+    let waiting = {
+        let manager = manager.clone();
+        let dir = dir.clone();
+        tokio::spawn(async move { manager.add_download(url(2), dir.join("model-2.bin")).await })
+    };
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(!waiting.is_finished());
+
+    // A finished task makes room
+    backend.fail(first, "The response status is not successful. status=404", 3).await;
+    let second = tokio::time::timeout(Duration::from_secs(2), waiting).await.unwrap().unwrap().unwrap();
+    assert_ne!(second, first);
+}
\ No newline at end of file
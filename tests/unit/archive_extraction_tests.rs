@@ -0,0 +1,146 @@
+//! Unit tests for archive extraction
+
+use burncloud_download::{DownloadError, DownloadEvent, EventBus, TaskId};
+use burncloud_download::hooks::{ArchiveFormat, ExtractArchive, HookContext, PostDownloadHook};
+use burncloud_download::hooks::extract::{default_extract_dir, extract_archive};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_extract_{}_{}", name, std::process::id()))
+}
+
+fn fresh_dir(name: &str) -> PathBuf {
+    let dir = unique_temp_dir(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (name, data) in entries {
+        zip.start_file(*name, zip::write::FileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+fn write_tar_gz(path: &Path, entries: &[(&str, &[u8])]) {
+    let encoder = flate2::write::GzEncoder::new(std::fs::File::create(path).unwrap(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    for (name, data) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, name, *data).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap();
+}
+
+#[test]
+fn test_detect_archive_format() {
+    assert_eq!(ArchiveFormat::detect(Path::new("data/model.zip")), Some(ArchiveFormat::Zip));
+    assert_eq!(ArchiveFormat::detect(Path::new("data/model.TAR.GZ")), Some(ArchiveFormat::TarGz));
+    assert_eq!(ArchiveFormat::detect(Path::new("data/model.tgz")), Some(ArchiveFormat::TarGz));
+    assert_eq!(ArchiveFormat::detect(Path::new("data/model.7z")), Some(ArchiveFormat::SevenZ));
+    assert_eq!(ArchiveFormat::detect(Path::new("data/model.gguf")), None);
+}
+
+#[test]
+fn test_default_extract_dir() {
+    assert_eq!(default_extract_dir(Path::new("data/model.tar.gz"), ArchiveFormat::TarGz), PathBuf::from("data/model"));
+    assert_eq!(default_extract_dir(Path::new("data/Model.ZIP"), ArchiveFormat::Zip), PathBuf::from("data/Model"));
+    assert_eq!(default_extract_dir(Path::new("data/weights.7z"), ArchiveFormat::SevenZ), PathBuf::from("data/weights"));
+}
+
+#[test]
+fn test_extract_zip_reports_progress() {
+    let dir = fresh_dir("zip");
+    let archive = dir.join("bundle.zip");
+    write_zip(&archive, &[("a.txt", b"hello"), ("nested/b.txt", b"world!")]);
+
+    let mut reports = Vec::new();
+    let destination = dir.join("out");
+    let extracted = extract_archive(&archive, ArchiveFormat::Zip, &destination, |done, total| reports.push((done, total))).unwrap();
+
+    assert_eq!(extracted, 11);
+    assert_eq!(reports, vec![(5, Some(11)), (11, Some(11))]);
+    assert_eq!(std::fs::read(destination.join("a.txt")).unwrap(), b"hello");
+    assert_eq!(std::fs::read(destination.join("nested/b.txt")).unwrap(), b"world!");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_extract_tar_gz() {
+    let dir = fresh_dir("tar");
+    let archive = dir.join("bundle.tar.gz");
+    write_tar_gz(&archive, &[("a.txt", b"hello"), ("nested/b.txt", b"world!")]);
+
+    let mut reports = Vec::new();
+    let destination = dir.join("out");
+    let extracted = extract_archive(&archive, ArchiveFormat::TarGz, &destination, |done, total| reports.push((done, total))).unwrap();
+
+    assert_eq!(extracted, 11);
+    assert_eq!(reports, vec![(5, None), (11, None)]);
+    assert_eq!(std::fs::read(destination.join("nested/b.txt")).unwrap(), b"world!");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_corrupt_archive_fails() {
+    let dir = fresh_dir("corrupt");
+    let archive = dir.join("broken.zip");
+    std::fs::write(&archive, b"not a zip file").unwrap();
+
+    let result = extract_archive(&archive, ArchiveFormat::Zip, &dir.join("out"), |_, _| {});
+    assert!(matches!(result, Err(DownloadError::ExtractionFailed(_))));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_extract_hook_publishes_progress_events() {
+    let dir = fresh_dir("hook");
+    let archive = dir.join("bundle.zip");
+    write_zip(&archive, &[("a.txt", b"hello")]);
+
+    let bus = Arc::new(EventBus::default());
+    let mut events = bus.subscribe_events();
+    let task_id = TaskId::new();
+
+    let hook = ExtractArchive::new().with_events(bus.clone());
+    let mut context = HookContext::new(task_id, "https://example.com/bundle.zip".to_string(), archive.clone());
+    hook.run(&mut context).await.unwrap();
+
+    // The archive stays where it was for the hooks after extraction
+    assert_eq!(context.path, archive);
+    assert!(dir.join("bundle").join("a.txt").exists());
+    match events.try_recv().unwrap() {
+        DownloadEvent::ExtractionProgress { task_id: id, extracted_bytes, total_bytes } => {
+            assert_eq!(id, task_id);
+            assert_eq!(extracted_bytes, 5);
+            assert_eq!(total_bytes, Some(5));
+        }
+        other => panic!("Expected extraction progress, got {:?}", other),
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_extract_hook_skips_other_files() {
+    let dir = fresh_dir("skip");
+    let file = dir.join("model.gguf");
+    std::fs::write(&file, b"weights").unwrap();
+
+    let mut context = HookContext::new(TaskId::new(), "https://example.com/model.gguf".to_string(), file);
+    ExtractArchive::new().destination(dir.join("out")).run(&mut context).await.unwrap();
+    assert!(!dir.join("out").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,53 @@
+//! Unit tests for aria2 WebSocket notifications
+
+use burncloud_download::backend::aria2_notifications::{websocket_url, Aria2Event, Aria2Notification, Aria2Notifications};
+use tokio::time::Duration;
+
+#[test]
+fn test_parse_download_notifications() {
+    let message = r#"{"jsonrpc":"2.0","method":"aria2.onDownloadComplete","params":[{"gid":"2089b05ecca3d829"}]}"#;
+    let notification = Aria2Notification::parse(message).unwrap();
+    assert_eq!(notification.gid, "2089b05ecca3d829");
+    assert_eq!(notification.event, Aria2Event::Complete);
+
+    let message = r#"{"jsonrpc":"2.0","method":"aria2.onDownloadError","params":[{"gid":"abc"}]}"#;
+    assert_eq!(Aria2Notification::parse(message).unwrap().event, Aria2Event::Error);
+}
+
+#[test]
+fn test_parse_ignores_responses_and_unknown_methods() {
+    // Responses to RPC calls share the connection
+    assert!(Aria2Notification::parse(r#"{"jsonrpc":"2.0","id":"1","result":"OK"}"#).is_none());
+    assert!(Aria2Notification::parse(r#"{"method":"aria2.onSomethingNew","params":[{"gid":"abc"}]}"#).is_none());
+    assert!(Aria2Notification::parse(r#"{"method":"aria2.onDownloadStart","params":[]}"#).is_none());
+    assert!(Aria2Notification::parse("not json").is_none());
+}
+
+#[test]
+fn test_event_methods() {
+    assert_eq!(Aria2Event::from_method("aria2.onDownloadStart"), Some(Aria2Event::Start));
+    assert_eq!(Aria2Event::from_method("aria2.onDownloadPause"), Some(Aria2Event::Pause));
+    assert_eq!(Aria2Event::from_method("aria2.onDownloadStop"), Some(Aria2Event::Stop));
+    assert_eq!(Aria2Event::from_method("aria2.onBtDownloadComplete"), Some(Aria2Event::BtComplete));
+    assert_eq!(Aria2Event::from_method("aria2.addUri"), None);
+}
+
+#[test]
+fn test_websocket_url_from_rpc_url() {
+    assert_eq!(websocket_url("http://localhost:6800/jsonrpc").as_deref(), Some("ws://localhost:6800/jsonrpc"));
+    assert_eq!(websocket_url("https://aria2.example.com/jsonrpc").as_deref(), Some("wss://aria2.example.com/jsonrpc"));
+    assert_eq!(websocket_url("ws://localhost:6800/jsonrpc").as_deref(), Some("ws://localhost:6800/jsonrpc"));
+    assert_eq!(websocket_url("ftp://localhost/jsonrpc"), None);
+    assert_eq!(websocket_url("not a url"), None);
+}
+
+#[tokio::test]
+async fn test_listener_stays_disconnected_without_aria2() {
+    // Nothing listens on port 1, the listener keeps retrying in the background
+    let (notifications, _receiver) = Aria2Notifications::listen("ws://127.0.0.1:1/jsonrpc");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!notifications.is_connected());
+
+    notifications.stop();
+    assert!(!notifications.is_connected());
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+//! Unit tests for aria2 RPC result parsing
+
+use burncloud_download::Aria2GlobalStats;
+use serde_json::json;
+
+#[test]
+fn test_global_stats_parse_string_numbers() {
+    let stats = Aria2GlobalStats::from_value(&json!({
+        "downloadSpeed": "1048576",
+        "uploadSpeed": "0",
+        "numActive": "2",
+        "numWaiting": "5",
+        "numStopped": "3",
+        "numStoppedTotal": "12",
+    }));
+
+    assert_eq!(stats, Aria2GlobalStats {
+        download_speed: 1_048_576,
+        upload_speed: 0,
+        num_active: 2,
+        num_waiting: 5,
+        num_stopped: 3,
+        num_stopped_total: 12,
+    });
+}
+
+#[test]
+fn test_global_stats_default_missing_fields() {
+    let stats = Aria2GlobalStats::from_value(&json!({ "numActive": "1", "numWaiting": 4 }));
+
+    assert_eq!(stats.num_active, 1);
+    assert_eq!(stats.num_waiting, 0);
+    assert_eq!(stats.download_speed, 0);
+}
\ No newline at end of file
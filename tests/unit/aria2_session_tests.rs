@@ -0,0 +1,79 @@
+//! Unit tests for aria2 session files
+
+use burncloud_download::backend::aria2_session::{Aria2Session, SessionEntry};
+use burncloud_download::{DownloadOptions, Checksum, ChecksumAlgorithm};
+use std::path::{Path, PathBuf};
+
+const SESSION: &str = "# saved by aria2
+https://example.com/model.bin\thttps://mirror.example.com/model.bin
+ gid=2089b05ecca3d829
+ dir=/downloads
+ out=model.bin
+ pause=true
+ header=X-Token: abc
+ header=Cookie: session=1
+ max-download-limit=2M
+ checksum=sha-256=ABCDEF
+ split=4
+
+https://example.com/files/archive.zip
+";
+
+#[test]
+fn test_parse_session() {
+    let session = Aria2Session::parse(SESSION).unwrap();
+    assert_eq!(session.entries.len(), 2);
+
+    let model = &session.entries[0];
+    assert_eq!(model.uris.len(), 2);
+    assert_eq!(model.gid(), Some("2089b05ecca3d829"));
+    assert!(model.is_paused());
+    assert_eq!(model.target_path(), Some(PathBuf::from("/downloads/model.bin")));
+
+    let archive = &session.entries[1];
+    assert_eq!(archive.gid(), None);
+    assert!(!archive.is_paused());
+    assert_eq!(archive.target_path(), Some(PathBuf::from("archive.zip")));
+}
+
+#[test]
+fn test_session_options_become_download_options() {
+    let session = Aria2Session::parse(SESSION).unwrap();
+    let options = session.entries[0].download_options();
+
+    assert_eq!(options.headers, vec![("X-Token".to_string(), "abc".to_string())]);
+    assert_eq!(options.cookies.as_deref(), Some("session=1"));
+    assert_eq!(options.speed_limit, Some(2 * 1024 * 1024));
+    assert_eq!(options.checksum, Some(Checksum::new(ChecksumAlgorithm::Sha256, "abcdef")));
+    assert_eq!(options.segments, Some(4));
+}
+
+#[test]
+fn test_invalid_sessions_are_rejected() {
+    assert!(Aria2Session::parse(" gid=2089b05ecca3d829\n").is_err());
+    assert!(Aria2Session::parse("https://example.com/a.zip\n not-an-option\n").is_err());
+}
+
+#[test]
+fn test_download_roundtrip() {
+    let options = DownloadOptions::new()
+        .header("X-Token", "abc")
+        .speed_limit(4096)
+        .checksum(Checksum::new(ChecksumAlgorithm::Md5, "0123"));
+    let entry = SessionEntry::from_download(
+        vec!["https://example.com/a.zip".to_string()],
+        Path::new("/downloads/a.zip"),
+        Some("d270c8a39a0ab2f0"),
+        true,
+        &options,
+    );
+
+    let parsed = Aria2Session::parse(&Aria2Session::new(vec![entry.clone()]).to_string()).unwrap();
+
+    assert_eq!(parsed.entries, vec![entry]);
+    let restored = parsed.entries[0].download_options();
+    assert_eq!(restored.headers, options.headers);
+    assert_eq!(restored.speed_limit, Some(4096));
+    assert_eq!(restored.checksum, options.checksum);
+    assert_eq!(parsed.entries[0].target_path(), Some(PathBuf::from("/downloads/a.zip")));
+}
\ No newline at end of file
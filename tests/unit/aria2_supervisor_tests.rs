@@ -0,0 +1,58 @@
+//! Unit tests for aria2 process supervision helpers
+
+use std::path::PathBuf;
+use burncloud_download::SupervisorConfig;
+use burncloud_download::aria2_supervisor::parse_session;
+
+#[test]
+fn test_command_args_enable_rpc() {
+    let config = SupervisorConfig {
+        rpc_port: 6900,
+        secret: "token".to_string(),
+        session_file: PathBuf::from("/nonexistent/aria2.session"),
+        extra_args: vec!["--max-connection-per-server=4".to_string()],
+        ..SupervisorConfig::default()
+    };
+
+    let args = config.command_args();
+    assert!(args.contains(&"--enable-rpc=true".to_string()));
+    assert!(args.contains(&"--rpc-listen-port=6900".to_string()));
+    assert!(args.contains(&"--rpc-secret=token".to_string()));
+    assert!(args.contains(&"--save-session=/nonexistent/aria2.session".to_string()));
+    assert_eq!(args.last().unwrap(), "--max-connection-per-server=4");
+
+    // A missing session file must not be passed as input
+    assert!(!args.iter().any(|arg| arg.starts_with("--input-file")));
+
+    assert_eq!(config.rpc_url(), "http://127.0.0.1:6900/jsonrpc");
+}
+
+#[test]
+fn test_parse_session_entries() {
+    let content = "\
+https://example.com/a.zip\thttps://mirror.example.com/a.zip
+ gid=2089b05ecca3d829
+ dir=/downloads
+ out=a.zip
+https://example.com/b.iso
+ gid=d270c8a39a0ab2f0
+ pause=true
+";
+
+    let entries = parse_session(content);
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].uris.len(), 2);
+    assert_eq!(entries[0].gid.as_deref(), Some("2089b05ecca3d829"));
+    assert_eq!(entries[0].target_path(), Some(PathBuf::from("/downloads/a.zip")));
+    assert!(!entries[0].paused);
+
+    assert_eq!(entries[1].uris, vec!["https://example.com/b.iso".to_string()]);
+    assert!(entries[1].paused);
+    assert_eq!(entries[1].target_path(), None);
+}
+
+#[test]
+fn test_parse_empty_session() {
+    assert!(parse_session("").is_empty());
+}
\ No newline at end of file
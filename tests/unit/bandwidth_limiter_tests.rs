@@ -0,0 +1,60 @@
+//! Unit tests for BandwidthLimiter
+
+use std::path::PathBuf;
+use burncloud_download::services::BandwidthLimiter;
+use burncloud_download::{BasicDownloadManager, DownloadManager, TaskId};
+
+#[tokio::test]
+async fn test_unlimited_by_default() {
+    let limiter = BandwidthLimiter::new();
+    let task_id = TaskId::new();
+
+    assert_eq!(limiter.global_limit().await, 0);
+    assert_eq!(limiter.task_limit(task_id).await, 0);
+    assert_eq!(limiter.effective_limit(task_id, 3).await, 0);
+}
+
+#[tokio::test]
+async fn test_global_limit_is_shared_between_active_tasks() {
+    let limiter = BandwidthLimiter::new();
+    let task_id = TaskId::new();
+
+    limiter.set_global_limit(3000).await;
+    assert_eq!(limiter.effective_limit(task_id, 3).await, 1000);
+    assert_eq!(limiter.effective_limit(task_id, 0).await, 3000);
+}
+
+#[tokio::test]
+async fn test_stricter_limit_wins() {
+    let limiter = BandwidthLimiter::new();
+    let task_id = TaskId::new();
+
+    limiter.set_global_limit(3000).await;
+    limiter.set_task_limit(task_id, 500).await;
+    assert_eq!(limiter.effective_limit(task_id, 1).await, 500);
+
+    limiter.set_task_limit(task_id, 5000).await;
+    assert_eq!(limiter.effective_limit(task_id, 1).await, 3000);
+
+    // Zero clears the task limit
+    limiter.set_task_limit(task_id, 0).await;
+    assert_eq!(limiter.task_limit(task_id).await, 0);
+}
+
+#[tokio::test]
+async fn test_basic_manager_applies_task_limit_on_resume() {
+    let manager = BasicDownloadManager::new();
+    let task_id = manager.add_download(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    manager.set_task_download_limit(task_id, 1024).await.unwrap();
+    manager.pause_download(task_id).await.unwrap();
+    manager.resume_download(task_id).await.unwrap();
+
+    let progress = manager.get_progress(task_id).await.unwrap();
+    assert_eq!(progress.speed_bps, 1024);
+
+    assert!(manager.set_task_download_limit(TaskId::new(), 1024).await.is_err());
+}
@@ -0,0 +1,23 @@
+//! Unit tests for the blocking API
+
+use burncloud_download::blocking;
+
+#[test]
+fn test_shutdown_without_manager() {
+    // Nothing was started, so there is nothing to flush
+    blocking::shutdown().unwrap();
+    blocking::shutdown().unwrap();
+}
+
+#[test]
+fn test_calls_from_several_threads_share_the_runtime() {
+    let threads: Vec<_> = (0..4).map(|_| std::thread::spawn(blocking::shutdown)).collect();
+    for thread in threads {
+        thread.join().unwrap().unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_panics_inside_async_runtime() {
+    assert!(std::panic::catch_unwind(blocking::shutdown).is_err());
+}
\ No newline at end of file
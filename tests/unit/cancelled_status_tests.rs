@@ -0,0 +1,168 @@
+//! Unit tests for keeping cancelled tasks with a Cancelled status
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager, TaskStatus};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{FailureInfo, FailureKind, GcPolicy, RetryPolicy, CANCELLED_REASON};
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_cancelled_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    manager
+}
+
+async fn wait_for_status(manager: &PersistentAria2Manager, task_id: TaskId, expected: TaskStatus) {
+    for _ in 0..100 {
+        if manager.task_status(task_id).await.unwrap() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Task {} did not reach {:?}, it is {:?}", task_id, expected, manager.task_status(task_id).await.unwrap());
+}
+
+#[test]
+fn test_cancelled_status_round_trip() {
+    let status = TaskStatus::Cancelled.to_download_status();
+    assert_eq!(status, DownloadStatus::Failed(CANCELLED_REASON.to_string()));
+    assert_eq!(TaskStatus::from_download_status(status), TaskStatus::Cancelled);
+    assert!(TaskStatus::Cancelled.can_transition_to_duplicate());
+
+    assert_eq!(FailureInfo::new(CANCELLED_REASON, None).kind, FailureKind::Cancelled);
+}
+
+#[tokio::test]
+async fn test_cancel_and_keep_retains_task() {
+    let dir = test_dir("keep");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.set_status(task_id, DownloadStatus::Downloading).await.unwrap();
+    wait_for_status(&manager, task_id, TaskStatus::Downloading).await;
+
+    assert!(manager.cancel_and_keep(task_id, false).await.unwrap());
+    assert!(backend.task(task_id).await.is_err());
+
+    // The task is still known, unlike one that never existed
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::Cancelled);
+    let task = manager.get_task(task_id).await.unwrap();
+    assert_eq!(task.status, DownloadStatus::Failed(CANCELLED_REASON.to_string()));
+    let failure = manager.failure_info(task_id).await.unwrap().unwrap();
+    assert_eq!(failure.kind, FailureKind::Cancelled);
+
+    // Cancelling again changes nothing
+    assert!(!manager.cancel_and_keep(task_id, false).await.unwrap());
+    assert_eq!(manager.failure_history(task_id, usize::MAX).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_cancelled_task_is_not_reused() {
+    let dir = test_dir("readd");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    assert!(manager.cancel_and_keep(task_id, false).await.unwrap());
+
+    // The same request starts a new download instead of returning the cancelled task
+    let new_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    assert_ne!(new_id, task_id);
+    assert!(backend.task(new_id).await.is_ok());
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_cancelled_task_is_not_collected_as_orphan() {
+    let dir = test_dir("gc");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    assert!(manager.cancel_and_keep(task_id, true).await.unwrap());
+
+    // No backend entry and no file, but the task is history rather than an orphan
+    manager.set_gc_policy(GcPolicy::new()).await;
+    let report = manager.run_gc().await.unwrap();
+    assert!(report.removed.is_empty());
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_cancel_and_keep_unknown_task() {
+    let dir = test_dir("unknown");
+    let manager = manager(Arc::new(MemoryBackend::default()), &dir).await;
+
+    let result = manager.cancel_and_keep(TaskId::new(), false).await;
+    assert!(matches!(result, Err(DownloadError::TaskNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_completed_task_is_not_cancelled() {
+    let dir = test_dir("completed");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+    wait_for_status(&manager, task_id, TaskStatus::Completed).await;
+
+    assert!(!manager.cancel_and_keep(task_id, true).await.unwrap());
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_cancel_and_keep_deletes_partial_files() {
+    let dir = test_dir("files");
+    std::fs::create_dir_all(&dir).unwrap();
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    // Downloads are written to a temporary file next to the target by default
+    let partial = dir.join("model.bin.part");
+    let control = dir.join("model.bin.part.aria2");
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(&partial, b"partial").unwrap();
+    std::fs::write(&control, b"control").unwrap();
+
+    assert!(manager.cancel_and_keep(task_id, true).await.unwrap());
+    assert!(!partial.exists());
+    assert!(!control.exists());
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn test_cancel_download_removes_kept_task() {
+    let dir = test_dir("remove");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    assert!(manager.cancel_and_keep(task_id, false).await.unwrap());
+
+    manager.cancel_download(task_id).await.unwrap();
+    assert!(matches!(manager.get_task(task_id).await, Err(DownloadError::TaskNotFound(_))));
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+//! Unit tests for checking the checksum of a completed download
+//!
+//! The manager runs on an in-memory backend, which doesn't check checksums
+//! itself, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{Checksum, ChecksumAlgorithm, DownloadError, DownloadOptions, PersistentAria2Manager, TaskStatus};
+use burncloud_download::hooks::{HookContext, PostDownloadHook, VerifyChecksum};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+const WEIGHTS_SHA256: &str = "a2d42c4aa884e21216cbb8da4c7ba2fcf9b6033b2331666e666145c24caf7a38";
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_checksum_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+#[tokio::test]
+async fn test_matching_file_passes() {
+    let dir = test_dir("match");
+    let path = dir.join("model.bin");
+    std::fs::write(&path, b"model weights").unwrap();
+
+    // Digests are compared regardless of case
+    let hook = VerifyChecksum::new(Checksum {
+        algorithm: ChecksumAlgorithm::Sha256,
+        value: WEIGHTS_SHA256.to_uppercase(),
+    });
+    let mut context = HookContext::new(TaskId::new(), URL.to_string(), path);
+    hook.run(&mut context).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_mismatching_file_fails() {
+    let dir = test_dir("mismatch");
+    let path = dir.join("model.bin");
+    std::fs::write(&path, b"garbage").unwrap();
+
+    let hook = VerifyChecksum::new(Checksum::new(ChecksumAlgorithm::Sha256, WEIGHTS_SHA256));
+    let mut context = HookContext::new(TaskId::new(), URL.to_string(), path);
+    match hook.run(&mut context).await {
+        Err(DownloadError::ChecksumMismatch { expected, actual }) => {
+            assert_eq!(expected, WEIGHTS_SHA256);
+            assert_ne!(actual, WEIGHTS_SHA256);
+        }
+        other => panic!("Expected a checksum mismatch, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_backend_without_checksums_gets_file_checked() {
+    let dir = test_dir("backend");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend.clone())
+        .download_dir(&dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+
+    let options = DownloadOptions::new().checksum(Checksum::new(ChecksumAlgorithm::Sha256, WEIGHTS_SHA256));
+    let task_id = manager.add_download_with_options(URL.to_string(), dir.join("model.bin"), options).await.unwrap();
+    assert!(!backend.verifies_checksum(task_id).await);
+    std::fs::write(dir.join("model.bin.part"), b"garbage").unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+
+    for _ in 0..100 {
+        if let TaskStatus::PostProcessingFailed(reason) = manager.task_status(task_id).await.unwrap() {
+            assert!(reason.starts_with("verify-checksum:"), "unexpected reason: {}", reason);
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Checksum of task {} was not checked", task_id);
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+//! Unit tests for awaiting task completion
+
+use burncloud_download::{BasicDownloadManager, DownloadError, DownloadManager, DownloadStatus, DownloadTask, RetryPolicy, TaskQueueManager};
+use burncloud_download::services::{CompletionWaiters, TaskOutcome};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+#[tokio::test]
+async fn test_resolve_wakes_every_waiter() {
+    let waiters = Arc::new(CompletionWaiters::new());
+    let mut task = DownloadTask::new("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip"));
+    let task_id = task.id;
+
+    let first = waiters.subscribe(task_id).await;
+    let second = waiters.subscribe(task_id).await;
+    assert!(waiters.is_waiting(task_id).await);
+
+    task.update_status(DownloadStatus::Completed);
+    waiters.resolve(task_id, TaskOutcome::Completed(task)).await;
+    assert!(!waiters.is_waiting(task_id).await);
+
+    assert_eq!(first.wait(None, None).await.unwrap().id, task_id);
+    assert_eq!(second.wait(None, None).await.unwrap().id, task_id);
+}
+
+#[tokio::test]
+async fn test_current_outcome_resolves_at_once() {
+    let waiters = CompletionWaiters::new();
+    let mut task = DownloadTask::new("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip"));
+    task.update_status(DownloadStatus::Failed("404".to_string()));
+
+    let receiver = waiters.subscribe(task.id).await;
+    let result = receiver.wait(TaskOutcome::of(&task), None).await;
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { reason, .. }) if reason == "404"));
+}
+
+#[tokio::test]
+async fn test_queue_manager_waits_for_completion() {
+    let manager = Arc::new(TaskQueueManager::new());
+    let task_id = manager.add_task("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip")).await.unwrap();
+
+    let waiter = {
+        let manager = manager.clone();
+        tokio::spawn(async move { manager.wait_for_completion(task_id, None).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    manager.complete_task(task_id).await.unwrap();
+
+    let task = waiter.await.unwrap().unwrap();
+    assert_eq!(task.status, DownloadStatus::Completed);
+
+    // Finished tasks resolve right away
+    assert!(manager.wait_for_completion(task_id, None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_queue_manager_reports_final_failure() {
+    let manager = Arc::new(TaskQueueManager::new());
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    let task_id = manager.add_task("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip")).await.unwrap();
+
+    let waiter = {
+        let manager = manager.clone();
+        tokio::spawn(async move { manager.wait_for_completion(task_id, None).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    manager.fail_task(task_id, "connection reset".to_string()).await.unwrap();
+
+    let result = waiter.await.unwrap();
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { task_id: failed, .. }) if failed == task_id));
+}
+
+#[tokio::test]
+async fn test_cancelled_task_ends_the_wait() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let task_id = manager.add_download("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip")).await.unwrap();
+
+    let waiter = {
+        let manager = manager.clone();
+        tokio::spawn(async move { manager.wait_for_completion(task_id, None).await })
+    };
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    manager.cancel_download(task_id).await.unwrap();
+
+    assert!(matches!(waiter.await.unwrap(), Err(DownloadError::TaskNotFound(id)) if id == task_id));
+}
+
+#[tokio::test]
+async fn test_wait_times_out() {
+    let manager = BasicDownloadManager::new();
+    let task_id = manager.add_download("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip")).await.unwrap();
+
+    let result = manager.wait_for_completion(task_id, Some(Duration::from_millis(50))).await;
+    assert!(matches!(result, Err(DownloadError::WaitTimeout { .. })));
+
+    let unknown = manager.wait_for_completion(burncloud_download::TaskId::new(), None).await;
+    assert!(matches!(unknown, Err(DownloadError::TaskNotFound(_))));
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+//! Unit tests for conditional download outcomes
+
+use burncloud_download::{ConditionalDownload, TaskId};
+
+#[test]
+fn test_started_carries_task_id() {
+    let task_id = TaskId::new();
+    assert_eq!(ConditionalDownload::Started(task_id).task_id(), Some(task_id));
+    assert_eq!(ConditionalDownload::UpToDate.task_id(), None);
+}
+
+#[test]
+fn test_serialization_roundtrip() {
+    let outcome = ConditionalDownload::Started(TaskId::new());
+    let json = serde_json::to_string(&outcome).unwrap();
+    assert_eq!(serde_json::from_str::<ConditionalDownload>(&json).unwrap(), outcome);
+
+    let json = serde_json::to_string(&ConditionalDownload::UpToDate).unwrap();
+    assert_eq!(json, "\"UpToDate\"");
+}
\ No newline at end of file
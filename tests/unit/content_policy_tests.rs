@@ -0,0 +1,66 @@
+//! Unit tests for the content type and extension policy
+
+use burncloud_download::{ContentPolicy, DownloadError};
+use std::path::Path;
+
+#[test]
+fn test_empty_policy_allows_everything() {
+    let policy = ContentPolicy::new();
+    assert!(policy.is_empty());
+    assert!(!policy.checks_mime_types());
+    assert!(policy.check_mime_type("application/x-msdownload").is_ok());
+    assert!(policy.check_filename(Path::new("/downloads/setup.exe")).is_ok());
+    assert!(policy.check_filename(Path::new("/downloads/README")).is_ok());
+}
+
+#[test]
+fn test_blocked_mime_types() {
+    let policy = ContentPolicy::new().block_mime_types(["application/x-msdownload", "Video/*"]);
+    assert!(policy.checks_mime_types());
+
+    assert!(matches!(
+        policy.check_mime_type("application/x-msdownload"),
+        Err(DownloadError::ContentNotAllowed(_))
+    ));
+    assert!(policy.check_mime_type("video/mp4").is_err());
+    assert!(policy.check_mime_type("application/zip").is_ok());
+}
+
+#[test]
+fn test_allowed_mime_types_ignore_parameters() {
+    let policy = ContentPolicy::new().allow_mime_types(["text/plain", "image/*"]);
+
+    assert!(policy.check_mime_type("text/plain; charset=utf-8").is_ok());
+    assert!(policy.check_mime_type("IMAGE/PNG").is_ok());
+    assert!(policy.check_mime_type("text/html").is_err());
+}
+
+#[test]
+fn test_block_wins_over_allow() {
+    let policy = ContentPolicy::new()
+        .allow_mime_types(["image/*"])
+        .block_mime_types(["image/svg+xml"]);
+
+    assert!(policy.check_mime_type("image/png").is_ok());
+    assert!(policy.check_mime_type("image/svg+xml").is_err());
+}
+
+#[test]
+fn test_extensions() {
+    let blocked = ContentPolicy::new().block_extensions([".exe", "BAT"]);
+    assert!(blocked.check_filename(Path::new("/downloads/setup.EXE")).is_err());
+    assert!(blocked.check_filename(Path::new("/downloads/run.bat")).is_err());
+    assert!(blocked.check_filename(Path::new("/downloads/model.bin")).is_ok());
+    assert!(blocked.check_filename(Path::new("/downloads/README")).is_ok());
+
+    let allowed = ContentPolicy::new().allow_extensions(["safetensors", "json"]);
+    assert!(allowed.check_filename(Path::new("/models/model.safetensors")).is_ok());
+    assert!(allowed.check_filename(Path::new("/models/model.safetensors.exe")).is_err());
+    assert!(allowed.check_filename(Path::new("/models/README")).is_err());
+}
+
+#[test]
+fn test_serde_defaults_missing_lists() {
+    let policy: ContentPolicy = serde_json::from_str(r#"{"blocked_extensions": ["exe"]}"#).unwrap();
+    assert_eq!(policy, ContentPolicy::new().block_extensions(["exe"]));
+}
\ No newline at end of file
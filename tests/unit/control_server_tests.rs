@@ -0,0 +1,126 @@
+//! Unit tests for the HTTP control server
+
+use burncloud_download::server::ControlServer;
+use burncloud_download::server::wire::{event_json, parse_task_id, task_id_json};
+use burncloud_download::{BasicDownloadManager, DownloadEvent, DownloadManager, SpeedSample, TaskSpeed, TaskId};
+use serde_json::Value;
+use std::sync::Arc;
+
+async fn start(server: ControlServer) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, server.router()).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+#[test]
+fn test_task_ids_roundtrip_through_urls() {
+    let task_id = TaskId::new();
+    let raw = task_id_json(task_id);
+
+    assert_eq!(parse_task_id(raw.as_str().unwrap()).unwrap(), task_id);
+    assert!(parse_task_id("not-a-task").is_err());
+}
+
+#[test]
+fn test_event_names_and_payloads() {
+    let task_id = TaskId::new();
+    let (name, data) = event_json(&DownloadEvent::Failed { task_id, error: "404".to_string() });
+
+    assert_eq!(name, "failed");
+    assert_eq!(data["task_id"], task_id_json(task_id));
+    assert_eq!(data["error"], "404");
+}
+
+#[test]
+fn test_speed_sample_event_payload() {
+    let task_id = TaskId::new();
+    let sample = SpeedSample::new(std::time::SystemTime::now(), vec![TaskSpeed { task_id, speed_bps: 4096, average_speed_bps: 2048 }]);
+    let (name, data) = event_json(&DownloadEvent::SpeedSample { sample });
+
+    assert_eq!(name, "speed");
+    assert!(data.get("task_id").is_none());
+    assert_eq!(data["total_speed_bps"], 4096);
+    assert_eq!(data["total_average_speed_bps"], 2048);
+    assert_eq!(data["tasks"][0]["task_id"], task_id_json(task_id));
+    assert_eq!(data["tasks"][0]["speed_bps"], 4096);
+}
+
+#[tokio::test]
+async fn test_add_pause_and_cancel_over_http() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let base = start(ControlServer::new(manager.clone())).await;
+    let client = reqwest::Client::new();
+
+    let added: Value = client.post(format!("{}/tasks", base))
+        .json(&serde_json::json!({ "url": "https://example.com/file.zip", "target_path": "data/file.zip" }))
+        .send().await.unwrap()
+        .json().await.unwrap();
+    let id = added["task_id"].as_str().unwrap().to_string();
+
+    let task: Value = client.get(format!("{}/tasks/{}", base, id)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(task["url"], "https://example.com/file.zip");
+
+    let paused = client.post(format!("{}/tasks/{}/pause", base, id)).send().await.unwrap();
+    assert!(paused.status().is_success());
+    let task: Value = client.get(format!("{}/tasks/{}", base, id)).send().await.unwrap().json().await.unwrap();
+    assert_eq!(task["status"], "paused");
+
+    let cancelled = client.delete(format!("{}/tasks/{}", base, id)).send().await.unwrap();
+    assert!(cancelled.status().is_success());
+    let missing = client.get(format!("{}/tasks/{}", base, id)).send().await.unwrap();
+    assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_token_is_required() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let base = start(ControlServer::new(manager).with_token("secret")).await;
+    let client = reqwest::Client::new();
+
+    let denied = client.get(format!("{}/tasks", base)).send().await.unwrap();
+    assert_eq!(denied.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let allowed = client.get(format!("{}/tasks", base)).bearer_auth("secret").send().await.unwrap();
+    assert!(allowed.status().is_success());
+}
+
+#[tokio::test]
+async fn test_events_need_a_bus() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let base = start(ControlServer::new(manager)).await;
+
+    let response = reqwest::get(format!("{}/events", base)).await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_snapshot_lists_tasks_with_progress() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let task_id = manager.add_download("https://example.com/file.zip".to_string(), "data/file.zip".into()).await.unwrap();
+    let base = start(ControlServer::new(manager)).await;
+
+    let snapshot: Value = reqwest::get(format!("{}/snapshot", base)).await.unwrap().json().await.unwrap();
+    let tasks = snapshot.as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["id"], task_id_json(task_id));
+    assert!(tasks[0]["progress"]["downloaded_bytes"].is_u64());
+}
+
+#[tokio::test]
+async fn test_tasks_are_paged() {
+    let manager = Arc::new(BasicDownloadManager::new());
+    for i in 0..3 {
+        manager.add_download(format!("https://example.com/file{}.zip", i), format!("data/file{}.zip", i).into()).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    let base = start(ControlServer::new(manager)).await;
+
+    let page: Value = reqwest::get(format!("{}/tasks?offset=1&limit=1&order=CreatedAsc", base)).await.unwrap().json().await.unwrap();
+    let tasks = page.as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["url"], "https://example.com/file1.zip");
+}
\ No newline at end of file
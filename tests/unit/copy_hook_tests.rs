@@ -0,0 +1,100 @@
+//! Unit tests for copying completed downloads to further directories
+
+use burncloud_download::{DownloadEvent, EventBus, TaskId};
+use burncloud_download::hooks::{CopyStatus, CopyToDirectories, CopyTracker, HookContext, PostDownloadHook};
+use burncloud_download::models::DownloadOptions;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn fresh_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_copy_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_copy_to_collects_directories() {
+    let options = DownloadOptions::new()
+        .copy_to("/cache")
+        .copy_to("/mnt/shared");
+    assert_eq!(options.copy_to, vec![PathBuf::from("/cache"), PathBuf::from("/mnt/shared")]);
+    assert_ne!(options, DownloadOptions::new());
+}
+
+#[tokio::test]
+async fn test_copy_writes_every_destination() {
+    let dir = fresh_dir("every");
+    let file = dir.join("model.bin");
+    std::fs::write(&file, b"weights").unwrap();
+
+    let tracker = Arc::new(CopyTracker::new());
+    let bus = Arc::new(EventBus::default());
+    let mut events = bus.subscribe_events();
+    let task_id = TaskId::new();
+
+    let hook = CopyToDirectories::new([dir.join("cache"), dir.join("shared")])
+        .with_tracker(tracker.clone())
+        .with_events(bus.clone());
+    let mut context = HookContext::new(task_id, "https://example.com/model.bin".to_string(), file.clone());
+    hook.run(&mut context).await.unwrap();
+
+    // The original stays where it was
+    assert_eq!(context.path, file);
+    assert_eq!(std::fs::read(dir.join("cache").join("model.bin")).unwrap(), b"weights");
+    assert_eq!(std::fs::read(dir.join("shared").join("model.bin")).unwrap(), b"weights");
+    assert!(!dir.join("cache").join("model.bin.copying").exists());
+
+    let copies = tracker.copies(task_id).await;
+    assert_eq!(copies.len(), 2);
+    for copy in &copies {
+        assert_eq!(copy.status, CopyStatus::Completed);
+        assert_eq!((copy.copied_bytes, copy.total_bytes), (7, 7));
+    }
+
+    match events.try_recv().unwrap() {
+        DownloadEvent::CopyProgress { task_id: id, destination, copied_bytes, total_bytes } => {
+            assert_eq!(id, task_id);
+            assert_eq!(destination, dir.join("cache").join("model.bin"));
+            assert_eq!((copied_bytes, total_bytes), (7, 7));
+        }
+        other => panic!("Expected copy progress, got {:?}", other),
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_failed_copy_fails_hook_and_retry_skips_finished_copies() {
+    let dir = fresh_dir("failed");
+    let file = dir.join("model.bin");
+    std::fs::write(&file, b"weights").unwrap();
+    // A file where the directory should be makes the second copy fail
+    std::fs::write(dir.join("blocked"), b"").unwrap();
+
+    let tracker = Arc::new(CopyTracker::new());
+    let task_id = TaskId::new();
+    let hook = CopyToDirectories::new([dir.join("cache"), dir.join("blocked")])
+        .with_tracker(tracker.clone());
+    let mut context = HookContext::new(task_id, "https://example.com/model.bin".to_string(), file.clone());
+
+    assert!(hook.run(&mut context).await.is_err());
+    let copies = tracker.copies(task_id).await;
+    assert_eq!(copies[0].status, CopyStatus::Completed);
+    assert!(matches!(copies[1].status, CopyStatus::Failed(_)));
+
+    // Once the destination is usable, only the failed copy is made
+    std::fs::remove_file(dir.join("blocked")).unwrap();
+    let cached = dir.join("cache").join("model.bin");
+    let modified = std::fs::metadata(&cached).unwrap().modified().unwrap();
+    hook.run(&mut context).await.unwrap();
+
+    assert_eq!(std::fs::metadata(&cached).unwrap().modified().unwrap(), modified);
+    assert_eq!(std::fs::read(dir.join("blocked").join("model.bin")).unwrap(), b"weights");
+    assert!(tracker.copies(task_id).await.iter().all(|copy| copy.status == CopyStatus::Completed));
+
+    tracker.remove_task(task_id).await;
+    assert!(tracker.copies(task_id).await.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
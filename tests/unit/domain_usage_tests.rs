@@ -0,0 +1,92 @@
+//! Unit tests for the per-host download accounting
+
+use burncloud_download::{DomainUsageTracker, TaskId};
+
+fn bytes_of(usage: &[burncloud_download::DomainUsage], host: &str) -> Option<u64> {
+    usage.iter().find(|usage| usage.host == host).map(|usage| usage.bytes_downloaded)
+}
+
+#[tokio::test]
+async fn test_counts_progress_deltas_by_host() {
+    let tracker = DomainUsageTracker::in_memory().await.unwrap();
+    let first = TaskId::new();
+    let second = TaskId::new();
+    let third = TaskId::new();
+
+    tracker.track(first, "https://cdn.example.com/a.bin", 0).await;
+    tracker.track(second, "https://cdn.example.com/b.bin", 0).await;
+    tracker.track(third, "http://mirror.example.org:8080/c.bin", 0).await;
+
+    tracker.observe(first, 100).await;
+    tracker.observe(first, 250).await;
+    tracker.observe(second, 50).await;
+    tracker.observe(third, 400).await;
+
+    let usage = tracker.usage().await;
+    assert_eq!(usage[0].host, "mirror.example.org:8080");
+    assert_eq!(bytes_of(&usage, "mirror.example.org:8080"), Some(400));
+    assert_eq!(bytes_of(&usage, "cdn.example.com"), Some(300));
+}
+
+#[tokio::test]
+async fn test_resumed_bytes_are_not_counted_again() {
+    let tracker = DomainUsageTracker::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+
+    tracker.track(task_id, "https://example.com/file.bin", 1000).await;
+    tracker.observe(task_id, 1200).await;
+
+    assert_eq!(bytes_of(&tracker.usage().await, "example.com"), Some(200));
+}
+
+#[tokio::test]
+async fn test_restarted_download_counts_again() {
+    let tracker = DomainUsageTracker::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+
+    tracker.track(task_id, "https://example.com/file.bin", 0).await;
+    tracker.observe(task_id, 500).await;
+    // The server ignored the range request, the download starts over
+    tracker.observe(task_id, 100).await;
+
+    assert_eq!(bytes_of(&tracker.usage().await, "example.com"), Some(600));
+}
+
+#[tokio::test]
+async fn test_untracked_and_forgotten_tasks_are_ignored() {
+    let tracker = DomainUsageTracker::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+
+    tracker.observe(task_id, 100).await;
+    assert!(tracker.usage().await.is_empty());
+
+    tracker.track(task_id, "https://example.com/file.bin", 0).await;
+    tracker.observe(task_id, 100).await;
+    tracker.forget(task_id).await;
+    tracker.observe(task_id, 300).await;
+
+    assert_eq!(bytes_of(&tracker.usage().await, "example.com"), Some(100));
+}
+
+#[tokio::test]
+async fn test_flushed_usage_survives_reopen() {
+    let dir = std::env::temp_dir().join(format!("burncloud_domain_usage_{}", std::process::id()));
+    let path = dir.join("metadata.db");
+    let task_id = TaskId::new();
+
+    {
+        let tracker = DomainUsageTracker::open(&path).await.unwrap();
+        tracker.track(task_id, "https://example.com/file.bin", 0).await;
+        tracker.observe(task_id, 100).await;
+        tracker.flush().await.unwrap();
+        tracker.observe(task_id, 150).await;
+        tracker.flush().await.unwrap();
+        // Never flushed, lost with the tracker
+        tracker.observe(task_id, 175).await;
+    }
+
+    let tracker = DomainUsageTracker::open(&path).await.unwrap();
+    assert_eq!(bytes_of(&tracker.usage().await, "example.com"), Some(150));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,210 @@
+//! Unit tests for the DownloadBackend abstraction
+//!
+//! Uses an in-memory backend to make sure the trait is usable as a trait object
+//! without an aria2 daemon.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use async_trait::async_trait;
+
+use burncloud_download::{DownloadError, InMemoryTaskRepository, PersistentAria2Manager, TaskRepository};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::DownloadOptions;
+use burncloud_download::types::{TaskId, DownloadTask, DownloadProgress, DownloadStatus};
+use super::support::MemoryBackend;
+
+/// Repository whose saves fail while `failing` is set
+#[derive(Default)]
+struct FailingRepository {
+    inner: InMemoryTaskRepository,
+    failing: AtomicBool,
+}
+
+#[async_trait]
+impl TaskRepository for FailingRepository {
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(DownloadError::DatabaseError("disk I/O error".to_string()));
+        }
+        self.inner.save_task(task).await
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError> {
+        self.inner.get_task(task_id).await
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.inner.list_tasks().await
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_task(task_id).await
+    }
+
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.inner.save_progress(task_id, progress).await
+    }
+
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError> {
+        self.inner.get_progress(task_id).await
+    }
+
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_progress(task_id).await
+    }
+}
+
+/// Repository counting single saves and recording the size of each batch
+#[derive(Default)]
+struct RecordingRepository {
+    inner: InMemoryTaskRepository,
+    single_saves: AtomicUsize,
+    batches: Mutex<Vec<usize>>,
+}
+
+#[async_trait]
+impl TaskRepository for RecordingRepository {
+    async fn save_task(&self, task: &DownloadTask) -> Result<(), DownloadError> {
+        self.single_saves.fetch_add(1, Ordering::SeqCst);
+        self.inner.save_task(task).await
+    }
+
+    async fn save_tasks(&self, tasks: &[DownloadTask]) -> Result<(), DownloadError> {
+        self.batches.lock().unwrap().push(tasks.len());
+        self.inner.save_tasks(tasks).await
+    }
+
+    async fn get_task(&self, task_id: &TaskId) -> Result<DownloadTask, DownloadError> {
+        self.inner.get_task(task_id).await
+    }
+
+    async fn list_tasks(&self) -> Result<Vec<DownloadTask>, DownloadError> {
+        self.inner.list_tasks().await
+    }
+
+    async fn delete_task(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_task(task_id).await
+    }
+
+    async fn save_progress(&self, task_id: &TaskId, progress: &DownloadProgress) -> Result<(), DownloadError> {
+        self.inner.save_progress(task_id, progress).await
+    }
+
+    async fn get_progress(&self, task_id: &TaskId) -> Result<DownloadProgress, DownloadError> {
+        self.inner.get_progress(task_id).await
+    }
+
+    async fn delete_progress(&self, task_id: &TaskId) -> Result<(), DownloadError> {
+        self.inner.delete_progress(task_id).await
+    }
+}
+
+#[tokio::test]
+async fn test_backend_usable_as_trait_object() {
+    let backend: Arc<dyn DownloadBackend> = Arc::new(MemoryBackend::new().starting_downloads());
+
+    let task_id = backend.add(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("data/file.zip")
+    ).await.unwrap();
+
+    let task = backend.task(task_id).await.unwrap();
+    assert_eq!(task.url, "https://example.com/file.zip");
+    assert_eq!(backend.active_count().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_backend_lifecycle() {
+    let backend: Arc<dyn DownloadBackend> = Arc::new(MemoryBackend::new().starting_downloads());
+
+    let task_id = backend.add(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("data/file.zip")
+    ).await.unwrap();
+
+    backend.pause(task_id).await.unwrap();
+    assert_eq!(backend.task(task_id).await.unwrap().status, DownloadStatus::Paused);
+    assert_eq!(backend.active_count().await.unwrap(), 0);
+
+    backend.resume(task_id).await.unwrap();
+    assert_eq!(backend.task(task_id).await.unwrap().status, DownloadStatus::Downloading);
+
+    backend.cancel(task_id).await.unwrap();
+    assert!(backend.task(task_id).await.is_err());
+    assert!(backend.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_multi_source_uses_primary_url() {
+    let backend: Arc<dyn DownloadBackend> = Arc::new(MemoryBackend::new().starting_downloads());
+
+    let task_id = backend.add_multi_source(
+        vec![
+            "https://mirror1.example.com/file.zip".to_string(),
+            "https://mirror2.example.com/file.zip".to_string(),
+        ],
+        PathBuf::from("data/file.zip"),
+        &DownloadOptions::default()
+    ).await.unwrap();
+
+    assert_eq!(backend.task(task_id).await.unwrap().url, "https://mirror1.example.com/file.zip");
+    assert!(backend.add_multi_source(Vec::new(), PathBuf::from("data/x.zip"), &DownloadOptions::default()).await.is_err());
+}
+
+
+#[tokio::test]
+async fn test_unsaved_download_is_stopped_and_releases_its_path() {
+    let dir = std::env::temp_dir().join(format!("burncloud_backend_unsaved_{}", std::process::id()));
+    let backend = Arc::new(MemoryBackend::new());
+    let repository = Arc::new(FailingRepository::default());
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend.clone())
+        .task_repository(repository.clone())
+        .download_dir(&dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    // Nothing listens on the discard port, so probes fail right away
+    let url = "http://127.0.0.1:9/file.zip";
+
+    repository.failing.store(true, Ordering::SeqCst);
+    let result = manager.add_download(url.to_string(), dir.join("file.zip")).await;
+    assert!(matches!(result, Err(DownloadError::DatabaseError(_))));
+    assert!(backend.list().await.unwrap().is_empty());
+
+    // The path is free again for the next attempt
+    repository.failing.store(false, Ordering::SeqCst);
+    let task_id = manager.add_download(url.to_string(), dir.join("file.zip")).await.unwrap();
+    assert!(backend.task(task_id).await.is_ok());
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_shutdown_saves_all_tasks_in_one_batch() {
+    let dir = std::env::temp_dir().join(format!("burncloud_backend_batch_{}", std::process::id()));
+    let backend = Arc::new(MemoryBackend::new());
+    let repository = Arc::new(RecordingRepository::default());
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend.clone())
+        .task_repository(repository.clone())
+        .download_dir(&dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    // Nothing listens on the discard port, so probes fail right away
+    manager.add_download("http://127.0.0.1:9/a.zip".to_string(), dir.join("a.zip")).await.unwrap();
+    manager.add_download("http://127.0.0.1:9/b.zip".to_string(), dir.join("b.zip")).await.unwrap();
+    let single_saves = repository.single_saves.load(Ordering::SeqCst);
+
+    manager.shutdown().await.unwrap();
+    assert_eq!(repository.single_saves.load(Ordering::SeqCst), single_saves);
+    assert_eq!(repository.batches.lock().unwrap().last(), Some(&2));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
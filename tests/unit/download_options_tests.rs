@@ -0,0 +1,139 @@
+//! Unit tests for DownloadOptions
+
+use std::path::PathBuf;
+use serde_json::json;
+use burncloud_download::{
+    DownloadOptions, Checksum, ChecksumAlgorithm, Priority, TaskQueueManager, DownloadManager,
+    DownloadError, SegmentDefaults, CancellationToken,
+};
+
+#[test]
+fn test_default_options_map_to_no_aria2_options() {
+    assert!(DownloadOptions::default().to_aria2_options().is_empty());
+}
+
+#[test]
+fn test_options_map_to_aria2_options() {
+    let options = DownloadOptions::new()
+        .header("Authorization", "Bearer token")
+        .cookies("session=abc")
+        .user_agent("burncloud/1.0")
+        .proxy("http://proxy:8080")
+        .speed_limit(1024)
+        .checksum(Checksum::new(ChecksumAlgorithm::Sha256, "ABCDEF"))
+        .segments(32);
+
+    let aria2 = options.to_aria2_options();
+    assert_eq!(aria2["header"], json!(["Authorization: Bearer token", "Cookie: session=abc"]));
+    assert_eq!(aria2["user-agent"], json!("burncloud/1.0"));
+    assert_eq!(aria2["all-proxy"], json!("http://proxy:8080"));
+    assert_eq!(aria2["max-download-limit"], json!("1024"));
+    assert_eq!(aria2["checksum"], json!("sha-256=abcdef"));
+    assert_eq!(aria2["split"], json!("32"));
+    // aria2 caps connections per server at 16
+    assert_eq!(aria2["max-connection-per-server"], json!("16"));
+}
+
+#[test]
+fn test_continue_partial_option() {
+    let aria2 = DownloadOptions::new().continue_partial(true).to_aria2_options();
+    assert_eq!(aria2["continue"], json!("true"));
+
+    // Options persisted before the field existed still deserialize
+    let options: DownloadOptions = serde_json::from_str(r#"{"headers":[],"segments":4}"#).unwrap();
+    assert!(!options.continue_partial);
+    assert_eq!(options.segments, Some(4));
+}
+
+#[test]
+fn test_auto_extract_options() {
+    let options = DownloadOptions::new().auto_extract(true);
+    assert!(options.auto_extract);
+    assert_eq!(options.extract_dir, None);
+
+    let options = DownloadOptions::new().extract_to("data/unpacked");
+    assert!(options.auto_extract);
+    assert_eq!(options.extract_dir, Some(std::path::PathBuf::from("data/unpacked")));
+
+    // Extraction happens after the download, aria2 never sees it
+    assert!(options.to_aria2_options().is_empty());
+}
+
+#[test]
+fn test_options_serialization_roundtrip() {
+    let options = DownloadOptions::new()
+        .priority(Priority::High)
+        .header("X-Test", "1");
+
+    let serialized = serde_json::to_string(&options).unwrap();
+    let deserialized: DownloadOptions = serde_json::from_str(&serialized).unwrap();
+    assert_eq!(deserialized, options);
+}
+
+#[test]
+fn test_cancel_token_is_not_serialized() {
+    let token = CancellationToken::new();
+    let options = DownloadOptions::new().cancel_token(token.clone());
+    assert!(options.cancel_token.is_some());
+    assert_eq!(options, DownloadOptions::default());
+
+    let serialized = serde_json::to_string(&options).unwrap();
+    let deserialized: DownloadOptions = serde_json::from_str(&serialized).unwrap();
+    assert!(deserialized.cancel_token.is_none());
+
+    // Clones share the token
+    token.cancel();
+    assert!(options.clone().cancel_token.unwrap().is_cancelled());
+}
+
+#[tokio::test]
+async fn test_queue_applies_priority_from_options() {
+    let manager = TaskQueueManager::new();
+    let task_id = manager.add_download_with_options(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip"),
+        DownloadOptions::new().priority(Priority::Urgent)
+    ).await.unwrap();
+
+    assert_eq!(manager.get_priority(task_id).await.unwrap(), Priority::Urgent);
+}
+
+#[test]
+fn test_segment_options_map_to_aria2_options() {
+    let aria2 = DownloadOptions::new()
+        .segments(8)
+        .max_connections_per_server(4)
+        .min_split_size(4 * 1024 * 1024)
+        .to_aria2_options();
+
+    assert_eq!(aria2["split"], json!("8"));
+    assert_eq!(aria2["max-connection-per-server"], json!("4"));
+    assert_eq!(aria2["min-split-size"], json!("4194304"));
+}
+
+#[test]
+fn test_segment_options_are_validated() {
+    assert!(DownloadOptions::new().validate().is_ok());
+    assert!(DownloadOptions::new().segments(64).max_connections_per_server(16).min_split_size(1024 * 1024).validate().is_ok());
+
+    assert!(matches!(DownloadOptions::new().segments(0).validate(), Err(DownloadError::InvalidOption(_))));
+    assert!(DownloadOptions::new().segments(65).validate().is_err());
+    assert!(DownloadOptions::new().max_connections_per_server(17).validate().is_err());
+    assert!(DownloadOptions::new().min_split_size(512 * 1024).validate().is_err());
+    assert!(SegmentDefaults { segments: Some(100), ..Default::default() }.validate().is_err());
+}
+
+#[test]
+fn test_segment_defaults_fill_unset_options() {
+    let defaults = SegmentDefaults {
+        segments: Some(16),
+        max_connections_per_server: Some(8),
+        min_split_size: Some(8 * 1024 * 1024),
+    };
+
+    let mut options = DownloadOptions::new().segments(4);
+    options.apply_segment_defaults(&defaults);
+    assert_eq!(options.segments, Some(4));
+    assert_eq!(options.max_connections_per_server, Some(8));
+    assert_eq!(options.min_split_size, Some(8 * 1024 * 1024));
+}
\ No newline at end of file
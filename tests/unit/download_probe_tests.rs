@@ -0,0 +1,117 @@
+//! Unit tests for file name resolution and probe responses
+
+use burncloud_download::RemoteMetadata;
+use burncloud_download::probe::{resolve_filename, filename_from_url, filename_from_content_disposition, truncate_filename, DEFAULT_FILENAME, DEFAULT_MAX_FILENAME_LENGTH};
+use burncloud_download::probe::filename::sanitize_filename;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT_RANGES, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE};
+use reqwest::StatusCode;
+
+#[test]
+fn test_content_disposition_filename() {
+    assert_eq!(filename_from_content_disposition(r#"attachment; filename="model.bin""#), Some("model.bin".to_string()));
+    assert_eq!(filename_from_content_disposition("attachment; filename=model.bin"), Some("model.bin".to_string()));
+    assert_eq!(filename_from_content_disposition(r#"attachment; filename="a;b \"c\".zip""#), Some(r#"a;b "c".zip"#.to_string()));
+    assert_eq!(filename_from_content_disposition("inline"), None);
+}
+
+#[test]
+fn test_extended_filename_wins() {
+    let header = r#"attachment; filename="fallback.zip"; filename*=UTF-8''na%C3%AFve%20file.zip"#;
+    assert_eq!(filename_from_content_disposition(header), Some("naïve file.zip".to_string()));
+
+    let latin1 = "attachment; filename*=iso-8859-1'en'caf%E9.txt";
+    assert_eq!(filename_from_content_disposition(latin1), Some("café.txt".to_string()));
+}
+
+#[test]
+fn test_filename_is_sanitized() {
+    assert_eq!(filename_from_content_disposition(r#"attachment; filename="../../etc/passwd""#), Some("passwd".to_string()));
+    assert_eq!(sanitize_filename(r"C:\temp\evil.exe"), Some("evil.exe".to_string()));
+    assert_eq!(sanitize_filename(".."), None);
+    assert_eq!(sanitize_filename("dir/"), None);
+}
+
+#[test]
+fn test_filename_from_url() {
+    assert_eq!(filename_from_url("https://example.com/files/model%20v2.bin?token=abc"), Some("model v2.bin".to_string()));
+    assert_eq!(filename_from_url("https://example.com/?id=123"), None);
+    assert_eq!(filename_from_url("not a url"), None);
+    assert_eq!(filename_from_url("https://example.com/files/..%2F..%2Fetc%2Fpasswd"), Some("passwd".to_string()));
+    assert_eq!(filename_from_url("https://example.com/files/%2E%2E"), None);
+}
+
+#[test]
+fn test_truncate_filename() {
+    assert_eq!(truncate_filename("model.bin", 20), "model.bin");
+    assert_eq!(truncate_filename("a-very-long-model-name.safetensors", 20), "a-very-l.safetensors");
+    assert_eq!(truncate_filename("archive.tar.gz", 10), "archive.gz");
+
+    // Cut on character boundaries, dropping an extension that leaves no room
+    assert_eq!(truncate_filename("日本語のファイル.txt", 11), "日本.txt");
+    assert_eq!(truncate_filename("name.extension", 5), "name");
+    assert_eq!(truncate_filename("日本語", 2), "do");
+
+    let long = format!("{}.zip", "x".repeat(300));
+    let truncated = truncate_filename(&long, DEFAULT_MAX_FILENAME_LENGTH);
+    assert_eq!(truncated.len(), DEFAULT_MAX_FILENAME_LENGTH);
+    assert!(truncated.ends_with(".zip"));
+}
+
+#[test]
+fn test_resolve_filename_order() {
+    let original = "https://example.com/get?id=123";
+    let redirected = "https://cdn.example.com/store/archive.tar.gz";
+
+    assert_eq!(resolve_filename(Some(r#"attachment; filename="real.tar.gz""#), redirected, original), "real.tar.gz");
+    assert_eq!(resolve_filename(None, redirected, original), "archive.tar.gz");
+    assert_eq!(resolve_filename(None, "https://example.com/?id=1", "https://example.com/?id=1"), DEFAULT_FILENAME);
+}
+
+#[test]
+fn test_metadata_from_head_response() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1048576"));
+    headers.insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+    headers.insert(CONTENT_DISPOSITION, HeaderValue::from_static(r#"attachment; filename="weights.zip""#));
+
+    let metadata = RemoteMetadata::from_response(
+        "https://example.com/download?id=7",
+        "https://cdn.example.com/7",
+        StatusCode::OK,
+        &headers,
+    );
+
+    assert_eq!(metadata.filename, "weights.zip");
+    assert_eq!(metadata.final_url, "https://cdn.example.com/7");
+    assert_eq!(metadata.size, Some(1048576));
+    assert!(metadata.resumable);
+    assert_eq!(metadata.content_type.as_deref(), Some("application/zip"));
+}
+
+#[test]
+fn test_metadata_from_ranged_response() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1"));
+    headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-0/5000"));
+
+    let metadata = RemoteMetadata::from_response(
+        "https://example.com/file.iso",
+        "https://example.com/file.iso",
+        StatusCode::PARTIAL_CONTENT,
+        &headers,
+    );
+
+    assert_eq!(metadata.filename, "file.iso");
+    assert_eq!(metadata.size, Some(5000));
+    assert!(metadata.resumable);
+
+    let metadata = RemoteMetadata::from_response(
+        "https://example.com/file.iso",
+        "https://example.com/file.iso",
+        StatusCode::OK,
+        &HeaderMap::new(),
+    );
+    assert_eq!(metadata.size, None);
+    assert!(!metadata.resumable);
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+//! Unit tests for named download profiles
+//!
+//! Uses an in-memory backend so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::models::{DownloadOptions, DownloadProfile, OverwritePolicy};
+use burncloud_download::services::InMemoryTaskRepository;
+use burncloud_download::services::task_metadata_store::TaskMetadataStore;
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_profile_{}_{}", name, std::process::id()))
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.gguf";
+
+async fn ephemeral_manager(dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .download_dir(dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[test]
+fn test_profile_download_options() {
+    let profile = DownloadProfile::new("/mnt/models")
+        .concurrency(8)
+        .speed_limit(1024)
+        .overwrite(OverwritePolicy::Skip);
+
+    let options = profile.download_options();
+    assert_eq!(options.segments, Some(8));
+    assert_eq!(options.speed_limit, Some(1024));
+    assert_eq!(options.overwrite, OverwritePolicy::Skip);
+
+    let defaults = DownloadProfile::new("/data/sets").download_options();
+    assert_eq!(defaults, DownloadOptions::default());
+}
+
+#[test]
+fn test_profile_deserializes_with_defaults() {
+    let profile: DownloadProfile = serde_json::from_str(r#"{"directory": "/data/sets"}"#).unwrap();
+
+    assert_eq!(profile, DownloadProfile::new("/data/sets"));
+}
+
+#[tokio::test]
+async fn test_metadata_store_saves_profiles() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let models = DownloadProfile::new("/mnt/models").concurrency(4);
+
+    store.put_profile("models", &models).await.unwrap();
+    store.put_profile("datasets", &DownloadProfile::new("/data/sets")).await.unwrap();
+    let profiles = store.profiles().await.unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(profiles["models"], models);
+
+    assert!(store.remove_profile("datasets").await.unwrap());
+    assert!(!store.remove_profile("datasets").await.unwrap());
+    assert_eq!(store.profiles().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_download_with_profile_saves_in_profile_directory() {
+    let dir = test_dir("directory");
+    let manager = ephemeral_manager(&dir).await;
+    manager.set_profile("models", DownloadProfile::new(dir.join("models")).concurrency(8)).await.unwrap();
+
+    let task_id = manager.download_with_profile("models", URL).await.unwrap();
+    let task = manager.get_task(task_id).await.unwrap();
+    assert_eq!(task.target_path, dir.join("models").join("model.gguf"));
+    assert_eq!(manager.task_profile(task_id).await.unwrap().as_deref(), Some("models"));
+
+    let task_id = manager.download_with_profile_to("models", URL, "llama/model.gguf").await.unwrap();
+    let task = manager.get_task(task_id).await.unwrap();
+    assert_eq!(task.target_path, dir.join("models").join("llama/model.gguf"));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_download_with_unknown_profile_fails() {
+    let dir = test_dir("unknown");
+    let manager = ephemeral_manager(&dir).await;
+
+    let result = manager.download_with_profile("models", URL).await;
+    assert!(matches!(result, Err(DownloadError::Config(_))));
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_profile_rejects_absolute_paths() {
+    let dir = test_dir("absolute");
+    let manager = ephemeral_manager(&dir).await;
+    manager.set_profile("models", DownloadProfile::new(dir.join("models"))).await.unwrap();
+
+    let result = manager.download_with_profile_to("models", URL, dir.join("elsewhere.gguf")).await;
+    assert!(matches!(result, Err(DownloadError::InvalidPath(_))));
+    assert!(manager.set_profile("", DownloadProfile::new(&dir)).await.is_err());
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_profiles_survive_restart() {
+    let dir = test_dir("restart");
+    let db_path = dir.join("metadata.db");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .db_path(&db_path)
+        .task_repository(Arc::new(InMemoryTaskRepository::new()))
+        .profile("models", DownloadProfile::new(dir.join("models")))
+        .build()
+        .await
+        .unwrap();
+    manager.set_profile("datasets", DownloadProfile::new(dir.join("sets"))).await.unwrap();
+    manager.shutdown().await.unwrap();
+    drop(manager);
+
+    // The configured profile replaces the saved one of the same name
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .db_path(&db_path)
+        .task_repository(Arc::new(InMemoryTaskRepository::new()))
+        .profile("models", DownloadProfile::new(dir.join("models")).concurrency(2))
+        .build()
+        .await
+        .unwrap();
+    let profiles = manager.profiles().await;
+    assert_eq!(profiles["datasets"], DownloadProfile::new(dir.join("sets")));
+    assert_eq!(profiles["models"].concurrency, Some(2));
+
+    assert!(manager.remove_profile("datasets").await.unwrap());
+    assert!(manager.profile("datasets").await.is_none());
+    manager.shutdown().await.unwrap();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,114 @@
+//! Unit tests for scheduled downloads
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use burncloud_download::{DownloadManager, TaskQueueManager, DownloadScheduler, ScheduleSpec};
+use burncloud_download::scheduler::ScheduleStore;
+
+fn at_unix(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+async fn create_scheduler() -> (Arc<TaskQueueManager>, DownloadScheduler) {
+    let manager = Arc::new(TaskQueueManager::new());
+    let store = ScheduleStore::in_memory().await.unwrap();
+    let scheduler = DownloadScheduler::new(manager.clone(), store);
+    (manager, scheduler)
+}
+
+#[test]
+fn test_every_next_occurrence() {
+    let spec = ScheduleSpec::Every { start: at_unix(1_000), interval: Duration::from_secs(60) };
+
+    assert_eq!(spec.next_occurrence(at_unix(500)), Some(at_unix(1_000)));
+    assert_eq!(spec.next_occurrence(at_unix(1_000)), Some(at_unix(1_060)));
+    // Missed runs are skipped rather than replayed
+    assert_eq!(spec.next_occurrence(at_unix(1_250)), Some(at_unix(1_260)));
+}
+
+#[test]
+fn test_daily_and_weekly_next_occurrence() {
+    // 1970-01-05 00:00 UTC was a Monday
+    let monday = 4 * 86_400;
+
+    let daily = ScheduleSpec::DailyAt { hour: 2, minute: 30 };
+    assert_eq!(daily.next_occurrence(at_unix(monday)), Some(at_unix(monday + 9_000)));
+    assert_eq!(daily.next_occurrence(at_unix(monday + 9_000)), Some(at_unix(monday + 86_400 + 9_000)));
+
+    let weekly = ScheduleSpec::WeeklyAt { weekday: 2, hour: 0, minute: 0 };
+    assert_eq!(weekly.next_occurrence(at_unix(monday)), Some(at_unix(monday + 2 * 86_400)));
+    assert_eq!(weekly.next_occurrence(at_unix(monday + 2 * 86_400)), Some(at_unix(monday + 9 * 86_400)));
+}
+
+#[test]
+fn test_one_off_has_no_next_occurrence() {
+    let spec = ScheduleSpec::At(at_unix(1_000));
+    assert!(!spec.is_recurring());
+    assert_eq!(spec.next_occurrence(at_unix(0)), None);
+}
+
+#[test]
+fn test_invalid_specs_are_rejected() {
+    assert!(ScheduleSpec::Every { start: at_unix(0), interval: Duration::ZERO }.validate().is_err());
+    assert!(ScheduleSpec::DailyAt { hour: 24, minute: 0 }.validate().is_err());
+    assert!(ScheduleSpec::WeeklyAt { weekday: 7, hour: 0, minute: 0 }.validate().is_err());
+    assert!(ScheduleSpec::DailyAt { hour: 23, minute: 59 }.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_due_one_off_download_is_promoted_once() {
+    let (manager, scheduler) = create_scheduler().await;
+
+    scheduler.schedule_download(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip"),
+        ScheduleSpec::At(SystemTime::now() - Duration::from_secs(1))
+    ).await.unwrap();
+
+    let started = scheduler.run_due().await.unwrap();
+    assert_eq!(started.len(), 1);
+    assert!(manager.get_task(started[0]).await.is_ok());
+
+    // One-off entries are removed after they run
+    assert!(scheduler.list_schedules().await.unwrap().is_empty());
+    assert!(scheduler.run_due().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_future_download_is_not_promoted() {
+    let (manager, scheduler) = create_scheduler().await;
+
+    let id = scheduler.schedule_download(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip"),
+        ScheduleSpec::At(SystemTime::now() + Duration::from_secs(3_600))
+    ).await.unwrap();
+
+    assert!(scheduler.run_due().await.unwrap().is_empty());
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+
+    scheduler.cancel_schedule(id).await.unwrap();
+    assert!(scheduler.list_schedules().await.unwrap().is_empty());
+    assert!(scheduler.cancel_schedule(id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_recurring_download_is_rescheduled() {
+    let (_manager, scheduler) = create_scheduler().await;
+    let start = SystemTime::now() - Duration::from_secs(1);
+
+    scheduler.schedule_download(
+        "https://example.com/feed.xml".to_string(),
+        PathBuf::from("/downloads/feed.xml"),
+        ScheduleSpec::Every { start, interval: Duration::from_secs(3_600) }
+    ).await.unwrap();
+
+    let started = scheduler.run_due().await.unwrap();
+    assert_eq!(started.len(), 1);
+
+    let schedules = scheduler.list_schedules().await.unwrap();
+    assert_eq!(schedules.len(), 1);
+    assert!(schedules[0].next_run > SystemTime::now());
+    assert_eq!(schedules[0].last_task_id, Some(started[0]));
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+//! Unit tests for reading downloads while they run
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed;
+//! the tests write the downloaded bytes themselves.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_stream_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .download_to_temp_file(true)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_stream_follows_download_to_completion() {
+    let dir = test_dir("follow");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let target = dir.join("model.bin");
+    let partial = dir.join("model.bin.part");
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+
+    // Preallocated to the full size, with only the first bytes downloaded
+    let mut data = b"hello".to_vec();
+    data.resize(11, 0);
+    std::fs::write(&partial, &data).unwrap();
+    backend.set_downloaded(task_id, 5).await;
+
+    let mut stream = manager.open_stream(task_id).await.unwrap();
+    let mut start = [0; 5];
+    stream.read_exact(&mut start).await.unwrap();
+    assert_eq!(&start, b"hello");
+
+    // Nothing more until more bytes are downloaded
+    let mut byte = [0; 1];
+    assert!(tokio::time::timeout(Duration::from_millis(300), stream.read(&mut byte)).await.is_err());
+
+    std::fs::write(&partial, b"hello world").unwrap();
+    backend.set_downloaded(task_id, 11).await;
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+    manager.refresh(task_id).await.unwrap();
+
+    let mut rest = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut rest)).await.unwrap().unwrap();
+    assert_eq!(rest, b" world");
+    assert_eq!(std::fs::read(&target).unwrap(), b"hello world");
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_stream_reads_completed_download() {
+    let dir = test_dir("completed");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let target = dir.join("model.bin");
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+    std::fs::write(dir.join("model.bin.part"), b"weights").unwrap();
+    backend.set_downloaded(task_id, 7).await;
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+    manager.refresh(task_id).await.unwrap();
+
+    let mut stream = manager.open_stream(task_id).await.unwrap();
+    let mut data = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut data)).await.unwrap().unwrap();
+    assert_eq!(data, b"weights");
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_stream_fails_with_download() {
+    let dir = test_dir("failed");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+
+    let mut stream = manager.open_stream(task_id).await.unwrap();
+    backend.set_status(task_id, DownloadStatus::Failed("connection reset".to_string())).await.unwrap();
+
+    let mut data = Vec::new();
+    let result = tokio::time::timeout(Duration::from_secs(2), stream.read_to_end(&mut data)).await.unwrap();
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("connection reset"));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_stream_of_unknown_task() {
+    let dir = test_dir("unknown");
+    let manager = manager(Arc::new(MemoryBackend::default()), &dir).await;
+
+    let task_id = TaskId::new();
+    assert!(matches!(manager.open_stream(task_id).await, Err(DownloadError::TaskNotFound(id)) if id == task_id));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -92,4 +92,14 @@ mod tests {
         assert!(!DuplicatePolicy::AllowDuplicate.allows_reuse(&completed_status));
         assert!(!DuplicatePolicy::AllowDuplicate.allows_reuse(&waiting_status));
     }
+    #[test]
+    fn test_convenience_default_policy() {
+        assert_eq!(burncloud_download::default_duplicate_policy(), DuplicatePolicy::ReuseExisting);
+
+        burncloud_download::set_default_duplicate_policy(DuplicatePolicy::AllowDuplicate);
+        assert_eq!(burncloud_download::default_duplicate_policy(), DuplicatePolicy::AllowDuplicate);
+
+        burncloud_download::set_default_duplicate_policy(DuplicatePolicy::default());
+        assert_eq!(burncloud_download::default_duplicate_policy(), DuplicatePolicy::ReuseExisting);
+    }
 }
\ No newline at end of file
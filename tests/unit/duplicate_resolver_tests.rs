@@ -0,0 +1,158 @@
+//! Unit tests for duplicate resolution
+
+use async_trait::async_trait;
+use burncloud_download::{
+    BasicDownloadManager, DownloadManager, DownloadError, DuplicateCandidate, DuplicateDecision,
+    DuplicateDecisionHandler, DuplicatePolicy, DuplicatePreview, DuplicateReason, DuplicateResolver, TaskStatus,
+    TaskQueueManager,
+};
+use burncloud_download::types::TaskId;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Handler that always reuses the last candidate
+struct PickLast;
+
+#[async_trait]
+impl DuplicateDecisionHandler for PickLast {
+    async fn decide(&self, _url: &str, _target_path: &Path, candidates: &[DuplicateCandidate]) -> DuplicateDecision {
+        candidates.last().map(DuplicateDecision::reuse).unwrap_or(DuplicateDecision::CreateNew)
+    }
+}
+
+fn candidate(status: TaskStatus) -> DuplicateCandidate {
+    DuplicateCandidate::new(TaskId::new(), status, DuplicateReason::UrlAndPath)
+}
+
+async fn resolve(resolver: &DuplicateResolver, policy: DuplicatePolicy, candidates: &[DuplicateCandidate]) -> DuplicateDecision {
+    resolver.resolve("https://example.com/file.zip", Path::new("./downloads/file.zip"), &policy, candidates).await
+}
+
+#[tokio::test]
+async fn test_no_candidates_creates_new() {
+    let resolver = DuplicateResolver::new();
+    for policy in [DuplicatePolicy::ReuseExisting, DuplicatePolicy::FailIfDuplicate, DuplicatePolicy::PromptUser] {
+        assert_eq!(resolve(&resolver, policy, &[]).await, DuplicateDecision::CreateNew);
+    }
+}
+
+#[tokio::test]
+async fn test_policies_map_to_decisions() {
+    let resolver = DuplicateResolver::new();
+    let completed = candidate(TaskStatus::Completed);
+    let paused = candidate(TaskStatus::Paused);
+
+    let decision = resolve(&resolver, DuplicatePolicy::ReuseExisting, &[completed.clone()]).await;
+    assert_eq!(decision, DuplicateDecision::reuse(&completed));
+    assert_eq!(decision.task_id(), Some(completed.task_id));
+
+    assert!(resolve(&resolver, DuplicatePolicy::AllowDuplicate, &[completed.clone()]).await.is_create_new());
+    assert!(resolve(&resolver, DuplicatePolicy::ReuseIfComplete, &[paused.clone()]).await.is_create_new());
+
+    // The first candidate the policy accepts is reused
+    let decision = resolve(&resolver, DuplicatePolicy::ReuseIfIncomplete, &[completed.clone(), paused.clone()]).await;
+    assert_eq!(decision.task_id(), Some(paused.task_id));
+
+    let decision = resolve(&resolver, DuplicatePolicy::FailIfDuplicate, &[completed.clone()]).await;
+    assert!(decision.is_reject());
+    assert_eq!(decision.task_id(), Some(completed.task_id));
+}
+
+#[tokio::test]
+async fn test_prompt_user_asks_handler() {
+    let resolver = DuplicateResolver::new();
+    let candidates = [candidate(TaskStatus::Completed), candidate(TaskStatus::Paused)];
+
+    // Without a handler a new task is created
+    assert!(resolve(&resolver, DuplicatePolicy::PromptUser, &candidates).await.is_create_new());
+
+    resolver.set_handler(Arc::new(PickLast)).await;
+    let decision = resolve(&resolver, DuplicatePolicy::PromptUser, &candidates).await;
+    assert_eq!(decision, DuplicateDecision::reuse(&candidates[1]));
+
+    resolver.clear_handler().await;
+    assert!(resolve(&resolver, DuplicatePolicy::PromptUser, &candidates).await.is_create_new());
+}
+
+#[tokio::test]
+async fn test_manager_applies_decision() {
+    let manager = BasicDownloadManager::new();
+    let url = "https://example.com/file.zip";
+    let path = PathBuf::from("./downloads/file.zip");
+
+    let (task_id, decision) = manager.add_download_with_policy(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    assert!(decision.is_create_new());
+
+    let (reused_id, decision) = manager.add_download_with_policy(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    assert_eq!(reused_id, task_id);
+    assert!(decision.is_reuse());
+
+    let result = manager.add_download_with_policy(url, &path, DuplicatePolicy::FailIfDuplicate).await;
+    assert!(matches!(result, Err(DownloadError::PolicyViolation { task_id: id, .. }) if id == task_id));
+
+    manager.set_duplicate_handler(Arc::new(PickLast)).await;
+    let (prompted_id, _) = manager.add_download_with_policy(url, &path, DuplicatePolicy::PromptUser).await.unwrap();
+    assert_eq!(prompted_id, task_id);
+}
+#[tokio::test]
+async fn test_preview_does_not_ask_handler() {
+    let resolver = DuplicateResolver::new();
+    let candidates = [candidate(TaskStatus::Completed), candidate(TaskStatus::Paused)];
+
+    assert_eq!(resolver.preview(&DuplicatePolicy::PromptUser, &[]).await, DuplicatePreview::CreateNew);
+    assert_eq!(
+        resolver.preview(&DuplicatePolicy::ReuseExisting, &candidates).await,
+        DuplicateDecision::reuse(&candidates[0]).into()
+    );
+    assert!(matches!(
+        resolver.preview(&DuplicatePolicy::FailIfDuplicate, &candidates).await,
+        DuplicatePreview::Reject { task_id, .. } if task_id == candidates[0].task_id
+    ));
+
+    // Without a handler prompting falls back to a new task
+    assert_eq!(resolver.preview(&DuplicatePolicy::PromptUser, &candidates).await, DuplicatePreview::CreateNew);
+
+    resolver.set_handler(Arc::new(PickLast)).await;
+    let preview = resolver.preview(&DuplicatePolicy::PromptUser, &candidates).await;
+    assert!(preview.requires_decision());
+    assert_eq!(preview, DuplicatePreview::RequiresDecision { candidates: candidates.to_vec() });
+}
+
+#[tokio::test]
+async fn test_manager_evaluates_without_adding() {
+    let manager = BasicDownloadManager::new();
+    let url = "https://example.com/file.zip";
+    let path = PathBuf::from("./downloads/file.zip");
+
+    let preview = manager.evaluate_duplicate(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    assert_eq!(preview, DuplicatePreview::CreateNew);
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+
+    let (task_id, _) = manager.add_download_with_policy(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    let preview = manager.evaluate_duplicate(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    assert!(matches!(preview, DuplicatePreview::Reuse { task_id: id, .. } if id == task_id));
+    assert!(matches!(
+        manager.evaluate_duplicate(url, &path, DuplicatePolicy::FailIfDuplicate).await.unwrap(),
+        DuplicatePreview::Reject { .. }
+    ));
+    assert_eq!(manager.list_tasks().await.unwrap().len(), 1);
+
+    assert!(manager.evaluate_duplicate("file:///etc/passwd", &path, DuplicatePolicy::ReuseExisting).await.is_err());
+}
+
+#[tokio::test]
+async fn test_queue_evaluates_without_adding() {
+    let queue = TaskQueueManager::new();
+    let url = "https://example.com/file.zip";
+    let path = PathBuf::from("./downloads/file.zip");
+
+    let (task_id, _) = queue.add_download_with_policy(url, &path, DuplicatePolicy::ReuseExisting).await.unwrap();
+    queue.set_duplicate_handler(Arc::new(PickLast)).await;
+
+    let preview = queue.evaluate_duplicate(url, &path, DuplicatePolicy::PromptUser).await.unwrap();
+    let DuplicatePreview::RequiresDecision { candidates } = preview else {
+        panic!("expected a decision to be required, got {:?}", preview);
+    };
+    assert_eq!(candidates[0].task_id, task_id);
+    assert_eq!(queue.list_tasks().await.unwrap().len(), 1);
+}
\ No newline at end of file
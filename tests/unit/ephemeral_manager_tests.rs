@@ -0,0 +1,68 @@
+//! Unit tests for running PersistentAria2Manager without database files
+//!
+//! Uses an in-memory backend so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::PersistentAria2Manager;
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::services::{TaskRepository, InMemoryTaskRepository};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_ephemeral_{}_{}", name, std::process::id()))
+}
+
+// Nothing listens on the discard port, so size probes fail right away
+const URL: &str = "http://127.0.0.1:9/file.zip";
+
+#[tokio::test]
+async fn test_ephemeral_manager_writes_no_database() {
+    let dir = test_dir("no_database");
+    let db_path = dir.join("tasks.db");
+
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .db_path(&db_path)
+        .download_dir(&dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("file.zip")).await.unwrap();
+    assert_eq!(manager.get_task(task_id).await.unwrap().url, URL);
+    assert!(manager.list_tasks().await.unwrap().iter().any(|task| task.id == task_id));
+
+    manager.cancel_download(task_id).await.unwrap();
+    assert!(manager.get_task(task_id).await.is_err());
+    manager.shutdown().await.unwrap();
+
+    assert!(!db_path.exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_ephemeral_manager_saves_to_given_repository() {
+    let dir = test_dir("repository");
+    let repository = Arc::new(InMemoryTaskRepository::new());
+
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .download_dir(&dir)
+        .task_repository(repository.clone())
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("file.zip")).await.unwrap();
+    assert_eq!(repository.get_task(&task_id).await.unwrap().url, URL);
+
+    manager.cancel_download(task_id).await.unwrap();
+    assert!(repository.get_task(&task_id).await.is_err());
+    manager.shutdown().await.unwrap();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+//! Unit tests for channel-based event streaming
+
+use std::path::PathBuf;
+use burncloud_download::{TaskQueueManager, DownloadEvent};
+use burncloud_download::types::{DownloadStatus, DownloadProgress};
+
+#[tokio::test]
+async fn test_subscribe_events_receives_status_changes() {
+    let manager = TaskQueueManager::new();
+    let mut events = manager.subscribe_events();
+
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    match events.recv().await.unwrap() {
+        DownloadEvent::StatusChanged { task_id: id, new_status, .. } => {
+            assert_eq!(id, task_id);
+            assert_eq!(new_status, DownloadStatus::Downloading);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+
+    manager.complete_task(task_id).await.unwrap();
+    let mut completed = false;
+    while let Ok(event) = events.try_recv() {
+        if matches!(event, DownloadEvent::Completed { .. }) {
+            completed = true;
+        }
+    }
+    assert!(completed);
+}
+
+#[tokio::test]
+async fn test_subscribe_progress_sees_latest_value() {
+    let manager = TaskQueueManager::new();
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    let mut progress_rx = manager.subscribe_progress(task_id).await.unwrap();
+    assert_eq!(progress_rx.borrow().downloaded_bytes, 0);
+
+    manager.update_progress(task_id, DownloadProgress {
+        downloaded_bytes: 2048,
+        total_bytes: Some(4096),
+        speed_bps: 1024,
+        eta_seconds: Some(2),
+    }).await.unwrap();
+
+    progress_rx.changed().await.unwrap();
+    assert_eq!(progress_rx.borrow().downloaded_bytes, 2048);
+
+    // Cancelling the task closes the channel
+    manager.cancel_task(task_id).await.unwrap();
+    assert!(progress_rx.changed().await.is_err());
+}
@@ -0,0 +1,88 @@
+//! Unit tests for single-callback event listeners
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+use burncloud_download::{TaskQueueManager, DownloadEvent, EventBus};
+use burncloud_download::services::ListenerHandler;
+use burncloud_download::traits::{DownloadEventHandler, DownloadEventListener};
+use burncloud_download::types::{TaskId, DownloadStatus};
+
+#[tokio::test]
+async fn test_listener_handler_turns_calls_into_events() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let handler = ListenerHandler::new(Arc::new(sender));
+    let task_id = TaskId::new();
+
+    handler.on_status_changed(task_id, DownloadStatus::Waiting, DownloadStatus::Downloading).await;
+    handler.on_retry_scheduled(task_id, 2, Duration::from_secs(4)).await;
+    handler.on_download_restored(task_id, 1024).await;
+    handler.on_download_completed(task_id).await;
+
+    match receiver.recv().await.unwrap() {
+        DownloadEvent::StatusChanged { task_id: id, old_status, new_status } => {
+            assert_eq!(id, task_id);
+            assert_eq!(old_status, DownloadStatus::Waiting);
+            assert_eq!(new_status, DownloadStatus::Downloading);
+        }
+        other => panic!("Expected status change, got {:?}", other),
+    }
+    assert!(matches!(receiver.recv().await.unwrap(),
+        DownloadEvent::RetryScheduled { attempt: 2, delay, .. } if delay == Duration::from_secs(4)));
+    assert!(matches!(receiver.recv().await.unwrap(), DownloadEvent::Restored { resumed_from: 1024, .. }));
+    assert!(matches!(receiver.recv().await.unwrap(), DownloadEvent::Completed { task_id: id } if id == task_id));
+}
+
+#[tokio::test]
+async fn test_broadcast_sender_is_a_listener() {
+    let (sender, mut first) = broadcast::channel(8);
+    let mut second = sender.subscribe();
+    let task_id = TaskId::new();
+
+    sender.on_event(DownloadEvent::Failed { task_id, error: "404".to_string() }).await;
+
+    assert!(matches!(first.recv().await.unwrap(), DownloadEvent::Failed { error, .. } if error == "404"));
+    assert!(matches!(second.recv().await.unwrap(), DownloadEvent::Failed { task_id: id, .. } if id == task_id));
+}
+
+#[tokio::test]
+async fn test_listener_ignores_closed_channel() {
+    let (sender, receiver) = mpsc::channel(1);
+    drop(receiver);
+
+    sender.on_event(DownloadEvent::Completed { task_id: TaskId::new() }).await;
+}
+
+#[tokio::test]
+async fn test_event_bus_publishes_listened_events() {
+    let bus = EventBus::default();
+    let mut events = bus.subscribe_events();
+    let task_id = TaskId::new();
+
+    bus.on_event(DownloadEvent::Completed { task_id }).await;
+
+    assert!(matches!(events.try_recv().unwrap(), DownloadEvent::Completed { task_id: id } if id == task_id));
+}
+
+#[tokio::test]
+async fn test_queue_manager_delivers_to_listener() {
+    let manager = TaskQueueManager::new();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    manager.add_event_listener(Arc::new(sender)).await;
+
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    let event = tokio::time::timeout(Duration::from_secs(2), receiver.recv()).await.unwrap().unwrap();
+    match event {
+        DownloadEvent::StatusChanged { task_id: id, new_status, .. } => {
+            assert_eq!(id, task_id);
+            assert_eq!(new_status, DownloadStatus::Downloading);
+        }
+        other => panic!("Unexpected event: {:?}", other),
+    }
+}
\ No newline at end of file
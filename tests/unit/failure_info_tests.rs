@@ -0,0 +1,131 @@
+//! Unit tests for structured failure diagnostics
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{DownloadEvent, PersistentAria2Manager};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{Backoff, FailureInfo, FailureKind, RetryOn, RetryPolicy, EXPIRED_REASON};
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_failure_info_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+async fn wait_for_failures(manager: &PersistentAria2Manager, task_id: TaskId, count: usize) -> Vec<FailureInfo> {
+    for _ in 0..100 {
+        let history = manager.failure_history(task_id, usize::MAX).await.unwrap();
+        if history.len() >= count {
+            return history;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Task {} did not record {} failures", task_id, count);
+}
+
+#[test]
+fn test_classify_failures() {
+    let not_found = FailureInfo::new("The response status is not successful. status=404", Some(3));
+    assert_eq!(not_found.kind, FailureKind::NotFound);
+    assert_eq!(not_found.http_status, Some(404));
+    assert_eq!(not_found.aria2_error_code, Some(3));
+    assert_eq!(not_found.retry_count, 0);
+
+    // Without an error code the HTTP status decides, then the message
+    assert_eq!(FailureInfo::new("HTTP 401 Unauthorized", None).kind, FailureKind::Unauthorized);
+    assert_eq!(FailureInfo::new("HTTP 503 Service Unavailable", None).kind, FailureKind::Http);
+    assert_eq!(FailureInfo::new("Connection timed out", None).kind, FailureKind::Timeout);
+    assert_eq!(FailureInfo::new("Connection refused", None).kind, FailureKind::Network);
+    assert_eq!(FailureInfo::new(EXPIRED_REASON, None).kind, FailureKind::Expired);
+    assert_eq!(FailureInfo::new("Checksum mismatch in piece 3: expected a, got b", None).kind, FailureKind::Checksum);
+    assert_eq!(FailureInfo::new("something odd", None).kind, FailureKind::Unknown);
+    assert_eq!(FailureInfo::new("something odd", Some(9)).kind, FailureKind::DiskFull);
+    assert_eq!(FailureInfo::new("status=abc", None).http_status, None);
+}
+
+#[tokio::test]
+async fn test_failure_recorded_with_diagnostics() {
+    let dir = test_dir("recorded");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    let mut events = manager.subscribe_events();
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    assert_eq!(manager.failure_info(task_id).await.unwrap(), None);
+    backend.fail(task_id, "The response status is not successful. status=404", 3).await;
+
+    let history = wait_for_failures(&manager, task_id, 1).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, FailureKind::NotFound);
+    assert_eq!(history[0].http_status, Some(404));
+    assert_eq!(history[0].aria2_error_code, Some(3));
+    assert_eq!(manager.failure_info(task_id).await.unwrap(), Some(history[0].clone()));
+
+    let recorded = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            if let Ok(DownloadEvent::FailureRecorded { task_id: id, failure }) = events.recv().await {
+                return (id, failure);
+            }
+        }
+    }).await.unwrap();
+    assert_eq!(recorded, (task_id, history[0].clone()));
+}
+
+#[tokio::test]
+async fn test_failure_history_counts_retries() {
+    let dir = test_dir("history");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    manager.set_retry_policy(RetryPolicy {
+        max_attempts: 1,
+        backoff: Backoff::Fixed(Duration::from_millis(10)),
+        retry_on: RetryOn::AnyError,
+    }).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.fail(task_id, "Connection reset by peer", 6).await;
+    wait_for_failures(&manager, task_id, 1).await;
+
+    // The retry resumes the download, which the poller has to see before it fails again
+    for _ in 0..100 {
+        if backend.task(task_id).await.unwrap().status == DownloadStatus::Downloading {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    backend.fail(task_id, "The response status is not successful. status=404", 3).await;
+
+    let history = wait_for_failures(&manager, task_id, 2).await;
+    assert_eq!(history[0].kind, FailureKind::Network);
+    assert_eq!(history[0].retry_count, 0);
+    assert_eq!(history[1].kind, FailureKind::NotFound);
+    assert_eq!(history[1].retry_count, 1);
+    assert!(history[1].last_attempt_at >= history[0].last_attempt_at);
+
+    // Only the last entries are returned, oldest first
+    let last = manager.failure_history(task_id, 1).await.unwrap();
+    assert_eq!(last, vec![history[1].clone()]);
+    assert_eq!(manager.failure_info(task_id).await.unwrap(), Some(history[1].clone()));
+}
\ No newline at end of file
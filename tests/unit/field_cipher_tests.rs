@@ -0,0 +1,245 @@
+//! Unit tests for at-rest encryption of stored fields
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::DownloadError;
+use burncloud_download::services::{FieldCipher, TaskRepository, InMemoryTaskRepository, EncryptedTaskRepository};
+use burncloud_download::services::task_metadata_store::TaskMetadataStore;
+use burncloud_download::scheduler::{ScheduleStore, ScheduleSpec};
+use burncloud_download::RemoteValidators;
+use sqlx::Row;
+use burncloud_download::types::{TaskId, DownloadTask};
+
+const SIGNED_URL: &str = "https://example.com/model.bin?X-Amz-Signature=secret";
+
+fn cipher(key_id: &str, byte: u8) -> FieldCipher {
+    FieldCipher::new(key_id, [byte; 32]).unwrap()
+}
+
+#[test]
+fn test_encrypt_and_decrypt() {
+    let cipher = cipher("k1", 1);
+
+    let sealed = cipher.encrypt(SIGNED_URL).unwrap();
+    assert!(sealed.starts_with("enc:v1:k1:"));
+    assert!(!sealed.contains("secret"));
+    assert_eq!(cipher.decrypt(&sealed).unwrap(), SIGNED_URL);
+
+    // A fresh nonce every time
+    assert_ne!(cipher.encrypt(SIGNED_URL).unwrap(), sealed);
+}
+
+#[test]
+fn test_plaintext_is_read_as_is() {
+    let cipher = cipher("k1", 1);
+
+    assert_eq!(cipher.decrypt(SIGNED_URL).unwrap(), SIGNED_URL);
+    assert!(!cipher.is_current(SIGNED_URL));
+}
+
+#[test]
+fn test_previous_keys_still_decrypt() {
+    let old = cipher("k1", 1);
+    let sealed = old.encrypt(SIGNED_URL).unwrap();
+
+    let rotated = cipher("k2", 2).with_previous_key("k1", [1; 32]).unwrap();
+    assert_eq!(rotated.key_id(), "k2");
+    assert_eq!(rotated.decrypt(&sealed).unwrap(), SIGNED_URL);
+    assert!(!rotated.is_current(&sealed));
+    assert!(rotated.is_current(&rotated.encrypt(SIGNED_URL).unwrap()));
+
+    // Without the old key the value cannot be read
+    let result = cipher("k2", 2).decrypt(&sealed);
+    assert!(matches!(result, Err(DownloadError::EncryptionError(_))));
+}
+
+#[test]
+fn test_wrong_key_fails() {
+    let sealed = cipher("k1", 1).encrypt(SIGNED_URL).unwrap();
+
+    let result = cipher("k1", 9).decrypt(&sealed);
+    assert!(matches!(result, Err(DownloadError::EncryptionError(_))));
+}
+
+#[test]
+fn test_invalid_keys_rejected() {
+    assert!(matches!(FieldCipher::new("", [0; 32]), Err(DownloadError::Config(_))));
+    assert!(matches!(FieldCipher::new("a:b", [0; 32]), Err(DownloadError::Config(_))));
+    assert!(matches!(FieldCipher::from_base64("k1", "c2hvcnQ="), Err(DownloadError::Config(_))));
+
+    // 32 zero bytes
+    let key = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    assert!(FieldCipher::from_base64("k1", key).is_ok());
+}
+
+#[test]
+fn test_debug_hides_keys() {
+    let cipher = cipher("k2", 7).with_previous_key("k1", [1; 32]).unwrap();
+
+    let debug = format!("{:?}", cipher);
+    assert!(debug.contains("k2"));
+    assert!(debug.contains("k1"));
+}
+
+#[tokio::test]
+async fn test_encrypted_repository_stores_ciphertext() {
+    let inner = Arc::new(InMemoryTaskRepository::new());
+    let repository = EncryptedTaskRepository::new(inner.clone(), cipher("k1", 1));
+
+    let task = DownloadTask::new(SIGNED_URL.to_string(), PathBuf::from("./downloads/model.bin"));
+    repository.save_task(&task).await.unwrap();
+
+    let stored = inner.get_task(&task.id).await.unwrap();
+    assert!(stored.url.starts_with("enc:v1:k1:"));
+    assert_eq!(repository.get_task(&task.id).await.unwrap().url, SIGNED_URL);
+    assert_eq!(repository.list_tasks().await.unwrap()[0].url, SIGNED_URL);
+}
+
+#[tokio::test]
+async fn test_encrypted_repository_finds_by_url_hash() {
+    let repository = EncryptedTaskRepository::new(Arc::new(InMemoryTaskRepository::new()), cipher("k1", 1));
+
+    let task = DownloadTask::new(SIGNED_URL.to_string(), PathBuf::from("./downloads/model.bin"));
+    repository.save_task(&task).await.unwrap();
+
+    let url_hash = burncloud_download::models::FileIdentifier::new(SIGNED_URL, &task.target_path, None).url_hash;
+    assert_eq!(repository.find_by_url_hash(&url_hash).await.unwrap(), vec![task.id]);
+}
+
+#[tokio::test]
+async fn test_encrypted_repository_reencrypts() {
+    let inner = Arc::new(InMemoryTaskRepository::new());
+    let plain = DownloadTask::new(SIGNED_URL.to_string(), PathBuf::from("./downloads/a.bin"));
+    inner.save_task(&plain).await.unwrap();
+    let old = EncryptedTaskRepository::new(inner.clone(), cipher("k1", 1));
+    let sealed = DownloadTask::new(SIGNED_URL.to_string(), PathBuf::from("./downloads/b.bin"));
+    old.save_task(&sealed).await.unwrap();
+
+    let rotated = cipher("k2", 2).with_previous_key("k1", [1; 32]).unwrap();
+    let repository = EncryptedTaskRepository::new(inner.clone(), rotated);
+    assert_eq!(repository.reencrypt().await.unwrap(), 2);
+    assert_eq!(repository.reencrypt().await.unwrap(), 0);
+
+    // Readable with the new key alone
+    let current = EncryptedTaskRepository::new(inner, cipher("k2", 2));
+    assert_eq!(current.get_task(&plain.id).await.unwrap().url, SIGNED_URL);
+    assert_eq!(current.get_task(&sealed.id).await.unwrap().url, SIGNED_URL);
+}
+
+#[tokio::test]
+async fn test_metadata_store_encrypts_values() {
+    let store = TaskMetadataStore::in_memory().await.unwrap().with_cipher(cipher("k1", 1));
+    let task_id = TaskId::new();
+
+    store.put(&task_id, "source_urls", &vec![SIGNED_URL.to_string()]).await.unwrap();
+    let urls: Option<Vec<String>> = store.get(&task_id, "source_urls").await.unwrap();
+    assert_eq!(urls, Some(vec![SIGNED_URL.to_string()]));
+
+    let entries: Vec<(TaskId, Vec<String>)> = store.entries("source_urls").await.unwrap();
+    assert_eq!(entries, vec![(task_id, vec![SIGNED_URL.to_string()])]);
+    assert_eq!(store.reencrypt().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_metadata_store_reencrypts_plaintext() {
+    let path = std::env::temp_dir().join(format!("burncloud_field_cipher_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let task_id = TaskId::new();
+
+    let plain = TaskMetadataStore::open(&path).await.unwrap();
+    plain.put(&task_id, "retry_attempts", &3u32).await.unwrap();
+
+    let store = TaskMetadataStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    assert_eq!(store.get::<u32>(&task_id, "retry_attempts").await.unwrap(), Some(3));
+    assert_eq!(store.reencrypt().await.unwrap(), 1);
+    assert_eq!(store.get::<u32>(&task_id, "retry_attempts").await.unwrap(), Some(3));
+
+    // Unreadable without the key now
+    assert!(plain.get::<u32>(&task_id, "retry_attempts").await.is_err());
+
+    let _ = std::fs::remove_file(&path);
+}
+
+fn temp_db(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("burncloud_field_cipher_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn raw_column(path: &PathBuf, query: &str) -> Vec<String> {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .connect(&format!("sqlite://{}", path.display()))
+        .await
+        .unwrap();
+    let rows = sqlx::query(query).fetch_all(&pool).await.unwrap();
+    rows.iter().map(|row| row.get::<String, _>(0)).collect()
+}
+
+#[tokio::test]
+async fn test_validators_keyed_by_url_hash() {
+    let path = temp_db("validators");
+    let target = PathBuf::from("/tmp/model.bin");
+    let task_id = TaskId::new();
+    let validators = RemoteValidators { etag: Some("\"v1\"".to_string()), last_modified: None, size: Some(42) };
+
+    let store = TaskMetadataStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    store.put_download_validators(SIGNED_URL, &target, &task_id, &validators).await.unwrap();
+    assert_eq!(store.get_download_validators(SIGNED_URL, &target).await.unwrap(), Some((task_id, validators)));
+
+    let urls = raw_column(&path, "SELECT url FROM download_validators").await;
+    assert_eq!(urls.len(), 1);
+    assert!(!urls[0].contains("secret"));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_reencrypt_hashes_plaintext_validator_urls() {
+    let path = temp_db("validators_plain");
+    let target = PathBuf::from("/tmp/model.bin");
+    let task_id = TaskId::new();
+    let validators = RemoteValidators { etag: None, last_modified: None, size: Some(7) };
+
+    let plain = TaskMetadataStore::open(&path).await.unwrap();
+    plain.put_download_validators(SIGNED_URL, &target, &task_id, &validators).await.unwrap();
+
+    let store = TaskMetadataStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    assert_eq!(store.get_download_validators(SIGNED_URL, &target).await.unwrap(), Some((task_id, validators.clone())));
+    assert_eq!(store.reencrypt().await.unwrap(), 1);
+    assert_eq!(store.get_download_validators(SIGNED_URL, &target).await.unwrap(), Some((task_id, validators)));
+    assert!(raw_column(&path, "SELECT url FROM download_validators").await.iter().all(|url| !url.contains("secret")));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_schedule_store_encrypts_urls() {
+    let path = temp_db("schedules");
+    let spec = ScheduleSpec::DailyAt { hour: 2, minute: 30 };
+
+    let store = ScheduleStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    store.insert(SIGNED_URL, &PathBuf::from("/tmp/model.bin"), &spec, std::time::SystemTime::now()).await.unwrap();
+    assert_eq!(store.list().await.unwrap()[0].url, SIGNED_URL);
+    assert!(!raw_column(&path, "SELECT url FROM scheduled_downloads").await[0].contains("secret"));
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn test_reencrypt_seals_plaintext_scheduled_urls() {
+    let path = temp_db("schedules_plain");
+    let spec = ScheduleSpec::DailyAt { hour: 2, minute: 30 };
+
+    let plain = ScheduleStore::open(&path).await.unwrap();
+    plain.insert(SIGNED_URL, &PathBuf::from("/tmp/model.bin"), &spec, std::time::SystemTime::now()).await.unwrap();
+
+    let store = TaskMetadataStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    assert_eq!(store.reencrypt().await.unwrap(), 1);
+    assert!(!raw_column(&path, "SELECT url FROM scheduled_downloads").await[0].contains("secret"));
+
+    let schedules = ScheduleStore::open(&path).await.unwrap().with_cipher(cipher("k1", 1));
+    assert_eq!(schedules.list().await.unwrap()[0].url, SIGNED_URL);
+
+    let _ = std::fs::remove_file(&path);
+}
\ No newline at end of file
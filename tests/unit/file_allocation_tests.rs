@@ -0,0 +1,62 @@
+//! Unit tests for file allocation modes
+
+use burncloud_download::{DownloadOptions, FileAllocation, ManagerConfig};
+use burncloud_download::backend::SessionEntry;
+use burncloud_download::manager::config::ENV_FILE_ALLOCATION;
+use serde_json::json;
+use std::fs::File;
+use std::path::Path;
+
+#[test]
+fn test_allocation_maps_to_aria2_option() {
+    assert!(!DownloadOptions::new().to_aria2_options().contains_key("file-allocation"));
+
+    let aria2 = DownloadOptions::new().file_allocation(FileAllocation::Falloc).to_aria2_options();
+    assert_eq!(aria2["file-allocation"], json!("falloc"));
+}
+
+#[test]
+fn test_allocation_names_roundtrip() {
+    for allocation in [FileAllocation::None, FileAllocation::Prealloc, FileAllocation::Falloc, FileAllocation::Trunc] {
+        assert_eq!(allocation.to_string().parse::<FileAllocation>(), Ok(allocation));
+    }
+    assert_eq!("TRUNC".parse::<FileAllocation>(), Ok(FileAllocation::Trunc));
+    assert!("sparse".parse::<FileAllocation>().is_err());
+}
+
+#[test]
+fn test_allocation_survives_aria2_sessions() {
+    let options = DownloadOptions::new().file_allocation(FileAllocation::Prealloc);
+    let entry = SessionEntry::from_download(vec!["https://example.com/model.bin".to_string()], Path::new("model.bin"), None, false, &options);
+
+    assert_eq!(entry.option("file-allocation"), Some("prealloc"));
+    assert_eq!(entry.download_options().file_allocation, Some(FileAllocation::Prealloc));
+}
+
+#[test]
+fn test_native_allocation_sets_file_length() {
+    let dir = std::env::temp_dir().join(format!("burncloud_file_allocation_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for (name, allocation, len) in [
+        ("none.bin", FileAllocation::None, 0),
+        ("trunc.bin", FileAllocation::Trunc, 64 * 1024),
+        ("falloc.bin", FileAllocation::Falloc, 64 * 1024),
+    ] {
+        let path = dir.join(name);
+        let file = File::create(&path).unwrap();
+        allocation.allocate(&file, 64 * 1024).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), len, "{}", name);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_allocation_default_from_environment() {
+    std::env::set_var(ENV_FILE_ALLOCATION, "falloc");
+    assert_eq!(ManagerConfig::from_env().unwrap().file_allocation, Some(FileAllocation::Falloc));
+
+    std::env::remove_var(ENV_FILE_ALLOCATION);
+    assert_eq!(ManagerConfig::from_env().unwrap().file_allocation, None);
+}
\ No newline at end of file
@@ -0,0 +1,104 @@
+//! Unit tests for per-file progress and file selection
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::models::FileProgress;
+use burncloud_download::types::{TaskId, DownloadProgress};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_file_progress_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.torrent";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .download_to_temp_file(false)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+fn file(index: u32, path: PathBuf, downloaded: u64, total: Option<u64>) -> FileProgress {
+    FileProgress { index, path, downloaded, total, selected: true }
+}
+
+#[test]
+fn test_file_progress_percentage() {
+    let half = file(1, PathBuf::from("model.bin"), 50, Some(100));
+    assert_eq!(half.percentage(), Some(50.0));
+    assert!(!half.is_complete());
+
+    let unknown = file(2, PathBuf::from("config.json"), 10, None);
+    assert_eq!(unknown.percentage(), None);
+    assert!(!unknown.is_complete());
+
+    assert!(file(3, PathBuf::from("tokenizer.json"), 20, Some(20)).is_complete());
+
+    let mut progress = DownloadProgress::new();
+    progress.downloaded_bytes = 5;
+    progress.total_bytes = Some(0);
+    let whole = FileProgress::whole("model.bin", &progress);
+    assert_eq!(whole.index, 1);
+    assert_eq!(whole.total, None);
+    assert!(whole.selected);
+}
+
+#[tokio::test]
+async fn test_single_file_download_is_one_file() {
+    let dir = test_dir("single");
+    let manager = manager(Arc::new(MemoryBackend::default()), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    let files = manager.get_file_progress(task_id).await.unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].path, dir.join("model.bin"));
+
+    // Single files cannot be selected
+    let result = manager.select_files(task_id, &[1]).await;
+    assert!(matches!(result, Err(DownloadError::InvalidOption(_))));
+}
+
+#[tokio::test]
+async fn test_select_files_of_multi_file_download() {
+    let dir = test_dir("multi");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("repo")).await.unwrap();
+    let files = vec![
+        file(1, dir.join("repo/model.safetensors"), 100, Some(1000)),
+        file(2, dir.join("repo/config.json"), 10, Some(10)),
+        file(3, dir.join("repo/README.md"), 0, None),
+    ];
+    backend.set_files(task_id, files.clone()).await;
+    assert_eq!(manager.get_file_progress(task_id).await.unwrap(), files);
+
+    assert!(matches!(manager.select_files(task_id, &[]).await, Err(DownloadError::InvalidOption(_))));
+    assert!(matches!(manager.select_files(task_id, &[1, 4]).await, Err(DownloadError::InvalidOption(_))));
+    assert_eq!(backend.selection(task_id).await, None);
+
+    manager.select_files(task_id, &[1, 2]).await.unwrap();
+    assert_eq!(backend.selection(task_id).await, Some(vec![1, 2]));
+}
+
+#[tokio::test]
+async fn test_file_progress_of_unknown_task() {
+    let dir = test_dir("unknown");
+    let manager = manager(Arc::new(MemoryBackend::default()), &dir).await;
+
+    let result = manager.get_file_progress(TaskId::new()).await;
+    assert!(matches!(result, Err(DownloadError::TaskNotFound(_))));
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+//! Unit tests for stale task garbage collection
+
+use burncloud_download::{GcPolicy, GcReport, StaleTaskAction, StallTracker, RestoredTask};
+use burncloud_download::types::TaskId;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_gc_policy_defaults_and_builder() {
+    let policy = GcPolicy::default();
+    assert_eq!(policy.stall_timeout, Duration::from_secs(6 * 60 * 60));
+    assert_eq!(policy.action, StaleTaskAction::Requeue);
+    assert!(policy.remove_orphans);
+
+    let policy = GcPolicy::new()
+        .stall_timeout(Duration::from_secs(3600))
+        .action(StaleTaskAction::Fail)
+        .remove_orphans(false);
+    assert_eq!(policy.stall_timeout, Duration::from_secs(3600));
+    assert_eq!(policy.action, StaleTaskAction::Fail);
+    assert!(!policy.remove_orphans);
+}
+
+#[test]
+fn test_gc_report_totals() {
+    let mut report = GcReport::default();
+    assert!(report.is_empty());
+
+    let task_id = TaskId::new();
+    report.failed.push(TaskId::new());
+    report.requeued.push(RestoredTask { task_id, original_task_id: TaskId::new(), resumed_from: 4096 });
+    report.removed.push(TaskId::new());
+    assert!(!report.is_empty());
+    assert_eq!(report.total(), 3);
+}
+
+#[tokio::test]
+async fn test_unseen_tasks_count_from_tracker_creation() {
+    let before = SystemTime::now();
+    let stalls = StallTracker::new();
+    let after = SystemTime::now();
+
+    let since = stalls.last_progress(TaskId::new()).await;
+    assert!(since >= before && since <= after);
+}
+
+#[tokio::test]
+async fn test_only_growing_downloads_count_as_progress() {
+    let stalls = StallTracker::new();
+    let task_id = TaskId::new();
+
+    stalls.observe(task_id, 1024).await;
+    let first = stalls.last_progress(task_id).await;
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    stalls.observe(task_id, 1024).await;
+    assert_eq!(stalls.last_progress(task_id).await, first);
+
+    stalls.observe(task_id, 2048).await;
+    assert!(stalls.last_progress(task_id).await > first);
+}
+
+#[tokio::test]
+async fn test_removed_task_is_forgotten() {
+    let stalls = StallTracker::new();
+    let task_id = TaskId::new();
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    stalls.observe(task_id, 1024).await;
+    let observed = stalls.last_progress(task_id).await;
+
+    stalls.remove_task(task_id).await;
+    assert!(stalls.last_progress(task_id).await < observed);
+}
\ No newline at end of file
@@ -0,0 +1,95 @@
+//! Unit tests for adding and removing event handlers
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::path::PathBuf;
+use async_trait::async_trait;
+
+use burncloud_download::{TaskId, DownloadStatus, DownloadProgress};
+use burncloud_download::queue::manager::TaskQueueManager;
+use burncloud_download::services::HandlerRegistry;
+use burncloud_download::traits::{DownloadEventHandler, DownloadManager};
+
+/// Handler counting the status changes it receives
+#[derive(Default)]
+struct Counter {
+    status_changes: AtomicUsize,
+}
+
+impl Counter {
+    fn count(&self) -> usize {
+        self.status_changes.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl DownloadEventHandler for Counter {
+    async fn on_status_changed(&self, _task_id: TaskId, _old_status: DownloadStatus, _new_status: DownloadStatus) {
+        self.status_changes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn on_progress_updated(&self, _task_id: TaskId, _progress: DownloadProgress) {}
+
+    async fn on_download_completed(&self, _task_id: TaskId) {}
+
+    async fn on_download_failed(&self, _task_id: TaskId, _error: String) {}
+}
+
+async fn add_task(manager: &TaskQueueManager) -> TaskId {
+    manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_registry_removes_by_id() {
+    let fixed: Arc<dyn DownloadEventHandler> = Arc::new(Counter::default());
+    let registry = HandlerRegistry::new(vec![fixed]);
+
+    let first = registry.add(Arc::new(Counter::default())).await;
+    let second = registry.add(Arc::new(Counter::default())).await;
+    assert_ne!(first, second);
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.list().read().await.len(), 3);
+
+    assert!(registry.remove(first).await);
+    assert!(!registry.remove(first).await);
+    assert_eq!(registry.len(), 1);
+    assert_eq!(registry.list().read().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_removed_handler_receives_no_events() {
+    let manager = TaskQueueManager::new();
+    let counter = Arc::new(Counter::default());
+
+    let id = manager.add_event_handler(counter.clone()).await;
+    let task_id = add_task(&manager).await;
+    let received = counter.count();
+    assert!(received > 0);
+
+    assert!(manager.remove_event_handler(id).await);
+    manager.pause_task(task_id).await.unwrap();
+    assert_eq!(counter.count(), received);
+
+    // The manager no longer holds the handler
+    assert_eq!(Arc::strong_count(&counter), 1);
+}
+
+#[tokio::test]
+async fn test_weak_handler_is_removed_once_dropped() {
+    let manager = TaskQueueManager::new();
+    let counter = Arc::new(Counter::default());
+    let handler: Arc<dyn DownloadEventHandler> = counter.clone();
+
+    let id = manager.add_weak_event_handler(&handler).await;
+    drop(handler);
+    let task_id = add_task(&manager).await;
+    assert!(counter.count() > 0);
+
+    // The last reference goes, the next event removes the registration
+    drop(counter);
+    manager.pause_task(task_id).await.unwrap();
+    assert!(!manager.remove_event_handler(id).await);
+}
\ No newline at end of file
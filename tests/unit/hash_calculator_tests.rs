@@ -0,0 +1,85 @@
+//! Unit tests for background content hashing
+
+use burncloud_download::BackgroundHashCalculator;
+use burncloud_download::services::TaskMetadataStore;
+use burncloud_download::services::hash_calculator::{HashCalculator, hash_file, FILE_HASH_KEY};
+use burncloud_download::types::TaskId;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_hash_{}_{}", name, std::process::id()))
+}
+
+async fn wait_for_hash(calculator: &BackgroundHashCalculator, task_id: TaskId) -> Option<String> {
+    for _ in 0..200 {
+        if !calculator.is_pending(task_id).await {
+            return calculator.hash_for(task_id).await;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn test_hash_file_is_blake3() {
+    let dir = unique_temp_dir("blake3");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let path = dir.join("hello.txt");
+    tokio::fs::write(&path, b"hello").await.unwrap();
+
+    assert_eq!(hash_file(&path).unwrap(), blake3::hash(b"hello").to_hex().to_string());
+    assert!(hash_file(&dir.join("missing")).is_err());
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrency_limit_is_at_least_one() {
+    assert_eq!(BackgroundHashCalculator::with_concurrency(0).concurrency(), 1);
+    assert_eq!(BackgroundHashCalculator::with_concurrency(4).concurrency(), 4);
+}
+
+#[tokio::test]
+async fn test_queued_hashes_are_persisted_and_searchable() {
+    let dir = unique_temp_dir("queue");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let first = dir.join("a.bin");
+    let second = dir.join("b.bin");
+    tokio::fs::write(&first, vec![7u8; 10_000]).await.unwrap();
+    tokio::fs::write(&second, vec![7u8; 10_000]).await.unwrap();
+
+    let store = Arc::new(TaskMetadataStore::in_memory().await.unwrap());
+    let calculator = BackgroundHashCalculator::with_concurrency(1).with_store(store.clone());
+    let (first_id, second_id) = (TaskId::new(), TaskId::new());
+
+    calculator.queue_calculation(first_id, &first).await.unwrap();
+    calculator.queue_calculation(second_id, &second).await.unwrap();
+
+    let hash = wait_for_hash(&calculator, first_id).await.unwrap();
+    assert_eq!(wait_for_hash(&calculator, second_id).await, Some(hash.clone()));
+    assert_eq!(store.get::<String>(&first_id, FILE_HASH_KEY).await.unwrap(), Some(hash.clone()));
+
+    // Same content is found regardless of where it was downloaded from
+    let mut matches = calculator.find_by_hash(&hash).await;
+    matches.sort_by_key(|id| id.to_string());
+    let mut expected = vec![first_id, second_id];
+    expected.sort_by_key(|id| id.to_string());
+    assert_eq!(matches, expected);
+
+    // A fresh calculator over the same store still knows the hashes
+    let reopened = BackgroundHashCalculator::new().with_store(store);
+    assert_eq!(reopened.hash_for(first_id).await, Some(hash));
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_failed_hash_is_not_recorded() {
+    let calculator = BackgroundHashCalculator::new();
+    let task_id = TaskId::new();
+
+    calculator.queue_calculation(task_id, &unique_temp_dir("missing").join("none.bin")).await.unwrap();
+    assert_eq!(wait_for_hash(&calculator, task_id).await, None);
+}
\ No newline at end of file
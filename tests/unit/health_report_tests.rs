@@ -0,0 +1,41 @@
+//! Unit tests for the health report
+
+use burncloud_download::HealthReport;
+use std::time::SystemTime;
+
+fn healthy_report() -> HealthReport {
+    HealthReport {
+        backend_reachable: true,
+        aria2_version: Some("1.37.0".to_string()),
+        database_reachable: true,
+        last_poll: Some(SystemTime::now()),
+        poller_alive: true,
+        active_downloads: 2,
+        waiting_downloads: 5,
+        problems: Vec::new(),
+    }
+}
+
+#[test]
+fn test_report_without_problems_is_healthy() {
+    assert!(healthy_report().is_healthy());
+}
+
+#[test]
+fn test_report_with_problems_is_unhealthy() {
+    let report = HealthReport {
+        backend_reachable: false,
+        aria2_version: None,
+        problems: vec!["aria2 is unreachable: connection refused".to_string()],
+        ..healthy_report()
+    };
+    assert!(!report.is_healthy());
+}
+
+#[test]
+fn test_report_serializes_for_probes() {
+    let report = healthy_report();
+    let json = serde_json::to_string(&report).unwrap();
+    let parsed: HealthReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, report);
+}
\ No newline at end of file
@@ -0,0 +1,172 @@
+//! Unit tests for post-download hooks
+
+use burncloud_download::{DownloadError, TaskId, TaskStatus, DownloadStatus};
+use burncloud_download::hooks::{HookPipeline, HookContext, PostProcessingState, MoveToDirectory, FnHook, PostDownloadHook};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_hooks_{}_{}", name, std::process::id()))
+}
+
+fn context(task_id: TaskId, path: PathBuf) -> HookContext {
+    HookContext::new(task_id, "https://example.com/file.zip".to_string(), path)
+}
+
+/// Hook appending its label to a shared log
+fn recording_hook(label: &'static str, log: Arc<std::sync::Mutex<Vec<&'static str>>>) -> Arc<dyn PostDownloadHook> {
+    Arc::new(FnHook::new(label, move |context| {
+        let log = log.clone();
+        async move {
+            log.lock().unwrap().push(label);
+            Ok(context)
+        }
+    }))
+}
+
+#[tokio::test]
+async fn test_hooks_run_in_order_global_first() {
+    let pipeline = HookPipeline::new();
+    let task_id = TaskId::new();
+    let other_task = TaskId::new();
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    pipeline.add_task_hook(task_id, recording_hook("task", log.clone())).await;
+    pipeline.add_global_hook(recording_hook("global-1", log.clone())).await;
+    pipeline.add_global_hook(recording_hook("global-2", log.clone())).await;
+    pipeline.add_task_hook(other_task, recording_hook("other", log.clone())).await;
+
+    assert!(pipeline.prepare(context(task_id, PathBuf::from("/downloads/file.zip"))).await);
+    let path = pipeline.execute(task_id).await.unwrap();
+
+    assert_eq!(path, PathBuf::from("/downloads/file.zip"));
+    assert_eq!(*log.lock().unwrap(), vec!["global-1", "global-2", "task"]);
+    assert_eq!(pipeline.state(task_id).await, Some(PostProcessingState::Succeeded { path }));
+}
+
+#[tokio::test]
+async fn test_task_is_prepared_once() {
+    let pipeline = HookPipeline::new();
+    let task_id = TaskId::new();
+
+    assert_eq!(pipeline.state(task_id).await, None);
+    assert!(pipeline.prepare(context(task_id, PathBuf::from("/downloads/file.zip"))).await);
+    assert!(!pipeline.prepare(context(task_id, PathBuf::from("/downloads/file.zip"))).await);
+    assert_eq!(pipeline.state(task_id).await, Some(PostProcessingState::Pending));
+}
+
+#[tokio::test]
+async fn test_failed_hook_is_retried_without_rerunning_earlier_hooks() {
+    let pipeline = HookPipeline::new();
+    let task_id = TaskId::new();
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    pipeline.add_global_hook(recording_hook("first", log.clone())).await;
+    let flaky_calls = calls.clone();
+    pipeline.add_global_hook(Arc::new(FnHook::new("flaky", move |context| {
+        let calls = flaky_calls.clone();
+        async move {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Err(DownloadError::General("disk unavailable".to_string()));
+            }
+            Ok(context)
+        }
+    }))).await;
+    pipeline.add_global_hook(recording_hook("last", log.clone())).await;
+
+    pipeline.prepare(context(task_id, PathBuf::from("/downloads/file.zip"))).await;
+    match pipeline.execute(task_id).await {
+        Err(DownloadError::PostProcessingFailed { task_id: failed, hook, reason }) => {
+            assert_eq!(failed, task_id);
+            assert_eq!(hook, "flaky");
+            assert!(reason.contains("disk unavailable"));
+        }
+        other => panic!("Expected a post-processing failure, got {:?}", other),
+    }
+    assert!(matches!(pipeline.state(task_id).await, Some(PostProcessingState::Failed { ref hook, .. }) if hook == "flaky"));
+    assert_eq!(*log.lock().unwrap(), vec!["first"]);
+
+    pipeline.retry(task_id).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(*log.lock().unwrap(), vec!["first", "last"]);
+
+    // Only failed runs can be retried
+    assert!(matches!(pipeline.retry(task_id).await, Err(DownloadError::InvalidTaskState { .. })));
+    assert!(matches!(pipeline.retry(TaskId::new()).await, Err(DownloadError::TaskNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_hooks_registered_after_completion_do_not_apply() {
+    let pipeline = HookPipeline::new();
+    let task_id = TaskId::new();
+    let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    pipeline.prepare(context(task_id, PathBuf::from("/downloads/file.zip"))).await;
+    pipeline.add_global_hook(recording_hook("late", log.clone())).await;
+    pipeline.execute(task_id).await.unwrap();
+
+    assert!(log.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_move_hook_updates_path_for_later_hooks() {
+    let dir = unique_temp_dir("move");
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let source = dir.join("file.bin");
+    tokio::fs::write(&source, b"payload").await.unwrap();
+
+    let pipeline = HookPipeline::new();
+    let task_id = TaskId::new();
+    let seen = Arc::new(std::sync::Mutex::new(None));
+
+    pipeline.add_global_hook(Arc::new(MoveToDirectory::new(dir.join("final")))).await;
+    let seen_path = seen.clone();
+    pipeline.add_global_hook(Arc::new(FnHook::new("inspect", move |context: HookContext| {
+        let seen = seen_path.clone();
+        async move {
+            *seen.lock().unwrap() = Some(context.path.clone());
+            Ok(context)
+        }
+    }))).await;
+
+    pipeline.prepare(context(task_id, source.clone())).await;
+    let path = pipeline.execute(task_id).await.unwrap();
+
+    let expected = dir.join("final").join("file.bin");
+    assert_eq!(path, expected);
+    assert_eq!(seen.lock().unwrap().clone(), Some(expected.clone()));
+    assert!(!source.exists());
+    assert_eq!(tokio::fs::read(&expected).await.unwrap(), b"payload");
+
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_set_permissions_hook() {
+    use burncloud_download::hooks::SetPermissions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = unique_temp_dir("chmod");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let file = dir.join("script.sh");
+    tokio::fs::write(&file, b"#!/bin/sh").await.unwrap();
+
+    let mut context = context(TaskId::new(), file.clone());
+    SetPermissions::new(0o750).run(&mut context).await.unwrap();
+
+    let mode = tokio::fs::metadata(&file).await.unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o750);
+
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+}
+
+#[test]
+fn test_post_processing_failed_status() {
+    let status = TaskStatus::PostProcessingFailed("move: permission denied".to_string());
+    assert_eq!(status.to_download_status(), DownloadStatus::Failed("move: permission denied".to_string()));
+    assert!(!status.can_transition_to_duplicate());
+}
\ No newline at end of file
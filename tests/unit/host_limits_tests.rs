@@ -0,0 +1,72 @@
+//! Unit tests for per-host concurrency limits
+
+use burncloud_download::HostLimits;
+use burncloud_download::queue::manager::TaskQueueManager;
+use burncloud_download::types::DownloadStatus;
+use std::path::PathBuf;
+
+#[test]
+fn test_unconfigured_hosts_are_unlimited() {
+    let limits = HostLimits::new();
+    assert_eq!(limits.slot_for("https://example.com/file.zip"), None);
+    assert_eq!(limits.slot_for("not a url"), None);
+}
+
+#[test]
+fn test_domain_limit_covers_subdomains() {
+    let limits = HostLimits::new()
+        .default_limit(4)
+        .limit("Example.com", 2)
+        .limit("cdn.example.com", 1);
+
+    assert_eq!(limits.slot_for("https://example.com/a"), Some(("example.com".to_string(), 2)));
+    assert_eq!(limits.slot_for("https://www.EXAMPLE.com/a"), Some(("example.com".to_string(), 2)));
+    assert_eq!(limits.slot_for("https://eu.cdn.example.com/a"), Some(("cdn.example.com".to_string(), 1)));
+    assert_eq!(limits.slot_for("https://other.org/a"), Some(("other.org".to_string(), 4)));
+}
+
+#[tokio::test]
+async fn test_queue_enforces_host_limit() {
+    let manager = TaskQueueManager::new();
+    manager.set_host_limits(HostLimits::new().limit("example.com", 1)).await.unwrap();
+
+    let first = manager.add_task("https://example.com/a.zip".to_string(), PathBuf::from("/downloads/a.zip")).await.unwrap();
+    let second = manager.add_task("https://cdn.example.com/b.zip".to_string(), PathBuf::from("/downloads/b.zip")).await.unwrap();
+    let other = manager.add_task("https://other.org/c.zip".to_string(), PathBuf::from("/downloads/c.zip")).await.unwrap();
+
+    // The other host starts although a task was queued before it
+    assert_eq!(manager.get_task(first).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Waiting);
+    assert_eq!(manager.get_task(other).await.unwrap().status, DownloadStatus::Downloading);
+
+    manager.complete_task(first).await.unwrap();
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_raising_host_limit_starts_queued_tasks() {
+    let manager = TaskQueueManager::new();
+    manager.set_host_limits(HostLimits::new().default_limit(1)).await.unwrap();
+
+    let first = manager.add_task("https://example.com/a.zip".to_string(), PathBuf::from("/downloads/a.zip")).await.unwrap();
+    let second = manager.add_task("https://example.com/b.zip".to_string(), PathBuf::from("/downloads/b.zip")).await.unwrap();
+    assert_eq!(manager.active_download_count().await, 1);
+
+    manager.set_host_limits(HostLimits::new().default_limit(2)).await.unwrap();
+    assert_eq!(manager.get_task(first).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_resumed_task_waits_for_its_host() {
+    let manager = TaskQueueManager::new();
+    manager.set_host_limits(HostLimits::new().limit("example.com", 1)).await.unwrap();
+
+    let first = manager.add_task("https://example.com/a.zip".to_string(), PathBuf::from("/downloads/a.zip")).await.unwrap();
+    manager.pause_task(first).await.unwrap();
+    let second = manager.add_task("https://example.com/b.zip".to_string(), PathBuf::from("/downloads/b.zip")).await.unwrap();
+
+    manager.resume_task(first).await.unwrap();
+    assert_eq!(manager.get_task(first).await.unwrap().status, DownloadStatus::Waiting);
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Downloading);
+}
\ No newline at end of file
@@ -0,0 +1,135 @@
+//! Unit tests for Hugging Face Hub repository downloads
+
+use burncloud_download::{BasicDownloadManager, DownloadError, DownloadManager, DownloadStatus};
+use burncloud_download::sources::huggingface::{HfFile, HfRepoInfo};
+use burncloud_download::sources::{HfClient, HfRepo};
+use std::path::PathBuf;
+
+const API_RESPONSE: &str = r#"{
+    "id": "org/model",
+    "sha": "0123456789abcdef0123456789abcdef01234567",
+    "siblings": [
+        { "rfilename": "config.json", "size": 12 },
+        { "rfilename": "onnx/model.onnx", "size": 2048, "lfs": { "sha256": "ABCDEF", "size": 2048, "pointerSize": 134 } }
+    ]
+}"#;
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_hf_{}_{}", name, std::process::id()))
+}
+
+#[test]
+fn test_repo_info_from_api_response() {
+    let info = HfRepoInfo::from_json(API_RESPONSE).unwrap();
+
+    assert_eq!(info.sha.as_deref(), Some("0123456789abcdef0123456789abcdef01234567"));
+    assert_eq!(info.files, vec![
+        HfFile { path: "config.json".to_string(), size: Some(12), sha256: None },
+        HfFile { path: "onnx/model.onnx".to_string(), size: Some(2048), sha256: Some("abcdef".to_string()) },
+    ]);
+    assert_eq!(info.total_size(), Some(2060));
+
+    assert!(matches!(HfRepoInfo::from_json("not json"), Err(DownloadError::Network(_))));
+}
+
+#[test]
+fn test_repo_and_file_urls() {
+    let client = HfClient::new();
+    let model = HfRepo::model("org/model");
+
+    assert_eq!(client.api_url(&model).unwrap(), "https://huggingface.co/api/models/org/model/revision/main?blobs=true");
+    assert_eq!(
+        client.file_url(&model, "onnx/model v2.onnx").unwrap(),
+        "https://huggingface.co/org/model/resolve/main/onnx/model%20v2.onnx"
+    );
+
+    // Branch names may contain slashes, which must stay in one segment
+    let dataset = HfRepo::dataset("org/data").revision("refs/pr/1");
+    let mirror = HfClient::new().with_endpoint("https://hf-mirror.example.com/");
+    assert_eq!(
+        mirror.file_url(&dataset, "train.parquet").unwrap(),
+        "https://hf-mirror.example.com/datasets/org/data/resolve/refs%2Fpr%2F1/train.parquet"
+    );
+}
+
+#[test]
+fn test_repo_id_validation() {
+    assert!(HfRepo::model("org/model").validate().is_ok());
+    assert!(HfRepo::model("gpt2").validate().is_ok());
+    assert!(HfRepo::model("org/model-v1.5_final").validate().is_ok());
+
+    for invalid in ["", "org/", "/model", "a/b/c", "org/../x", "org/mo del"] {
+        assert!(matches!(HfRepo::model(invalid).validate(), Err(DownloadError::InvalidUrl(_))), "{} accepted", invalid);
+    }
+}
+
+#[test]
+fn test_group_name_identifies_repo_and_revision() {
+    assert_eq!(HfRepo::model("org/model").group_name(), "huggingface:models/org/model@main");
+    assert_eq!(HfRepo::dataset("org/data").revision("v1").group_name(), "huggingface:datasets/org/data@v1");
+}
+
+#[test]
+fn test_client_debug_hides_token() {
+    let client = HfClient::new().with_token("hf_secret");
+    assert!(!format!("{:?}", client).contains("hf_secret"));
+}
+
+#[tokio::test]
+async fn test_download_files_adds_a_task_per_file() {
+    let dir = unique_temp_dir("files");
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+
+    let manager = BasicDownloadManager::new();
+    let info = HfRepoInfo::from_json(API_RESPONSE).unwrap();
+    let download = HfClient::new()
+        .download_files(&manager, &HfRepo::model("org/model"), &info, &dir)
+        .await
+        .unwrap();
+
+    // The revision is pinned to the listed commit
+    assert_eq!(download.repo.revision, "0123456789abcdef0123456789abcdef01234567");
+    assert_eq!(download.files.len(), 2);
+    assert_eq!(download.files[1].target_path, dir.join("onnx").join("model.onnx"));
+
+    let task = manager.get_task(download.files[1].task_id).await.unwrap();
+    assert_eq!(
+        task.url,
+        "https://huggingface.co/org/model/resolve/0123456789abcdef0123456789abcdef01234567/onnx/model.onnx"
+    );
+    assert_eq!(download.task_ids().len(), 2);
+    assert_eq!(download.group_id, None);
+}
+
+#[tokio::test]
+async fn test_complete_files_on_disk_are_kept() {
+    let dir = unique_temp_dir("resume");
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    tokio::fs::write(dir.join("config.json"), b"{\"a\": true}\n").await.unwrap();
+
+    let manager = BasicDownloadManager::new();
+    let info = HfRepoInfo::from_json(API_RESPONSE).unwrap();
+    let download = HfClient::new()
+        .download_files(&manager, &HfRepo::model("org/model"), &info, &dir)
+        .await
+        .unwrap();
+
+    let config = manager.get_task(download.files[0].task_id).await.unwrap();
+    assert_eq!(config.status, DownloadStatus::Completed);
+    assert_eq!(config.target_path, dir.join("config.json"));
+
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+}
+
+#[tokio::test]
+async fn test_escaping_file_paths_are_rejected() {
+    let manager = BasicDownloadManager::new();
+    let info = HfRepoInfo::from_json(r#"{ "siblings": [{ "rfilename": "../outside.bin" }] }"#).unwrap();
+
+    let result = HfClient::new()
+        .download_files(&manager, &HfRepo::model("org/model"), &info, &unique_temp_dir("escape"))
+        .await;
+    assert!(matches!(result, Err(DownloadError::InvalidPath(_))));
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+//! Unit tests for cancelling operations in flight
+
+use std::sync::Arc;
+use std::time::Duration;
+use burncloud_download::{CancellationToken, DownloadError, TaskId};
+use burncloud_download::services::InflightOps;
+
+async fn hang() -> burncloud_download::Result<()> {
+    std::future::pending().await
+}
+
+#[tokio::test]
+async fn test_operation_runs_to_completion() {
+    let ops = InflightOps::new();
+    let token = CancellationToken::new();
+
+    let result = ops.run("add", Some(&token), async { Ok(7) }).await.unwrap();
+    assert_eq!(result, 7);
+}
+
+#[tokio::test]
+async fn test_token_aborts_operation() {
+    let ops = InflightOps::new();
+    let token = CancellationToken::new();
+
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        canceller.cancel();
+    });
+
+    let result = ops.run("add", Some(&token), hang()).await;
+    assert!(matches!(result, Err(DownloadError::Cancelled(_))));
+}
+
+#[tokio::test]
+async fn test_abort_all_aborts_every_operation() {
+    let ops = Arc::new(InflightOps::new());
+
+    let first = tokio::spawn({
+        let ops = ops.clone();
+        async move { ops.run("pause", None, hang()).await }
+    });
+    let second = tokio::spawn({
+        let ops = ops.clone();
+        async move { ops.run("resume", Some(&CancellationToken::new()), hang()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    ops.abort_all();
+
+    assert!(matches!(first.await.unwrap(), Err(DownloadError::Cancelled(_))));
+    assert!(matches!(second.await.unwrap(), Err(DownloadError::Cancelled(_))));
+
+    // Operations started afterwards are not affected
+    assert_eq!(ops.run("cancel", None, async { Ok(1) }).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_tracked_token_applies_to_task() {
+    let ops = InflightOps::new();
+    let task_id = TaskId::new();
+    let token = CancellationToken::new();
+
+    assert!(ops.token_for(task_id).await.is_none());
+    ops.track(task_id, Some(token.clone())).await;
+    assert!(ops.token_for(task_id).await.is_some());
+
+    ops.remove_task(task_id).await;
+    assert!(ops.token_for(task_id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_cancelled_token_is_forgotten() {
+    let ops = InflightOps::new();
+    let task_id = TaskId::new();
+    let token = CancellationToken::new();
+    ops.track(task_id, Some(token.clone())).await;
+
+    token.cancel();
+    assert!(ops.token_for(task_id).await.is_none());
+}
\ No newline at end of file
@@ -0,0 +1,90 @@
+//! Unit tests for fault isolation of event handlers
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::sync::{broadcast, RwLock};
+
+use burncloud_download::{HandlerFailure, TaskId, DownloadStatus, DownloadProgress};
+use burncloud_download::services::IsolatedHandler;
+use burncloud_download::traits::DownloadEventHandler;
+
+/// Handler panicking on completion and hanging on failure, counting progress
+#[derive(Default)]
+struct Faulty {
+    progress_calls: AtomicUsize,
+}
+
+#[async_trait]
+impl DownloadEventHandler for Faulty {
+    async fn on_status_changed(&self, _task_id: TaskId, _old_status: DownloadStatus, _new_status: DownloadStatus) {}
+
+    async fn on_progress_updated(&self, _task_id: TaskId, _progress: DownloadProgress) {
+        self.progress_calls.fetch_add(1, Ordering::SeqCst);
+    }
+
+    async fn on_download_completed(&self, _task_id: TaskId) {
+        panic!("handler bug");
+    }
+
+    async fn on_download_failed(&self, _task_id: TaskId, _error: String) {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_panic_is_contained_and_reported() {
+    let faulty = Arc::new(Faulty::default());
+    let (errors, mut received) = broadcast::channel(8);
+    let handler = IsolatedHandler::new(faulty.clone(), Duration::from_secs(1), false, errors);
+
+    handler.on_download_completed(TaskId::new()).await;
+
+    let error = received.recv().await.unwrap();
+    assert_eq!(error.event, "on_download_completed");
+    assert_eq!(error.failure, HandlerFailure::Panicked("handler bug".to_string()));
+    assert!(!error.removed);
+    let registered: Arc<dyn DownloadEventHandler> = faulty.clone();
+    assert!(Arc::ptr_eq(&error.handler, &registered));
+
+    // The handler keeps receiving events
+    handler.on_progress_updated(TaskId::new(), DownloadProgress::new()).await;
+    assert_eq!(faulty.progress_calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_slow_call_is_aborted_after_timeout() {
+    let (errors, mut received) = broadcast::channel(8);
+    let handler = IsolatedHandler::new(Arc::new(Faulty::default()), Duration::from_millis(50), false, errors);
+
+    let started = std::time::Instant::now();
+    handler.on_download_failed(TaskId::new(), "boom".to_string()).await;
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    let error = received.recv().await.unwrap();
+    assert_eq!(error.event, "on_download_failed");
+    assert_eq!(error.failure, HandlerFailure::TimedOut(Duration::from_millis(50)));
+}
+
+#[tokio::test]
+async fn test_faulty_handler_is_removed() {
+    let faulty = Arc::new(Faulty::default());
+    let (errors, mut received) = broadcast::channel(8);
+    let list = Arc::new(RwLock::new(Vec::<Arc<dyn DownloadEventHandler>>::new()));
+    let handler = Arc::new(IsolatedHandler::new(faulty.clone(), Duration::from_secs(1), true, errors)
+        .listed_in(&list));
+    list.write().await.push(handler.clone());
+
+    handler.on_download_completed(TaskId::new()).await;
+
+    assert!(received.recv().await.unwrap().removed);
+    assert!(handler.is_removed());
+    assert!(list.read().await.is_empty());
+
+    // Removed handlers receive nothing more, nor are they reported again
+    handler.on_progress_updated(TaskId::new(), DownloadProgress::new()).await;
+    handler.on_download_completed(TaskId::new()).await;
+    assert_eq!(faulty.progress_calls.load(Ordering::SeqCst), 0);
+    assert!(received.try_recv().is_err());
+}
\ No newline at end of file
@@ -0,0 +1,157 @@
+//! Unit tests for task list ordering
+
+use burncloud_download::{BasicDownloadManager, DownloadManager, ListOrder, PersistentAria2Manager, TaskQueueManager};
+use burncloud_download::types::DownloadTask;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use super::support::MemoryBackend;
+
+fn task_at(name: &str, created_secs_ago: u64, updated_secs_ago: u64) -> DownloadTask {
+    let now = SystemTime::now();
+    let mut task = DownloadTask::new(format!("https://example.com/{}", name), PathBuf::from(format!("/downloads/{}", name)));
+    task.created_at = now - Duration::from_secs(created_secs_ago);
+    task.updated_at = now - Duration::from_secs(updated_secs_ago);
+    task
+}
+
+fn names(tasks: &[DownloadTask]) -> Vec<String> {
+    tasks.iter().map(|task| task.url.rsplit('/').next().unwrap().to_string()).collect()
+}
+
+#[test]
+fn test_sort_by_creation_and_update_time() {
+    let mut tasks = vec![task_at("b", 20, 5), task_at("a", 30, 1), task_at("c", 10, 15)];
+
+    ListOrder::CreatedAsc.sort(&mut tasks);
+    assert_eq!(names(&tasks), ["a", "b", "c"]);
+
+    ListOrder::CreatedDesc.sort(&mut tasks);
+    assert_eq!(names(&tasks), ["c", "b", "a"]);
+
+    ListOrder::UpdatedAsc.sort(&mut tasks);
+    assert_eq!(names(&tasks), ["c", "b", "a"]);
+
+    ListOrder::UpdatedDesc.sort(&mut tasks);
+    assert_eq!(names(&tasks), ["a", "b", "c"]);
+}
+
+#[test]
+fn test_default_order_is_newest_first() {
+    assert_eq!(ListOrder::default(), ListOrder::CreatedDesc);
+}
+
+#[tokio::test]
+async fn test_duplicate_candidates_newest_first() {
+    let manager = TaskQueueManager::new();
+    let url = "https://example.com/model.bin";
+    let path = PathBuf::from("/downloads/model.bin");
+
+    let older = manager.add_task(url.to_string(), path.clone()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let newer = manager.add_task(url.to_string(), path.clone()).await.unwrap();
+
+    let candidates = manager.get_duplicate_candidates(url, &path).await.unwrap();
+    assert_eq!(candidates, vec![newer, older]);
+
+    let tasks = manager.get_duplicate_tasks(url, &path, ListOrder::CreatedAsc).await.unwrap();
+    assert_eq!(tasks.iter().map(|task| task.id).collect::<Vec<_>>(), vec![older, newer]);
+    assert!(tasks[0].created_at <= tasks[1].created_at);
+}
+
+#[tokio::test]
+async fn test_duplicate_tasks_match_any_spelling_of_the_path() {
+    let dir = std::env::temp_dir().join(format!("burncloud_list_order_spelling_{}", std::process::id()));
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new()))
+        .download_dir(&dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    // Nothing listens on the discard port, so probes fail right away
+    let url = "http://127.0.0.1:9/model.bin";
+
+    let task_id = manager.add_download(url.to_string(), dir.join("model.bin")).await.unwrap();
+    let same_path = dir.join("sub").join("..").join("model.bin");
+    let tasks = manager.get_duplicate_tasks(url, &same_path, ListOrder::CreatedAsc).await.unwrap();
+    assert_eq!(tasks.iter().map(|task| task.id).collect::<Vec<_>>(), vec![task_id]);
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_list_tasks_ordered() {
+    let manager = TaskQueueManager::new();
+
+    let mut added = Vec::new();
+    for i in 0..3 {
+        added.push(manager.add_task(
+            format!("https://example.com/file{}.zip", i),
+            PathBuf::from(format!("/downloads/file{}.zip", i))
+        ).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let tasks = manager.list_tasks_ordered(ListOrder::CreatedAsc).await.unwrap();
+    assert_eq!(tasks.iter().map(|task| task.id).collect::<Vec<_>>(), added);
+}
+
+#[tokio::test]
+async fn test_list_tasks_with_progress() {
+    let manager = TaskQueueManager::new();
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    let snapshot = manager.list_tasks_with_progress().await.unwrap();
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].0.id, task_id);
+    assert_eq!(snapshot[0].1.downloaded_bytes, 0);
+}
+
+#[tokio::test]
+async fn test_list_tasks_is_stable_oldest_first() {
+    let manager = BasicDownloadManager::new();
+
+    let mut added = Vec::new();
+    for i in 0..5 {
+        added.push(manager.add_download(
+            format!("https://example.com/file{}.zip", i),
+            PathBuf::from(format!("/downloads/file{}.zip", i))
+        ).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let ids = |tasks: Vec<DownloadTask>| tasks.into_iter().map(|task| task.id).collect::<Vec<_>>();
+    assert_eq!(ids(manager.list_tasks().await.unwrap()), added);
+    assert_eq!(ids(manager.list_tasks().await.unwrap()), added);
+}
+
+#[tokio::test]
+async fn test_list_tasks_page() {
+    let manager = TaskQueueManager::new();
+
+    let mut added = Vec::new();
+    for i in 0..5 {
+        added.push(manager.add_task(
+            format!("https://example.com/file{}.zip", i),
+            PathBuf::from(format!("/downloads/file{}.zip", i))
+        ).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    added.reverse();
+
+    let mut paged = Vec::new();
+    for offset in (0..6).step_by(2) {
+        let page = manager.list_tasks_page(offset, 2, ListOrder::CreatedDesc).await.unwrap();
+        assert!(page.len() <= 2);
+        paged.extend(page.into_iter().map(|task| task.id));
+    }
+    assert_eq!(paged, added);
+
+    assert!(manager.list_tasks_page(10, 2, ListOrder::CreatedDesc).await.unwrap().is_empty());
+    assert!(manager.list_tasks_page(0, 0, ListOrder::CreatedDesc).await.unwrap().is_empty());
+}
\ No newline at end of file
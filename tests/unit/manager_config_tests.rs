@@ -0,0 +1,181 @@
+//! Unit tests for persistent manager configuration
+
+use std::path::PathBuf;
+use burncloud_download::{ManagerConfig, DownloadError};
+use burncloud_download::manager::config::{ENV_POLL_INTERVAL_SECS, ENV_DOWNLOAD_DIR, ENV_MAX_RETRIES, ENV_SEGMENTS, ENV_MIN_SPLIT_SIZE, ENV_MAX_FILE_SIZE, ENV_RPC_REQUEST_TIMEOUT_SECS, ENV_NO_SPACE_HEADROOM, ENV_MAX_FILENAME_LENGTH, ENV_MAX_QUEUED_TASKS, ENV_MAX_ADDITIONS_PER_SECOND, ENV_ADMISSION_MODE, ENV_BT_LISTEN_PORTS, ENV_BT_DHT, ENV_BT_MAX_PEERS, ENV_BT_SEED_TIME_SECS, ENV_SFTP_KNOWN_HOSTS};
+use burncloud_download::{AdmissionLimits, AdmissionMode, PortRange, TorrentSettings};
+
+#[test]
+fn test_default_config_matches_manager_defaults() {
+    let config = ManagerConfig::default();
+
+    assert_eq!(config.rpc_url, "http://localhost:6800/jsonrpc");
+    assert_eq!(config.poll_interval_secs, 1);
+    assert_eq!(config.progress_save_interval_secs, 5);
+    assert_eq!(config.download_dir, PathBuf::from("./data"));
+    assert!(config.db_path.is_none());
+    assert!(config.max_concurrent_downloads.is_none());
+}
+
+#[test]
+fn test_environment_overrides() {
+    std::env::set_var(ENV_POLL_INTERVAL_SECS, "3");
+    std::env::set_var(ENV_DOWNLOAD_DIR, "/srv/downloads");
+    std::env::set_var(ENV_MAX_RETRIES, "7");
+
+    let config = ManagerConfig::from_env().unwrap();
+    assert_eq!(config.poll_interval_secs, 3);
+    assert_eq!(config.download_dir, PathBuf::from("/srv/downloads"));
+    assert_eq!(config.retry_policy.max_attempts, 7);
+
+    std::env::set_var(ENV_POLL_INTERVAL_SECS, "often");
+    assert!(matches!(ManagerConfig::from_env(), Err(DownloadError::Config(_))));
+
+    std::env::remove_var(ENV_POLL_INTERVAL_SECS);
+    std::env::remove_var(ENV_DOWNLOAD_DIR);
+    std::env::remove_var(ENV_MAX_RETRIES);
+}
+
+#[cfg(feature = "toml-config")]
+#[test]
+fn test_toml_config_uses_defaults_for_missing_keys() {
+    let config = ManagerConfig::from_toml_str(r#"
+        rpc_url = "http://aria2:6800/jsonrpc"
+        max_concurrent_downloads = 8
+    "#).unwrap();
+
+    assert_eq!(config.rpc_url, "http://aria2:6800/jsonrpc");
+    assert_eq!(config.max_concurrent_downloads, Some(8));
+    assert_eq!(config.poll_interval_secs, 1);
+}
+
+#[test]
+fn test_segment_defaults_from_environment() {
+    std::env::set_var(ENV_SEGMENTS, "16");
+    std::env::set_var(ENV_MIN_SPLIT_SIZE, "4194304");
+
+    let config = ManagerConfig::from_env().unwrap();
+    assert_eq!(config.segment_defaults.segments, Some(16));
+    assert_eq!(config.segment_defaults.max_connections_per_server, None);
+    assert_eq!(config.segment_defaults.min_split_size, Some(4 * 1024 * 1024));
+
+    std::env::remove_var(ENV_SEGMENTS);
+    std::env::remove_var(ENV_MIN_SPLIT_SIZE);
+}
+
+#[cfg(feature = "toml-config")]
+#[test]
+fn test_toml_segment_defaults() {
+    let config = ManagerConfig::from_toml_str(r#"
+        [segment_defaults]
+        segments = 16
+        max_connections_per_server = 8
+    "#).unwrap();
+
+    assert_eq!(config.segment_defaults.segments, Some(16));
+    assert_eq!(config.segment_defaults.max_connections_per_server, Some(8));
+    assert!(config.segment_defaults.validate().is_ok());
+}
+
+#[cfg(feature = "toml-config")]
+#[test]
+fn test_toml_download_profiles() {
+    let config = ManagerConfig::from_toml_str(r#"
+        [profiles.models]
+        directory = "/mnt/models"
+        concurrency = 8
+
+        [profiles.datasets]
+        directory = "/data/sets"
+        speed_limit = 1048576
+        overwrite = "Skip"
+    "#).unwrap();
+
+    assert_eq!(config.profiles.len(), 2);
+    assert_eq!(config.profiles["models"].directory, PathBuf::from("/mnt/models"));
+    assert_eq!(config.profiles["models"].concurrency, Some(8));
+    assert_eq!(config.profiles["datasets"].speed_limit, Some(1024 * 1024));
+    assert_eq!(config.profiles["datasets"].overwrite, burncloud_download::OverwritePolicy::Skip);
+    assert!(ManagerConfig::default().profiles.is_empty());
+}
+
+#[test]
+fn test_max_file_size_from_environment() {
+    std::env::set_var(ENV_MAX_FILE_SIZE, "1048576");
+    assert_eq!(ManagerConfig::from_env().unwrap().max_file_size, Some(1024 * 1024));
+    std::env::remove_var(ENV_MAX_FILE_SIZE);
+
+    assert_eq!(ManagerConfig::default().max_file_size, None);
+}
+
+#[test]
+fn test_max_filename_length_from_environment() {
+    std::env::set_var(ENV_MAX_FILENAME_LENGTH, "64");
+    assert_eq!(ManagerConfig::from_env().unwrap().max_filename_length, 64);
+    std::env::remove_var(ENV_MAX_FILENAME_LENGTH);
+
+    assert_eq!(ManagerConfig::default().max_filename_length, burncloud_download::probe::DEFAULT_MAX_FILENAME_LENGTH);
+}
+
+#[test]
+fn test_no_space_headroom_from_environment() {
+    std::env::set_var(ENV_NO_SPACE_HEADROOM, "4096");
+    assert_eq!(ManagerConfig::from_env().unwrap().no_space_headroom, 4096);
+    std::env::remove_var(ENV_NO_SPACE_HEADROOM);
+
+    assert_eq!(ManagerConfig::default().no_space_headroom, burncloud_download::services::DEFAULT_NO_SPACE_HEADROOM);
+}
+
+#[test]
+fn test_rpc_timeouts_from_environment() {
+    std::env::set_var(ENV_RPC_REQUEST_TIMEOUT_SECS, "5");
+    let config = ManagerConfig::from_env().unwrap();
+    std::env::remove_var(ENV_RPC_REQUEST_TIMEOUT_SECS);
+
+    assert_eq!(config.rpc_timeouts.request, std::time::Duration::from_secs(5));
+    assert_eq!(config.rpc_timeouts.connect, ManagerConfig::default().rpc_timeouts.connect);
+}
+
+#[test]
+fn test_admission_limits_from_environment() {
+    std::env::set_var(ENV_MAX_QUEUED_TASKS, "500");
+    std::env::set_var(ENV_MAX_ADDITIONS_PER_SECOND, "20");
+    std::env::set_var(ENV_ADMISSION_MODE, "wait");
+    let config = ManagerConfig::from_env().unwrap();
+    std::env::remove_var(ENV_MAX_QUEUED_TASKS);
+    std::env::remove_var(ENV_MAX_ADDITIONS_PER_SECOND);
+    std::env::remove_var(ENV_ADMISSION_MODE);
+
+    assert_eq!(config.admission, AdmissionLimits::new().max_queued_tasks(500).max_additions_per_second(20).mode(AdmissionMode::Wait));
+    assert_eq!(ManagerConfig::default().admission.mode, AdmissionMode::Reject);
+}
+
+#[test]
+fn test_torrent_settings_from_environment() {
+    std::env::set_var(ENV_BT_LISTEN_PORTS, "6881-6999");
+    std::env::set_var(ENV_BT_DHT, "false");
+    std::env::set_var(ENV_BT_MAX_PEERS, "80");
+    std::env::set_var(ENV_BT_SEED_TIME_SECS, "3600");
+    let config = ManagerConfig::from_env().unwrap();
+    std::env::remove_var(ENV_BT_LISTEN_PORTS);
+    std::env::remove_var(ENV_BT_DHT);
+    std::env::remove_var(ENV_BT_MAX_PEERS);
+    std::env::remove_var(ENV_BT_SEED_TIME_SECS);
+
+    assert_eq!(config.torrent, TorrentSettings::new()
+        .listen_ports(PortRange::new(6881, 6999))
+        .dht(false)
+        .max_peers(80)
+        .seed_time(std::time::Duration::from_secs(3600)));
+    assert!(ManagerConfig::default().torrent.is_empty());
+}
+
+#[test]
+fn test_sftp_known_hosts_from_environment() {
+    std::env::set_var(ENV_SFTP_KNOWN_HOSTS, "/etc/burncloud/known_hosts");
+    let config = ManagerConfig::from_env().unwrap();
+    std::env::remove_var(ENV_SFTP_KNOWN_HOSTS);
+
+    assert_eq!(config.sftp_known_hosts, Some(std::path::PathBuf::from("/etc/burncloud/known_hosts")));
+    assert_eq!(ManagerConfig::default().sftp_known_hosts, None);
+}
\ No newline at end of file
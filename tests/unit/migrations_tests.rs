@@ -0,0 +1,91 @@
+//! Unit tests for the schema migrations of the crate-owned database
+
+use burncloud_download::migrations::{self, MIGRATIONS};
+use burncloud_download::services::TaskMetadataStore;
+use burncloud_download::TaskId;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::Row;
+use std::path::PathBuf;
+
+fn unique_db(name: &str) -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("burncloud_migrations_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join("metadata.db");
+    (dir, path)
+}
+
+#[test]
+fn test_versions_increase() {
+    let versions: Vec<u32> = MIGRATIONS.iter().map(|migration| migration.version).collect();
+    assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(migrations::latest_version(), *versions.last().unwrap());
+}
+
+#[tokio::test]
+async fn test_migrations_run_once() {
+    let (dir, path) = unique_db("once");
+
+    let applied = migrations::migrate(&path).await.unwrap();
+    assert_eq!(applied, MIGRATIONS.iter().map(|migration| migration.version).collect::<Vec<_>>());
+    assert!(migrations::migrate(&path).await.unwrap().is_empty());
+
+    let pool = SqlitePoolOptions::new().connect(&format!("sqlite://{}", path.display())).await.unwrap();
+    assert_eq!(migrations::current_version(&pool).await.unwrap(), migrations::latest_version());
+    let tables: Vec<String> = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .fetch_all(&pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| row.get("name"))
+        .collect();
+    for table in ["task_metadata", "task_journal", "progress_history", "task_url_hashes", "domain_usage"] {
+        assert!(tables.iter().any(|name| name == table), "missing table {}", table);
+    }
+    pool.close().await;
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_database_without_versions_is_adopted() {
+    let (dir, path) = unique_db("adopt");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // A database written before migrations existed, with data in it
+    let pool = SqlitePoolOptions::new()
+        .connect(&format!("sqlite://{}?mode=rwc", path.display()))
+        .await
+        .unwrap();
+    sqlx::query(MIGRATIONS[0].statements[1]).execute(&pool).await.unwrap();
+    sqlx::query("INSERT INTO task_gid_mapping (task_id, gid, updated_at) VALUES (?, 'abc', 0)")
+        .bind(serde_json::to_string(&TaskId::new()).unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(migrations::current_version(&pool).await.unwrap(), 0);
+    pool.close().await;
+
+    assert_eq!(migrations::migrate(&path).await.unwrap().len(), MIGRATIONS.len());
+
+    let pool = SqlitePoolOptions::new().connect(&format!("sqlite://{}", path.display())).await.unwrap();
+    let rows = sqlx::query("SELECT COUNT(*) AS count FROM task_gid_mapping").fetch_one(&pool).await.unwrap();
+    assert_eq!(rows.get::<i64, _>("count"), 1);
+    pool.close().await;
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_stores_open_migrated_databases() {
+    let (dir, path) = unique_db("stores");
+    let task_id = TaskId::new();
+
+    let store = TaskMetadataStore::open(&path).await.unwrap();
+    store.put_gid(&task_id, "2089b05ecca3d829").await.unwrap();
+    assert!(migrations::migrate(&path).await.unwrap().is_empty());
+
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    store.put_gid(&task_id, "2089b05ecca3d829").await.unwrap();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,107 @@
+//! Unit tests for mirror health tracking
+
+use burncloud_download::{MirrorManager, MirrorStats};
+use burncloud_download::types::TaskId;
+use std::time::Duration;
+
+fn urls() -> Vec<String> {
+    vec![
+        "https://a.example.com/file.bin".to_string(),
+        "https://b.example.com/file.bin".to_string(),
+        "https://c.example.com:8443/file.bin".to_string(),
+    ]
+}
+
+#[tokio::test]
+async fn test_rank_keeps_order_without_history() {
+    let mirrors = MirrorManager::new();
+    assert_eq!(mirrors.rank(urls()).await, urls());
+    assert!(mirrors.stats().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_failing_mirror_moves_to_the_back() {
+    let mirrors = MirrorManager::new();
+
+    for _ in 0..3 {
+        let task_id = TaskId::new();
+        mirrors.track(task_id, "https://a.example.com/other.bin").await;
+        mirrors.task_failed(task_id).await;
+    }
+    let task_id = TaskId::new();
+    mirrors.track(task_id, "https://c.example.com:8443/other.bin").await;
+    mirrors.task_completed(task_id).await;
+
+    let ranked = mirrors.rank(urls()).await;
+    assert_eq!(ranked[0], "https://c.example.com:8443/file.bin");
+    assert_eq!(ranked[2], "https://a.example.com/file.bin");
+
+    let stats = mirrors.host_stats("https://a.example.com/x").await.unwrap();
+    assert_eq!(stats.failures, 3);
+    assert_eq!(stats.failure_rate(), 1.0);
+    assert!(stats.last_failure.is_some());
+    assert_eq!(mirrors.stats().await[0].host, "c.example.com:8443");
+}
+
+#[tokio::test]
+async fn test_first_bytes_record_latency_once() {
+    let mirrors = MirrorManager::new();
+    let task_id = TaskId::new();
+    mirrors.track(task_id, "https://a.example.com/file.bin").await;
+    assert!(mirrors.awaits_first_bytes(task_id).await);
+
+    // Nothing arrived yet
+    mirrors.observe(task_id, 0).await;
+    assert!(mirrors.awaits_first_bytes(task_id).await);
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    mirrors.observe(task_id, 1024).await;
+    assert!(!mirrors.awaits_first_bytes(task_id).await);
+
+    let latency = mirrors.host_stats("https://a.example.com/").await.unwrap().average_latency.unwrap();
+    assert!(latency >= Duration::from_millis(20));
+
+    mirrors.observe(task_id, 4096).await;
+    assert_eq!(mirrors.host_stats("https://a.example.com/").await.unwrap().average_latency, Some(latency));
+}
+
+#[tokio::test]
+async fn test_retried_task_counts_both_outcomes() {
+    let mirrors = MirrorManager::new();
+    let task_id = TaskId::new();
+    mirrors.track(task_id, "https://b.example.com/file.bin").await;
+
+    mirrors.task_failed(task_id).await;
+    mirrors.task_completed(task_id).await;
+    // Untracked once completed
+    mirrors.task_completed(task_id).await;
+
+    let stats = mirrors.host_stats("https://b.example.com/file.bin").await.unwrap();
+    assert_eq!((stats.successes, stats.failures), (1, 1));
+}
+
+#[tokio::test]
+async fn test_forgotten_task_counts_nothing() {
+    let mirrors = MirrorManager::new();
+    let task_id = TaskId::new();
+    mirrors.track(task_id, "https://b.example.com/file.bin").await;
+    mirrors.forget(task_id).await;
+    mirrors.task_failed(task_id).await;
+
+    assert!(mirrors.stats().await.is_empty());
+}
+
+#[test]
+fn test_score_prefers_reliable_fast_hosts() {
+    let unknown = MirrorStats::new("unknown.example.com");
+    assert_eq!(unknown.score(), 0.5);
+    assert_eq!(unknown.failure_rate(), 0.0);
+
+    let mut reliable = MirrorStats::new("reliable.example.com");
+    reliable.successes = 8;
+    let mut slow = reliable.clone();
+    slow.average_latency = Some(Duration::from_secs(2));
+
+    assert!(reliable.score() > unknown.score());
+    assert!(reliable.score() > slow.score());
+}
\ No newline at end of file
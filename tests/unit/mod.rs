@@ -2,6 +2,7 @@
 //!
 //! Following TDD methodology - all tests are written first and must fail before implementation.
 
+pub mod support;
 pub mod file_identifier_tests;
 pub mod task_status_tests;
 pub mod duplicate_policy_tests;
@@ -10,4 +11,89 @@ pub mod hash_calculation_tests;
 pub mod duplicate_detector_tests;
 pub mod task_repository_tests;
 pub mod queue_manager_tests;
-pub mod persistent_aria2_manager_tests;
\ No newline at end of file
+pub mod persistent_aria2_manager_tests;
+pub mod download_backend_tests;
+pub mod bandwidth_limiter_tests;
+pub mod retry_policy_tests;
+pub mod download_options_tests;
+pub mod event_bus_tests;
+pub mod download_scheduler_tests;
+pub mod storage_checker_tests;
+pub mod manager_config_tests;
+pub mod aria2_supervisor_tests;
+pub mod task_metadata_store_tests;
+pub mod partial_download_tests;
+pub mod duplicate_resolver_tests;
+pub mod hash_calculator_tests;
+pub mod target_path_registry_tests;
+pub mod overwrite_policy_tests;
+pub mod hook_pipeline_tests;
+pub mod archive_extraction_tests;
+pub mod download_probe_tests;
+pub mod url_policy_tests;
+pub mod credentials_tests;
+pub mod huggingface_tests;
+pub mod task_groups_tests;
+pub mod status_tracker_tests;
+pub mod task_journal_tests;
+pub mod recovery_report_tests;
+pub mod task_export_tests;
+pub mod aria2_session_tests;
+#[cfg(feature = "server")]
+pub mod control_server_tests;
+pub mod aria2_notifications_tests;
+pub mod speed_smoother_tests;
+pub mod progress_history_tests;
+pub mod completion_waiters_tests;
+pub mod blocking_tests;
+pub mod scheme_router_tests;
+pub mod mirror_manager_tests;
+pub mod file_allocation_tests;
+pub mod part_file_tests;
+pub mod gc_tests;
+pub mod host_limits_tests;
+pub mod queue_scheduler_tests;
+pub mod throttle_schedule_tests;
+pub mod aria2_rpc_tests;
+pub mod health_report_tests;
+pub mod list_order_tests;
+pub mod remote_validators_tests;
+pub mod conditional_download_tests;
+pub mod size_guard_tests;
+pub mod content_policy_tests;
+pub mod scan_hook_tests;
+pub mod throttled_handler_tests;
+pub mod inflight_ops_tests;
+pub mod timeout_backend_tests;
+pub mod task_cache_tests;
+pub mod domain_usage_tests;
+pub mod isolated_handler_tests;
+pub mod handler_registry_tests;
+pub mod migrations_tests;
+pub mod ephemeral_manager_tests;
+#[cfg(feature = "postgres")]
+pub mod postgres_repository_tests;
+pub mod field_cipher_tests;
+pub mod download_profile_tests;
+pub mod task_owner_tests;
+pub mod task_deadline_tests;
+pub mod probe_url_tests;
+pub mod update_task_url_tests;
+pub mod copy_hook_tests;
+pub mod take_file_tests;
+pub mod download_stream_tests;
+pub mod piece_checksum_tests;
+pub mod failure_info_tests;
+pub mod no_space_tests;
+pub mod progress_kind_tests;
+pub mod admission_tests;
+pub mod cancelled_status_tests;
+pub mod trash_tests;
+pub mod file_progress_tests;
+pub mod torrent_settings_tests;
+pub mod speed_sample_tests;
+pub mod event_listener_tests;
+pub mod serialization_tests;
+pub mod verified_reuse_tests;
+pub mod relocate_task_tests;
+pub mod checksum_hook_tests;
\ No newline at end of file
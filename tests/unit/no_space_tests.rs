@@ -0,0 +1,122 @@
+//! Unit tests for pausing downloads on a full disk
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{PersistentAria2Manager, TaskStatus};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{FailureKind, RetryPolicy};
+use burncloud_download::services::NoSpaceWatch;
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_no_space_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .no_space_headroom(u64::MAX)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    manager
+}
+
+async fn wait_for_status(manager: &PersistentAria2Manager, task_id: TaskId, expected: TaskStatus) {
+    for _ in 0..100 {
+        if manager.task_status(task_id).await.unwrap() == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Task {} did not reach {:?}, it is {:?}", task_id, expected, manager.task_status(task_id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_watch_waits_for_headroom() {
+    let dir = test_dir("watch");
+    let watch = NoSpaceWatch::new(u64::MAX);
+    let task_id = TaskId::new();
+    assert!(watch.is_empty().await);
+
+    watch.pause(task_id, dir.join("model.bin")).await;
+    assert!(watch.is_paused(task_id).await);
+    assert!(watch.ready().await.is_empty());
+
+    // Tasks stay in the watch until they are removed
+    watch.set_headroom(0);
+    assert_eq!(watch.headroom(), 0);
+    assert_eq!(watch.ready().await, vec![task_id]);
+    assert_eq!(watch.ready().await, vec![task_id]);
+
+    assert!(watch.remove_task(task_id).await);
+    assert!(!watch.remove_task(task_id).await);
+    assert!(watch.is_empty().await);
+}
+
+#[tokio::test]
+async fn test_disk_full_pauses_instead_of_failing() {
+    let dir = test_dir("paused");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.fail(task_id, "Not enough disk space", 9).await;
+
+    wait_for_status(&manager, task_id, TaskStatus::PausedNoSpace).await;
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Paused);
+    let failure = manager.failure_info(task_id).await.unwrap().unwrap();
+    assert_eq!(failure.kind, FailureKind::DiskFull);
+
+    // Without the headroom the task keeps waiting
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::PausedNoSpace);
+
+    manager.set_no_space_headroom(0);
+    wait_for_status(&manager, task_id, TaskStatus::Downloading).await;
+    assert_eq!(manager.failure_history(task_id, usize::MAX).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_other_failures_still_fail() {
+    let dir = test_dir("other");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.fail(task_id, "The response status is not successful. status=404", 3).await;
+
+    wait_for_status(&manager, task_id, TaskStatus::Failed("The response status is not successful. status=404".to_string())).await;
+}
+
+#[tokio::test]
+async fn test_manual_resume_stops_waiting() {
+    let dir = test_dir("manual");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    backend.fail(task_id, "No space left on device", 1).await;
+    wait_for_status(&manager, task_id, TaskStatus::PausedNoSpace).await;
+
+    backend.resume(task_id).await.unwrap();
+    wait_for_status(&manager, task_id, TaskStatus::Downloading).await;
+
+    // A later pause by hand is an ordinary pause
+    backend.pause(task_id).await.unwrap();
+    wait_for_status(&manager, task_id, TaskStatus::Paused).await;
+}
\ No newline at end of file
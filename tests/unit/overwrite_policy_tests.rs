@@ -0,0 +1,83 @@
+//! Unit tests for overwrite policies
+
+use burncloud_download::{
+    BasicDownloadManager, DownloadError, DownloadManager, DownloadOptions, DownloadStatus,
+    OverwritePolicy, TaskQueueManager,
+};
+use burncloud_download::models::TargetAction;
+use std::path::PathBuf;
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_overwrite_{}_{}", name, std::process::id()))
+}
+
+async fn existing_file(name: &str) -> (PathBuf, PathBuf) {
+    let dir = unique_temp_dir(name);
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let target = dir.join("file.zip");
+    tokio::fs::write(&target, b"existing").await.unwrap();
+    (dir, target)
+}
+
+#[test]
+fn test_default_policy_renames() {
+    assert_eq!(OverwritePolicy::default(), OverwritePolicy::RenameWithSuffix);
+    assert_eq!(DownloadOptions::default().overwrite, OverwritePolicy::RenameWithSuffix);
+}
+
+#[test]
+fn test_missing_file_is_downloaded_under_every_policy() {
+    let target = unique_temp_dir("missing").join("file.zip");
+    for policy in [OverwritePolicy::Overwrite, OverwritePolicy::Skip, OverwritePolicy::RenameWithSuffix, OverwritePolicy::Fail] {
+        assert_eq!(
+            policy.apply(&target).unwrap(),
+            TargetAction::Download { path: target.clone(), overwrite: false }
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_policies_for_existing_file() {
+    let (dir, target) = existing_file("apply").await;
+
+    assert_eq!(
+        OverwritePolicy::Overwrite.apply(&target).unwrap(),
+        TargetAction::Download { path: target.clone(), overwrite: true }
+    );
+    assert_eq!(OverwritePolicy::Skip.apply(&target).unwrap(), TargetAction::Skip(target.clone()));
+    assert_eq!(
+        OverwritePolicy::RenameWithSuffix.apply(&target).unwrap(),
+        TargetAction::Download { path: dir.join("file (1).zip"), overwrite: false }
+    );
+    assert!(matches!(OverwritePolicy::Fail.apply(&target), Err(DownloadError::FileExists(path)) if path == target));
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_managers_apply_policy_before_start() {
+    let (dir, target) = existing_file("managers").await;
+    let managers: Vec<Box<dyn DownloadManager>> = vec![
+        Box::new(BasicDownloadManager::new()),
+        Box::new(TaskQueueManager::new()),
+    ];
+
+    for manager in managers {
+        let url = "https://example.com/file.zip".to_string();
+
+        let renamed = manager.add_download(url.clone(), target.clone()).await.unwrap();
+        assert_eq!(manager.get_task(renamed).await.unwrap().target_path, dir.join("file (1).zip"));
+
+        let skip = DownloadOptions::new().overwrite(OverwritePolicy::Skip);
+        let skipped = manager.add_download_with_options(url.clone(), target.clone(), skip).await.unwrap();
+        let task = manager.get_task(skipped).await.unwrap();
+        assert_eq!(task.status, DownloadStatus::Completed);
+        assert_eq!(task.target_path, target);
+
+        let fail = DownloadOptions::new().overwrite(OverwritePolicy::Fail);
+        let result = manager.add_download_with_options(url, target.clone(), fail).await;
+        assert!(matches!(result, Err(DownloadError::FileExists(_))));
+    }
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
\ No newline at end of file
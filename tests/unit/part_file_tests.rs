@@ -0,0 +1,76 @@
+//! Unit tests for downloading to temporary files
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use burncloud_download::backend::PartFileBackend;
+use burncloud_download::backend::part_file::part_path;
+use burncloud_download::traits::DownloadBackend;
+use burncloud_download::types::{DownloadTask, DownloadStatus};
+use super::support::FileBackend;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_part_file_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_part_path_appends_suffix() {
+    assert_eq!(part_path(Path::new("/data/model.bin"), ".part"), PathBuf::from("/data/model.bin.part"));
+    assert_eq!(part_path(Path::new("model.tar.gz"), ".tmp"), PathBuf::from("model.tar.gz.tmp"));
+}
+
+#[tokio::test]
+async fn test_download_is_moved_into_place_on_completion() {
+    let dir = temp_dir("complete");
+    let target = dir.join("model.bin");
+    let inner = Arc::new(FileBackend::writing(b"model weights"));
+    let backend = PartFileBackend::new(inner.clone(), ".part");
+
+    let task_id = backend.add("https://example.com/model.bin".to_string(), target.clone()).await.unwrap();
+
+    // The engine writes the temporary file, the task keeps its target path
+    let task = backend.task(task_id).await.unwrap();
+    assert_eq!(task.target_path, target);
+    assert!(dir.join("model.bin.part").exists());
+    assert!(!target.exists());
+
+    inner.resume(task_id).await.unwrap();
+    let task = backend.task(task_id).await.unwrap();
+    assert_eq!(task.status, DownloadStatus::Completed);
+    assert_eq!(std::fs::read(&target).unwrap(), b"model weights");
+    assert!(!dir.join("model.bin.part").exists());
+
+    // Later calls find the file already in place
+    assert_eq!(backend.list().await.unwrap()[0].status, DownloadStatus::Completed);
+    assert!(target.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_completion_without_file_is_reported() {
+    let dir = temp_dir("missing");
+    let target = dir.join("model.bin");
+    let inner = Arc::new(FileBackend::writing(b"model weights"));
+    let backend = PartFileBackend::new(inner.clone(), ".part");
+
+    let task_id = backend.add("https://example.com/model.bin".to_string(), target.clone()).await.unwrap();
+    std::fs::remove_file(dir.join("model.bin.part")).unwrap();
+    inner.resume(task_id).await.unwrap();
+
+    assert_eq!(backend.task(task_id).await.unwrap().status, DownloadStatus::Completed);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_reattach_uses_the_temporary_file() {
+    let inner = Arc::new(FileBackend::writing(b"model weights"));
+    let backend = PartFileBackend::new(inner.clone(), ".download");
+    let task = DownloadTask::new("https://example.com/model.bin".to_string(), PathBuf::from("/data/model.bin"));
+
+    assert!(!backend.reattach(&task, "2089b05ecca3d829").await.unwrap());
+    assert_eq!(inner.reattached().await, vec![PathBuf::from("/data/model.bin.download")]);
+}
\ No newline at end of file
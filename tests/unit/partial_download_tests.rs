@@ -0,0 +1,100 @@
+//! Unit tests for partial download detection
+
+use std::path::PathBuf;
+use burncloud_download::PartialDownload;
+use burncloud_download::services::partial_download::{control_file_path, parse_control_file, ControlState};
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_partial_{}_{}", name, std::process::id()))
+}
+
+/// Build a version 1 aria2 control file
+fn control_file(piece_length: u32, total_length: u64, bitfield: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&1u16.to_be_bytes());
+    bytes.extend_from_slice(&[0u8; 4]);
+    bytes.extend_from_slice(&0u32.to_be_bytes());
+    bytes.extend_from_slice(&piece_length.to_be_bytes());
+    bytes.extend_from_slice(&total_length.to_be_bytes());
+    bytes.extend_from_slice(&0u64.to_be_bytes());
+    bytes.extend_from_slice(&(bitfield.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(bitfield);
+    bytes.extend_from_slice(&0u32.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn test_control_file_path() {
+    assert_eq!(
+        control_file_path(&PathBuf::from("data/file.zip")),
+        PathBuf::from("data/file.zip.aria2")
+    );
+}
+
+#[test]
+fn test_parse_control_file_counts_completed_pieces() {
+    // 3 pieces of 1024 bytes, the last one 452 bytes; pieces 0 and 2 complete
+    let control = parse_control_file(&control_file(1024, 2500, &[0b1010_0000])).unwrap();
+
+    assert_eq!(control.piece_length, 1024);
+    assert_eq!(control.total_length, 2500);
+    assert_eq!(control.completed_length, 1024 + 452);
+}
+
+#[test]
+fn test_parse_control_file_rejects_invalid_data() {
+    assert!(parse_control_file(&[]).is_none());
+    assert!(parse_control_file(&control_file(1024, 2500, &[0xff])[..20]).is_none());
+    // Bitfield too short for the number of pieces
+    assert!(parse_control_file(&control_file(1024, 10 * 1024, &[0xff])).is_none());
+    // Unsupported version
+    let mut bytes = control_file(1024, 2500, &[0xe0]);
+    bytes[1] = 0;
+    assert!(parse_control_file(&bytes).is_none());
+}
+
+#[tokio::test]
+async fn test_inspect_partial_download() {
+    let dir = unique_temp_dir("inspect");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let target = dir.join("file.zip");
+
+    assert!(PartialDownload::inspect(&target).await.is_none());
+
+    // Without a control file the download continues from the end of the file
+    tokio::fs::write(&target, vec![0u8; 700]).await.unwrap();
+    let partial = PartialDownload::inspect(&target).await.unwrap();
+    assert_eq!(partial.control, ControlState::Missing);
+    assert_eq!(partial.verify(), Ok(700));
+
+    // With a control file only completed pieces count
+    tokio::fs::write(control_file_path(&target), control_file(512, 2048, &[0b1000_0000])).await.unwrap();
+    let partial = PartialDownload::inspect(&target).await.unwrap();
+    assert_eq!(partial.verify(), Ok(512));
+
+    partial.discard().await.unwrap();
+    assert!(!target.exists());
+    assert!(!control_file_path(&target).exists());
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_verify_rejects_inconsistent_partial() {
+    let dir = unique_temp_dir("verify");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let target = dir.join("file.zip");
+
+    // Partial file larger than the download recorded in the control file
+    tokio::fs::write(&target, vec![0u8; 4096]).await.unwrap();
+    tokio::fs::write(control_file_path(&target), control_file(512, 2048, &[0xf0])).await.unwrap();
+    assert!(PartialDownload::inspect(&target).await.unwrap().verify().is_err());
+
+    // Corrupt control file
+    tokio::fs::write(control_file_path(&target), b"garbage").await.unwrap();
+    let partial = PartialDownload::inspect(&target).await.unwrap();
+    assert_eq!(partial.control, ControlState::Unreadable);
+    assert!(partial.verify().is_err());
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,138 @@
+//! Unit tests for piecewise checksum verification
+
+use burncloud_download::{ChecksumAlgorithm, DownloadError, DownloadOptions, PieceChecksums, TaskId};
+use burncloud_download::hooks::{HookContext, PostDownloadHook, VerifyPieces};
+use burncloud_download::services::PieceVerifier;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const AAAA_SHA256: &str = "61be55a8e2f6b4e172338bddf184d6dbee29c98853e0a0485ecee7f27b9af0b4";
+const BBBB_SHA256: &str = "81cc5b17018674b401b42f35ba07bb79e211239c23bffe658da1577e3e646877";
+const CC_SHA256: &str = "355b1bbfc96725cdce8f4a2708fda310a80e6d13315aec4e5eed2a75fe8032ce";
+
+fn fresh_file(name: &str, content: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_pieces_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("model.bin");
+    std::fs::write(&file, content).unwrap();
+    file
+}
+
+fn expected() -> PieceChecksums {
+    PieceChecksums::new(ChecksumAlgorithm::Sha256, 4, [AAAA_SHA256, BBBB_SHA256, CC_SHA256])
+}
+
+#[test]
+fn test_piece_checksums_option() {
+    let checksums = PieceChecksums::new(ChecksumAlgorithm::Md5, 4, ["74B87337454200D4D33F80C4663DC5E5"]);
+    assert_eq!(checksums.digests, vec!["74b87337454200d4d33f80c4663dc5e5".to_string()]);
+
+    let options = DownloadOptions::new().piece_checksums(checksums.clone());
+    assert_eq!(options.piece_checksums, Some(checksums));
+    assert!(options.validate().is_ok());
+    assert_ne!(options, DownloadOptions::new());
+
+    let invalid = DownloadOptions::new().piece_checksums(PieceChecksums::compute(ChecksumAlgorithm::Sha1, 0));
+    assert!(matches!(invalid.validate(), Err(DownloadError::InvalidOption(_))));
+}
+
+#[tokio::test]
+async fn test_progressive_check_of_written_pieces() {
+    let file = fresh_file("progressive", b"aaaabbbbcc");
+    let verifier = PieceVerifier::new();
+    let task_id = TaskId::new();
+    verifier.track(task_id, file.clone(), expected(), true).await;
+    assert!(verifier.is_progressive(task_id).await);
+
+    // Only whole downloaded pieces are checked
+    verifier.observe(task_id, 6).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await, Some(vec![AAAA_SHA256.to_string()]));
+    verifier.observe(task_id, 8).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await.unwrap().len(), 2);
+
+    // The short last piece is checked once the download completed
+    verifier.finish(task_id, &file).await.unwrap();
+    assert_eq!(
+        verifier.digests(task_id).await,
+        Some(vec![AAAA_SHA256.to_string(), BBBB_SHA256.to_string(), CC_SHA256.to_string()])
+    );
+}
+
+#[tokio::test]
+async fn test_progressive_mismatch_fails_fast() {
+    let file = fresh_file("mismatch", b"aaaaxxxx");
+    let verifier = PieceVerifier::new();
+    let task_id = TaskId::new();
+    verifier.track(task_id, file, expected(), true).await;
+
+    let result = verifier.observe(task_id, 8).await;
+    assert!(matches!(
+        result,
+        Err(DownloadError::PieceChecksumMismatch { piece: 1, ref expected, .. }) if expected == BBBB_SHA256
+    ));
+    // The good piece before it stays checked
+    assert_eq!(verifier.digests(task_id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_missing_file_waits_for_data() {
+    let file = std::env::temp_dir().join(format!("burncloud_pieces_missing_{}.bin", std::process::id()));
+    let verifier = PieceVerifier::new();
+    let task_id = TaskId::new();
+    verifier.track(task_id, file, expected(), true).await;
+
+    verifier.observe(task_id, 4).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await, Some(Vec::new()));
+}
+
+#[tokio::test]
+async fn test_segmented_download_checked_on_completion() {
+    let file = fresh_file("segmented", b"aaaabbbbcc");
+    let verifier = Arc::new(PieceVerifier::new());
+    let task_id = TaskId::new();
+    verifier.track(task_id, file.clone(), expected(), false).await;
+    assert!(!verifier.is_progressive(task_id).await);
+
+    // Segments fill the file in any order, nothing is checked before it completes
+    verifier.observe(task_id, 10).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await, Some(Vec::new()));
+
+    let hook = VerifyPieces::new(verifier.clone());
+    assert_eq!(hook.name(), "verify-pieces");
+    let mut context = HookContext::new(task_id, "https://example.com/model.bin".to_string(), file);
+    hook.run(&mut context).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_piece_count_must_match() {
+    let file = fresh_file("short", b"aaaabbbb");
+    let verifier = PieceVerifier::new();
+    let task_id = TaskId::new();
+    verifier.track(task_id, file.clone(), expected(), false).await;
+    assert!(matches!(verifier.finish(task_id, &file).await, Err(DownloadError::VerificationError(_))));
+
+    let file = fresh_file("long", b"aaaabbbbccccdd");
+    let task_id = TaskId::new();
+    verifier.track(task_id, file.clone(), expected(), false).await;
+    assert!(verifier.finish(task_id, &file).await.is_err());
+}
+
+#[tokio::test]
+async fn test_compute_without_expected_digests() {
+    let file = fresh_file("compute", b"aaaabbbbcc");
+    let verifier = PieceVerifier::new();
+    let task_id = TaskId::new();
+    verifier.track(task_id, file.clone(), PieceChecksums::compute(ChecksumAlgorithm::Md5, 4), false).await;
+
+    verifier.finish(task_id, &file).await.unwrap();
+    assert_eq!(verifier.digests(task_id).await, Some(vec![
+        "74b87337454200d4d33f80c4663dc5e5".to_string(),
+        "65ba841e01d6db7733e90a5b7f9e6f80".to_string(),
+        "e0323a9039add2978bf5b49550572c7c".to_string(),
+    ]));
+
+    verifier.remove_task(task_id).await;
+    assert_eq!(verifier.digests(task_id).await, None);
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+//! Unit tests for probing URLs before they are downloaded
+//!
+//! Uses an in-memory backend and a one-shot local HTTP server, so no aria2
+//! daemon or network access is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::DownloadManager;
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_probe_url_{}_{}", name, std::process::id()))
+}
+
+async fn manager(dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::new().starting_downloads()))
+        .download_dir(dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Answer a single request with `headers` and no body, returning the server URL
+async fn serve_once(headers: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request).await;
+        let response = format!("HTTP/1.1 200 OK\r\n{}Connection: close\r\n\r\n", headers);
+        let _ = stream.write_all(response.as_bytes()).await;
+    });
+
+    format!("http://{}/download?id=42", address)
+}
+
+#[tokio::test]
+async fn test_probe_url_reports_remote_file() {
+    let dir = test_dir("remote");
+    let manager = manager(&dir).await;
+    let url = serve_once(concat!(
+        "Content-Length: 1234\r\n",
+        "Accept-Ranges: bytes\r\n",
+        "Content-Type: application/zip\r\n",
+        "Content-Disposition: attachment; filename=\"model.zip\"\r\n",
+    )).await;
+
+    let result = manager.probe_url(&url).await.unwrap();
+    assert_eq!(result.size, Some(1234));
+    assert!(result.resumable);
+    assert_eq!(result.filename, "model.zip");
+    assert_eq!(result.content_type.as_deref(), Some("application/zip"));
+    assert_eq!(result.final_url, url);
+
+    // Probing adds nothing to the queue
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_probe_url_checks_url_policy() {
+    let dir = test_dir("policy");
+    let manager = manager(&dir).await;
+
+    let result = manager.probe_url("file:///etc/passwd").await;
+    assert!(matches!(result, Err(DownloadError::InvalidUrl(_))));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_probe_url_fails_for_unreachable_server() {
+    let dir = test_dir("unreachable");
+    let manager = manager(&dir).await;
+
+    // Nothing listens on the discard port
+    assert!(manager.probe_url("http://127.0.0.1:9/file.zip").await.is_err());
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
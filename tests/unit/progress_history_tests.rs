@@ -0,0 +1,94 @@
+//! Unit tests for the per-task progress history
+
+use burncloud_download::{DownloadProgress, ProgressHistory, ProgressSample, TaskId};
+use std::time::{Duration, SystemTime};
+
+fn sample(at: SystemTime, downloaded_bytes: u64, speed_bps: u64) -> ProgressSample {
+    ProgressSample {
+        recorded_at: at,
+        downloaded_bytes,
+        speed_bps,
+    }
+}
+
+#[tokio::test]
+async fn test_ring_buffer_keeps_latest_samples() {
+    let history = ProgressHistory::new(3);
+    let task_id = TaskId::new();
+    let start = SystemTime::now();
+
+    for second in 0..5u64 {
+        history.record_sample(task_id, sample(start + Duration::from_secs(second), second * 100, 100)).await.unwrap();
+    }
+
+    let samples = history.samples(task_id, None).await;
+    let downloaded: Vec<u64> = samples.iter().map(|sample| sample.downloaded_bytes).collect();
+    assert_eq!(downloaded, vec![200, 300, 400]);
+}
+
+#[tokio::test]
+async fn test_samples_since() {
+    let history = ProgressHistory::new(10);
+    let task_id = TaskId::new();
+    let start = SystemTime::now();
+
+    for second in 0..4u64 {
+        history.record_sample(task_id, sample(start + Duration::from_secs(second), second, 0)).await.unwrap();
+    }
+
+    let recent = history.samples(task_id, Some(start + Duration::from_secs(2))).await;
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[0].downloaded_bytes, 2);
+    assert!(history.samples(TaskId::new(), None).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_record_progress() {
+    let history = ProgressHistory::new(10);
+    let task_id = TaskId::new();
+    let progress = DownloadProgress {
+        downloaded_bytes: 2048,
+        total_bytes: Some(4096),
+        speed_bps: 512,
+        eta_seconds: Some(4),
+    };
+
+    history.record(task_id, &progress).await.unwrap();
+    let samples = history.samples(task_id, None).await;
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].downloaded_bytes, 2048);
+    assert_eq!(samples[0].speed_bps, 512);
+
+    history.remove_task(task_id).await.unwrap();
+    assert!(history.samples(task_id, None).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_zero_capacity_disables_history() {
+    let history = ProgressHistory::in_memory(0).await.unwrap();
+    let task_id = TaskId::new();
+
+    history.record_sample(task_id, sample(SystemTime::now(), 1, 1)).await.unwrap();
+    assert!(history.samples(task_id, None).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_persisted_history_survives_reopen() {
+    let dir = std::env::temp_dir().join(format!("burncloud_progress_history_{}", std::process::id()));
+    let path = dir.join("metadata.db");
+    let task_id = TaskId::new();
+    let start = SystemTime::now();
+
+    {
+        let history = ProgressHistory::open(&path, 2).await.unwrap();
+        for second in 0..3u64 {
+            history.record_sample(task_id, sample(start + Duration::from_secs(second), second, 10)).await.unwrap();
+        }
+    }
+
+    let history = ProgressHistory::open(&path, 2).await.unwrap();
+    let samples = history.samples(task_id, None).await;
+    assert_eq!(samples.iter().map(|sample| sample.downloaded_bytes).collect::<Vec<_>>(), vec![1, 2]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+//! Unit tests for progress kinds of downloads with and without a known size
+
+use burncloud_download::{DownloadProgress, ProgressKind, SmoothedProgress};
+use burncloud_download::models::completion_percentage;
+
+fn progress(downloaded_bytes: u64, total_bytes: Option<u64>) -> DownloadProgress {
+    DownloadProgress {
+        downloaded_bytes,
+        total_bytes,
+        speed_bps: 100,
+        eta_seconds: None,
+    }
+}
+
+#[test]
+fn test_known_size_is_determinate() {
+    let progress = progress(250, Some(1000));
+    assert_eq!(ProgressKind::of(&progress), ProgressKind::Determinate);
+    assert_eq!(completion_percentage(&progress), Some(25.0));
+}
+
+#[test]
+fn test_unknown_size_is_indeterminate() {
+    for progress in [progress(4096, None), progress(4096, Some(0))] {
+        assert_eq!(ProgressKind::of(&progress), ProgressKind::Indeterminate);
+        assert!(ProgressKind::of(&progress).is_indeterminate());
+        assert_eq!(completion_percentage(&progress), None);
+    }
+}
+
+#[test]
+fn test_all_bytes_received_is_finalizing() {
+    let complete = progress(1000, Some(1000));
+    assert_eq!(ProgressKind::of(&complete), ProgressKind::Finalizing);
+    assert_eq!(completion_percentage(&complete), Some(100.0));
+
+    // More bytes than announced never exceed 100%
+    assert_eq!(completion_percentage(&progress(1500, Some(1000))), Some(100.0));
+}
+
+#[test]
+fn test_smoothed_progress_without_size() {
+    let smoothed = SmoothedProgress::new(progress(4096, None), 100);
+    assert_eq!(smoothed.kind, ProgressKind::Indeterminate);
+    assert_eq!(smoothed.completion_percentage(), None);
+    assert_eq!(smoothed.average_eta_seconds, None);
+    assert_eq!(smoothed.downloaded_bytes(), 4096);
+    assert_eq!(smoothed.average_speed_bps, 100);
+}
\ No newline at end of file
@@ -6,6 +6,8 @@ use async_trait::async_trait;
 use burncloud_download::types::{TaskId, DownloadStatus, DownloadProgress};
 use burncloud_download::traits::{DownloadEventHandler, DownloadManager};
 use burncloud_download::queue::manager::TaskQueueManager;
+use burncloud_download::models::Priority;
+use burncloud_download::DownloadError;
 
 // Test event handler for capturing events
 struct TestEventHandler {
@@ -263,4 +265,130 @@ async fn test_download_manager_trait_implementation() {
     // Test cancel_download
     manager.cancel_download(task_id).await.unwrap();
     assert!(manager.get_task(task_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_priority_orders_waiting_queue() {
+    let manager = TaskQueueManager::new();
+
+    // Fill all download slots
+    for i in 0..3 {
+        manager.add_task(
+            format!("https://example.com/active{}.zip", i),
+            PathBuf::from(format!("/downloads/active{}.zip", i))
+        ).await.unwrap();
+    }
+
+    let low = manager.add_download_with_priority(
+        "https://example.com/low.zip".to_string(),
+        PathBuf::from("/downloads/low.zip"),
+        Priority::Low
+    ).await.unwrap();
+    let urgent = manager.add_download_with_priority(
+        "https://example.com/urgent.zip".to_string(),
+        PathBuf::from("/downloads/urgent.zip"),
+        Priority::Urgent
+    ).await.unwrap();
+
+    assert_eq!(manager.get_priority(urgent).await.unwrap(), Priority::Urgent);
+
+    // Freeing one slot should start the urgent task even though it was queued last
+    let first_active = manager.list_tasks().await.unwrap().into_iter()
+        .find(|task| task.status == DownloadStatus::Downloading)
+        .unwrap();
+    manager.complete_task(first_active.id).await.unwrap();
+
+    assert_eq!(manager.get_task(urgent).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(low).await.unwrap().status, DownloadStatus::Waiting);
+}
+
+#[tokio::test]
+async fn test_set_priority_reorders_waiting_task() {
+    let manager = TaskQueueManager::new();
+
+    let mut active_ids = Vec::new();
+    for i in 0..3 {
+        active_ids.push(manager.add_task(
+            format!("https://example.com/active{}.zip", i),
+            PathBuf::from(format!("/downloads/active{}.zip", i))
+        ).await.unwrap());
+    }
+
+    let first = manager.add_task(
+        "https://example.com/first.zip".to_string(),
+        PathBuf::from("/downloads/first.zip")
+    ).await.unwrap();
+    let second = manager.add_task(
+        "https://example.com/second.zip".to_string(),
+        PathBuf::from("/downloads/second.zip")
+    ).await.unwrap();
+
+    manager.set_priority(second, Priority::High).await.unwrap();
+    manager.complete_task(active_ids[0]).await.unwrap();
+
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(first).await.unwrap().status, DownloadStatus::Waiting);
+
+    // Unknown tasks are rejected
+    assert!(manager.set_priority(TaskId::new(), Priority::Low).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pause_resume_cancel_all() {
+    let manager = TaskQueueManager::new();
+
+    let mut task_ids = Vec::new();
+    for i in 0..4 {
+        task_ids.push(manager.add_task(
+            format!("https://example.com/file{}.zip", i),
+            PathBuf::from(format!("/downloads/file{}.zip", i))
+        ).await.unwrap());
+    }
+
+    // Active and queued tasks are paused together, nothing is promoted
+    let paused = manager.pause_all().await.unwrap();
+    assert_eq!(paused.len(), 4);
+    assert_eq!(manager.active_download_count().await, 0);
+    for task_id in &task_ids {
+        assert_eq!(manager.get_task(*task_id).await.unwrap().status, DownloadStatus::Paused);
+    }
+
+    // Resuming respects the concurrency limit
+    let resumed = manager.resume_all().await.unwrap();
+    assert_eq!(resumed.len(), 4);
+    assert_eq!(manager.active_download_count().await, 3);
+    let waiting = manager.list_tasks().await.unwrap().into_iter()
+        .filter(|task| task.status == DownloadStatus::Waiting)
+        .count();
+    assert_eq!(waiting, 1);
+
+    let cancelled = manager.cancel_all().await.unwrap();
+    assert_eq!(cancelled.len(), 4);
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+    assert_eq!(manager.active_download_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_errors_are_typed() {
+    let manager = TaskQueueManager::new();
+
+    let missing = TaskId::new();
+    match manager.pause_download(missing).await {
+        Err(DownloadError::TaskNotFound(task_id)) => assert_eq!(task_id, missing),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+    manager.complete_task(task_id).await.unwrap();
+
+    match manager.pause_download(task_id).await {
+        Err(DownloadError::InvalidTaskState { operation, status, .. }) => {
+            assert_eq!(operation, "pause");
+            assert_eq!(status, DownloadStatus::Completed);
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+//! Unit tests for queue scheduling strategies
+
+use burncloud_download::{FifoScheduler, PriorityScheduler, RoundRobinScheduler, QueueScheduler, QueuedTask, TaskQueueManager};
+use burncloud_download::models::Priority;
+use burncloud_download::types::{DownloadStatus, DownloadTask, TaskId};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn task(name: &str) -> DownloadTask {
+    DownloadTask::new(format!("https://example.com/{}", name), PathBuf::from(format!("/downloads/{}", name)))
+}
+
+fn queued<'a>(task: &'a DownloadTask, priority: Priority, group: Option<&'a str>) -> QueuedTask<'a> {
+    QueuedTask { task, priority, group }
+}
+
+#[test]
+fn test_fifo_ignores_priority() {
+    let (a, b) = (task("a"), task("b"));
+    let candidates = [queued(&a, Priority::Low, None), queued(&b, Priority::High, None)];
+
+    assert_eq!(FifoScheduler.next(&candidates, &[]), Some(0));
+    assert_eq!(FifoScheduler.next(&[], &[]), None);
+}
+
+#[test]
+fn test_priority_picks_earliest_of_highest() {
+    let (a, b, c) = (task("a"), task("b"), task("c"));
+    let candidates = [
+        queued(&a, Priority::Low, None),
+        queued(&b, Priority::High, None),
+        queued(&c, Priority::High, None),
+    ];
+
+    assert_eq!(PriorityScheduler.next(&candidates, &[]), Some(1));
+}
+
+#[test]
+fn test_round_robin_alternates_groups() {
+    let tasks: Vec<DownloadTask> = (0..4).map(|i| task(&format!("file{}", i))).collect();
+    let scheduler = RoundRobinScheduler::new();
+
+    // The big group queued first, the small one after it
+    let groups = ["big", "big", "big", "small"];
+    let mut remaining: Vec<usize> = (0..tasks.len()).collect();
+    let mut started = Vec::new();
+    while !remaining.is_empty() {
+        let candidates: Vec<QueuedTask<'_>> = remaining.iter()
+            .map(|&i| queued(&tasks[i], Priority::Normal, Some(groups[i])))
+            .collect();
+        let index = scheduler.next(&candidates, &[]).unwrap();
+        started.push(remaining.remove(index));
+    }
+
+    assert_eq!(started, vec![0, 3, 1, 2]);
+}
+
+#[test]
+fn test_round_robin_prefers_groups_with_fewer_downloads() {
+    let (a, b, c) = (task("a"), task("b"), task("c"));
+    let candidates = [queued(&a, Priority::Normal, Some("big")), queued(&b, Priority::Normal, None)];
+    let active = [queued(&c, Priority::Normal, Some("big"))];
+
+    assert_eq!(RoundRobinScheduler::new().next(&candidates, &active), Some(1));
+}
+
+#[tokio::test]
+async fn test_queue_shares_slots_between_groups() {
+    let manager = TaskQueueManager::with_scheduler(Arc::new(RoundRobinScheduler::new()));
+
+    let mut big = Vec::new();
+    for i in 0..6 {
+        let task_id = manager.add_task(
+            format!("https://example.com/big{}.bin", i),
+            PathBuf::from(format!("/downloads/big{}.bin", i))
+        ).await.unwrap();
+        manager.set_task_group(task_id, "big").await.unwrap();
+        big.push(task_id);
+    }
+    let small = manager.add_task(
+        "https://example.com/small.bin".to_string(),
+        PathBuf::from("/downloads/small.bin")
+    ).await.unwrap();
+    manager.set_task_group(small, "small").await.unwrap();
+    assert_eq!(manager.task_group(small).await.as_deref(), Some("small"));
+
+    // The small group is served before the rest of the big one
+    manager.complete_task(big[0]).await.unwrap();
+    assert_eq!(manager.get_task(small).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(big[3]).await.unwrap().status, DownloadStatus::Waiting);
+
+    assert!(manager.set_task_group(TaskId::new(), "other").await.is_err());
+}
\ No newline at end of file
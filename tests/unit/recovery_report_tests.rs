@@ -0,0 +1,35 @@
+//! Unit tests for the recovery report
+
+use burncloud_download::{RecoveryReport, RestoredTask, FailedRecovery, TaskId};
+use std::path::PathBuf;
+
+#[test]
+fn test_empty_report_is_complete() {
+    let report = RecoveryReport::default();
+
+    assert!(report.is_complete());
+    assert_eq!(report.total(), 0);
+}
+
+#[test]
+fn test_report_counts_every_outcome() {
+    let original = TaskId::new();
+    let report = RecoveryReport {
+        restored: vec![RestoredTask {
+            task_id: TaskId::new(),
+            original_task_id: original,
+            resumed_from: 4096,
+        }],
+        failed: vec![FailedRecovery {
+            task_id: TaskId::new(),
+            url: "https://example.com/gone.bin".to_string(),
+            target_path: PathBuf::from("data/gone.bin"),
+            error: "Network error: connection refused".to_string(),
+        }],
+        skipped_completed: vec![TaskId::new(), TaskId::new()],
+    };
+
+    assert!(!report.is_complete());
+    assert_eq!(report.total(), 4);
+    assert_eq!(report.restored[0].original_task_id, original);
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+//! Unit tests for moving downloads to a new path
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::PersistentAria2Manager;
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::types::DownloadStatus;
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_relocate_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_relocated_download_is_found_at_new_path() {
+    let dir = test_dir("restart");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let old_path = dir.join("model.bin");
+    let new_path = dir.join("models").join("model.bin");
+
+    let task_id = manager.add_download(URL.to_string(), old_path.clone()).await.unwrap();
+    let relocated_id = manager.relocate_task(task_id, &new_path).await.unwrap();
+    assert_eq!(backend.task(relocated_id).await.unwrap().target_path, new_path);
+
+    assert_eq!(manager.find_duplicate_task(URL, &old_path).await.unwrap(), None);
+    assert_eq!(manager.find_duplicate_task(URL, &new_path).await.unwrap(), Some(relocated_id));
+    assert_eq!(manager.add_download(URL.to_string(), new_path.clone()).await.unwrap(), relocated_id);
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_relocated_completed_download_is_found_at_new_path() {
+    let dir = test_dir("completed");
+    std::fs::create_dir_all(&dir).unwrap();
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let old_path = dir.join("model.bin");
+    let new_path = dir.join("models").join("model.bin");
+
+    let task_id = manager.add_download(URL.to_string(), old_path.clone()).await.unwrap();
+    std::fs::write(&old_path, b"weights").unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+
+    assert_eq!(manager.relocate_task(task_id, &new_path).await.unwrap(), task_id);
+    assert_eq!(std::fs::read(&new_path).unwrap(), b"weights");
+
+    assert_eq!(manager.find_duplicate_task(URL, &old_path).await.unwrap(), None);
+    assert_eq!(manager.find_duplicate_task(URL, &new_path).await.unwrap(), Some(task_id));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+//! Unit tests for comparing remote file versions
+
+use burncloud_download::{RemoteMetadata, RemoteValidators};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, ETAG, LAST_MODIFIED};
+use reqwest::StatusCode;
+
+fn validators(etag: Option<&str>, last_modified: Option<&str>, size: Option<u64>) -> RemoteValidators {
+    RemoteValidators {
+        etag: etag.map(str::to_string),
+        last_modified: last_modified.map(str::to_string),
+        size,
+    }
+}
+
+#[test]
+fn test_validators_from_response() {
+    let mut headers = HeaderMap::new();
+    headers.insert(ETAG, HeaderValue::from_static("\"abc123\""));
+    headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+    headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1024"));
+
+    let metadata = RemoteMetadata::from_response("https://example.com/a.bin", "https://example.com/a.bin", StatusCode::OK, &headers);
+    let validators = RemoteValidators::from_metadata(&metadata);
+    assert_eq!(validators.etag.as_deref(), Some("\"abc123\""));
+    assert_eq!(validators.last_modified.as_deref(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+    assert_eq!(validators.size, Some(1024));
+    assert!(!validators.is_empty());
+
+    let empty = RemoteMetadata::from_response("https://example.com/a.bin", "https://example.com/a.bin", StatusCode::OK, &HeaderMap::new());
+    assert!(RemoteValidators::from_metadata(&empty).is_empty());
+}
+
+#[test]
+fn test_etag_decides_first() {
+    let stored = validators(Some("\"v1\""), Some("Mon"), Some(10));
+    assert!(!stored.changed(&validators(Some("\"v1\""), Some("Tue"), Some(20))));
+    assert!(stored.changed(&validators(Some("\"v2\""), Some("Mon"), Some(10))));
+}
+
+#[test]
+fn test_falls_back_to_last_modified_then_size() {
+    let stored = validators(None, Some("Mon"), Some(10));
+    assert!(stored.changed(&validators(Some("\"v1\""), Some("Tue"), Some(10))));
+    assert!(!stored.changed(&validators(None, Some("Mon"), Some(20))));
+
+    let sized = validators(None, None, Some(10));
+    assert!(sized.changed(&validators(None, None, Some(11))));
+    assert!(!sized.changed(&validators(None, None, Some(10))));
+}
+
+#[test]
+fn test_nothing_in_common_is_unchanged() {
+    let stored = validators(Some("\"v1\""), None, None);
+    assert!(!stored.changed(&validators(None, Some("Mon"), Some(10))));
+    assert!(!stored.changed(&RemoteValidators::default()));
+}
\ No newline at end of file
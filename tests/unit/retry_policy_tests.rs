@@ -0,0 +1,89 @@
+//! Unit tests for RetryPolicy and automatic retry in TaskQueueManager
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use burncloud_download::{RetryPolicy, Backoff, RetryOn, TaskQueueManager, DownloadEventHandler};
+use burncloud_download::types::{TaskId, DownloadStatus, DownloadProgress};
+
+#[test]
+fn test_exponential_backoff_is_capped() {
+    let backoff = Backoff::Exponential {
+        initial_delay: Duration::from_secs(1),
+        multiplier: 2.0,
+        max_delay: Duration::from_secs(5),
+    };
+
+    assert_eq!(backoff.delay_for(1), Duration::from_secs(1));
+    assert_eq!(backoff.delay_for(2), Duration::from_secs(2));
+    assert_eq!(backoff.delay_for(3), Duration::from_secs(4));
+    assert_eq!(backoff.delay_for(4), Duration::from_secs(5));
+    assert_eq!(backoff.delay_for(100), Duration::from_secs(5));
+}
+
+#[test]
+fn test_retry_on_filters_errors() {
+    assert!(RetryOn::AnyError.matches("disk full"));
+    assert!(RetryOn::Transient.matches("Connection reset by peer"));
+    assert!(RetryOn::Transient.matches("HTTP 503 Service Unavailable"));
+    assert!(!RetryOn::Transient.matches("HTTP 404 Not Found"));
+    assert!(RetryOn::Matching(vec!["checksum".to_string()]).matches("Checksum mismatch"));
+}
+
+#[test]
+fn test_should_retry_respects_max_attempts() {
+    let policy = RetryPolicy { max_attempts: 2, ..RetryPolicy::default() };
+
+    assert!(policy.should_retry(0, "error"));
+    assert!(policy.should_retry(1, "error"));
+    assert!(!policy.should_retry(2, "error"));
+    assert!(!RetryPolicy::none().should_retry(0, "error"));
+}
+
+struct RetryCapture {
+    retries: Arc<Mutex<Vec<(TaskId, u32)>>>,
+}
+
+#[async_trait]
+impl DownloadEventHandler for RetryCapture {
+    async fn on_status_changed(&self, _task_id: TaskId, _old_status: DownloadStatus, _new_status: DownloadStatus) {}
+    async fn on_progress_updated(&self, _task_id: TaskId, _progress: DownloadProgress) {}
+    async fn on_download_completed(&self, _task_id: TaskId) {}
+    async fn on_download_failed(&self, _task_id: TaskId, _error: String) {}
+
+    async fn on_retry_scheduled(&self, task_id: TaskId, attempt: u32, _delay: Duration) {
+        self.retries.lock().await.push((task_id, attempt));
+    }
+}
+
+#[tokio::test]
+async fn test_queue_reschedules_failed_task() {
+    let manager = TaskQueueManager::new();
+    let retries = Arc::new(Mutex::new(Vec::new()));
+    manager.add_event_handler(Arc::new(RetryCapture { retries: retries.clone() })).await;
+    manager.set_retry_policy(RetryPolicy {
+        max_attempts: 1,
+        backoff: Backoff::Fixed(Duration::from_millis(10)),
+        retry_on: RetryOn::AnyError,
+    }).await;
+
+    let task_id = manager.add_task(
+        "https://example.com/file.zip".to_string(),
+        PathBuf::from("/downloads/file.zip")
+    ).await.unwrap();
+
+    manager.fail_task(task_id, "Connection error".to_string()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.retry_attempts(task_id).await, 1);
+    assert_eq!(*retries.lock().await, vec![(task_id, 1)]);
+
+    // Retry budget is exhausted, so the second failure sticks
+    manager.fail_task(task_id, "Connection error".to_string()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(matches!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Failed(_)));
+}
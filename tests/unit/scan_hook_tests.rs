@@ -0,0 +1,128 @@
+//! Unit tests for scanning completed downloads
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+
+use burncloud_download::{DownloadError, Result};
+use burncloud_download::backend::{PartFileBackend, ScanningBackend};
+use burncloud_download::hooks::{ScanHook, ScanVerdict, SCAN_REJECTED};
+use burncloud_download::traits::DownloadBackend;
+use burncloud_download::types::{TaskId, DownloadTask, DownloadStatus};
+use super::support::FileBackend;
+
+/// Scanner rejecting files containing a signature
+struct SignatureScanner;
+
+#[async_trait]
+impl ScanHook for SignatureScanner {
+    fn name(&self) -> &str {
+        "signature"
+    }
+
+    async fn scan(&self, _task_id: TaskId, path: &Path) -> Result<ScanVerdict> {
+        let contents = tokio::fs::read(path).await?;
+        if contents.windows(5).any(|window| window == b"EICAR") {
+            Ok(ScanVerdict::Rejected("EICAR test signature".to_string()))
+        } else {
+            Ok(ScanVerdict::Clean)
+        }
+    }
+}
+
+/// Scanner that cannot reach its engine
+struct BrokenScanner;
+
+#[async_trait]
+impl ScanHook for BrokenScanner {
+    fn name(&self) -> &str {
+        "broken"
+    }
+
+    async fn scan(&self, _task_id: TaskId, _path: &Path) -> Result<ScanVerdict> {
+        Err(DownloadError::General("scanner unavailable".to_string()))
+    }
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_scanning_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Poll a task until its scan finished
+async fn scanned_task(backend: &dyn DownloadBackend, task_id: TaskId) -> DownloadTask {
+    for _ in 0..200 {
+        let task = backend.task(task_id).await.unwrap();
+        if task.status != DownloadStatus::Downloading {
+            return task;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("scan of task {} did not finish", task_id);
+}
+
+#[tokio::test]
+async fn test_clean_file_completes_after_scan() {
+    let dir = temp_dir("clean");
+    let target = dir.join("model.bin");
+    let inner = Arc::new(FileBackend::writing(b"model weights"));
+    let scanning = Arc::new(ScanningBackend::new(inner.clone(), vec![Arc::new(SignatureScanner)], dir.join("quarantine")));
+    let backend = PartFileBackend::new(scanning, ".part");
+
+    let task_id = backend.add("https://example.com/model.bin".to_string(), target.clone()).await.unwrap();
+    inner.resume(task_id).await.unwrap();
+
+    // Reported as downloading until the scan finished, without moving the file
+    let first = backend.task(task_id).await.unwrap();
+    assert_eq!(first.status, DownloadStatus::Downloading);
+
+    let task = scanned_task(&backend, task_id).await;
+    assert_eq!(task.status, DownloadStatus::Completed);
+    assert_eq!(std::fs::read(&target).unwrap(), b"model weights");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_rejected_file_is_quarantined() {
+    let dir = temp_dir("rejected");
+    let target = dir.join("setup.exe");
+    let quarantine = dir.join("quarantine");
+    let inner = Arc::new(FileBackend::writing(b"X5O!P%@AP EICAR"));
+    let backend = ScanningBackend::new(inner.clone(), vec![Arc::new(SignatureScanner)], &quarantine);
+
+    let task_id = backend.add("https://example.com/setup.exe".to_string(), target.clone()).await.unwrap();
+    inner.resume(task_id).await.unwrap();
+
+    let task = scanned_task(&backend, task_id).await;
+    match task.status {
+        DownloadStatus::Failed(reason) => {
+            assert!(reason.starts_with(SCAN_REJECTED));
+            assert!(reason.contains("EICAR test signature"));
+        }
+        status => panic!("expected a failed task, got {:?}", status),
+    }
+    assert!(!target.exists());
+    assert!(quarantine.join(format!("{}-setup.exe", task_id)).exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_failing_scanner_rejects_the_file() {
+    let dir = temp_dir("broken");
+    let target = dir.join("model.bin");
+    let inner = Arc::new(FileBackend::writing(b"model weights"));
+    let backend = ScanningBackend::new(inner.clone(), vec![Arc::new(SignatureScanner), Arc::new(BrokenScanner)], dir.join("quarantine"));
+
+    let task_id = backend.add("https://example.com/model.bin".to_string(), target.clone()).await.unwrap();
+    inner.resume(task_id).await.unwrap();
+
+    let task = scanned_task(&backend, task_id).await;
+    assert!(matches!(task.status, DownloadStatus::Failed(reason) if reason.contains("scanner unavailable")));
+    assert!(!target.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
\ No newline at end of file
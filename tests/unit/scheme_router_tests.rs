@@ -0,0 +1,87 @@
+//! Unit tests for routing downloads to backends by URL scheme
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use burncloud_download::{DownloadError, SchemeRouter};
+use burncloud_download::traits::DownloadBackend;
+use burncloud_download::types::{TaskId, DownloadTask, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn backend() -> Arc<MemoryBackend> {
+    Arc::new(MemoryBackend::new().starting_downloads().reattaching())
+}
+
+fn router() -> (SchemeRouter, Arc<MemoryBackend>, Arc<MemoryBackend>) {
+    let default = backend();
+    let sftp = backend();
+    let router = SchemeRouter::new(default.clone()).route("sftp", sftp.clone());
+    (router, default, sftp)
+}
+
+#[tokio::test]
+async fn test_downloads_are_routed_by_scheme() {
+    let (router, default, sftp) = router();
+
+    let ftp_task = router.add("ftp://ftp.example.com/file.iso".to_string(), PathBuf::from("file.iso")).await.unwrap();
+    let sftp_task = router.add("SFTP://host.example.com/data.bin".to_string(), PathBuf::from("data.bin")).await.unwrap();
+
+    assert!(default.has(ftp_task).await);
+    assert!(!default.has(sftp_task).await);
+    assert!(sftp.has(sftp_task).await);
+}
+
+#[tokio::test]
+async fn test_task_operations_reach_the_owning_backend() {
+    let (router, _default, sftp) = router();
+    let task_id = router.add("sftp://host.example.com/data.bin".to_string(), PathBuf::from("data.bin")).await.unwrap();
+
+    router.pause(task_id).await.unwrap();
+    assert_eq!(sftp.task(task_id).await.unwrap().status, DownloadStatus::Paused);
+    assert_eq!(router.task(task_id).await.unwrap().status, DownloadStatus::Paused);
+
+    router.cancel(task_id).await.unwrap();
+    assert!(!sftp.has(task_id).await);
+    assert!(matches!(router.task(task_id).await, Err(DownloadError::TaskNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_listing_and_limits_cover_every_backend() {
+    let (router, default, sftp) = router();
+    router.add("https://example.com/a.bin".to_string(), PathBuf::from("a.bin")).await.unwrap();
+    router.add("sftp://host.example.com/b.bin".to_string(), PathBuf::from("b.bin")).await.unwrap();
+
+    assert_eq!(router.list().await.unwrap().len(), 2);
+    assert_eq!(router.active_count().await.unwrap(), 2);
+
+    router.set_global_speed_limit(1024).await.unwrap();
+    assert_eq!(default.global_limit().await, 1024);
+    assert_eq!(sftp.global_limit().await, 1024);
+}
+
+#[tokio::test]
+async fn test_reattach_uses_the_backend_of_the_task_url() {
+    let (router, default, sftp) = router();
+    let task = DownloadTask::new("sftp://host.example.com/data.bin".to_string(), PathBuf::from("data.bin"));
+
+    assert!(router.reattach(&task, "sftp-engine").await.unwrap());
+    assert!(sftp.has(task.id).await);
+    assert!(!default.has(task.id).await);
+    assert!(router.progress(task.id).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_snapshot_combines_every_backend() {
+    let (router, _, _) = router();
+    let ftp_task = router.add("ftp://ftp.example.com/a.iso".to_string(), PathBuf::from("a.iso")).await.unwrap();
+    let sftp_task = router.add("sftp://host.example.com/b.bin".to_string(), PathBuf::from("b.bin")).await.unwrap();
+
+    let snapshot = router.list_with_progress().await.unwrap();
+    let mut ids: Vec<TaskId> = snapshot.iter().map(|(task, _)| task.id).collect();
+    ids.sort_by_key(|id| id.to_string());
+    let mut expected = vec![ftp_task, sftp_task];
+    expected.sort_by_key(|id| id.to_string());
+
+    assert_eq!(ids, expected);
+    assert!(snapshot.iter().all(|(_, progress)| progress.downloaded_bytes == 0));
+}
\ No newline at end of file
@@ -0,0 +1,166 @@
+//! Unit tests for the serde formats of public types
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use burncloud_download::{DownloadEvent, RecoveryReport, RestoredTask, MirrorStats, SpeedSample, TaskSpeed};
+use burncloud_download::models::CANCELLED_REASON;
+use burncloud_download::types::{DownloadTask, DownloadProgress, DownloadStatus, TaskId};
+use burncloud_download::types::serialize::{download_progress, download_status, download_task, download_tasks};
+
+#[derive(Serialize, Deserialize)]
+struct Status(#[serde(with = "download_status")] DownloadStatus);
+
+#[derive(Serialize, Deserialize)]
+struct Progress(#[serde(with = "download_progress")] DownloadProgress);
+
+#[derive(Serialize, Deserialize)]
+struct Task(#[serde(with = "download_task")] DownloadTask);
+
+#[derive(Serialize, Deserialize)]
+struct Tasks(#[serde(with = "download_tasks")] Vec<DownloadTask>);
+
+fn at_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+#[test]
+fn test_status_format() {
+    assert_eq!(serde_json::to_value(Status(DownloadStatus::Downloading)).unwrap(), json!({ "status": "downloading" }));
+    assert_eq!(serde_json::to_value(Status(DownloadStatus::Failed("404".to_string()))).unwrap(),
+        json!({ "status": "failed", "error": "404" }));
+    assert_eq!(serde_json::to_value(Status(DownloadStatus::Failed(CANCELLED_REASON.to_string()))).unwrap(),
+        json!({ "status": "cancelled" }));
+}
+
+#[test]
+fn test_status_round_trip() {
+    for status in [
+        DownloadStatus::Waiting,
+        DownloadStatus::Downloading,
+        DownloadStatus::Paused,
+        DownloadStatus::Completed,
+        DownloadStatus::Failed("Connection reset".to_string()),
+        DownloadStatus::Failed(CANCELLED_REASON.to_string()),
+    ] {
+        let json = serde_json::to_string(&Status(status.clone())).unwrap();
+        assert_eq!(serde_json::from_str::<Status>(&json).unwrap().0, status);
+    }
+}
+
+#[test]
+fn test_unknown_status_is_rejected() {
+    let error = serde_json::from_value::<Status>(json!({ "status": "sleeping" })).err().unwrap();
+    assert!(error.to_string().contains("sleeping"));
+}
+
+#[test]
+fn test_progress_round_trip() {
+    let mut progress = DownloadProgress::new();
+    progress.downloaded_bytes = 512;
+    progress.total_bytes = Some(2048);
+    progress.speed_bps = 128;
+    progress.eta_seconds = Some(12);
+
+    let value = serde_json::to_value(Progress(progress)).unwrap();
+    assert_eq!(value, json!({ "downloaded_bytes": 512, "total_bytes": 2048, "speed_bps": 128, "eta_seconds": 12 }));
+
+    let progress = serde_json::from_value::<Progress>(value).unwrap().0;
+    assert_eq!((progress.downloaded_bytes, progress.total_bytes), (512, Some(2048)));
+    assert_eq!((progress.speed_bps, progress.eta_seconds), (128, Some(12)));
+}
+
+#[test]
+fn test_task_round_trip() {
+    let mut task = DownloadTask::new("https://example.com/model.bin".to_string(), PathBuf::from("/data/model.bin"));
+    task.status = DownloadStatus::Failed("404".to_string());
+    task.created_at = at_millis(1_700_000_000_123);
+    task.updated_at = at_millis(1_700_000_060_456);
+
+    let value = serde_json::to_value(Task(task.clone())).unwrap();
+    assert_eq!(value["url"], "https://example.com/model.bin");
+    assert_eq!(value["target_path"], "/data/model.bin");
+    assert_eq!(value["status"], "failed");
+    assert_eq!(value["error"], "404");
+    assert_eq!(value["created_at"], 1_700_000_000_123u64);
+    assert_eq!(value["updated_at"], 1_700_000_060_456u64);
+
+    let restored = serde_json::from_value::<Task>(value).unwrap().0;
+    assert_eq!(restored.id, task.id);
+    assert_eq!(restored.url, task.url);
+    assert_eq!(restored.target_path, task.target_path);
+    assert_eq!(restored.status, task.status);
+    assert_eq!(restored.created_at, task.created_at);
+    assert_eq!(restored.updated_at, task.updated_at);
+}
+
+#[test]
+fn test_task_list_round_trip() {
+    let tasks = vec![
+        DownloadTask::new("https://example.com/a.bin".to_string(), PathBuf::from("/data/a.bin")),
+        DownloadTask::new("https://example.com/b.bin".to_string(), PathBuf::from("/data/b.bin")),
+    ];
+
+    let json = serde_json::to_string(&Tasks(tasks.clone())).unwrap();
+    let restored = serde_json::from_str::<Tasks>(&json).unwrap().0;
+    assert_eq!(restored.iter().map(|task| task.id).collect::<Vec<_>>(), tasks.iter().map(|task| task.id).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_events_are_tagged() {
+    let task_id = TaskId::new();
+    let event = DownloadEvent::StatusChanged {
+        task_id,
+        old_status: DownloadStatus::Downloading,
+        new_status: DownloadStatus::Completed,
+    };
+
+    let value = serde_json::to_value(&event).unwrap();
+    assert_eq!(value["event"], "status_changed");
+    assert_eq!(value["old_status"], json!({ "status": "downloading" }));
+    assert_eq!(value["new_status"], json!({ "status": "completed" }));
+
+    let retry = serde_json::to_value(DownloadEvent::RetryScheduled { task_id, attempt: 2, delay: Duration::from_millis(1500) }).unwrap();
+    assert_eq!(retry["event"], "retry_scheduled");
+    assert_eq!(retry["delay_ms"], 1500);
+}
+
+#[test]
+fn test_events_round_trip() {
+    let task_id = TaskId::new();
+    let events = vec![
+        DownloadEvent::Completed { task_id },
+        DownloadEvent::Failed { task_id, error: "404".to_string() },
+        DownloadEvent::Expired { task_id, expires_at: at_millis(1_700_000_000_000) },
+        DownloadEvent::SpeedSample {
+            sample: SpeedSample::new(at_millis(1_700_000_000_500), vec![TaskSpeed { task_id, speed_bps: 10, average_speed_bps: 8 }]),
+        },
+    ];
+
+    for event in events {
+        let json = serde_json::to_string(&event).unwrap();
+        let restored: DownloadEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        assert_eq!(restored.task_id(), event.task_id());
+    }
+}
+
+#[test]
+fn test_reports_round_trip() {
+    let task_id = TaskId::new();
+    let mut report = RecoveryReport::default();
+    report.restored.push(RestoredTask { task_id, original_task_id: TaskId::new(), resumed_from: 4096 });
+    report.skipped_completed.push(task_id);
+
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(serde_json::from_str::<RecoveryReport>(&json).unwrap(), report);
+
+    let mut stats = MirrorStats::new("mirror.example.com");
+    stats.average_latency = Some(Duration::from_millis(250));
+    let value = serde_json::to_value(&stats).unwrap();
+    assert_eq!(value["average_latency_ms"], 250);
+    assert!(value["last_failure_ms"].is_null());
+    assert_eq!(serde_json::from_value::<MirrorStats>(value).unwrap(), stats);
+}
\ No newline at end of file
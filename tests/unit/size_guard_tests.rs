@@ -0,0 +1,59 @@
+//! Unit tests for maximum file size enforcement
+
+use burncloud_download::{DownloadError, DownloadOptions, TaskId};
+use burncloud_download::services::SizeGuard;
+
+#[test]
+fn test_check_against_limit() {
+    assert!(SizeGuard::check(100, Some(100)).is_ok());
+    assert!(SizeGuard::check(u64::MAX, None).is_ok());
+    assert!(matches!(
+        SizeGuard::check(101, Some(100)),
+        Err(DownloadError::FileTooLarge { size: 101, limit: 100 })
+    ));
+}
+
+#[test]
+fn test_task_limit_overrides_default() {
+    let guard = SizeGuard::new(Some(1000));
+    assert_eq!(guard.limit_for(None), Some(1000));
+    assert_eq!(guard.limit_for(Some(5000)), Some(5000));
+    assert_eq!(SizeGuard::default().limit_for(None), None);
+
+    let options = DownloadOptions::new().max_file_size(5000);
+    assert_eq!(options.max_file_size, Some(5000));
+}
+
+#[tokio::test]
+async fn test_observe_until_size_known() {
+    let guard = SizeGuard::new(Some(1000));
+    let task_id = TaskId::new();
+    guard.track(task_id, None).await;
+    assert!(guard.is_tracked(task_id).await);
+
+    // Unknown sizes keep the task watched
+    assert!(guard.observe(task_id, None).await.is_ok());
+    assert!(guard.is_tracked(task_id).await);
+
+    assert!(guard.observe(task_id, Some(500)).await.is_ok());
+    assert!(!guard.is_tracked(task_id).await);
+}
+
+#[tokio::test]
+async fn test_observe_oversized_download() {
+    let guard = SizeGuard::new(None);
+    let task_id = TaskId::new();
+    guard.track(task_id, Some(1000)).await;
+
+    let result = guard.observe(task_id, Some(2000)).await;
+    assert!(matches!(result, Err(DownloadError::FileTooLarge { size: 2000, limit: 1000 })));
+    assert!(!guard.is_tracked(task_id).await);
+}
+
+#[tokio::test]
+async fn test_unlimited_tasks_are_not_watched() {
+    let guard = SizeGuard::default();
+    let task_id = TaskId::new();
+    guard.track(task_id, None).await;
+    assert!(!guard.is_tracked(task_id).await);
+}
\ No newline at end of file
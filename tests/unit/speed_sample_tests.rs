@@ -0,0 +1,145 @@
+//! Unit tests for per-task and aggregate speed samples
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use burncloud_download::PersistentAria2Manager;
+use burncloud_download::traits::{DownloadEventHandler, DownloadManager};
+use burncloud_download::models::{DownloadEvent, SpeedSample, TaskSpeed};
+use burncloud_download::types::{TaskId, DownloadProgress, DownloadStatus};
+use super::support::MemoryBackend;
+
+/// Handler keeping every speed sample it receives
+#[derive(Default)]
+struct SampleRecorder {
+    samples: Mutex<Vec<SpeedSample>>,
+}
+
+#[async_trait]
+impl DownloadEventHandler for SampleRecorder {
+    async fn on_status_changed(&self, _task_id: TaskId, _old_status: DownloadStatus, _new_status: DownloadStatus) {}
+
+    async fn on_progress_updated(&self, _task_id: TaskId, _progress: DownloadProgress) {}
+
+    async fn on_download_completed(&self, _task_id: TaskId) {}
+
+    async fn on_download_failed(&self, _task_id: TaskId, _error: String) {}
+
+    async fn on_speed_sample(&self, sample: SpeedSample) {
+        self.samples.lock().await.push(sample);
+    }
+}
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_speed_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf, sample_interval: Duration) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .speed_sample_interval(sample_interval)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+fn speed(task_id: TaskId, speed_bps: u64, average_speed_bps: u64) -> TaskSpeed {
+    TaskSpeed { task_id, speed_bps, average_speed_bps }
+}
+
+#[test]
+fn test_sample_sums_task_speeds() {
+    let (first, second) = (TaskId::new(), TaskId::new());
+    let sample = SpeedSample::new(SystemTime::now(), vec![speed(first, 1000, 800), speed(second, 500, 700)]);
+
+    assert_eq!(sample.total_speed_bps, 1500);
+    assert_eq!(sample.total_average_speed_bps, 1500);
+    assert_eq!(sample.task(second), Some(&speed(second, 500, 700)));
+    assert_eq!(sample.task(TaskId::new()), None);
+    assert!(!sample.is_idle());
+}
+
+#[test]
+fn test_empty_sample_is_idle() {
+    let sample = SpeedSample::new(SystemTime::now(), Vec::new());
+
+    assert_eq!(sample.total_speed_bps, 0);
+    assert_eq!(sample.total_average_speed_bps, 0);
+    assert!(sample.is_idle());
+}
+
+#[test]
+fn test_sample_totals_saturate() {
+    let sample = SpeedSample::new(SystemTime::now(), vec![speed(TaskId::new(), u64::MAX, 1), speed(TaskId::new(), 1, 1)]);
+
+    assert_eq!(sample.total_speed_bps, u64::MAX);
+    assert_eq!(sample.total_average_speed_bps, 2);
+}
+
+#[tokio::test]
+async fn test_handlers_receive_speed_samples() {
+    let dir = test_dir("handler");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, Duration::from_millis(40)).await;
+    let recorder = Arc::new(SampleRecorder::default());
+    manager.add_event_handler(recorder.clone()).await;
+
+    let first = manager.add_download("http://127.0.0.1:9/a.bin".to_string(), dir.join("a.bin")).await.unwrap();
+    let second = manager.add_download("http://127.0.0.1:9/b.bin".to_string(), dir.join("b.bin")).await.unwrap();
+    backend.set_speed(first, 3000).await;
+    backend.set_speed(second, 1000).await;
+
+    let mut sample = None;
+    for _ in 0..100 {
+        sample = recorder.samples.lock().await.iter()
+            .find(|sample| sample.tasks.len() == 2 && sample.total_speed_bps == 4000)
+            .cloned();
+        if sample.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let sample = sample.expect("No speed sample covering both tasks");
+    assert_eq!(sample.task(first).unwrap().speed_bps, 3000);
+    assert_eq!(sample.task(second).unwrap().speed_bps, 1000);
+    assert!(sample.total_average_speed_bps > 0);
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_event_subscribers_receive_speed_samples() {
+    let dir = test_dir("events");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, Duration::from_millis(20)).await;
+    let mut events = manager.subscribe_events();
+
+    let task_id = manager.add_download("http://127.0.0.1:9/a.bin".to_string(), dir.join("a.bin")).await.unwrap();
+    backend.set_speed(task_id, 2048).await;
+
+    let sample = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            match events.recv().await.unwrap() {
+                DownloadEvent::SpeedSample { sample } if sample.total_speed_bps == 2048 => break sample,
+                _ => continue,
+            }
+        }
+    }).await.expect("No speed sample event");
+    assert_eq!(sample.tasks.len(), 1);
+    assert_eq!(sample.tasks[0].task_id, task_id);
+    assert_eq!(DownloadEvent::SpeedSample { sample }.task_id(), None);
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
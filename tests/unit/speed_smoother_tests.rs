@@ -0,0 +1,94 @@
+//! Unit tests for speed and ETA smoothing
+
+use burncloud_download::{DownloadProgress, SpeedSmoother, SmoothedProgress, TaskId};
+use std::time::{Duration, Instant};
+
+fn progress(downloaded_bytes: u64, speed_bps: u64) -> DownloadProgress {
+    DownloadProgress {
+        downloaded_bytes,
+        total_bytes: Some(10_000),
+        speed_bps,
+        eta_seconds: (speed_bps > 0).then(|| (10_000 - downloaded_bytes) / speed_bps),
+    }
+}
+
+#[tokio::test]
+async fn test_first_sample_starts_the_average() {
+    let smoother = SpeedSmoother::new(Duration::from_secs(5));
+    let task_id = TaskId::new();
+
+    let smoothed = smoother.smooth(task_id, progress(0, 1000)).await;
+    assert_eq!(smoothed.average_speed_bps, 1000);
+    assert_eq!(smoothed.average_eta_seconds, Some(10));
+    assert_eq!(smoother.average_speed(task_id).await, Some(1000));
+}
+
+#[tokio::test]
+async fn test_spikes_are_damped() {
+    let smoother = SpeedSmoother::new(Duration::from_secs(5));
+    let task_id = TaskId::new();
+    let start = Instant::now();
+
+    smoother.smooth_at(task_id, progress(0, 1000), start).await;
+    let smoothed = smoother.smooth_at(task_id, progress(1000, 5000), start + Duration::from_secs(1)).await;
+
+    // The instant speed is kept, the average only moves part of the way
+    assert_eq!(smoothed.instant_speed_bps(), 5000);
+    assert_eq!(smoothed.instant_eta_seconds(), Some(1));
+    assert!(smoothed.average_speed_bps > 1000 && smoothed.average_speed_bps < 2000);
+    assert_eq!(smoothed.average_eta_seconds, Some(9000 / smoothed.average_speed_bps));
+}
+
+#[tokio::test]
+async fn test_average_follows_sustained_change() {
+    let smoother = SpeedSmoother::new(Duration::from_secs(2));
+    let task_id = TaskId::new();
+    let start = Instant::now();
+
+    smoother.smooth_at(task_id, progress(0, 0), start).await;
+    let mut smoothed = None;
+    for second in 1..=20 {
+        smoothed = Some(smoother.smooth_at(task_id, progress(0, 2000), start + Duration::from_secs(second)).await);
+    }
+    assert!(smoothed.unwrap().average_speed_bps >= 1990);
+}
+
+#[tokio::test]
+async fn test_zero_window_reports_instant_speed() {
+    let smoother = SpeedSmoother::new(Duration::ZERO);
+    let task_id = TaskId::new();
+
+    smoother.smooth(task_id, progress(0, 1000)).await;
+    let smoothed = smoother.smooth(task_id, progress(0, 3000)).await;
+    assert_eq!(smoothed.average_speed_bps, 3000);
+}
+
+#[tokio::test]
+async fn test_tasks_are_averaged_separately() {
+    let smoother = SpeedSmoother::default();
+    let first = TaskId::new();
+    let second = TaskId::new();
+
+    smoother.smooth(first, progress(0, 1000)).await;
+    smoother.smooth(second, progress(0, 4000)).await;
+    assert_eq!(smoother.average_speed(first).await, Some(1000));
+    assert_eq!(smoother.average_speed(second).await, Some(4000));
+
+    smoother.remove_task(first).await;
+    assert_eq!(smoother.average_speed(first).await, None);
+}
+
+#[test]
+fn test_unknown_size_has_no_eta() {
+    let progress = DownloadProgress {
+        downloaded_bytes: 500,
+        total_bytes: None,
+        speed_bps: 100,
+        eta_seconds: None,
+    };
+    let smoothed = SmoothedProgress::new(progress, 100);
+    assert_eq!(smoothed.average_eta_seconds, None);
+
+    let stalled = SmoothedProgress::new(self::progress(500, 0), 0);
+    assert_eq!(stalled.average_eta_seconds, None);
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+//! Unit tests for task status tracking
+
+use burncloud_download::services::StatusTracker;
+use burncloud_download::{DownloadStatus, DownloadTask, TaskId};
+use std::path::PathBuf;
+
+#[tokio::test]
+async fn test_unseen_tasks_count_as_changed() {
+    let tracker = StatusTracker::new();
+    let task_id = TaskId::new();
+
+    assert_eq!(tracker.last_status(task_id).await, None);
+    assert!(tracker.has_changed(task_id, &DownloadStatus::Waiting).await);
+    assert_eq!(tracker.record(task_id, DownloadStatus::Waiting).await, None);
+    assert_eq!(tracker.last_status(task_id).await, Some(DownloadStatus::Waiting));
+}
+
+#[tokio::test]
+async fn test_only_transitions_are_changes() {
+    let tracker = StatusTracker::new();
+    let task_id = TaskId::new();
+    tracker.record(task_id, DownloadStatus::Downloading).await;
+
+    assert!(!tracker.has_changed(task_id, &DownloadStatus::Downloading).await);
+    assert!(tracker.has_changed(task_id, &DownloadStatus::Completed).await);
+
+    assert_eq!(tracker.record(task_id, DownloadStatus::Failed("timeout".to_string())).await, Some(DownloadStatus::Downloading));
+    // A different failure reason is a change as well
+    assert!(tracker.has_changed(task_id, &DownloadStatus::Failed("404".to_string())).await);
+    assert!(!tracker.has_changed(task_id, &DownloadStatus::Failed("timeout".to_string())).await);
+}
+
+#[tokio::test]
+async fn test_removed_tasks_are_forgotten() {
+    let tracker = StatusTracker::new();
+    let task_id = TaskId::new();
+    let other = TaskId::new();
+    tracker.record(task_id, DownloadStatus::Paused).await;
+    tracker.record(other, DownloadStatus::Paused).await;
+
+    tracker.remove_task(task_id).await;
+
+    assert_eq!(tracker.last_status(task_id).await, None);
+    assert_eq!(tracker.last_status(other).await, Some(DownloadStatus::Paused));
+}
+
+#[tokio::test]
+async fn test_changed_keeps_only_dirty_tasks() {
+    let tracker = StatusTracker::new();
+    let task = |status| {
+        let mut task = DownloadTask::new("https://example.com/file.bin".to_string(), PathBuf::from("/tmp/file.bin"));
+        task.status = status;
+        task
+    };
+    let idle = task(DownloadStatus::Downloading);
+    let finished = task(DownloadStatus::Completed);
+    let unseen = task(DownloadStatus::Waiting);
+    tracker.record(idle.id, DownloadStatus::Downloading).await;
+    tracker.record(finished.id, DownloadStatus::Downloading).await;
+
+    let changed = tracker.changed(vec![idle, finished.clone(), unseen.clone()]).await;
+
+    let ids: Vec<TaskId> = changed.iter().map(|task| task.id).collect();
+    assert_eq!(ids, vec![finished.id, unseen.id]);
+}
\ No newline at end of file
@@ -0,0 +1,83 @@
+//! Unit tests for disk space and quota checks
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use burncloud_download::{StorageChecker, DownloadError};
+use burncloud_download::storage::{available_space, directory_size};
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_storage_{}_{}", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn test_directory_size_counts_nested_files() {
+    let dir = unique_temp_dir("size");
+    tokio::fs::create_dir_all(dir.join("nested")).await.unwrap();
+    tokio::fs::write(dir.join("a.bin"), vec![0u8; 100]).await.unwrap();
+    tokio::fs::write(dir.join("nested/b.bin"), vec![0u8; 50]).await.unwrap();
+
+    assert_eq!(directory_size(&dir).await.unwrap(), 150);
+    assert_eq!(directory_size(&dir.join("missing")).await.unwrap(), 0);
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_available_space_for_missing_path() {
+    let path = unique_temp_dir("space").join("not/yet/created.zip");
+    assert!(available_space(&path).unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_insufficient_disk_space_is_rejected() {
+    let checker = StorageChecker::new();
+    let target = unique_temp_dir("disk").join("huge.iso");
+
+    match checker.check_size(&target, u64::MAX).await {
+        Err(DownloadError::InsufficientDiskSpace { required, .. }) => assert_eq!(required, u64::MAX),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+    assert!(checker.check_size(&target, 1).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_quota_limits_directory_growth() {
+    let dir = unique_temp_dir("quota");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    tokio::fs::write(dir.join("existing.bin"), vec![0u8; 600]).await.unwrap();
+
+    let checker = StorageChecker::new();
+    checker.set_quota(&dir, 1_000).await;
+
+    assert!(checker.check_size(&dir.join("small.bin"), 400).await.is_ok());
+    match checker.check_size(&dir.join("large.bin"), 401).await {
+        Err(DownloadError::QuotaExceeded { remaining, .. }) => assert_eq!(remaining, 400),
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Paths outside the quota directory are unaffected
+    assert!(checker.check_size(&unique_temp_dir("other").join("large.bin"), 401).await.is_ok());
+
+    checker.remove_quota(&dir).await;
+    assert!(checker.check_size(&dir.join("large.bin"), 401).await.is_ok());
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_size_probe_gives_up_on_silent_server() {
+    // Accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/file.zip", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            connections.push(stream);
+        }
+    });
+
+    let checker = StorageChecker::with_timeout(Duration::from_millis(200));
+    let started = Instant::now();
+    assert_eq!(checker.probe_size(&url).await, None);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
\ No newline at end of file
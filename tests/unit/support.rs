@@ -0,0 +1,307 @@
+//! Shared fakes for unit tests
+//!
+//! [`MemoryBackend`] stands in for aria2, so managers can be tested without
+//! a daemon. Downloads only change status when a test tells them to.
+//! [`FileBackend`] writes real files for backends wrapping another one.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use burncloud_download::{DownloadError, Result};
+use burncloud_download::traits::DownloadBackend;
+use burncloud_download::models::{DownloadOptions, FileProgress};
+use burncloud_download::types::{TaskId, DownloadTask, DownloadProgress, DownloadStatus};
+
+/// In-memory backend whose downloads change status only when told to
+#[derive(Default)]
+pub struct MemoryBackend {
+    tasks: RwLock<HashMap<TaskId, DownloadTask>>,
+    codes: RwLock<HashMap<TaskId, u32>>,
+    downloaded: RwLock<HashMap<TaskId, u64>>,
+    speeds: RwLock<HashMap<TaskId, u64>>,
+    /// Files of multi-file downloads, single-file downloads have none
+    files: RwLock<HashMap<TaskId, Vec<FileProgress>>>,
+    selections: RwLock<HashMap<TaskId, Vec<u32>>>,
+    global_limit: RwLock<u64>,
+    options: RwLock<HashMap<TaskId, DownloadOptions>>,
+    /// Whether new downloads start downloading instead of waiting
+    starts_downloads: bool,
+    /// Whether sources can be swapped, like aria2, or not, like SFTP
+    swaps_sources: bool,
+    /// Whether downloads survive a restart, so restored tasks are taken back
+    reattaches: bool,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start new downloads right away instead of leaving them waiting
+    pub fn starting_downloads(mut self) -> Self {
+        self.starts_downloads = true;
+        self
+    }
+
+    /// Let downloads continue from another URL
+    pub fn swapping_sources(mut self) -> Self {
+        self.swaps_sources = true;
+        self
+    }
+
+    /// Take restored tasks back instead of starting them again
+    pub fn reattaching(mut self) -> Self {
+        self.reattaches = true;
+        self
+    }
+
+    pub async fn has(&self, task_id: TaskId) -> bool {
+        self.tasks.read().await.contains_key(&task_id)
+    }
+
+    /// Get the options a download was added with
+    pub async fn options(&self, task_id: TaskId) -> Option<DownloadOptions> {
+        self.options.read().await.get(&task_id).cloned()
+    }
+
+    /// Get the last global speed limit that was set
+    pub async fn global_limit(&self) -> u64 {
+        *self.global_limit.read().await
+    }
+
+    pub async fn set_status(&self, task_id: TaskId, status: DownloadStatus) -> Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id)
+            .ok_or(DownloadError::TaskNotFound(task_id))?;
+        task.update_status(status);
+        Ok(())
+    }
+
+    /// Fail a download with an engine error code
+    pub async fn fail(&self, task_id: TaskId, message: &str, code: u32) {
+        self.codes.write().await.insert(task_id, code);
+        self.set_status(task_id, DownloadStatus::Failed(message.to_string())).await.unwrap();
+    }
+
+    pub async fn set_downloaded(&self, task_id: TaskId, bytes: u64) {
+        self.downloaded.write().await.insert(task_id, bytes);
+    }
+
+    pub async fn set_speed(&self, task_id: TaskId, speed_bps: u64) {
+        self.speeds.write().await.insert(task_id, speed_bps);
+    }
+
+    /// Make a download consist of several files
+    pub async fn set_files(&self, task_id: TaskId, files: Vec<FileProgress>) {
+        self.files.write().await.insert(task_id, files);
+    }
+
+    /// Get the indices of the files selected for a download
+    pub async fn selection(&self, task_id: TaskId) -> Option<Vec<u32>> {
+        self.selections.read().await.get(&task_id).cloned()
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for MemoryBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        let mut task = DownloadTask::new(url, target_path);
+        if self.starts_downloads {
+            task.update_status(DownloadStatus::Downloading);
+        }
+        let task_id = task.id;
+        self.tasks.write().await.insert(task_id, task);
+        Ok(task_id)
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let task_id = self.add(url, target_path).await?;
+        self.options.write().await.insert(task_id, options.clone());
+        Ok(task_id)
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, options: &DownloadOptions) -> Result<TaskId> {
+        let url = urls.into_iter().next()
+            .ok_or_else(|| DownloadError::InvalidUrl("At least one source URL is required".to_string()))?;
+        self.add_with_options(url, target_path, options).await
+    }
+
+    async fn pause(&self, task_id: TaskId) -> Result<()> {
+        self.set_status(task_id, DownloadStatus::Paused).await
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        self.codes.write().await.remove(&task_id);
+        self.set_status(task_id, DownloadStatus::Downloading).await
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.tasks.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, task_id: TaskId) -> Result<DownloadProgress> {
+        self.task(task_id).await?;
+        let mut progress = DownloadProgress::new();
+        progress.downloaded_bytes = self.downloaded.read().await.get(&task_id).copied().unwrap_or(0);
+        progress.speed_bps = self.speeds.read().await.get(&task_id).copied().unwrap_or(0);
+        Ok(progress)
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.tasks.read().await.get(&task_id).cloned()
+            .ok_or(DownloadError::TaskNotFound(task_id))
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        Ok(self.tasks.read().await.values().cloned().collect())
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        Ok(self.tasks.read().await.values().filter(|t| t.status.is_active()).count())
+    }
+
+    async fn set_global_speed_limit(&self, bytes_per_sec: u64) -> Result<()> {
+        *self.global_limit.write().await = bytes_per_sec;
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, task_id: TaskId, _bytes_per_sec: u64) -> Result<()> {
+        self.task(task_id).await.map(|_| ())
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        self.task(task_id).await.map(|_| Some(task_id.to_string()))
+    }
+
+    async fn reattach(&self, task: &DownloadTask, _engine_id: &str) -> Result<bool> {
+        if !self.reattaches {
+            return Ok(false);
+        }
+        self.tasks.write().await.insert(task.id, task.clone());
+        Ok(true)
+    }
+
+    async fn change_source(&self, task_id: TaskId, new_url: &str, options: &DownloadOptions) -> Result<bool> {
+        if !self.swaps_sources {
+            return Ok(false);
+        }
+        assert!(options.continue_partial);
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id)
+            .ok_or(DownloadError::TaskNotFound(task_id))?;
+        task.url = new_url.to_string();
+        Ok(true)
+    }
+
+    async fn error_code(&self, task_id: TaskId) -> Result<Option<u32>> {
+        Ok(self.codes.read().await.get(&task_id).copied())
+    }
+
+    async fn files(&self, task_id: TaskId) -> Result<Vec<FileProgress>> {
+        let task = self.task(task_id).await?;
+        match self.files.read().await.get(&task_id) {
+            Some(files) => Ok(files.clone()),
+            None => Ok(vec![FileProgress::whole(task.target_path, &self.progress(task_id).await?)]),
+        }
+    }
+
+    async fn select_files(&self, task_id: TaskId, indices: &[u32]) -> Result<bool> {
+        if !self.files.read().await.contains_key(&task_id) {
+            return Ok(false);
+        }
+        self.selections.write().await.insert(task_id, indices.to_vec());
+        Ok(true)
+    }
+}
+
+/// Backend writing the file at once and completing on `resume`
+#[derive(Default)]
+pub struct FileBackend {
+    tasks: RwLock<HashMap<TaskId, DownloadTask>>,
+    contents: &'static [u8],
+    reattached: RwLock<Vec<PathBuf>>,
+}
+
+impl FileBackend {
+    pub fn writing(contents: &'static [u8]) -> Self {
+        Self { contents, ..Default::default() }
+    }
+
+    /// Get the target paths of the tasks it was asked to reattach
+    pub async fn reattached(&self) -> Vec<PathBuf> {
+        self.reattached.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for FileBackend {
+    async fn add(&self, url: String, target_path: PathBuf) -> Result<TaskId> {
+        tokio::fs::write(&target_path, self.contents).await?;
+        let mut task = DownloadTask::new(url, target_path);
+        task.update_status(DownloadStatus::Downloading);
+        let task_id = task.id;
+        self.tasks.write().await.insert(task_id, task);
+        Ok(task_id)
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        self.add(url, target_path).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        self.add(urls[0].clone(), target_path).await
+    }
+
+    async fn pause(&self, _task_id: TaskId) -> Result<()> {
+        Ok(())
+    }
+
+    async fn resume(&self, task_id: TaskId) -> Result<()> {
+        let mut tasks = self.tasks.write().await;
+        let task = tasks.get_mut(&task_id).ok_or(DownloadError::TaskNotFound(task_id))?;
+        task.update_status(DownloadStatus::Completed);
+        Ok(())
+    }
+
+    async fn cancel(&self, task_id: TaskId) -> Result<()> {
+        self.tasks.write().await.remove(&task_id);
+        Ok(())
+    }
+
+    async fn progress(&self, _task_id: TaskId) -> Result<DownloadProgress> {
+        Ok(DownloadProgress::new())
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.tasks.read().await.get(&task_id).cloned()
+            .ok_or(DownloadError::TaskNotFound(task_id))
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        Ok(self.tasks.read().await.values().cloned().collect())
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        Ok(self.tasks.read().await.len())
+    }
+
+    async fn set_global_speed_limit(&self, _bytes_per_sec: u64) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, _task_id: TaskId, _bytes_per_sec: u64) -> Result<()> {
+        Ok(())
+    }
+
+    async fn engine_id(&self, task_id: TaskId) -> Result<Option<String>> {
+        Ok(Some(task_id.to_string()))
+    }
+
+    async fn reattach(&self, task: &DownloadTask, _engine_id: &str) -> Result<bool> {
+        self.reattached.write().await.push(task.target_path.clone());
+        Ok(false)
+    }
+}
\ No newline at end of file
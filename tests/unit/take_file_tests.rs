@@ -0,0 +1,133 @@
+//! Unit tests for handing completed files over to their consumers
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{DownloadError, PersistentAria2Manager, PostProcessingState};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::GcPolicy;
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_take_file_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Add a download, write its file and let the manager see it complete
+async fn completed_download(manager: &PersistentAria2Manager, backend: &MemoryBackend, target: PathBuf) -> TaskId {
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+    std::fs::write(&target, b"weights").unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+
+    for _ in 0..100 {
+        if matches!(manager.post_processing_state(task_id).await, Some(PostProcessingState::Succeeded { .. })) {
+            return task_id;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Task {} was not post-processed", task_id);
+}
+
+#[tokio::test]
+async fn test_take_file_in_place_only_once() {
+    let dir = test_dir("in_place");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let target = dir.join("model.bin");
+    let task_id = completed_download(&manager, &backend, target.clone()).await;
+
+    assert_eq!(manager.taken_file(task_id).await.unwrap(), None);
+    assert_eq!(manager.take_file(task_id).await.unwrap(), target);
+    assert_eq!(std::fs::read(&target).unwrap(), b"weights");
+    assert_eq!(manager.taken_file(task_id).await.unwrap(), Some(target.clone()));
+
+    let again = manager.take_file(task_id).await;
+    assert!(matches!(again, Err(DownloadError::FileTaken(id)) if id == task_id));
+    let relocated = manager.relocate_task(task_id, dir.join("elsewhere.bin")).await;
+    assert!(matches!(relocated, Err(DownloadError::FileTaken(_))));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_take_file_moves_to_destination() {
+    let dir = test_dir("move");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let target = dir.join("model.bin");
+    let task_id = completed_download(&manager, &backend, target.clone()).await;
+
+    let destination = dir.join("loader").join("model.bin");
+    let taken = manager.take_file_to(task_id, Some(destination.clone())).await.unwrap();
+    assert_eq!(taken, destination);
+    assert_eq!(std::fs::read(&destination).unwrap(), b"weights");
+    assert!(!target.exists());
+
+    // The engine forgetting the task doesn't make it an orphan to collect
+    backend.cancel(task_id).await.unwrap();
+    manager.set_gc_policy(GcPolicy::new()).await;
+    let report = manager.run_gc().await.unwrap();
+    assert!(report.removed.is_empty());
+    assert_eq!(manager.taken_file(task_id).await.unwrap(), Some(destination));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_take_file_has_a_single_winner() {
+    let dir = test_dir("race");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = Arc::new(manager(backend.clone(), &dir).await);
+    let task_id = completed_download(&manager, &backend, dir.join("model.bin")).await;
+
+    let first = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.take_file(task_id).await }
+    });
+    let second = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.take_file(task_id).await }
+    });
+    let results = [first.await.unwrap(), second.await.unwrap()];
+
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(results.iter().any(|result| matches!(result, Err(DownloadError::FileTaken(_)))));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_take_file_requires_completed_download() {
+    let dir = test_dir("unfinished");
+    let manager = manager(Arc::new(MemoryBackend::default()), &dir).await;
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+
+    let result = manager.take_file(task_id).await;
+    assert!(matches!(result, Err(DownloadError::InvalidTaskState { task_id: id, .. }) if id == task_id));
+    assert_eq!(manager.taken_file(task_id).await.unwrap(), None);
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+//! Unit tests for the target path registry
+
+use burncloud_download::{DownloadError, TaskId};
+use burncloud_download::services::TargetPathRegistry;
+use burncloud_download::utils::paths::{renamed_path, normalize_path, path_key, windows_path_key, move_file};
+use std::path::{Path, PathBuf};
+
+fn unique_temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_paths_{}_{}", name, std::process::id()))
+}
+
+#[test]
+fn test_renamed_path() {
+    assert_eq!(renamed_path(Path::new("data/file.zip"), 1), PathBuf::from("data/file (1).zip"));
+    assert_eq!(renamed_path(Path::new("data/archive.tar.gz"), 2), PathBuf::from("data/archive.tar (2).gz"));
+    assert_eq!(renamed_path(Path::new("data/README"), 3), PathBuf::from("data/README (3)"));
+}
+
+#[test]
+fn test_normalize_path() {
+    assert_eq!(normalize_path(Path::new("/data/./a/../file.zip")), PathBuf::from("/data/file.zip"));
+    assert_eq!(normalize_path(Path::new("file.zip")), std::env::current_dir().unwrap().join("file.zip"));
+}
+
+#[test]
+fn test_windows_path_key() {
+    let key = windows_path_key(r"C:\data\file.zip");
+    assert_eq!(key, r"c:\data\file.zip");
+    assert_eq!(windows_path_key("C:/data/file.zip"), key);
+    assert_eq!(windows_path_key(r"c:\Data\FILE.zip"), key);
+    assert_eq!(windows_path_key(r"\\?\C:\data\file.zip"), key);
+    assert_eq!(windows_path_key(r"C:\data\\.\tmp\..\file.zip\"), key);
+
+    // Never climbs above the drive or the share
+    assert_eq!(windows_path_key(r"C:\..\.."), r"c:\");
+    assert_eq!(windows_path_key(r"\\?\UNC\Server\Share\file.zip"), r"\\server\share\file.zip");
+    assert_eq!(windows_path_key(r"//server/share/../file.zip"), r"\\server\share\file.zip");
+}
+
+#[cfg(not(windows))]
+#[test]
+fn test_path_key_keeps_case_outside_windows() {
+    assert_eq!(path_key(Path::new("/data/./File.zip")), PathBuf::from("/data/File.zip"));
+    assert_ne!(path_key(Path::new("/data/File.zip")), path_key(Path::new("/data/file.zip")));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_path_key_ignores_case_and_separators_on_windows() {
+    assert_eq!(path_key(Path::new(r"C:\Data\file.zip")), path_key(Path::new("c:/data/FILE.zip")));
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn test_reservation_conflicts_across_spellings_on_windows() {
+    let registry = TargetPathRegistry::new();
+    let task_id = TaskId::new();
+
+    let path = registry.reserve(Path::new(r"C:\downloads\file.zip"), false).await.unwrap();
+    registry.assign(&path, task_id).await;
+    assert!(registry.reserve(Path::new("c:/Downloads/FILE.zip"), false).await.is_err());
+}
+
+#[tokio::test]
+async fn test_conflicting_reservation_is_rejected() {
+    let registry = TargetPathRegistry::new();
+    let task_id = TaskId::new();
+
+    let path = registry.reserve(Path::new("/downloads/file.zip"), false).await.unwrap();
+    registry.assign(&path, task_id).await;
+
+    // The same file through a different spelling still conflicts
+    match registry.reserve(Path::new("/downloads/./file.zip"), false).await {
+        Err(DownloadError::TargetPathConflict { task_id: owner, .. }) => assert_eq!(owner, Some(task_id)),
+        other => panic!("Expected a target path conflict, got {:?}", other),
+    }
+
+    registry.release(task_id).await;
+    assert_eq!(registry.owner(&path).await, None);
+    assert!(registry.reserve(&path, false).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_auto_rename_skips_taken_and_existing_files() {
+    let dir = unique_temp_dir("rename");
+    tokio::fs::create_dir_all(&dir).await.unwrap();
+    let target = dir.join("file.zip");
+
+    // "file (1).zip" already exists on disk
+    tokio::fs::write(dir.join("file (1).zip"), b"old").await.unwrap();
+
+    let registry = TargetPathRegistry::new();
+    assert_eq!(registry.reserve(&target, true).await.unwrap(), target);
+    assert_eq!(registry.reserve(&target, true).await.unwrap(), dir.join("file (2).zip"));
+    assert_eq!(registry.reserve(&target, true).await.unwrap(), dir.join("file (3).zip"));
+
+    // A dropped reservation frees the name again
+    registry.release_path(&dir.join("file (2).zip")).await;
+    assert_eq!(registry.reserve(&target, true).await.unwrap(), dir.join("file (2).zip"));
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_move_file_replaces_source() {
+    let dir = unique_temp_dir("move");
+    tokio::fs::create_dir_all(dir.join("models")).await.unwrap();
+    tokio::fs::write(dir.join("model.bin"), b"weights").await.unwrap();
+
+    move_file(&dir.join("model.bin"), &dir.join("models/model.bin")).await.unwrap();
+    assert!(!dir.join("model.bin").exists());
+    assert_eq!(tokio::fs::read(dir.join("models/model.bin")).await.unwrap(), b"weights");
+
+    let missing = move_file(&dir.join("missing.bin"), &dir.join("models/missing.bin")).await;
+    assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
\ No newline at end of file
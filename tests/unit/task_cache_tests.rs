@@ -0,0 +1,63 @@
+//! Unit tests for the task state cache
+
+use std::path::PathBuf;
+use std::time::Duration;
+use burncloud_download::services::TaskCache;
+use burncloud_download::types::{DownloadTask, DownloadProgress, DownloadStatus, TaskId};
+
+fn task() -> DownloadTask {
+    DownloadTask::new("https://example.com/file.zip".to_string(), PathBuf::from("data/file.zip"))
+}
+
+#[tokio::test]
+async fn test_cached_entries_are_served_within_ttl() {
+    let cache = TaskCache::new(Duration::from_secs(60));
+    let mut task = task();
+    task.update_status(DownloadStatus::Downloading);
+    let progress = DownloadProgress {
+        downloaded_bytes: 2048,
+        total_bytes: Some(4096),
+        speed_bps: 1024,
+        eta_seconds: Some(2),
+    };
+
+    cache.put_task(&task).await;
+    cache.put_progress(task.id, &progress).await;
+
+    assert_eq!(cache.task(task.id).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(cache.progress(task.id).await.unwrap().downloaded_bytes, 2048);
+    assert!(cache.task(TaskId::new()).await.is_none());
+}
+
+#[tokio::test]
+async fn test_entries_expire_after_ttl() {
+    let cache = TaskCache::new(Duration::from_millis(20));
+    let task = task();
+    cache.put_task(&task).await;
+    cache.put_progress(task.id, &DownloadProgress::new()).await;
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(cache.task(task.id).await.is_none());
+    assert!(cache.progress(task.id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_invalidate_drops_task_and_progress() {
+    let cache = TaskCache::new(Duration::from_secs(60));
+    let task = task();
+    cache.put_task(&task).await;
+    cache.put_progress(task.id, &DownloadProgress::new()).await;
+
+    cache.invalidate(task.id).await;
+    assert!(cache.task(task.id).await.is_none());
+    assert!(cache.progress(task.id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_zero_ttl_disables_caching() {
+    let cache = TaskCache::new(Duration::ZERO);
+    let task = task();
+    cache.put_task(&task).await;
+
+    assert!(cache.task(task.id).await.is_none());
+}
\ No newline at end of file
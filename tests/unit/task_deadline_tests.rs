@@ -0,0 +1,199 @@
+//! Unit tests for start deadlines of queued tasks
+//!
+//! The manager tests use an in-memory backend that queues every download, so
+//! no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use burncloud_download::{DownloadError, PersistentAria2Manager, TaskStatus};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{DownloadEvent, DownloadOptions, EXPIRED_REASON};
+use burncloud_download::queue::manager::TaskQueueManager;
+use burncloud_download::services::DeadlineTracker;
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("burncloud_deadline_{}_{}", name, std::process::id()))
+}
+
+// Nothing listens on the discard port, so size probes fail right away
+const URL: &str = "http://127.0.0.1:9/file.zip";
+
+async fn add(manager: &TaskQueueManager, name: &str, options: DownloadOptions) -> TaskId {
+    manager.add_download_with_options(
+        format!("https://example.com/{}", name),
+        PathBuf::from("/downloads").join(name),
+        options,
+    ).await.unwrap()
+}
+
+#[test]
+fn test_expired_status_round_trip() {
+    let status = TaskStatus::Expired.to_download_status();
+    assert_eq!(status, DownloadStatus::Failed(EXPIRED_REASON.to_string()));
+    assert_eq!(TaskStatus::from_download_status(status), TaskStatus::Expired);
+    assert_eq!(
+        TaskStatus::from_download_status(DownloadStatus::Failed("timeout".to_string())),
+        TaskStatus::Failed("timeout".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_deadline_tracker_reports_due_tasks() {
+    let tracker = DeadlineTracker::new();
+    let now = SystemTime::now();
+    let due = TaskId::new();
+    let later = TaskId::new();
+
+    tracker.track(due, Some(now - Duration::from_secs(1))).await;
+    tracker.track(later, Some(now + Duration::from_secs(60))).await;
+    tracker.track(TaskId::new(), None).await;
+
+    assert_eq!(tracker.due(now).await, vec![(due, now - Duration::from_secs(1))]);
+    assert_eq!(tracker.deadline(later).await, Some(now + Duration::from_secs(60)));
+
+    tracker.remove_task(due).await;
+    tracker.remove_task(later).await;
+    assert!(tracker.is_empty().await);
+}
+
+#[tokio::test]
+async fn test_queue_expires_waiting_task() {
+    let manager = TaskQueueManager::new();
+    let mut events = manager.subscribe_events();
+    for name in ["a.zip", "b.zip", "c.zip"] {
+        add(&manager, name, DownloadOptions::new()).await;
+    }
+
+    let expires_at = SystemTime::now() + Duration::from_millis(50);
+    let task_id = add(&manager, "late.zip", DownloadOptions::new().expires_at(expires_at)).await;
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Waiting);
+    assert_eq!(manager.task_expires_at(task_id).await, Some(expires_at));
+
+    let result = manager.wait_for_completion(task_id, Some(Duration::from_secs(2))).await;
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { reason, .. }) if reason == EXPIRED_REASON));
+    let task = manager.get_task(task_id).await.unwrap();
+    assert_eq!(TaskStatus::from_download_status(task.status), TaskStatus::Expired);
+    assert_eq!(manager.task_expires_at(task_id).await, None);
+
+    let expired = loop {
+        match events.recv().await.unwrap() {
+            DownloadEvent::Expired { task_id, expires_at } => break (task_id, expires_at),
+            _ => continue,
+        }
+    };
+    assert_eq!(expired, (task_id, expires_at));
+}
+
+#[tokio::test]
+async fn test_queue_keeps_task_started_in_time() {
+    let manager = TaskQueueManager::new();
+    let expires_at = SystemTime::now() + Duration::from_millis(50);
+    let task_id = add(&manager, "file.zip", DownloadOptions::new().expires_at(expires_at)).await;
+
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.task_expires_at(task_id).await, None);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_queue_never_starts_task_past_deadline() {
+    let manager = TaskQueueManager::new();
+    let expired = SystemTime::now() - Duration::from_secs(1);
+    let task_id = add(&manager, "file.zip", DownloadOptions::new().expires_at(expired)).await;
+
+    // A slot was free, but the deadline had already passed
+    let result = manager.wait_for_completion(task_id, Some(Duration::from_secs(2))).await;
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { reason, .. }) if reason == EXPIRED_REASON));
+    assert_eq!(manager.active_download_count().await, 0);
+}
+
+#[tokio::test]
+async fn test_queue_checks_paused_task_when_resumed() {
+    let manager = TaskQueueManager::new();
+    for name in ["a.zip", "b.zip", "c.zip"] {
+        add(&manager, name, DownloadOptions::new()).await;
+    }
+    let expires_at = SystemTime::now() + Duration::from_millis(50);
+    let task_id = add(&manager, "late.zip", DownloadOptions::new().expires_at(expires_at)).await;
+    manager.pause_task(task_id).await.unwrap();
+
+    // Paused tasks don't expire, they are checked once they wait again
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Paused);
+
+    manager.resume_task(task_id).await.unwrap();
+    let result = manager.wait_for_completion(task_id, Some(Duration::from_secs(2))).await;
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { reason, .. }) if reason == EXPIRED_REASON));
+}
+
+#[tokio::test]
+async fn test_manager_expires_waiting_download() {
+    let dir = test_dir("manager");
+    let manager = PersistentAria2Manager::builder()
+        .backend(Arc::new(MemoryBackend::default()))
+        .download_dir(&dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    let mut events = manager.subscribe_events();
+
+    let expires_at = SystemTime::now() + Duration::from_millis(50);
+    let task_id = manager.add_download_with_options(
+        URL.to_string(),
+        dir.join("file.zip"),
+        DownloadOptions::new().expires_at(expires_at),
+    ).await.unwrap();
+    assert_eq!(manager.task_expires_at(task_id).await.unwrap(), Some(expires_at));
+
+    let result = manager.wait_for_completion(task_id, Some(Duration::from_secs(2))).await;
+    assert!(matches!(result, Err(DownloadError::DownloadFailed { reason, .. }) if reason == EXPIRED_REASON));
+    assert_eq!(manager.task_status(task_id).await.unwrap(), TaskStatus::Expired);
+
+    let expired = loop {
+        match events.recv().await.unwrap() {
+            DownloadEvent::Expired { task_id, expires_at } => break (task_id, expires_at),
+            _ => continue,
+        }
+    };
+    assert_eq!(expired, (task_id, expires_at));
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_expired_download_is_not_reused() {
+    let dir = test_dir("readd");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend.clone())
+        .download_dir(&dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+
+    let task_id = manager.add_download_with_options(
+        URL.to_string(),
+        dir.join("file.zip"),
+        DownloadOptions::new().expires_at(SystemTime::now() + Duration::from_millis(50)),
+    ).await.unwrap();
+    assert!(manager.wait_for_completion(task_id, Some(Duration::from_secs(2))).await.is_err());
+
+    // The same request starts a new download instead of returning the expired task
+    let new_id = manager.add_download(URL.to_string(), dir.join("file.zip")).await.unwrap();
+    assert_ne!(new_id, task_id);
+    assert!(backend.task(new_id).await.is_ok());
+
+    manager.shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,68 @@
+//! Unit tests for the task import/export format
+
+use burncloud_download::{TaskExport, ExportedTask, ImportPolicy, TaskStatus, DownloadOptions, Checksum, ChecksumAlgorithm, Priority};
+use burncloud_download::models::task_export::TASK_EXPORT_VERSION;
+use std::path::PathBuf;
+
+fn sample_task() -> ExportedTask {
+    ExportedTask {
+        url: "https://example.com/model.bin".to_string(),
+        sources: Vec::new(),
+        target_path: PathBuf::from("data/model.bin"),
+        status: TaskStatus::Paused,
+        options: DownloadOptions::new()
+            .priority(Priority::High)
+            .checksum(Checksum::new(ChecksumAlgorithm::Sha256, "ABCDEF")),
+    }
+}
+
+#[test]
+fn test_export_roundtrip() {
+    let export = TaskExport::new(vec![sample_task()]);
+    assert_eq!(export.version, TASK_EXPORT_VERSION);
+
+    let mut json = Vec::new();
+    export.to_writer(&mut json).unwrap();
+    let imported = TaskExport::from_reader(json.as_slice()).unwrap();
+
+    assert_eq!(imported, export);
+    assert_eq!(imported.tasks[0].options.priority, Priority::High);
+}
+
+#[test]
+fn test_minimal_task_uses_default_options() {
+    let json = r#"{
+        "version": 1,
+        "exported_at": 0,
+        "tasks": [
+            { "url": "https://example.com/a.zip", "target_path": "data/a.zip", "status": "Waiting" }
+        ]
+    }"#;
+
+    let export = TaskExport::from_reader(json.as_bytes()).unwrap();
+
+    assert_eq!(export.tasks.len(), 1);
+    assert!(export.tasks[0].sources.is_empty());
+    assert_eq!(export.tasks[0].options, DownloadOptions::default());
+}
+
+#[test]
+fn test_newer_versions_are_rejected() {
+    let json = format!(r#"{{ "version": {}, "exported_at": 0, "tasks": [] }}"#, TASK_EXPORT_VERSION + 1);
+
+    assert!(TaskExport::from_reader(json.as_bytes()).is_err());
+    assert!(TaskExport::from_reader("not json".as_bytes()).is_err());
+}
+
+#[test]
+fn test_single_source_omits_sources() {
+    let mut json = Vec::new();
+    TaskExport::new(vec![sample_task()]).to_writer(&mut json).unwrap();
+
+    assert!(!String::from_utf8(json).unwrap().contains("\"sources\""));
+}
+
+#[test]
+fn test_import_policy_defaults_to_skip() {
+    assert_eq!(ImportPolicy::default(), ImportPolicy::SkipExisting);
+}
\ No newline at end of file
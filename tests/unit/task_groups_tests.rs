@@ -0,0 +1,161 @@
+//! Unit tests for task groups
+
+use burncloud_download::{BasicDownloadManager, DownloadError, DownloadManager, DownloadProgress, DownloadStatus, TaskId};
+use burncloud_download::groups::{aggregate_progress, aggregate_status, GroupStore, TaskGroups};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+async fn setup() -> (Arc<BasicDownloadManager>, TaskGroups) {
+    let manager = Arc::new(BasicDownloadManager::new());
+    let groups = TaskGroups::new(manager.clone(), GroupStore::in_memory().await.unwrap());
+    (manager, groups)
+}
+
+async fn add_task(manager: &BasicDownloadManager, name: &str) -> TaskId {
+    manager.add_download(
+        format!("https://example.com/{}", name),
+        PathBuf::from(format!("./test_downloads/groups/{}", name)),
+    ).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_create_group_and_add_tasks() {
+    let (manager, groups) = setup().await;
+    let shard1 = add_task(&manager, "model-00001.bin").await;
+    let shard2 = add_task(&manager, "model-00002.bin").await;
+
+    let group_id = groups.create_group("model shards").await.unwrap();
+    groups.add_task(group_id, shard1).await.unwrap();
+    groups.add_task(group_id, shard2).await.unwrap();
+    // Adding a member again does nothing
+    groups.add_task(group_id, shard1).await.unwrap();
+
+    let group = groups.group(group_id).await.unwrap();
+    assert_eq!(group.name, "model shards");
+    assert_eq!(group.task_ids, vec![shard1, shard2]);
+    assert_eq!(groups.group_of(shard2).await.unwrap(), Some(group_id));
+    assert_eq!(groups.find_group("model shards").await.unwrap().map(|group| group.id), Some(group_id));
+    assert_eq!(groups.list_groups().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_task_belongs_to_one_group() {
+    let (manager, groups) = setup().await;
+    let task_id = add_task(&manager, "file.bin").await;
+
+    let first = groups.create_group("first").await.unwrap();
+    let second = groups.create_group("second").await.unwrap();
+    groups.add_task(first, task_id).await.unwrap();
+
+    assert!(groups.add_task(second, task_id).await.is_err());
+
+    assert!(groups.remove_task(task_id).await.unwrap());
+    groups.add_task(second, task_id).await.unwrap();
+    assert_eq!(groups.group_of(task_id).await.unwrap(), Some(second));
+}
+
+#[tokio::test]
+async fn test_unknown_groups_and_tasks_are_rejected() {
+    let (manager, groups) = setup().await;
+    let group_id = groups.create_group("group").await.unwrap();
+
+    assert!(matches!(groups.add_task(group_id, TaskId::new()).await, Err(DownloadError::TaskNotFound(_))));
+
+    let task_id = add_task(&manager, "file.bin").await;
+    groups.delete_group(group_id).await.unwrap();
+    assert!(groups.add_task(group_id, task_id).await.is_err());
+    assert!(groups.delete_group(group_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_pause_resume_and_cancel_group() {
+    let (manager, groups) = setup().await;
+    let shard1 = add_task(&manager, "a.bin").await;
+    let shard2 = add_task(&manager, "b.bin").await;
+    let outsider = add_task(&manager, "c.bin").await;
+
+    let group_id = groups.create_group("shards").await.unwrap();
+    groups.add_task(group_id, shard1).await.unwrap();
+    groups.add_task(group_id, shard2).await.unwrap();
+
+    assert_eq!(groups.pause_group(group_id).await.unwrap(), vec![shard1, shard2]);
+    assert_eq!(groups.status(group_id).await.unwrap(), DownloadStatus::Paused);
+    assert_eq!(manager.get_task(outsider).await.unwrap().status, DownloadStatus::Downloading);
+
+    assert_eq!(groups.resume_group(group_id).await.unwrap(), vec![shard1, shard2]);
+    assert_eq!(groups.status(group_id).await.unwrap(), DownloadStatus::Downloading);
+
+    assert_eq!(groups.cancel_group(group_id).await.unwrap(), vec![shard1, shard2]);
+    assert!(manager.get_task(shard1).await.is_err());
+    assert!(manager.get_task(outsider).await.is_ok());
+    assert!(groups.group(group_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_removed_tasks_leave_the_group() {
+    let (manager, groups) = setup().await;
+    let kept = add_task(&manager, "kept.bin").await;
+    let removed = add_task(&manager, "removed.bin").await;
+
+    let group_id = groups.create_group("group").await.unwrap();
+    groups.add_task(group_id, kept).await.unwrap();
+    groups.add_task(group_id, removed).await.unwrap();
+
+    manager.cancel_download(removed).await.unwrap();
+
+    let progress = groups.progress(group_id).await.unwrap();
+    assert_eq!(progress.total_bytes, manager.get_progress(kept).await.unwrap().total_bytes);
+    assert_eq!(groups.group(group_id).await.unwrap().task_ids, vec![kept]);
+}
+
+#[tokio::test]
+async fn test_groups_are_persisted() {
+    let db_path = std::env::temp_dir().join(format!("burncloud_groups_{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&db_path);
+    let task_id = TaskId::new();
+
+    let group_id = {
+        let store = GroupStore::open(&db_path).await.unwrap();
+        let group_id = store.insert("persisted").await.unwrap();
+        store.add_member(group_id, &task_id).await.unwrap();
+        group_id
+    };
+
+    let reopened = GroupStore::open(&db_path).await.unwrap();
+    let group = reopened.get(group_id).await.unwrap().unwrap();
+    assert_eq!(group.name, "persisted");
+    assert_eq!(group.task_ids, vec![task_id]);
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn test_aggregate_status() {
+    use DownloadStatus::*;
+
+    assert_eq!(aggregate_status(&[Completed, Completed]), Completed);
+    assert_eq!(aggregate_status(&[Completed, Downloading, Paused]), Downloading);
+    assert_eq!(aggregate_status(&[Completed, Waiting, Paused]), Waiting);
+    assert_eq!(aggregate_status(&[Completed, Paused]), Paused);
+    assert_eq!(aggregate_status(&[Downloading, Failed("404".to_string())]), Failed("404".to_string()));
+    assert_eq!(aggregate_status(&[]), Completed);
+}
+
+#[test]
+fn test_aggregate_progress() {
+    let progress = aggregate_progress(&[
+        DownloadProgress { downloaded_bytes: 100, total_bytes: Some(400), speed_bps: 50, eta_seconds: Some(6) },
+        DownloadProgress { downloaded_bytes: 200, total_bytes: Some(600), speed_bps: 150, eta_seconds: Some(2) },
+    ]);
+    assert_eq!(progress.downloaded_bytes, 300);
+    assert_eq!(progress.total_bytes, Some(1000));
+    assert_eq!(progress.speed_bps, 200);
+    assert_eq!(progress.eta_seconds, Some(3));
+
+    let unknown = aggregate_progress(&[
+        DownloadProgress { downloaded_bytes: 100, total_bytes: Some(400), speed_bps: 0, eta_seconds: None },
+        DownloadProgress { downloaded_bytes: 0, total_bytes: None, speed_bps: 0, eta_seconds: None },
+    ]);
+    assert_eq!(unknown.total_bytes, None);
+    assert_eq!(unknown.eta_seconds, None);
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+//! Unit tests for the write-ahead journal of task state changes
+
+use burncloud_download::{DownloadStatus, TaskId};
+use burncloud_download::services::{TaskJournal, JournaledState};
+
+#[tokio::test]
+async fn test_pending_entries_until_committed() {
+    let journal = TaskJournal::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+
+    let paused = journal.record(&task_id, &JournaledState::Paused).await.unwrap();
+    let removed = journal.record(&task_id, &JournaledState::Removed).await.unwrap();
+    assert!(paused < removed);
+
+    let pending = journal.pending().await.unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].seq, paused);
+    assert_eq!(pending[0].task_id, task_id);
+    assert_eq!(pending[0].state, JournaledState::Paused);
+    assert_eq!(pending[1].state, JournaledState::Removed);
+
+    journal.commit(paused).await.unwrap();
+    let pending = journal.pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].seq, removed);
+}
+
+#[tokio::test]
+async fn test_batch_record_and_commit() {
+    let journal = TaskJournal::in_memory().await.unwrap();
+    let first = TaskId::new();
+    let second = TaskId::new();
+    let changes = vec![
+        (first, JournaledState::Completed),
+        (second, JournaledState::Failed("timeout".to_string())),
+    ];
+
+    let seqs = journal.record_all(&changes).await.unwrap();
+    assert_eq!(seqs.len(), 2);
+
+    let pending = journal.pending().await.unwrap();
+    let recorded: Vec<_> = pending.iter().map(|entry| (entry.task_id, entry.state.clone())).collect();
+    assert_eq!(recorded, changes);
+    assert_eq!(pending.iter().map(|entry| entry.seq).collect::<Vec<_>>(), seqs);
+
+    journal.commit_all(&seqs).await.unwrap();
+    assert!(journal.pending().await.unwrap().is_empty());
+
+    // Empty batches are no-ops
+    assert!(journal.record_all(&[]).await.unwrap().is_empty());
+    journal.commit_all(&[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_journaled_state_maps_statuses() {
+    let failed = DownloadStatus::Failed("404".to_string());
+
+    assert_eq!(JournaledState::from(&DownloadStatus::Paused), JournaledState::Paused);
+    assert_eq!(JournaledState::from(&failed).status(), Some(failed));
+    assert_eq!(JournaledState::Downloading.status(), Some(DownloadStatus::Downloading));
+    assert_eq!(JournaledState::Removed.status(), None);
+}
+
+#[tokio::test]
+async fn test_entries_survive_reopen() {
+    let dir = std::env::temp_dir().join(format!("burncloud_journal_reopen_{}", std::process::id()));
+    let path = dir.join("metadata.db");
+    let task_id = TaskId::new();
+
+    {
+        let journal = TaskJournal::open(&path).await.unwrap();
+        journal.record(&task_id, &JournaledState::Paused).await.unwrap();
+    }
+
+    let journal = TaskJournal::open(&path).await.unwrap();
+    let pending = journal.pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].task_id, task_id);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+//! Unit tests for the task metadata store and its GID mapping
+
+use burncloud_download::{TaskId, DownloadError, RemoteValidators};
+use burncloud_download::services::TaskMetadataStore;
+use burncloud_download::services::task_metadata_store::RETRY_ATTEMPTS_KEY;
+use std::path::Path;
+
+#[tokio::test]
+async fn test_gid_mapping_roundtrip() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+
+    assert_eq!(store.get_gid(&task_id).await.unwrap(), None);
+
+    store.put_gid(&task_id, "2089b05ecca3d829").await.unwrap();
+    store.put_gid(&task_id, "d270c8a39a0ab2f0").await.unwrap();
+    assert_eq!(store.get_gid(&task_id).await.unwrap().as_deref(), Some("d270c8a39a0ab2f0"));
+
+    store.remove_task(&task_id).await.unwrap();
+    assert_eq!(store.get_gid(&task_id).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_rekey_moves_values_and_gid() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let old_id = TaskId::new();
+    let new_id = TaskId::new();
+
+    store.put(&old_id, RETRY_ATTEMPTS_KEY, &2u32).await.unwrap();
+    store.put_gid(&old_id, "2089b05ecca3d829").await.unwrap();
+
+    store.rekey_task(&old_id, &new_id).await.unwrap();
+
+    assert_eq!(store.get::<u32>(&new_id, RETRY_ATTEMPTS_KEY).await.unwrap(), Some(2));
+    assert_eq!(store.get::<u32>(&old_id, RETRY_ATTEMPTS_KEY).await.unwrap(), None);
+    assert_eq!(store.get_gid(&new_id).await.unwrap().as_deref(), Some("2089b05ecca3d829"));
+    assert_eq!(store.get_gid(&old_id).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_target_path_claims_are_unique() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let (first, second) = (TaskId::new(), TaskId::new());
+    let path = Path::new("/downloads/file.zip");
+
+    store.claim_path(&first, path).await.unwrap();
+    // Claiming again for the same task is fine
+    store.claim_path(&first, path).await.unwrap();
+    assert_eq!(store.path_owner(path).await.unwrap(), Some(first));
+
+    match store.claim_path(&second, path).await {
+        Err(DownloadError::TargetPathConflict { task_id, .. }) => assert_eq!(task_id, Some(first)),
+        other => panic!("Expected a target path conflict, got {:?}", other),
+    }
+
+    store.release_path(&first).await.unwrap();
+    store.claim_path(&second, path).await.unwrap();
+    assert_eq!(store.path_owner(path).await.unwrap(), Some(second));
+
+    store.remove_task(&second).await.unwrap();
+    assert_eq!(store.path_owner(path).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_download_validators_by_url_and_path() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let task_id = TaskId::new();
+    let url = "https://example.com/model.bin";
+    let path = Path::new("/downloads/model.bin");
+    let validators = RemoteValidators {
+        etag: Some("\"v1\"".to_string()),
+        last_modified: None,
+        size: Some(42),
+    };
+
+    assert_eq!(store.get_download_validators(url, path).await.unwrap(), None);
+
+    store.put_download_validators(url, path, &task_id, &validators).await.unwrap();
+    assert_eq!(store.get_download_validators(url, path).await.unwrap(), Some((task_id, validators.clone())));
+    assert_eq!(store.get_download_validators(url, Path::new("/downloads/other.bin")).await.unwrap(), None);
+
+    // Restored tasks keep their record under the new ID
+    let new_id = TaskId::new();
+    store.rekey_task(&task_id, &new_id).await.unwrap();
+    assert_eq!(store.get_download_validators(url, path).await.unwrap(), Some((new_id, validators)));
+}
+
+#[tokio::test]
+async fn test_url_hash_index_lookup() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let first = TaskId::new();
+    let second = TaskId::new();
+    let other = TaskId::new();
+    let path = Path::new("/downloads/file.zip");
+
+    store.put_url_hash(&first, "hash-a", path).await.unwrap();
+    store.put_url_hash(&second, "hash-a", path).await.unwrap();
+    store.put_url_hash(&other, "hash-a", Path::new("/downloads/other.zip")).await.unwrap();
+
+    assert_eq!(store.find_by_url_hash_and_path("hash-a", path).await.unwrap(), vec![first, second]);
+    assert!(store.find_by_url_hash_and_path("hash-b", path).await.unwrap().is_empty());
+
+    // Restored tasks keep their entry under the new ID, deleted ones lose it
+    let restored = TaskId::new();
+    store.rekey_task(&first, &restored).await.unwrap();
+    store.remove_task(&second).await.unwrap();
+    assert_eq!(store.find_by_url_hash_and_path("hash-a", path).await.unwrap(), vec![restored]);
+
+    store.remove_url_hash(&restored).await.unwrap();
+    assert!(store.find_by_url_hash_and_path("hash-a", path).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_url_hash_backfill_keeps_existing_entries() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let indexed = TaskId::new();
+    let missing = TaskId::new();
+
+    store.put_url_hash(&indexed, "hash-a", Path::new("/downloads/a.zip")).await.unwrap();
+    store.backfill_url_hashes(&[
+        (indexed, "hash-stale".to_string(), Path::new("/downloads/stale.zip").to_path_buf()),
+        (missing, "hash-b".to_string(), Path::new("/downloads/b.zip").to_path_buf()),
+    ]).await.unwrap();
+
+    assert_eq!(store.find_by_url_hash_and_path("hash-a", Path::new("/downloads/a.zip")).await.unwrap(), vec![indexed]);
+    assert!(store.find_by_url_hash_and_path("hash-stale", Path::new("/downloads/stale.zip")).await.unwrap().is_empty());
+    assert_eq!(store.find_by_url_hash_and_path("hash-b", Path::new("/downloads/b.zip")).await.unwrap(), vec![missing]);
+}
\ No newline at end of file
@@ -0,0 +1,124 @@
+//! Unit tests for per-owner task isolation and quotas
+
+use burncloud_download::OwnerQuotas;
+use burncloud_download::models::DownloadOptions;
+use burncloud_download::queue::manager::TaskQueueManager;
+use burncloud_download::services::task_metadata_store::TaskMetadataStore;
+use burncloud_download::traits::DownloadManager;
+use burncloud_download::types::{TaskId, DownloadStatus};
+use std::path::PathBuf;
+
+async fn add_owned(manager: &TaskQueueManager, owner: &str, name: &str) -> TaskId {
+    manager.add_download_with_options(
+        format!("https://example.com/{}", name),
+        PathBuf::from("/downloads").join(name),
+        DownloadOptions::new().owner(owner),
+    ).await.unwrap()
+}
+
+#[test]
+fn test_owner_quotas() {
+    let quotas = OwnerQuotas::new()
+        .default_quota(2)
+        .quota("alice", 1);
+
+    assert_eq!(quotas.quota_for("alice"), Some(1));
+    assert_eq!(quotas.quota_for("bob"), Some(2));
+    assert_eq!(OwnerQuotas::new().quota_for("bob"), None);
+}
+
+#[tokio::test]
+async fn test_queue_enforces_owner_quota() {
+    let manager = TaskQueueManager::new();
+    manager.set_owner_quotas(OwnerQuotas::new().quota("alice", 1)).await.unwrap();
+
+    let first = add_owned(&manager, "alice", "a.zip").await;
+    let second = add_owned(&manager, "alice", "b.zip").await;
+    let other = add_owned(&manager, "bob", "c.zip").await;
+
+    // Bob starts although Alice queued a task before him
+    assert_eq!(manager.get_task(first).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Waiting);
+    assert_eq!(manager.get_task(other).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.task_owner(second).await.as_deref(), Some("alice"));
+
+    // Alice's next task starts once her running one is done
+    manager.complete_task(first).await.unwrap();
+    assert_eq!(manager.get_task(second).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_raising_owner_quota_starts_queued_tasks() {
+    let manager = TaskQueueManager::new();
+    manager.set_owner_quotas(OwnerQuotas::new().default_quota(1)).await.unwrap();
+
+    add_owned(&manager, "alice", "a.zip").await;
+    let queued = add_owned(&manager, "alice", "b.zip").await;
+    assert_eq!(manager.get_task(queued).await.unwrap().status, DownloadStatus::Waiting);
+
+    manager.set_owner_quotas(OwnerQuotas::new().default_quota(2)).await.unwrap();
+    assert_eq!(manager.get_task(queued).await.unwrap().status, DownloadStatus::Downloading);
+    assert_eq!(manager.owner_quotas().await.default_quota, Some(2));
+}
+
+#[tokio::test]
+async fn test_list_tasks_for_owner() {
+    let manager = TaskQueueManager::new();
+    let alice = add_owned(&manager, "alice", "a.zip").await;
+    add_owned(&manager, "bob", "b.zip").await;
+    manager.add_task("https://example.com/c.zip".to_string(), PathBuf::from("/downloads/c.zip")).await.unwrap();
+
+    let tasks = manager.list_tasks_for_owner("alice").await.unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, alice);
+    assert!(manager.list_tasks_for_owner("carol").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_pause_all_for_owner_leaves_others_running() {
+    let manager = TaskQueueManager::new();
+    let alice = add_owned(&manager, "alice", "a.zip").await;
+    let bob = add_owned(&manager, "bob", "b.zip").await;
+
+    let paused = manager.pause_all_for_owner("alice").await.unwrap();
+    assert_eq!(paused, vec![alice]);
+    assert_eq!(manager.get_task(alice).await.unwrap().status, DownloadStatus::Paused);
+    assert_eq!(manager.get_task(bob).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_cancel_all_for_owner_frees_slots_for_others() {
+    let manager = TaskQueueManager::new();
+    for name in ["a.zip", "b.zip", "c.zip"] {
+        add_owned(&manager, "alice", name).await;
+    }
+    let bob = add_owned(&manager, "bob", "d.zip").await;
+    assert_eq!(manager.get_task(bob).await.unwrap().status, DownloadStatus::Waiting);
+
+    let cancelled = manager.cancel_all_for_owner("alice").await.unwrap();
+    assert_eq!(cancelled.len(), 3);
+    assert!(manager.list_tasks_for_owner("alice").await.unwrap().is_empty());
+    assert_eq!(manager.list_tasks().await.unwrap().len(), 1);
+    assert_eq!(manager.get_task(bob).await.unwrap().status, DownloadStatus::Downloading);
+}
+
+#[tokio::test]
+async fn test_metadata_store_indexes_owners() {
+    let store = TaskMetadataStore::in_memory().await.unwrap();
+    let alice = TaskId::new();
+    let bob = TaskId::new();
+
+    store.put_owner(&alice, "alice").await.unwrap();
+    store.put_owner(&bob, "bob").await.unwrap();
+    assert_eq!(store.owner_of(&alice).await.unwrap().as_deref(), Some("alice"));
+    assert_eq!(store.tasks_of_owner("alice").await.unwrap(), vec![alice]);
+
+    // Restored tasks keep their owner
+    let restored = TaskId::new();
+    store.rekey_task(&alice, &restored).await.unwrap();
+    assert_eq!(store.tasks_of_owner("alice").await.unwrap(), vec![restored]);
+
+    store.remove_task(&restored).await.unwrap();
+    assert!(store.tasks_of_owner("alice").await.unwrap().is_empty());
+    assert_eq!(store.owner_of(&bob).await.unwrap().as_deref(), Some("bob"));
+}
\ No newline at end of file
@@ -2,9 +2,12 @@
 //!
 //! These tests verify the TaskRepository trait methods.
 
-use burncloud_download::services::task_repository::{TaskRepository, DefaultTaskRepository};
-use burncloud_download::types::{TaskId};
-use std::path::Path;
+use burncloud_download::services::task_repository::{TaskRepository, DefaultTaskRepository, InMemoryTaskRepository};
+use burncloud_download::models::{FileIdentifier, ListOrder};
+use burncloud_download::types::{TaskId, DownloadTask, DownloadStatus, DownloadProgress};
+use burncloud_download::DownloadError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 #[tokio::test]
 async fn test_find_by_url_hash_and_path() {
@@ -45,6 +48,114 @@ async fn test_update_duplicate_fields() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_saved_task_is_listed_until_deleted() {
+    let repository = InMemoryTaskRepository::new();
+
+    let mut task = DownloadTask::new("https://example.com/file.zip".to_string(), PathBuf::from("./downloads/file.zip"));
+    repository.save_task(&task).await.unwrap();
+
+    // Saving again replaces the task
+    task.update_status(DownloadStatus::Paused);
+    repository.save_task(&task).await.unwrap();
+
+    assert_eq!(repository.get_task(&task.id).await.unwrap().status, DownloadStatus::Paused);
+    let listed = repository.list_tasks().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, task.id);
+
+    repository.delete_task(&task.id).await.unwrap();
+    assert!(matches!(repository.get_task(&task.id).await, Err(DownloadError::TaskNotFound(id)) if id == task.id));
+    assert!(repository.list_tasks().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_progress_is_saved_separately_from_task() {
+    let repository = InMemoryTaskRepository::new();
+    let task_id = TaskId::new();
+
+    assert!(repository.get_progress(&task_id).await.is_err());
+
+    let progress = DownloadProgress {
+        downloaded_bytes: 1024,
+        total_bytes: Some(10240),
+        speed_bps: 512,
+        eta_seconds: Some(18),
+    };
+    repository.save_progress(&task_id, &progress).await.unwrap();
+
+    let saved = repository.get_progress(&task_id).await.unwrap();
+    assert_eq!(saved.downloaded_bytes, 1024);
+    assert_eq!(saved.total_bytes, Some(10240));
+
+    // Deleting the task keeps its progress until that is deleted too
+    repository.delete_task(&task_id).await.unwrap();
+    assert!(repository.get_progress(&task_id).await.is_ok());
+    repository.delete_progress(&task_id).await.unwrap();
+    assert!(repository.get_progress(&task_id).await.is_err());
+}
+
+#[tokio::test]
+async fn test_find_by_url_hash_scans_saved_tasks() {
+    let repository = InMemoryTaskRepository::new();
+
+    let first = DownloadTask::new("https://example.com/file.zip?b=2&a=1".to_string(), PathBuf::from("./downloads/a/file.zip"));
+    let second = DownloadTask::new("https://example.com/file.zip?a=1&b=2".to_string(), PathBuf::from("./downloads/b/file.zip"));
+    let other = DownloadTask::new("https://example.com/other.zip".to_string(), PathBuf::from("./downloads/a/file.zip"));
+    for task in [&first, &second, &other] {
+        repository.save_task(task).await.unwrap();
+    }
+
+    let url_hash = FileIdentifier::new("https://example.com/file.zip?a=1&b=2", Path::new(""), None).url_hash;
+
+    let mut found = repository.find_by_url_hash(&url_hash).await.unwrap();
+    found.sort_by_key(|task_id| task_id.to_string());
+    let mut expected = vec![first.id, second.id];
+    expected.sort_by_key(|task_id| task_id.to_string());
+    assert_eq!(found, expected);
+
+    // Paths are compared in their normalized form
+    let found = repository.find_by_url_hash_and_path(&url_hash, Path::new("./downloads/b/../a/file.zip")).await.unwrap();
+    assert_eq!(found, vec![first.id]);
+}
+
+#[tokio::test]
+async fn test_save_tasks_saves_whole_batch() {
+    let repository = InMemoryTaskRepository::new();
+
+    let mut first = DownloadTask::new("https://example.com/a.zip".to_string(), PathBuf::from("./downloads/a.zip"));
+    let second = DownloadTask::new("https://example.com/b.zip".to_string(), PathBuf::from("./downloads/b.zip"));
+    repository.save_task(&first).await.unwrap();
+
+    first.update_status(DownloadStatus::Completed);
+    repository.save_tasks(&[first.clone(), second.clone()]).await.unwrap();
+
+    assert_eq!(repository.get_task(&first.id).await.unwrap().status, DownloadStatus::Completed);
+    assert_eq!(repository.get_task(&second.id).await.unwrap().url, second.url);
+    assert_eq!(repository.list_tasks().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_list_tasks_page_skips_excluded_tasks() {
+    let repository = InMemoryTaskRepository::new();
+
+    let mut saved = Vec::new();
+    for i in 0..4 {
+        let task = DownloadTask::new(format!("https://example.com/file{}.zip", i), PathBuf::from(format!("./downloads/file{}.zip", i)));
+        repository.save_task(&task).await.unwrap();
+        saved.push(task.id);
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    let ordered = repository.list_tasks_ordered(ListOrder::CreatedDesc).await.unwrap();
+    assert_eq!(ordered.iter().map(|task| task.id).collect::<Vec<_>>(), saved.iter().rev().copied().collect::<Vec<_>>());
+
+    let excluded = HashSet::from([saved[1]]);
+    let page = repository.list_tasks_page(1, 2, ListOrder::CreatedAsc, &excluded).await.unwrap();
+    assert_eq!(page.iter().map(|task| task.id).collect::<Vec<_>>(), vec![saved[2], saved[3]]);
+    assert!(repository.list_tasks_page(3, 2, ListOrder::CreatedAsc, &excluded).await.unwrap().is_empty());
+}
+
 // Helper functions for testing
 
 async fn create_test_repository() -> impl TaskRepository {
@@ -0,0 +1,88 @@
+//! Unit tests for time-of-day bandwidth rules
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use burncloud_download::{TaskQueueManager, ThrottleSchedule, Throttler};
+
+const MB: u64 = 1024 * 1024;
+
+/// A time on 1970-01-02 (UTC)
+fn at(hour: u64, minute: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(86_400 + hour * 3600 + minute * 60)
+}
+
+fn business_hours() -> ThrottleSchedule {
+    ThrottleSchedule::new()
+        .rule((9, 0), (17, 30), MB)
+        .rule((22, 0), (6, 0), 0)
+        .default_limit(4 * MB)
+}
+
+#[test]
+fn test_limit_follows_time_of_day() {
+    let schedule = business_hours();
+
+    assert_eq!(schedule.limit_at(at(9, 0)), MB);
+    assert_eq!(schedule.limit_at(at(17, 29)), MB);
+    assert_eq!(schedule.limit_at(at(17, 30)), 4 * MB);
+    assert_eq!(schedule.limit_at(at(8, 59)), 4 * MB);
+}
+
+#[test]
+fn test_rules_wrap_past_midnight() {
+    let schedule = business_hours();
+
+    assert_eq!(schedule.limit_at(at(23, 0)), 0);
+    assert_eq!(schedule.limit_at(at(0, 0)), 0);
+    assert_eq!(schedule.limit_at(at(5, 59)), 0);
+    assert_eq!(schedule.limit_at(at(6, 0)), 4 * MB);
+}
+
+#[test]
+fn test_first_matching_rule_wins() {
+    let schedule = ThrottleSchedule::new()
+        .rule((12, 0), (13, 0), MB)
+        .rule((0, 0), (0, 0), 2 * MB);
+
+    assert_eq!(schedule.limit_at(at(12, 30)), MB);
+    assert_eq!(schedule.limit_at(at(3, 0)), 2 * MB);
+}
+
+#[test]
+fn test_invalid_times_are_rejected() {
+    assert!(ThrottleSchedule::new().rule((24, 0), (1, 0), MB).validate().is_err());
+    assert!(ThrottleSchedule::new().rule((1, 0), (2, 60), MB).validate().is_err());
+    assert!(business_hours().validate().is_ok());
+
+    let manager = Arc::new(TaskQueueManager::new());
+    assert!(Throttler::new(manager, ThrottleSchedule::new().rule((25, 0), (1, 0), MB)).is_err());
+}
+
+#[tokio::test]
+async fn test_throttler_sets_global_limit() {
+    let manager = Arc::new(TaskQueueManager::new());
+    let all_day = ThrottleSchedule::new().rule((0, 0), (0, 0), 2 * MB);
+    let throttler = Throttler::new(manager.clone(), all_day).unwrap();
+
+    assert_eq!(throttler.apply().await.unwrap(), 2 * MB);
+    assert_eq!(manager.bandwidth_limiter().global_limit().await, 2 * MB);
+
+    // A new schedule takes effect right away
+    let limit = throttler.set_schedule(ThrottleSchedule::new().default_limit(MB)).await.unwrap();
+    assert_eq!(limit, MB);
+    assert_eq!(manager.bandwidth_limiter().global_limit().await, MB);
+}
+
+#[tokio::test]
+async fn test_background_task_applies_schedule() {
+    let manager = Arc::new(TaskQueueManager::new());
+    let throttler = Throttler::new(manager.clone(), ThrottleSchedule::new().default_limit(3 * MB))
+        .unwrap()
+        .with_poll_interval(Duration::from_millis(10));
+
+    throttler.start().await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    throttler.shutdown().await;
+
+    assert_eq!(manager.bandwidth_limiter().global_limit().await, 3 * MB);
+}
\ No newline at end of file
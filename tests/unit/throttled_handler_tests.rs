@@ -0,0 +1,116 @@
+//! Unit tests for progress coalescing of event handlers
+
+use std::sync::Arc;
+use std::time::Duration;
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use burncloud_download::{ProgressDelivery, TaskId, DownloadStatus, DownloadProgress};
+use burncloud_download::services::ThrottledHandler;
+use burncloud_download::traits::DownloadEventHandler;
+
+/// Handler recording the progress and status changes it receives
+#[derive(Default)]
+struct Recorder {
+    progress: Mutex<Vec<u64>>,
+    statuses: Mutex<Vec<DownloadStatus>>,
+}
+
+#[async_trait]
+impl DownloadEventHandler for Recorder {
+    async fn on_status_changed(&self, _task_id: TaskId, _old_status: DownloadStatus, new_status: DownloadStatus) {
+        self.statuses.lock().await.push(new_status);
+    }
+
+    async fn on_progress_updated(&self, _task_id: TaskId, progress: DownloadProgress) {
+        self.progress.lock().await.push(progress.downloaded_bytes);
+    }
+
+    async fn on_download_completed(&self, _task_id: TaskId) {}
+
+    async fn on_download_failed(&self, _task_id: TaskId, _error: String) {}
+}
+
+fn progress(downloaded_bytes: u64) -> DownloadProgress {
+    DownloadProgress {
+        downloaded_bytes,
+        total_bytes: Some(10_000),
+        speed_bps: 0,
+        eta_seconds: None,
+    }
+}
+
+#[tokio::test]
+async fn test_unlimited_delivery_passes_everything() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new());
+    let task_id = TaskId::new();
+
+    for bytes in [1, 2, 3] {
+        handler.on_progress_updated(task_id, progress(bytes)).await;
+    }
+    assert_eq!(*recorder.progress.lock().await, vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_min_delta_bytes_coalesces_updates() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new().min_delta_bytes(1000));
+    let task_id = TaskId::new();
+
+    for bytes in [0, 400, 900, 1000, 1500, 2100] {
+        handler.on_progress_updated(task_id, progress(bytes)).await;
+    }
+    assert_eq!(*recorder.progress.lock().await, vec![0, 1000, 2100]);
+}
+
+#[tokio::test]
+async fn test_min_interval_limits_rate() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new().min_interval(Duration::from_millis(50)));
+    let task_id = TaskId::new();
+
+    handler.on_progress_updated(task_id, progress(100)).await;
+    handler.on_progress_updated(task_id, progress(200)).await;
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    handler.on_progress_updated(task_id, progress(300)).await;
+
+    assert_eq!(*recorder.progress.lock().await, vec![100, 300]);
+}
+
+#[tokio::test]
+async fn test_held_back_update_is_flushed_before_status_change() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new().min_interval(Duration::from_secs(60)));
+    let task_id = TaskId::new();
+
+    handler.on_progress_updated(task_id, progress(100)).await;
+    handler.on_progress_updated(task_id, progress(700)).await;
+    handler.on_status_changed(task_id, DownloadStatus::Downloading, DownloadStatus::Paused).await;
+
+    assert_eq!(*recorder.progress.lock().await, vec![100, 700]);
+    assert_eq!(*recorder.statuses.lock().await, vec![DownloadStatus::Paused]);
+}
+
+#[tokio::test]
+async fn test_final_update_is_always_delivered() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new().min_interval(Duration::from_secs(60)));
+    let task_id = TaskId::new();
+
+    handler.on_progress_updated(task_id, progress(100)).await;
+    handler.on_progress_updated(task_id, progress(10_000)).await;
+
+    assert_eq!(*recorder.progress.lock().await, vec![100, 10_000]);
+}
+
+#[tokio::test]
+async fn test_tasks_are_throttled_separately() {
+    let recorder = Arc::new(Recorder::default());
+    let handler = ThrottledHandler::new(recorder.clone(), ProgressDelivery::new().min_delta_bytes(1000));
+
+    handler.on_progress_updated(TaskId::new(), progress(10)).await;
+    handler.on_progress_updated(TaskId::new(), progress(20)).await;
+
+    assert_eq!(*recorder.progress.lock().await, vec![10, 20]);
+}
\ No newline at end of file
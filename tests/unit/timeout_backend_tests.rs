@@ -0,0 +1,180 @@
+//! Unit tests for backend call timeouts and circuit breaking
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use async_trait::async_trait;
+
+use burncloud_download::{DownloadError, Result, RpcTimeouts, TimeoutBackend};
+use burncloud_download::traits::DownloadBackend;
+use burncloud_download::models::DownloadOptions;
+use burncloud_download::types::{TaskId, DownloadTask, DownloadProgress};
+
+/// Backend answering every call straight away, or never while hanging
+#[derive(Default)]
+struct WedgedBackend {
+    hanging: AtomicBool,
+    calls: AtomicUsize,
+}
+
+impl WedgedBackend {
+    async fn respond(&self) {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.hanging.load(Ordering::SeqCst) {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[async_trait]
+impl DownloadBackend for WedgedBackend {
+    async fn add(&self, _url: String, _target_path: PathBuf) -> Result<TaskId> {
+        self.respond().await;
+        Ok(TaskId::new())
+    }
+
+    async fn add_with_options(&self, url: String, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        self.add(url, target_path).await
+    }
+
+    async fn add_multi_source(&self, urls: Vec<String>, target_path: PathBuf, _options: &DownloadOptions) -> Result<TaskId> {
+        self.add(urls[0].clone(), target_path).await
+    }
+
+    async fn pause(&self, _task_id: TaskId) -> Result<()> {
+        self.respond().await;
+        Ok(())
+    }
+
+    async fn resume(&self, _task_id: TaskId) -> Result<()> {
+        self.respond().await;
+        Ok(())
+    }
+
+    async fn cancel(&self, _task_id: TaskId) -> Result<()> {
+        self.respond().await;
+        Ok(())
+    }
+
+    async fn progress(&self, _task_id: TaskId) -> Result<DownloadProgress> {
+        self.respond().await;
+        Ok(DownloadProgress::new())
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<DownloadTask> {
+        self.respond().await;
+        Err(DownloadError::TaskNotFound(task_id))
+    }
+
+    async fn list(&self) -> Result<Vec<DownloadTask>> {
+        self.respond().await;
+        Ok(Vec::new())
+    }
+
+    async fn active_count(&self) -> Result<usize> {
+        self.respond().await;
+        Ok(0)
+    }
+
+    async fn set_global_speed_limit(&self, _bytes_per_sec: u64) -> Result<()> {
+        self.respond().await;
+        Ok(())
+    }
+
+    async fn set_task_speed_limit(&self, _task_id: TaskId, _bytes_per_sec: u64) -> Result<()> {
+        self.respond().await;
+        Ok(())
+    }
+
+    async fn engine_id(&self, _task_id: TaskId) -> Result<Option<String>> {
+        self.respond().await;
+        Ok(None)
+    }
+
+    async fn reattach(&self, _task: &DownloadTask, _engine_id: &str) -> Result<bool> {
+        self.respond().await;
+        Ok(false)
+    }
+}
+
+fn timeouts() -> RpcTimeouts {
+    RpcTimeouts::new()
+        .request(Duration::from_millis(20))
+        .circuit_breaker(2, Duration::from_millis(100))
+}
+
+#[tokio::test]
+async fn test_fast_calls_pass_through() {
+    let inner = Arc::new(WedgedBackend::default());
+    let backend = TimeoutBackend::new(inner.clone(), timeouts());
+
+    assert_eq!(backend.active_count().await.unwrap(), 0);
+    assert!(backend.list().await.unwrap().is_empty());
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_slow_call_fails_with_backend_timeout() {
+    let inner = Arc::new(WedgedBackend::default());
+    inner.hanging.store(true, Ordering::SeqCst);
+    let backend = TimeoutBackend::new(inner, timeouts());
+
+    match backend.pause(TaskId::new()).await {
+        Err(DownloadError::BackendTimeout { operation, timeout }) => {
+            assert_eq!(operation, "pause");
+            assert_eq!(timeout, Duration::from_millis(20));
+        }
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert!(!backend.is_open());
+}
+
+#[tokio::test]
+async fn test_consecutive_timeouts_open_the_circuit() {
+    let inner = Arc::new(WedgedBackend::default());
+    inner.hanging.store(true, Ordering::SeqCst);
+    let backend = TimeoutBackend::new(inner.clone(), timeouts());
+
+    for _ in 0..2 {
+        assert!(matches!(backend.list().await, Err(DownloadError::BackendTimeout { .. })));
+    }
+    assert!(backend.is_open());
+
+    // Open circuit fails calls without reaching the backend
+    assert!(matches!(backend.list().await, Err(DownloadError::DownloaderUnavailable(_))));
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+    // After the cooldown a successful call closes the circuit
+    inner.hanging.store(false, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert!(backend.list().await.is_ok());
+    assert!(!backend.is_open());
+}
+
+#[tokio::test]
+async fn test_success_resets_the_timeout_count() {
+    let inner = Arc::new(WedgedBackend::default());
+    let backend = TimeoutBackend::new(inner.clone(), timeouts());
+
+    inner.hanging.store(true, Ordering::SeqCst);
+    assert!(backend.list().await.is_err());
+    inner.hanging.store(false, Ordering::SeqCst);
+    assert!(backend.list().await.is_ok());
+    inner.hanging.store(true, Ordering::SeqCst);
+    assert!(backend.list().await.is_err());
+
+    assert!(!backend.is_open());
+}
+
+#[tokio::test]
+async fn test_zero_threshold_never_opens_the_circuit() {
+    let inner = Arc::new(WedgedBackend::default());
+    inner.hanging.store(true, Ordering::SeqCst);
+    let backend = TimeoutBackend::new(inner, timeouts().circuit_breaker(0, Duration::from_secs(60)));
+
+    for _ in 0..3 {
+        assert!(matches!(backend.list().await, Err(DownloadError::BackendTimeout { .. })));
+    }
+    assert!(!backend.is_open());
+}
\ No newline at end of file
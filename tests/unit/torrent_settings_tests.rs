@@ -0,0 +1,73 @@
+//! Unit tests for BitTorrent and DHT settings
+
+use std::time::Duration;
+use burncloud_download::{DownloadError, PortRange, TorrentSettings};
+use serde_json::json;
+
+#[test]
+fn test_port_range_parsing() {
+    assert_eq!("6881".parse::<PortRange>().unwrap(), PortRange::single(6881));
+    assert_eq!("6881-6999".parse::<PortRange>().unwrap(), PortRange::new(6881, 6999));
+    assert_eq!(PortRange::new(6881, 6999).to_string(), "6881-6999");
+    assert_eq!(PortRange::single(6881).to_string(), "6881");
+
+    for invalid in ["", "0", "6999-6881", "port", "6881-", "70000"] {
+        assert!(matches!(invalid.parse::<PortRange>(), Err(DownloadError::InvalidOption(_))), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_settings_validation() {
+    assert!(TorrentSettings::new().validate().is_ok());
+    assert!(TorrentSettings::new().seed_ratio(1.5).validate().is_ok());
+
+    assert!(TorrentSettings::new().seed_ratio(-1.0).validate().is_err());
+    assert!(TorrentSettings::new().seed_ratio(f64::NAN).validate().is_err());
+    assert!(TorrentSettings::new().listen_ports(PortRange::new(7000, 6000)).validate().is_err());
+    assert!(TorrentSettings::new().dht_listen_ports(PortRange::single(0)).validate().is_err());
+}
+
+#[test]
+fn test_startup_args() {
+    assert!(TorrentSettings::new().startup_args().is_empty());
+    assert!(!TorrentSettings::new().max_peers(10).has_startup_options());
+
+    let settings = TorrentSettings::new()
+        .listen_ports(PortRange::new(6881, 6889))
+        .dht(true)
+        .dht_listen_ports(PortRange::single(6881));
+    assert!(settings.has_startup_options());
+    assert_eq!(settings.startup_args(), vec![
+        "--listen-port=6881-6889".to_string(),
+        "--enable-dht=true".to_string(),
+        "--dht-listen-port=6881".to_string(),
+    ]);
+
+    // Turning the DHT off covers IPv6 as well
+    assert_eq!(TorrentSettings::new().dht(false).startup_args(), vec![
+        "--enable-dht=false".to_string(),
+        "--enable-dht6=false".to_string(),
+    ]);
+}
+
+#[test]
+fn test_aria2_options() {
+    assert!(TorrentSettings::new().to_aria2_options().is_empty());
+    assert!(TorrentSettings::new().is_empty());
+
+    let settings = TorrentSettings::new()
+        .require_encryption(true)
+        .max_peers(0)
+        .seed_ratio(2.0)
+        .seed_time(Duration::from_secs(90));
+    let options = settings.to_aria2_options();
+    assert_eq!(options.get("bt-require-crypto"), Some(&json!("true")));
+    assert_eq!(options.get("bt-min-crypto-level"), Some(&json!("arc4")));
+    assert_eq!(options.get("bt-max-peers"), Some(&json!("0")));
+    assert_eq!(options.get("seed-ratio"), Some(&json!("2")));
+    assert_eq!(options.get("seed-time"), Some(&json!("1.5")));
+    assert!(!settings.is_empty());
+
+    let options = TorrentSettings::new().require_encryption(false).to_aria2_options();
+    assert_eq!(options.get("bt-min-crypto-level"), Some(&json!("plain")));
+}
\ No newline at end of file
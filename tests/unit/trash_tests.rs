@@ -0,0 +1,253 @@
+//! Unit tests for deleting tasks into the trash and restoring them
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use burncloud_download::{DownloadError, PersistentAria2Manager};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{DeleteMode, DuplicateDecision, DuplicatePolicy, ListOrder, RetryPolicy, TrashPolicy};
+use burncloud_download::types::DownloadStatus;
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_trash_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf, policy: TrashPolicy) -> PersistentAria2Manager {
+    let manager = PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .trash_policy(policy)
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap();
+    manager.set_retry_policy(RetryPolicy::none()).await;
+    manager
+}
+
+#[test]
+fn test_trash_policy_expiry() {
+    let policy = TrashPolicy::new().ttl(Duration::from_secs(60));
+    assert_eq!(TrashPolicy::default().dir, None);
+
+    let deleted_at = SystemTime::now();
+    assert!(!policy.is_expired(deleted_at, deleted_at + Duration::from_secs(59)));
+    assert!(policy.is_expired(deleted_at, deleted_at + Duration::from_secs(60)));
+    // Clocks going back never purge anything
+    assert!(!policy.is_expired(deleted_at, deleted_at - Duration::from_secs(1)));
+}
+
+#[tokio::test]
+async fn test_trash_keeps_task_until_restored() {
+    let dir = test_dir("keep");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    assert!(backend.task(task_id).await.is_err());
+
+    let trash = manager.list_trash().await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].task_id, task_id);
+    assert_eq!(trash[0].original_path, dir.join("model.bin"));
+    assert_eq!(trash[0].trash_path, None);
+    // Trashed tasks are only listed by the trash
+    assert!(matches!(manager.get_task(task_id).await, Err(DownloadError::TaskNotFound(_))));
+    assert!(manager.list_tasks().await.unwrap().is_empty());
+
+    // Deleting into the trash again changes nothing
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    assert_eq!(manager.list_trash().await.unwrap(), trash);
+
+    let restored = manager.restore_deleted(task_id).await.unwrap();
+    assert!(backend.task(restored).await.is_ok());
+    assert!(manager.list_trash().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_trash_dir_holds_partial_files() {
+    let dir = test_dir("dir");
+    std::fs::create_dir_all(&dir).unwrap();
+    let trash_dir = dir.join("trash");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::new().dir(&trash_dir)).await;
+
+    // Downloads are written to a temporary file next to the target by default
+    let partial = dir.join("model.bin.part");
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(&partial, b"partial").unwrap();
+    std::fs::write(dir.join("model.bin.part.aria2"), b"control").unwrap();
+
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    let task_dir = trash_dir.join(task_id.to_string());
+    assert_eq!(manager.list_trash().await.unwrap()[0].trash_path, Some(task_dir.join("model.bin")));
+    assert!(!partial.exists());
+    assert_eq!(std::fs::read(task_dir.join("model.bin.part")).unwrap(), b"partial");
+    assert!(task_dir.join("model.bin.part.aria2").exists());
+
+    manager.delete_task(task_id, DeleteMode::Purge).await.unwrap();
+    assert!(!task_dir.exists());
+    assert!(manager.list_trash().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_completed_file_is_restored() {
+    let dir = test_dir("completed");
+    std::fs::create_dir_all(&dir).unwrap();
+    let trash_dir = dir.join("trash");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::new().dir(&trash_dir)).await;
+
+    let target = dir.join("model.bin");
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+    for _ in 0..100 {
+        if manager.get_task(task_id).await.unwrap().status == DownloadStatus::Completed {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    std::fs::write(&target, b"weights").unwrap();
+
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    assert!(!target.exists());
+    assert!(trash_dir.join(task_id.to_string()).join("model.bin").exists());
+
+    // Finished tasks keep their ID
+    assert_eq!(manager.restore_deleted(task_id).await.unwrap(), task_id);
+    assert_eq!(std::fs::read(&target).unwrap(), b"weights");
+    assert_eq!(manager.get_task(task_id).await.unwrap().status, DownloadStatus::Completed);
+}
+
+#[tokio::test]
+async fn test_restore_refuses_to_overwrite() {
+    let dir = test_dir("overwrite");
+    std::fs::create_dir_all(&dir).unwrap();
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::new().dir(dir.join("trash"))).await;
+
+    let partial = dir.join("model.bin.part");
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(&partial, b"partial").unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+
+    std::fs::write(&partial, b"another download").unwrap();
+    let result = manager.restore_deleted(task_id).await;
+    assert!(matches!(result, Err(DownloadError::FileExists(path)) if path == partial));
+    assert_eq!(manager.list_trash().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_expired_trash_is_purged() {
+    let dir = test_dir("purge");
+    std::fs::create_dir_all(&dir).unwrap();
+    let trash_dir = dir.join("trash");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::new().dir(&trash_dir)).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(dir.join("model.bin.part"), b"partial").unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+
+    // Not yet due
+    assert!(manager.purge_trash().await.unwrap().is_empty());
+
+    manager.set_trash_policy(TrashPolicy::new().dir(&trash_dir).ttl(Duration::ZERO)).await;
+    let report = manager.run_gc().await.unwrap();
+    assert_eq!(report.purged, vec![task_id]);
+    assert!(manager.list_trash().await.unwrap().is_empty());
+    assert!(!trash_dir.join(task_id.to_string()).exists());
+    assert!(matches!(manager.get_task(task_id).await, Err(DownloadError::TaskNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_purge_deletes_task_and_partial_files() {
+    let dir = test_dir("purge_mode");
+    std::fs::create_dir_all(&dir).unwrap();
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+
+    let partial = dir.join("model.bin.part");
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(&partial, b"partial").unwrap();
+
+    manager.delete_task(task_id, DeleteMode::Purge).await.unwrap();
+    assert!(!partial.exists());
+    assert!(matches!(manager.get_task(task_id).await, Err(DownloadError::TaskNotFound(_))));
+    assert!(matches!(manager.restore_deleted(task_id).await, Err(DownloadError::TaskNotFound(_))));
+}
+
+#[tokio::test]
+async fn test_trashed_task_is_not_reused() {
+    let dir = test_dir("reuse");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+    let target = dir.join("model.bin");
+
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    assert_eq!(manager.find_duplicate_task(URL, &target).await.unwrap(), None);
+
+    // The same download starts over instead of returning the stopped task
+    let (new_task, decision) = manager.add_download_with_policy(URL, &target, DuplicatePolicy::ReuseExisting).await.unwrap();
+    assert_ne!(new_task, task_id);
+    assert!(matches!(decision, DuplicateDecision::CreateNew));
+    assert_eq!(manager.list_tasks().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_restored_task_is_found_as_duplicate() {
+    let dir = test_dir("reindex");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+    let target = dir.join("model.bin");
+
+    let task_id = manager.add_download(URL.to_string(), target.clone()).await.unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+    let restored = manager.restore_deleted(task_id).await.unwrap();
+
+    assert_eq!(manager.find_duplicate_task(URL, &target).await.unwrap(), Some(restored));
+}
+
+#[tokio::test]
+async fn test_trashed_task_is_left_out_of_pages() {
+    let dir = test_dir("pages");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+
+    let mut added = Vec::new();
+    for i in 0..3 {
+        added.push(manager.add_download(format!("{}?part={}", URL, i), dir.join(format!("model{}.bin", i))).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    manager.delete_task(added[0], DeleteMode::Trash).await.unwrap();
+
+    let page = manager.list_tasks_page(0, 2, ListOrder::CreatedAsc).await.unwrap();
+    assert_eq!(page.iter().map(|task| task.id).collect::<Vec<_>>(), added[1..]);
+    let ordered = manager.list_tasks_ordered(ListOrder::CreatedDesc).await.unwrap();
+    assert_eq!(ordered.iter().map(|task| task.id).collect::<Vec<_>>(), vec![added[2], added[1]]);
+}
+
+#[tokio::test]
+async fn test_trashed_task_is_not_listed_as_duplicate() {
+    let dir = test_dir("duplicates");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir, TrashPolicy::default()).await;
+
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    manager.delete_task(task_id, DeleteMode::Trash).await.unwrap();
+
+    let duplicates = manager.get_duplicate_tasks(URL, &dir.join("model.bin"), ListOrder::CreatedAsc).await.unwrap();
+    assert!(duplicates.is_empty());
+}
\ No newline at end of file
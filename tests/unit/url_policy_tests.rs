@@ -0,0 +1,79 @@
+//! Unit tests for URL validation
+
+use burncloud_download::{BasicDownloadManager, DownloadError, DownloadManager, TaskQueueManager, UrlPolicy};
+use std::path::PathBuf;
+
+fn reason(result: Result<(), DownloadError>) -> String {
+    match result {
+        Err(DownloadError::InvalidUrl(reason)) => reason,
+        other => panic!("Expected an invalid URL error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_default_schemes_are_accepted() {
+    let policy = UrlPolicy::default();
+
+    assert!(policy.validate("https://example.com/file.zip").is_ok());
+    assert!(policy.validate("http://example.com:8080/file.zip?x=1").is_ok());
+    assert!(policy.validate("ftp://ftp.example.com/pub/file.iso").is_ok());
+    assert!(policy.validate("sftp://files.example.com/data.bin").is_ok());
+    assert!(policy.validate("magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a").is_ok());
+    assert!(policy.validate("http://192.168.1.10/file.zip").is_ok());
+    assert!(policy.validate("http://[::1]:8080/file.zip").is_ok());
+}
+
+#[test]
+fn test_rejection_reasons() {
+    let policy = UrlPolicy::default();
+
+    assert_eq!(reason(policy.validate("")), "URL is empty");
+    assert!(reason(policy.validate("   ")).contains("empty"));
+    assert!(reason(policy.validate(" https://example.com/a ")).contains("whitespace"));
+    assert!(reason(policy.validate("not a url")).contains("is not a valid URL"));
+    assert!(reason(policy.validate("file:///etc/passwd")).contains("scheme 'file'"));
+    assert!(reason(policy.validate("magnet:?dn=name")).contains("exact topic"));
+    assert!(reason(policy.validate("http://localhost/file.zip")).contains("plain host 'localhost'"));
+}
+
+#[test]
+fn test_plain_hosts_can_be_allowed() {
+    let policy = UrlPolicy::new().allow_plain_hosts(true);
+    assert!(policy.validate("http://localhost:8000/file.zip").is_ok());
+    assert!(policy.validate("http://mirror/file.zip").is_ok());
+}
+
+#[test]
+fn test_custom_schemes() {
+    let policy = UrlPolicy::new().allowed_schemes(["HTTPS"]);
+    assert!(policy.validate("https://example.com/file.zip").is_ok());
+    assert!(reason(policy.validate("http://example.com/file.zip")).contains("allowed schemes are https"));
+}
+
+#[tokio::test]
+async fn test_managers_reject_invalid_urls() {
+    let basic = BasicDownloadManager::new();
+    let queue = TaskQueueManager::new();
+
+    for manager in [&basic as &dyn DownloadManager, &queue as &dyn DownloadManager] {
+        let result = manager.add_download("garbage".to_string(), PathBuf::from("/downloads/file.zip")).await;
+        assert!(matches!(result, Err(DownloadError::InvalidUrl(_))));
+
+        let result = manager.add_download_multi_source(
+            vec!["https://example.com/file.zip".to_string(), "".to_string()],
+            PathBuf::from("/downloads/file.zip"),
+        ).await;
+        assert!(matches!(result, Err(DownloadError::InvalidUrl(_))));
+
+        assert!(manager.list_tasks().await.unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_manager_url_policy_can_be_relaxed() {
+    let queue = TaskQueueManager::new();
+    assert!(queue.add_download("http://localhost:8000/file.zip".to_string(), PathBuf::from("/downloads/file.zip")).await.is_err());
+
+    queue.set_url_policy(UrlPolicy::new().allow_plain_hosts(true)).await;
+    assert!(queue.add_download("http://localhost:8000/file.zip".to_string(), PathBuf::from("/downloads/file.zip")).await.is_ok());
+}
\ No newline at end of file
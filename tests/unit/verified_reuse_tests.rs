@@ -0,0 +1,160 @@
+//! Unit tests for reusing completed files only while they verify
+//!
+//! The manager runs on an in-memory backend, so no aria2 daemon is needed.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use burncloud_download::{PersistentAria2Manager, TaskStatus};
+use burncloud_download::traits::{DownloadBackend, DownloadManager};
+use burncloud_download::models::{DuplicatePolicy, DuplicatePreview};
+use burncloud_download::types::{TaskId, DownloadStatus};
+use super::support::MemoryBackend;
+
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("burncloud_verified_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Nothing listens on the discard port, so probes fail right away
+const URL: &str = "http://127.0.0.1:9/model.bin";
+
+async fn manager(backend: Arc<MemoryBackend>, dir: &PathBuf) -> PersistentAria2Manager {
+    PersistentAria2Manager::builder()
+        .backend(backend)
+        .download_dir(dir)
+        .poll_interval(Duration::from_millis(20))
+        .ephemeral(true)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Download `content` to `dir/model.bin` and wait until its hash is recorded
+async fn complete(manager: &PersistentAria2Manager, backend: &MemoryBackend, dir: &PathBuf, content: &[u8]) -> TaskId {
+    let task_id = manager.add_download(URL.to_string(), dir.join("model.bin")).await.unwrap();
+    std::fs::write(dir.join("model.bin.part"), content).unwrap();
+    backend.set_status(task_id, DownloadStatus::Completed).await.unwrap();
+
+    for _ in 0..100 {
+        if manager.file_hash(task_id).await.is_some() {
+            return task_id;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("File of task {} was not hashed", task_id);
+}
+
+#[test]
+fn test_reuse_verified_policy() {
+    let policy = DuplicatePolicy::ReuseVerified;
+
+    assert!(policy.allows_reuse(&TaskStatus::Completed));
+    assert!(policy.allows_reuse(&TaskStatus::Paused));
+    assert!(policy.verifies_files());
+    assert!(!DuplicatePolicy::ReuseExisting.verifies_files());
+    assert!(!policy.should_fail_on_duplicate());
+}
+
+#[tokio::test]
+async fn test_intact_file_is_reused() {
+    let dir = test_dir("intact");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+    assert!(manager.verify_task_validity(&task_id).await.unwrap());
+
+    let preview = manager.evaluate_duplicate(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert!(matches!(preview, DuplicatePreview::Reuse { task_id: id, .. } if id == task_id));
+
+    let (reused, _) = manager.add_download_with_policy(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert_eq!(reused, task_id);
+    assert_eq!(backend.list().await.unwrap().len(), 1);
+    assert_eq!(std::fs::read(dir.join("model.bin")).unwrap(), b"weights");
+}
+
+#[tokio::test]
+async fn test_corrupt_file_is_downloaded_again() {
+    let dir = test_dir("corrupt");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+
+    std::fs::write(dir.join("model.bin"), b"garbage").unwrap();
+    assert!(!manager.verify_task_validity(&task_id).await.unwrap());
+    let preview = manager.evaluate_duplicate(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert!(matches!(preview, DuplicatePreview::CreateNew));
+    // Previewing changes nothing
+    assert!(manager.get_task(task_id).await.is_ok());
+
+    let (new_task, _) = manager.add_download_with_policy(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert_ne!(new_task, task_id);
+    assert!(manager.get_task(task_id).await.is_err());
+
+    // The new download takes the path of the corrupt file instead of a renamed one
+    assert_eq!(manager.get_task(new_task).await.unwrap().target_path, dir.join("model.bin"));
+    assert!(!dir.join("model.bin").exists());
+}
+
+#[tokio::test]
+async fn test_corrupt_file_is_removed_for_path_spelled_differently() {
+    let dir = test_dir("spelling");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+
+    std::fs::write(dir.join("model.bin"), b"garbage").unwrap();
+    let same_path = dir.join("sub").join("..").join("model.bin");
+    let (new_task, _) = manager.add_download_with_policy(URL, &same_path, DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert_ne!(new_task, task_id);
+    assert!(!dir.join("model.bin").exists());
+}
+
+#[tokio::test]
+async fn test_corrupt_task_matched_by_content_is_kept() {
+    let dir = test_dir("content");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+
+    // A good copy elsewhere matches the task by content, while its own file is corrupt
+    std::fs::write(dir.join("copy.bin"), b"weights").unwrap();
+    std::fs::write(dir.join("model.bin"), b"garbage").unwrap();
+    let (new_task, _) = manager.add_download_with_policy(URL, &dir.join("copy.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert_ne!(new_task, task_id);
+
+    // The task of the other path is not the one requested
+    assert!(manager.get_task(task_id).await.is_ok());
+    assert!(backend.has(task_id).await);
+    assert_eq!(std::fs::read(dir.join("model.bin")).unwrap(), b"garbage");
+}
+
+#[tokio::test]
+async fn test_missing_file_is_downloaded_again() {
+    let dir = test_dir("missing");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+
+    std::fs::remove_file(dir.join("model.bin")).unwrap();
+    assert!(!manager.verify_task_validity(&task_id).await.unwrap());
+
+    let (new_task, _) = manager.add_download_with_policy(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseVerified).await.unwrap();
+    assert_ne!(new_task, task_id);
+    assert_eq!(manager.task_status(new_task).await.unwrap(), TaskStatus::Waiting);
+}
+
+#[tokio::test]
+async fn test_other_policies_skip_verification() {
+    let dir = test_dir("unverified");
+    let backend = Arc::new(MemoryBackend::default());
+    let manager = manager(backend.clone(), &dir).await;
+    let task_id = complete(&manager, &backend, &dir, b"weights").await;
+
+    std::fs::write(dir.join("model.bin"), b"garbage").unwrap();
+    let (reused, _) = manager.add_download_with_policy(URL, &dir.join("model.bin"), DuplicatePolicy::ReuseIfComplete).await.unwrap();
+    assert_eq!(reused, task_id);
+}
\ No newline at end of file